@@ -0,0 +1,299 @@
+//! Hardware-in-the-loop acceptance test
+//!
+//! Runs a fixed sequence of checks against a real VL6180X attached to a Linux
+//! I2C bus and prints a pass/fail report. Intended to be run by maintainers
+//! and contributors against real silicon before a release:
+//!
+//! ```text
+//! cargo run --example hil_selftest --features hil -- /dev/i2c-1 [address]
+//! ```
+//!
+//! The process exits with a non-zero status if any check fails.
+
+use std::process::ExitCode;
+use std::time::Duration;
+
+use embedded_hal::delay::DelayNs;
+use linux_embedded_hal::{Delay, I2cdev};
+use vl6180x::{
+    device::DEFAULT_ADDRESS,
+    registers::{
+        AlsThresholds, FreshOutOfReset, InterruptClear, InterruptConfigGpio, ModeGpio1, ModelId,
+        RangeIntermeasurementPeriod, RangeResultStatus, RangeResultValue, RangeStart,
+        ResultInterruptStatusGpio,
+    },
+    Device, Error, GpioFunction, GpioPolarity, InterruptMode, Luminance,
+};
+
+/// Number of single-shot measurements taken per sensor
+const SAMPLES: usize = 10;
+
+/// Number of samples collected while in continuous mode
+const CONTINUOUS_SAMPLES: usize = 5;
+
+/// Maximum distance the ranging core can report (in millimeters)
+const MAX_RANGE_MM: f64 = 255.0;
+
+/// Upper bound on a plausible ALS reading (in lux)
+const MAX_LUX: f32 = 100_000.0;
+
+type Sensor = Device<I2cdev>;
+
+/// Outcome of a single check
+struct Check {
+    name: &'static str,
+    result: Result<String, String>,
+}
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let Some(bus) = args.next() else {
+        eprintln!("usage: hil_selftest <i2c bus path> [address]");
+        return ExitCode::from(2);
+    };
+    let address = match args.next() {
+        Some(value) => match u8::from_str_radix(value.trim_start_matches("0x"), 16) {
+            Ok(address) => address,
+            Err(_) => {
+                eprintln!("invalid address: {}", value);
+                return ExitCode::from(2);
+            }
+        },
+        None => DEFAULT_ADDRESS,
+    };
+
+    let i2c = match I2cdev::new(&bus) {
+        Ok(i2c) => i2c,
+        Err(e) => {
+            eprintln!("failed to open {}: {}", bus, e);
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut sensor = Device::new_with_address(i2c, address);
+    let mut delay = Delay;
+
+    println!("VL6180X HIL self-test on {} @ 0x{:02X}", bus, address);
+
+    let checks = [
+        run("identity", || identity(&mut sensor)),
+        run("initialization", || initialize(&mut sensor)),
+        run("single-shot range", || {
+            range_single(&mut sensor, &mut delay)
+        }),
+        run("single-shot ALS", || als_single(&mut sensor, &mut delay)),
+        run("continuous range", || {
+            range_continuous(&mut sensor, &mut delay)
+        }),
+        run("threshold interrupts", || thresholds(&mut sensor)),
+    ];
+
+    let failed = checks.iter().filter(|check| check.result.is_err()).count();
+
+    println!();
+    for check in &checks {
+        match &check.result {
+            Ok(detail) => println!("PASS  {:<24} {}", check.name, detail),
+            Err(detail) => println!("FAIL  {:<24} {}", check.name, detail),
+        }
+    }
+    println!();
+    println!("{} passed, {} failed", checks.len() - failed, failed);
+
+    if failed == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn run(name: &'static str, check: impl FnOnce() -> Result<String, String>) -> Check {
+    Check {
+        name,
+        result: check(),
+    }
+}
+
+fn bus(error: Error) -> String {
+    format!("device error: {}", error)
+}
+
+fn identity(sensor: &mut Sensor) -> Result<String, String> {
+    match sensor.read_register::<ModelId>().map_err(bus)? {
+        ModelId::VL6180X => Ok("model id 0xB4".into()),
+        ModelId::Unknown(id) => Err(format!("unexpected model id 0x{:02X}", id)),
+    }
+}
+
+fn initialize(sensor: &mut Sensor) -> Result<String, String> {
+    let fresh: FreshOutOfReset = sensor.read_register().map_err(bus)?;
+    sensor
+        .write_register(FreshOutOfReset { fresh: false })
+        .map_err(bus)?;
+
+    let config = InterruptConfigGpio {
+        range_interrupt: InterruptMode::NewSampleReady,
+        als_interrupt: InterruptMode::NewSampleReady,
+    };
+    sensor.write_register(config).map_err(bus)?;
+    sensor
+        .write_register(ModeGpio1 {
+            function: GpioFunction::InterruptOutput,
+            polarity: GpioPolarity::ActiveLow,
+        })
+        .map_err(bus)?;
+
+    let readback: InterruptConfigGpio = sensor.read_register().map_err(bus)?;
+    if readback.range_interrupt != config.range_interrupt
+        || readback.als_interrupt != config.als_interrupt
+    {
+        return Err(format!("interrupt config read back as {:?}", readback));
+    }
+
+    Ok(format!("fresh out of reset: {}", fresh.fresh))
+}
+
+fn range_single(sensor: &mut Sensor, delay: &mut Delay) -> Result<String, String> {
+    let mut min = f64::MAX;
+    let mut max = f64::MIN;
+
+    for _ in 0..SAMPLES {
+        let mm = match sensor.measure_range_single(delay) {
+            Ok(distance) => distance.as_millimeters(),
+            // No target in front of the sensor is a valid bench condition
+            Err(Error::RangeError(code)) if !is_hardware_fault(code) => continue,
+            Err(e) => return Err(bus(e)),
+        };
+
+        if !(0.0..=MAX_RANGE_MM).contains(&mm) {
+            return Err(format!("distance {} mm out of bounds", mm));
+        }
+        min = min.min(mm);
+        max = max.max(mm);
+    }
+
+    if min > max {
+        Ok(format!("{} samples, no target", SAMPLES))
+    } else {
+        Ok(format!("{} samples, {}..={} mm", SAMPLES, min, max))
+    }
+}
+
+fn als_single(sensor: &mut Sensor, delay: &mut Delay) -> Result<String, String> {
+    let mut min = f32::MAX;
+    let mut max = f32::MIN;
+
+    for _ in 0..SAMPLES {
+        let Luminance { lux } = sensor.measure_als_single(delay).map_err(bus)?;
+
+        if !lux.is_finite() || !(0.0..=MAX_LUX).contains(&lux) {
+            return Err(format!("light level {} lux out of bounds", lux));
+        }
+        min = min.min(lux);
+        max = max.max(lux);
+    }
+
+    Ok(format!("{} samples, {:.1}..={:.1} lux", SAMPLES, min, max))
+}
+
+fn range_continuous(sensor: &mut Sensor, delay: &mut Delay) -> Result<String, String> {
+    let period = Duration::from_millis(50);
+    sensor
+        .write_register(RangeIntermeasurementPeriod { period })
+        .map_err(bus)?;
+    sensor.write_register(RangeStart::Continuous).map_err(bus)?;
+
+    let mut samples = 0;
+    for _ in 0..(CONTINUOUS_SAMPLES * 20) {
+        let status: ResultInterruptStatusGpio = sensor.read_register().map_err(bus)?;
+        if status.range_interrupt {
+            let _: RangeResultValue = sensor.read_register().map_err(bus)?;
+            sensor
+                .write_register(InterruptClear {
+                    clear_range: true,
+                    clear_als: false,
+                    clear_error: false,
+                })
+                .map_err(bus)?;
+
+            samples += 1;
+            if samples == CONTINUOUS_SAMPLES {
+                break;
+            }
+        }
+        delay.delay_ms(10);
+    }
+
+    // Writing the start bit again in continuous mode halts ranging
+    sensor.write_register(RangeStart::Continuous).map_err(bus)?;
+
+    let mut stopped = false;
+    for _ in 0..100 {
+        let status: RangeResultStatus = sensor.read_register().map_err(bus)?;
+        if status.device_ready {
+            stopped = true;
+            break;
+        }
+        delay.delay_ms(1);
+    }
+
+    if samples < CONTINUOUS_SAMPLES {
+        Err(format!(
+            "only {} of {} samples arrived",
+            samples, CONTINUOUS_SAMPLES
+        ))
+    } else if !stopped {
+        Err("device never became ready after stop".into())
+    } else {
+        Ok(format!(
+            "{} samples at {} ms, stopped cleanly",
+            samples,
+            period.as_millis()
+        ))
+    }
+}
+
+fn thresholds(sensor: &mut Sensor) -> Result<String, String> {
+    let original: InterruptConfigGpio = sensor.read_register().map_err(bus)?;
+
+    let thresholds = AlsThresholds {
+        high: Luminance { lux: 4000.0 },
+        low: Luminance { lux: 100.0 },
+    };
+    sensor.write_register(thresholds).map_err(bus)?;
+    sensor
+        .write_register(InterruptConfigGpio {
+            range_interrupt: InterruptMode::NewSampleReady,
+            als_interrupt: InterruptMode::OutOfWindow,
+        })
+        .map_err(bus)?;
+
+    let readback_thresholds: AlsThresholds = sensor.read_register().map_err(bus)?;
+    let readback_config: InterruptConfigGpio = sensor.read_register().map_err(bus)?;
+
+    sensor.write_register(original).map_err(bus)?;
+
+    if readback_thresholds.high != thresholds.high || readback_thresholds.low != thresholds.low {
+        return Err(format!(
+            "ALS thresholds read back as {:?}",
+            readback_thresholds
+        ));
+    }
+    if readback_config.als_interrupt != InterruptMode::OutOfWindow {
+        return Err(format!(
+            "interrupt config read back as {:?}",
+            readback_config
+        ));
+    }
+
+    Ok("ALS out-of-window thresholds applied".into())
+}
+
+/// Range error codes that indicate a fault in the sensor rather than the scene
+fn is_hardware_fault(code: vl6180x::RangeErrorCode) -> bool {
+    use vl6180x::RangeErrorCode::*;
+    matches!(
+        code,
+        VcselContinuityTest | VcselWatchdogTest | VcselWatchdog | Pll1Lock | Pll2Lock
+    )
+}
@@ -0,0 +1,131 @@
+//! Drives the high-level helpers with arbitrary I2C responses
+//!
+//! Every helper must return `Ok` or `Err` without panicking. Each transaction
+//! consumes at least one input byte and the bus fails once the input is used
+//! up, so a helper that keeps polling a broken bus still terminates.
+//!
+//! Run with `cargo fuzz run device` from the repository root.
+
+#![no_main]
+
+use core::time::Duration;
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, NoAcknowledgeSource, Operation};
+use libfuzzer_sys::fuzz_target;
+use measurements::Length;
+use vl6180x::device::{scan_for_vl6180x, DEFAULT_ADDRESS};
+use vl6180x::events::EventQueue;
+use vl6180x::pololu_compat::Vl6180x;
+use vl6180x::registers::IdentificationBlock;
+use vl6180x::{st_compat, AdaptiveTiming, Device, Luminance};
+
+/// I2C bus answering from fuzzer-provided bytes
+///
+/// The first byte of each transaction decides its fate: below 0x08 it fails
+/// with an error of that kind, otherwise reads are filled from the following
+/// bytes and writes succeed.
+struct Bus<'a> {
+    data: &'a [u8],
+}
+
+impl Bus<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8], ErrorKind> {
+        if self.data.len() < len {
+            self.data = &[];
+            return Err(ErrorKind::Other);
+        }
+        let (taken, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(taken)
+    }
+}
+
+impl ErrorType for Bus<'_> {
+    type Error = ErrorKind;
+}
+
+impl I2c for Bus<'_> {
+    fn transaction(&mut self, _: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+        match self.take(1)?[0] {
+            0x00 => return Err(ErrorKind::Bus),
+            0x01 => return Err(ErrorKind::ArbitrationLoss),
+            0x02 => return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+            0x03 => return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data)),
+            0x04 => return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Unknown)),
+            0x05 => return Err(ErrorKind::Overrun),
+            0x06 | 0x07 => return Err(ErrorKind::Other),
+            _ => {}
+        }
+        for op in ops {
+            if let Operation::Read(buf) = op {
+                let len = buf.len();
+                buf.copy_from_slice(self.take(len)?);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Adaptive timing moving the convergence limit over its whole range
+const ADAPTIVE: AdaptiveTiming = AdaptiveTiming {
+    min_convergence: Duration::from_millis(1),
+    max_convergence: Duration::from_millis(63),
+    step: Duration::from_millis(5),
+    bright_ambient: 100,
+    dark_ambient: 10,
+    high_utilization: 90,
+    low_utilization: 30,
+};
+
+struct NoDelay;
+
+impl DelayNs for NoDelay {
+    fn delay_ns(&mut self, _: u32) {}
+}
+
+fuzz_target!(|data: &[u8]| {
+    let mut bus = Bus { data };
+    let mut delay = NoDelay;
+    let mut events = EventQueue::<4>::new();
+
+    let mut found = [0; 2];
+    let _ = scan_for_vl6180x(&mut bus, &[DEFAULT_ADDRESS, 0x30, 0x31], &mut found);
+
+    let mut dev = Device::new(&mut bus);
+    let _ = dev.read_register::<IdentificationBlock>();
+    let _ = dev.wait_for_boot(&mut delay, Duration::from_millis(10));
+    let _ = st_compat::prepare(&mut dev);
+    let _ = dev.read_range(&mut delay);
+    let _ = dev.measure_range_single(&mut delay);
+    let _ = dev.read_range_quick();
+    let _ = dev.read_als(&mut delay);
+    let _ = dev.measure_als_single(&mut delay);
+    let _ = dev.measure_both(&mut delay);
+    let _ = dev.handle_interrupt(&mut events);
+    events.drain().for_each(drop);
+    let _ = dev.health_check();
+    let _ = dev.measure_distance_with_retries(3, &mut delay);
+    let _ = dev.configure_beam_break_interrupt(Length::from_millimeters(50.0));
+    let _ = st_compat::range_poll_measurement(&mut dev, &mut delay);
+    let _ = st_compat::als_poll_measurement(&mut dev, &mut delay);
+    let _ = dev.read_calibration_registers();
+    let _ = dev.release();
+
+    let mut dev = Device::new(&mut bus);
+    dev.set_strict(true);
+    dev.set_busy_check(true);
+    dev.enable_adaptive_timing(ADAPTIVE);
+    let _ = dev.read_range(&mut delay);
+    let _ = dev.measure_both(&mut delay);
+    let _ = dev.configure_als_for(Luminance { lux: 1000.0 }, Duration::from_millis(100));
+    let _ = dev.release();
+
+    let mut sensor = Vl6180x::new(&mut bus, NoDelay);
+    let _ = sensor.init();
+    let _ = sensor.configure_default();
+    let _ = sensor.read_range_single_millimeters();
+    let _ = sensor.read_ambient_single();
+    let _ = sensor.read_range_single_millimeters_sentinel();
+    let _ = sensor.read_ambient_single_sentinel();
+});
@@ -0,0 +1,276 @@
+//! Beam-break counting
+//!
+//! With the sensor looking across a doorway, conveyor or chute, every object
+//! passing through blocks the beam once. [`BeamBreakCounter`] debounces the
+//! blocked and clear states so one crossing counts once, however much the
+//! readings chatter around the trigger distance.
+//!
+//! The counter can be fed range readings, or driven from the interrupt path
+//! with little bus traffic: configure an out-of-window interrupt with
+//! [`Device::configure_beam_break_interrupt`] and, on every range interrupt,
+//! flip the window with [`Device::set_beam_break_window`] and feed the new
+//! state with [`BeamBreakCounter::feed_state`]. The window only ever
+//! triggers on the side the beam is not on, so each interrupt is a change of
+//! state and costs one status read, one clear and one threshold write.
+
+use measurements::Length;
+
+use crate::device::{Device, DeviceAddress};
+use crate::registers::RangeThresholds;
+use crate::types::{Error, RangeInterrupt, RangeReading};
+
+/// Whether an object is in the beam
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BlockedState {
+    /// Nothing closer than the trigger distance
+    Clear,
+    /// An object closer than the trigger distance
+    Blocked,
+}
+
+impl BlockedState {
+    /// Returns the opposite state.
+    pub fn flipped(self) -> Self {
+        match self {
+            Self::Clear => Self::Blocked,
+            Self::Blocked => Self::Clear,
+        }
+    }
+}
+
+/// Trigger distance and debounce times of a [`BeamBreakCounter`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BeamBreakConfig {
+    /// Distance below which the beam is blocked
+    pub trigger: Length,
+    /// Time in ms the beam must stay blocked for a crossing to count
+    pub min_blocked_ms: u32,
+    /// Time in ms the beam must stay clear before the next crossing can count
+    pub min_clear_ms: u32,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for BeamBreakConfig {
+    fn format(&self, f: defmt::Formatter) {
+        use crate::types::Millimeters;
+
+        defmt::write!(
+            f,
+            "BeamBreakConfig {{ trigger: {}, min_blocked_ms: {}, min_clear_ms: {} }}",
+            Millimeters(self.trigger),
+            self.min_blocked_ms,
+            self.min_clear_ms
+        );
+    }
+}
+
+/// Debounced counter of beam crossings
+///
+/// A change of the raw state, blocked or clear, is only accepted once it has
+/// lasted the configured minimum time; shorter excursions are discarded. The
+/// count increases each time a blocked state is accepted and never
+/// decreases, wrapping around after `u32::MAX` crossings.
+///
+/// The caller supplies a millisecond tick with every input. The tick may wrap
+/// around. Between inputs, call [`update`](BeamBreakCounter::update) to
+/// accept a pending change once its minimum time has passed; with range
+/// readings arriving continuously this happens on its own.
+///
+/// # Example
+/// ```
+/// use measurements::Length;
+/// use vl6180x::beam::{BeamBreakConfig, BeamBreakCounter, BlockedState};
+/// use vl6180x::RangeReading;
+///
+/// let mut counter = BeamBreakCounter::new(BeamBreakConfig {
+///     trigger: Length::from_millimeters(80.0),
+///     min_blocked_ms: 30,
+///     min_clear_ms: 50,
+/// });
+/// let at = |mm| RangeReading::Valid(Length::from_millimeters(mm));
+///
+/// assert_eq!(counter.feed(0, at(40.0)), None);
+/// assert_eq!(counter.feed(30, at(45.0)), Some(BlockedState::Blocked));
+/// assert_eq!(counter.feed(60, RangeReading::NoTarget), None);
+/// assert_eq!(counter.update(110), Some(BlockedState::Clear));
+/// assert_eq!(counter.count(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BeamBreakCounter {
+    config: BeamBreakConfig,
+    state: BlockedState,
+    pending: Option<u32>,
+    count: u32,
+}
+
+impl BeamBreakCounter {
+    /// Creates a counter with the beam clear and a count of zero.
+    pub fn new(config: BeamBreakConfig) -> Self {
+        Self {
+            config,
+            state: BlockedState::Clear,
+            pending: None,
+            count: 0,
+        }
+    }
+
+    /// Returns the parameters of the counter.
+    pub fn config(&self) -> BeamBreakConfig {
+        self.config
+    }
+
+    /// Returns the number of crossings counted.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Returns the debounced state of the beam.
+    pub fn state(&self) -> BlockedState {
+        self.state
+    }
+
+    /// Processes a range reading.
+    ///
+    /// Valid readings below the trigger distance block the beam, all other
+    /// valid readings and no-target readings clear it. Failed readings are
+    /// skipped.
+    ///
+    /// Returns the new debounced state if it changed.
+    pub fn feed(&mut self, tick_ms: u32, reading: RangeReading) -> Option<BlockedState> {
+        let raw = match reading {
+            RangeReading::Valid(distance)
+                if distance.as_millimeters() < self.config.trigger.as_millimeters() =>
+            {
+                BlockedState::Blocked
+            }
+            RangeReading::Valid(_) | RangeReading::NoTarget => BlockedState::Clear,
+            RangeReading::Failed(_) => return self.update(tick_ms),
+        };
+        self.feed_state(tick_ms, raw)
+    }
+
+    /// Processes a raw beam state, e.g. derived from a threshold interrupt.
+    ///
+    /// Returns the new debounced state if it changed.
+    pub fn feed_state(&mut self, tick_ms: u32, raw: BlockedState) -> Option<BlockedState> {
+        if raw == self.state {
+            self.pending = None;
+            return None;
+        }
+        if self.pending.is_none() {
+            self.pending = Some(tick_ms);
+        }
+        self.update(tick_ms)
+    }
+
+    /// Accepts a pending change of state once it has lasted its minimum time.
+    ///
+    /// Returns the new debounced state if it changed.
+    pub fn update(&mut self, tick_ms: u32) -> Option<BlockedState> {
+        let since = self.pending?;
+        let min_ms = match self.state {
+            BlockedState::Clear => self.config.min_blocked_ms,
+            BlockedState::Blocked => self.config.min_clear_ms,
+        };
+        if tick_ms.wrapping_sub(since) < min_ms {
+            return None;
+        }
+
+        self.pending = None;
+        self.state = self.state.flipped();
+        if self.state == BlockedState::Blocked {
+            self.count = self.count.wrapping_add(1);
+        }
+        Some(self.state)
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Configures an out-of-window range interrupt for beam-break counting.
+    ///
+    /// Selects [`RangeInterrupt::OutOfWindow`] with the window for a clear
+    /// beam, so the first interrupt reports a blocked beam. Route the
+    /// interrupt to a pin with
+    /// [`configure_gpio1_interrupt`](Device::configure_gpio1_interrupt) or
+    /// poll it, and start continuous ranging.
+    ///
+    /// # Arguments
+    /// * `trigger` - Distance below which the beam is blocked
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn configure_beam_break_interrupt(&mut self, trigger: Length) -> Result<(), Error> {
+        let window = beam_window(trigger, BlockedState::Clear);
+        self.set_range_interrupt(RangeInterrupt::OutOfWindow {
+            low: window.low,
+            high: window.high,
+        })
+    }
+
+    /// Moves the beam-break window so the interrupt triggers on leaving `state`.
+    ///
+    /// Call after each range interrupt with the state the beam is now in.
+    /// Writes only the thresholds, one transaction.
+    ///
+    /// # Arguments
+    /// * `trigger` - Distance below which the beam is blocked
+    /// * `state` - Raw state of the beam
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn set_beam_break_window(
+        &mut self,
+        trigger: Length,
+        state: BlockedState,
+    ) -> Result<(), Error> {
+        self.write_register(beam_window(trigger, state))
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously configures an out-of-window range interrupt for beam-break counting.
+    ///
+    /// This is the async version of [`configure_beam_break_interrupt`](Device::configure_beam_break_interrupt).
+    pub async fn configure_beam_break_interrupt_async(
+        &mut self,
+        trigger: Length,
+    ) -> Result<(), Error> {
+        let window = beam_window(trigger, BlockedState::Clear);
+        self.set_range_interrupt_async(RangeInterrupt::OutOfWindow {
+            low: window.low,
+            high: window.high,
+        })
+        .await
+    }
+
+    /// Asynchronously moves the beam-break window.
+    ///
+    /// This is the async version of [`set_beam_break_window`](Device::set_beam_break_window).
+    pub async fn set_beam_break_window_async(
+        &mut self,
+        trigger: Length,
+        state: BlockedState,
+    ) -> Result<(), Error> {
+        self.write_register_async(beam_window(trigger, state)).await
+    }
+}
+
+/// Out-of-window thresholds triggering when the beam leaves `state`
+///
+/// Range values are whole millimeters, so a clear beam becomes blocked below
+/// `trigger` and a blocked beam clears above `trigger - 1mm`.
+fn beam_window(trigger: Length, state: BlockedState) -> RangeThresholds {
+    match state {
+        BlockedState::Clear => RangeThresholds::below(trigger),
+        BlockedState::Blocked => {
+            RangeThresholds::above(Length::from_millimeters(trigger.as_millimeters() - 1.0))
+        }
+    }
+}
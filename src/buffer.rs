@@ -0,0 +1,189 @@
+//! Measurement buffering
+//!
+//! Continuous measurements often arrive faster than the application consumes
+//! them, e.g. read in an interrupt handler and processed by the main loop.
+//! [`MeasurementBuffer`] is a fixed-size ring buffer for that hand off, for
+//! [`RangeReading`](crate::RangeReading)s, [`AlsReading`](crate::AlsReading)s
+//! or their [`Timestamped`](crate::clock::Timestamped) versions.
+//!
+//! Like [`EventQueue`](crate::events::EventQueue) the buffer is not
+//! synchronized; share it with the platform's primitive of choice.
+
+/// What [`MeasurementBuffer::push`] does when the buffer is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum OverflowPolicy {
+    /// Discard the oldest measurement to make room, keeping the latest ones
+    #[default]
+    DropOldest,
+    /// Discard the new measurement, keeping the earliest ones
+    RejectNewest,
+}
+
+/// Fixed-capacity FIFO of measurements
+///
+/// When the buffer is full a measurement is discarded according to the
+/// [`OverflowPolicy`] and counted, so the consumer can tell that it fell
+/// behind.
+///
+/// # Example
+/// ```
+/// use measurements::Length;
+/// use vl6180x::buffer::{MeasurementBuffer, OverflowPolicy};
+/// use vl6180x::RangeReading;
+///
+/// let mut buffer = MeasurementBuffer::<RangeReading, 2>::new(OverflowPolicy::DropOldest);
+/// let at = |mm| RangeReading::Valid(Length::from_millimeters(mm));
+///
+/// // Producer side
+/// buffer.push(at(10.0));
+/// buffer.push(at(20.0));
+/// buffer.push(at(30.0));
+///
+/// // Consumer side
+/// let mut readings = buffer.drain();
+/// assert_eq!(readings.next(), Some(at(20.0)));
+/// assert_eq!(readings.next(), Some(at(30.0)));
+/// assert_eq!(readings.next(), None);
+/// assert_eq!(buffer.overflows(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MeasurementBuffer<T, const N: usize> {
+    items: [Option<T>; N],
+    head: usize,
+    len: usize,
+    policy: OverflowPolicy,
+    overflows: u32,
+}
+
+impl<T, const N: usize> MeasurementBuffer<T, N> {
+    /// Creates an empty buffer.
+    ///
+    /// # Panics
+    /// Panics if `N` is zero.
+    pub const fn new(policy: OverflowPolicy) -> Self {
+        assert!(N > 0, "a measurement buffer needs room for one measurement");
+        Self {
+            items: [const { None }; N],
+            head: 0,
+            len: 0,
+            policy,
+            overflows: 0,
+        }
+    }
+
+    /// Appends a measurement.
+    ///
+    /// Returns the measurement discarded because the buffer was full: the
+    /// oldest one with [`OverflowPolicy::DropOldest`], `item` itself with
+    /// [`OverflowPolicy::RejectNewest`].
+    pub fn push(&mut self, item: T) -> Option<T> {
+        if self.len < N {
+            self.items[(self.head + self.len) % N] = Some(item);
+            self.len += 1;
+            return None;
+        }
+
+        self.overflows = self.overflows.saturating_add(1);
+        match self.policy {
+            OverflowPolicy::DropOldest => {
+                let oldest = self.items[self.head].replace(item);
+                self.head = (self.head + 1) % N;
+                oldest
+            }
+            OverflowPolicy::RejectNewest => Some(item),
+        }
+    }
+
+    /// Removes and returns the oldest measurement.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let item = self.items[self.head].take();
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        item
+    }
+
+    /// Returns the oldest measurement without removing it.
+    pub fn peek(&self) -> Option<&T> {
+        self.iter().next()
+    }
+
+    /// Returns an iterator over the buffered measurements, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        (0..self.len).filter_map(move |i| self.items[(self.head + i) % N].as_ref())
+    }
+
+    /// Returns an iterator removing measurements oldest first.
+    pub fn drain(&mut self) -> Drain<'_, T, N> {
+        Drain { buffer: self }
+    }
+
+    /// Removes all measurements, keeping the overflow counter.
+    pub fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
+
+    /// Number of buffered measurements.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether no measurements are buffered.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns whether the next push overflows.
+    pub const fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Maximum number of buffered measurements.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns the overflow policy.
+    pub const fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    /// Number of measurements discarded because the buffer was full.
+    ///
+    /// Saturates rather than wraps.
+    pub const fn overflows(&self) -> u32 {
+        self.overflows
+    }
+
+    /// Clears the overflow counter.
+    pub fn reset_overflows(&mut self) {
+        self.overflows = 0;
+    }
+}
+
+impl<T, const N: usize> Default for MeasurementBuffer<T, N> {
+    /// Empty buffer dropping the oldest measurement on overflow
+    fn default() -> Self {
+        Self::new(OverflowPolicy::default())
+    }
+}
+
+/// Draining iterator returned by [`MeasurementBuffer::drain`]
+///
+/// Measurements not consumed before the iterator is dropped stay in the
+/// buffer.
+pub struct Drain<'a, T, const N: usize> {
+    buffer: &'a mut MeasurementBuffer<T, N>,
+}
+
+impl<T, const N: usize> Iterator for Drain<'_, T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.buffer.pop()
+    }
+}
@@ -0,0 +1,356 @@
+//! Persistable per-unit calibration
+//!
+//! The part-to-part range offset and the crosstalk compensation rate are
+//! measured once per module, usually on the production line, and must survive
+//! firmware updates. [`CalibrationData`] holds them together with a compact,
+//! versioned binary format for storing them in a few bytes of EEPROM.
+//!
+//! # Format (version 1)
+//!
+//! All multi-byte values are big endian.
+//!
+//! | Offset | Size | Field |
+//! |--------|------|-------|
+//! | 0      | 1    | Format version (`1`) |
+//! | 1      | 1    | Range offset in mm, two's complement ([`RangePartToPartOffset`]) |
+//! | 2      | 2    | Crosstalk compensation rate ([`RangeCrosstalkCompensationRate`]) |
+//! | 4      | 1    | Range scaling factor |
+//! | 5      | 2    | CRC-16/CCITT-FALSE over bytes 0..5 |
+//!
+//! Once released, a format version is never changed; new fields get a new
+//! version number, and blobs of every earlier version stay readable.
+//!
+//! [`CalibrationRegisters`] is the live counterpart: every calibration
+//! relevant range register, read and written as one group.
+
+use measurements::Length;
+
+use crate::config::{crc16, ConfigFormatError};
+use crate::device::{Device, DeviceAddress, HOLD, RELEASE};
+use crate::registers::{
+    RangeCheckEnables, RangeCrosstalkCompensationRate, RangeCrosstalkValidHeight,
+    RangeIgnoreThreshold, RangeIgnoreValidHeight, RangePartToPartOffset,
+};
+use crate::types::{Error, ErrorContext};
+
+/// Per-unit calibration of a sensor
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CalibrationData {
+    /// Offset added to every range value, -128mm to 127mm
+    pub offset: Length,
+    /// Crosstalk compensation rate in Mcps (9.7 fixed point)
+    pub crosstalk_rate: u16,
+    /// Range scaling factor; this crate ranges at the native 1x scaling only
+    pub scaling: u8,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for CalibrationData {
+    fn format(&self, f: defmt::Formatter) {
+        use crate::types::Millimeters;
+
+        defmt::write!(
+            f,
+            "CalibrationData {{ offset: {}, crosstalk_rate: {}, scaling: {} }}",
+            Millimeters(self.offset),
+            self.crosstalk_rate,
+            self.scaling
+        );
+    }
+}
+
+impl CalibrationData {
+    /// Current binary format version
+    pub const FORMAT_VERSION: u8 = 1;
+
+    /// Number of bytes produced by [`CalibrationData::to_blob`]
+    pub const ENCODED_LEN: usize = 7;
+
+    /// Offset of the checksum within the encoded blob
+    const CHECKSUM_OFFSET: usize = Self::ENCODED_LEN - 2;
+
+    /// Encodes the calibration into `buf`
+    ///
+    /// Returns the number of bytes written, always
+    /// [`CalibrationData::ENCODED_LEN`].
+    ///
+    /// # Errors
+    /// * [`ConfigFormatError::BufferTooSmall`] if `buf` cannot hold the blob
+    /// * [`ConfigFormatError::InvalidField`] if the offset is outside -128mm
+    ///   to 127mm
+    pub fn to_blob(&self, buf: &mut [u8]) -> Result<usize, ConfigFormatError> {
+        let out = buf
+            .get_mut(..Self::ENCODED_LEN)
+            .ok_or(ConfigFormatError::BufferTooSmall)?;
+
+        let offset = self.offset.as_millimeters();
+        if !(i8::MIN as f64..=i8::MAX as f64).contains(&offset) {
+            return Err(ConfigFormatError::InvalidField);
+        }
+
+        out[0] = Self::FORMAT_VERSION;
+        out[1] = offset as i8 as u8;
+        out[2..4].copy_from_slice(&self.crosstalk_rate.to_be_bytes());
+        out[4] = self.scaling;
+        let checksum = crc16(&out[..Self::CHECKSUM_OFFSET]);
+        out[Self::CHECKSUM_OFFSET..].copy_from_slice(&checksum.to_be_bytes());
+
+        Ok(Self::ENCODED_LEN)
+    }
+
+    /// Decodes a calibration previously written by [`CalibrationData::to_blob`]
+    ///
+    /// Bytes beyond the encoded length are ignored.
+    ///
+    /// # Errors
+    /// * [`ConfigFormatError::BufferTooSmall`] if `bytes` is truncated
+    /// * [`ConfigFormatError::UnsupportedVersion`] if the version byte is unknown
+    /// * [`ConfigFormatError::ChecksumMismatch`] if the blob is corrupt
+    pub fn from_blob(bytes: &[u8]) -> Result<Self, ConfigFormatError> {
+        let version = *bytes.first().ok_or(ConfigFormatError::BufferTooSmall)?;
+        if version != Self::FORMAT_VERSION {
+            return Err(ConfigFormatError::UnsupportedVersion(version));
+        }
+
+        let bytes = bytes
+            .get(..Self::ENCODED_LEN)
+            .ok_or(ConfigFormatError::BufferTooSmall)?;
+        let (payload, checksum) = bytes.split_at(Self::CHECKSUM_OFFSET);
+        if crc16(payload) != u16::from_be_bytes([checksum[0], checksum[1]]) {
+            return Err(ConfigFormatError::ChecksumMismatch);
+        }
+
+        Ok(Self {
+            offset: Length::from_millimeters(payload[1] as i8 as f64),
+            crosstalk_rate: u16::from_be_bytes([payload[2], payload[3]]),
+            scaling: payload[4],
+        })
+    }
+}
+
+/// Calibration relevant range registers
+///
+/// Distances are in millimeters at the native 1x range scaling, the only
+/// scaling this crate ranges at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CalibrationRegisters {
+    /// Part-to-part range offset
+    pub offset: RangePartToPartOffset,
+    /// Crosstalk compensation rate
+    pub crosstalk_rate: RangeCrosstalkCompensationRate,
+    /// Minimum range crosstalk compensation is applied to
+    pub crosstalk_valid_height: RangeCrosstalkValidHeight,
+    /// Whether the range ignore check of [`RangeCheckEnables`] is enabled
+    pub range_ignore: bool,
+    /// Range below which the range ignore threshold applies
+    pub range_ignore_valid_height: RangeIgnoreValidHeight,
+    /// Return rate below which a range is ignored
+    pub range_ignore_threshold: RangeIgnoreThreshold,
+}
+
+impl From<&CalibrationRegisters> for CalibrationData {
+    /// The persistable part of the registers
+    fn from(registers: &CalibrationRegisters) -> Self {
+        Self {
+            offset: registers.offset.offset,
+            crosstalk_rate: registers.crosstalk_rate.rate,
+            scaling: 1,
+        }
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Reads every calibration relevant range register.
+    ///
+    /// Costs six I2C transactions.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn read_calibration_registers(&mut self) -> Result<CalibrationRegisters, Error> {
+        let checks: RangeCheckEnables = self.read_register()?;
+        Ok(CalibrationRegisters {
+            offset: self.read_register()?,
+            crosstalk_rate: self.read_register()?,
+            crosstalk_valid_height: self.read_register()?,
+            range_ignore: checks.enable_range_check,
+            range_ignore_valid_height: self.read_register()?,
+            range_ignore_threshold: self.read_register()?,
+        })
+    }
+
+    /// Writes every calibration relevant range register.
+    ///
+    /// The registers are written inside a
+    /// [`GroupedParameterHold`](crate::registers::GroupedParameterHold), so a
+    /// running measurement never uses a partial set. The other range checks
+    /// are kept as they are.
+    ///
+    /// # Arguments
+    /// * `registers` - Register values to write
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn write_calibration_registers(
+        &mut self,
+        registers: &CalibrationRegisters,
+    ) -> Result<(), Error> {
+        let checks: RangeCheckEnables = self.read_register()?;
+        self.write_register(HOLD)?;
+        let written = self.write_calibration_group(registers, checks);
+        self.write_register(RELEASE)?;
+        written
+    }
+
+    fn write_calibration_group(
+        &mut self,
+        registers: &CalibrationRegisters,
+        checks: RangeCheckEnables,
+    ) -> Result<(), Error> {
+        self.write_register(registers.offset)?;
+        self.write_register(registers.crosstalk_rate)?;
+        self.write_register(registers.crosstalk_valid_height)?;
+        self.write_register(registers.range_ignore_valid_height)?;
+        self.write_register(registers.range_ignore_threshold)?;
+        self.write_register(RangeCheckEnables {
+            enable_range_check: registers.range_ignore,
+            ..checks
+        })
+    }
+
+    /// Writes a calibration to the range offset and crosstalk registers.
+    ///
+    /// The registers are reloaded from the module's NVM on every boot, so
+    /// apply the calibration again after each reset.
+    ///
+    /// # Arguments
+    /// * `calibration` - Calibration to apply
+    ///
+    /// # Errors
+    /// * `Error::SerializationError` - The scaling factor is not 1
+    /// * `Error::BusError` - I2C communication failed
+    pub fn apply_calibration(&mut self, calibration: &CalibrationData) -> Result<(), Error> {
+        let (offset, crosstalk) = calibration_registers(calibration)?;
+        self.write_register(offset)?;
+        self.write_register(crosstalk)
+    }
+
+    /// Reads the calibration currently in use from the device.
+    ///
+    /// Right after boot this is the factory calibration from the module's NVM.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn read_calibration(&mut self) -> Result<CalibrationData, Error> {
+        let offset: RangePartToPartOffset = self.read_register()?;
+        let crosstalk: RangeCrosstalkCompensationRate = self.read_register()?;
+        Ok(CalibrationData {
+            offset: offset.offset,
+            crosstalk_rate: crosstalk.rate,
+            scaling: 1,
+        })
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously reads every calibration relevant range register.
+    ///
+    /// This is the async version of
+    /// [`read_calibration_registers`](Device::read_calibration_registers).
+    pub async fn read_calibration_registers_async(
+        &mut self,
+    ) -> Result<CalibrationRegisters, Error> {
+        let checks: RangeCheckEnables = self.read_register_async().await?;
+        Ok(CalibrationRegisters {
+            offset: self.read_register_async().await?,
+            crosstalk_rate: self.read_register_async().await?,
+            crosstalk_valid_height: self.read_register_async().await?,
+            range_ignore: checks.enable_range_check,
+            range_ignore_valid_height: self.read_register_async().await?,
+            range_ignore_threshold: self.read_register_async().await?,
+        })
+    }
+
+    /// Asynchronously writes every calibration relevant range register.
+    ///
+    /// This is the async version of
+    /// [`write_calibration_registers`](Device::write_calibration_registers).
+    pub async fn write_calibration_registers_async(
+        &mut self,
+        registers: &CalibrationRegisters,
+    ) -> Result<(), Error> {
+        let checks: RangeCheckEnables = self.read_register_async().await?;
+        self.write_register_async(HOLD).await?;
+        let written = self.write_calibration_group_async(registers, checks).await;
+        self.write_register_async(RELEASE).await?;
+        written
+    }
+
+    async fn write_calibration_group_async(
+        &mut self,
+        registers: &CalibrationRegisters,
+        checks: RangeCheckEnables,
+    ) -> Result<(), Error> {
+        self.write_register_async(registers.offset).await?;
+        self.write_register_async(registers.crosstalk_rate).await?;
+        self.write_register_async(registers.crosstalk_valid_height)
+            .await?;
+        self.write_register_async(registers.range_ignore_valid_height)
+            .await?;
+        self.write_register_async(registers.range_ignore_threshold)
+            .await?;
+        self.write_register_async(RangeCheckEnables {
+            enable_range_check: registers.range_ignore,
+            ..checks
+        })
+        .await
+    }
+
+    /// Asynchronously writes a calibration to the range offset and crosstalk registers.
+    ///
+    /// This is the async version of [`apply_calibration`](Device::apply_calibration).
+    pub async fn apply_calibration_async(
+        &mut self,
+        calibration: &CalibrationData,
+    ) -> Result<(), Error> {
+        let (offset, crosstalk) = calibration_registers(calibration)?;
+        self.write_register_async(offset).await?;
+        self.write_register_async(crosstalk).await
+    }
+
+    /// Asynchronously reads the calibration currently in use from the device.
+    ///
+    /// This is the async version of [`read_calibration`](Device::read_calibration).
+    pub async fn read_calibration_async(&mut self) -> Result<CalibrationData, Error> {
+        let offset: RangePartToPartOffset = self.read_register_async().await?;
+        let crosstalk: RangeCrosstalkCompensationRate = self.read_register_async().await?;
+        Ok(CalibrationData {
+            offset: offset.offset,
+            crosstalk_rate: crosstalk.rate,
+            scaling: 1,
+        })
+    }
+}
+
+/// Registers a calibration is written to
+fn calibration_registers(
+    calibration: &CalibrationData,
+) -> Result<(RangePartToPartOffset, RangeCrosstalkCompensationRate), Error> {
+    if calibration.scaling != 1 {
+        return Err(Error::SerializationError(ErrorContext::argument()));
+    }
+    Ok((
+        RangePartToPartOffset {
+            offset: calibration.offset,
+        },
+        RangeCrosstalkCompensationRate {
+            rate: calibration.crosstalk_rate,
+        },
+    ))
+}
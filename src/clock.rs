@@ -0,0 +1,61 @@
+//! Measurement timestamps
+//!
+//! The driver has no notion of time besides delays. Attach a [`Clock`] to a
+//! [`Device`](crate::Device) with
+//! [`set_clock`](crate::Device::set_clock) and every sample observed by the
+//! measurement helpers is stamped with the time its sample-ready flag was
+//! seen, ready to be correlated with other sensors.
+
+use core::fmt;
+
+/// Monotonic millisecond clock supplied by the application
+///
+/// Usually a thin wrapper around the platform's timer, e.g.
+/// `embassy_time::Instant::now().as_millis()`.
+///
+/// # Example
+/// ```
+/// use vl6180x::clock::Clock;
+///
+/// struct Uptime;
+///
+/// impl Clock for Uptime {
+///     fn now_millis(&self) -> u64 {
+///         // Read the platform's monotonic timer here
+///         0
+///     }
+/// }
+///
+/// static UPTIME: Uptime = Uptime;
+/// ```
+pub trait Clock: Sync {
+    /// Milliseconds since an arbitrary, fixed epoch
+    fn now_millis(&self) -> u64;
+}
+
+/// A measurement together with the time its sample became ready
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Timestamped<T> {
+    /// The measurement
+    pub value: T,
+    /// [`Clock::now_millis`] when the sample-ready flag was observed, or
+    /// `None` without a clock attached
+    pub timestamp: Option<u64>,
+}
+
+/// Clock attached to a device, compared by identity
+#[derive(Clone, Copy)]
+pub(crate) struct ClockRef(pub(crate) &'static dyn Clock);
+
+impl fmt::Debug for ClockRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Clock")
+    }
+}
+
+impl PartialEq for ClockRef {
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::addr_eq(self.0, other.0)
+    }
+}
@@ -0,0 +1,282 @@
+//! Persistable sensor configuration
+//!
+//! [`FullConfig`] collects the writable configuration registers into a single
+//! value and defines a compact, fixed-layout binary format for it, intended for
+//! storing a sensor's configuration in a few bytes of MCU flash or EEPROM.
+//!
+//! # Format (version 1)
+//!
+//! All multi-byte values are big endian. Fields use the same integer encoding
+//! as the corresponding device register, so the layout is pinned by the
+//! datasheet rather than by this crate's types.
+//!
+//! | Offset | Size | Field |
+//! |--------|------|-------|
+//! | 0      | 1    | Format version (`1`) |
+//! | 1      | 4    | Range thresholds: high mm (u16), low mm (u16) |
+//! | 5      | 1    | [`RangeIntermeasurementPeriod`] |
+//! | 6      | 1    | [`RangeMaxConvergenceTime`] |
+//! | 7      | 2    | [`RangeCrosstalkCompensationRate`] |
+//! | 9      | 1    | [`RangeCrosstalkValidHeight`] |
+//! | 10     | 2    | [`RangeEarlyConvergenceEstimate`] |
+//! | 12     | 1    | [`RangeCheckEnables`] |
+//! | 13     | 1    | [`RangeVhvRepeatRate`] |
+//! | 14     | 4    | [`AlsThresholds`] |
+//! | 18     | 1    | [`AlsIntermeasurementPeriod`] |
+//! | 19     | 1    | [`AlsAnalogueGain`] |
+//! | 20     | 2    | [`AlsIntegrationPeriod`] |
+//! | 22     | 1    | [`InterruptConfigGpio`] |
+//! | 23     | 1    | [`ModeGpio0`] |
+//! | 24     | 1    | [`ModeGpio1`] |
+//! | 25     | 2    | CRC-16/CCITT-FALSE over bytes 0..25 |
+//!
+//! Once released, a format version is never changed; new fields get a new
+//! version number.
+
+use core::fmt;
+
+use measurements::Length;
+use regiface::{ByteArray, FromByteArray, ToByteArray};
+
+use crate::registers::{
+    AlsAnalogueGain, AlsIntegrationPeriod, AlsIntermeasurementPeriod, AlsThresholds,
+    InterruptConfigGpio, ModeGpio0, ModeGpio1, RangeCheckEnables, RangeCrosstalkCompensationRate,
+    RangeCrosstalkValidHeight, RangeEarlyConvergenceEstimate, RangeIntermeasurementPeriod,
+    RangeMaxConvergenceTime, RangeThresholds, RangeVhvRepeatRate,
+};
+
+/// Errors produced while encoding or decoding a [`FullConfig`] or a
+/// [`CalibrationData`](crate::calibration::CalibrationData)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ConfigFormatError {
+    /// The buffer is shorter than the encoded blob
+    BufferTooSmall,
+    /// The blob was written with a format version this crate cannot read
+    /// Contains the version byte found in the blob
+    UnsupportedVersion(u8),
+    /// The stored checksum does not match the contents, e.g. after a
+    /// partially written flash page
+    ChecksumMismatch,
+    /// A field holds a value that cannot be represented in the format
+    InvalidField,
+}
+
+impl fmt::Display for ConfigFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BufferTooSmall => write!(f, "Buffer too small for configuration"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "Unsupported configuration format version: {}", version)
+            }
+            Self::ChecksumMismatch => write!(f, "Configuration checksum mismatch"),
+            Self::InvalidField => write!(f, "Invalid configuration field"),
+        }
+    }
+}
+
+/// Snapshot of the sensor's writable configuration registers
+///
+/// The default holds the power-on values, see
+/// [`POWER_ON_DEFAULTS`](crate::registers::POWER_ON_DEFAULTS).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FullConfig {
+    /// Range interrupt thresholds
+    pub range_thresholds: RangeThresholds,
+    /// Range continuous mode period
+    pub range_intermeasurement_period: RangeIntermeasurementPeriod,
+    /// Range convergence time limit
+    pub range_max_convergence_time: RangeMaxConvergenceTime,
+    /// Range crosstalk compensation rate
+    pub range_crosstalk_compensation_rate: RangeCrosstalkCompensationRate,
+    /// Minimum range for crosstalk compensation
+    pub range_crosstalk_valid_height: RangeCrosstalkValidHeight,
+    /// Early convergence estimate threshold
+    pub range_early_convergence_estimate: RangeEarlyConvergenceEstimate,
+    /// Range result checks
+    pub range_check_enables: RangeCheckEnables,
+    /// VHV recalibration rate
+    pub range_vhv_repeat_rate: RangeVhvRepeatRate,
+    /// ALS interrupt thresholds
+    pub als_thresholds: AlsThresholds,
+    /// ALS continuous mode period
+    pub als_intermeasurement_period: AlsIntermeasurementPeriod,
+    /// ALS analog gain
+    pub als_analogue_gain: AlsAnalogueGain,
+    /// ALS integration time
+    pub als_integration_period: AlsIntegrationPeriod,
+    /// Interrupt modes
+    pub interrupt_config: InterruptConfigGpio,
+    /// GPIO0 mode
+    pub gpio0: ModeGpio0,
+    /// GPIO1 mode
+    pub gpio1: ModeGpio1,
+}
+
+impl FullConfig {
+    /// Current binary format version
+    pub const FORMAT_VERSION: u8 = 1;
+
+    /// Number of bytes produced by [`FullConfig::to_bytes`]
+    pub const ENCODED_LEN: usize = 27;
+
+    /// Offset of the checksum within the encoded blob
+    const CHECKSUM_OFFSET: usize = Self::ENCODED_LEN - 2;
+
+    /// Encodes the configuration into `buf`
+    ///
+    /// Returns the number of bytes written, always [`FullConfig::ENCODED_LEN`].
+    ///
+    /// # Errors
+    /// * [`ConfigFormatError::BufferTooSmall`] if `buf` cannot hold the blob
+    /// * [`ConfigFormatError::InvalidField`] if a field is outside the range
+    ///   its register can represent
+    pub fn to_bytes(&self, buf: &mut [u8]) -> Result<usize, ConfigFormatError> {
+        let out = buf
+            .get_mut(..Self::ENCODED_LEN)
+            .ok_or(ConfigFormatError::BufferTooSmall)?;
+
+        let mut writer = Cursor { buf: out, pos: 0 };
+        writer.put_raw(&[Self::FORMAT_VERSION]);
+        writer.put_raw(&millimeters(self.range_thresholds.high)?);
+        writer.put_raw(&millimeters(self.range_thresholds.low)?);
+        writer.put(self.range_intermeasurement_period)?;
+        writer.put(self.range_max_convergence_time)?;
+        writer.put(self.range_crosstalk_compensation_rate)?;
+        writer.put(self.range_crosstalk_valid_height)?;
+        writer.put(self.range_early_convergence_estimate)?;
+        writer.put(self.range_check_enables)?;
+        writer.put(self.range_vhv_repeat_rate)?;
+        writer.put(self.als_thresholds)?;
+        writer.put(self.als_intermeasurement_period)?;
+        writer.put(self.als_analogue_gain)?;
+        writer.put(self.als_integration_period)?;
+        writer.put(self.interrupt_config)?;
+        writer.put(self.gpio0)?;
+        writer.put(self.gpio1)?;
+
+        let checksum = crc16(&writer.buf[..Self::CHECKSUM_OFFSET]);
+        writer.put_raw(&checksum.to_be_bytes());
+
+        Ok(writer.pos)
+    }
+
+    /// Decodes a configuration previously written by [`FullConfig::to_bytes`]
+    ///
+    /// Bytes beyond [`FullConfig::ENCODED_LEN`] are ignored.
+    ///
+    /// # Errors
+    /// * [`ConfigFormatError::BufferTooSmall`] if `bytes` is truncated
+    /// * [`ConfigFormatError::UnsupportedVersion`] if the version byte is unknown
+    /// * [`ConfigFormatError::ChecksumMismatch`] if the blob is corrupt
+    /// * [`ConfigFormatError::InvalidField`] if a field fails to decode
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ConfigFormatError> {
+        let bytes = bytes
+            .get(..Self::ENCODED_LEN)
+            .ok_or(ConfigFormatError::BufferTooSmall)?;
+
+        if bytes[0] != Self::FORMAT_VERSION {
+            return Err(ConfigFormatError::UnsupportedVersion(bytes[0]));
+        }
+
+        let (payload, checksum) = bytes.split_at(Self::CHECKSUM_OFFSET);
+        if crc16(payload) != u16::from_be_bytes([checksum[0], checksum[1]]) {
+            return Err(ConfigFormatError::ChecksumMismatch);
+        }
+
+        let mut reader = Cursor {
+            buf: payload,
+            pos: 1,
+        };
+        let high = reader.take_raw(2);
+        let high = u16::from_be_bytes([high[0], high[1]]);
+        let low = reader.take_raw(2);
+        let low = u16::from_be_bytes([low[0], low[1]]);
+
+        Ok(Self {
+            range_thresholds: RangeThresholds {
+                high: Length::from_millimeters(high as f64),
+                low: Length::from_millimeters(low as f64),
+            },
+            range_intermeasurement_period: reader.take()?,
+            range_max_convergence_time: reader.take()?,
+            range_crosstalk_compensation_rate: reader.take()?,
+            range_crosstalk_valid_height: reader.take()?,
+            range_early_convergence_estimate: reader.take()?,
+            range_check_enables: reader.take()?,
+            range_vhv_repeat_rate: reader.take()?,
+            als_thresholds: reader.take()?,
+            als_intermeasurement_period: reader.take()?,
+            als_analogue_gain: reader.take()?,
+            als_integration_period: reader.take()?,
+            interrupt_config: reader.take()?,
+            gpio0: reader.take()?,
+            gpio1: reader.take()?,
+        })
+    }
+}
+
+/// Sequential reader/writer over a fixed-size blob
+///
+/// Callers bound the buffer to [`FullConfig::ENCODED_LEN`] up front, so the
+/// slicing below cannot go out of range.
+struct Cursor<B> {
+    buf: B,
+    pos: usize,
+}
+
+impl Cursor<&mut [u8]> {
+    fn put_raw(&mut self, bytes: &[u8]) {
+        self.buf[self.pos..self.pos + bytes.len()].copy_from_slice(bytes);
+        self.pos += bytes.len();
+    }
+
+    fn put<R: ToByteArray>(&mut self, register: R) -> Result<(), ConfigFormatError> {
+        let bytes = register
+            .to_bytes()
+            .map_err(|_| ConfigFormatError::InvalidField)?;
+        self.put_raw(bytes.as_ref());
+        Ok(())
+    }
+}
+
+impl<'a> Cursor<&'a [u8]> {
+    fn take_raw(&mut self, len: usize) -> &'a [u8] {
+        let bytes = &self.buf[self.pos..self.pos + len];
+        self.pos += len;
+        bytes
+    }
+
+    fn take<R: FromByteArray>(&mut self) -> Result<R, ConfigFormatError> {
+        let mut array = R::Array::new();
+        let len = array.as_ref().len();
+        array.as_mut().copy_from_slice(self.take_raw(len));
+        R::from_bytes(array).map_err(|_| ConfigFormatError::InvalidField)
+    }
+}
+
+/// Encodes a threshold distance as whole millimeters
+fn millimeters(length: Length) -> Result<[u8; 2], ConfigFormatError> {
+    let mm = length.as_millimeters();
+    if !(0.0..=u16::MAX as f64).contains(&mm) {
+        return Err(ConfigFormatError::InvalidField);
+    }
+    Ok((mm as u16).to_be_bytes())
+}
+
+/// CRC-16/CCITT-FALSE (polynomial 0x1021, initial value 0xFFFF)
+pub(crate) fn crc16(bytes: &[u8]) -> u16 {
+    let mut crc = 0xFFFFu16;
+    for &byte in bytes {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
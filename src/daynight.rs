@@ -0,0 +1,270 @@
+//! Day and night switching
+//!
+//! Backlights and display themes commonly follow the ambient light: dim at
+//! night, bright by day. [`DayNightSwitch`] decides between the two from ALS
+//! readings with a hysteresis band around the threshold and a minimum dwell
+//! time, so neither a slowly fading dusk nor a passing shadow makes it flap.
+//!
+//! The switch can be fed ALS readings, or driven from the interrupt path:
+//! program a level interrupt with [`Device::configure_day_night`] for the
+//! [`side`](DayNightSwitch::side) the light is on, and on every ALS interrupt
+//! feed the new side with [`DayNightSwitch::feed_side`] and program the
+//! interrupt again. The interrupt only triggers once the light crosses the
+//! whole band, so between changes the sensor runs without any bus traffic;
+//! call [`update`](DayNightSwitch::update) from a timer to accept a change
+//! once its dwell time has passed.
+
+use crate::device::{Device, DeviceAddress};
+use crate::registers::{AlsAnalogueGain, AlsIntegrationPeriod};
+use crate::types::{AlsInterrupt, AlsReading, Error, Luminance};
+
+/// Lighting condition reported by a [`DayNightSwitch`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DayNight {
+    /// The light is above the band
+    Day,
+    /// The light is below the band
+    Night,
+}
+
+impl DayNight {
+    /// Returns the opposite condition.
+    pub fn flipped(self) -> Self {
+        match self {
+            Self::Day => Self::Night,
+            Self::Night => Self::Day,
+        }
+    }
+}
+
+/// Threshold, hysteresis and dwell time of a [`DayNightSwitch`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DayNightConfig {
+    /// Center of the band
+    pub threshold: Luminance,
+    /// Half width of the band: the light is on the day side above
+    /// `threshold + hysteresis` and on the night side below
+    /// `threshold - hysteresis`
+    pub hysteresis: Luminance,
+    /// Time in ms the light must stay on the other side before the switch
+    /// follows it
+    pub dwell_ms: u32,
+}
+
+impl DayNightConfig {
+    /// Light level above which the light is on the day side
+    pub fn day_level(&self) -> Luminance {
+        Luminance::from_lux(self.threshold.lux + self.hysteresis.lux)
+    }
+
+    /// Light level below which the light is on the night side
+    pub fn night_level(&self) -> Luminance {
+        Luminance::from_lux(self.threshold.lux - self.hysteresis.lux)
+    }
+}
+
+impl Default for DayNightConfig {
+    /// Indoor lighting: switching around 50 lux with a 20 lux hysteresis
+    /// and a 5s dwell time
+    fn default() -> Self {
+        Self {
+            threshold: Luminance::from_lux(50.0),
+            hysteresis: Luminance::from_lux(20.0),
+            dwell_ms: 5000,
+        }
+    }
+}
+
+/// Debounced day and night decision
+///
+/// The side of the band the light was last seen on changes only when the
+/// light crosses the whole band; readings within the band keep it. A change
+/// of side is only accepted once it has lasted the dwell time; shorter
+/// excursions are discarded.
+///
+/// The caller supplies a millisecond tick with every input. The tick may wrap
+/// around. Between inputs, call [`update`](DayNightSwitch::update) to accept
+/// a pending change once its dwell time has passed; with ALS readings
+/// arriving continuously this happens on its own.
+///
+/// # Example
+/// ```
+/// use vl6180x::daynight::{DayNight, DayNightConfig, DayNightSwitch};
+/// use vl6180x::{AlsReading, Luminance};
+///
+/// let mut switch = DayNightSwitch::new(
+///     DayNightConfig {
+///         threshold: Luminance::from_lux(50.0),
+///         hysteresis: Luminance::from_lux(10.0),
+///         dwell_ms: 1000,
+///     },
+///     DayNight::Day,
+/// );
+/// let at = |lux| AlsReading::Valid(Luminance::from_lux(lux));
+///
+/// // Dusk: within the band nothing happens, below it the dwell time starts
+/// assert_eq!(switch.feed(0, at(45.0)), None);
+/// assert_eq!(switch.feed(500, at(35.0)), None);
+/// assert_eq!(switch.feed(1000, at(42.0)), None);
+/// assert_eq!(switch.feed(1500, at(30.0)), Some(DayNight::Night));
+/// ```
+#[derive(Debug, Clone)]
+pub struct DayNightSwitch {
+    config: DayNightConfig,
+    state: DayNight,
+    side: DayNight,
+    pending: Option<u32>,
+}
+
+impl DayNightSwitch {
+    /// Creates a switch in `initial` state, with the light on that side.
+    pub fn new(config: DayNightConfig, initial: DayNight) -> Self {
+        Self {
+            config,
+            state: initial,
+            side: initial,
+            pending: None,
+        }
+    }
+
+    /// Returns the parameters of the switch.
+    pub fn config(&self) -> DayNightConfig {
+        self.config
+    }
+
+    /// Returns the debounced condition.
+    pub fn state(&self) -> DayNight {
+        self.state
+    }
+
+    /// Returns the side of the band the light was last seen on.
+    pub fn side(&self) -> DayNight {
+        self.side
+    }
+
+    /// Processes an ALS reading.
+    ///
+    /// Valid readings beyond the band and saturated readings put the light
+    /// on the day side, valid readings below the band and dark readings on
+    /// the night side. Readings within the band keep the side, and failed
+    /// readings are skipped.
+    ///
+    /// Returns the new debounced condition if it changed.
+    pub fn feed(&mut self, tick_ms: u32, reading: AlsReading) -> Option<DayNight> {
+        let side = match reading {
+            AlsReading::Valid(light) if light.lux > self.config.day_level().lux => DayNight::Day,
+            AlsReading::Valid(light) if light.lux < self.config.night_level().lux => {
+                DayNight::Night
+            }
+            AlsReading::Saturated => DayNight::Day,
+            AlsReading::Dark => DayNight::Night,
+            AlsReading::Valid(_) => self.side,
+            AlsReading::Failed(_) => return self.update(tick_ms),
+        };
+        self.feed_side(tick_ms, side)
+    }
+
+    /// Processes the side of the band the light is on, e.g. derived from a
+    /// threshold interrupt.
+    ///
+    /// Returns the new debounced condition if it changed.
+    pub fn feed_side(&mut self, tick_ms: u32, side: DayNight) -> Option<DayNight> {
+        self.side = side;
+        if side == self.state {
+            self.pending = None;
+            return None;
+        }
+        if self.pending.is_none() {
+            self.pending = Some(tick_ms);
+        }
+        self.update(tick_ms)
+    }
+
+    /// Accepts a pending change once it has lasted the dwell time.
+    ///
+    /// Returns the new debounced condition if it changed.
+    pub fn update(&mut self, tick_ms: u32) -> Option<DayNight> {
+        let since = self.pending?;
+        if tick_ms.wrapping_sub(since) < self.config.dwell_ms {
+            return None;
+        }
+
+        self.pending = None;
+        self.state = self.side;
+        Some(self.state)
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Programs an ALS level interrupt triggering when the light leaves `side`.
+    ///
+    /// On the day side the interrupt triggers below
+    /// [`night_level`](DayNightConfig::night_level), on the night side above
+    /// [`day_level`](DayNightConfig::day_level). The device compares raw ALS
+    /// counts, so the levels are converted with the ALS gain and integration
+    /// period configured at the time; call again after changing them. Route
+    /// the interrupt to a pin with
+    /// [`configure_gpio1_interrupt`](Device::configure_gpio1_interrupt) or
+    /// poll it, and start continuous ALS measurements.
+    ///
+    /// # Arguments
+    /// * `config` - Band of the switch
+    /// * `side` - Side of the band the light is on, see
+    ///   [`DayNightSwitch::side`]
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn configure_day_night(
+        &mut self,
+        config: &DayNightConfig,
+        side: DayNight,
+    ) -> Result<(), Error> {
+        let gain = self.read_register()?;
+        let integration = self.read_register()?;
+        self.set_als_interrupt(day_night_interrupt(config, side, gain, integration))
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously programs an ALS level interrupt triggering when the light leaves `side`.
+    ///
+    /// This is the async version of [`configure_day_night`](Device::configure_day_night).
+    pub async fn configure_day_night_async(
+        &mut self,
+        config: &DayNightConfig,
+        side: DayNight,
+    ) -> Result<(), Error> {
+        let gain = self.read_register_async().await?;
+        let integration = self.read_register_async().await?;
+        self.set_als_interrupt_async(day_night_interrupt(config, side, gain, integration))
+            .await
+    }
+}
+
+/// Level interrupt, in raw counts, triggering when the light leaves `side`
+fn day_night_interrupt(
+    config: &DayNightConfig,
+    side: DayNight,
+    gain: AlsAnalogueGain,
+    integration: AlsIntegrationPeriod,
+) -> AlsInterrupt {
+    let counts = |level: Luminance| {
+        Luminance::from_lux(f32::from(level.to_counts(gain.gain, integration.period)))
+    };
+    match side {
+        DayNight::Day => AlsInterrupt::LevelLow {
+            low: counts(config.night_level()),
+        },
+        DayNight::Night => AlsInterrupt::LevelHigh {
+            high: counts(config.day_level()),
+        },
+    }
+}
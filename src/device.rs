@@ -3,11 +3,83 @@
 //! This module provides the main interface for interacting with VL6180X devices
 //! through I2C communication. It supports both blocking and asynchronous operations.
 
-use regiface::{errors::Error as RegifaceError, ByteArray, ReadableRegister, WritableRegister};
+use regiface::{ByteArray, ReadableRegister};
+
+use crate::clock::ClockRef;
+use crate::registers::DatasheetLimits;
+use crate::types::{Access, AdaptiveTiming, Error, SpuriousInterruptPolicy, Timeouts};
+
+mod adaptive;
+mod address;
+mod als;
+mod batch;
+mod boot;
+mod busy;
+mod cache;
+mod check;
+mod combined;
+mod continuous;
+mod duty;
+mod guard;
+mod health;
+mod interleaved;
+mod interrupt;
+mod many;
+mod mode;
+#[cfg(feature = "nb")]
+mod nonblocking;
+mod paranoid;
+mod parts;
+mod period;
+mod probe;
+mod profile;
+mod range;
+mod recovery;
+mod scan;
+mod selftest;
+mod settings;
+mod split;
+mod stats;
+mod timestamp;
+mod wake;
+mod watch;
+mod wire;
+
+pub use address::{Address, DeviceAddress, FixedAddress, RuntimeAddress};
+#[cfg(feature = "pololu-compat")]
+pub(crate) use als::als_result;
+pub use check::{HealthReport, HealthVerdict};
+pub use guard::{ContinuousAlsGuard, ContinuousGuard};
+#[cfg(feature = "stats")]
+pub use health::HealthStats;
+pub use many::RegisterTuple;
+pub use mode::{
+    Continuous, ContinuousAls, ContinuousRanging, Idle, Mode, TransitionError, TypedDevice,
+};
+pub use parts::DeviceState;
+pub(crate) use period::{HOLD, RELEASE};
+pub use profile::Profile;
+#[cfg(feature = "pololu-compat")]
+pub(crate) use range::range_result;
+pub use scan::{scan_for_vl6180x, scan_for_vl6180x_async};
+pub use selftest::{SelfTestCheck, SelfTestReport, SELF_TEST_RANGES};
+pub use split::{ConfigHandle, ResultReader};
+#[cfg(feature = "bus-stats")]
+pub use stats::BusStats;
+pub use watch::Watch;
 
 /// Default I2C address for the VL6180X (7-bit)
 pub const DEFAULT_ADDRESS: u8 = 0x29;
 
+/// Interval between status polls in the measurement helpers (in microseconds)
+const POLL_INTERVAL_US: u32 = 1_000;
+
+/// Number of status polls that fit in `timeout`, at least one
+pub(crate) fn poll_limit(timeout: core::time::Duration) -> u32 {
+    let polls = timeout.as_micros() / POLL_INTERVAL_US as u128;
+    polls.clamp(1, u32::MAX as u128) as u32
+}
+
 /// Main device interface for the VL6180X sensor.
 ///
 /// This struct wraps an I2C interface and provides methods to interact with the sensor.
@@ -15,9 +87,66 @@ pub const DEFAULT_ADDRESS: u8 = 0x29;
 /// asynchronous operations through embedded-hal-async.
 ///
 /// The VL6180X uses 16-bit register addresses.
-pub struct Device<I2C> {
+///
+/// # Sharing the bus
+///
+/// Every register read or write is issued as a single I2C transaction, so the
+/// device works over shared-bus wrappers such as `embedded-hal-bus`'s
+/// `RefCellDevice` or `CriticalSectionDevice` (or `embassy-embedded-hal`'s
+/// `I2cDevice` for async). Multi-step helpers, such as the measurement
+/// helpers, are sequences of such transactions and never rely on holding the
+/// bus between them: other peripherals may be accessed in between without
+/// corrupting the sequence.
+///
+/// ```no_run
+/// use core::cell::RefCell;
+/// use embedded_hal::i2c::I2c;
+/// use embedded_hal_bus::i2c::RefCellDevice;
+/// use vl6180x::{registers::ModelId, Device};
+///
+/// fn shared<I2C: I2c>(i2c: I2C) -> Result<(), vl6180x::Error> {
+///     let bus = RefCell::new(i2c);
+///
+///     let mut sensor = Device::new(RefCellDevice::new(&bus));
+///     let mut imu = RefCellDevice::new(&bus);
+///
+///     let _model_id: ModelId = sensor.read_register()?;
+///     imu.write(0x68, &[0x6B, 0x00]).ok();
+///     let _model_id: ModelId = sensor.read_register()?;
+///
+///     Ok(())
+/// }
+/// ```
+///
+/// # Compile-time address
+///
+/// The address is normally chosen at runtime with
+/// [`new_with_address`](Device::new_with_address). A sensor whose address is
+/// known when the firmware is built can carry it in its type instead, see
+/// [`StaticDevice`]. Both forms have the same methods; only the constructors
+/// differ.
+pub struct Device<I2C, A = RuntimeAddress> {
     i2c: I2C,
-    address: u8,
+    address: A,
+    #[cfg(feature = "bus-stats")]
+    stats: BusStats,
+    #[cfg(feature = "stats")]
+    health: HealthStats,
+    last_drop_error: Option<Error>,
+    strict: bool,
+    busy_check: bool,
+    paranoid: bool,
+    spurious_policy: SpuriousInterruptPolicy,
+    cache: cache::ConfigCache,
+    timeouts: Timeouts,
+    adaptive_timing: Option<AdaptiveTiming>,
+    clock: Option<ClockRef>,
+    bus_recovery: Option<recovery::RecoveryHook>,
+    last_sample_time: Option<u64>,
+    operation: Option<wire::OperationProgress>,
+    in_flight: continuous::InFlight,
+    profiles: &'static [Profile],
+    active_profile: Option<profile::ActiveProfile>,
 }
 
 impl<I2C> Device<I2C> {
@@ -33,20 +162,127 @@ impl<I2C> Device<I2C> {
     ///
     /// # Arguments
     /// * `i2c` - An I2C interface implementing the required embedded-hal traits
-    /// * `address` - Custom 7-bit I2C address
-    pub fn new_with_address(i2c: I2C, address: u8) -> Self {
-        Self { i2c, address }
+    /// * `address` - Custom I2C address, a `u8` being taken as 7-bit, see
+    ///   [`Address`]
+    pub fn new_with_address(i2c: I2C, address: impl Into<Address>) -> Self {
+        Self::with_address(i2c, RuntimeAddress(address.into().get()))
+    }
+}
+
+/// [`Device`] with its I2C address fixed at compile time
+///
+/// Holds no address at runtime and shares every method with the runtime
+/// address form, which stays the default.
+///
+/// # Example
+/// ```no_run
+/// use embedded_hal::i2c::I2c;
+/// use vl6180x::device::StaticDevice;
+/// use vl6180x::registers::ModelId;
+///
+/// fn model_id<I2C: I2c>(i2c: I2C) -> Result<ModelId, vl6180x::Error> {
+///     let mut sensor = StaticDevice::<_, 0x30>::new_static(i2c);
+///     sensor.read_register()
+/// }
+/// ```
+pub type StaticDevice<I2C, const ADDR: u8 = DEFAULT_ADDRESS> = Device<I2C, FixedAddress<ADDR>>;
+
+impl<I2C, const ADDR: u8> Device<I2C, FixedAddress<ADDR>> {
+    /// Creates a new Device instance at the address `ADDR`.
+    ///
+    /// # Arguments
+    /// * `i2c` - An I2C interface implementing the required embedded-hal traits
+    pub fn new_static(i2c: I2C) -> Self {
+        Self::with_address(i2c, FixedAddress)
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A> {
+    /// Creates a device with default settings at `address`
+    fn with_address(i2c: I2C, address: A) -> Self {
+        Self {
+            i2c,
+            address,
+            #[cfg(feature = "bus-stats")]
+            stats: BusStats::default(),
+            #[cfg(feature = "stats")]
+            health: HealthStats::default(),
+            last_drop_error: None,
+            strict: false,
+            busy_check: false,
+            paranoid: false,
+            spurious_policy: SpuriousInterruptPolicy::Report,
+            cache: cache::ConfigCache::default(),
+            timeouts: Timeouts::default(),
+            adaptive_timing: None,
+            clock: None,
+            bus_recovery: None,
+            last_sample_time: None,
+            operation: None,
+            in_flight: continuous::InFlight::default(),
+            profiles: &[],
+            active_profile: None,
+        }
+    }
+
+    /// Sets the timeouts used by the wait and poll helpers.
+    ///
+    /// Measurement helpers wait up to `timeouts.range` or `timeouts.als` for
+    /// a sample, and for the sensor to stop continuous measurements.
+    /// [`wait_for_boot`](Device::wait_for_boot) and
+    /// [`wait_until_ready`](Device::wait_until_ready) use them when called
+    /// without a timeout. Defaults to [`Timeouts::default`].
+    pub fn set_timeouts(&mut self, timeouts: Timeouts) {
+        self.timeouts = timeouts;
+    }
+
+    /// Returns the timeouts used by the wait and poll helpers, see
+    /// [`set_timeouts`](Device::set_timeouts).
+    pub fn timeouts(&self) -> Timeouts {
+        self.timeouts
+    }
+
+    /// Enables or disables strict mode.
+    ///
+    /// In strict mode every register write first checks the value against
+    /// the field ranges the datasheet documents for the register, see
+    /// [`DatasheetLimits`], and fails with `Error::OutOfSpec` instead of
+    /// writing a value outside them. Otherwise writes only fail for values
+    /// the register cannot encode at all. Strict mode is off by default.
+    ///
+    /// Raw [`write_block`](Device::write_block) writes are never checked.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Returns whether strict mode is enabled, see [`set_strict`](Device::set_strict).
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Checks a register value against its datasheet limits in strict mode
+    fn check_limits<R: DatasheetLimits>(&self, register: &R) -> Result<(), Error> {
+        if self.strict {
+            register.check_limits().map_err(Error::OutOfSpec)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the 7-bit I2C address of the device.
+    pub fn address(&self) -> u8 {
+        self.address.get()
     }
 
     /// Releases the underlying I2C device.
     ///
     /// This method consumes the Device instance and returns the wrapped I2C interface.
+    /// Use [`into_parts`](Device::into_parts) to keep the address and settings.
     pub fn release(self) -> I2C {
         self.i2c
     }
 }
 
-impl<I2C> Device<I2C>
+impl<I2C, A: DeviceAddress> Device<I2C, A>
 where
     I2C: embedded_hal::i2c::I2c,
 {
@@ -56,97 +292,357 @@ where
     /// * `R` - Register type implementing ReadableRegister with u16 ID
     ///
     /// # Errors
-    /// * `RegifaceError::BusError` - I2C communication failed
-    /// * `RegifaceError::DeserializationError` - Failed to parse register value
-    pub fn read_register<R>(&mut self) -> Result<R, RegifaceError>
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::DeserializationError` - Failed to parse register value
+    /// * `Error::InconsistentRead` - Result reads disagreed, in paranoid mode
+    ///   only, see [`set_paranoid`](Device::set_paranoid)
+    ///
+    /// A few slow-changing configuration registers are served from a cache
+    /// after their first access, see [`invalidate_cache`](Device::invalidate_cache).
+    pub fn read_register<R>(&mut self) -> Result<R, Error>
     where
         R: ReadableRegister<IdType = u16>,
     {
-        let reg_addr = R::id().to_be_bytes();
         let mut buf = R::Array::new();
+        if !self.cache.load(R::id(), buf.as_mut()) {
+            self.read_register_into::<R>(&mut buf)?;
+        }
+        wire::decode(buf)
+    }
 
-        self.i2c
-            .write_read(self.address, &reg_addr, buf.as_mut())
-            .map_err(|_| RegifaceError::BusError)?;
+    /// Reads a register value, treating a NACK as the device being absent.
+    ///
+    /// Returns `Ok(None)` if the device did not acknowledge its address, which
+    /// happens while it is held in reset or still booting.
+    fn probe_register<R>(&mut self) -> Result<Option<R>, Error>
+    where
+        R: ReadableRegister<IdType = u16>,
+    {
+        let mut buf = R::Array::new();
+        let result = self.i2c.write_read(
+            self.address.get(),
+            &wire::address_bytes(R::id()),
+            buf.as_mut(),
+        );
+        self.finish_probe(buf, result)
+    }
+
+    /// Reads a register's raw bytes into a caller-provided buffer.
+    ///
+    /// The bus transaction reads directly into `buf` and the bytes are not
+    /// decoded; call [`FromByteArray::from_bytes`](regiface::FromByteArray::from_bytes)
+    /// on them later to obtain the register value.
+    ///
+    /// # Type Parameters
+    /// * `R` - Register type implementing ReadableRegister with u16 ID
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::InconsistentRead` - Result reads disagreed, in paranoid mode
+    ///   only, see [`set_paranoid`](Device::set_paranoid)
+    pub fn read_register_into<R>(&mut self, buf: &mut R::Array) -> Result<(), Error>
+    where
+        R: ReadableRegister<IdType = u16>,
+    {
+        self.read_into(R::id(), Access::Register, buf.as_mut())?;
+        self.verify_read::<R>(buf)?;
+        self.cache.observe(R::id(), buf.as_ref());
+        Ok(())
+    }
+
+    /// Reads `buf.len()` bytes starting at a register address.
+    ///
+    /// Issues a single I2C transaction that reads directly into `buf`.
+    ///
+    /// # Arguments
+    /// * `address` - 16-bit register address to start reading from
+    /// * `buf` - Buffer receiving the bytes
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn read_raw_into(&mut self, address: u16, buf: &mut [u8]) -> Result<(), Error> {
+        self.read_into(address, Access::Raw, buf)
+    }
+
+    /// Reads `buf.len()` bytes starting at `address`, reporting failures as `access`
+    fn read_into(
+        &mut self,
+        address: u16,
+        access: fn(u16) -> Access,
+        buf: &mut [u8],
+    ) -> Result<(), Error> {
+        let mut retries = 0;
+        loop {
+            let result =
+                self.i2c
+                    .write_read(self.address.get(), &wire::address_bytes(address), buf);
+            self.record_read(buf.len(), result.is_ok());
+            if !self.retry_after(&result, &mut retries) {
+                return self.finish_read(access(address), result);
+            }
+        }
+    }
 
-        R::from_bytes(buf).map_err(|_| RegifaceError::DeserializationError)
+    /// Reads `N` contiguous bytes starting at a register address.
+    ///
+    /// The device auto-increments the register address during a read, so the
+    /// whole block is fetched with a single I2C transaction. This is faster
+    /// than reading the registers one by one and guarantees the values belong
+    /// to the same sample. Decoded views of the commonly used blocks are
+    /// available as [`IdentificationBlock`](crate::registers::IdentificationBlock),
+    /// [`RangeStatusBlock`](crate::registers::RangeStatusBlock),
+    /// [`AlsResultBlock`](crate::registers::AlsResultBlock),
+    /// [`HistoryBuffer`](crate::registers::HistoryBuffer) and
+    /// [`RangeResultBlock`](crate::registers::RangeResultBlock) through
+    /// [`read_register`](Device::read_register).
+    ///
+    /// # Arguments
+    /// * `start` - 16-bit register address of the first byte
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn read_block<const N: usize>(&mut self, start: u16) -> Result<[u8; N], Error> {
+        let mut buf = [0u8; N];
+        self.read_raw_into(start, &mut buf)?;
+        Ok(buf)
     }
 
     /// Writes a value to a device register.
     ///
     /// # Type Parameters
-    /// * `R` - Register type implementing WritableRegister with u16 ID
+    /// * `R` - Register type implementing [`DatasheetLimits`]
     ///
     /// # Arguments
     /// * `register` - The register value to write
     ///
     /// # Errors
-    /// * `RegifaceError::BusError` - I2C communication failed
-    /// * `RegifaceError::SerializationError` - Failed to serialize register value
-    pub fn write_register<R>(&mut self, register: R) -> Result<(), RegifaceError>
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::SerializationError` - Failed to serialize register value
+    /// * `Error::OutOfSpec` - A field is outside its datasheet range, in
+    ///   strict mode only
+    /// * `Error::DeviceBusy` - A measurement the register must not be written
+    ///   during is running, with the busy check enabled only
+    pub fn write_register<R>(&mut self, register: R) -> Result<(), Error>
     where
-        R: WritableRegister<IdType = u16>,
+        R: DatasheetLimits,
     {
-        let reg_addr = R::id().to_be_bytes();
-        let value = register
-            .to_bytes()
-            .map_err(|_| RegifaceError::SerializationError)?;
+        self.check_limits(&register)?;
+        self.check_idle::<R>()?;
+        let value = wire::encode(register)?;
+        self.write_bytes(R::id(), Access::Register, value.as_ref())?;
+        self.cache.observe(R::id(), value.as_ref());
+        Ok(())
+    }
 
-        self.i2c
-            .transaction(
-                self.address,
-                &mut [
-                    embedded_hal::i2c::Operation::Write(&reg_addr),
-                    embedded_hal::i2c::Operation::Write(value.as_ref()),
-                ],
-            )
-            .map_err(|_| RegifaceError::BusError)
+    /// Reads a register, modifies it and writes it back.
+    ///
+    /// Fields of the register that `f` leaves alone keep their current value
+    /// on the device. The read and the write are separate transactions.
+    ///
+    /// # Arguments
+    /// * `f` - Modifies the current register value
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::DeserializationError` - Failed to parse register value
+    /// * `Error::SerializationError` - Failed to serialize register value
+    /// * `Error::OutOfSpec` - A field is outside its datasheet range, in
+    ///   strict mode only
+    /// * `Error::DeviceBusy` - A measurement the register must not be written
+    ///   during is running, with the busy check enabled only
+    pub fn modify_register<R, F>(&mut self, f: F) -> Result<(), Error>
+    where
+        R: ReadableRegister<IdType = u16> + DatasheetLimits,
+        F: FnOnce(&mut R),
+    {
+        let mut register: R = self.read_register()?;
+        f(&mut register);
+        self.write_register(register)
+    }
+
+    /// Writes contiguous bytes starting at a register address.
+    ///
+    /// The device auto-increments the register address during a write, so
+    /// adjacent registers can be configured with a single I2C transaction
+    /// carrying the start address followed by `data`.
+    ///
+    /// # Arguments
+    /// * `start` - 16-bit register address of the first byte
+    /// * `data` - Bytes to write
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn write_block(&mut self, start: u16, data: &[u8]) -> Result<(), Error> {
+        self.cache.clear();
+        self.write_bytes(start, Access::Raw, data)
+    }
+
+    /// Writes `data` starting at `start`, reporting failures as `access`
+    fn write_bytes(
+        &mut self,
+        start: u16,
+        access: fn(u16) -> Access,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        let reg_addr = wire::address_bytes(start);
+        let mut retries = 0;
+        loop {
+            let result = self.i2c.transaction(
+                self.address.get(),
+                &mut wire::write_operations(&reg_addr, data),
+            );
+            self.record_write(data.len(), result.is_ok());
+            if !self.retry_after(&result, &mut retries) {
+                return self.finish_write(access(start), result);
+            }
+        }
     }
 }
 
-impl<I2C> Device<I2C>
+impl<I2C, A: DeviceAddress> Device<I2C, A>
 where
     I2C: embedded_hal_async::i2c::I2c,
 {
     /// Asynchronously reads a register value from the device.
     ///
     /// This is the async version of [`read_register`](Device::read_register).
-    pub async fn read_register_async<R>(&mut self) -> Result<R, RegifaceError>
+    pub async fn read_register_async<R>(&mut self) -> Result<R, Error>
     where
         R: ReadableRegister<IdType = u16>,
     {
-        let reg_addr = R::id().to_be_bytes();
         let mut buf = R::Array::new();
+        if !self.cache.load(R::id(), buf.as_mut()) {
+            self.read_register_into_async::<R>(&mut buf).await?;
+        }
+        wire::decode(buf)
+    }
 
-        self.i2c
-            .write_read(self.address, &reg_addr, buf.as_mut())
-            .await
-            .map_err(|_| RegifaceError::BusError)?;
+    /// Asynchronously reads a register value, treating a NACK as the device being absent.
+    ///
+    /// This is the async version of [`probe_register`](Device::probe_register).
+    async fn probe_register_async<R>(&mut self) -> Result<Option<R>, Error>
+    where
+        R: ReadableRegister<IdType = u16>,
+    {
+        let mut buf = R::Array::new();
+        let result = self
+            .i2c
+            .write_read(
+                self.address.get(),
+                &wire::address_bytes(R::id()),
+                buf.as_mut(),
+            )
+            .await;
+        self.finish_probe(buf, result)
+    }
+
+    /// Asynchronously reads a register's raw bytes into a caller-provided buffer.
+    ///
+    /// This is the async version of [`read_register_into`](Device::read_register_into).
+    /// With DMA-capable HALs the transfer lands directly in `buf`.
+    pub async fn read_register_into_async<R>(&mut self, buf: &mut R::Array) -> Result<(), Error>
+    where
+        R: ReadableRegister<IdType = u16>,
+    {
+        self.read_into_async(R::id(), Access::Register, buf.as_mut())
+            .await?;
+        self.verify_read_async::<R>(buf).await?;
+        self.cache.observe(R::id(), buf.as_ref());
+        Ok(())
+    }
+
+    /// Asynchronously reads `buf.len()` bytes starting at a register address.
+    ///
+    /// This is the async version of [`read_raw_into`](Device::read_raw_into).
+    pub async fn read_raw_into_async(&mut self, address: u16, buf: &mut [u8]) -> Result<(), Error> {
+        self.read_into_async(address, Access::Raw, buf).await
+    }
+
+    async fn read_into_async(
+        &mut self,
+        address: u16,
+        access: fn(u16) -> Access,
+        buf: &mut [u8],
+    ) -> Result<(), Error> {
+        let mut retries = 0;
+        loop {
+            let result = self
+                .i2c
+                .write_read(self.address.get(), &wire::address_bytes(address), buf)
+                .await;
+            self.record_read(buf.len(), result.is_ok());
+            if !self.retry_after(&result, &mut retries) {
+                return self.finish_read(access(address), result);
+            }
+        }
+    }
 
-        R::from_bytes(buf).map_err(|_| RegifaceError::DeserializationError)
+    /// Asynchronously reads `N` contiguous bytes starting at a register address.
+    ///
+    /// This is the async version of [`read_block`](Device::read_block).
+    pub async fn read_block_async<const N: usize>(&mut self, start: u16) -> Result<[u8; N], Error> {
+        let mut buf = [0u8; N];
+        self.read_raw_into_async(start, &mut buf).await?;
+        Ok(buf)
     }
 
     /// Asynchronously writes a value to a device register.
     ///
     /// This is the async version of [`write_register`](Device::write_register).
-    pub async fn write_register_async<R>(&mut self, register: R) -> Result<(), RegifaceError>
+    pub async fn write_register_async<R>(&mut self, register: R) -> Result<(), Error>
     where
-        R: WritableRegister<IdType = u16>,
+        R: DatasheetLimits,
     {
-        let reg_addr = R::id().to_be_bytes();
-        let value = register
-            .to_bytes()
-            .map_err(|_| RegifaceError::SerializationError)?;
+        self.check_limits(&register)?;
+        self.check_idle_async::<R>().await?;
+        let value = wire::encode(register)?;
+        self.write_bytes_async(R::id(), Access::Register, value.as_ref())
+            .await?;
+        self.cache.observe(R::id(), value.as_ref());
+        Ok(())
+    }
 
-        self.i2c
-            .transaction(
-                self.address,
-                &mut [
-                    embedded_hal_async::i2c::Operation::Write(&reg_addr),
-                    embedded_hal_async::i2c::Operation::Write(value.as_ref()),
-                ],
-            )
-            .await
-            .map_err(|_| RegifaceError::BusError)
+    /// Asynchronously reads a register, modifies it and writes it back.
+    ///
+    /// This is the async version of [`modify_register`](Device::modify_register).
+    pub async fn modify_register_async<R, F>(&mut self, f: F) -> Result<(), Error>
+    where
+        R: ReadableRegister<IdType = u16> + DatasheetLimits,
+        F: FnOnce(&mut R),
+    {
+        let mut register: R = self.read_register_async().await?;
+        f(&mut register);
+        self.write_register_async(register).await
+    }
+
+    /// Asynchronously writes contiguous bytes starting at a register address.
+    ///
+    /// This is the async version of [`write_block`](Device::write_block).
+    pub async fn write_block_async(&mut self, start: u16, data: &[u8]) -> Result<(), Error> {
+        self.cache.clear();
+        self.write_bytes_async(start, Access::Raw, data).await
+    }
+
+    async fn write_bytes_async(
+        &mut self,
+        start: u16,
+        access: fn(u16) -> Access,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        let reg_addr = wire::address_bytes(start);
+        let mut retries = 0;
+        loop {
+            let result = self
+                .i2c
+                .transaction(
+                    self.address.get(),
+                    &mut wire::write_operations(&reg_addr, data),
+                )
+                .await;
+            self.record_write(data.len(), result.is_ok());
+            if !self.retry_after(&result, &mut retries) {
+                return self.finish_write(access(start), result);
+            }
+        }
     }
 }
@@ -0,0 +1,110 @@
+//! Adaptive convergence time
+//!
+//! Trades ranging reliability in bright ambient light against power in the
+//! dark by adjusting the convergence time limit after every measurement.
+
+use super::{Device, DeviceAddress};
+use crate::registers::{
+    RangeIntermeasurementPeriod, RangeMaxConvergenceTime, RangeResultBlock, RangeResultStatus,
+};
+use crate::types::{AdaptiveTiming, Error};
+
+impl<I2C, A: DeviceAddress> Device<I2C, A> {
+    /// Adapts the range convergence time limit after every single-shot
+    /// measurement.
+    ///
+    /// Once enabled, the measurement helpers built on single-shot ranging,
+    /// such as [`read_range`](Device::read_range) and
+    /// [`measure_range_single`](Device::measure_range_single), read the
+    /// diagnostics of every completed measurement and move
+    /// [`RangeMaxConvergenceTime`] as described in [`AdaptiveTiming`]. The new
+    /// limit is written inside a grouped parameter hold, and only if a
+    /// measurement with it still fits in the configured intermeasurement
+    /// period, so continuous ranging started later remains valid.
+    ///
+    /// Costs two I2C transactions per measurement, plus four when the limit
+    /// changes.
+    ///
+    /// # Arguments
+    /// * `policy` - Bounds and thresholds of the adjustment
+    ///
+    /// # Panics
+    /// If `policy.min_convergence` is greater than `policy.max_convergence`.
+    pub fn enable_adaptive_timing(&mut self, policy: AdaptiveTiming) {
+        assert!(
+            policy.min_convergence <= policy.max_convergence,
+            "minimum convergence time above the maximum"
+        );
+        self.adaptive_timing = Some(policy);
+    }
+
+    /// Stops adapting the convergence time limit, leaving it at its current value.
+    pub fn disable_adaptive_timing(&mut self) {
+        self.adaptive_timing = None;
+    }
+
+    /// Returns the adaptive timing policy, if enabled.
+    pub fn adaptive_timing(&self) -> Option<AdaptiveTiming> {
+        self.adaptive_timing
+    }
+}
+
+/// Returns the convergence limit register to write, if any
+fn adjusted(
+    policy: &AdaptiveTiming,
+    status: RangeResultStatus,
+    result: &RangeResultBlock,
+    limit: RangeMaxConvergenceTime,
+) -> Option<RangeMaxConvergenceTime> {
+    let time = policy.next_convergence(limit.time, status.error_code, result);
+    (time != limit.time).then_some(RangeMaxConvergenceTime { time })
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Applies the adaptive timing policy after a completed measurement
+    pub(super) fn adapt_timing(&mut self, status: RangeResultStatus) -> Result<(), Error> {
+        let Some(policy) = self.adaptive_timing else {
+            return Ok(());
+        };
+        let result: RangeResultBlock = self.read_register()?;
+        let limit: RangeMaxConvergenceTime = self.read_register()?;
+        let Some(register) = adjusted(&policy, status, &result, limit) else {
+            return Ok(());
+        };
+
+        let period: RangeIntermeasurementPeriod = self.read_register()?;
+        if register.measurement_time() > period.period {
+            return Ok(());
+        }
+        self.write_held(register)
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Async version of `adapt_timing`
+    pub(super) async fn adapt_timing_async(
+        &mut self,
+        status: RangeResultStatus,
+    ) -> Result<(), Error> {
+        let Some(policy) = self.adaptive_timing else {
+            return Ok(());
+        };
+        let result: RangeResultBlock = self.read_register_async().await?;
+        let limit: RangeMaxConvergenceTime = self.read_register_async().await?;
+        let Some(register) = adjusted(&policy, status, &result, limit) else {
+            return Ok(());
+        };
+
+        let period: RangeIntermeasurementPeriod = self.read_register_async().await?;
+        if register.measurement_time() > period.period {
+            return Ok(());
+        }
+        self.write_held_async(register).await
+    }
+}
@@ -0,0 +1,141 @@
+//! Runtime and compile-time I2C addresses
+//!
+//! [`Device`](super::Device) is generic over where its address lives: in a
+//! field set at construction, or in a const parameter of the type. Both
+//! forms share every method; only the constructors differ.
+//!
+//! Datasheets and vendor code often quote the 8-bit form of an address, the
+//! 7-bit address shifted left with the R/W bit appended: the VL6180X's 0x29
+//! becomes 0x52 to write and 0x53 to read. [`Address`] makes the notation
+//! explicit.
+
+use core::fmt;
+
+use super::DEFAULT_ADDRESS;
+
+/// 7-bit I2C address of a device
+///
+/// Plain `u8` values convert as 7-bit addresses, so the constructors taking
+/// `impl Into<Address>` still accept `0x29`. Use
+/// [`from_8bit`](Address::from_8bit) for an address quoted in 8-bit notation.
+///
+/// # Example
+/// ```
+/// use vl6180x::device::Address;
+///
+/// assert_eq!(Address::from_8bit(0x52), Address::DEFAULT);
+/// assert_eq!(Address::from_8bit(0x53), Address::from(0x29));
+/// assert_eq!(Address::from_7bit(0x80), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Address(u8);
+
+impl Address {
+    /// Address of a VL6180X out of reset (0x29)
+    pub const DEFAULT: Self = Self(DEFAULT_ADDRESS);
+
+    /// Address from its 7-bit form, or `None` above 0x7F
+    pub const fn from_7bit(address: u8) -> Option<Self> {
+        if address <= 0x7F {
+            Some(Self(address))
+        } else {
+            None
+        }
+    }
+
+    /// Address from its 8-bit form, with the R/W bit either clear (write
+    /// address) or set (read address)
+    pub const fn from_8bit(address: u8) -> Self {
+        Self(address >> 1)
+    }
+
+    /// Returns the 7-bit address.
+    pub const fn get(self) -> u8 {
+        self.0
+    }
+
+    /// Returns the 8-bit write address, with the R/W bit clear.
+    pub const fn write_8bit(self) -> u8 {
+        self.0 << 1
+    }
+
+    /// Returns the 8-bit read address, with the R/W bit set.
+    pub const fn read_8bit(self) -> u8 {
+        self.0 << 1 | 1
+    }
+
+    /// Returns whether this looks like the default address in 8-bit
+    /// notation, 0x52 or 0x53, passed as a 7-bit address.
+    pub const fn looks_8bit(self) -> bool {
+        self.0 >> 1 == DEFAULT_ADDRESS
+    }
+}
+
+impl Default for Address {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Takes `address` as a 7-bit address.
+impl From<u8> for Address {
+    fn from(address: u8) -> Self {
+        Self(address)
+    }
+}
+
+impl From<Address> for u8 {
+    fn from(address: Address) -> Self {
+        address.0
+    }
+}
+
+/// Shows both notations, e.g. `0x29 (8-bit 0x52/0x53)`.
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "0x{:02X} (8-bit 0x{:02X}/0x{:02X})",
+            self.0,
+            self.write_8bit(),
+            self.read_8bit()
+        )
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Source of a device's 7-bit I2C address
+///
+/// Implemented by [`RuntimeAddress`] and [`FixedAddress`] only.
+pub trait DeviceAddress: sealed::Sealed + Copy {
+    /// Returns the 7-bit I2C address
+    fn get(self) -> u8;
+}
+
+/// Address chosen at runtime, the default for [`Device`](super::Device)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RuntimeAddress(pub(super) u8);
+
+impl sealed::Sealed for RuntimeAddress {}
+
+impl DeviceAddress for RuntimeAddress {
+    fn get(self) -> u8 {
+        self.0
+    }
+}
+
+/// Address fixed at compile time, stored in the type instead of the device
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FixedAddress<const ADDR: u8 = DEFAULT_ADDRESS>;
+
+impl<const ADDR: u8> sealed::Sealed for FixedAddress<ADDR> {}
+
+impl<const ADDR: u8> DeviceAddress for FixedAddress<ADDR> {
+    fn get(self) -> u8 {
+        ADDR
+    }
+}
@@ -0,0 +1,406 @@
+//! ALS measurement helpers
+//!
+//! High-level wrappers around the SYSALS start/poll/read/clear sequence.
+
+use core::time::Duration;
+
+use super::{health::Measurement, poll_limit, Device, DeviceAddress, POLL_INTERVAL_US};
+use crate::registers::{
+    AlsAnalogueGain, AlsIntegrationPeriod, AlsResultBlock, AlsResultValue, AlsStart,
+    InterruptClear, ResultAlsStatus, ResultInterruptStatusGpio,
+};
+use crate::types::{AlsErrorCode, AlsGain, AlsReading, Error, GainFit, Luminance};
+
+/// Interrupt clear value acknowledging an ALS sample
+pub(super) const CLEAR_ALS: InterruptClear = InterruptClear {
+    clear_range: false,
+    clear_als: true,
+    clear_error: false,
+};
+
+/// Status and value of a completed ALS measurement, with the gain and
+/// integration period it was measured with
+pub(crate) type AlsSample = (
+    ResultAlsStatus,
+    AlsResultValue,
+    AlsAnalogueGain,
+    AlsIntegrationPeriod,
+);
+
+/// Converts a completed ALS sample into a light level
+pub(crate) fn als_result(
+    (status, value, gain, integration): AlsSample,
+) -> Result<Luminance, Error> {
+    match status.error_code {
+        AlsErrorCode::NoError => Ok(Luminance::from_counts(
+            value.raw_count,
+            gain.gain,
+            integration.period,
+        )),
+        code => Err(Error::AlsError(code)),
+    }
+}
+
+/// Gain selection and the registers applying it together with `integration`
+fn als_configuration(
+    max_lux: Luminance,
+    integration: Duration,
+) -> (GainFit, AlsAnalogueGain, AlsIntegrationPeriod) {
+    let fit = AlsGain::for_max_lux(max_lux, integration);
+    (
+        fit,
+        AlsAnalogueGain { gain: fit.gain() },
+        AlsIntegrationPeriod {
+            period: integration,
+        },
+    )
+}
+
+/// Classifies a completed ALS sample
+pub(super) fn als_reading((status, value, gain, integration): AlsSample) -> AlsReading {
+    AlsReading::new(
+        status.error_code,
+        value.raw_count,
+        gain.gain,
+        integration.period,
+    )
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A> {
+    /// Number of status polls that fit in the ALS timeout
+    fn als_poll_limit(&self) -> u32 {
+        poll_limit(self.timeouts.als)
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Performs a single-shot ALS measurement.
+    ///
+    /// Starts a measurement, polls the interrupt status until a new ALS sample
+    /// is reported, reads the result and clears the ALS interrupt. The raw count
+    /// is converted to lux using the currently configured gain and integration
+    /// period. The ALS interrupt must be configured for
+    /// [`InterruptMode::NewSampleReady`](crate::types::InterruptMode::NewSampleReady).
+    ///
+    /// Costs five I2C transactions plus one per status poll, three once the
+    /// gain and integration period are cached, see
+    /// [`invalidate_cache`](Device::invalidate_cache).
+    ///
+    /// # Errors
+    /// * `Error::Timeout` - No sample was reported within the ALS timeout,
+    ///   see [`set_timeouts`](Device::set_timeouts)
+    /// * `Error::AlsError` - The measurement completed with an error code
+    /// * `Error::DeviceBusy` - Continuous ALS measurements were started with
+    ///   [`start_continuous_als`](Device::start_continuous_als), or a
+    ///   measurement started by `try_read_als` was not collected yet
+    pub fn measure_als_single<D>(&mut self, delay: &mut D) -> Result<Luminance, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        let result = self.als_single(delay).and_then(als_result);
+        self.record_measurement(Measurement::Als, &result);
+        result
+    }
+
+    /// Performs a single-shot ALS measurement with its own timeout.
+    ///
+    /// Like [`measure_als_single`](Device::measure_als_single), but waits up
+    /// to `timeout` for the sample instead of the configured ALS timeout.
+    ///
+    /// # Arguments
+    /// * `delay` - Delay provider used between polls
+    /// * `timeout` - Maximum time to wait for the sample
+    ///
+    /// # Errors
+    /// * `Error::Timeout` - No sample was reported within `timeout`
+    /// * `Error::AlsError` - The measurement completed with an error code
+    /// * `Error::DeviceBusy` - Continuous ALS measurements were started with
+    ///   [`start_continuous_als`](Device::start_continuous_als), or a
+    ///   measurement started by `try_read_als` was not collected yet
+    pub fn measure_als_single_within<D>(
+        &mut self,
+        delay: &mut D,
+        timeout: Duration,
+    ) -> Result<Luminance, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        let result = self
+            .als_single_within(delay, Some(poll_limit(timeout)))
+            .and_then(als_result);
+        self.record_measurement(Measurement::Als, &result);
+        result
+    }
+
+    /// Performs a single-shot ALS measurement and classifies its outcome.
+    ///
+    /// Like [`measure_als_single`](Device::measure_als_single), but a completed
+    /// measurement is never an error: overflow, including a count stuck at
+    /// full scale, is reported as [`AlsReading::Saturated`] and underflow as
+    /// [`AlsReading::Dark`].
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::Timeout` - No sample was reported within the polling budget
+    /// * `Error::DeviceBusy` - Continuous ALS measurements were started with
+    ///   [`start_continuous_als`](Device::start_continuous_als), or a
+    ///   measurement started by `try_read_als` was not collected yet
+    pub fn read_als<D>(&mut self, delay: &mut D) -> Result<AlsReading, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        let result = self.als_single(delay);
+        self.record_measurement(Measurement::Als, &result.and_then(als_result));
+        result.map(als_reading)
+    }
+
+    /// Configures gain and integration period for an expected maximum light level.
+    ///
+    /// Writes the gain chosen by [`AlsGain::for_max_lux`] together with
+    /// `integration`, and returns the selection so a
+    /// [`GainFit::Saturates`] can be acted on.
+    ///
+    /// # Arguments
+    /// * `max_lux` - Brightest light level the ALS should measure
+    /// * `integration` - Integration period (1ms to 512ms)
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::SerializationError` - `integration` is out of range
+    pub fn configure_als_for(
+        &mut self,
+        max_lux: Luminance,
+        integration: Duration,
+    ) -> Result<GainFit, Error> {
+        let (fit, gain, integration) = als_configuration(max_lux, integration);
+        self.write_register(integration)?;
+        self.write_register(gain)?;
+        Ok(fit)
+    }
+
+    fn als_single<D>(&mut self, delay: &mut D) -> Result<AlsSample, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        self.als_single_within(delay, Some(self.als_poll_limit()))
+    }
+
+    /// Performs a single-shot ALS measurement with a custom polling budget
+    ///
+    /// `poll_limit` of `None` polls until a sample is reported.
+    pub(crate) fn als_single_within<D>(
+        &mut self,
+        delay: &mut D,
+        poll_limit: Option<u32>,
+    ) -> Result<AlsSample, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        self.refuse_during_continuous_als()?;
+        self.refuse_during_pending_als()?;
+        self.write_register(AlsStart::SingleShot)?;
+        self.wait_als_sample_within(delay, poll_limit)
+    }
+
+    /// Polls for the next ALS sample and converts it into a light level
+    pub(super) fn wait_als<D>(&mut self, delay: &mut D) -> Result<Luminance, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        self.wait_als_sample(delay).and_then(als_result)
+    }
+
+    /// Polls for the next ALS sample, reads it and clears its interrupt
+    fn wait_als_sample<D>(&mut self, delay: &mut D) -> Result<AlsSample, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        self.wait_als_sample_within(delay, Some(self.als_poll_limit()))
+    }
+
+    fn wait_als_sample_within<D>(
+        &mut self,
+        delay: &mut D,
+        poll_limit: Option<u32>,
+    ) -> Result<AlsSample, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        let mut polls = 0;
+        loop {
+            let status: ResultInterruptStatusGpio = self.read_register()?;
+            if status.als_interrupt {
+                self.mark_sample();
+                break;
+            }
+
+            polls += 1;
+            if poll_limit.is_some_and(|limit| polls >= limit) {
+                return Err(Error::Timeout);
+            }
+            delay.delay_us(POLL_INTERVAL_US);
+        }
+
+        let block: AlsResultBlock = self.read_register()?;
+        self.write_register(CLEAR_ALS)?;
+
+        let gain: AlsAnalogueGain = self.read_register()?;
+        let integration: AlsIntegrationPeriod = self.read_register()?;
+
+        Ok((block.status, block.value, gain, integration))
+    }
+
+    /// Waits for the ALS core to become ready after a stop
+    pub(super) fn wait_als_core_ready<D>(&mut self, delay: &mut D) -> Result<(), Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        let limit = self.als_poll_limit();
+        let mut polls = 0;
+        loop {
+            let status: ResultAlsStatus = self.read_register()?;
+            if status.device_ready {
+                return Ok(());
+            }
+
+            polls += 1;
+            if polls >= limit {
+                return Err(Error::Timeout);
+            }
+            delay.delay_us(POLL_INTERVAL_US);
+        }
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously configures gain and integration period for an expected maximum light level.
+    ///
+    /// This is the async version of [`configure_als_for`](Device::configure_als_for).
+    pub async fn configure_als_for_async(
+        &mut self,
+        max_lux: Luminance,
+        integration: Duration,
+    ) -> Result<GainFit, Error> {
+        let (fit, gain, integration) = als_configuration(max_lux, integration);
+        self.write_register_async(integration).await?;
+        self.write_register_async(gain).await?;
+        Ok(fit)
+    }
+
+    /// Asynchronously performs a single-shot ALS measurement.
+    ///
+    /// This is the async version of [`measure_als_single`](Device::measure_als_single).
+    pub async fn measure_als_single_async<D>(&mut self, delay: &mut D) -> Result<Luminance, Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        let result = self.als_single_async(delay).await.and_then(als_result);
+        self.record_measurement(Measurement::Als, &result);
+        result
+    }
+
+    /// Asynchronously performs a single-shot ALS measurement with its own timeout.
+    ///
+    /// This is the async version of [`measure_als_single_within`](Device::measure_als_single_within).
+    pub async fn measure_als_single_within_async<D>(
+        &mut self,
+        delay: &mut D,
+        timeout: Duration,
+    ) -> Result<Luminance, Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        let result = self
+            .als_single_within_async(delay, poll_limit(timeout))
+            .await
+            .and_then(als_result);
+        self.record_measurement(Measurement::Als, &result);
+        result
+    }
+
+    /// Asynchronously performs a single-shot ALS measurement and classifies its outcome.
+    ///
+    /// This is the async version of [`read_als`](Device::read_als).
+    pub async fn read_als_async<D>(&mut self, delay: &mut D) -> Result<AlsReading, Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        let result = self.als_single_async(delay).await;
+        self.record_measurement(Measurement::Als, &result.and_then(als_result));
+        result.map(als_reading)
+    }
+
+    async fn als_single_async<D>(&mut self, delay: &mut D) -> Result<AlsSample, Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        self.als_single_within_async(delay, self.als_poll_limit())
+            .await
+    }
+
+    /// Async version of `als_single_within`, always bounded
+    async fn als_single_within_async<D>(
+        &mut self,
+        delay: &mut D,
+        limit: u32,
+    ) -> Result<AlsSample, Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        self.refuse_during_continuous_als()?;
+        self.refuse_during_pending_als()?;
+        self.write_register_async(AlsStart::SingleShot).await?;
+
+        let mut polls = 0;
+        loop {
+            let status: ResultInterruptStatusGpio = self.read_register_async().await?;
+            if status.als_interrupt {
+                self.mark_sample();
+                break;
+            }
+
+            polls += 1;
+            if polls >= limit {
+                return Err(Error::Timeout);
+            }
+            delay.delay_us(POLL_INTERVAL_US).await;
+        }
+
+        let block: AlsResultBlock = self.read_register_async().await?;
+        self.write_register_async(CLEAR_ALS).await?;
+
+        let gain: AlsAnalogueGain = self.read_register_async().await?;
+        let integration: AlsIntegrationPeriod = self.read_register_async().await?;
+
+        Ok((block.status, block.value, gain, integration))
+    }
+
+    /// Asynchronously waits for the ALS core to become ready after a stop.
+    ///
+    /// This is the async version of [`wait_als_core_ready`](Device::wait_als_core_ready).
+    pub(super) async fn wait_als_core_ready_async<D>(&mut self, delay: &mut D) -> Result<(), Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        let limit = self.als_poll_limit();
+        let mut polls = 0;
+        loop {
+            let status: ResultAlsStatus = self.read_register_async().await?;
+            if status.device_ready {
+                return Ok(());
+            }
+
+            polls += 1;
+            if polls >= limit {
+                return Err(Error::Timeout);
+            }
+            delay.delay_us(POLL_INTERVAL_US).await;
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Adjacent typed register writes in one transaction
+//!
+//! The device auto-increments the register address during a write, so
+//! helpers writing neighbouring registers send them as one block, the same
+//! way [`Device::write_block`] does, while keeping the strict mode, busy
+//! and cache handling of [`Device::write_register`].
+
+use regiface::ByteArray;
+
+use super::{wire, Device, DeviceAddress};
+use crate::registers::DatasheetLimits;
+use crate::types::{Access, Error};
+
+/// Most bytes a batch holds
+const MAX_BYTES: usize = 8;
+
+/// Most registers a batch holds
+const MAX_REGISTERS: usize = 4;
+
+/// Typed register writes waiting to be sent as one block
+#[derive(Debug, Default)]
+pub(super) struct Batch {
+    start: u16,
+    bytes: [u8; MAX_BYTES],
+    len: usize,
+    /// Address and width of every register in the block
+    registers: [(u16, usize); MAX_REGISTERS],
+    count: usize,
+}
+
+impl Batch {
+    /// Returns whether `width` bytes at `id` can be appended to the block
+    fn extends(&self, id: u16, width: usize) -> bool {
+        self.len == 0
+            || usize::from(self.start) + self.len == usize::from(id)
+                && self.len + width <= MAX_BYTES
+                && self.count < MAX_REGISTERS
+    }
+
+    fn push(&mut self, id: u16, value: &[u8]) {
+        if self.len == 0 {
+            self.start = id;
+        }
+        self.bytes[self.len..self.len + value.len()].copy_from_slice(value);
+        self.len += value.len();
+        self.registers[self.count] = (id, value.len());
+        self.count += 1;
+    }
+
+    fn clear(&mut self) {
+        self.len = 0;
+        self.count = 0;
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A> {
+    /// Records the registers of a written block in the configuration cache
+    fn observe_batch(&mut self, batch: &mut Batch) {
+        let mut offset = 0;
+        for &(id, width) in &batch.registers[..batch.count] {
+            self.cache.observe(id, &batch.bytes[offset..offset + width]);
+            offset += width;
+        }
+        batch.clear();
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Checks `register` like [`write_register`](Device::write_register) and
+    /// queues it, first writing the batch if `register` does not follow it
+    pub(super) fn batch_register<R>(&mut self, batch: &mut Batch, register: R) -> Result<(), Error>
+    where
+        R: DatasheetLimits,
+    {
+        self.check_limits(&register)?;
+        self.check_idle::<R>()?;
+        let value = wire::encode(register)?;
+        if !batch.extends(R::id(), value.as_ref().len()) {
+            self.write_batch(batch)?;
+        }
+        batch.push(R::id(), value.as_ref());
+        Ok(())
+    }
+
+    /// Writes the queued registers in one transaction
+    pub(super) fn write_batch(&mut self, batch: &mut Batch) -> Result<(), Error> {
+        if batch.len == 0 {
+            return Ok(());
+        }
+        self.write_bytes(batch.start, Access::Register, &batch.bytes[..batch.len])?;
+        self.observe_batch(batch);
+        Ok(())
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Async version of `batch_register`
+    pub(super) async fn batch_register_async<R>(
+        &mut self,
+        batch: &mut Batch,
+        register: R,
+    ) -> Result<(), Error>
+    where
+        R: DatasheetLimits,
+    {
+        self.check_limits(&register)?;
+        self.check_idle_async::<R>().await?;
+        let value = wire::encode(register)?;
+        if !batch.extends(R::id(), value.as_ref().len()) {
+            self.write_batch_async(batch).await?;
+        }
+        batch.push(R::id(), value.as_ref());
+        Ok(())
+    }
+
+    /// Async version of `write_batch`
+    pub(super) async fn write_batch_async(&mut self, batch: &mut Batch) -> Result<(), Error> {
+        if batch.len == 0 {
+            return Ok(());
+        }
+        self.write_bytes_async(batch.start, Access::Register, &batch.bytes[..batch.len])
+            .await?;
+        self.observe_batch(batch);
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+//! Power-up helpers
+//!
+//! Waiting for the firmware to finish booting after power-up or after XSHUT
+//! is released.
+
+use core::time::Duration;
+
+use super::{Device, DeviceAddress, POLL_INTERVAL_US};
+use crate::registers::FirmwareBootup;
+use crate::types::Error;
+
+/// Time between boot flag polls
+const BOOT_POLL_INTERVAL: Duration = Duration::from_micros(POLL_INTERVAL_US as u64);
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Waits for the firmware to report a completed boot.
+    ///
+    /// Polls `FIRMWARE__BOOTUP` until the boot flag is set, tolerating NACKs
+    /// while the device is still starting up. Use this instead of a fixed
+    /// delay after power-up or after releasing XSHUT.
+    ///
+    /// Returns the time spent waiting, measured in poll intervals.
+    ///
+    /// # Arguments
+    /// * `delay` - Delay provider used between polls
+    /// * `timeout` - Maximum time to wait, or `None` for the configured boot
+    ///   timeout, see [`set_timeouts`](Device::set_timeouts)
+    ///
+    /// # Errors
+    /// * `Error::Timeout` - The boot flag was not set within the timeout
+    /// * `Error::BusError` - I2C communication failed with an error other than a NACK
+    pub fn wait_for_boot<D, T>(&mut self, delay: &mut D, timeout: T) -> Result<Duration, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+        T: Into<Option<Duration>>,
+    {
+        self.cache.clear();
+        let timeout = timeout.into().unwrap_or(self.timeouts.boot);
+        let mut elapsed = Duration::ZERO;
+        loop {
+            if let Some(FirmwareBootup { booted: true }) = self.probe_register()? {
+                return Ok(elapsed);
+            }
+
+            if elapsed >= timeout {
+                return Err(Error::Timeout);
+            }
+            delay.delay_us(POLL_INTERVAL_US);
+            elapsed += BOOT_POLL_INTERVAL;
+        }
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously waits for the firmware to report a completed boot.
+    ///
+    /// This is the async version of [`wait_for_boot`](Device::wait_for_boot).
+    pub async fn wait_for_boot_async<D, T>(
+        &mut self,
+        delay: &mut D,
+        timeout: T,
+    ) -> Result<Duration, Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+        T: Into<Option<Duration>>,
+    {
+        self.cache.clear();
+        let timeout = timeout.into().unwrap_or(self.timeouts.boot);
+        let mut elapsed = Duration::ZERO;
+        loop {
+            if let Some(FirmwareBootup { booted: true }) = self.probe_register_async().await? {
+                return Ok(elapsed);
+            }
+
+            if elapsed >= timeout {
+                return Err(Error::Timeout);
+            }
+            delay.delay_us(POLL_INTERVAL_US).await;
+            elapsed += BOOT_POLL_INTERVAL;
+        }
+    }
+}
@@ -0,0 +1,179 @@
+//! Busy check
+//!
+//! Refusing configuration writes while a measurement is running, and waiting
+//! for running measurements to finish.
+
+use core::time::Duration;
+
+use super::{Device, DeviceAddress, POLL_INTERVAL_US};
+use crate::registers::{BusyPolicy, DatasheetLimits};
+use crate::types::Error;
+
+/// Address of RESULT__RANGE_STATUS, directly followed by RESULT__ALS_STATUS
+const STATUS_START: u16 = 0x004D;
+
+/// Device ready bit of both status registers
+const DEVICE_READY: u8 = 0x01;
+
+/// Time between device ready polls
+const READY_POLL_INTERVAL: Duration = Duration::from_micros(POLL_INTERVAL_US as u64);
+
+/// Measurements in progress, decoded from the two status registers
+#[derive(Debug, Clone, Copy)]
+struct Running {
+    range: bool,
+    als: bool,
+}
+
+impl Running {
+    fn from_status([range, als]: [u8; 2]) -> Self {
+        Self {
+            range: range & DEVICE_READY == 0,
+            als: als & DEVICE_READY == 0,
+        }
+    }
+
+    fn any(self) -> bool {
+        self.range || self.als
+    }
+
+    fn blocks(self, policy: BusyPolicy) -> bool {
+        (self.range && policy.needs_range_idle()) || (self.als && policy.needs_als_idle())
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A> {
+    /// Enables or disables the busy check.
+    ///
+    /// The datasheet does not support changing the configuration of a running
+    /// measurement. With the busy check enabled, writing a configuration
+    /// register first reads the device ready bits of `RESULT__RANGE_STATUS`
+    /// and `RESULT__ALS_STATUS`, and fails with `Error::DeviceBusy` instead of
+    /// writing while a measurement the register affects is running, see
+    /// [`BusyPolicy`]. Start commands, interrupt clears and the other
+    /// registers that are safe to write at any time are written without the
+    /// extra read, and reads are never checked. The busy check is off by
+    /// default.
+    ///
+    /// To wait for running measurements to finish instead, call
+    /// [`wait_until_ready`](Device::wait_until_ready) before the write.
+    pub fn set_busy_check(&mut self, enabled: bool) {
+        self.busy_check = enabled;
+    }
+
+    /// Returns whether the busy check is enabled, see
+    /// [`set_busy_check`](Device::set_busy_check).
+    pub fn busy_check_enabled(&self) -> bool {
+        self.busy_check
+    }
+
+    /// Default timeout of waits that may be ended by either sensor
+    pub(super) fn ready_timeout(&self) -> Duration {
+        self.timeouts.range.max(self.timeouts.als)
+    }
+
+    /// Returns whether the busy check has to read the status before writing `R`
+    fn needs_idle_check<R: DatasheetLimits>(&self) -> bool {
+        self.busy_check && R::BUSY_POLICY != BusyPolicy::Anytime
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Waits until neither sensor is running a measurement.
+    ///
+    /// Polls the device ready bits of `RESULT__RANGE_STATUS` and
+    /// `RESULT__ALS_STATUS` until both are set. Returns the time spent
+    /// waiting, measured in poll intervals.
+    ///
+    /// # Arguments
+    /// * `delay` - Delay provider used between polls
+    /// * `timeout` - Maximum time to wait, or `None` for the longer of the
+    ///   configured range and ALS timeouts, see [`set_timeouts`](Device::set_timeouts)
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::Timeout` - A measurement was still running after the timeout
+    pub fn wait_until_ready<D, T>(&mut self, delay: &mut D, timeout: T) -> Result<Duration, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+        T: Into<Option<Duration>>,
+    {
+        let timeout = timeout.into().unwrap_or(self.ready_timeout());
+        let mut elapsed = Duration::ZERO;
+        loop {
+            if !Running::from_status(self.read_block(STATUS_START)?).any() {
+                return Ok(elapsed);
+            }
+
+            if elapsed >= timeout {
+                return Err(Error::Timeout);
+            }
+            delay.delay_us(POLL_INTERVAL_US);
+            elapsed += READY_POLL_INTERVAL;
+        }
+    }
+
+    /// Fails with `Error::DeviceBusy` if the busy check is enabled and a
+    /// measurement blocking writes to `R` is running
+    pub(super) fn check_idle<R: DatasheetLimits>(&mut self) -> Result<(), Error> {
+        if !self.needs_idle_check::<R>() {
+            return Ok(());
+        }
+
+        let running = Running::from_status(self.read_block(STATUS_START)?);
+        if running.blocks(R::BUSY_POLICY) {
+            Err(Error::DeviceBusy)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously waits until neither sensor is running a measurement.
+    ///
+    /// This is the async version of [`wait_until_ready`](Device::wait_until_ready).
+    pub async fn wait_until_ready_async<D, T>(
+        &mut self,
+        delay: &mut D,
+        timeout: T,
+    ) -> Result<Duration, Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+        T: Into<Option<Duration>>,
+    {
+        let timeout = timeout.into().unwrap_or(self.ready_timeout());
+        let mut elapsed = Duration::ZERO;
+        loop {
+            if !Running::from_status(self.read_block_async(STATUS_START).await?).any() {
+                return Ok(elapsed);
+            }
+
+            if elapsed >= timeout {
+                return Err(Error::Timeout);
+            }
+            delay.delay_us(POLL_INTERVAL_US).await;
+            elapsed += READY_POLL_INTERVAL;
+        }
+    }
+
+    /// Async version of `check_idle`
+    pub(super) async fn check_idle_async<R: DatasheetLimits>(&mut self) -> Result<(), Error> {
+        if !self.needs_idle_check::<R>() {
+            return Ok(());
+        }
+
+        let running = Running::from_status(self.read_block_async(STATUS_START).await?);
+        if running.blocks(R::BUSY_POLICY) {
+            Err(Error::DeviceBusy)
+        } else {
+            Ok(())
+        }
+    }
+}
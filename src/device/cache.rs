@@ -0,0 +1,112 @@
+//! Cache of slow-changing configuration registers
+//!
+//! Converting an ALS count into lux needs the ALS gain and integration
+//! period, and every interrupt helper modifies the interrupt configuration.
+//! These registers only change when written, so the typed reads serve them
+//! from a cache once they have been read or written through the typed API.
+
+use regiface::Register;
+
+use super::{Device, DeviceAddress};
+use crate::registers::{
+    AlsAnalogueGain, AlsIntegrationPeriod, FreshOutOfReset, InterruptConfigGpio,
+};
+
+/// Widest cached register in bytes
+const MAX_WIDTH: usize = 2;
+
+/// Last known contents of the cached registers
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct ConfigCache {
+    entries: [Option<[u8; MAX_WIDTH]>; 3],
+}
+
+/// Addresses and widths of the cached registers, by slot
+fn cached() -> [(u16, usize); 3] {
+    [
+        (InterruptConfigGpio::id(), 1),
+        (AlsAnalogueGain::id(), 1),
+        (AlsIntegrationPeriod::id(), 2),
+    ]
+}
+
+/// Slot and width of the register at `address`, if it is cached
+fn slot(address: u16) -> Option<(usize, usize)> {
+    cached()
+        .iter()
+        .enumerate()
+        .find_map(|(slot, &(id, width))| (id == address).then_some((slot, width)))
+}
+
+/// Returns whether `len` bytes at `address` touch the `width` bytes at `id`
+fn overlaps(address: u16, len: usize, id: u16, width: usize) -> bool {
+    let (start, end) = (usize::from(address), usize::from(address) + len);
+    let id = usize::from(id);
+    start < id + width && id < end
+}
+
+impl ConfigCache {
+    /// Copies the cached bytes of the register at `address` into `buf`,
+    /// returning whether they were cached
+    pub(super) fn load(&self, address: u16, buf: &mut [u8]) -> bool {
+        match slot(address) {
+            Some((slot, width)) if buf.len() == width => match &self.entries[slot] {
+                Some(bytes) => {
+                    buf.copy_from_slice(&bytes[..width]);
+                    true
+                }
+                None => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Records the bytes of a typed read or write of the register at `address`
+    ///
+    /// Accesses covering only part of a cached register, such as a
+    /// [`RawRegister`](crate::registers::RawRegister) spanning it, forget it.
+    pub(super) fn observe(&mut self, address: u16, bytes: &[u8]) {
+        let fresh = usize::from(FreshOutOfReset::id()).wrapping_sub(usize::from(address));
+        if bytes.get(fresh).is_some_and(|fresh| fresh & 0x01 != 0) {
+            self.clear();
+            return;
+        }
+        for (slot, (id, width)) in cached().into_iter().enumerate() {
+            if id == address && bytes.len() == width {
+                let mut entry = [0; MAX_WIDTH];
+                entry[..width].copy_from_slice(bytes);
+                self.entries[slot] = Some(entry);
+            } else if overlaps(address, bytes.len(), id, width) {
+                self.entries[slot] = None;
+            }
+        }
+    }
+
+    /// Forgets every cached register
+    pub(super) fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A> {
+    /// Forgets the cached configuration registers.
+    ///
+    /// [`read_register`](Device::read_register) serves the interrupt
+    /// configuration, the ALS gain and the ALS integration period from a
+    /// cache once they were read or written through the typed API, which
+    /// saves the measurement and interrupt helpers two or three transactions
+    /// each. The cache follows every change the driver sees:
+    /// * Typed reads and writes update it.
+    /// * Raw [`write_block`](Device::write_block) writes and
+    ///   [`wait_for_boot`](Device::wait_for_boot) clear it.
+    /// * Reading [`FreshOutOfReset`] with the flag set clears it.
+    ///
+    /// Changes it cannot see, such as another bus master writing the
+    /// registers or a reset that was not noticed, leave the cache stale; call
+    /// this after them. The next read of each register goes to the device
+    /// again. [`read_register_into`](Device::read_register_into) always reads
+    /// from the device.
+    pub fn invalidate_cache(&mut self) {
+        self.cache.clear();
+    }
+}
@@ -0,0 +1,130 @@
+//! Device health check
+//!
+//! A single call answering "is the sensor alive and sane?" for watchdog tasks.
+
+use super::{Device, DeviceAddress};
+use crate::registers::{FirmwareBootup, FreshOutOfReset, ModelId, RangeResultStatus};
+use crate::types::Error;
+
+/// Overall outcome of a [`Device::health_check`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HealthVerdict {
+    /// The sensor identified itself, has booted and kept its configuration
+    Healthy,
+    /// The sensor responded but has been reset or has not finished booting,
+    /// so it must be initialized again
+    NeedsReinit,
+    /// Nothing acknowledged the address, or the device at the address is not
+    /// a VL6180X
+    NotResponding,
+}
+
+/// Individual findings of a [`Device::health_check`]
+///
+/// Findings that could not be obtained because the device did not respond
+/// are `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct HealthReport {
+    /// Overall verdict
+    pub verdict: HealthVerdict,
+    /// Model ID reported by the device
+    pub model_id: Option<ModelId>,
+    /// Whether the firmware reports a completed boot
+    pub booted: Option<bool>,
+    /// Whether the device has been reset since the flag was last cleared
+    pub fresh_out_of_reset: Option<bool>,
+    /// Whether the ranging core is ready to start a measurement
+    ///
+    /// Informational only: this is `false` while a measurement is running,
+    /// e.g. in continuous mode, and does not affect the verdict.
+    pub device_ready: Option<bool>,
+}
+
+impl HealthReport {
+    /// Report for a device that did not acknowledge its address
+    const NOT_RESPONDING: Self = Self {
+        verdict: HealthVerdict::NotResponding,
+        model_id: None,
+        booted: None,
+        fresh_out_of_reset: None,
+        device_ready: None,
+    };
+
+    fn new(
+        model_id: ModelId,
+        booted: FirmwareBootup,
+        fresh: FreshOutOfReset,
+        status: RangeResultStatus,
+    ) -> Self {
+        let verdict = if model_id != ModelId::VL6180X {
+            HealthVerdict::NotResponding
+        } else if !booted.booted || fresh.fresh {
+            HealthVerdict::NeedsReinit
+        } else {
+            HealthVerdict::Healthy
+        };
+
+        Self {
+            verdict,
+            model_id: Some(model_id),
+            booted: Some(booted.booted),
+            fresh_out_of_reset: Some(fresh.fresh),
+            device_ready: Some(status.device_ready),
+        }
+    }
+
+    /// Returns whether the verdict is [`HealthVerdict::Healthy`]
+    pub fn is_healthy(&self) -> bool {
+        self.verdict == HealthVerdict::Healthy
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Checks whether the sensor is alive and sane.
+    ///
+    /// Reads the model ID, firmware boot flag, fresh-out-of-reset flag and
+    /// range readiness. A NACK on the first read yields a
+    /// [`HealthVerdict::NotResponding`] report rather than an error. The check
+    /// only reads registers; it does not clear the fresh-out-of-reset flag.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed after the device responded
+    pub fn health_check(&mut self) -> Result<HealthReport, Error> {
+        let Some(model_id) = self.probe_register::<ModelId>()? else {
+            return Ok(HealthReport::NOT_RESPONDING);
+        };
+
+        Ok(HealthReport::new(
+            model_id,
+            self.read_register()?,
+            self.read_register()?,
+            self.read_register()?,
+        ))
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously checks whether the sensor is alive and sane.
+    ///
+    /// This is the async version of [`health_check`](Device::health_check).
+    pub async fn health_check_async(&mut self) -> Result<HealthReport, Error> {
+        let Some(model_id) = self.probe_register_async::<ModelId>().await? else {
+            return Ok(HealthReport::NOT_RESPONDING);
+        };
+
+        Ok(HealthReport::new(
+            model_id,
+            self.read_register_async().await?,
+            self.read_register_async().await?,
+            self.read_register_async().await?,
+        ))
+    }
+}
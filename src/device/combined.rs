@@ -0,0 +1,250 @@
+//! Combined range and ALS measurements
+
+use super::{poll_limit, Device, DeviceAddress, POLL_INTERVAL_US};
+use crate::registers::{
+    AlsIntegrationPeriod, AlsIntermeasurementPeriod, InterruptConfigGpio, RangeMaxConvergenceTime,
+    ReadoutAveraging, ResultInterruptStatusGpio,
+};
+use crate::types::{AlsReading, Error, InterruptMode, PeriodTooShort, RangeReading};
+
+/// Interrupt configuration the single-shot helpers wait on
+const NEW_SAMPLES: InterruptConfigGpio = InterruptConfigGpio {
+    range_interrupt: InterruptMode::NewSampleReady,
+    als_interrupt: InterruptMode::NewSampleReady,
+};
+
+/// Combines the measurements with the outcome of restoring the interrupt
+/// configuration; a measurement error takes precedence
+fn restored<T>(measured: Result<T, Error>, restore: Result<(), Error>) -> Result<T, Error> {
+    let measured = measured?;
+    restore.map(|()| measured)
+}
+
+/// Fails with `Error::PeriodTooShort` unless a range measurement with
+/// `limit` and `averaging` fits between the end of one continuous ALS
+/// measurement and the start of the next, with the datasheet's interleaved
+/// mode margins
+fn check_als_gap(
+    period: AlsIntermeasurementPeriod,
+    integration: AlsIntegrationPeriod,
+    limit: RangeMaxConvergenceTime,
+    averaging: ReadoutAveraging,
+) -> Result<(), Error> {
+    let required =
+        integration.min_intermeasurement_period_with(limit.measurement_time_with(averaging));
+    if required > period.period {
+        return Err(Error::PeriodTooShort(PeriodTooShort {
+            period: period.period,
+            required,
+        }));
+    }
+    Ok(())
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Measures distance and light level in one call.
+    ///
+    /// Runs a single-shot ALS measurement followed by a single-shot range
+    /// measurement, each waiting up to its configured timeout, see
+    /// [`set_timeouts`](Device::set_timeouts). The sensor cannot run both
+    /// single-shot measurements at once, so they take the ALS integration
+    /// period plus the range measurement time.
+    ///
+    /// Both interrupts are switched to
+    /// [`InterruptMode::NewSampleReady`] for the measurements if they are
+    /// configured otherwise, and restored afterwards, also on failure. Only
+    /// the range and ALS interrupts of the two samples are cleared; a pending
+    /// error interrupt stays set.
+    ///
+    /// Costs ten I2C transactions plus one per status poll, and two more if
+    /// the interrupt configuration is switched.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use embedded_hal::{delay::DelayNs, i2c::I2c};
+    /// use vl6180x::{AlsReading, Device, RangeReading};
+    ///
+    /// fn show<I2C: I2c, D: DelayNs>(sensor: &mut Device<I2C>, delay: &mut D) {
+    ///     if let Ok((RangeReading::Valid(distance), AlsReading::Valid(light))) =
+    ///         sensor.measure_both(delay)
+    ///     {
+    ///         let _ = (distance.as_millimeters(), light.lux);
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::Timeout` - A sample was not reported within its timeout
+    pub fn measure_both<D>(&mut self, delay: &mut D) -> Result<(RangeReading, AlsReading), Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        let config: InterruptConfigGpio = self.read_register()?;
+        if config == NEW_SAMPLES {
+            return self.read_als_then_range(delay);
+        }
+
+        self.write_register(NEW_SAMPLES)?;
+        let measured = self.read_als_then_range(delay);
+        let restore = self.write_register(config);
+        restored(measured, restore)
+    }
+
+    fn read_als_then_range<D>(&mut self, delay: &mut D) -> Result<(RangeReading, AlsReading), Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        let als = self.read_als(delay)?;
+        let range = self.read_range(delay)?;
+        Ok((range, als))
+    }
+
+    /// Takes a single-shot range measurement while continuous ALS is running.
+    ///
+    /// The sensor aborts a running measurement when another one is started,
+    /// so the range measurement must fall between two ALS measurements. This
+    /// waits until the running ALS measurement reports its sample, then
+    /// takes the range measurement in the idle time before the next one
+    /// starts, like [`read_range`](Device::read_range). The ALS sample and its
+    /// interrupt are left pending for the application to read, so the ALS
+    /// schedule carries on without losing a sample.
+    ///
+    /// Constraints:
+    /// * Both interrupts must be configured for
+    ///   [`InterruptMode::NewSampleReady`].
+    /// * The ALS intermeasurement period must leave room for one range
+    ///   measurement after the integration period, see
+    ///   [`RangeMaxConvergenceTime::measurement_time_with`] and
+    ///   [`AlsIntegrationPeriod::min_intermeasurement_period`].
+    /// * The previous ALS sample must have been read and its interrupt
+    ///   cleared; otherwise the end of the running ALS measurement cannot be
+    ///   told apart.
+    /// * Only for occasional measurements. For a range measurement after
+    ///   every ALS measurement, enable the interleaved mode instead.
+    ///
+    /// Waits up to the ALS timeout for the ALS sample and up to the range
+    /// timeout for the range sample, see [`set_timeouts`](Device::set_timeouts).
+    /// Costs eight I2C transactions plus one per status poll, seven once the
+    /// integration period is cached.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::PeriodTooShort` - A range measurement does not fit between
+    ///   two ALS measurements
+    /// * `Error::DeviceBusy` - An ALS sample is already pending
+    /// * `Error::Timeout` - A sample was not reported within its timeout
+    pub fn range_single_during_continuous_als<D>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<RangeReading, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        check_als_gap(
+            self.read_register()?,
+            self.read_register()?,
+            self.read_register()?,
+            self.read_register()?,
+        )?;
+
+        let limit = poll_limit(self.timeouts.als);
+        let mut polls = 0;
+        loop {
+            let status: ResultInterruptStatusGpio = self.read_register()?;
+            if status.als_interrupt {
+                if polls == 0 {
+                    return Err(Error::DeviceBusy);
+                }
+                break;
+            }
+
+            polls += 1;
+            if polls >= limit {
+                return Err(Error::Timeout);
+            }
+            delay.delay_us(POLL_INTERVAL_US);
+        }
+
+        self.read_range(delay)
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously measures distance and light level in one call.
+    ///
+    /// This is the async version of [`measure_both`](Device::measure_both).
+    pub async fn measure_both_async<D>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(RangeReading, AlsReading), Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        let config: InterruptConfigGpio = self.read_register_async().await?;
+        if config == NEW_SAMPLES {
+            return self.read_als_then_range_async(delay).await;
+        }
+
+        self.write_register_async(NEW_SAMPLES).await?;
+        let measured = self.read_als_then_range_async(delay).await;
+        let restore = self.write_register_async(config).await;
+        restored(measured, restore)
+    }
+
+    async fn read_als_then_range_async<D>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(RangeReading, AlsReading), Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        let als = self.read_als_async(delay).await?;
+        let range = self.read_range_async(delay).await?;
+        Ok((range, als))
+    }
+
+    /// Asynchronously takes a single-shot range measurement while continuous ALS is running.
+    ///
+    /// This is the async version of [`range_single_during_continuous_als`](Device::range_single_during_continuous_als).
+    pub async fn range_single_during_continuous_als_async<D>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<RangeReading, Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        check_als_gap(
+            self.read_register_async().await?,
+            self.read_register_async().await?,
+            self.read_register_async().await?,
+            self.read_register_async().await?,
+        )?;
+
+        let limit = poll_limit(self.timeouts.als);
+        let mut polls = 0;
+        loop {
+            let status: ResultInterruptStatusGpio = self.read_register_async().await?;
+            if status.als_interrupt {
+                if polls == 0 {
+                    return Err(Error::DeviceBusy);
+                }
+                break;
+            }
+
+            polls += 1;
+            if polls >= limit {
+                return Err(Error::Timeout);
+            }
+            delay.delay_us(POLL_INTERVAL_US).await;
+        }
+
+        self.read_range_async(delay).await
+    }
+}
@@ -0,0 +1,480 @@
+//! Tracked continuous measurements
+//!
+//! Writing the start bit while a continuous measurement runs stops it rather
+//! than starting it again. Every helper of this driver starts and stops
+//! continuous measurements through [`begin_continuous`](Device::begin_continuous)
+//! and [`end_continuous`](Device::end_continuous), which remember what runs,
+//! so that repeated starts and stops cannot toggle the sensor into the
+//! opposite mode.
+
+use core::time::Duration;
+
+use super::als::{als_reading, CLEAR_ALS};
+use super::period::check_period;
+use super::{Device, DeviceAddress};
+use crate::registers::{
+    AlsAnalogueGain, AlsIntegrationPeriod, AlsIntermeasurementPeriod, AlsResultBlock, AlsStart,
+    RangeStart,
+};
+use crate::types::{Error, LatestAls};
+
+/// Measurements started and not collected or stopped yet
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct InFlight {
+    /// Single-shot range measurement started by the non-blocking reader
+    pub(super) range: bool,
+    /// Single-shot ALS measurement started by the non-blocking reader
+    pub(super) als: bool,
+    /// Continuous ranging
+    pub(super) continuous_range: bool,
+    /// Continuous ALS measurements
+    pub(super) continuous_als: bool,
+    /// Interleaved mode started by
+    /// [`start_interleaved`](Device::start_interleaved)
+    pub(super) interleaved: bool,
+    /// An async single-shot range measurement whose sample was not cleared,
+    /// because its future was dropped or failed while waiting
+    pub(super) abandoned_range: bool,
+}
+
+/// Kind of continuous measurement started by a start bit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ContinuousKind {
+    /// Continuous ranging, started by `SYSRANGE__START`
+    Range,
+    /// Continuous ALS measurements, started by `SYSALS__START`
+    Als,
+    /// Interleaved mode, started by `SYSALS__START` with
+    /// `INTERLEAVED_MODE__ENABLE` set
+    Interleaved,
+}
+
+impl InFlight {
+    /// Tracked running state of `kind`
+    fn running(&mut self, kind: ContinuousKind) -> &mut bool {
+        match kind {
+            ContinuousKind::Range => &mut self.continuous_range,
+            ContinuousKind::Als => &mut self.continuous_als,
+            ContinuousKind::Interleaved => &mut self.interleaved,
+        }
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A> {
+    /// Returns whether continuous ranging started by this driver runs.
+    ///
+    /// Covers every helper that starts ranging, such as
+    /// [`start_continuous_range`](Device::start_continuous_range),
+    /// [`continuous_ranging_scope`](Device::continuous_ranging_scope),
+    /// [`into_continuous_range`](Device::into_continuous_range) and
+    /// [`arm_wake_on_approach`](Device::arm_wake_on_approach). Ranging started
+    /// by writing [`RangeStart`] directly is not tracked.
+    pub fn is_continuous_range_running(&self) -> bool {
+        self.in_flight.continuous_range
+    }
+
+    /// Returns whether continuous ALS measurements started by this driver
+    /// run.
+    ///
+    /// As with [`is_continuous_range_running`](Device::is_continuous_range_running),
+    /// measurements started by writing [`AlsStart`] directly are not tracked.
+    pub fn is_continuous_als_running(&self) -> bool {
+        self.in_flight.continuous_als
+    }
+
+    /// Fails with `Error::DeviceBusy` while tracked continuous ranging or
+    /// interleaved mode runs
+    pub(super) fn refuse_during_continuous_range(&self) -> Result<(), Error> {
+        if self.in_flight.continuous_range || self.in_flight.interleaved {
+            Err(Error::DeviceBusy)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Fails with `Error::DeviceBusy` while tracked continuous ALS
+    /// measurements or interleaved mode run
+    pub(super) fn refuse_during_continuous_als(&self) -> Result<(), Error> {
+        if self.in_flight.continuous_als || self.in_flight.interleaved {
+            Err(Error::DeviceBusy)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Fails with `Error::DeviceBusy` while a single-shot range measurement
+    /// started by the non-blocking reader waits to be collected
+    pub(super) fn refuse_during_pending_range(&self) -> Result<(), Error> {
+        if self.in_flight.range {
+            Err(Error::DeviceBusy)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Fails with `Error::DeviceBusy` while a single-shot ALS measurement
+    /// started by the non-blocking reader waits to be collected
+    pub(super) fn refuse_during_pending_als(&self) -> Result<(), Error> {
+        if self.in_flight.als {
+            Err(Error::DeviceBusy)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Starts continuous ranging.
+    ///
+    /// Writes `SYSRANGE__START` once; calling it again while ranging runs
+    /// does nothing instead of stopping it. Until
+    /// [`stop_continuous_range`](Device::stop_continuous_range) is called,
+    /// single-shot range measurements fail with `Error::DeviceBusy`. Samples
+    /// are picked up with [`handle_interrupt`](Device::handle_interrupt), or
+    /// read with [`read_range_quick`](Device::read_range_quick) once
+    /// [`pending_interrupts`](Device::pending_interrupts) reports them.
+    ///
+    /// Costs one I2C transaction, none if ranging already runs.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use embedded_hal::{delay::DelayNs, i2c::I2c};
+    /// use vl6180x::{Device, Error};
+    ///
+    /// fn pause<I2C: I2c, D: DelayNs>(sensor: &mut Device<I2C>, delay: &mut D) -> Result<(), Error> {
+    ///     sensor.start_continuous_range()?;
+    ///     // A second start leaves ranging running
+    ///     sensor.start_continuous_range()?;
+    ///     sensor.stop_continuous_range(delay)
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// * `Error::DeviceBusy` - Interleaved mode runs, see
+    ///   [`start_interleaved`](Device::start_interleaved)
+    /// * `Error::BusError` - I2C communication failed
+    pub fn start_continuous_range(&mut self) -> Result<(), Error> {
+        if self.in_flight.interleaved {
+            return Err(Error::DeviceBusy);
+        }
+        self.begin_continuous(ContinuousKind::Range)
+    }
+
+    /// Stops continuous ranging started with
+    /// [`start_continuous_range`](Device::start_continuous_range).
+    ///
+    /// Writes the start bit again to stop ranging, then polls
+    /// `RESULT__RANGE_STATUS` until the ranging core reports ready, as the
+    /// datasheet prescribes. Does nothing if ranging was not started. The
+    /// range interrupt of the last sample is left pending.
+    ///
+    /// Costs one I2C transaction plus one per status poll, none if ranging
+    /// was not started.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::Timeout` - The ranging core did not become ready within the
+    ///   range timeout; ranging is stopped nonetheless
+    pub fn stop_continuous_range<D>(&mut self, delay: &mut D) -> Result<(), Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        self.halt_continuous_range(delay)
+    }
+
+    /// Starts continuous ALS measurements every `period`.
+    ///
+    /// Validates `period` against the configured integration period, see
+    /// [`AlsIntegrationPeriod::min_intermeasurement_period`], writes it to
+    /// `SYSALS__INTERMEASUREMENT_PERIOD` and starts the measurements. Until
+    /// [`stop_continuous_als`](Device::stop_continuous_als) is called,
+    /// single-shot ALS measurements fail with `Error::DeviceBusy`. Samples
+    /// are read with [`read_latest_als`](Device::read_latest_als).
+    ///
+    /// Costs three I2C transactions, two once the integration period is
+    /// cached.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use core::time::Duration;
+    /// use embedded_hal::{delay::DelayNs, i2c::I2c};
+    /// use vl6180x::{Device, Error};
+    ///
+    /// fn light_log<I2C: I2c, D: DelayNs>(sensor: &mut Device<I2C>, delay: &mut D) -> Result<(), Error> {
+    ///     sensor.start_continuous_als(Duration::from_millis(500))?;
+    ///     for _ in 0..10 {
+    ///         delay.delay_ms(500);
+    ///         let latest = sensor.read_latest_als()?;
+    ///         if latest.new {
+    ///             // log latest.reading
+    ///         }
+    ///     }
+    ///     sensor.stop_continuous_als(delay)
+    /// }
+    /// ```
+    ///
+    /// # Arguments
+    /// * `period` - Time between the starts of two measurements
+    ///
+    /// # Errors
+    /// * `Error::DeviceBusy` - Continuous ALS measurements or interleaved
+    ///   mode already run; stop them first, or change the period with
+    ///   [`update_als_period`](Device::update_als_period)
+    /// * `Error::PeriodTooShort` - `period` is too short for the integration
+    ///   period
+    /// * `Error::SerializationError` - `period` is outside 10ms to 2560ms
+    /// * `Error::BusError` - I2C communication failed
+    pub fn start_continuous_als(&mut self, period: Duration) -> Result<(), Error> {
+        self.refuse_during_continuous_als()?;
+        let integration: AlsIntegrationPeriod = self.read_register()?;
+        let register = check_period(
+            AlsIntermeasurementPeriod { period },
+            period,
+            integration.min_intermeasurement_period(),
+        )?;
+        self.write_register(register)?;
+        self.begin_continuous(ContinuousKind::Als)
+    }
+
+    /// Stops continuous ALS measurements started with
+    /// [`start_continuous_als`](Device::start_continuous_als).
+    ///
+    /// The ALS counterpart of [`stop_continuous_range`](Device::stop_continuous_range):
+    /// writes the start bit again, then polls `RESULT__ALS_STATUS` until the
+    /// ALS core reports ready. Does nothing if the measurements were not
+    /// started.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::Timeout` - The ALS core did not become ready within the ALS
+    ///   timeout; the measurements are stopped nonetheless
+    pub fn stop_continuous_als<D>(&mut self, delay: &mut D) -> Result<(), Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        self.halt_continuous_als(delay)
+    }
+
+    /// Reads the latest ALS sample without waiting for a new one.
+    ///
+    /// Reads the ALS status, the interrupt status and the count in one
+    /// transaction. If the ALS interrupt reports a new sample, it is cleared
+    /// and the sample marked [`new`](LatestAls::new); otherwise the previous
+    /// sample is returned again. The ALS interrupt must be configured for
+    /// [`InterruptMode::NewSampleReady`](crate::types::InterruptMode::NewSampleReady).
+    ///
+    /// Costs three I2C transactions, one more for a new sample, and two less
+    /// once the gain and integration period are cached.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn read_latest_als(&mut self) -> Result<LatestAls, Error> {
+        let block: AlsResultBlock = self.read_register()?;
+        let gain: AlsAnalogueGain = self.read_register()?;
+        let integration: AlsIntegrationPeriod = self.read_register()?;
+        let new = block.interrupt.als_interrupt;
+        if new {
+            self.mark_sample();
+            self.write_register(CLEAR_ALS)?;
+        }
+        Ok(LatestAls {
+            reading: als_reading((block.status, block.value, gain, integration)),
+            new,
+        })
+    }
+
+    /// Starts continuous measurements of `kind` unless they are tracked as
+    /// running
+    ///
+    /// The only place continuous measurements are started, so that the
+    /// tracked state follows the sensor.
+    pub(super) fn begin_continuous(&mut self, kind: ContinuousKind) -> Result<(), Error> {
+        if *self.in_flight.running(kind) {
+            return Ok(());
+        }
+        match kind {
+            ContinuousKind::Range => self.write_register(RangeStart::Continuous)?,
+            ContinuousKind::Als | ContinuousKind::Interleaved => {
+                self.write_register(AlsStart::Continuous)?
+            }
+        }
+        *self.in_flight.running(kind) = true;
+        Ok(())
+    }
+
+    /// Stops continuous measurements of `kind` if they are tracked as
+    /// running, without waiting for the core to become ready
+    ///
+    /// Returns whether a stop was written. The only place continuous
+    /// measurements are stopped: writing the start bit while nothing runs
+    /// would start a measurement instead.
+    pub(super) fn end_continuous(&mut self, kind: ContinuousKind) -> Result<bool, Error> {
+        if !*self.in_flight.running(kind) {
+            return Ok(false);
+        }
+        match kind {
+            ContinuousKind::Range => self.write_register(RangeStart::Continuous)?,
+            ContinuousKind::Als | ContinuousKind::Interleaved => {
+                self.write_register(AlsStart::Continuous)?
+            }
+        }
+        *self.in_flight.running(kind) = false;
+        Ok(true)
+    }
+
+    /// Stops tracked continuous ranging and waits for the ranging core to
+    /// become ready; does nothing if ranging is not tracked as running
+    pub(super) fn halt_continuous_range<D>(&mut self, delay: &mut D) -> Result<(), Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        if self.end_continuous(ContinuousKind::Range)? {
+            self.wait_range_core_ready(delay)?;
+        }
+        Ok(())
+    }
+
+    /// Stops tracked continuous ALS measurements and waits for the ALS core
+    /// to become ready; does nothing if they are not tracked as running
+    pub(super) fn halt_continuous_als<D>(&mut self, delay: &mut D) -> Result<(), Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        if self.end_continuous(ContinuousKind::Als)? {
+            self.wait_als_core_ready(delay)?;
+        }
+        Ok(())
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously starts continuous ranging.
+    ///
+    /// This is the async version of [`start_continuous_range`](Device::start_continuous_range).
+    pub async fn start_continuous_range_async(&mut self) -> Result<(), Error> {
+        if self.in_flight.interleaved {
+            return Err(Error::DeviceBusy);
+        }
+        self.begin_continuous_async(ContinuousKind::Range).await
+    }
+
+    /// Asynchronously stops continuous ranging.
+    ///
+    /// This is the async version of [`stop_continuous_range`](Device::stop_continuous_range).
+    pub async fn stop_continuous_range_async<D>(&mut self, delay: &mut D) -> Result<(), Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        self.halt_continuous_range_async(delay).await
+    }
+
+    /// Asynchronously starts continuous ALS measurements every `period`.
+    ///
+    /// This is the async version of [`start_continuous_als`](Device::start_continuous_als).
+    pub async fn start_continuous_als_async(&mut self, period: Duration) -> Result<(), Error> {
+        self.refuse_during_continuous_als()?;
+        let integration: AlsIntegrationPeriod = self.read_register_async().await?;
+        let register = check_period(
+            AlsIntermeasurementPeriod { period },
+            period,
+            integration.min_intermeasurement_period(),
+        )?;
+        self.write_register_async(register).await?;
+        self.begin_continuous_async(ContinuousKind::Als).await
+    }
+
+    /// Asynchronously stops continuous ALS measurements.
+    ///
+    /// This is the async version of [`stop_continuous_als`](Device::stop_continuous_als).
+    pub async fn stop_continuous_als_async<D>(&mut self, delay: &mut D) -> Result<(), Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        self.halt_continuous_als_async(delay).await
+    }
+
+    /// Asynchronously reads the latest ALS sample without waiting for a new one.
+    ///
+    /// This is the async version of [`read_latest_als`](Device::read_latest_als).
+    pub async fn read_latest_als_async(&mut self) -> Result<LatestAls, Error> {
+        let block: AlsResultBlock = self.read_register_async().await?;
+        let gain: AlsAnalogueGain = self.read_register_async().await?;
+        let integration: AlsIntegrationPeriod = self.read_register_async().await?;
+        let new = block.interrupt.als_interrupt;
+        if new {
+            self.mark_sample();
+            self.write_register_async(CLEAR_ALS).await?;
+        }
+        Ok(LatestAls {
+            reading: als_reading((block.status, block.value, gain, integration)),
+            new,
+        })
+    }
+
+    /// Async version of `begin_continuous`
+    pub(super) async fn begin_continuous_async(
+        &mut self,
+        kind: ContinuousKind,
+    ) -> Result<(), Error> {
+        if *self.in_flight.running(kind) {
+            return Ok(());
+        }
+        match kind {
+            ContinuousKind::Range => self.write_register_async(RangeStart::Continuous).await?,
+            ContinuousKind::Als | ContinuousKind::Interleaved => {
+                self.write_register_async(AlsStart::Continuous).await?
+            }
+        }
+        *self.in_flight.running(kind) = true;
+        Ok(())
+    }
+
+    /// Async version of `end_continuous`
+    pub(super) async fn end_continuous_async(
+        &mut self,
+        kind: ContinuousKind,
+    ) -> Result<bool, Error> {
+        if !*self.in_flight.running(kind) {
+            return Ok(false);
+        }
+        match kind {
+            ContinuousKind::Range => self.write_register_async(RangeStart::Continuous).await?,
+            ContinuousKind::Als | ContinuousKind::Interleaved => {
+                self.write_register_async(AlsStart::Continuous).await?
+            }
+        }
+        *self.in_flight.running(kind) = false;
+        Ok(true)
+    }
+
+    /// Async version of `halt_continuous_range`
+    pub(super) async fn halt_continuous_range_async<D>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        if self.end_continuous_async(ContinuousKind::Range).await? {
+            self.wait_range_core_ready_async(delay).await?;
+        }
+        Ok(())
+    }
+
+    /// Async version of `halt_continuous_als`
+    pub(super) async fn halt_continuous_als_async<D>(&mut self, delay: &mut D) -> Result<(), Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        if self.end_continuous_async(ContinuousKind::Als).await? {
+            self.wait_als_core_ready_async(delay).await?;
+        }
+        Ok(())
+    }
+}
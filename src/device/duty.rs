@@ -0,0 +1,284 @@
+//! Duty-cycled measurements
+//!
+//! The lowest-power way to sample occasionally: keep the sensor shut down
+//! through XSHUT (GPIO0) and power it up for one measurement at a time.
+
+use core::time::Duration;
+
+use super::{Device, DeviceAddress};
+use crate::calibration::CalibrationData;
+use crate::registers::{FreshOutOfReset, InterruptConfigGpio};
+use crate::types::{AlsReading, DutyCycle, Error, InterruptMode, RangeReading};
+
+/// Interrupt configuration the measurement helpers poll for
+const NEW_SAMPLE_READY: InterruptConfigGpio = InterruptConfigGpio {
+    range_interrupt: InterruptMode::NewSampleReady,
+    als_interrupt: InterruptMode::NewSampleReady,
+};
+
+/// Delay provider adding up the time it was asked to wait
+struct Metered<'a, D> {
+    delay: &'a mut D,
+    elapsed: Duration,
+}
+
+impl<'a, D> Metered<'a, D> {
+    fn new(delay: &'a mut D) -> Self {
+        Self {
+            delay,
+            elapsed: Duration::ZERO,
+        }
+    }
+}
+
+impl<D: embedded_hal::delay::DelayNs> embedded_hal::delay::DelayNs for Metered<'_, D> {
+    fn delay_ns(&mut self, ns: u32) {
+        self.elapsed += Duration::from_nanos(ns.into());
+        self.delay.delay_ns(ns);
+    }
+}
+
+impl<D: embedded_hal_async::delay::DelayNs> embedded_hal_async::delay::DelayNs for Metered<'_, D> {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.elapsed += Duration::from_nanos(ns.into());
+        self.delay.delay_ns(ns).await;
+    }
+}
+
+/// Shuts the sensor down again, reporting the measurement's error first
+fn power_down<P, T>(xshut: &mut P, result: Result<T, Error>) -> Result<T, Error>
+where
+    P: embedded_hal::digital::OutputPin,
+{
+    let shutdown = xshut.set_low();
+    let value = result?;
+    shutdown.map_err(|_| Error::PinError)?;
+    Ok(value)
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Powers the sensor up, takes one range measurement and shuts it down.
+    ///
+    /// Runs the whole duty cycle in order: power cycles the sensor through
+    /// XSHUT, waits for the firmware to boot, clears the fresh-out-of-reset
+    /// flag, configures both interrupts for
+    /// [`InterruptMode::NewSampleReady`], applies `calibration`, takes a
+    /// measurement like [`read_range`](Device::read_range) and drives XSHUT
+    /// low. XSHUT ends low on every path, errors included, once it could be
+    /// driven at all.
+    ///
+    /// The sensor boots with its power-on configuration and the default I2C
+    /// address, so the device must use [`DEFAULT_ADDRESS`](super::DEFAULT_ADDRESS)
+    /// and any other configuration is lost with every cycle. The active
+    /// profile is forgotten.
+    ///
+    /// # Arguments
+    /// * `xshut` - Output pin driving XSHUT (GPIO0), low while idle
+    /// * `delay` - Delay provider
+    /// * `calibration` - Calibration to restore after boot
+    ///
+    /// # Errors
+    /// * `Error::PinError` - Driving XSHUT failed
+    /// * `Error::Timeout` - The firmware did not boot, or no sample was
+    ///   reported, within the configured timeouts
+    /// * `Error::SerializationError` - The calibration's scaling factor is not 1
+    /// * `Error::BusError` - I2C communication failed
+    ///
+    /// # Example
+    /// ```no_run
+    /// use embedded_hal::{delay::DelayNs, digital::OutputPin, i2c::I2c};
+    /// use vl6180x::calibration::CalibrationData;
+    /// use vl6180x::{Device, Error};
+    ///
+    /// fn sample<I2C: I2c, P: OutputPin, D: DelayNs>(
+    ///     sensor: &mut Device<I2C>,
+    ///     xshut: &mut P,
+    ///     delay: &mut D,
+    ///     calibration: &CalibrationData,
+    /// ) -> Result<(), Error> {
+    ///     let cycle = sensor.duty_cycle_measurement(xshut, delay, calibration)?;
+    ///     let _awake = cycle.boot_time + cycle.measurement_time;
+    ///     let _distance = cycle.reading.ok();
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn duty_cycle_measurement<P, D>(
+        &mut self,
+        xshut: &mut P,
+        delay: &mut D,
+        calibration: &CalibrationData,
+    ) -> Result<DutyCycle<RangeReading>, Error>
+    where
+        P: embedded_hal::digital::OutputPin,
+        D: embedded_hal::delay::DelayNs,
+    {
+        let result = self
+            .duty_cycle_power_up(xshut, delay, Some(calibration))
+            .and_then(|boot_time| {
+                let mut metered = Metered::new(delay);
+                let reading = self.read_range(&mut metered)?;
+                Ok(DutyCycle {
+                    reading,
+                    boot_time,
+                    measurement_time: metered.elapsed,
+                })
+            });
+        power_down(xshut, result)
+    }
+
+    /// Powers the sensor up, takes one ALS measurement and shuts it down.
+    ///
+    /// Like [`duty_cycle_measurement`](Device::duty_cycle_measurement), but
+    /// measures like [`read_als`](Device::read_als) with the power-on gain
+    /// and integration period. No calibration applies to the ALS.
+    ///
+    /// # Errors
+    /// * `Error::PinError` - Driving XSHUT failed
+    /// * `Error::Timeout` - The firmware did not boot, or no sample was
+    ///   reported, within the configured timeouts
+    /// * `Error::BusError` - I2C communication failed
+    pub fn duty_cycle_als<P, D>(
+        &mut self,
+        xshut: &mut P,
+        delay: &mut D,
+    ) -> Result<DutyCycle<AlsReading>, Error>
+    where
+        P: embedded_hal::digital::OutputPin,
+        D: embedded_hal::delay::DelayNs,
+    {
+        let result = self
+            .duty_cycle_power_up(xshut, delay, None)
+            .and_then(|boot_time| {
+                let mut metered = Metered::new(delay);
+                let reading = self.read_als(&mut metered)?;
+                Ok(DutyCycle {
+                    reading,
+                    boot_time,
+                    measurement_time: metered.elapsed,
+                })
+            });
+        power_down(xshut, result)
+    }
+
+    /// Boots the sensor and prepares it for a single-shot measurement,
+    /// returning the boot time
+    fn duty_cycle_power_up<P, D>(
+        &mut self,
+        xshut: &mut P,
+        delay: &mut D,
+        calibration: Option<&CalibrationData>,
+    ) -> Result<Duration, Error>
+    where
+        P: embedded_hal::digital::OutputPin,
+        D: embedded_hal::delay::DelayNs,
+    {
+        let boot_time = self.power_cycle(xshut, delay, None)?;
+        self.active_profile = None;
+
+        let reset: FreshOutOfReset = self.read_register()?;
+        if reset.fresh {
+            self.write_register(FreshOutOfReset { fresh: false })?;
+        }
+        self.write_register(NEW_SAMPLE_READY)?;
+        if let Some(calibration) = calibration {
+            self.apply_calibration(calibration)?;
+        }
+        Ok(boot_time)
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously powers the sensor up, takes one range measurement and
+    /// shuts it down.
+    ///
+    /// This is the async version of [`duty_cycle_measurement`](Device::duty_cycle_measurement).
+    pub async fn duty_cycle_measurement_async<P, D>(
+        &mut self,
+        xshut: &mut P,
+        delay: &mut D,
+        calibration: &CalibrationData,
+    ) -> Result<DutyCycle<RangeReading>, Error>
+    where
+        P: embedded_hal::digital::OutputPin,
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        let result = match self
+            .duty_cycle_power_up_async(xshut, delay, Some(calibration))
+            .await
+        {
+            Ok(boot_time) => {
+                let mut metered = Metered::new(delay);
+                self.read_range_async(&mut metered)
+                    .await
+                    .map(|reading| DutyCycle {
+                        reading,
+                        boot_time,
+                        measurement_time: metered.elapsed,
+                    })
+            }
+            Err(e) => Err(e),
+        };
+        power_down(xshut, result)
+    }
+
+    /// Asynchronously powers the sensor up, takes one ALS measurement and
+    /// shuts it down.
+    ///
+    /// This is the async version of [`duty_cycle_als`](Device::duty_cycle_als).
+    pub async fn duty_cycle_als_async<P, D>(
+        &mut self,
+        xshut: &mut P,
+        delay: &mut D,
+    ) -> Result<DutyCycle<AlsReading>, Error>
+    where
+        P: embedded_hal::digital::OutputPin,
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        let result = match self.duty_cycle_power_up_async(xshut, delay, None).await {
+            Ok(boot_time) => {
+                let mut metered = Metered::new(delay);
+                self.read_als_async(&mut metered)
+                    .await
+                    .map(|reading| DutyCycle {
+                        reading,
+                        boot_time,
+                        measurement_time: metered.elapsed,
+                    })
+            }
+            Err(e) => Err(e),
+        };
+        power_down(xshut, result)
+    }
+
+    /// Async version of `duty_cycle_power_up`
+    async fn duty_cycle_power_up_async<P, D>(
+        &mut self,
+        xshut: &mut P,
+        delay: &mut D,
+        calibration: Option<&CalibrationData>,
+    ) -> Result<Duration, Error>
+    where
+        P: embedded_hal::digital::OutputPin,
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        let boot_time = self.power_cycle_async(xshut, delay, None).await?;
+        self.active_profile = None;
+
+        let reset: FreshOutOfReset = self.read_register_async().await?;
+        if reset.fresh {
+            self.write_register_async(FreshOutOfReset { fresh: false })
+                .await?;
+        }
+        self.write_register_async(NEW_SAMPLE_READY).await?;
+        if let Some(calibration) = calibration {
+            self.apply_calibration_async(calibration).await?;
+        }
+        Ok(boot_time)
+    }
+}
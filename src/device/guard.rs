@@ -0,0 +1,246 @@
+//! Scoped continuous measurements
+//!
+//! Guards that start a continuous measurement and stop it again when they go
+//! out of scope, so an early return cannot leave the sensor free-running.
+
+use measurements::Length;
+
+use super::continuous::ContinuousKind;
+use super::{health::Measurement, Device, DeviceAddress, RuntimeAddress};
+use crate::types::{Error, Luminance};
+
+/// Continuous ranging that is stopped when the guard is dropped
+///
+/// Created by [`Device::continuous_ranging_scope`].
+pub struct ContinuousGuard<'a, I2C, A = RuntimeAddress>
+where
+    I2C: embedded_hal::i2c::I2c,
+    A: DeviceAddress,
+{
+    device: &'a mut Device<I2C, A>,
+    active: bool,
+}
+
+/// Continuous ALS measurements that are stopped when the guard is dropped
+///
+/// Created by [`Device::continuous_als_scope`].
+pub struct ContinuousAlsGuard<'a, I2C, A = RuntimeAddress>
+where
+    I2C: embedded_hal::i2c::I2c,
+    A: DeviceAddress,
+{
+    device: &'a mut Device<I2C, A>,
+    active: bool,
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A> {
+    /// Returns the error of the last stop issued by a dropped guard.
+    ///
+    /// Dropping a [`ContinuousGuard`] or [`ContinuousAlsGuard`] cannot report
+    /// a failure to stop the measurement, so it is recorded here instead.
+    /// Every guard drop overwrites it, with `None` when the stop succeeded.
+    pub fn last_drop_error(&self) -> Option<Error> {
+        self.last_drop_error
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Starts continuous ranging for the lifetime of the returned guard.
+    ///
+    /// When the guard is dropped, ranging is stopped if it is still running.
+    /// Ranging that already ran when the scope was entered is taken over and
+    /// stopped as well.
+    /// The stop is best-effort and does not wait for the measurement in
+    /// progress to finish; any error is available from
+    /// [`last_drop_error`](Device::last_drop_error). Use
+    /// [`ContinuousGuard::stop`] to stop and wait with error reporting.
+    ///
+    /// # Example
+    /// ```
+    /// use embedded_hal::{delay::DelayNs, i2c::I2c};
+    /// use measurements::Length;
+    /// use vl6180x::{Device, Error};
+    /// # use embedded_hal::i2c::{ErrorKind, ErrorType, Operation};
+    /// # struct Bus { interrupt_status: u8, start_writes: u32 }
+    /// # impl ErrorType for Bus { type Error = ErrorKind; }
+    /// # impl I2c for Bus {
+    /// #     fn transaction(&mut self, _: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+    /// #         match ops {
+    /// #             [Operation::Write(reg), Operation::Read(buf)] => buf.fill(match reg[1] {
+    /// #                 0x4F => self.interrupt_status,
+    /// #                 0x62 => 100,
+    /// #                 _ => 0x00,
+    /// #             }),
+    /// #             [Operation::Write([0x00, 0x18]), Operation::Write(_)] => self.start_writes += 1,
+    /// #             _ => {}
+    /// #         }
+    /// #         Ok(())
+    /// #     }
+    /// # }
+    /// # struct NoDelay;
+    /// # impl DelayNs for NoDelay { fn delay_ns(&mut self, _: u32) {} }
+    ///
+    /// fn first_sample<I2C: I2c, D: DelayNs>(
+    ///     sensor: &mut Device<I2C>,
+    ///     delay: &mut D,
+    /// ) -> Result<Length, Error> {
+    ///     let mut ranging = sensor.continuous_ranging_scope()?;
+    ///     // Ranging stops when `ranging` goes out of scope, also on early return
+    ///     ranging.next_sample(delay)
+    /// }
+    ///
+    /// # let mut bus = Bus { interrupt_status: 0x04, start_writes: 0 };
+    /// let mut sensor = Device::new(&mut bus);
+    ///
+    /// let distance = first_sample(&mut sensor, &mut NoDelay)?;
+    /// assert_eq!(distance.as_millimeters(), 100.0);
+    /// assert_eq!(sensor.last_drop_error(), None);
+    /// # drop(sensor);
+    /// // One write to start ranging, one to stop it
+    /// assert_eq!(bus.start_writes, 2);
+    ///
+    /// // A sensor that never reports a sample
+    /// # bus.interrupt_status = 0x00;
+    /// let mut sensor = Device::new(&mut bus);
+    /// assert_eq!(first_sample(&mut sensor, &mut NoDelay), Err(Error::Timeout));
+    /// # drop(sensor);
+    /// // Ranging was still stopped on the early return
+    /// assert_eq!(bus.start_writes, 4);
+    /// # Ok::<(), Error>(())
+    /// ```
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn continuous_ranging_scope(&mut self) -> Result<ContinuousGuard<'_, I2C, A>, Error> {
+        self.begin_continuous(ContinuousKind::Range)?;
+        Ok(ContinuousGuard {
+            device: self,
+            active: true,
+        })
+    }
+
+    /// Starts continuous ALS measurements for the lifetime of the returned guard.
+    ///
+    /// The ALS counterpart of [`continuous_ranging_scope`](Device::continuous_ranging_scope).
+    /// The configured intermeasurement period is first validated against the
+    /// integration period, see
+    /// [`AlsIntegrationPeriod::min_intermeasurement_period`](crate::registers::AlsIntegrationPeriod::min_intermeasurement_period).
+    ///
+    /// # Errors
+    /// * `Error::PeriodTooShort` - The intermeasurement period is too short
+    ///   for the integration period
+    /// * `Error::BusError` - I2C communication failed
+    pub fn continuous_als_scope(&mut self) -> Result<ContinuousAlsGuard<'_, I2C, A>, Error> {
+        self.check_als_period()?;
+        self.begin_continuous(ContinuousKind::Als)?;
+        Ok(ContinuousAlsGuard {
+            device: self,
+            active: true,
+        })
+    }
+}
+
+impl<I2C, A> ContinuousGuard<'_, I2C, A>
+where
+    A: DeviceAddress,
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Waits for the next range sample, reads it and clears its interrupt.
+    ///
+    /// The range interrupt must be configured for
+    /// [`InterruptMode::NewSampleReady`](crate::types::InterruptMode::NewSampleReady).
+    ///
+    /// # Errors
+    /// * `Error::Timeout` - No sample was reported within the polling budget
+    /// * `Error::RangeError` - The measurement completed with an error code
+    pub fn next_sample<D>(&mut self, delay: &mut D) -> Result<Length, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        let result = self.device.wait_range(delay);
+        self.device.record_measurement(Measurement::Range, &result);
+        result
+    }
+
+    /// Stops ranging and waits for the ranging core to become ready.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::Timeout` - The ranging core did not stop
+    pub fn stop<D>(mut self, delay: &mut D) -> Result<(), Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        self.active = false;
+        self.device.halt_continuous_range(delay)
+    }
+}
+
+impl<I2C, A> Drop for ContinuousGuard<'_, I2C, A>
+where
+    A: DeviceAddress,
+    I2C: embedded_hal::i2c::I2c,
+{
+    fn drop(&mut self) {
+        if !self.active {
+            return;
+        }
+
+        let result = self.device.end_continuous(ContinuousKind::Range);
+        self.device.last_drop_error = result.err();
+    }
+}
+
+impl<I2C, A> ContinuousAlsGuard<'_, I2C, A>
+where
+    A: DeviceAddress,
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Waits for the next ALS sample, reads it and clears its interrupt.
+    ///
+    /// The ALS interrupt must be configured for
+    /// [`InterruptMode::NewSampleReady`](crate::types::InterruptMode::NewSampleReady).
+    ///
+    /// # Errors
+    /// * `Error::Timeout` - No sample was reported within the polling budget
+    /// * `Error::AlsError` - The measurement completed with an error code
+    pub fn next_sample<D>(&mut self, delay: &mut D) -> Result<Luminance, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        let result = self.device.wait_als(delay);
+        self.device.record_measurement(Measurement::Als, &result);
+        result
+    }
+
+    /// Stops ALS measurements and waits for the ALS core to become ready.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::Timeout` - The ALS core did not stop
+    pub fn stop<D>(mut self, delay: &mut D) -> Result<(), Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        self.active = false;
+        self.device.halt_continuous_als(delay)
+    }
+}
+
+impl<I2C, A> Drop for ContinuousAlsGuard<'_, I2C, A>
+where
+    A: DeviceAddress,
+    I2C: embedded_hal::i2c::I2c,
+{
+    fn drop(&mut self) {
+        if !self.active {
+            return;
+        }
+
+        let result = self.device.end_continuous(ContinuousKind::Als);
+        self.device.last_drop_error = result.err();
+    }
+}
@@ -0,0 +1,104 @@
+//! Measurement health counters
+//!
+//! With the `stats` feature enabled, the high-level measurement helpers tally
+//! their outcomes so fleet devices can report cheap health telemetry. Without
+//! the feature the recording hooks compile to nothing and [`Device`] carries
+//! no extra state.
+
+use super::{Device, DeviceAddress};
+use crate::types::Error;
+
+/// Kind of measurement taken by a high-level helper
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Measurement {
+    Range,
+    Als,
+}
+
+/// Measurement outcome counters accumulated by a [`Device`]
+///
+/// Each measurement attempt increments exactly one of the outcome counters.
+/// All counters saturate rather than wrap.
+#[cfg(feature = "stats")]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct HealthStats {
+    /// Number of range measurements attempted
+    pub range_measurements: u32,
+    /// Number of ALS measurements attempted
+    pub als_measurements: u32,
+    /// Number of range measurements that returned a valid distance
+    pub range_ok: u32,
+    /// Number of ALS measurements that returned a valid light level
+    pub als_ok: u32,
+    /// Number of range measurements rejected by the device's error code
+    pub range_errors: u32,
+    /// Number of ALS measurements rejected by the device's error code
+    pub als_errors: u32,
+    /// Number of measurements aborted by an I2C or GPIO pin error, a
+    /// spurious interrupt or inconsistent result reads
+    pub bus_errors: u32,
+    /// Number of measurements aborted by a register codec error or a rejected
+    /// configuration value
+    pub codec_errors: u32,
+    /// Number of measurements that timed out or stalled waiting for a sample,
+    /// or found the device busy
+    pub timeouts: u32,
+}
+
+#[cfg(feature = "stats")]
+impl HealthStats {
+    fn record<T>(&mut self, kind: Measurement, result: &Result<T, Error>) {
+        let (attempts, ok) = match kind {
+            Measurement::Range => (&mut self.range_measurements, &mut self.range_ok),
+            Measurement::Als => (&mut self.als_measurements, &mut self.als_ok),
+        };
+        *attempts = attempts.saturating_add(1);
+
+        let outcome = match result {
+            Ok(_) => ok,
+            Err(Error::RangeError(_)) => &mut self.range_errors,
+            Err(Error::AlsError(_)) => &mut self.als_errors,
+            Err(
+                Error::BusError(_)
+                | Error::PinError
+                | Error::SpuriousInterrupt
+                | Error::InconsistentRead(_)
+                | Error::UnexpectedModel(_)
+                | Error::EightBitAddress(_),
+            ) => &mut self.bus_errors,
+            Err(
+                Error::SerializationError(_)
+                | Error::DeserializationError(_)
+                | Error::OutOfSpec(_)
+                | Error::PeriodTooShort(_),
+            ) => &mut self.codec_errors,
+            Err(Error::Timeout | Error::Stalled | Error::DeviceBusy) => &mut self.timeouts,
+        };
+        *outcome = outcome.saturating_add(1);
+    }
+}
+
+#[cfg(feature = "stats")]
+impl<I2C, A: DeviceAddress> Device<I2C, A> {
+    /// Returns the measurement outcomes recorded since creation or the last reset.
+    pub fn health_stats(&self) -> HealthStats {
+        self.health
+    }
+
+    /// Clears the measurement outcome counters.
+    pub fn reset_health_stats(&mut self) {
+        self.health = HealthStats::default();
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A> {
+    /// Records the outcome of a high-level measurement.
+    #[inline(always)]
+    pub(super) fn record_measurement<T>(&mut self, kind: Measurement, result: &Result<T, Error>) {
+        #[cfg(feature = "stats")]
+        self.health.record(kind, result);
+        #[cfg(not(feature = "stats"))]
+        let _ = (kind, result);
+    }
+}
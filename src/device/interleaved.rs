@@ -0,0 +1,238 @@
+//! Interleaved mode
+//!
+//! Continuous ALS measurements each followed immediately by a range
+//! measurement, run off the ALS timer. The datasheet recommends this mode
+//! over running continuous ranging and continuous ALS side by side.
+
+use core::time::Duration;
+
+use super::als::als_reading;
+use super::continuous::ContinuousKind;
+use super::period::check_period;
+use super::{Device, DeviceAddress};
+use crate::registers::{
+    AlsAnalogueGain, AlsIntegrationPeriod, AlsIntermeasurementPeriod, AlsResultBlock,
+    InterleavedModeEnable, InterruptClear, RangeMaxConvergenceTime, RangeStatusBlock,
+    ReadoutAveraging,
+};
+use crate::types::{AlsReading, Error, RangeReading};
+
+/// Interrupt clear value acknowledging a range and an ALS sample
+const CLEAR_SAMPLES: InterruptClear = InterruptClear {
+    clear_range: true,
+    clear_als: true,
+    clear_error: false,
+};
+
+/// Fails with `Error::PeriodTooShort` unless an ALS integration followed by
+/// a range measurement fits into `period` with the datasheet's margins, and
+/// with `Error::SerializationError` if the register cannot encode `period`
+fn interleaved_period(
+    period: Duration,
+    integration: AlsIntegrationPeriod,
+    limit: RangeMaxConvergenceTime,
+    averaging: ReadoutAveraging,
+) -> Result<AlsIntermeasurementPeriod, Error> {
+    let required =
+        integration.min_intermeasurement_period_with(limit.measurement_time_with(averaging));
+    check_period(AlsIntermeasurementPeriod { period }, period, required)
+}
+
+/// Classifies the latest range and ALS samples
+fn interleaved_readings(
+    range: RangeStatusBlock,
+    als: AlsResultBlock,
+    gain: AlsAnalogueGain,
+    integration: AlsIntegrationPeriod,
+) -> (RangeReading, AlsReading) {
+    (
+        RangeReading::new(range.status.error_code, range.distance),
+        als_reading((als.status, als.value, gain, integration)),
+    )
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A> {
+    /// Returns whether interleaved mode was started with
+    /// [`start_interleaved`](Device::start_interleaved) and not stopped
+    /// since.
+    pub fn is_interleaved_running(&self) -> bool {
+        self.in_flight.interleaved
+    }
+
+    /// Fails with `Error::DeviceBusy` while any tracked continuous
+    /// measurement runs
+    fn refuse_during_continuous(&self) -> Result<(), Error> {
+        self.refuse_during_continuous_range()?;
+        self.refuse_during_continuous_als()
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Starts interleaved mode: an ALS measurement every `als_period`, each
+    /// followed immediately by a range measurement.
+    ///
+    /// Validates `als_period` against the configured ALS integration period,
+    /// range convergence limit and readout averaging with the datasheet's
+    /// interleaved mode margins: the integration period times 1.1 plus the
+    /// range measurement time must fit into 0.9 times `als_period`. Then
+    /// writes `als_period` to `SYSALS__INTERMEASUREMENT_PERIOD`, enables
+    /// `INTERLEAVED_MODE__ENABLE` and starts continuous ALS measurements,
+    /// which drive the range measurements. The ranging intermeasurement
+    /// period has no effect in this mode.
+    ///
+    /// Until [`stop_interleaved`](Device::stop_interleaved) is called,
+    /// single-shot measurements fail with `Error::DeviceBusy`. Samples are
+    /// read with [`read_interleaved`](Device::read_interleaved).
+    ///
+    /// Costs six I2C transactions, five once the integration period is
+    /// cached.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use core::time::Duration;
+    /// use embedded_hal::{delay::DelayNs, i2c::I2c};
+    /// use vl6180x::{Device, Error};
+    ///
+    /// fn monitor<I2C: I2c, D: DelayNs>(sensor: &mut Device<I2C>, delay: &mut D) -> Result<(), Error> {
+    ///     // Both measurements at 10Hz
+    ///     sensor.start_interleaved(Duration::from_millis(100))?;
+    ///     for _ in 0..50 {
+    ///         delay.delay_ms(100);
+    ///         let (_range, _light) = sensor.read_interleaved()?;
+    ///     }
+    ///     sensor.stop_interleaved(delay)
+    /// }
+    /// ```
+    ///
+    /// # Arguments
+    /// * `als_period` - Time between the starts of two ALS measurements
+    ///
+    /// # Errors
+    /// * `Error::DeviceBusy` - A continuous measurement or interleaved mode
+    ///   started by this driver already runs
+    /// * `Error::PeriodTooShort` - `als_period` does not hold both
+    ///   measurements
+    /// * `Error::SerializationError` - `als_period` is outside 10ms to 2560ms
+    /// * `Error::BusError` - I2C communication failed
+    pub fn start_interleaved(&mut self, als_period: Duration) -> Result<(), Error> {
+        self.refuse_during_continuous()?;
+        let integration = self.read_register()?;
+        let limit = self.read_register()?;
+        let averaging = self.read_register()?;
+        let period = interleaved_period(als_period, integration, limit, averaging)?;
+
+        self.write_register(period)?;
+        self.write_register(InterleavedModeEnable { enabled: true })?;
+        self.begin_continuous(ContinuousKind::Interleaved)
+    }
+
+    /// Stops interleaved mode started with
+    /// [`start_interleaved`](Device::start_interleaved).
+    ///
+    /// Stops the continuous ALS measurements, waits for the ALS and ranging
+    /// cores to report ready and disables `INTERLEAVED_MODE__ENABLE` again.
+    /// Does nothing if interleaved mode was not started. The interrupts of
+    /// the last samples are left pending.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::Timeout` - A core did not become ready within its timeout;
+    ///   the measurements are stopped nonetheless, but interleaved mode is
+    ///   left enabled
+    pub fn stop_interleaved<D>(&mut self, delay: &mut D) -> Result<(), Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        if !self.end_continuous(ContinuousKind::Interleaved)? {
+            return Ok(());
+        }
+
+        self.wait_als_core_ready(delay)?;
+        self.wait_range_core_ready(delay)?;
+        self.write_register(InterleavedModeEnable { enabled: false })
+    }
+
+    /// Reads the latest range and ALS samples and clears both interrupts.
+    ///
+    /// Does not wait for new samples: either reading repeats the previous
+    /// one until the sensor reports the next. Only the range and ALS
+    /// interrupts are cleared; a pending error interrupt stays set.
+    ///
+    /// Costs five I2C transactions, three once the gain and integration
+    /// period are cached.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn read_interleaved(&mut self) -> Result<(RangeReading, AlsReading), Error> {
+        let range: RangeStatusBlock = self.read_register()?;
+        let als: AlsResultBlock = self.read_register()?;
+        let gain = self.read_register()?;
+        let integration = self.read_register()?;
+        if range.interrupt.range_interrupt || als.interrupt.als_interrupt {
+            self.mark_sample();
+        }
+        self.write_register(CLEAR_SAMPLES)?;
+        Ok(interleaved_readings(range, als, gain, integration))
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously starts interleaved mode.
+    ///
+    /// This is the async version of [`start_interleaved`](Device::start_interleaved).
+    pub async fn start_interleaved_async(&mut self, als_period: Duration) -> Result<(), Error> {
+        self.refuse_during_continuous()?;
+        let integration = self.read_register_async().await?;
+        let limit = self.read_register_async().await?;
+        let averaging = self.read_register_async().await?;
+        let period = interleaved_period(als_period, integration, limit, averaging)?;
+
+        self.write_register_async(period).await?;
+        self.write_register_async(InterleavedModeEnable { enabled: true })
+            .await?;
+        self.begin_continuous_async(ContinuousKind::Interleaved)
+            .await
+    }
+
+    /// Asynchronously stops interleaved mode.
+    ///
+    /// This is the async version of [`stop_interleaved`](Device::stop_interleaved).
+    pub async fn stop_interleaved_async<D>(&mut self, delay: &mut D) -> Result<(), Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        if !self
+            .end_continuous_async(ContinuousKind::Interleaved)
+            .await?
+        {
+            return Ok(());
+        }
+
+        self.wait_als_core_ready_async(delay).await?;
+        self.wait_range_core_ready_async(delay).await?;
+        self.write_register_async(InterleavedModeEnable { enabled: false })
+            .await
+    }
+
+    /// Asynchronously reads the latest range and ALS samples and clears both
+    /// interrupts.
+    ///
+    /// This is the async version of [`read_interleaved`](Device::read_interleaved).
+    pub async fn read_interleaved_async(&mut self) -> Result<(RangeReading, AlsReading), Error> {
+        let range: RangeStatusBlock = self.read_register_async().await?;
+        let als: AlsResultBlock = self.read_register_async().await?;
+        let gain = self.read_register_async().await?;
+        let integration = self.read_register_async().await?;
+        if range.interrupt.range_interrupt || als.interrupt.als_interrupt {
+            self.mark_sample();
+        }
+        self.write_register_async(CLEAR_SAMPLES).await?;
+        Ok(interleaved_readings(range, als, gain, integration))
+    }
+}
@@ -0,0 +1,554 @@
+//! Interrupt handling
+//!
+//! The minimal interrupt handler work: read the pending interrupts, clear
+//! them and queue them for the main loop.
+
+use core::future::{poll_fn, Future};
+use core::pin::{pin, Pin};
+use core::task::Poll;
+use core::time::Duration;
+
+use super::{Device, DeviceAddress, POLL_INTERVAL_US};
+use crate::events::{EventQueue, InterruptEvent, InterruptSources};
+use crate::registers::{InterruptClear, InterruptConfigGpio, ModeGpio1, ResultInterruptStatusGpio};
+use crate::types::{
+    AlsInterrupt, Error, GpioFunction, GpioPolarity, InterruptMode, RangeInterrupt,
+    SpuriousInterruptPolicy,
+};
+
+/// Time between interrupt pin polls
+const PIN_POLL_INTERVAL: Duration = Duration::from_micros(POLL_INTERVAL_US as u64);
+
+/// Queues the events flagged in `status`
+///
+/// Returns the clear value acknowledging them, or `None` if nothing was pending.
+pub(super) fn enqueue<const N: usize>(
+    status: ResultInterruptStatusGpio,
+    queue: &mut EventQueue<N>,
+) -> Option<InterruptClear> {
+    for event in InterruptEvent::from_status(status) {
+        queue.push(event);
+    }
+    acknowledge(status)
+}
+
+/// Returns the clear value acknowledging the interrupts flagged in `status`,
+/// or `None` if nothing was pending
+pub(super) fn acknowledge(status: ResultInterruptStatusGpio) -> Option<InterruptClear> {
+    let pending = status.range_interrupt || status.als_interrupt || status.error_interrupt;
+    pending.then_some(InterruptClear {
+        clear_range: status.range_interrupt,
+        clear_als: status.als_interrupt,
+        clear_error: status.error_interrupt,
+    })
+}
+
+/// Returns whether `pin` is at its active level
+fn pin_active<P: embedded_hal::digital::InputPin>(
+    pin: &mut P,
+    active_high: bool,
+) -> Result<bool, Error> {
+    pin.is_high()
+        .map(|high| high == active_high)
+        .map_err(|_| Error::PinError)
+}
+
+/// Completes with the output of `future`, or with `None` if `expiry`
+/// completes first
+async fn until<F, E>(future: F, mut expiry: Pin<&mut E>) -> Option<F::Output>
+where
+    F: Future,
+    E: Future<Output = ()>,
+{
+    let mut future = pin!(future);
+    poll_fn(|cx| {
+        if let Poll::Ready(output) = future.as_mut().poll(cx) {
+            return Poll::Ready(Some(output));
+        }
+        expiry.as_mut().poll(cx).map(|()| None)
+    })
+    .await
+}
+
+/// Waits for `pin` to be at the given level, failing with `Error::Timeout`
+/// once `expiry` completes
+async fn wait_for_level<P, E>(pin: &mut P, high: bool, expiry: Pin<&mut E>) -> Result<(), Error>
+where
+    P: embedded_hal_async::digital::Wait,
+    E: Future<Output = ()>,
+{
+    let level = async {
+        if high {
+            pin.wait_for_high().await
+        } else {
+            pin.wait_for_low().await
+        }
+    };
+    match until(level, expiry).await {
+        Some(result) => result.map_err(|_| Error::PinError),
+        None => Err(Error::Timeout),
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A> {
+    /// Sets how the interrupt pin waits handle a spurious interrupt.
+    ///
+    /// A spurious interrupt is GPIO1 asserting while
+    /// `RESULT__INTERRUPT_STATUS_GPIO` reports no pending source, e.g. an
+    /// edge caused by a slow pull-up on an active-low line. Either way no
+    /// status is returned without a pending source. Defaults to
+    /// [`SpuriousInterruptPolicy::Report`].
+    pub fn set_spurious_interrupt_policy(&mut self, policy: SpuriousInterruptPolicy) {
+        self.spurious_policy = policy;
+    }
+
+    /// Returns the spurious interrupt policy, see
+    /// [`set_spurious_interrupt_policy`](Device::set_spurious_interrupt_policy).
+    pub fn spurious_interrupt_policy(&self) -> SpuriousInterruptPolicy {
+        self.spurious_policy
+    }
+
+    /// Passes on a status with a pending source; otherwise applies the
+    /// spurious interrupt policy, `None` meaning to keep waiting
+    fn filter_spurious(
+        &self,
+        status: ResultInterruptStatusGpio,
+    ) -> Result<Option<ResultInterruptStatusGpio>, Error> {
+        if InterruptEvent::from_status(status).next().is_some() {
+            return Ok(Some(status));
+        }
+        match self.spurious_policy {
+            SpuriousInterruptPolicy::Report => Err(Error::SpuriousInterrupt),
+            SpuriousInterruptPolicy::KeepWaiting => Ok(None),
+        }
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Reads, clears and queues the pending interrupts.
+    ///
+    /// Intended to be called from the GPIO1 interrupt handler. This consumes
+    /// the interrupts; use [`peek_interrupt_status`](Device::peek_interrupt_status)
+    /// to inspect them without clearing. Costs one I2C transaction, plus one
+    /// to clear the interrupts if any were pending.
+    /// Events that do not fit in the queue are counted by
+    /// [`EventQueue::overflows`] and still cleared on the device.
+    ///
+    /// Returns the interrupt status that was read.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use embedded_hal::i2c::I2c;
+    /// use vl6180x::{events::{EventQueue, InterruptEvent}, Device};
+    ///
+    /// // `events` is shared between both halves through the platform's
+    /// // synchronization primitive, e.g. a critical section mutex.
+    /// fn on_gpio1<I2C: I2c>(sensor: &mut Device<I2C>, events: &mut EventQueue<8>) {
+    ///     sensor.handle_interrupt(events).ok();
+    /// }
+    ///
+    /// fn main_loop(events: &mut EventQueue<8>) {
+    ///     for event in events.drain() {
+    ///         match event {
+    ///             InterruptEvent::Range => { /* read the range result */ }
+    ///             InterruptEvent::Als => { /* read the ALS result */ }
+    ///             InterruptEvent::Error => { /* reinitialize */ }
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn handle_interrupt<const N: usize>(
+        &mut self,
+        queue: &mut EventQueue<N>,
+    ) -> Result<ResultInterruptStatusGpio, Error> {
+        let status: ResultInterruptStatusGpio = self.read_register()?;
+        if let Some(clear) = enqueue(status, queue) {
+            self.write_register(clear)?;
+        }
+        Ok(status)
+    }
+
+    /// Reads the pending interrupts without clearing them.
+    ///
+    /// Issues exactly one read of `RESULT__INTERRUPT_STATUS_GPIO` and never
+    /// writes `SYSTEM__INTERRUPT_CLEAR`, so it can be used for diagnostics
+    /// while [`handle_interrupt`](Device::handle_interrupt) or other code owns
+    /// clearing.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn peek_interrupt_status(&mut self) -> Result<ResultInterruptStatusGpio, Error> {
+        self.read_register()
+    }
+
+    /// Returns the pending interrupt sources without clearing them.
+    ///
+    /// The typed form of [`peek_interrupt_status`](Device::peek_interrupt_status),
+    /// with the same single read.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn pending_interrupts(&mut self) -> Result<InterruptSources, Error> {
+        self.peek_interrupt_status().map(InterruptSources::from)
+    }
+
+    /// Waits for the GPIO1 interrupt output to assert and reads the pending interrupts.
+    ///
+    /// Reads the configured [`GpioPolarity`] from `SYSTEM__MODE_GPIO1`, then
+    /// polls the level of `pin` without touching the I2C bus until it is
+    /// active. An active level is sampled a second time before the interrupt
+    /// status is read, so a glitch shorter than one pin read is waited out
+    /// rather than acted on. The interrupt status is then read and returned;
+    /// the interrupts are not cleared, so the caller can read the results
+    /// first.
+    ///
+    /// A status is only returned with a pending interrupt source. An asserted
+    /// pin with nothing pending is handled according to the
+    /// [spurious interrupt policy](Device::set_spurious_interrupt_policy): by
+    /// default it is reported as `Error::SpuriousInterrupt`, otherwise the
+    /// wait resumes once the pin has deasserted, so a pin stuck active does
+    /// not turn it into I2C polling. A spurious interrupt usually points at a
+    /// polarity mismatch, noise on the line or a slow pull-up.
+    ///
+    /// # Arguments
+    /// * `pin` - Input pin wired to GPIO1
+    /// * `delay` - Delay provider used between pin polls
+    /// * `timeout` - Maximum time to wait, or `None` for the longer of the
+    ///   configured range and ALS timeouts, see [`set_timeouts`](Device::set_timeouts)
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::PinError` - Reading the pin failed
+    /// * `Error::Timeout` - No interrupt with a pending source within the timeout
+    /// * `Error::SpuriousInterrupt` - The pin was asserted with nothing
+    ///   pending, with [`SpuriousInterruptPolicy::Report`]
+    pub fn wait_for_interrupt_pin<P, D, T>(
+        &mut self,
+        pin: &mut P,
+        delay: &mut D,
+        timeout: T,
+    ) -> Result<ResultInterruptStatusGpio, Error>
+    where
+        P: embedded_hal::digital::InputPin,
+        D: embedded_hal::delay::DelayNs,
+        T: Into<Option<Duration>>,
+    {
+        let timeout = timeout.into().unwrap_or(self.ready_timeout());
+        let gpio: ModeGpio1 = self.read_register()?;
+        let active_high = gpio.polarity == GpioPolarity::ActiveHigh;
+
+        // Cleared by a spurious interrupt until the pin deasserts again
+        let mut armed = true;
+        let mut elapsed = Duration::ZERO;
+        loop {
+            if !pin_active(pin, active_high)? {
+                armed = true;
+            } else if armed && pin_active(pin, active_high)? {
+                let status: ResultInterruptStatusGpio = self.read_register()?;
+                if let Some(status) = self.filter_spurious(status)? {
+                    return Ok(status);
+                }
+                armed = false;
+            }
+
+            if elapsed >= timeout {
+                return Err(Error::Timeout);
+            }
+            delay.delay_us(POLL_INTERVAL_US);
+            elapsed += PIN_POLL_INTERVAL;
+        }
+    }
+
+    /// Configures the range and ALS interrupts and routes them to GPIO1.
+    ///
+    /// Programs the thresholds of threshold-based conditions before selecting
+    /// the interrupt modes, so a level or window interrupt never runs against
+    /// the power-on thresholds of zero.
+    ///
+    /// # Arguments
+    /// * `polarity` - Active level of the GPIO1 interrupt output
+    /// * `range` - Range interrupt condition
+    /// * `als` - ALS interrupt condition
+    ///
+    /// # Example
+    /// ```no_run
+    /// use embedded_hal::i2c::I2c;
+    /// use measurements::Length;
+    /// use vl6180x::{AlsInterrupt, Device, GpioPolarity, RangeInterrupt};
+    ///
+    /// fn proximity_alert<I2C: I2c>(sensor: &mut Device<I2C>) -> Result<(), vl6180x::Error> {
+    ///     sensor.configure_gpio1_interrupt(
+    ///         GpioPolarity::ActiveLow,
+    ///         RangeInterrupt::LevelLow {
+    ///             low: Length::from_millimeters(50.0),
+    ///         },
+    ///         AlsInterrupt::Disabled,
+    ///     )
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn configure_gpio1_interrupt(
+        &mut self,
+        polarity: GpioPolarity,
+        range: RangeInterrupt,
+        als: AlsInterrupt,
+    ) -> Result<(), Error> {
+        if let Some(thresholds) = range.thresholds() {
+            self.write_register(thresholds)?;
+        }
+        if let Some(thresholds) = als.thresholds() {
+            self.write_register(thresholds)?;
+        }
+
+        self.write_register(InterruptConfigGpio {
+            range_interrupt: range.mode(),
+            als_interrupt: als.mode(),
+        })?;
+        self.write_register(ModeGpio1 {
+            function: GpioFunction::InterruptOutput,
+            polarity,
+        })
+    }
+
+    /// Sets the range interrupt condition, leaving the ALS interrupt mode unchanged.
+    ///
+    /// Programs the thresholds of a threshold-based condition, then updates
+    /// only the range field of the interrupt configuration register.
+    ///
+    /// # Example
+    /// ```
+    /// use measurements::Length;
+    /// use vl6180x::{AlsInterrupt, Device, InterruptMode, RangeInterrupt};
+    /// # use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, Operation};
+    /// # struct Bus { regs: [u8; 0x100] }
+    /// # impl ErrorType for Bus { type Error = ErrorKind; }
+    /// # impl I2c for Bus {
+    /// #     fn transaction(&mut self, _: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+    /// #         match ops {
+    /// #             [Operation::Write(reg), Operation::Read(buf)] => {
+    /// #                 let start = reg[1] as usize;
+    /// #                 buf.copy_from_slice(&self.regs[start..start + buf.len()]);
+    /// #             }
+    /// #             [Operation::Write(reg), Operation::Write(data)] => {
+    /// #                 let start = reg[1] as usize;
+    /// #                 self.regs[start..start + data.len()].copy_from_slice(data);
+    /// #             }
+    /// #             _ => {}
+    /// #         }
+    /// #         Ok(())
+    /// #     }
+    /// # }
+    /// # let mut bus = Bus { regs: [0; 0x100] };
+    /// let mut sensor = Device::new(&mut bus);
+    ///
+    /// sensor.set_als_interrupt(AlsInterrupt::NewSampleReady)?;
+    /// sensor.set_range_interrupt(RangeInterrupt::LevelLow {
+    ///     low: Length::from_millimeters(50.0),
+    /// })?;
+    ///
+    /// assert_eq!(sensor.range_interrupt_mode()?, InterruptMode::LevelLow);
+    /// assert_eq!(sensor.als_interrupt_mode()?, InterruptMode::NewSampleReady);
+    /// # drop(sensor);
+    /// # assert_eq!(bus.regs[0x14], 0x21);
+    /// # Ok::<(), vl6180x::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn set_range_interrupt(&mut self, range: RangeInterrupt) -> Result<(), Error> {
+        if let Some(thresholds) = range.thresholds() {
+            self.write_register(thresholds)?;
+        }
+        self.modify_register(|config: &mut InterruptConfigGpio| {
+            config.range_interrupt = range.mode();
+        })
+    }
+
+    /// Sets the ALS interrupt condition, leaving the range interrupt mode unchanged.
+    ///
+    /// The ALS counterpart of [`set_range_interrupt`](Device::set_range_interrupt).
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn set_als_interrupt(&mut self, als: AlsInterrupt) -> Result<(), Error> {
+        if let Some(thresholds) = als.thresholds() {
+            self.write_register(thresholds)?;
+        }
+        self.modify_register(|config: &mut InterruptConfigGpio| {
+            config.als_interrupt = als.mode();
+        })
+    }
+
+    /// Reads the range interrupt mode.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn range_interrupt_mode(&mut self) -> Result<InterruptMode, Error> {
+        let config: InterruptConfigGpio = self.read_register()?;
+        Ok(config.range_interrupt)
+    }
+
+    /// Reads the ALS interrupt mode.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn als_interrupt_mode(&mut self) -> Result<InterruptMode, Error> {
+        let config: InterruptConfigGpio = self.read_register()?;
+        Ok(config.als_interrupt)
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously reads, clears and queues the pending interrupts.
+    ///
+    /// This is the async version of [`handle_interrupt`](Device::handle_interrupt).
+    pub async fn handle_interrupt_async<const N: usize>(
+        &mut self,
+        queue: &mut EventQueue<N>,
+    ) -> Result<ResultInterruptStatusGpio, Error> {
+        let status: ResultInterruptStatusGpio = self.read_register_async().await?;
+        if let Some(clear) = enqueue(status, queue) {
+            self.write_register_async(clear).await?;
+        }
+        Ok(status)
+    }
+
+    /// Asynchronously reads the pending interrupts without clearing them.
+    ///
+    /// This is the async version of [`peek_interrupt_status`](Device::peek_interrupt_status).
+    pub async fn peek_interrupt_status_async(
+        &mut self,
+    ) -> Result<ResultInterruptStatusGpio, Error> {
+        self.read_register_async().await
+    }
+
+    /// Asynchronously returns the pending interrupt sources without clearing them.
+    ///
+    /// This is the async version of [`pending_interrupts`](Device::pending_interrupts).
+    pub async fn pending_interrupts_async(&mut self) -> Result<InterruptSources, Error> {
+        self.peek_interrupt_status_async()
+            .await
+            .map(InterruptSources::from)
+    }
+
+    /// Asynchronously waits for the GPIO1 interrupt output to assert and
+    /// reads the pending interrupts.
+    ///
+    /// This is the async version of
+    /// [`wait_for_interrupt_pin`](Device::wait_for_interrupt_pin). Instead of
+    /// polling the pin, it waits for the active level through
+    /// [`Wait`](embedded_hal_async::digital::Wait), racing it against a
+    /// single `delay` of `timeout`; the I2C transactions are never cut short
+    /// by the timeout.
+    pub async fn wait_for_interrupt_pin_async<P, D, T>(
+        &mut self,
+        pin: &mut P,
+        delay: &mut D,
+        timeout: T,
+    ) -> Result<ResultInterruptStatusGpio, Error>
+    where
+        P: embedded_hal_async::digital::Wait + embedded_hal::digital::InputPin,
+        D: embedded_hal_async::delay::DelayNs,
+        T: Into<Option<Duration>>,
+    {
+        let timeout = timeout.into().unwrap_or(self.ready_timeout());
+        let gpio: ModeGpio1 = self.read_register_async().await?;
+        let active_high = gpio.polarity == GpioPolarity::ActiveHigh;
+
+        let micros = timeout.as_micros().min(u128::from(u32::MAX)) as u32;
+        let mut expiry = pin!(delay.delay_us(micros));
+        loop {
+            wait_for_level(pin, active_high, expiry.as_mut()).await?;
+            if !pin_active(pin, active_high)? {
+                continue;
+            }
+
+            let status: ResultInterruptStatusGpio = self.read_register_async().await?;
+            if let Some(status) = self.filter_spurious(status)? {
+                return Ok(status);
+            }
+            wait_for_level(pin, !active_high, expiry.as_mut()).await?;
+        }
+    }
+
+    /// Asynchronously configures the range and ALS interrupts and routes them to GPIO1.
+    ///
+    /// This is the async version of [`configure_gpio1_interrupt`](Device::configure_gpio1_interrupt).
+    pub async fn configure_gpio1_interrupt_async(
+        &mut self,
+        polarity: GpioPolarity,
+        range: RangeInterrupt,
+        als: AlsInterrupt,
+    ) -> Result<(), Error> {
+        if let Some(thresholds) = range.thresholds() {
+            self.write_register_async(thresholds).await?;
+        }
+        if let Some(thresholds) = als.thresholds() {
+            self.write_register_async(thresholds).await?;
+        }
+
+        self.write_register_async(InterruptConfigGpio {
+            range_interrupt: range.mode(),
+            als_interrupt: als.mode(),
+        })
+        .await?;
+        self.write_register_async(ModeGpio1 {
+            function: GpioFunction::InterruptOutput,
+            polarity,
+        })
+        .await
+    }
+
+    /// Asynchronously sets the range interrupt condition.
+    ///
+    /// This is the async version of [`set_range_interrupt`](Device::set_range_interrupt).
+    pub async fn set_range_interrupt_async(&mut self, range: RangeInterrupt) -> Result<(), Error> {
+        if let Some(thresholds) = range.thresholds() {
+            self.write_register_async(thresholds).await?;
+        }
+        self.modify_register_async(|config: &mut InterruptConfigGpio| {
+            config.range_interrupt = range.mode();
+        })
+        .await
+    }
+
+    /// Asynchronously sets the ALS interrupt condition.
+    ///
+    /// This is the async version of [`set_als_interrupt`](Device::set_als_interrupt).
+    pub async fn set_als_interrupt_async(&mut self, als: AlsInterrupt) -> Result<(), Error> {
+        if let Some(thresholds) = als.thresholds() {
+            self.write_register_async(thresholds).await?;
+        }
+        self.modify_register_async(|config: &mut InterruptConfigGpio| {
+            config.als_interrupt = als.mode();
+        })
+        .await
+    }
+
+    /// Asynchronously reads the range interrupt mode.
+    ///
+    /// This is the async version of [`range_interrupt_mode`](Device::range_interrupt_mode).
+    pub async fn range_interrupt_mode_async(&mut self) -> Result<InterruptMode, Error> {
+        let config: InterruptConfigGpio = self.read_register_async().await?;
+        Ok(config.range_interrupt)
+    }
+
+    /// Asynchronously reads the ALS interrupt mode.
+    ///
+    /// This is the async version of [`als_interrupt_mode`](Device::als_interrupt_mode).
+    pub async fn als_interrupt_mode_async(&mut self) -> Result<InterruptMode, Error> {
+        let config: InterruptConfigGpio = self.read_register_async().await?;
+        Ok(config.als_interrupt)
+    }
+}
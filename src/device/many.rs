@@ -0,0 +1,119 @@
+//! Reading several registers at once
+//!
+//! Sugar for reading a handful of unrelated registers into a tuple instead of
+//! a ladder of `read_register` calls.
+
+use regiface::ReadableRegister;
+
+use super::{Device, DeviceAddress};
+use crate::types::Error;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A tuple of register types read by [`Device::read_many`]
+///
+/// This trait is sealed and implemented for tuples of one to eight
+/// [`ReadableRegister`] types with a u16 ID.
+#[allow(async_fn_in_trait)]
+pub trait RegisterTuple: sealed::Sealed + Sized {
+    /// Reads every register of the tuple, in order.
+    fn read<I2C, Addr: DeviceAddress>(device: &mut Device<I2C, Addr>) -> Result<Self, Error>
+    where
+        I2C: embedded_hal::i2c::I2c;
+
+    /// Asynchronously reads every register of the tuple, in order.
+    async fn read_async<I2C, Addr: DeviceAddress>(
+        device: &mut Device<I2C, Addr>,
+    ) -> Result<Self, Error>
+    where
+        I2C: embedded_hal_async::i2c::I2c;
+}
+
+macro_rules! register_tuple {
+    ($($r:ident),+) => {
+        impl<$($r),+> sealed::Sealed for ($($r,)+)
+        where
+            $($r: ReadableRegister<IdType = u16>,)+
+        {
+        }
+
+        impl<$($r),+> RegisterTuple for ($($r,)+)
+        where
+            $($r: ReadableRegister<IdType = u16>,)+
+        {
+            fn read<I2C, Addr: DeviceAddress>(device: &mut Device<I2C, Addr>) -> Result<Self, Error>
+            where
+                I2C: embedded_hal::i2c::I2c,
+            {
+                Ok(($(device.read_register::<$r>()?,)+))
+            }
+
+            async fn read_async<I2C, Addr: DeviceAddress>(device: &mut Device<I2C, Addr>) -> Result<Self, Error>
+            where
+                I2C: embedded_hal_async::i2c::I2c,
+            {
+                Ok(($(device.read_register_async::<$r>().await?,)+))
+            }
+        }
+    };
+}
+
+register_tuple!(A);
+register_tuple!(A, B);
+register_tuple!(A, B, C);
+register_tuple!(A, B, C, D);
+register_tuple!(A, B, C, D, E);
+register_tuple!(A, B, C, D, E, F);
+register_tuple!(A, B, C, D, E, F, G);
+register_tuple!(A, B, C, D, E, F, G, H);
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Reads several registers into a tuple.
+    ///
+    /// The registers are read one after the other with
+    /// [`read_register`](Device::read_register), in the order of the tuple,
+    /// stopping at the first error. Costs one I2C transaction per register
+    /// not served from the configuration cache; unlike the block registers
+    /// such as [`RangeStatusBlock`](crate::registers::RangeStatusBlock), the
+    /// values need not belong to the same sample.
+    ///
+    /// # Type Parameters
+    /// * `T` - Tuple of up to eight register types
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::DeserializationError` - Failed to parse a register value
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use embedded_hal::i2c::I2c;
+    /// # use vl6180x::Device;
+    /// use vl6180x::registers::{AlsAnalogueGain, RangeResultStatus, RangeResultValue};
+    ///
+    /// # fn example<I2C: I2c>(device: &mut Device<I2C>) -> Result<(), vl6180x::Error> {
+    /// let (status, value, gain) =
+    ///     device.read_many::<(RangeResultStatus, RangeResultValue, AlsAnalogueGain)>()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_many<T: RegisterTuple>(&mut self) -> Result<T, Error> {
+        T::read(self)
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously reads several registers into a tuple.
+    ///
+    /// This is the async version of [`read_many`](Device::read_many).
+    pub async fn read_many_async<T: RegisterTuple>(&mut self) -> Result<T, Error> {
+        T::read_async(self).await
+    }
+}
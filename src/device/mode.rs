@@ -0,0 +1,624 @@
+//! Typestate measurement modes
+//!
+//! Configuration access only while no continuous measurement is running.
+
+// A failed transition hands the device back by value, so its error is as large
+// as the device; there is no allocator to box it in.
+#![allow(clippy::result_large_err)]
+
+use core::fmt;
+use core::marker::PhantomData;
+
+use measurements::Length;
+use regiface::ReadableRegister;
+
+use super::continuous::ContinuousKind;
+use super::range::CLEAR_RANGE;
+use super::{Device, DeviceAddress, RuntimeAddress};
+use crate::events::EventQueue;
+use crate::registers::{
+    DatasheetLimits, InterruptClear, RangeStatusBlock, ResultInterruptStatusGpio, ResultRegister,
+};
+use crate::types::{Error, Luminance, RangeReading};
+
+/// No measurement is running; the device may be configured
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Idle;
+
+/// Continuous ranging is running
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ContinuousRanging;
+
+/// Continuous ALS measurements are running
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ContinuousAls;
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for super::Idle {}
+    impl Sealed for super::ContinuousRanging {}
+    impl Sealed for super::ContinuousAls {}
+}
+
+/// A measurement mode of a [`TypedDevice`]
+///
+/// This trait is sealed and implemented for [`Idle`], [`ContinuousRanging`]
+/// and [`ContinuousAls`].
+pub trait Mode: sealed::Sealed {}
+
+impl Mode for Idle {}
+impl Mode for ContinuousRanging {}
+impl Mode for ContinuousAls {}
+
+/// A mode in which a continuous measurement is running
+///
+/// This trait is sealed and implemented for [`ContinuousRanging`] and
+/// [`ContinuousAls`].
+pub trait Continuous: Mode {}
+
+impl Continuous for ContinuousRanging {}
+impl Continuous for ContinuousAls {}
+
+/// A failed mode transition
+///
+/// Hands back the device in its original mode together with the error, so the
+/// caller can retry or fall back to the untyped [`Device`].
+pub struct TransitionError<T> {
+    /// The device, still in the mode it was in before the transition
+    pub device: T,
+    /// Why the transition failed
+    pub error: Error,
+}
+
+impl<T> fmt::Debug for TransitionError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TransitionError")
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Display for TransitionError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "mode transition failed: {}", self.error)
+    }
+}
+
+impl<T> From<TransitionError<T>> for Error {
+    fn from(error: TransitionError<T>) -> Self {
+        error.error
+    }
+}
+
+/// A [`Device`] whose measurement mode is tracked in its type
+///
+/// The sensor must not be reconfigured while it is measuring continuously:
+/// configuration writes made during a running measurement are silently
+/// ignored or leave the device in an inconsistent state. This wrapper tracks
+/// the measurement mode in its type so that configuration access only exists
+/// while the device is [`Idle`]. Starting continuous measurements consumes
+/// the idle device and returns one in [`ContinuousRanging`] or
+/// [`ContinuousAls`] mode, which can only read results and handle interrupts
+/// until it is stopped again.
+///
+/// The untyped [`Device`] remains available as an escape hatch through
+/// [`TypedDevice::into_inner`].
+///
+/// # Example
+/// ```no_run
+/// use core::time::Duration;
+/// use embedded_hal::{delay::DelayNs, i2c::I2c};
+/// use vl6180x::{
+///     registers::{RangeIntermeasurementPeriod, RangeResultValue},
+///     Device,
+/// };
+///
+/// fn run<I2C: I2c, D: DelayNs>(i2c: I2C, delay: &mut D) -> Result<(), vl6180x::Error> {
+///     let mut sensor = Device::new(i2c).into_idle();
+///     sensor.write_register(RangeIntermeasurementPeriod {
+///         period: Duration::from_millis(100),
+///     })?;
+///
+///     let mut ranging = sensor.start_continuous_ranging()?;
+///     let _distance: RangeResultValue = ranging.read_register()?;
+///
+///     let _sensor = ranging.stop(delay)?;
+///     Ok(())
+/// }
+/// ```
+///
+/// Configuration is not available while measuring:
+/// ```compile_fail
+/// use embedded_hal::i2c::I2c;
+/// use vl6180x::{
+///     device::{ContinuousRanging, TypedDevice},
+///     registers::RangeIntermeasurementPeriod,
+/// };
+///
+/// fn reconfigure<I2C: I2c>(
+///     sensor: &mut TypedDevice<I2C, ContinuousRanging>,
+///     period: RangeIntermeasurementPeriod,
+/// ) -> Result<(), vl6180x::Error> {
+///     sensor.write_register(period)
+/// }
+/// ```
+pub struct TypedDevice<I2C, MODE: Mode, A: DeviceAddress = RuntimeAddress> {
+    device: Device<I2C, A>,
+    mode: PhantomData<MODE>,
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A> {
+    /// Wraps the device in a [`TypedDevice`] in [`Idle`] mode.
+    ///
+    /// The device must not be measuring continuously; stop any running
+    /// measurement first.
+    pub fn into_idle(self) -> TypedDevice<I2C, Idle, A> {
+        TypedDevice::wrap(self)
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Starts continuous ranging and returns the device in
+    /// [`ContinuousRanging`] mode.
+    ///
+    /// Shorthand for [`into_idle`](Device::into_idle) followed by
+    /// [`start_continuous_ranging`](TypedDevice::start_continuous_ranging),
+    /// which starts ranging with
+    /// [`start_continuous_range`](Device::start_continuous_range). Until
+    /// [`stop`](TypedDevice::stop) hands the device back, only results can be
+    /// read and interrupts cleared.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use embedded_hal::{delay::DelayNs, i2c::I2c};
+    /// use vl6180x::{Device, Error};
+    ///
+    /// fn sample<I2C: I2c, D: DelayNs>(sensor: Device<I2C>, delay: &mut D) -> Result<Device<I2C>, Error> {
+    ///     let mut ranging = sensor.into_continuous_range()?;
+    ///     delay.delay_ms(100);
+    ///     let _reading = ranging.read_latest()?;
+    ///     ranging.clear_interrupt()?;
+    ///     Ok(ranging.stop(delay)?.into_inner())
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// The device is returned in the [`TransitionError`]:
+    /// * `Error::DeviceBusy` - Interleaved mode is running
+    /// * `Error::BusError` - I2C communication failed
+    pub fn into_continuous_range(
+        self,
+    ) -> Result<TypedDevice<I2C, ContinuousRanging, A>, TransitionError<Self>> {
+        self.into_idle()
+            .start_continuous_ranging()
+            .map_err(TransitionError::into_inner)
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously starts continuous ranging and returns the device in
+    /// [`ContinuousRanging`] mode.
+    ///
+    /// This is the async version of [`into_continuous_range`](Device::into_continuous_range).
+    pub async fn into_continuous_range_async(
+        self,
+    ) -> Result<TypedDevice<I2C, ContinuousRanging, A>, TransitionError<Self>> {
+        self.into_idle()
+            .start_continuous_ranging_async()
+            .await
+            .map_err(TransitionError::into_inner)
+    }
+}
+
+impl<I2C, MODE: Mode, A: DeviceAddress> TransitionError<TypedDevice<I2C, MODE, A>> {
+    /// Unwraps the typed device of a failed transition
+    fn into_inner(self) -> TransitionError<Device<I2C, A>> {
+        TransitionError {
+            device: self.device.into_inner(),
+            error: self.error,
+        }
+    }
+}
+
+impl<I2C, MODE: Mode, A: DeviceAddress> TypedDevice<I2C, MODE, A> {
+    fn wrap(device: Device<I2C, A>) -> Self {
+        Self {
+            device,
+            mode: PhantomData,
+        }
+    }
+
+    fn fail<T>(self, error: Error) -> Result<T, TransitionError<Self>> {
+        Err(TransitionError {
+            device: self,
+            error,
+        })
+    }
+
+    /// Returns the untyped device.
+    ///
+    /// The measurement mode is no longer tracked; a running continuous
+    /// measurement keeps running.
+    pub fn into_inner(self) -> Device<I2C, A> {
+        self.device
+    }
+}
+
+impl<I2C, A: DeviceAddress> TypedDevice<I2C, Idle, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Reads a register value from the device.
+    ///
+    /// See [`Device::read_register`].
+    pub fn read_register<R>(&mut self) -> Result<R, Error>
+    where
+        R: ReadableRegister<IdType = u16>,
+    {
+        self.device.read_register()
+    }
+
+    /// Writes a value to a device register.
+    ///
+    /// Continuous measurements should be started with
+    /// [`start_continuous_ranging`](TypedDevice::start_continuous_ranging) and
+    /// [`start_continuous_als`](TypedDevice::start_continuous_als) rather than
+    /// by writing the start registers directly.
+    ///
+    /// See [`Device::write_register`].
+    pub fn write_register<R>(&mut self, register: R) -> Result<(), Error>
+    where
+        R: DatasheetLimits,
+    {
+        self.device.write_register(register)
+    }
+
+    /// Performs a single-shot range measurement.
+    ///
+    /// See [`Device::measure_range_single`].
+    pub fn measure_range_single<D>(&mut self, delay: &mut D) -> Result<Length, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        self.device.measure_range_single(delay)
+    }
+
+    /// Performs a single-shot ALS measurement.
+    ///
+    /// See [`Device::measure_als_single`].
+    pub fn measure_als_single<D>(&mut self, delay: &mut D) -> Result<Luminance, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        self.device.measure_als_single(delay)
+    }
+
+    /// Starts continuous ranging.
+    ///
+    /// See [`Device::start_continuous_range`].
+    ///
+    /// # Errors
+    /// The idle device is returned in the [`TransitionError`]:
+    /// * `Error::DeviceBusy` - Interleaved mode is running
+    /// * `Error::BusError` - I2C communication failed
+    pub fn start_continuous_ranging(
+        mut self,
+    ) -> Result<TypedDevice<I2C, ContinuousRanging, A>, TransitionError<Self>> {
+        match self.device.start_continuous_range() {
+            Ok(()) => Ok(TypedDevice::wrap(self.device)),
+            Err(error) => self.fail(error),
+        }
+    }
+
+    /// Starts continuous ALS measurements.
+    ///
+    /// The configured intermeasurement period is first validated against the
+    /// integration period, see
+    /// [`AlsIntegrationPeriod::min_intermeasurement_period`](crate::registers::AlsIntegrationPeriod::min_intermeasurement_period).
+    ///
+    /// # Errors
+    /// The idle device is returned in the [`TransitionError`]:
+    /// * `Error::PeriodTooShort` - The intermeasurement period is too short
+    ///   for the integration period
+    /// * `Error::BusError` - I2C communication failed
+    pub fn start_continuous_als(
+        mut self,
+    ) -> Result<TypedDevice<I2C, ContinuousAls, A>, TransitionError<Self>> {
+        let started = self
+            .device
+            .check_als_period()
+            .and_then(|()| self.device.begin_continuous(ContinuousKind::Als));
+        match started {
+            Ok(()) => Ok(TypedDevice::wrap(self.device)),
+            Err(error) => self.fail(error),
+        }
+    }
+}
+
+impl<I2C, MODE, A: DeviceAddress> TypedDevice<I2C, MODE, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+    MODE: Continuous,
+{
+    /// Reads a result register from the device.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::DeserializationError` - Failed to parse register value
+    pub fn read_register<R>(&mut self) -> Result<R, Error>
+    where
+        R: ResultRegister,
+    {
+        self.device.read_register()
+    }
+
+    /// Clears the selected interrupt flags.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn clear_interrupts(&mut self, clear: InterruptClear) -> Result<(), Error> {
+        self.device.write_register(clear)
+    }
+
+    /// Reads, clears and queues the pending interrupts.
+    ///
+    /// See [`Device::handle_interrupt`].
+    pub fn handle_interrupt<const N: usize>(
+        &mut self,
+        queue: &mut EventQueue<N>,
+    ) -> Result<ResultInterruptStatusGpio, Error> {
+        self.device.handle_interrupt(queue)
+    }
+}
+
+impl<I2C, A: DeviceAddress> TypedDevice<I2C, ContinuousRanging, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Reads the status and distance of the latest range sample in one
+    /// transaction.
+    ///
+    /// The sample is classified as by [`RangeReading::new`]. Whether it is
+    /// new is reported by the range interrupt, see
+    /// [`handle_interrupt`](TypedDevice::handle_interrupt).
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn read_latest(&mut self) -> Result<RangeReading, Error> {
+        let block: RangeStatusBlock = self.device.read_register()?;
+        Ok(RangeReading::new(block.status.error_code, block.distance))
+    }
+
+    /// Clears the range interrupt, acknowledging the latest sample.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn clear_interrupt(&mut self) -> Result<(), Error> {
+        self.device.write_register(CLEAR_RANGE)
+    }
+
+    /// Stops continuous ranging.
+    ///
+    /// Waits for the ranging core to finish the measurement in progress, see
+    /// [`Device::stop_continuous_range`].
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::Timeout` - The ranging core did not stop
+    ///
+    /// On error the device is returned still in continuous ranging mode.
+    pub fn stop<D>(
+        mut self,
+        delay: &mut D,
+    ) -> Result<TypedDevice<I2C, Idle, A>, TransitionError<Self>>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        match self.device.stop_continuous_range(delay) {
+            Ok(()) => Ok(TypedDevice::wrap(self.device)),
+            Err(error) => self.fail(error),
+        }
+    }
+}
+
+impl<I2C, A: DeviceAddress> TypedDevice<I2C, ContinuousAls, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Stops continuous ALS measurements.
+    ///
+    /// Waits for the ALS core to finish the integration in progress, see
+    /// [`Device::stop_continuous_als`].
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::Timeout` - The ALS core did not stop
+    ///
+    /// On error the device is returned still in continuous ALS mode.
+    pub fn stop<D>(
+        mut self,
+        delay: &mut D,
+    ) -> Result<TypedDevice<I2C, Idle, A>, TransitionError<Self>>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        match self.device.stop_continuous_als(delay) {
+            Ok(()) => Ok(TypedDevice::wrap(self.device)),
+            Err(error) => self.fail(error),
+        }
+    }
+}
+
+impl<I2C, A: DeviceAddress> TypedDevice<I2C, Idle, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously reads a register value from the device.
+    ///
+    /// This is the async version of [`read_register`](TypedDevice::read_register).
+    pub async fn read_register_async<R>(&mut self) -> Result<R, Error>
+    where
+        R: ReadableRegister<IdType = u16>,
+    {
+        self.device.read_register_async().await
+    }
+
+    /// Asynchronously writes a value to a device register.
+    ///
+    /// This is the async version of [`write_register`](TypedDevice::write_register).
+    pub async fn write_register_async<R>(&mut self, register: R) -> Result<(), Error>
+    where
+        R: DatasheetLimits,
+    {
+        self.device.write_register_async(register).await
+    }
+
+    /// Asynchronously performs a single-shot range measurement.
+    ///
+    /// This is the async version of [`measure_range_single`](TypedDevice::measure_range_single).
+    pub async fn measure_range_single_async<D>(&mut self, delay: &mut D) -> Result<Length, Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        self.device.measure_range_single_async(delay).await
+    }
+
+    /// Asynchronously performs a single-shot ALS measurement.
+    ///
+    /// This is the async version of [`measure_als_single`](TypedDevice::measure_als_single).
+    pub async fn measure_als_single_async<D>(&mut self, delay: &mut D) -> Result<Luminance, Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        self.device.measure_als_single_async(delay).await
+    }
+
+    /// Asynchronously starts continuous ranging.
+    ///
+    /// This is the async version of [`start_continuous_ranging`](TypedDevice::start_continuous_ranging).
+    pub async fn start_continuous_ranging_async(
+        mut self,
+    ) -> Result<TypedDevice<I2C, ContinuousRanging, A>, TransitionError<Self>> {
+        match self.device.start_continuous_range_async().await {
+            Ok(()) => Ok(TypedDevice::wrap(self.device)),
+            Err(error) => self.fail(error),
+        }
+    }
+
+    /// Asynchronously starts continuous ALS measurements.
+    ///
+    /// This is the async version of [`start_continuous_als`](TypedDevice::start_continuous_als).
+    pub async fn start_continuous_als_async(
+        mut self,
+    ) -> Result<TypedDevice<I2C, ContinuousAls, A>, TransitionError<Self>> {
+        let started = match self.device.check_als_period_async().await {
+            Ok(()) => {
+                self.device
+                    .begin_continuous_async(ContinuousKind::Als)
+                    .await
+            }
+            Err(error) => Err(error),
+        };
+        match started {
+            Ok(()) => Ok(TypedDevice::wrap(self.device)),
+            Err(error) => self.fail(error),
+        }
+    }
+}
+
+impl<I2C, MODE, A: DeviceAddress> TypedDevice<I2C, MODE, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+    MODE: Continuous,
+{
+    /// Asynchronously reads a result register from the device.
+    ///
+    /// This is the async version of [`read_register`](TypedDevice::read_register).
+    pub async fn read_register_async<R>(&mut self) -> Result<R, Error>
+    where
+        R: ResultRegister,
+    {
+        self.device.read_register_async().await
+    }
+
+    /// Asynchronously clears the selected interrupt flags.
+    ///
+    /// This is the async version of [`clear_interrupts`](TypedDevice::clear_interrupts).
+    pub async fn clear_interrupts_async(&mut self, clear: InterruptClear) -> Result<(), Error> {
+        self.device.write_register_async(clear).await
+    }
+
+    /// Asynchronously reads, clears and queues the pending interrupts.
+    ///
+    /// This is the async version of [`handle_interrupt`](TypedDevice::handle_interrupt).
+    pub async fn handle_interrupt_async<const N: usize>(
+        &mut self,
+        queue: &mut EventQueue<N>,
+    ) -> Result<ResultInterruptStatusGpio, Error> {
+        self.device.handle_interrupt_async(queue).await
+    }
+}
+
+impl<I2C, A: DeviceAddress> TypedDevice<I2C, ContinuousRanging, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously reads the latest range sample.
+    ///
+    /// This is the async version of [`read_latest`](TypedDevice::read_latest).
+    pub async fn read_latest_async(&mut self) -> Result<RangeReading, Error> {
+        let block: RangeStatusBlock = self.device.read_register_async().await?;
+        Ok(RangeReading::new(block.status.error_code, block.distance))
+    }
+
+    /// Asynchronously clears the range interrupt.
+    ///
+    /// This is the async version of [`clear_interrupt`](TypedDevice::clear_interrupt).
+    pub async fn clear_interrupt_async(&mut self) -> Result<(), Error> {
+        self.device.write_register_async(CLEAR_RANGE).await
+    }
+
+    /// Asynchronously stops continuous ranging.
+    ///
+    /// This is the async version of [`stop`](TypedDevice::stop).
+    pub async fn stop_async<D>(
+        mut self,
+        delay: &mut D,
+    ) -> Result<TypedDevice<I2C, Idle, A>, TransitionError<Self>>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        match self.device.stop_continuous_range_async(delay).await {
+            Ok(()) => Ok(TypedDevice::wrap(self.device)),
+            Err(error) => self.fail(error),
+        }
+    }
+}
+
+impl<I2C, A: DeviceAddress> TypedDevice<I2C, ContinuousAls, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously stops continuous ALS measurements.
+    ///
+    /// This is the async version of [`stop`](TypedDevice::stop).
+    pub async fn stop_async<D>(
+        mut self,
+        delay: &mut D,
+    ) -> Result<TypedDevice<I2C, Idle, A>, TransitionError<Self>>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        match self.device.stop_continuous_als_async(delay).await {
+            Ok(()) => Ok(TypedDevice::wrap(self.device)),
+            Err(error) => self.fail(error),
+        }
+    }
+}
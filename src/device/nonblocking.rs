@@ -0,0 +1,129 @@
+//! Non-blocking measurements in the `nb` style
+//!
+//! The readers start a single-shot measurement on their first call and
+//! return [`nb::Error::WouldBlock`] until its sample is ready, so they can be
+//! wrapped in `nb::block!` or polled from a super loop.
+
+use measurements::Length;
+
+use super::als::{als_result, CLEAR_ALS};
+use super::health::Measurement;
+use super::range::{range_result, CLEAR_RANGE};
+use super::{Device, DeviceAddress};
+use crate::registers::{
+    AlsResultBlock, AlsStart, RangeResultStatus, RangeResultValue, RangeStart,
+    ResultInterruptStatusGpio,
+};
+use crate::types::{Error, Luminance};
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Reads the distance of a single-shot measurement without blocking.
+    ///
+    /// The first call starts a measurement and returns `WouldBlock`. Later
+    /// calls poll the interrupt status once and return `WouldBlock` until the
+    /// sample is ready, then read it, clear the range interrupt and return
+    /// the distance; the next call starts a new measurement. Any error also
+    /// ends the measurement. There is no timeout; bound the number of calls
+    /// to give up. The range interrupt must be configured for
+    /// [`InterruptMode::NewSampleReady`](crate::types::InterruptMode::NewSampleReady).
+    /// Until the sample is collected, the blocking and async single-shot
+    /// range measurements fail with `Error::DeviceBusy`.
+    ///
+    /// Costs one I2C transaction per call, and three more on the call
+    /// returning the sample.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use embedded_hal::i2c::I2c;
+    /// use measurements::Length;
+    /// use vl6180x::{Device, Error};
+    ///
+    /// fn distance<I2C: I2c>(sensor: &mut Device<I2C>) -> Result<Length, Error> {
+    ///     nb::block!(sensor.try_read_range())
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::RangeError` - The measurement completed with an error code
+    /// * `Error::DeviceBusy` - Continuous ranging was started with
+    ///   [`start_continuous_range`](Device::start_continuous_range)
+    pub fn try_read_range(&mut self) -> nb::Result<Length, Error> {
+        if !self.in_flight.range {
+            self.refuse_during_continuous_range()?;
+            self.write_register(RangeStart::SingleShot)?;
+            self.in_flight.range = true;
+            return Err(nb::Error::WouldBlock);
+        }
+
+        let status = self.read_register::<ResultInterruptStatusGpio>();
+        if matches!(status, Ok(status) if !status.range_interrupt) {
+            return Err(nb::Error::WouldBlock);
+        }
+        self.in_flight.range = false;
+
+        let result = status.and_then(|_| {
+            self.mark_sample();
+            let status: RangeResultStatus = self.read_register()?;
+            let value: RangeResultValue = self.read_register()?;
+            self.write_register(CLEAR_RANGE)?;
+            range_result((status, value))
+        });
+        self.record_measurement(Measurement::Range, &result);
+        result.map_err(nb::Error::Other)
+    }
+
+    /// Reads the distance of a single-shot measurement without blocking.
+    ///
+    /// Another name for [`try_read_range`](Device::try_read_range), next to
+    /// the blocking [`measure_range_single`](Device::measure_range_single).
+    ///
+    /// # Errors
+    /// See [`try_read_range`](Device::try_read_range).
+    pub fn range_single_nb(&mut self) -> nb::Result<Length, Error> {
+        self.try_read_range()
+    }
+
+    /// Reads the light level of a single-shot ALS measurement without blocking.
+    ///
+    /// The ALS counterpart of [`try_read_range`](Device::try_read_range);
+    /// the single-shot ALS measurements fail with `Error::DeviceBusy` until
+    /// the sample is collected.
+    /// Costs one I2C transaction per call, and four more on the call
+    /// returning the sample, two once the gain and integration period are
+    /// cached.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::AlsError` - The measurement completed with an error code
+    /// * `Error::DeviceBusy` - Continuous ALS measurements were started with
+    ///   [`start_continuous_als`](Device::start_continuous_als)
+    pub fn try_read_als(&mut self) -> nb::Result<Luminance, Error> {
+        if !self.in_flight.als {
+            self.refuse_during_continuous_als()?;
+            self.write_register(AlsStart::SingleShot)?;
+            self.in_flight.als = true;
+            return Err(nb::Error::WouldBlock);
+        }
+
+        let status = self.read_register::<ResultInterruptStatusGpio>();
+        if matches!(status, Ok(status) if !status.als_interrupt) {
+            return Err(nb::Error::WouldBlock);
+        }
+        self.in_flight.als = false;
+
+        let result = status.and_then(|_| {
+            self.mark_sample();
+            let block: AlsResultBlock = self.read_register()?;
+            self.write_register(CLEAR_ALS)?;
+            let gain = self.read_register()?;
+            let integration = self.read_register()?;
+            als_result((block.status, block.value, gain, integration))
+        });
+        self.record_measurement(Measurement::Als, &result);
+        result.map_err(nb::Error::Other)
+    }
+}
@@ -0,0 +1,114 @@
+//! Paranoid result reads
+//!
+//! Reading measurement results twice and comparing them, to catch bytes
+//! corrupted on a noisy bus that still acknowledges every transfer.
+
+use core::ops::RangeInclusive;
+
+use regiface::{ByteArray, ReadableRegister};
+
+use super::{Device, DeviceAddress};
+use crate::types::{Access, Error, InconsistentRead};
+
+/// Result registers (0x04D - 0x080), the only reads that are verified
+const RESULT_REGISTERS: RangeInclusive<u16> = 0x004D..=0x0080;
+
+/// Settles a mismatch between the first two reads with a third one
+///
+/// Keeps the value two of the three reads agree on in `first`, and fails if
+/// all three differ.
+fn tie_break(address: u16, first: &mut [u8], second: &[u8], third: &[u8]) -> Result<(), Error> {
+    if third == second {
+        first.copy_from_slice(third);
+        Ok(())
+    } else if third == first {
+        Ok(())
+    } else {
+        Err(Error::InconsistentRead(InconsistentRead::between(
+            address, first, second,
+        )))
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A> {
+    /// Enables or disables paranoid result reads.
+    ///
+    /// On a noisy bus a byte can be corrupted without the transfer failing.
+    /// In paranoid mode every typed read of a result register (0x04D -
+    /// 0x080), such as [`RangeResultValue`](crate::registers::RangeResultValue),
+    /// [`AlsResultValue`](crate::registers::AlsResultValue) and the status
+    /// registers, is issued twice back-to-back. If the two reads differ a
+    /// third read breaks the tie, and the read fails with
+    /// `Error::InconsistentRead` if it matches neither. Configuration reads
+    /// and raw reads are issued once. Paranoid mode is off by default.
+    ///
+    /// A result can legitimately change between the reads when a new sample
+    /// lands in between, e.g. in continuous mode, which also shows up as a
+    /// mismatch settled by the third read.
+    pub fn set_paranoid(&mut self, enabled: bool) {
+        self.paranoid = enabled;
+    }
+
+    /// Returns whether paranoid result reads are enabled, see
+    /// [`set_paranoid`](Device::set_paranoid).
+    pub fn is_paranoid(&self) -> bool {
+        self.paranoid
+    }
+
+    /// Returns whether reads of `R` are verified
+    fn verifies<R: ReadableRegister<IdType = u16>>(&self) -> bool {
+        self.paranoid && RESULT_REGISTERS.contains(&R::id())
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Reads `R` again in paranoid mode and settles any mismatch with `buf`
+    pub(super) fn verify_read<R>(&mut self, buf: &mut R::Array) -> Result<(), Error>
+    where
+        R: ReadableRegister<IdType = u16>,
+    {
+        if !self.verifies::<R>() {
+            return Ok(());
+        }
+
+        let mut second = R::Array::new();
+        self.read_into(R::id(), Access::Register, second.as_mut())?;
+        if second.as_ref() == buf.as_ref() {
+            return Ok(());
+        }
+
+        let mut third = R::Array::new();
+        self.read_into(R::id(), Access::Register, third.as_mut())?;
+        tie_break(R::id(), buf.as_mut(), second.as_ref(), third.as_ref())
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously reads `R` again in paranoid mode and settles any mismatch with `buf`
+    pub(super) async fn verify_read_async<R>(&mut self, buf: &mut R::Array) -> Result<(), Error>
+    where
+        R: ReadableRegister<IdType = u16>,
+    {
+        if !self.verifies::<R>() {
+            return Ok(());
+        }
+
+        let mut second = R::Array::new();
+        self.read_into_async(R::id(), Access::Register, second.as_mut())
+            .await?;
+        if second.as_ref() == buf.as_ref() {
+            return Ok(());
+        }
+
+        let mut third = R::Array::new();
+        self.read_into_async(R::id(), Access::Register, third.as_mut())
+            .await?;
+        tie_break(R::id(), buf.as_mut(), second.as_ref(), third.as_ref())
+    }
+}
@@ -0,0 +1,183 @@
+//! Taking a device apart and putting it back together
+//!
+//! Lets the bus be handed to another driver for a while without losing the
+//! device's address and settings, or be borrowed by the device instead of
+//! owned.
+
+use super::cache::ConfigCache;
+use super::continuous::InFlight;
+use super::profile::ActiveProfile;
+use super::recovery::RecoveryHook;
+#[cfg(feature = "bus-stats")]
+use super::BusStats;
+#[cfg(feature = "stats")]
+use super::HealthStats;
+use super::{Address, Device, DeviceAddress, Profile, RuntimeAddress};
+use crate::clock::ClockRef;
+use crate::types::{AdaptiveTiming, Error, SpuriousInterruptPolicy, Timeouts};
+
+/// Everything a [`Device`] holds besides its bus
+///
+/// Returned by [`Device::into_parts`] and consumed by [`Device::from_parts`].
+/// Carries the I2C address, strict mode, the busy check, paranoid reads, the
+/// spurious interrupt policy, the cached configuration registers, the
+/// timeouts, the adaptive timing policy, the clock, the bus recovery hook, the
+/// loaded and active profiles, the measurements started by the non-blocking
+/// readers, the continuous measurements started by
+/// [`Device::start_continuous_range`] and [`Device::start_continuous_als`]
+/// and interleaved mode, the last error of a dropped measurement guard, and the bus and health
+/// counters when their features are enabled.
+///
+/// A device with its address fixed at compile time, such as a
+/// [`StaticDevice`](super::StaticDevice), has a state of the same address
+/// type, so it can only be put back together at that address.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceState<A: DeviceAddress = RuntimeAddress> {
+    address: A,
+    #[cfg(feature = "bus-stats")]
+    stats: BusStats,
+    #[cfg(feature = "stats")]
+    health: HealthStats,
+    last_drop_error: Option<Error>,
+    strict: bool,
+    busy_check: bool,
+    paranoid: bool,
+    spurious_policy: SpuriousInterruptPolicy,
+    cache: ConfigCache,
+    timeouts: Timeouts,
+    adaptive_timing: Option<AdaptiveTiming>,
+    clock: Option<ClockRef>,
+    bus_recovery: Option<RecoveryHook>,
+    in_flight: InFlight,
+    profiles: &'static [Profile],
+    active_profile: Option<ActiveProfile>,
+}
+
+impl<A: DeviceAddress> DeviceState<A> {
+    /// Returns the 7-bit I2C address of the device.
+    pub fn address(&self) -> u8 {
+        self.address.get()
+    }
+
+    /// Returns the timeouts of the device, see [`Device::set_timeouts`].
+    pub fn timeouts(&self) -> Timeouts {
+        self.timeouts
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A> {
+    /// Splits the device into its bus and its state.
+    ///
+    /// Unlike [`release`](Device::release), which drops the state, the
+    /// returned [`DeviceState`] restores an equivalent device with
+    /// [`from_parts`](Device::from_parts) once the bus is available again.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use embedded_hal::i2c::I2c;
+    /// use vl6180x::Device;
+    ///
+    /// fn lend_bus<I2C: I2c>(sensor: Device<I2C>, other: impl FnOnce(&mut I2C)) -> Device<I2C> {
+    ///     let (mut i2c, state) = sensor.into_parts();
+    ///     other(&mut i2c);
+    ///     Device::from_parts(i2c, state)
+    /// }
+    /// ```
+    pub fn into_parts(self) -> (I2C, DeviceState<A>) {
+        let state = DeviceState {
+            address: self.address,
+            #[cfg(feature = "bus-stats")]
+            stats: self.stats,
+            #[cfg(feature = "stats")]
+            health: self.health,
+            last_drop_error: self.last_drop_error,
+            strict: self.strict,
+            busy_check: self.busy_check,
+            paranoid: self.paranoid,
+            spurious_policy: self.spurious_policy,
+            cache: self.cache,
+            timeouts: self.timeouts,
+            adaptive_timing: self.adaptive_timing,
+            clock: self.clock,
+            bus_recovery: self.bus_recovery,
+            in_flight: self.in_flight,
+            profiles: self.profiles,
+            active_profile: self.active_profile,
+        };
+        (self.i2c, state)
+    }
+
+    /// Reassembles a device from a bus and the state returned by
+    /// [`into_parts`](Device::into_parts).
+    ///
+    /// # Arguments
+    /// * `i2c` - An I2C interface implementing the required embedded-hal traits
+    /// * `state` - State of the device taken apart
+    pub fn from_parts(i2c: I2C, state: DeviceState<A>) -> Self {
+        Self {
+            i2c,
+            address: state.address,
+            #[cfg(feature = "bus-stats")]
+            stats: state.stats,
+            #[cfg(feature = "stats")]
+            health: state.health,
+            last_drop_error: state.last_drop_error,
+            strict: state.strict,
+            busy_check: state.busy_check,
+            paranoid: state.paranoid,
+            spurious_policy: state.spurious_policy,
+            cache: state.cache,
+            timeouts: state.timeouts,
+            adaptive_timing: state.adaptive_timing,
+            clock: state.clock,
+            bus_recovery: state.bus_recovery,
+            last_sample_time: None,
+            operation: None,
+            in_flight: state.in_flight,
+            profiles: state.profiles,
+            active_profile: state.active_profile,
+        }
+    }
+}
+
+impl<'a, I2C> Device<&'a mut I2C> {
+    /// Creates a device borrowing the bus, with the default I2C address (0x29).
+    ///
+    /// `&mut I2C` implements the blocking and async embedded-hal I2C traits
+    /// whenever `I2C` does, so the device works the same as over an owned
+    /// bus. Several drivers can then take turns on one bus without a bus
+    /// manager: the borrow ends when the device is dropped or
+    /// [`release`](Device::release)d. Settings survive from one borrow to
+    /// the next with [`into_parts`](Device::into_parts) and
+    /// [`from_parts`](Device::from_parts).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use embedded_hal::i2c::I2c;
+    /// use vl6180x::registers::ModelId;
+    /// use vl6180x::Device;
+    ///
+    /// fn check_both<I2C: I2c>(i2c: &mut I2C) -> Result<(), vl6180x::Error> {
+    ///     let _left: ModelId = Device::on_bus(i2c).read_register()?;
+    ///     let _right: ModelId = Device::on_bus_with_address(i2c, 0x30).read_register()?;
+    ///     // The bus is available to other drivers again
+    ///     i2c.write(0x68, &[0x6B, 0x00]).ok();
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn on_bus(i2c: &'a mut I2C) -> Self {
+        Self::new(i2c)
+    }
+
+    /// Creates a device borrowing the bus, with a custom I2C address.
+    ///
+    /// See [`on_bus`](Device::on_bus).
+    ///
+    /// # Arguments
+    /// * `i2c` - The bus to borrow
+    /// * `address` - Custom I2C address, a `u8` being taken as 7-bit, see
+    ///   [`Address`]
+    pub fn on_bus_with_address(i2c: &'a mut I2C, address: impl Into<Address>) -> Self {
+        Self::new_with_address(i2c, address)
+    }
+}
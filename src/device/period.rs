@@ -0,0 +1,254 @@
+//! Intermeasurement period updates
+//!
+//! Changing the sample rate of continuous measurements without tearing them
+//! down in application code.
+
+use core::time::Duration;
+
+use super::continuous::ContinuousKind;
+use super::{wire, Device, DeviceAddress};
+use crate::registers::{
+    AlsIntegrationPeriod, AlsIntermeasurementPeriod, DatasheetLimits, GroupedParameterHold,
+    RangeIntermeasurementPeriod, RangeMaxConvergenceTime,
+};
+use crate::types::{Error, PeriodTooShort, PeriodUpdate};
+
+/// Parameter hold value telling the firmware not to copy the configuration
+pub(crate) const HOLD: GroupedParameterHold = GroupedParameterHold { hold: true };
+
+/// Parameter hold value releasing the configuration to the firmware
+pub(crate) const RELEASE: GroupedParameterHold = GroupedParameterHold { hold: false };
+
+/// Fails with `Error::PeriodTooShort` if `period` is shorter than `required`
+/// and with `Error::SerializationError` if the register cannot encode it
+pub(super) fn check_period<R: DatasheetLimits + Copy>(
+    register: R,
+    period: Duration,
+    required: Duration,
+) -> Result<R, Error> {
+    if period < required {
+        return Err(Error::PeriodTooShort(PeriodTooShort { period, required }));
+    }
+    wire::encode(register)?;
+    Ok(register)
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Changes the ranging intermeasurement period, restarting continuous
+    /// ranging if it is running.
+    ///
+    /// The period is validated against the estimated duration of one range
+    /// measurement with the configured convergence limit, see
+    /// [`RangeMaxConvergenceTime::measurement_time`].
+    ///
+    /// Unless continuous ranging started by this driver runs, the period is
+    /// written in place inside a [`GroupedParameterHold`] and applies from
+    /// the next start. Otherwise continuous ranging is stopped, reconfigured
+    /// and restarted: the
+    /// datasheet does not list the intermeasurement period among the
+    /// registers the firmware picks up from a parameter hold while running.
+    /// The returned [`PeriodUpdate`] reports which of the two happened. A
+    /// single-shot measurement in progress is left alone.
+    ///
+    /// # Arguments
+    /// * `delay` - Delay provider used while waiting for the ranging core to stop
+    /// * `period` - New intermeasurement period
+    ///
+    /// # Errors
+    /// * `Error::DeviceBusy` - Interleaved mode runs
+    /// * `Error::PeriodTooShort` - `period` is shorter than one measurement
+    /// * `Error::SerializationError` - `period` is outside 10ms to 2560ms
+    /// * `Error::Timeout` - The ranging core did not stop
+    /// * `Error::BusError` - I2C communication failed
+    pub fn update_range_period<D>(
+        &mut self,
+        delay: &mut D,
+        period: Duration,
+    ) -> Result<PeriodUpdate, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        if self.in_flight.interleaved {
+            return Err(Error::DeviceBusy);
+        }
+        let limit: RangeMaxConvergenceTime = self.read_register()?;
+        let register = check_period(
+            RangeIntermeasurementPeriod { period },
+            period,
+            limit.measurement_time(),
+        )?;
+
+        if !self.in_flight.continuous_range {
+            self.write_held(register)?;
+            return Ok(PeriodUpdate::InPlace);
+        }
+
+        self.halt_continuous_range(delay)?;
+        let written = self.write_register(register);
+        self.begin_continuous(ContinuousKind::Range)?;
+        written.map(|()| PeriodUpdate::Restarted)
+    }
+
+    /// Changes the ALS intermeasurement period, restarting continuous ALS
+    /// measurements if they are running.
+    ///
+    /// The ALS counterpart of [`update_range_period`](Device::update_range_period).
+    /// The period is validated against the configured integration period,
+    /// see [`AlsIntegrationPeriod::min_intermeasurement_period`].
+    ///
+    /// # Arguments
+    /// * `delay` - Delay provider used while waiting for the ALS core to stop
+    /// * `period` - New intermeasurement period
+    ///
+    /// # Errors
+    /// * `Error::DeviceBusy` - Interleaved mode runs
+    /// * `Error::PeriodTooShort` - `period` is too short for the integration period
+    /// * `Error::SerializationError` - `period` is outside 10ms to 2560ms
+    /// * `Error::Timeout` - The ALS core did not stop
+    /// * `Error::BusError` - I2C communication failed
+    pub fn update_als_period<D>(
+        &mut self,
+        delay: &mut D,
+        period: Duration,
+    ) -> Result<PeriodUpdate, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        if self.in_flight.interleaved {
+            return Err(Error::DeviceBusy);
+        }
+        let integration: AlsIntegrationPeriod = self.read_register()?;
+        let register = check_period(
+            AlsIntermeasurementPeriod { period },
+            period,
+            integration.min_intermeasurement_period(),
+        )?;
+
+        if !self.in_flight.continuous_als {
+            self.write_held(register)?;
+            return Ok(PeriodUpdate::InPlace);
+        }
+
+        self.halt_continuous_als(delay)?;
+        let written = self.write_register(register);
+        self.begin_continuous(ContinuousKind::Als)?;
+        written.map(|()| PeriodUpdate::Restarted)
+    }
+
+    /// Fails with `Error::PeriodTooShort` unless the configured ALS
+    /// intermeasurement period covers the configured integration period
+    pub(super) fn check_als_period(&mut self) -> Result<(), Error> {
+        let integration: AlsIntegrationPeriod = self.read_register()?;
+        let period: AlsIntermeasurementPeriod = self.read_register()?;
+        check_period(
+            period,
+            period.period,
+            integration.min_intermeasurement_period(),
+        )?;
+        Ok(())
+    }
+
+    /// Writes a register inside a grouped parameter hold, releasing the hold
+    /// even if the write fails
+    pub(super) fn write_held<R: DatasheetLimits>(&mut self, register: R) -> Result<(), Error> {
+        self.write_register(HOLD)?;
+        let written = self.write_register(register);
+        self.write_register(RELEASE)?;
+        written
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously changes the ranging intermeasurement period.
+    ///
+    /// This is the async version of [`update_range_period`](Device::update_range_period).
+    pub async fn update_range_period_async<D>(
+        &mut self,
+        delay: &mut D,
+        period: Duration,
+    ) -> Result<PeriodUpdate, Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        if self.in_flight.interleaved {
+            return Err(Error::DeviceBusy);
+        }
+        let limit: RangeMaxConvergenceTime = self.read_register_async().await?;
+        let register = check_period(
+            RangeIntermeasurementPeriod { period },
+            period,
+            limit.measurement_time(),
+        )?;
+
+        if !self.in_flight.continuous_range {
+            self.write_held_async(register).await?;
+            return Ok(PeriodUpdate::InPlace);
+        }
+
+        self.halt_continuous_range_async(delay).await?;
+        let written = self.write_register_async(register).await;
+        self.begin_continuous_async(ContinuousKind::Range).await?;
+        written.map(|()| PeriodUpdate::Restarted)
+    }
+
+    /// Asynchronously changes the ALS intermeasurement period.
+    ///
+    /// This is the async version of [`update_als_period`](Device::update_als_period).
+    pub async fn update_als_period_async<D>(
+        &mut self,
+        delay: &mut D,
+        period: Duration,
+    ) -> Result<PeriodUpdate, Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        if self.in_flight.interleaved {
+            return Err(Error::DeviceBusy);
+        }
+        let integration: AlsIntegrationPeriod = self.read_register_async().await?;
+        let register = check_period(
+            AlsIntermeasurementPeriod { period },
+            period,
+            integration.min_intermeasurement_period(),
+        )?;
+
+        if !self.in_flight.continuous_als {
+            self.write_held_async(register).await?;
+            return Ok(PeriodUpdate::InPlace);
+        }
+
+        self.halt_continuous_als_async(delay).await?;
+        let written = self.write_register_async(register).await;
+        self.begin_continuous_async(ContinuousKind::Als).await?;
+        written.map(|()| PeriodUpdate::Restarted)
+    }
+
+    /// Async version of `check_als_period`
+    pub(super) async fn check_als_period_async(&mut self) -> Result<(), Error> {
+        let integration: AlsIntegrationPeriod = self.read_register_async().await?;
+        let period: AlsIntermeasurementPeriod = self.read_register_async().await?;
+        check_period(
+            period,
+            period.period,
+            integration.min_intermeasurement_period(),
+        )?;
+        Ok(())
+    }
+
+    /// Async version of `write_held`
+    pub(super) async fn write_held_async<R: DatasheetLimits>(
+        &mut self,
+        register: R,
+    ) -> Result<(), Error> {
+        self.write_register_async(HOLD).await?;
+        let written = self.write_register_async(register).await;
+        self.write_register_async(RELEASE).await?;
+        written
+    }
+}
@@ -0,0 +1,169 @@
+//! Probing constructors
+//!
+//! Constructors that check a VL6180X answers at the address before handing
+//! out a device, giving the bus back if not.
+
+use super::{Address, Device, DeviceAddress, FixedAddress, DEFAULT_ADDRESS};
+use crate::registers::ModelId;
+use crate::types::Error;
+
+/// Fails with `Error::UnexpectedModel` for any model but the VL6180X
+fn check_model(model_id: ModelId) -> Result<(), Error> {
+    match model_id {
+        ModelId::VL6180X => Ok(()),
+        ModelId::Unknown(id) => Err(Error::UnexpectedModel(id)),
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A> {
+    /// Hands out the device if the probe succeeded, or releases the bus
+    ///
+    /// A bus error at an address that looks like 8-bit notation becomes
+    /// `Error::EightBitAddress`.
+    fn probe_result(self, probe: Result<(), Error>) -> Result<Self, (I2C, Error)> {
+        let address = Address::from(self.address());
+        match probe {
+            Ok(()) => Ok(self),
+            Err(Error::BusError(_)) if address.looks_8bit() => {
+                Err((self.release(), Error::EightBitAddress(address)))
+            }
+            Err(error) => Err((self.release(), error)),
+        }
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Reads the model ID, releasing the bus if it is not a VL6180X
+    fn probed(mut self) -> Result<Self, (I2C, Error)> {
+        let probe = self.read_register().and_then(check_model);
+        self.probe_result(probe)
+    }
+}
+
+impl<I2C> Device<I2C>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Creates a new Device instance with the default I2C address (0x29),
+    /// checking that a VL6180X responds there.
+    ///
+    /// Reads the model ID once. Use [`new`](Device::new) to create the
+    /// device before the sensor is powered or out of reset.
+    ///
+    /// # Arguments
+    /// * `i2c` - An I2C interface implementing the required embedded-hal traits
+    ///
+    /// # Errors
+    /// The bus is returned together with the error:
+    /// * `Error::BusError` - I2C communication failed, e.g. nothing
+    ///   acknowledged the address
+    /// * `Error::UnexpectedModel` - The device at the address is not a VL6180X
+    ///
+    /// # Example
+    /// ```no_run
+    /// use embedded_hal::i2c::I2c;
+    /// use vl6180x::Device;
+    ///
+    /// fn find<I2C: I2c>(i2c: I2C) -> Result<Device<I2C>, I2C> {
+    ///     Device::try_new(i2c)
+    ///         .or_else(|(i2c, _)| Device::try_new_with_address(i2c, 0x30))
+    ///         .map_err(|(i2c, _)| i2c)
+    /// }
+    /// ```
+    pub fn try_new(i2c: I2C) -> Result<Self, (I2C, Error)> {
+        Self::try_new_with_address(i2c, DEFAULT_ADDRESS)
+    }
+
+    /// Creates a new Device instance with a custom I2C address, checking
+    /// that a VL6180X responds there.
+    ///
+    /// See [`try_new`](Device::try_new).
+    ///
+    /// # Arguments
+    /// * `i2c` - An I2C interface implementing the required embedded-hal traits
+    /// * `address` - Custom I2C address, a `u8` being taken as 7-bit
+    ///
+    /// # Errors
+    /// The bus is returned together with the error:
+    /// * `Error::BusError` - I2C communication failed, e.g. nothing
+    ///   acknowledged the address
+    /// * `Error::UnexpectedModel` - The device at the address is not a VL6180X
+    /// * `Error::EightBitAddress` - Nothing answered at 0x52 or 0x53, the
+    ///   default address in 8-bit notation
+    pub fn try_new_with_address(
+        i2c: I2C,
+        address: impl Into<Address>,
+    ) -> Result<Self, (I2C, Error)> {
+        Self::new_with_address(i2c, address).probed()
+    }
+}
+
+impl<I2C, const ADDR: u8> Device<I2C, FixedAddress<ADDR>>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Creates a new Device instance at the address `ADDR`, checking that a
+    /// VL6180X responds there.
+    ///
+    /// See [`try_new`](Device::try_new).
+    ///
+    /// # Arguments
+    /// * `i2c` - An I2C interface implementing the required embedded-hal traits
+    ///
+    /// # Errors
+    /// The bus is returned together with the error, as for
+    /// [`try_new_with_address`](Device::try_new_with_address).
+    pub fn try_new_static(i2c: I2C) -> Result<Self, (I2C, Error)> {
+        Self::new_static(i2c).probed()
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    async fn probed_async(mut self) -> Result<Self, (I2C, Error)> {
+        let probe = self.read_register_async().await.and_then(check_model);
+        self.probe_result(probe)
+    }
+}
+
+impl<I2C> Device<I2C>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously creates a new Device instance with the default I2C
+    /// address (0x29), checking that a VL6180X responds there.
+    ///
+    /// This is the async version of [`try_new`](Device::try_new).
+    pub async fn try_new_async(i2c: I2C) -> Result<Self, (I2C, Error)> {
+        Self::try_new_with_address_async(i2c, DEFAULT_ADDRESS).await
+    }
+
+    /// Asynchronously creates a new Device instance with a custom I2C
+    /// address, checking that a VL6180X responds there.
+    ///
+    /// This is the async version of [`try_new_with_address`](Device::try_new_with_address).
+    pub async fn try_new_with_address_async(
+        i2c: I2C,
+        address: impl Into<Address>,
+    ) -> Result<Self, (I2C, Error)> {
+        Self::new_with_address(i2c, address).probed_async().await
+    }
+}
+
+impl<I2C, const ADDR: u8> Device<I2C, FixedAddress<ADDR>>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously creates a new Device instance at the address `ADDR`,
+    /// checking that a VL6180X responds there.
+    ///
+    /// This is the async version of [`try_new_static`](Device::try_new_static).
+    pub async fn try_new_static_async(i2c: I2C) -> Result<Self, (I2C, Error)> {
+        Self::new_static(i2c).probed_async().await
+    }
+}
@@ -0,0 +1,439 @@
+//! Measurement profiles
+//!
+//! Switching between named bundles of measurement settings at runtime, e.g.
+//! between a slow idle scan and fast tracking once something shows up.
+
+use super::batch::Batch;
+use super::continuous::ContinuousKind;
+use super::period::{check_period, HOLD, RELEASE};
+use super::{Device, DeviceAddress};
+use crate::registers::{
+    AlsAnalogueGain, AlsIntegrationPeriod, AlsIntermeasurementPeriod, InterruptConfigGpio,
+    RangeIntermeasurementPeriod, RangeMaxConvergenceTime, ReadoutAveraging,
+};
+use crate::types::{AlsGain, Error, ErrorContext, InterruptMode};
+
+/// Named bundle of range, ALS and interrupt settings
+///
+/// Load a set of profiles with [`Device::load_profiles`] and move between
+/// them with [`Device::switch_profile`], or apply one directly with
+/// [`Device::apply_profile`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Profile {
+    /// Name of the profile, for logging
+    pub name: &'static str,
+    /// Range continuous mode period
+    pub range_period: RangeIntermeasurementPeriod,
+    /// Range convergence time limit
+    pub max_convergence: RangeMaxConvergenceTime,
+    /// Range readout averaging
+    pub readout_averaging: ReadoutAveraging,
+    /// ALS continuous mode period
+    pub als_period: AlsIntermeasurementPeriod,
+    /// ALS integration time
+    pub als_integration: AlsIntegrationPeriod,
+    /// ALS analog gain
+    pub als_gain: AlsAnalogueGain,
+    /// Range and ALS interrupt modes
+    pub interrupts: InterruptConfigGpio,
+}
+
+impl Profile {
+    /// Slow idle scan
+    ///
+    /// Ranging every 1s with a 30ms convergence limit and 16 readout
+    /// averaging samples, ALS every 2s with a 50ms integration at gain 1, and
+    /// a range interrupt on every new sample.
+    pub const LOW_POWER: Self = Self {
+        name: "low_power",
+        range_period: RangeIntermeasurementPeriod::from_ms::<1000>(),
+        max_convergence: RangeMaxConvergenceTime::from_ms::<30>(),
+        readout_averaging: ReadoutAveraging::new(16),
+        als_period: AlsIntermeasurementPeriod::from_ms::<2000>(),
+        als_integration: AlsIntegrationPeriod::from_ms::<50>(),
+        als_gain: AlsAnalogueGain::new(AlsGain::Gain1),
+        interrupts: InterruptConfigGpio {
+            range_interrupt: InterruptMode::NewSampleReady,
+            als_interrupt: InterruptMode::Disabled,
+        },
+    };
+
+    /// Fast tracking of a moving target
+    ///
+    /// Ranging every 20ms with a 10ms convergence limit and the recommended
+    /// 48 readout averaging samples, ALS every 500ms with a 100ms integration
+    /// at gain 1, and a range interrupt on every new sample.
+    pub const FAST_TRACKING: Self = Self {
+        name: "fast_tracking",
+        range_period: RangeIntermeasurementPeriod::from_ms::<20>(),
+        max_convergence: RangeMaxConvergenceTime::from_ms::<10>(),
+        readout_averaging: ReadoutAveraging::RECOMMENDED,
+        als_period: AlsIntermeasurementPeriod::from_ms::<500>(),
+        als_integration: AlsIntegrationPeriod::from_ms::<100>(),
+        als_gain: AlsAnalogueGain::new(AlsGain::Gain1),
+        interrupts: InterruptConfigGpio {
+            range_interrupt: InterruptMode::NewSampleReady,
+            als_interrupt: InterruptMode::Disabled,
+        },
+    };
+
+    /// Returns whether both profiles configure the device identically,
+    /// whatever their names.
+    pub fn same_settings(&self, other: &Self) -> bool {
+        Self {
+            name: other.name,
+            ..*self
+        } == *other
+    }
+
+    /// Fails if a period is shorter than one measurement or cannot be encoded
+    fn check(&self) -> Result<(), Error> {
+        check_period(
+            self.range_period,
+            self.range_period.period,
+            self.max_convergence
+                .measurement_time_with(self.readout_averaging),
+        )?;
+        check_period(
+            self.als_period,
+            self.als_period.period,
+            self.als_integration.min_intermeasurement_period(),
+        )?;
+        Ok(())
+    }
+}
+
+/// Settings last written by a profile switch
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct ActiveProfile {
+    /// Index in the loaded profiles, `None` for a profile applied directly
+    index: Option<usize>,
+    settings: Profile,
+}
+
+/// Returns a field of `profile` if it differs from the active profile
+fn changed<T: PartialEq>(
+    profile: &Profile,
+    previous: Option<&Profile>,
+    field: fn(&Profile) -> T,
+) -> Option<T> {
+    let value = field(profile);
+    previous
+        .map(field)
+        .is_none_or(|old| old != value)
+        .then_some(value)
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A> {
+    /// Sets the profiles [`switch_profile`](Device::switch_profile) chooses from.
+    ///
+    /// No profile is active afterwards, so the first switch writes every
+    /// setting. The device keeps the slice for as long as it lives, which
+    /// is why it must be `'static`; a profile built at runtime can be
+    /// written with [`apply_profile`](Device::apply_profile) instead.
+    pub fn load_profiles(&mut self, profiles: &'static [Profile]) {
+        self.profiles = profiles;
+        self.active_profile = None;
+    }
+
+    /// Returns the loaded profiles.
+    pub fn profiles(&self) -> &'static [Profile] {
+        self.profiles
+    }
+
+    /// Returns the index of the profile last switched to.
+    ///
+    /// `None` before the first switch, after a switch failed part way and
+    /// after [`apply_profile`](Device::apply_profile).
+    pub fn active_profile(&self) -> Option<usize> {
+        self.active_profile.and_then(|active| active.index)
+    }
+
+    /// Looks up a loaded profile
+    fn loaded_profile(&self, index: usize) -> Result<Profile, Error> {
+        self.profiles
+            .get(index)
+            .copied()
+            .ok_or(Error::SerializationError(ErrorContext::argument()))
+    }
+
+    /// Returns the settings currently applied and the active profile once
+    /// `profile` is written
+    fn profile_switch(
+        &self,
+        profile: Profile,
+        index: Option<usize>,
+    ) -> (Option<Profile>, Option<ActiveProfile>) {
+        let previous = self.active_profile.map(|active| active.settings);
+        let next = ActiveProfile {
+            index,
+            settings: profile,
+        };
+        (previous, Some(next))
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Applies a loaded profile.
+    ///
+    /// Writes only the registers whose values differ from the active profile,
+    /// all of them if no profile is active, inside a
+    /// [`GroupedParameterHold`](crate::registers::GroupedParameterHold).
+    /// Continuous ranging and ALS measurements started by this driver are
+    /// stopped first and restarted afterwards. Switching to a profile with the
+    /// same settings touches nothing.
+    ///
+    /// # Arguments
+    /// * `delay` - Delay provider used while waiting for measurements to stop
+    /// * `index` - Index of the profile in the slice given to
+    ///   [`load_profiles`](Device::load_profiles)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use embedded_hal::{delay::DelayNs, i2c::I2c};
+    /// use vl6180x::device::Profile;
+    /// use vl6180x::Device;
+    ///
+    /// static PROFILES: [Profile; 2] = [Profile::LOW_POWER, Profile::FAST_TRACKING];
+    /// const IDLE: usize = 0;
+    /// const TRACKING: usize = 1;
+    ///
+    /// fn setup<I2C: I2c>(sensor: &mut Device<I2C>) {
+    ///     sensor.load_profiles(&PROFILES);
+    /// }
+    ///
+    /// fn on_target<I2C: I2c, D: DelayNs>(
+    ///     sensor: &mut Device<I2C>,
+    ///     delay: &mut D,
+    ///     seen: bool,
+    /// ) -> Result<(), vl6180x::Error> {
+    ///     sensor.switch_profile(delay, if seen { TRACKING } else { IDLE })
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// * `Error::PeriodTooShort` - A period of the profile is shorter than one
+    ///   measurement
+    /// * `Error::SerializationError` - `index` is not the index of a loaded
+    ///   profile, or a setting cannot be encoded
+    /// * `Error::DeviceBusy` - Interleaved mode runs, see
+    ///   [`start_interleaved`](Device::start_interleaved)
+    /// * `Error::Timeout` - A measurement did not stop
+    /// * `Error::BusError` - I2C communication failed
+    pub fn switch_profile<D>(&mut self, delay: &mut D, index: usize) -> Result<(), Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        let profile = self.loaded_profile(index)?;
+        self.switch_to(delay, profile, Some(index))
+    }
+
+    /// Applies a profile that was not loaded, such as one built at runtime.
+    ///
+    /// Writes the settings the same way as
+    /// [`switch_profile`](Device::switch_profile), comparing them with the
+    /// profile last switched to or applied. No loaded profile is active
+    /// afterwards.
+    ///
+    /// # Errors
+    /// * `Error::PeriodTooShort` - A period of the profile is shorter than one
+    ///   measurement
+    /// * `Error::SerializationError` - A setting cannot be encoded
+    /// * `Error::DeviceBusy` - Interleaved mode runs
+    /// * `Error::Timeout` - A measurement did not stop
+    /// * `Error::BusError` - I2C communication failed
+    pub fn apply_profile<D>(&mut self, delay: &mut D, profile: &Profile) -> Result<(), Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        self.switch_to(delay, *profile, None)
+    }
+
+    /// Writes `profile`, recording it as loaded profile `index`
+    fn switch_to<D>(
+        &mut self,
+        delay: &mut D,
+        profile: Profile,
+        index: Option<usize>,
+    ) -> Result<(), Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        profile.check()?;
+        let (previous, next) = self.profile_switch(profile, index);
+        if previous.is_some_and(|previous| previous.same_settings(&profile)) {
+            self.active_profile = next;
+            return Ok(());
+        }
+
+        if self.in_flight.interleaved {
+            return Err(Error::DeviceBusy);
+        }
+        self.active_profile = None;
+        let ranging = self.in_flight.continuous_range;
+        let measuring_als = self.in_flight.continuous_als;
+        self.halt_continuous_range(delay)?;
+        self.halt_continuous_als(delay)?;
+
+        self.write_register(HOLD)?;
+        let written = self.write_profile_changes(&profile, previous.as_ref());
+        self.write_register(RELEASE)?;
+        written?;
+
+        if ranging {
+            self.begin_continuous(ContinuousKind::Range)?;
+        }
+        if measuring_als {
+            self.begin_continuous(ContinuousKind::Als)?;
+        }
+        self.active_profile = next;
+        Ok(())
+    }
+
+    /// Writes the settings of `profile` that differ from `previous`
+    ///
+    /// Changed settings in adjacent registers, such as the range period and
+    /// convergence limit or the ALS period, gain and integration time, are
+    /// written as one block.
+    fn write_profile_changes(
+        &mut self,
+        profile: &Profile,
+        previous: Option<&Profile>,
+    ) -> Result<(), Error> {
+        let mut batch = Batch::default();
+        if let Some(period) = changed(profile, previous, |p| p.range_period) {
+            self.batch_register(&mut batch, period)?;
+        }
+        if let Some(limit) = changed(profile, previous, |p| p.max_convergence) {
+            self.batch_register(&mut batch, limit)?;
+        }
+        if let Some(averaging) = changed(profile, previous, |p| p.readout_averaging) {
+            self.batch_register(&mut batch, averaging)?;
+        }
+        if let Some(period) = changed(profile, previous, |p| p.als_period) {
+            self.batch_register(&mut batch, period)?;
+        }
+        if let Some(gain) = changed(profile, previous, |p| p.als_gain) {
+            self.batch_register(&mut batch, gain)?;
+        }
+        if let Some(integration) = changed(profile, previous, |p| p.als_integration) {
+            self.batch_register(&mut batch, integration)?;
+        }
+        if let Some(interrupts) = changed(profile, previous, |p| p.interrupts) {
+            self.batch_register(&mut batch, interrupts)?;
+        }
+        self.write_batch(&mut batch)
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously applies a loaded profile.
+    ///
+    /// This is the async version of [`switch_profile`](Device::switch_profile).
+    pub async fn switch_profile_async<D>(
+        &mut self,
+        delay: &mut D,
+        index: usize,
+    ) -> Result<(), Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        let profile = self.loaded_profile(index)?;
+        self.switch_to_async(delay, profile, Some(index)).await
+    }
+
+    /// Asynchronously applies a profile that was not loaded.
+    ///
+    /// This is the async version of [`apply_profile`](Device::apply_profile).
+    pub async fn apply_profile_async<D>(
+        &mut self,
+        delay: &mut D,
+        profile: &Profile,
+    ) -> Result<(), Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        self.switch_to_async(delay, *profile, None).await
+    }
+
+    /// Async version of `switch_to`
+    async fn switch_to_async<D>(
+        &mut self,
+        delay: &mut D,
+        profile: Profile,
+        index: Option<usize>,
+    ) -> Result<(), Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        profile.check()?;
+        let (previous, next) = self.profile_switch(profile, index);
+        if previous.is_some_and(|previous| previous.same_settings(&profile)) {
+            self.active_profile = next;
+            return Ok(());
+        }
+
+        if self.in_flight.interleaved {
+            return Err(Error::DeviceBusy);
+        }
+        self.active_profile = None;
+        let ranging = self.in_flight.continuous_range;
+        let measuring_als = self.in_flight.continuous_als;
+        self.halt_continuous_range_async(delay).await?;
+        self.halt_continuous_als_async(delay).await?;
+
+        self.write_register_async(HOLD).await?;
+        let written = self
+            .write_profile_changes_async(&profile, previous.as_ref())
+            .await;
+        self.write_register_async(RELEASE).await?;
+        written?;
+
+        if ranging {
+            self.begin_continuous_async(ContinuousKind::Range).await?;
+        }
+        if measuring_als {
+            self.begin_continuous_async(ContinuousKind::Als).await?;
+        }
+        self.active_profile = next;
+        Ok(())
+    }
+
+    /// Async version of `write_profile_changes`
+    async fn write_profile_changes_async(
+        &mut self,
+        profile: &Profile,
+        previous: Option<&Profile>,
+    ) -> Result<(), Error> {
+        let mut batch = Batch::default();
+        if let Some(period) = changed(profile, previous, |p| p.range_period) {
+            self.batch_register_async(&mut batch, period).await?;
+        }
+        if let Some(limit) = changed(profile, previous, |p| p.max_convergence) {
+            self.batch_register_async(&mut batch, limit).await?;
+        }
+        if let Some(averaging) = changed(profile, previous, |p| p.readout_averaging) {
+            self.batch_register_async(&mut batch, averaging).await?;
+        }
+        if let Some(period) = changed(profile, previous, |p| p.als_period) {
+            self.batch_register_async(&mut batch, period).await?;
+        }
+        if let Some(gain) = changed(profile, previous, |p| p.als_gain) {
+            self.batch_register_async(&mut batch, gain).await?;
+        }
+        if let Some(integration) = changed(profile, previous, |p| p.als_integration) {
+            self.batch_register_async(&mut batch, integration).await?;
+        }
+        if let Some(interrupts) = changed(profile, previous, |p| p.interrupts) {
+            self.batch_register_async(&mut batch, interrupts).await?;
+        }
+        self.write_batch_async(&mut batch).await
+    }
+}
@@ -0,0 +1,655 @@
+//! Range measurement helpers
+//!
+//! High-level wrappers around the SYSRANGE start/poll/read/clear sequence.
+
+use measurements::Length;
+
+use core::time::Duration;
+
+use super::{health::Measurement, poll_limit, Device, DeviceAddress, POLL_INTERVAL_US};
+use crate::registers::{
+    InterruptClear, RangeIntermeasurementPeriod, RangeMaxConvergenceTime, RangeResultStatus,
+    RangeResultValue, RangeStart, RangeStatusBlock, ReadoutAveraging, ResultInterruptStatusGpio,
+};
+use crate::types::{Error, PeriodTooShort, RangeErrorCode, RangeReading};
+
+/// Interrupt clear value acknowledging a range sample
+pub(super) const CLEAR_RANGE: InterruptClear = InterruptClear {
+    clear_range: true,
+    clear_als: false,
+    clear_error: false,
+};
+
+/// Status and value of a completed range measurement
+pub(crate) type RangeSample = (RangeResultStatus, RangeResultValue);
+
+/// Converts a completed range sample into the measured distance
+pub(crate) fn range_result((status, value): RangeSample) -> Result<Length, Error> {
+    if status.error_code.is_valid() {
+        Ok(value.distance)
+    } else {
+        Err(Error::RangeError(status.error_code))
+    }
+}
+
+/// Fails with `Error::PeriodTooShort` if a range measurement with `limit` and
+/// `averaging` does not fit into `period`
+fn check_averaging(
+    averaging: ReadoutAveraging,
+    limit: RangeMaxConvergenceTime,
+    period: RangeIntermeasurementPeriod,
+) -> Result<ReadoutAveraging, Error> {
+    let required = limit.measurement_time_with(averaging);
+    if required > period.period {
+        return Err(Error::PeriodTooShort(PeriodTooShort {
+            period: period.period,
+            required,
+        }));
+    }
+    Ok(averaging)
+}
+
+/// Classifies a completed range sample
+fn range_reading((status, value): RangeSample) -> RangeReading {
+    RangeReading::new(status.error_code, value.distance)
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A> {
+    /// Number of status polls that fit in the range timeout
+    fn range_poll_limit(&self) -> u32 {
+        poll_limit(self.timeouts.range)
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Performs a single-shot range measurement.
+    ///
+    /// Starts a measurement, polls the interrupt status until a new range sample
+    /// is reported, reads the result and clears the range interrupt. The range
+    /// interrupt must be configured for [`InterruptMode::NewSampleReady`](crate::types::InterruptMode::NewSampleReady).
+    ///
+    /// Costs four I2C transactions plus one per status poll.
+    ///
+    /// # Errors
+    /// * `Error::Timeout` - No sample was reported within the range timeout,
+    ///   see [`set_timeouts`](Device::set_timeouts)
+    /// * `Error::RangeError` - The measurement completed with an error code
+    /// * `Error::DeviceBusy` - Continuous ranging was started with
+    ///   [`start_continuous_range`](Device::start_continuous_range), or a
+    ///   measurement started by `try_read_range` was not collected yet
+    pub fn measure_range_single<D>(&mut self, delay: &mut D) -> Result<Length, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        let result = self.range_single(delay).and_then(range_result);
+        self.record_measurement(Measurement::Range, &result);
+        result
+    }
+
+    /// Performs a single-shot range measurement with its own timeout.
+    ///
+    /// Like [`measure_range_single`](Device::measure_range_single), but waits
+    /// up to `timeout` for the sample instead of the configured range timeout.
+    ///
+    /// # Arguments
+    /// * `delay` - Delay provider used between polls
+    /// * `timeout` - Maximum time to wait for the sample
+    ///
+    /// # Errors
+    /// * `Error::Timeout` - No sample was reported within `timeout`
+    /// * `Error::RangeError` - The measurement completed with an error code
+    /// * `Error::DeviceBusy` - Continuous ranging was started with
+    ///   [`start_continuous_range`](Device::start_continuous_range), or a
+    ///   measurement started by `try_read_range` was not collected yet
+    pub fn measure_range_single_within<D>(
+        &mut self,
+        delay: &mut D,
+        timeout: Duration,
+    ) -> Result<Length, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        let result = self
+            .range_single_within(delay, Some(poll_limit(timeout)))
+            .and_then(range_result);
+        self.record_measurement(Measurement::Range, &result);
+        result
+    }
+
+    /// Performs a single-shot range measurement and classifies its outcome.
+    ///
+    /// Like [`measure_range_single`](Device::measure_range_single), but a
+    /// completed measurement is never an error: a missing target and a failed
+    /// measurement are reported as [`RangeReading::NoTarget`] and
+    /// [`RangeReading::Failed`] according to
+    /// [`RangeErrorCode::classification`](crate::types::RangeErrorCode::classification).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use embedded_hal::{delay::DelayNs, i2c::I2c};
+    /// use vl6180x::{Device, RangeReading};
+    ///
+    /// fn report<I2C: I2c, D: DelayNs>(sensor: &mut Device<I2C>, delay: &mut D) {
+    ///     match sensor.read_range(delay) {
+    ///         Ok(RangeReading::Valid(distance)) => { /* use the distance */ }
+    ///         Ok(RangeReading::NoTarget) => { /* nothing in range */ }
+    ///         Ok(RangeReading::Failed(_code)) => { /* measurement fault */ }
+    ///         Err(_error) => { /* bus error or timeout */ }
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::Timeout` - No sample was reported within the polling budget
+    /// * `Error::DeviceBusy` - Continuous ranging was started with
+    ///   [`start_continuous_range`](Device::start_continuous_range), or a
+    ///   measurement started by `try_read_range` was not collected yet
+    pub fn read_range<D>(&mut self, delay: &mut D) -> Result<RangeReading, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        let result = self.range_single(delay);
+        self.record_measurement(Measurement::Range, &result.and_then(range_result));
+        result.map(range_reading)
+    }
+
+    /// Measures the distance, retrying measurements that failed transiently.
+    ///
+    /// Takes single-shot measurements until one is valid or `max_attempts`
+    /// measurements have been taken. Only status codes classified as
+    /// [transient](crate::types::RangeErrorClass::is_transient) are retried;
+    /// hardware faults, bus errors and timeouts are returned immediately. At
+    /// least one measurement is always taken.
+    ///
+    /// Returns the distance together with the number of measurements taken.
+    ///
+    /// # Arguments
+    /// * `max_attempts` - Maximum number of measurements to take
+    /// * `delay` - Delay provider
+    ///
+    /// # Example
+    /// ```
+    /// use vl6180x::{Device, Error, RangeErrorCode};
+    /// # use embedded_hal::delay::DelayNs;
+    /// # use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, Operation};
+    /// # struct Bus { statuses: &'static [u8] }
+    /// # impl ErrorType for Bus { type Error = ErrorKind; }
+    /// # impl I2c for Bus {
+    /// #     fn transaction(&mut self, _: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+    /// #         if let [Operation::Write(reg), Operation::Read(buf)] = ops {
+    /// #             buf[0] = match reg[1] {
+    /// #                 0x4D => {
+    /// #                     let (status, rest) = self.statuses.split_first().unwrap();
+    /// #                     self.statuses = rest;
+    /// #                     *status
+    /// #                 }
+    /// #                 0x4F => 0x04,
+    /// #                 _ => 100,
+    /// #             };
+    /// #         }
+    /// #         Ok(())
+    /// #     }
+    /// # }
+    /// # struct NoDelay;
+    /// # impl DelayNs for NoDelay { fn delay_ns(&mut self, _: u32) {} }
+    /// # let mut delay = NoDelay;
+    /// // Two measurements without convergence, then a valid one
+    /// # let bus = Bus { statuses: &[0x70, 0x70, 0x00] };
+    /// let mut sensor = Device::new(bus);
+    /// let (distance, attempts) = sensor.measure_distance_with_retries(5, &mut delay)?;
+    /// assert_eq!(distance.as_millimeters(), 100.0);
+    /// assert_eq!(attempts, 3);
+    ///
+    /// // A PLL lock failure is not retried
+    /// # let bus = Bus { statuses: &[0x40] };
+    /// let mut sensor = Device::new(bus);
+    /// assert_eq!(
+    ///     sensor.measure_distance_with_retries(5, &mut delay),
+    ///     Err(Error::RangeError(RangeErrorCode::Pll1Lock))
+    /// );
+    ///
+    /// // The last transient error is returned once the budget is exhausted
+    /// # let bus = Bus { statuses: &[0x70, 0xB0] };
+    /// let mut sensor = Device::new(bus);
+    /// assert_eq!(
+    ///     sensor.measure_distance_with_retries(2, &mut delay),
+    ///     Err(Error::RangeError(RangeErrorCode::SignalToNoiseRatio))
+    /// );
+    /// # Ok::<(), Error>(())
+    /// ```
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::Timeout` - No sample was reported within the polling budget
+    /// * `Error::RangeError` - A measurement failed with a non-transient
+    ///   error code, or the last attempt failed with a transient one
+    pub fn measure_distance_with_retries<D>(
+        &mut self,
+        max_attempts: u32,
+        delay: &mut D,
+    ) -> Result<(Length, u32), Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            match self.measure_range_single(delay) {
+                Ok(distance) => return Ok((distance, attempts)),
+                Err(Error::RangeError(code))
+                    if code.classification().is_transient() && attempts < max_attempts => {}
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    /// Reads the status and value of the latest range sample in one transaction.
+    ///
+    /// Fetches [`RangeStatusBlock`] with a single 22 byte burst instead of
+    /// reading the status and value registers separately, so both belong to
+    /// the same sample. Neither waits for a new sample nor clears the range
+    /// interrupt; write [`InterruptClear`] after each sample in a continuous
+    /// loop.
+    ///
+    /// Costs exactly one I2C transaction.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::DeserializationError` - The status holds an undefined error code
+    pub fn read_range_quick(&mut self) -> Result<(RangeErrorCode, Length), Error> {
+        let block: RangeStatusBlock = self.read_register()?;
+        Ok((block.status.error_code, block.distance))
+    }
+
+    /// Sets the readout averaging, checking that a range measurement still
+    /// fits into the intermeasurement period.
+    ///
+    /// More samples lower the range noise but lengthen every measurement, see
+    /// [`ReadoutAveraging::for_time_budget`] and
+    /// [`ReadoutAveraging::for_noise_target`]. The measurement time is
+    /// estimated with the configured convergence limit, see
+    /// [`RangeMaxConvergenceTime::measurement_time_with`].
+    ///
+    /// Costs three I2C transactions.
+    ///
+    /// # Arguments
+    /// * `averaging` - The new readout averaging
+    ///
+    /// # Errors
+    /// * `Error::PeriodTooShort` - A measurement would take longer than the
+    ///   intermeasurement period
+    /// * `Error::DeviceBusy` - Ranging is running, with the busy check
+    ///   enabled only
+    /// * `Error::BusError` - I2C communication failed
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use embedded_hal::i2c::I2c;
+    /// # use vl6180x::Device;
+    /// use core::time::Duration;
+    /// use vl6180x::registers::ReadoutAveraging;
+    ///
+    /// # fn example<I2C: I2c>(device: &mut Device<I2C>) -> Result<(), vl6180x::Error> {
+    /// // Spend at most 2.5ms of every measurement on averaging
+    /// let averaging = ReadoutAveraging::for_time_budget(Duration::from_micros(2_500));
+    /// device.set_readout_averaging(averaging)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_readout_averaging(&mut self, averaging: ReadoutAveraging) -> Result<(), Error> {
+        let limit = self.read_register()?;
+        let period = self.read_register()?;
+        self.write_register(check_averaging(averaging, limit, period)?)
+    }
+
+    fn range_single<D>(&mut self, delay: &mut D) -> Result<RangeSample, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        self.range_single_within(delay, Some(self.range_poll_limit()))
+    }
+
+    /// Performs a single-shot range measurement with a custom polling budget
+    ///
+    /// `poll_limit` of `None` polls until a sample is reported. As with the
+    /// async version, a measurement that times out or fails after its start
+    /// counts as abandoned until its interrupt is cleared.
+    pub(crate) fn range_single_within<D>(
+        &mut self,
+        delay: &mut D,
+        poll_limit: Option<u32>,
+    ) -> Result<RangeSample, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        self.refuse_during_continuous_range()?;
+        self.refuse_during_pending_range()?;
+        self.recover_abandoned_range(delay)?;
+        self.write_register(RangeStart::SingleShot)?;
+        self.in_flight.abandoned_range = true;
+        let sample = self.wait_range_sample_within(delay, poll_limit)?;
+        self.in_flight.abandoned_range = false;
+        self.adapt_timing(sample.0)?;
+        Ok(sample)
+    }
+
+    /// Polls for the next range sample and converts it into the measured distance
+    pub(super) fn wait_range<D>(&mut self, delay: &mut D) -> Result<Length, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        self.wait_range_sample(delay).and_then(range_result)
+    }
+
+    /// Polls for the next range sample, reads it and clears its interrupt
+    fn wait_range_sample<D>(&mut self, delay: &mut D) -> Result<RangeSample, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        self.wait_range_sample_within(delay, Some(self.range_poll_limit()))
+    }
+
+    fn wait_range_sample_within<D>(
+        &mut self,
+        delay: &mut D,
+        poll_limit: Option<u32>,
+    ) -> Result<RangeSample, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        let mut polls = 0;
+        loop {
+            let status: ResultInterruptStatusGpio = self.read_register()?;
+            if status.range_interrupt {
+                self.mark_sample();
+                break;
+            }
+
+            polls += 1;
+            if poll_limit.is_some_and(|limit| polls >= limit) {
+                return Err(Error::Timeout);
+            }
+            delay.delay_us(POLL_INTERVAL_US);
+        }
+
+        let status: RangeResultStatus = self.read_register()?;
+        let value: RangeResultValue = self.read_register()?;
+        self.write_register(CLEAR_RANGE)?;
+
+        Ok((status, value))
+    }
+
+    /// Waits for the ranging core to become ready after a stop
+    pub(super) fn wait_range_core_ready<D>(&mut self, delay: &mut D) -> Result<(), Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        let limit = self.range_poll_limit();
+        let mut polls = 0;
+        loop {
+            let status: RangeResultStatus = self.read_register()?;
+            if status.device_ready {
+                return Ok(());
+            }
+
+            polls += 1;
+            if polls >= limit {
+                return Err(Error::Timeout);
+            }
+            delay.delay_us(POLL_INTERVAL_US);
+        }
+    }
+
+    /// Lets a measurement left behind by a dropped async single-shot, or a
+    /// single-shot that timed out or failed, finish and clears its
+    /// interrupt, so that it is not taken for the sample of the next
+    /// measurement
+    fn recover_abandoned_range<D>(&mut self, delay: &mut D) -> Result<(), Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        if !self.in_flight.abandoned_range {
+            return Ok(());
+        }
+
+        let limit = self.range_poll_limit();
+        let mut polls = 0;
+        loop {
+            let interrupt: ResultInterruptStatusGpio = self.read_register()?;
+            if interrupt.range_interrupt {
+                break;
+            }
+            // Dropped after clearing the interrupt: nothing is left to wait for
+            let status: RangeResultStatus = self.read_register()?;
+            if status.device_ready {
+                break;
+            }
+
+            polls += 1;
+            if polls >= limit {
+                return Err(Error::Timeout);
+            }
+            delay.delay_us(POLL_INTERVAL_US);
+        }
+        self.write_register(CLEAR_RANGE)?;
+        self.in_flight.abandoned_range = false;
+        Ok(())
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously performs a single-shot range measurement.
+    ///
+    /// This is the async version of [`measure_range_single`](Device::measure_range_single).
+    ///
+    /// Cancellation-safe: if the future is dropped or fails before the range
+    /// interrupt is cleared, the next single-shot measurement first waits
+    /// for the abandoned one to finish and clears its interrupt, so that a
+    /// stale sample is never returned. Recovering costs at least two more I2C
+    /// transactions.
+    pub async fn measure_range_single_async<D>(&mut self, delay: &mut D) -> Result<Length, Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        let result = self.range_single_async(delay).await.and_then(range_result);
+        self.record_measurement(Measurement::Range, &result);
+        result
+    }
+
+    /// Asynchronously performs a single-shot range measurement with its own timeout.
+    ///
+    /// This is the async version of [`measure_range_single_within`](Device::measure_range_single_within).
+    pub async fn measure_range_single_within_async<D>(
+        &mut self,
+        delay: &mut D,
+        timeout: Duration,
+    ) -> Result<Length, Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        let result = self
+            .range_single_within_async(delay, poll_limit(timeout))
+            .await
+            .and_then(range_result);
+        self.record_measurement(Measurement::Range, &result);
+        result
+    }
+
+    /// Asynchronously performs a single-shot range measurement and classifies its outcome.
+    ///
+    /// This is the async version of [`read_range`](Device::read_range).
+    pub async fn read_range_async<D>(&mut self, delay: &mut D) -> Result<RangeReading, Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        let result = self.range_single_async(delay).await;
+        self.record_measurement(Measurement::Range, &result.and_then(range_result));
+        result.map(range_reading)
+    }
+
+    /// Asynchronously reads the status and value of the latest range sample in one transaction.
+    ///
+    /// This is the async version of [`read_range_quick`](Device::read_range_quick).
+    pub async fn read_range_quick_async(&mut self) -> Result<(RangeErrorCode, Length), Error> {
+        let block: RangeStatusBlock = self.read_register_async().await?;
+        Ok((block.status.error_code, block.distance))
+    }
+
+    /// Asynchronously sets the readout averaging, checking that a range
+    /// measurement still fits into the intermeasurement period.
+    ///
+    /// This is the async version of [`set_readout_averaging`](Device::set_readout_averaging).
+    pub async fn set_readout_averaging_async(
+        &mut self,
+        averaging: ReadoutAveraging,
+    ) -> Result<(), Error> {
+        let limit = self.read_register_async().await?;
+        let period = self.read_register_async().await?;
+        self.write_register_async(check_averaging(averaging, limit, period)?)
+            .await
+    }
+
+    /// Asynchronously measures the distance, retrying measurements that failed transiently.
+    ///
+    /// This is the async version of [`measure_distance_with_retries`](Device::measure_distance_with_retries).
+    pub async fn measure_distance_with_retries_async<D>(
+        &mut self,
+        max_attempts: u32,
+        delay: &mut D,
+    ) -> Result<(Length, u32), Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            match self.measure_range_single_async(delay).await {
+                Ok(distance) => return Ok((distance, attempts)),
+                Err(Error::RangeError(code))
+                    if code.classification().is_transient() && attempts < max_attempts => {}
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    async fn range_single_async<D>(&mut self, delay: &mut D) -> Result<RangeSample, Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        self.range_single_within_async(delay, self.range_poll_limit())
+            .await
+    }
+
+    /// Async version of `range_single_within`, always bounded
+    ///
+    /// The measurement counts as abandoned from its start until its
+    /// interrupt is cleared, so that the next measurement recovers if the
+    /// future is dropped in between.
+    async fn range_single_within_async<D>(
+        &mut self,
+        delay: &mut D,
+        limit: u32,
+    ) -> Result<RangeSample, Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        self.refuse_during_continuous_range()?;
+        self.refuse_during_pending_range()?;
+        self.recover_abandoned_range_async(delay).await?;
+        // Set before the write, which the future may be dropped during
+        self.in_flight.abandoned_range = true;
+        let started = self.write_register_async(RangeStart::SingleShot).await;
+        self.in_flight.abandoned_range = started.is_ok();
+        started?;
+
+        let mut polls = 0;
+        loop {
+            let status: ResultInterruptStatusGpio = self.read_register_async().await?;
+            if status.range_interrupt {
+                self.mark_sample();
+                break;
+            }
+
+            polls += 1;
+            if polls >= limit {
+                return Err(Error::Timeout);
+            }
+            delay.delay_us(POLL_INTERVAL_US).await;
+        }
+
+        let status: RangeResultStatus = self.read_register_async().await?;
+        let value: RangeResultValue = self.read_register_async().await?;
+        self.write_register_async(CLEAR_RANGE).await?;
+        self.in_flight.abandoned_range = false;
+        self.adapt_timing_async(status).await?;
+
+        Ok((status, value))
+    }
+
+    /// Asynchronously waits for the ranging core to become ready after a stop.
+    ///
+    /// This is the async version of [`wait_range_core_ready`](Device::wait_range_core_ready).
+    pub(super) async fn wait_range_core_ready_async<D>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        let limit = self.range_poll_limit();
+        let mut polls = 0;
+        loop {
+            let status: RangeResultStatus = self.read_register_async().await?;
+            if status.device_ready {
+                return Ok(());
+            }
+
+            polls += 1;
+            if polls >= limit {
+                return Err(Error::Timeout);
+            }
+            delay.delay_us(POLL_INTERVAL_US).await;
+        }
+    }
+
+    /// Asynchronously recovers from an abandoned async single-shot.
+    ///
+    /// This is the async version of [`recover_abandoned_range`](Device::recover_abandoned_range).
+    async fn recover_abandoned_range_async<D>(&mut self, delay: &mut D) -> Result<(), Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        if !self.in_flight.abandoned_range {
+            return Ok(());
+        }
+
+        let limit = self.range_poll_limit();
+        let mut polls = 0;
+        loop {
+            let interrupt: ResultInterruptStatusGpio = self.read_register_async().await?;
+            if interrupt.range_interrupt {
+                break;
+            }
+            let status: RangeResultStatus = self.read_register_async().await?;
+            if status.device_ready {
+                break;
+            }
+
+            polls += 1;
+            if polls >= limit {
+                return Err(Error::Timeout);
+            }
+            delay.delay_us(POLL_INTERVAL_US).await;
+        }
+        self.write_register_async(CLEAR_RANGE).await?;
+        self.in_flight.abandoned_range = false;
+        Ok(())
+    }
+}
@@ -0,0 +1,270 @@
+//! Stall recovery
+//!
+//! Ways to bring a sensor that stopped producing samples back into operation,
+//! from least to most disruptive, and a hook to bring the bus back after a
+//! failed transaction.
+
+use core::time::Duration;
+
+use super::continuous::{ContinuousKind, InFlight};
+use super::{Device, DeviceAddress};
+use crate::registers::{InterruptClear, POWER_ON_DEFAULTS};
+use crate::types::{BusRecoveryHook, Error};
+
+/// Time XSHUT is held low during a power cycle (in microseconds)
+const XSHUT_LOW_US: u32 = 1_000;
+
+/// Interrupt clear value acknowledging every interrupt source
+const CLEAR_ALL: InterruptClear = InterruptClear {
+    clear_range: true,
+    clear_als: true,
+    clear_error: true,
+};
+
+/// Bus recovery hook held by a device, compared by address
+#[derive(Debug, Clone, Copy)]
+pub(super) struct RecoveryHook(pub(super) BusRecoveryHook);
+
+impl PartialEq for RecoveryHook {
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::fn_addr_eq(self.0, other.0)
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A> {
+    /// Sets the hook called when a bus transaction fails.
+    ///
+    /// After a register read or write fails on the bus, and before the error
+    /// is returned, the hook is called once with the error kind. It can
+    /// recover the bus, e.g. clock out a stuck slave or reset the I2C
+    /// peripheral, and returns [`BusRecovery::Retry`](crate::BusRecovery::Retry)
+    /// to have the transaction issued again. A transaction is retried at most
+    /// three times. The hook is called after every failed attempt, the last
+    /// one included, so that it can leave the bus usable for the next
+    /// transaction; its answer to the last one is ignored. Failures that are
+    /// not on the bus, such as deserialization errors, never reach the hook,
+    /// and the device presence checks of the boot helpers, where a NACK is an
+    /// answer, do not use it.
+    ///
+    /// The hook is a plain function pointer and cannot capture state; reach
+    /// the pins of a bus clear through a `static`, e.g. behind a
+    /// critical-section mutex.
+    ///
+    /// Retries are invisible to the caller: a transaction that succeeds on
+    /// a retry returns as if it had succeeded at once. Measurement retries,
+    /// such as [`measure_distance_with_retries`](Device::measure_distance_with_retries),
+    /// thus only see bus errors the hook gave up on. The bus statistics of
+    /// the `bus-stats` feature count every attempt, failed ones as errors.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use embedded_hal::i2c::{ErrorKind, I2c};
+    /// use vl6180x::{BusRecovery, Device};
+    ///
+    /// fn recover_bus(kind: ErrorKind) -> BusRecovery {
+    ///     match kind {
+    ///         ErrorKind::ArbitrationLoss | ErrorKind::Bus => {
+    ///             // Toggle SCL until SDA is released and reset the peripheral
+    ///             BusRecovery::Retry
+    ///         }
+    ///         _ => BusRecovery::GiveUp,
+    ///     }
+    /// }
+    ///
+    /// fn configure<I2C: I2c>(sensor: &mut Device<I2C>) {
+    ///     sensor.set_bus_recovery(recover_bus);
+    /// }
+    /// ```
+    pub fn set_bus_recovery(&mut self, hook: BusRecoveryHook) {
+        self.bus_recovery = Some(RecoveryHook(hook));
+    }
+
+    /// Removes the bus recovery hook, see [`set_bus_recovery`](Device::set_bus_recovery).
+    pub fn clear_bus_recovery(&mut self) {
+        self.bus_recovery = None;
+    }
+
+    /// Returns the bus recovery hook, if set.
+    pub fn bus_recovery(&self) -> Option<BusRecoveryHook> {
+        self.bus_recovery.map(|hook| hook.0)
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Stops and restarts continuous ranging.
+    ///
+    /// If continuous ranging started by this driver runs it is stopped
+    /// first, then all pending interrupts are cleared and continuous ranging
+    /// is started again. The configuration registers are left untouched.
+    ///
+    /// # Errors
+    /// * `Error::DeviceBusy` - Interleaved mode is running
+    /// * `Error::Timeout` - The ranging core did not stop
+    pub fn restart_continuous_range<D>(&mut self, delay: &mut D) -> Result<(), Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        if self.in_flight.interleaved {
+            return Err(Error::DeviceBusy);
+        }
+        self.halt_continuous_range(delay)?;
+        self.write_register(CLEAR_ALL)?;
+        self.begin_continuous(ContinuousKind::Range)
+    }
+
+    /// Restores the power-on configuration without a power cycle.
+    ///
+    /// Stops the continuous measurements this driver started, writes every
+    /// entry of [`POWER_ON_DEFAULTS`] and clears all pending interrupts, then
+    /// writes `tuning`, one register at a time. Unlike
+    /// [`power_cycle`](Device::power_cycle) it works with XSHUT strapped
+    /// high, and the I2C address and the factory calibrated part-to-part
+    /// range offset survive it. Afterwards every configuration register type
+    /// reads back as its `Default`, and no measurement is tracked as running
+    /// or pending.
+    ///
+    /// Costs one I2C transaction per default and per tuning entry, one
+    /// interrupt clear and the transactions of stopping running measurements.
+    ///
+    /// # Arguments
+    /// * `delay` - Delay provider used while waiting for measurements to stop
+    /// * `tuning` - Register writes applied after the defaults, such as ST's
+    ///   mandatory private settings, as address and value pairs; empty to
+    ///   skip
+    ///
+    /// # Errors
+    /// * `Error::Timeout` - A measurement did not stop
+    /// * `Error::BusError` - I2C communication failed
+    pub fn factory_reset<D>(&mut self, delay: &mut D, tuning: &[(u16, u8)]) -> Result<(), Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        self.stop_interleaved(delay)?;
+        self.halt_continuous_range(delay)?;
+        self.halt_continuous_als(delay)?;
+
+        self.in_flight = InFlight::default();
+        self.active_profile = None;
+        for default in &POWER_ON_DEFAULTS {
+            self.write_block(default.address, default.bytes)?;
+        }
+        self.write_register(CLEAR_ALL)?;
+        for &(address, value) in tuning {
+            self.write_block(address, &[value])?;
+        }
+        Ok(())
+    }
+
+    /// Power cycles the sensor through its XSHUT pin.
+    ///
+    /// Drives XSHUT low, releases it and waits for the firmware to boot. The
+    /// device comes back with its power-on defaults: its I2C address reverts
+    /// to the default and it must be configured again. Once XSHUT is low, no
+    /// measurement is tracked as running or pending and no profile as
+    /// active.
+    ///
+    /// Returns the time the firmware took to boot.
+    ///
+    /// # Arguments
+    /// * `xshut` - Output pin driving XSHUT
+    /// * `delay` - Delay provider
+    /// * `boot_timeout` - Maximum time to wait for the firmware to boot, or
+    ///   `None` for the configured boot timeout
+    ///
+    /// # Errors
+    /// * `Error::PinError` - Driving XSHUT failed
+    /// * `Error::Timeout` - The firmware did not boot within the timeout
+    pub fn power_cycle<P, D, T>(
+        &mut self,
+        xshut: &mut P,
+        delay: &mut D,
+        boot_timeout: T,
+    ) -> Result<Duration, Error>
+    where
+        P: embedded_hal::digital::OutputPin,
+        D: embedded_hal::delay::DelayNs,
+        T: Into<Option<Duration>>,
+    {
+        xshut.set_low().map_err(|_| Error::PinError)?;
+        self.in_flight = InFlight::default();
+        self.active_profile = None;
+        delay.delay_us(XSHUT_LOW_US);
+        xshut.set_high().map_err(|_| Error::PinError)?;
+
+        self.wait_for_boot(delay, boot_timeout)
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously stops and restarts continuous ranging.
+    ///
+    /// This is the async version of [`restart_continuous_range`](Device::restart_continuous_range).
+    pub async fn restart_continuous_range_async<D>(&mut self, delay: &mut D) -> Result<(), Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        if self.in_flight.interleaved {
+            return Err(Error::DeviceBusy);
+        }
+        self.halt_continuous_range_async(delay).await?;
+        self.write_register_async(CLEAR_ALL).await?;
+        self.begin_continuous_async(ContinuousKind::Range).await
+    }
+
+    /// Asynchronously restores the power-on configuration without a power cycle.
+    ///
+    /// This is the async version of [`factory_reset`](Device::factory_reset).
+    pub async fn factory_reset_async<D>(
+        &mut self,
+        delay: &mut D,
+        tuning: &[(u16, u8)],
+    ) -> Result<(), Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        self.stop_interleaved_async(delay).await?;
+        self.halt_continuous_range_async(delay).await?;
+        self.halt_continuous_als_async(delay).await?;
+
+        self.in_flight = InFlight::default();
+        self.active_profile = None;
+        for default in &POWER_ON_DEFAULTS {
+            self.write_block_async(default.address, default.bytes)
+                .await?;
+        }
+        self.write_register_async(CLEAR_ALL).await?;
+        for &(address, value) in tuning {
+            self.write_block_async(address, &[value]).await?;
+        }
+        Ok(())
+    }
+
+    /// Asynchronously power cycles the sensor through its XSHUT pin.
+    ///
+    /// This is the async version of [`power_cycle`](Device::power_cycle).
+    pub async fn power_cycle_async<P, D, T>(
+        &mut self,
+        xshut: &mut P,
+        delay: &mut D,
+        boot_timeout: T,
+    ) -> Result<Duration, Error>
+    where
+        P: embedded_hal::digital::OutputPin,
+        D: embedded_hal_async::delay::DelayNs,
+        T: Into<Option<Duration>>,
+    {
+        xshut.set_low().map_err(|_| Error::PinError)?;
+        self.in_flight = InFlight::default();
+        self.active_profile = None;
+        delay.delay_us(XSHUT_LOW_US).await;
+        xshut.set_high().map_err(|_| Error::PinError)?;
+
+        self.wait_for_boot_async(delay, boot_timeout).await
+    }
+}
@@ -0,0 +1,127 @@
+//! Bus scanning
+//!
+//! Helpers for finding VL6180X sensors on a bus when their addresses are not
+//! known in advance, e.g. after a reboot that may or may not have reset a
+//! reprogrammed address back to the default.
+
+use regiface::{FromByteArray, Register};
+
+use super::wire::{address_bytes, is_nack};
+use crate::registers::ModelId;
+use crate::types::{Direction, Error, ErrorContext};
+
+/// Interprets the outcome of a `ModelId` read from one candidate address
+///
+/// Returns whether a VL6180X answered. A NACK means nothing is listening at
+/// the address and is not an error.
+fn probe_result<E>(result: Result<(), E>, model_id: [u8; 1]) -> Result<bool, Error>
+where
+    E: embedded_hal::i2c::Error,
+{
+    match result {
+        Ok(()) => Ok(ModelId::from_bytes(model_id) == Ok(ModelId::VL6180X)),
+        Err(e) if is_nack(&e) => Ok(false),
+        Err(_) => Err(Error::BusError(ErrorContext::register(
+            ModelId::id(),
+            Direction::Read,
+        ))),
+    }
+}
+
+/// Scans a set of addresses for VL6180X sensors.
+///
+/// Probes each candidate 7-bit address in order by reading the `ModelId`
+/// register and stores every address that answers with the VL6180X model ID
+/// (0xB4) in `found`. Scanning stops early once `found` is full.
+///
+/// Returns the number of addresses written to `found`.
+///
+/// # Arguments
+/// * `i2c` - The I2C bus to scan
+/// * `candidates` - 7-bit addresses to probe
+/// * `found` - Buffer receiving the addresses of detected sensors
+///
+/// # Errors
+/// * `Error::BusError` - A bus error other than a NACK occurred
+///
+/// # Example
+/// ```
+/// use vl6180x::device::{scan_for_vl6180x, DEFAULT_ADDRESS};
+/// # use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, NoAcknowledgeSource, Operation};
+/// # struct Bus;
+/// # impl ErrorType for Bus { type Error = ErrorKind; }
+/// # impl I2c for Bus {
+/// #     fn transaction(&mut self, address: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+/// #         match address {
+/// #             0x29 | 0x30 => {
+/// #                 if let Some(Operation::Read(buf)) = ops.last_mut() { buf[0] = 0xB4; }
+/// #                 Ok(())
+/// #             }
+/// #             _ => Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+/// #         }
+/// #     }
+/// # }
+/// # let mut i2c = Bus;
+///
+/// let mut found = [0u8; 4];
+/// let count = scan_for_vl6180x(&mut i2c, &[DEFAULT_ADDRESS, 0x2A, 0x2B, 0x30, 0x31], &mut found)?;
+///
+/// assert_eq!(&found[..count], &[0x29, 0x30]);
+/// # Ok::<(), vl6180x::Error>(())
+/// ```
+pub fn scan_for_vl6180x<I2C>(
+    i2c: &mut I2C,
+    candidates: &[u8],
+    found: &mut [u8],
+) -> Result<usize, Error>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    let reg_addr = address_bytes(ModelId::id());
+    let mut count = 0;
+
+    for &address in candidates {
+        if count == found.len() {
+            break;
+        }
+
+        let mut model_id = [0u8; 1];
+        let result = i2c.write_read(address, &reg_addr, &mut model_id);
+        if probe_result(result, model_id)? {
+            found[count] = address;
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
+
+/// Asynchronously scans a set of addresses for VL6180X sensors.
+///
+/// This is the async version of [`scan_for_vl6180x`].
+pub async fn scan_for_vl6180x_async<I2C>(
+    i2c: &mut I2C,
+    candidates: &[u8],
+    found: &mut [u8],
+) -> Result<usize, Error>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    let reg_addr = address_bytes(ModelId::id());
+    let mut count = 0;
+
+    for &address in candidates {
+        if count == found.len() {
+            break;
+        }
+
+        let mut model_id = [0u8; 1];
+        let result = i2c.write_read(address, &reg_addr, &mut model_id).await;
+        if probe_result(result, model_id)? {
+            found[count] = address;
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
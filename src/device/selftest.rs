@@ -0,0 +1,241 @@
+//! Power-on and production self-test
+//!
+//! Exercises the VHV calibration, the ranging path and the ambient light path
+//! once each and reports every check separately, so a test fixture can log
+//! what failed and why.
+
+use core::time::Duration;
+
+use super::{Device, DeviceAddress, POLL_INTERVAL_US};
+use crate::registers::{RangeResultBlock, RangeVhvRecalibrate};
+use crate::types::{AlsReading, Error, Mcps, RangeErrorClass, RangeReading};
+
+/// Number of range measurements taken by [`Device::self_test`]
+pub const SELF_TEST_RANGES: usize = 3;
+
+/// Time between VHV status polls
+const VHV_POLL_INTERVAL: Duration = Duration::from_micros(POLL_INTERVAL_US as u64);
+
+/// Outcome of one check of a [`SelfTestReport`], with the values it judged
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SelfTestCheck<T> {
+    /// Whether the check passed
+    pub passed: bool,
+    /// Values the check was decided on
+    pub value: T,
+}
+
+/// Per-check results of a [`Device::self_test`]
+///
+/// Values that could not be obtained because a measurement timed out are
+/// `None`; a check with a missing value has failed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SelfTestReport {
+    /// Time until a manual VHV recalibration completed
+    pub vhv: SelfTestCheck<Option<Duration>>,
+    /// Range measurements, failed if any reported a VCSEL or PLL fault
+    pub ranging: SelfTestCheck<[Option<RangeReading>; SELF_TEST_RANGES]>,
+    /// Reference channel rate of each range measurement, failed if any is
+    /// outside [`REFERENCE_RATE_MIN`](Self::REFERENCE_RATE_MIN) to
+    /// [`REFERENCE_RATE_MAX`](Self::REFERENCE_RATE_MAX)
+    pub reference_rate: SelfTestCheck<[Option<Mcps>; SELF_TEST_RANGES]>,
+    /// Ambient light measurement, failed if no light was detected
+    pub als: SelfTestCheck<Option<AlsReading>>,
+}
+
+impl SelfTestReport {
+    /// Lowest plausible reference channel rate
+    ///
+    /// The reference array sees the VCSEL through the package whatever the
+    /// target, so a rate below this means the emitter or the array is dead.
+    pub const REFERENCE_RATE_MIN: Mcps = Mcps::from_fixed(64);
+
+    /// Highest plausible reference channel rate
+    pub const REFERENCE_RATE_MAX: Mcps = Mcps::from_fixed(50 * 128);
+
+    fn new(
+        vhv: Option<Duration>,
+        ranges: [Option<RangeReading>; SELF_TEST_RANGES],
+        rates: [Option<Mcps>; SELF_TEST_RANGES],
+        als: Option<AlsReading>,
+    ) -> Self {
+        let healthy_range = |reading: &Option<RangeReading>| match reading {
+            Some(RangeReading::Failed(code)) => {
+                code.classification() != RangeErrorClass::HardwareFault
+            }
+            Some(_) => true,
+            None => false,
+        };
+        let plausible_rate = |rate: &Option<Mcps>| {
+            rate.is_some_and(|rate| {
+                (Self::REFERENCE_RATE_MIN..=Self::REFERENCE_RATE_MAX).contains(&rate)
+            })
+        };
+        let light_detected = match als {
+            Some(AlsReading::Valid(light)) => light.lux > 0.0,
+            Some(AlsReading::Saturated) => true,
+            Some(AlsReading::Dark | AlsReading::Failed(_)) | None => false,
+        };
+
+        Self {
+            vhv: SelfTestCheck {
+                passed: vhv.is_some(),
+                value: vhv,
+            },
+            ranging: SelfTestCheck {
+                passed: ranges.iter().all(healthy_range),
+                value: ranges,
+            },
+            reference_rate: SelfTestCheck {
+                passed: rates.iter().all(plausible_rate),
+                value: rates,
+            },
+            als: SelfTestCheck {
+                passed: light_detected,
+                value: als,
+            },
+        }
+    }
+
+    /// Returns whether every check passed
+    pub fn passed(&self) -> bool {
+        self.vhv.passed && self.ranging.passed && self.reference_rate.passed && self.als.passed
+    }
+}
+
+/// Turns a timeout into a missing value, keeping other errors
+fn timed_out<T>(result: Result<T, Error>) -> Result<Option<T>, Error> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(Error::Timeout) => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Checks that the optical and electrical paths of the sensor work.
+    ///
+    /// Runs a manual VHV recalibration, takes [`SELF_TEST_RANGES`] single-shot
+    /// range measurements, reading the reference channel rate of each, and
+    /// takes one ALS measurement. Every check is reported separately in the
+    /// returned [`SelfTestReport`]; measurement error codes and timeouts fail
+    /// their check rather than the self-test.
+    ///
+    /// The sensor must be idle with both interrupts configured for
+    /// [`InterruptMode::NewSampleReady`](crate::types::InterruptMode::NewSampleReady),
+    /// as for the single-shot helpers. The ALS check needs some ambient
+    /// light.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    ///
+    /// # Example
+    /// ```no_run
+    /// use embedded_hal::{delay::DelayNs, i2c::I2c};
+    /// use vl6180x::Device;
+    ///
+    /// fn end_of_line<I2C: I2c, D: DelayNs>(sensor: &mut Device<I2C>, delay: &mut D) -> bool {
+    ///     match sensor.self_test(delay) {
+    ///         Ok(report) => report.passed(),
+    ///         Err(_) => false,
+    ///     }
+    /// }
+    /// ```
+    pub fn self_test<D>(&mut self, delay: &mut D) -> Result<SelfTestReport, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        let vhv = timed_out(self.recalibrate_vhv(delay))?;
+
+        let mut ranges = [None; SELF_TEST_RANGES];
+        let mut rates = [None; SELF_TEST_RANGES];
+        for (range, rate) in ranges.iter_mut().zip(&mut rates) {
+            *range = timed_out(self.read_range(delay))?;
+            if range.is_some() {
+                let block: RangeResultBlock = self.read_register()?;
+                *rate = Some(Mcps::from_fixed(block.reference_rate));
+            }
+        }
+
+        let als = timed_out(self.read_als(delay))?;
+        Ok(SelfTestReport::new(vhv, ranges, rates, als))
+    }
+
+    /// Runs a manual VHV recalibration, returning the time until it completed
+    fn recalibrate_vhv<D>(&mut self, delay: &mut D) -> Result<Duration, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        self.write_register(RangeVhvRecalibrate { recalibrate: 1 })?;
+        let timeout = self.timeouts.range;
+        let mut elapsed = Duration::ZERO;
+        loop {
+            let status: RangeVhvRecalibrate = self.read_register()?;
+            if status.recalibrate & 0x03 == 0 {
+                return Ok(elapsed);
+            }
+
+            if elapsed >= timeout {
+                return Err(Error::Timeout);
+            }
+            delay.delay_us(POLL_INTERVAL_US);
+            elapsed += VHV_POLL_INTERVAL;
+        }
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously checks that the optical and electrical paths of the
+    /// sensor work.
+    ///
+    /// This is the async version of [`self_test`](Device::self_test).
+    pub async fn self_test_async<D>(&mut self, delay: &mut D) -> Result<SelfTestReport, Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        let vhv = timed_out(self.recalibrate_vhv_async(delay).await)?;
+
+        let mut ranges = [None; SELF_TEST_RANGES];
+        let mut rates = [None; SELF_TEST_RANGES];
+        for (range, rate) in ranges.iter_mut().zip(&mut rates) {
+            *range = timed_out(self.read_range_async(delay).await)?;
+            if range.is_some() {
+                let block: RangeResultBlock = self.read_register_async().await?;
+                *rate = Some(Mcps::from_fixed(block.reference_rate));
+            }
+        }
+
+        let als = timed_out(self.read_als_async(delay).await)?;
+        Ok(SelfTestReport::new(vhv, ranges, rates, als))
+    }
+
+    async fn recalibrate_vhv_async<D>(&mut self, delay: &mut D) -> Result<Duration, Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        self.write_register_async(RangeVhvRecalibrate { recalibrate: 1 })
+            .await?;
+        let timeout = self.timeouts.range;
+        let mut elapsed = Duration::ZERO;
+        loop {
+            let status: RangeVhvRecalibrate = self.read_register_async().await?;
+            if status.recalibrate & 0x03 == 0 {
+                return Ok(elapsed);
+            }
+
+            if elapsed >= timeout {
+                return Err(Error::Timeout);
+            }
+            delay.delay_us(POLL_INTERVAL_US).await;
+            elapsed += VHV_POLL_INTERVAL;
+        }
+    }
+}
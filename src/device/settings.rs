@@ -0,0 +1,215 @@
+//! Per-field settings
+//!
+//! Thin setters and getters for the parameters changed most often, so quick
+//! scripts need not build register values. Each goes through the register
+//! type, with its encoding and checks.
+
+use core::time::Duration;
+
+use measurements::Length;
+
+use super::{Device, DeviceAddress};
+use crate::registers::{
+    AlsAnalogueGain, AlsIntegrationPeriod, RangeCrosstalkCompensationRate, RangeMaxConvergenceTime,
+    RangePartToPartOffset,
+};
+use crate::types::{AlsGain, Error, Mcps};
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Sets the ALS analog gain.
+    ///
+    /// Written inside a grouped parameter hold, so running ALS measurements
+    /// pick it up as a whole. Costs three I2C transactions.
+    ///
+    /// # Errors
+    /// * `Error::DeviceBusy` - An ALS measurement is running, with the busy
+    ///   check enabled only
+    /// * `Error::BusError` - I2C communication failed
+    pub fn set_als_gain(&mut self, gain: AlsGain) -> Result<(), Error> {
+        self.write_held(AlsAnalogueGain::new(gain))
+    }
+
+    /// Returns the ALS analog gain.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn als_gain(&mut self) -> Result<AlsGain, Error> {
+        let register: AlsAnalogueGain = self.read_register()?;
+        Ok(register.gain)
+    }
+
+    /// Sets the ALS integration period.
+    ///
+    /// Written inside a grouped parameter hold, so running ALS measurements
+    /// pick it up as a whole. Costs three I2C transactions.
+    ///
+    /// # Errors
+    /// * `Error::SerializationError` - `period` is outside 1ms to 512ms
+    /// * `Error::DeviceBusy` - An ALS measurement is running, with the busy
+    ///   check enabled only
+    /// * `Error::BusError` - I2C communication failed
+    pub fn set_als_integration(&mut self, period: Duration) -> Result<(), Error> {
+        self.write_held(AlsIntegrationPeriod { period })
+    }
+
+    /// Returns the ALS integration period.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn als_integration(&mut self) -> Result<Duration, Error> {
+        let register: AlsIntegrationPeriod = self.read_register()?;
+        Ok(register.period)
+    }
+
+    /// Sets the range convergence time limit.
+    ///
+    /// Written inside a grouped parameter hold, like the adaptive timing
+    /// does. Costs three I2C transactions.
+    ///
+    /// # Errors
+    /// * `Error::SerializationError` - `time` is outside 1ms to 63ms
+    /// * `Error::DeviceBusy` - A range measurement is running, with the busy
+    ///   check enabled only
+    /// * `Error::BusError` - I2C communication failed
+    pub fn set_max_convergence_time(&mut self, time: Duration) -> Result<(), Error> {
+        self.write_held(RangeMaxConvergenceTime { time })
+    }
+
+    /// Returns the range convergence time limit.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn max_convergence_time(&mut self) -> Result<Duration, Error> {
+        let register: RangeMaxConvergenceTime = self.read_register()?;
+        Ok(register.time)
+    }
+
+    /// Sets the part-to-part range offset, replacing the factory calibration
+    /// until the next boot.
+    ///
+    /// The offset is written in whole millimeters.
+    ///
+    /// # Errors
+    /// * `Error::OutOfSpec` - `offset` is outside -128mm to 127mm, in strict
+    ///   mode only
+    /// * `Error::DeviceBusy` - A range measurement is running, with the busy
+    ///   check enabled only
+    /// * `Error::BusError` - I2C communication failed
+    pub fn set_range_offset(&mut self, offset: Length) -> Result<(), Error> {
+        self.write_register(RangePartToPartOffset { offset })
+    }
+
+    /// Returns the part-to-part range offset.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn range_offset(&mut self) -> Result<Length, Error> {
+        let register: RangePartToPartOffset = self.read_register()?;
+        Ok(register.offset)
+    }
+
+    /// Sets the crosstalk compensation rate.
+    ///
+    /// # Errors
+    /// * `Error::DeviceBusy` - A range measurement is running, with the busy
+    ///   check enabled only
+    /// * `Error::BusError` - I2C communication failed
+    pub fn set_crosstalk_rate(&mut self, rate: Mcps) -> Result<(), Error> {
+        self.write_register(RangeCrosstalkCompensationRate { rate: rate.fixed })
+    }
+
+    /// Returns the crosstalk compensation rate.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn crosstalk_rate(&mut self) -> Result<Mcps, Error> {
+        let register: RangeCrosstalkCompensationRate = self.read_register()?;
+        Ok(Mcps::from_fixed(register.rate))
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously sets the ALS analog gain.
+    ///
+    /// This is the async version of [`set_als_gain`](Device::set_als_gain).
+    pub async fn set_als_gain_async(&mut self, gain: AlsGain) -> Result<(), Error> {
+        self.write_held_async(AlsAnalogueGain::new(gain)).await
+    }
+
+    /// Asynchronously returns the ALS analog gain.
+    ///
+    /// This is the async version of [`als_gain`](Device::als_gain).
+    pub async fn als_gain_async(&mut self) -> Result<AlsGain, Error> {
+        let register: AlsAnalogueGain = self.read_register_async().await?;
+        Ok(register.gain)
+    }
+
+    /// Asynchronously sets the ALS integration period.
+    ///
+    /// This is the async version of [`set_als_integration`](Device::set_als_integration).
+    pub async fn set_als_integration_async(&mut self, period: Duration) -> Result<(), Error> {
+        self.write_held_async(AlsIntegrationPeriod { period }).await
+    }
+
+    /// Asynchronously returns the ALS integration period.
+    ///
+    /// This is the async version of [`als_integration`](Device::als_integration).
+    pub async fn als_integration_async(&mut self) -> Result<Duration, Error> {
+        let register: AlsIntegrationPeriod = self.read_register_async().await?;
+        Ok(register.period)
+    }
+
+    /// Asynchronously sets the range convergence time limit.
+    ///
+    /// This is the async version of [`set_max_convergence_time`](Device::set_max_convergence_time).
+    pub async fn set_max_convergence_time_async(&mut self, time: Duration) -> Result<(), Error> {
+        self.write_held_async(RangeMaxConvergenceTime { time })
+            .await
+    }
+
+    /// Asynchronously returns the range convergence time limit.
+    ///
+    /// This is the async version of [`max_convergence_time`](Device::max_convergence_time).
+    pub async fn max_convergence_time_async(&mut self) -> Result<Duration, Error> {
+        let register: RangeMaxConvergenceTime = self.read_register_async().await?;
+        Ok(register.time)
+    }
+
+    /// Asynchronously sets the part-to-part range offset.
+    ///
+    /// This is the async version of [`set_range_offset`](Device::set_range_offset).
+    pub async fn set_range_offset_async(&mut self, offset: Length) -> Result<(), Error> {
+        self.write_register_async(RangePartToPartOffset { offset })
+            .await
+    }
+
+    /// Asynchronously returns the part-to-part range offset.
+    ///
+    /// This is the async version of [`range_offset`](Device::range_offset).
+    pub async fn range_offset_async(&mut self) -> Result<Length, Error> {
+        let register: RangePartToPartOffset = self.read_register_async().await?;
+        Ok(register.offset)
+    }
+
+    /// Asynchronously sets the crosstalk compensation rate.
+    ///
+    /// This is the async version of [`set_crosstalk_rate`](Device::set_crosstalk_rate).
+    pub async fn set_crosstalk_rate_async(&mut self, rate: Mcps) -> Result<(), Error> {
+        self.write_register_async(RangeCrosstalkCompensationRate { rate: rate.fixed })
+            .await
+    }
+
+    /// Asynchronously returns the crosstalk compensation rate.
+    ///
+    /// This is the async version of [`crosstalk_rate`](Device::crosstalk_rate).
+    pub async fn crosstalk_rate_async(&mut self) -> Result<Mcps, Error> {
+        let register: RangeCrosstalkCompensationRate = self.read_register_async().await?;
+        Ok(Mcps::from_fixed(register.rate))
+    }
+}
@@ -0,0 +1,253 @@
+//! Split configuration and result access
+//!
+//! [`Device::split`] turns one device into two handles addressing the same
+//! sensor: a [`ConfigHandle`] with full register access for the main task and
+//! a [`ResultReader`] that can only read result registers and clear
+//! interrupts, suitable for handing to an interrupt handler.
+
+use regiface::ReadableRegister;
+
+use super::{interrupt, Device, DeviceAddress, RuntimeAddress};
+use crate::events::EventQueue;
+use crate::registers::{
+    DatasheetLimits, InterruptClear, ResultInterruptStatusGpio, ResultRegister,
+};
+use crate::types::{Error, Timeouts};
+
+/// Configuration half of a split [`Device`]
+///
+/// Has the same register access as the original device.
+pub struct ConfigHandle<I2C, A: DeviceAddress = RuntimeAddress> {
+    device: Device<I2C, A>,
+}
+
+/// Result half of a split [`Device`]
+///
+/// Can only read the measurement result registers and write the interrupt
+/// clear register.
+pub struct ResultReader<I2C, A: DeviceAddress = RuntimeAddress> {
+    device: Device<I2C, A>,
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A> {
+    /// Splits the device into a configuration handle and a result reader.
+    ///
+    /// `result_bus` must be a second handle to the same physical bus as the
+    /// device's own, such as two `embedded-hal-bus` `CriticalSectionDevice`s
+    /// created from the same bus. The reader addresses the same sensor as the
+    /// device and starts with its settings: strict mode, the busy check,
+    /// paranoid reads, the spurious interrupt policy, the timeouts, the
+    /// adaptive timing policy, the clock, the bus recovery hook and the
+    /// cached configuration registers. Changing a setting on one half later
+    /// does not affect the other.
+    ///
+    /// # Concurrency
+    ///
+    /// Every register access is a single I2C transaction, so mutual exclusion
+    /// on the bus is entirely up to the shared-bus wrapper: use one that is
+    /// safe to access from where each half runs (e.g. a critical section based
+    /// wrapper when the reader lives in an interrupt handler). The halves are
+    /// not otherwise synchronized. A multi-register sequence on one half, such
+    /// as reading a status followed by its value, may have accesses from the
+    /// other half interleaved with it; the result registers are stable until
+    /// the interrupt is cleared, so this is harmless as long as only the
+    /// reader clears interrupts. The configuration handle should not start or
+    /// stop measurements while the reader expects a particular mode.
+    ///
+    /// With the `bus-stats` feature each half keeps its own counters; the
+    /// device's counters move to the configuration handle.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use core::cell::RefCell;
+    /// use embedded_hal::i2c::I2c;
+    /// use embedded_hal_bus::i2c::RefCellDevice;
+    /// use vl6180x::{
+    ///     registers::{InterruptClear, RangeResultValue, RangeStart},
+    ///     Device,
+    /// };
+    ///
+    /// fn interleave<I2C: I2c>(i2c: I2C) -> Result<(), vl6180x::Error> {
+    ///     let bus = RefCell::new(i2c);
+    ///     let sensor = Device::new(RefCellDevice::new(&bus));
+    ///
+    ///     let (mut config, mut reader) = sensor.split(RefCellDevice::new(&bus));
+    ///
+    ///     config.write_register(RangeStart::SingleShot)?;
+    ///     let _distance: RangeResultValue = reader.read_register()?;
+    ///     reader.clear_interrupts(InterruptClear {
+    ///         clear_range: true,
+    ///         clear_als: false,
+    ///         clear_error: false,
+    ///     })?;
+    ///     config.write_register(RangeStart::SingleShot)?;
+    ///
+    ///     let (_sensor, _bus) = config.rejoin(reader);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn split(self, result_bus: I2C) -> (ConfigHandle<I2C, A>, ResultReader<I2C, A>) {
+        let mut reader = Device::with_address(result_bus, self.address);
+        reader.strict = self.strict;
+        reader.busy_check = self.busy_check;
+        reader.paranoid = self.paranoid;
+        reader.spurious_policy = self.spurious_policy;
+        reader.cache = self.cache;
+        reader.timeouts = self.timeouts;
+        reader.adaptive_timing = self.adaptive_timing;
+        reader.clock = self.clock;
+        reader.bus_recovery = self.bus_recovery;
+        (
+            ConfigHandle { device: self },
+            ResultReader { device: reader },
+        )
+    }
+}
+
+impl<I2C, A: DeviceAddress> ConfigHandle<I2C, A> {
+    /// Reassembles the original device.
+    ///
+    /// Returns the device together with the bus handle that was passed to
+    /// [`Device::split`].
+    pub fn rejoin(self, reader: ResultReader<I2C, A>) -> (Device<I2C, A>, I2C) {
+        (self.device, reader.device.release())
+    }
+}
+
+impl<I2C, A: DeviceAddress> ConfigHandle<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Reads a register value from the device.
+    ///
+    /// See [`Device::read_register`].
+    pub fn read_register<R>(&mut self) -> Result<R, Error>
+    where
+        R: ReadableRegister<IdType = u16>,
+    {
+        self.device.read_register()
+    }
+
+    /// Writes a value to a device register.
+    ///
+    /// See [`Device::write_register`].
+    pub fn write_register<R>(&mut self, register: R) -> Result<(), Error>
+    where
+        R: DatasheetLimits,
+    {
+        self.device.write_register(register)
+    }
+}
+
+impl<I2C, A: DeviceAddress> ConfigHandle<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously reads a register value from the device.
+    ///
+    /// This is the async version of [`read_register`](ConfigHandle::read_register).
+    pub async fn read_register_async<R>(&mut self) -> Result<R, Error>
+    where
+        R: ReadableRegister<IdType = u16>,
+    {
+        self.device.read_register_async().await
+    }
+
+    /// Asynchronously writes a value to a device register.
+    ///
+    /// This is the async version of [`write_register`](ConfigHandle::write_register).
+    pub async fn write_register_async<R>(&mut self, register: R) -> Result<(), Error>
+    where
+        R: DatasheetLimits,
+    {
+        self.device.write_register_async(register).await
+    }
+}
+
+impl<I2C, A: DeviceAddress> ResultReader<I2C, A> {
+    /// Returns the timeouts of the reader, see [`Device::timeouts`].
+    pub fn timeouts(&self) -> Timeouts {
+        self.device.timeouts()
+    }
+
+    /// Returns whether the reader verifies result reads, see
+    /// [`Device::set_paranoid`].
+    pub fn is_paranoid(&self) -> bool {
+        self.device.is_paranoid()
+    }
+}
+
+impl<I2C, A: DeviceAddress> ResultReader<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Reads a result register from the device.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::DeserializationError` - Failed to parse register value
+    pub fn read_register<R>(&mut self) -> Result<R, Error>
+    where
+        R: ResultRegister,
+    {
+        self.device.read_register()
+    }
+
+    /// Clears the selected interrupt flags.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn clear_interrupts(&mut self, clear: InterruptClear) -> Result<(), Error> {
+        self.device.write_register(clear)
+    }
+
+    /// Reads, clears and queues the pending interrupts.
+    ///
+    /// See [`Device::handle_interrupt`].
+    pub fn handle_interrupt<const N: usize>(
+        &mut self,
+        queue: &mut EventQueue<N>,
+    ) -> Result<ResultInterruptStatusGpio, Error> {
+        let status: ResultInterruptStatusGpio = self.read_register()?;
+        if let Some(clear) = interrupt::enqueue(status, queue) {
+            self.clear_interrupts(clear)?;
+        }
+        Ok(status)
+    }
+}
+
+impl<I2C, A: DeviceAddress> ResultReader<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously reads a result register from the device.
+    ///
+    /// This is the async version of [`read_register`](ResultReader::read_register).
+    pub async fn read_register_async<R>(&mut self) -> Result<R, Error>
+    where
+        R: ResultRegister,
+    {
+        self.device.read_register_async().await
+    }
+
+    /// Asynchronously clears the selected interrupt flags.
+    ///
+    /// This is the async version of [`clear_interrupts`](ResultReader::clear_interrupts).
+    pub async fn clear_interrupts_async(&mut self, clear: InterruptClear) -> Result<(), Error> {
+        self.device.write_register_async(clear).await
+    }
+
+    /// Asynchronously reads, clears and queues the pending interrupts.
+    ///
+    /// This is the async version of [`handle_interrupt`](ResultReader::handle_interrupt).
+    pub async fn handle_interrupt_async<const N: usize>(
+        &mut self,
+        queue: &mut EventQueue<N>,
+    ) -> Result<ResultInterruptStatusGpio, Error> {
+        let status: ResultInterruptStatusGpio = self.read_register_async().await?;
+        if let Some(clear) = interrupt::enqueue(status, queue) {
+            self.clear_interrupts_async(clear).await?;
+        }
+        Ok(status)
+    }
+}
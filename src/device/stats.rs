@@ -0,0 +1,93 @@
+//! Bus transaction instrumentation
+//!
+//! With the `bus-stats` feature enabled, every register access records the
+//! I2C transactions and bytes it cost, each attempt of a retried access as
+//! a transaction of its own. Without the feature the recording
+//! hooks compile to nothing and [`Device`] carries no extra state.
+
+use super::{Device, DeviceAddress};
+
+/// Number of address bytes sent at the start of every register access
+#[cfg(feature = "bus-stats")]
+const ADDRESS_BYTES: u32 = 2;
+
+/// I2C traffic counters accumulated by a [`Device`]
+///
+/// All counters saturate rather than wrap.
+#[cfg(feature = "bus-stats")]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BusStats {
+    /// Number of read transactions (register address write followed by a read)
+    pub reads: u32,
+    /// Number of write transactions
+    pub writes: u32,
+    /// Total bytes written, including register addresses
+    pub bytes_written: u32,
+    /// Total bytes read
+    pub bytes_read: u32,
+    /// Number of transactions that failed on the bus
+    pub errors: u32,
+}
+
+#[cfg(feature = "bus-stats")]
+impl BusStats {
+    /// Total number of transactions, successful or not
+    pub const fn transactions(&self) -> u32 {
+        self.reads.saturating_add(self.writes)
+    }
+
+    fn record_read(&mut self, len: usize, ok: bool) {
+        self.reads = self.reads.saturating_add(1);
+        self.bytes_written = self.bytes_written.saturating_add(ADDRESS_BYTES);
+        if ok {
+            self.bytes_read = self.bytes_read.saturating_add(len as u32);
+        } else {
+            self.errors = self.errors.saturating_add(1);
+        }
+    }
+
+    fn record_write(&mut self, len: usize, ok: bool) {
+        self.writes = self.writes.saturating_add(1);
+        if ok {
+            self.bytes_written = self
+                .bytes_written
+                .saturating_add(ADDRESS_BYTES + len as u32);
+        } else {
+            self.errors = self.errors.saturating_add(1);
+        }
+    }
+}
+
+#[cfg(feature = "bus-stats")]
+impl<I2C, A: DeviceAddress> Device<I2C, A> {
+    /// Returns the I2C traffic recorded since creation or the last reset.
+    pub fn stats(&self) -> BusStats {
+        self.stats
+    }
+
+    /// Clears the recorded I2C traffic counters.
+    pub fn reset_stats(&mut self) {
+        self.stats = BusStats::default();
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A> {
+    /// Records a read transaction of `len` data bytes.
+    #[inline(always)]
+    pub(super) fn record_read(&mut self, len: usize, ok: bool) {
+        #[cfg(feature = "bus-stats")]
+        self.stats.record_read(len, ok);
+        #[cfg(not(feature = "bus-stats"))]
+        let _ = (len, ok);
+    }
+
+    /// Records a write transaction of `len` data bytes.
+    #[inline(always)]
+    pub(super) fn record_write(&mut self, len: usize, ok: bool) {
+        #[cfg(feature = "bus-stats")]
+        self.stats.record_write(len, ok);
+        #[cfg(not(feature = "bus-stats"))]
+        let _ = (len, ok);
+    }
+}
@@ -0,0 +1,126 @@
+//! Timestamped measurements
+
+use super::{Device, DeviceAddress};
+use crate::clock::{Clock, ClockRef, Timestamped};
+use crate::types::{AlsReading, Error, RangeReading};
+
+impl<I2C, A: DeviceAddress> Device<I2C, A> {
+    /// Attaches a clock used to timestamp samples.
+    ///
+    /// From now on the measurement helpers note the time each sample-ready
+    /// flag is observed, see [`last_sample_time`](Device::last_sample_time).
+    /// Without a clock nothing is timestamped and no time is read.
+    ///
+    /// # Arguments
+    /// * `clock` - Monotonic millisecond clock
+    pub fn set_clock(&mut self, clock: &'static dyn Clock) {
+        self.clock = Some(ClockRef(clock));
+    }
+
+    /// Detaches the clock, see [`set_clock`](Device::set_clock).
+    pub fn remove_clock(&mut self) {
+        self.clock = None;
+        self.last_sample_time = None;
+    }
+
+    /// Returns the time the latest sample-ready flag was observed by a
+    /// measurement helper, or `None` without a clock attached.
+    pub fn last_sample_time(&self) -> Option<u64> {
+        self.last_sample_time
+    }
+
+    /// Notes the time a sample-ready flag was observed
+    pub(super) fn mark_sample(&mut self) {
+        self.last_sample_time = self.clock.map(|clock| clock.0.now_millis());
+    }
+
+    /// Pairs a measurement with the time its sample was marked
+    fn stamp<T>(&self, value: T) -> Timestamped<T> {
+        Timestamped {
+            value,
+            timestamp: self.last_sample_time,
+        }
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Performs a single-shot range measurement and timestamps it.
+    ///
+    /// Like [`read_range`](Device::read_range), with the time the sample
+    /// became ready attached. The timestamp is `None` without a clock, see
+    /// [`set_clock`](Device::set_clock).
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::Timeout` - No sample was reported within the polling budget
+    pub fn read_range_timestamped<D>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<Timestamped<RangeReading>, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        self.last_sample_time = None;
+        let reading = self.read_range(delay)?;
+        Ok(self.stamp(reading))
+    }
+
+    /// Performs a single-shot ALS measurement and timestamps it.
+    ///
+    /// Like [`read_als`](Device::read_als), with the time the sample became
+    /// ready attached. The timestamp is `None` without a clock, see
+    /// [`set_clock`](Device::set_clock).
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::Timeout` - No sample was reported within the polling budget
+    pub fn read_als_timestamped<D>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<Timestamped<AlsReading>, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        self.last_sample_time = None;
+        let reading = self.read_als(delay)?;
+        Ok(self.stamp(reading))
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously performs a single-shot range measurement and timestamps it.
+    ///
+    /// This is the async version of [`read_range_timestamped`](Device::read_range_timestamped).
+    pub async fn read_range_timestamped_async<D>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<Timestamped<RangeReading>, Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        self.last_sample_time = None;
+        let reading = self.read_range_async(delay).await?;
+        Ok(self.stamp(reading))
+    }
+
+    /// Asynchronously performs a single-shot ALS measurement and timestamps it.
+    ///
+    /// This is the async version of [`read_als_timestamped`](Device::read_als_timestamped).
+    pub async fn read_als_timestamped_async<D>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<Timestamped<AlsReading>, Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        self.last_sample_time = None;
+        let reading = self.read_als_async(delay).await?;
+        Ok(self.stamp(reading))
+    }
+}
@@ -0,0 +1,309 @@
+//! Wake on approach
+//!
+//! Lets a battery powered host sleep until something comes close, then
+//! return to normal ranging.
+
+use core::time::Duration;
+
+use measurements::Length;
+
+use super::batch::Batch;
+use super::continuous::ContinuousKind;
+use super::period::check_period;
+use super::range::CLEAR_RANGE;
+use super::{Device, DeviceAddress};
+use crate::registers::{
+    InterruptConfigGpio, ModeGpio1, RangeIntermeasurementPeriod, RangeMaxConvergenceTime,
+};
+use crate::types::{Error, GpioFunction, RangeInterrupt, RangeSchedule};
+
+impl<I2C, A: DeviceAddress> Device<I2C, A> {
+    /// Fails with `Error::DeviceBusy` while interleaved mode or a pending
+    /// single-shot owns the ranging core
+    fn refuse_during_other_ranging(&self) -> Result<(), Error> {
+        if self.in_flight.interleaved {
+            return Err(Error::DeviceBusy);
+        }
+        self.refuse_during_pending_range()
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Arms the sensor to raise GPIO1 when something comes within `threshold`.
+    ///
+    /// Stops continuous ranging started by this driver, routes the interrupt output
+    /// to GPIO1 keeping its configured polarity, selects a
+    /// [`LevelLow`](RangeInterrupt::LevelLow) range interrupt at `threshold`,
+    /// clears any pending range interrupt and starts continuous ranging every
+    /// `sample_period`. The ALS interrupt mode is left unchanged.
+    ///
+    /// The handshake with the host is:
+    ///
+    /// 1. Arm the sensor with this method.
+    /// 2. Configure GPIO1 as a wake source for the active edge of the
+    ///    interrupt output and put the host to sleep. Sleeping is up to the
+    ///    host; the driver does not touch the MCU.
+    /// 3. Once a sample falls below `threshold`, GPIO1 asserts and stays
+    ///    asserted until the range interrupt is cleared.
+    /// 4. After waking, call [`disarm_and_resume`](Device::disarm_and_resume),
+    ///    which clears the interrupt and returns to the normal rate.
+    ///
+    /// # Arguments
+    /// * `delay` - Delay provider used while waiting for ranging to stop
+    /// * `threshold` - Distance below which GPIO1 asserts
+    /// * `sample_period` - Intermeasurement period while armed
+    ///
+    /// # Example
+    /// ```no_run
+    /// use core::time::Duration;
+    /// use embedded_hal::{delay::DelayNs, i2c::I2c};
+    /// use measurements::Length;
+    /// use vl6180x::{Device, RangeInterrupt, RangeSchedule};
+    ///
+    /// fn sleep_until_approach<I2C: I2c, D: DelayNs>(
+    ///     sensor: &mut Device<I2C>,
+    ///     delay: &mut D,
+    ///     sleep: impl FnOnce(),
+    /// ) -> Result<(), vl6180x::Error> {
+    ///     sensor.arm_wake_on_approach(
+    ///         delay,
+    ///         Length::from_millimeters(60.0),
+    ///         Duration::from_millis(500),
+    ///     )?;
+    ///     sleep();
+    ///     sensor.disarm_and_resume(
+    ///         delay,
+    ///         RangeSchedule {
+    ///             period: Duration::from_millis(50),
+    ///             interrupt: RangeInterrupt::NewSampleReady,
+    ///         },
+    ///     )
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// * `Error::DeviceBusy` - Interleaved mode runs, or a single-shot range
+    ///   measurement started by `try_read_range` was not collected yet
+    /// * `Error::PeriodTooShort` - `sample_period` is shorter than one measurement
+    /// * `Error::SerializationError` - `sample_period` is outside 10ms to 2560ms
+    /// * `Error::Timeout` - Continuous ranging did not stop
+    /// * `Error::BusError` - I2C communication failed
+    pub fn arm_wake_on_approach<D>(
+        &mut self,
+        delay: &mut D,
+        threshold: Length,
+        sample_period: Duration,
+    ) -> Result<(), Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        self.begin_operation("arm_wake_on_approach");
+        let result = self.arm_wake(delay, threshold, sample_period);
+        self.end_operation(result)
+    }
+
+    /// Leaves wake on approach and restarts continuous ranging at the normal rate.
+    ///
+    /// Stops the slow ranging started by
+    /// [`arm_wake_on_approach`](Device::arm_wake_on_approach), selects the
+    /// range interrupt of `schedule`, clears the pending range interrupt so
+    /// GPIO1 is released, and starts continuous ranging every
+    /// `schedule.period`. GPIO1 keeps its interrupt output function.
+    ///
+    /// # Arguments
+    /// * `delay` - Delay provider used while waiting for ranging to stop
+    /// * `schedule` - Continuous ranging configuration to resume with
+    ///
+    /// # Errors
+    /// * `Error::DeviceBusy` - Interleaved mode runs, or a single-shot range
+    ///   measurement started by `try_read_range` was not collected yet
+    /// * `Error::PeriodTooShort` - The period is shorter than one measurement
+    /// * `Error::SerializationError` - The period is outside 10ms to 2560ms
+    /// * `Error::Timeout` - Continuous ranging did not stop
+    /// * `Error::BusError` - I2C communication failed
+    pub fn disarm_and_resume<D>(
+        &mut self,
+        delay: &mut D,
+        schedule: RangeSchedule,
+    ) -> Result<(), Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        self.begin_operation("disarm_and_resume");
+        let result = self.resume_ranging(delay, schedule);
+        self.end_operation(result)
+    }
+
+    fn arm_wake<D>(
+        &mut self,
+        delay: &mut D,
+        threshold: Length,
+        sample_period: Duration,
+    ) -> Result<(), Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        self.refuse_during_other_ranging()?;
+        let period = self.checked_range_period(sample_period)?;
+        self.halt_continuous_range(delay)?;
+        self.modify_register(|gpio: &mut ModeGpio1| {
+            gpio.function = GpioFunction::InterruptOutput;
+        })?;
+        self.start_ranging_with(period, RangeInterrupt::LevelLow { low: threshold })
+    }
+
+    fn resume_ranging<D>(&mut self, delay: &mut D, schedule: RangeSchedule) -> Result<(), Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        self.refuse_during_other_ranging()?;
+        let period = self.checked_range_period(schedule.period)?;
+        self.halt_continuous_range(delay)?;
+        self.start_ranging_with(period, schedule.interrupt)
+    }
+
+    /// Validates a continuous ranging period against the configured convergence limit
+    fn checked_range_period(
+        &mut self,
+        period: Duration,
+    ) -> Result<RangeIntermeasurementPeriod, Error> {
+        let limit: RangeMaxConvergenceTime = self.read_register()?;
+        check_period(
+            RangeIntermeasurementPeriod { period },
+            period,
+            limit.measurement_time(),
+        )
+    }
+
+    /// Configures and starts continuous ranging with the ranging core idle
+    ///
+    /// The thresholds and the period (0x019 - 0x01B), and the interrupt
+    /// configuration and clear (0x014 - 0x015), are written as one block each.
+    fn start_ranging_with(
+        &mut self,
+        period: RangeIntermeasurementPeriod,
+        interrupt: RangeInterrupt,
+    ) -> Result<(), Error> {
+        let mut batch = Batch::default();
+        if let Some(thresholds) = interrupt.thresholds() {
+            self.batch_register(&mut batch, thresholds)?;
+        }
+        self.batch_register(&mut batch, period)?;
+        self.write_batch(&mut batch)?;
+
+        let mut config: InterruptConfigGpio = self.read_register()?;
+        config.range_interrupt = interrupt.mode();
+        self.batch_register(&mut batch, config)?;
+        self.batch_register(&mut batch, CLEAR_RANGE)?;
+        self.write_batch(&mut batch)?;
+        self.begin_continuous(ContinuousKind::Range)
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously arms the sensor to raise GPIO1 when something comes within `threshold`.
+    ///
+    /// This is the async version of [`arm_wake_on_approach`](Device::arm_wake_on_approach).
+    pub async fn arm_wake_on_approach_async<D>(
+        &mut self,
+        delay: &mut D,
+        threshold: Length,
+        sample_period: Duration,
+    ) -> Result<(), Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        self.begin_operation("arm_wake_on_approach");
+        let result = self.arm_wake_async(delay, threshold, sample_period).await;
+        self.end_operation(result)
+    }
+
+    /// Asynchronously leaves wake on approach and restarts continuous ranging at the normal rate.
+    ///
+    /// This is the async version of [`disarm_and_resume`](Device::disarm_and_resume).
+    pub async fn disarm_and_resume_async<D>(
+        &mut self,
+        delay: &mut D,
+        schedule: RangeSchedule,
+    ) -> Result<(), Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        self.begin_operation("disarm_and_resume");
+        let result = self.resume_ranging_async(delay, schedule).await;
+        self.end_operation(result)
+    }
+
+    async fn arm_wake_async<D>(
+        &mut self,
+        delay: &mut D,
+        threshold: Length,
+        sample_period: Duration,
+    ) -> Result<(), Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        self.refuse_during_other_ranging()?;
+        let period = self.checked_range_period_async(sample_period).await?;
+        self.halt_continuous_range_async(delay).await?;
+        self.modify_register_async(|gpio: &mut ModeGpio1| {
+            gpio.function = GpioFunction::InterruptOutput;
+        })
+        .await?;
+        self.start_ranging_with_async(period, RangeInterrupt::LevelLow { low: threshold })
+            .await
+    }
+
+    async fn resume_ranging_async<D>(
+        &mut self,
+        delay: &mut D,
+        schedule: RangeSchedule,
+    ) -> Result<(), Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        self.refuse_during_other_ranging()?;
+        let period = self.checked_range_period_async(schedule.period).await?;
+        self.halt_continuous_range_async(delay).await?;
+        self.start_ranging_with_async(period, schedule.interrupt)
+            .await
+    }
+
+    async fn checked_range_period_async(
+        &mut self,
+        period: Duration,
+    ) -> Result<RangeIntermeasurementPeriod, Error> {
+        let limit: RangeMaxConvergenceTime = self.read_register_async().await?;
+        check_period(
+            RangeIntermeasurementPeriod { period },
+            period,
+            limit.measurement_time(),
+        )
+    }
+
+    async fn start_ranging_with_async(
+        &mut self,
+        period: RangeIntermeasurementPeriod,
+        interrupt: RangeInterrupt,
+    ) -> Result<(), Error> {
+        let mut batch = Batch::default();
+        if let Some(thresholds) = interrupt.thresholds() {
+            self.batch_register_async(&mut batch, thresholds).await?;
+        }
+        self.batch_register_async(&mut batch, period).await?;
+        self.write_batch_async(&mut batch).await?;
+
+        let mut config: InterruptConfigGpio = self.read_register_async().await?;
+        config.range_interrupt = interrupt.mode();
+        self.batch_register_async(&mut batch, config).await?;
+        self.batch_register_async(&mut batch, CLEAR_RANGE).await?;
+        self.write_batch_async(&mut batch).await?;
+        self.begin_continuous_async(ContinuousKind::Range).await
+    }
+}
@@ -0,0 +1,281 @@
+//! Callback-driven threshold watches
+//!
+//! For firmwares without an event loop of their own: register a closure per
+//! threshold condition, arm the conditions on the device and call
+//! [`Watch::service`] whenever the sensor may have raised an interrupt.
+
+use measurements::Length;
+
+use super::als::{als_result, AlsSample};
+use super::interrupt::acknowledge;
+use super::range::{range_result, RangeSample};
+use super::{Device, DeviceAddress, RuntimeAddress};
+use crate::registers::{
+    AlsAnalogueGain, AlsIntegrationPeriod, AlsResultBlock, RangeResultStatus, RangeResultValue,
+    ResultInterruptStatusGpio,
+};
+use crate::types::{AlsInterrupt, Error, Luminance, RangeInterrupt};
+
+/// Threshold conditions of a [`Device`] with a callback each
+///
+/// Created by [`Device::watch`]. The device evaluates one threshold
+/// condition per sensor, so a watch holds one range and one ALS slot;
+/// registering another condition for the same sensor replaces the first.
+/// The callbacks are stored in the watch itself and need no allocation.
+///
+/// The conditions take effect once [`arm`](Watch::arm) has written them to
+/// the device. Routing the interrupts to GPIO1 and starting the
+/// measurements is left to the caller.
+pub struct Watch<'a, I2C, R = fn(Length), A = fn(Luminance), Addr = RuntimeAddress> {
+    device: &'a mut Device<I2C, Addr>,
+    range: Option<(RangeInterrupt, R)>,
+    als: Option<(Luminance, bool, A)>,
+}
+
+/// Watch without any registered condition
+type EmptyWatch<'a, I2C, Addr> = Watch<'a, I2C, fn(Length), fn(Luminance), Addr>;
+
+impl<I2C, Addr: DeviceAddress> Device<I2C, Addr> {
+    /// Starts a set of threshold conditions with callbacks.
+    ///
+    /// See [`Watch`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// use embedded_hal::i2c::I2c;
+    /// use measurements::Length;
+    /// use vl6180x::{Device, Error, Luminance};
+    ///
+    /// fn run<I2C: I2c>(sensor: &mut Device<I2C>) -> Result<(), Error> {
+    ///     let mut close = 0;
+    ///     let mut watch = sensor
+    ///         .watch()
+    ///         .on_range_below(Length::from_millimeters(60.0), |_distance| close += 1)
+    ///         .on_als_above(Luminance::from_lux(500.0), |_light| {});
+    ///     watch.arm()?;
+    ///     loop {
+    ///         // e.g. after the GPIO1 interrupt fired
+    ///         watch.service()?;
+    ///     }
+    /// }
+    /// ```
+    pub fn watch(&mut self) -> EmptyWatch<'_, I2C, Addr> {
+        Watch {
+            device: self,
+            range: None,
+            als: None,
+        }
+    }
+}
+
+impl<'a, I2C, R, A, Addr> Watch<'a, I2C, R, A, Addr> {
+    /// Calls `callback` with the distance of each sample below `limit`.
+    pub fn on_range_below<F>(self, limit: Length, callback: F) -> Watch<'a, I2C, F, A, Addr>
+    where
+        F: FnMut(Length),
+    {
+        self.with_range(RangeInterrupt::LevelLow { low: limit }, callback)
+    }
+
+    /// Calls `callback` with the distance of each sample above `limit`.
+    pub fn on_range_above<F>(self, limit: Length, callback: F) -> Watch<'a, I2C, F, A, Addr>
+    where
+        F: FnMut(Length),
+    {
+        self.with_range(RangeInterrupt::LevelHigh { high: limit }, callback)
+    }
+
+    /// Calls `callback` with the light level of each sample below `limit`.
+    ///
+    /// The device compares raw ALS counts; [`arm`](Watch::arm) converts
+    /// `limit` with the ALS gain and integration period configured at that
+    /// time, so arm again after changing them.
+    pub fn on_als_below<F>(self, limit: Luminance, callback: F) -> Watch<'a, I2C, R, F, Addr>
+    where
+        F: FnMut(Luminance),
+    {
+        self.with_als(limit, false, callback)
+    }
+
+    /// Calls `callback` with the light level of each sample above `limit`.
+    ///
+    /// See [`on_als_below`](Watch::on_als_below) for how `limit` is applied.
+    pub fn on_als_above<F>(self, limit: Luminance, callback: F) -> Watch<'a, I2C, R, F, Addr>
+    where
+        F: FnMut(Luminance),
+    {
+        self.with_als(limit, true, callback)
+    }
+
+    fn with_range<F>(self, condition: RangeInterrupt, callback: F) -> Watch<'a, I2C, F, A, Addr> {
+        Watch {
+            device: self.device,
+            range: Some((condition, callback)),
+            als: self.als,
+        }
+    }
+
+    fn with_als<F>(self, limit: Luminance, above: bool, callback: F) -> Watch<'a, I2C, R, F, Addr> {
+        Watch {
+            device: self.device,
+            range: self.range,
+            als: Some((limit, above, callback)),
+        }
+    }
+
+    /// The ALS condition for the counts `limit` corresponds to
+    fn als_condition(
+        limit: Luminance,
+        above: bool,
+        gain: AlsAnalogueGain,
+        integration: AlsIntegrationPeriod,
+    ) -> AlsInterrupt {
+        let counts = Luminance::from_lux(f32::from(limit.to_counts(gain.gain, integration.period)));
+        if above {
+            AlsInterrupt::LevelHigh { high: counts }
+        } else {
+            AlsInterrupt::LevelLow { low: counts }
+        }
+    }
+
+    /// Hands the samples read for the pending interrupts to their callbacks
+    fn dispatch(&mut self, range: Option<RangeSample>, als: Option<AlsSample>)
+    where
+        R: FnMut(Length),
+        A: FnMut(Luminance),
+    {
+        if let (Some(sample), Some((_, callback))) = (range, &mut self.range) {
+            if let Ok(distance) = range_result(sample) {
+                callback(distance);
+            }
+        }
+        if let (Some(sample), Some((_, _, callback))) = (als, &mut self.als) {
+            if let Ok(light) = als_result(sample) {
+                callback(light);
+            }
+        }
+    }
+}
+
+impl<I2C, R, A, Addr> Watch<'_, I2C, R, A, Addr>
+where
+    Addr: DeviceAddress,
+    I2C: embedded_hal::i2c::I2c,
+    R: FnMut(Length),
+    A: FnMut(Luminance),
+{
+    /// Writes the thresholds and interrupt modes of the registered conditions.
+    ///
+    /// The interrupt mode of a sensor without a registered condition is left
+    /// unchanged.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn arm(&mut self) -> Result<(), Error> {
+        if let Some((condition, _)) = self.range {
+            self.device.set_range_interrupt(condition)?;
+        }
+        if let Some((limit, above, _)) = self.als {
+            let gain = self.device.read_register()?;
+            let integration = self.device.read_register()?;
+            let condition = Self::als_condition(limit, above, gain, integration);
+            self.device.set_als_interrupt(condition)?;
+        }
+        Ok(())
+    }
+
+    /// Reads the pending interrupts and invokes the callbacks of the sensors
+    /// that raised one.
+    ///
+    /// Call from the main loop or the GPIO1 interrupt handler. Reads the
+    /// latest sample of each watched sensor with a pending interrupt, clears
+    /// all pending interrupts, including those of unwatched sensors, and then
+    /// invokes the callbacks. Samples completed with an error code are
+    /// cleared without invoking the callback.
+    ///
+    /// Returns the interrupt status that was read.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::DeserializationError` - A status holds an undefined error code
+    pub fn service(&mut self) -> Result<ResultInterruptStatusGpio, Error> {
+        let status: ResultInterruptStatusGpio = self.device.read_register()?;
+
+        let range = if status.range_interrupt && self.range.is_some() {
+            let result: RangeResultStatus = self.device.read_register()?;
+            let value: RangeResultValue = self.device.read_register()?;
+            Some((result, value))
+        } else {
+            None
+        };
+        let als = if status.als_interrupt && self.als.is_some() {
+            let block: AlsResultBlock = self.device.read_register()?;
+            let gain = self.device.read_register()?;
+            let integration = self.device.read_register()?;
+            Some((block.status, block.value, gain, integration))
+        } else {
+            None
+        };
+
+        if let Some(clear) = acknowledge(status) {
+            self.device.write_register(clear)?;
+        }
+        self.dispatch(range, als);
+        Ok(status)
+    }
+}
+
+impl<I2C, R, A, Addr> Watch<'_, I2C, R, A, Addr>
+where
+    Addr: DeviceAddress,
+    I2C: embedded_hal_async::i2c::I2c,
+    R: FnMut(Length),
+    A: FnMut(Luminance),
+{
+    /// Asynchronously writes the thresholds and interrupt modes of the
+    /// registered conditions.
+    ///
+    /// This is the async version of [`arm`](Watch::arm).
+    pub async fn arm_async(&mut self) -> Result<(), Error> {
+        if let Some((condition, _)) = self.range {
+            self.device.set_range_interrupt_async(condition).await?;
+        }
+        if let Some((limit, above, _)) = self.als {
+            let gain = self.device.read_register_async().await?;
+            let integration = self.device.read_register_async().await?;
+            let condition = Self::als_condition(limit, above, gain, integration);
+            self.device.set_als_interrupt_async(condition).await?;
+        }
+        Ok(())
+    }
+
+    /// Asynchronously reads the pending interrupts and invokes the callbacks
+    /// of the sensors that raised one.
+    ///
+    /// This is the async version of [`service`](Watch::service).
+    pub async fn service_async(&mut self) -> Result<ResultInterruptStatusGpio, Error> {
+        let status: ResultInterruptStatusGpio = self.device.read_register_async().await?;
+
+        let range = if status.range_interrupt && self.range.is_some() {
+            let result: RangeResultStatus = self.device.read_register_async().await?;
+            let value: RangeResultValue = self.device.read_register_async().await?;
+            Some((result, value))
+        } else {
+            None
+        };
+        let als = if status.als_interrupt && self.als.is_some() {
+            let block: AlsResultBlock = self.device.read_register_async().await?;
+            let gain = self.device.read_register_async().await?;
+            let integration = self.device.read_register_async().await?;
+            Some((block.status, block.value, gain, integration))
+        } else {
+            None
+        };
+
+        if let Some(clear) = acknowledge(status) {
+            self.device.write_register_async(clear).await?;
+        }
+        self.dispatch(range, als);
+        Ok(status)
+    }
+}
@@ -0,0 +1,155 @@
+//! Register access core
+//!
+//! Everything about a register access except the bus call itself: address
+//! encoding, the operations of a write, register encoding and decoding, and
+//! turning the outcome of the bus call into an [`Error`] while recording it.
+//! The blocking and async register accessors are thin shims around these, so
+//! both emit the same transactions and report the same errors.
+//!
+//! Every attempt of a bus call is recorded in the bus statistics. A failed
+//! attempt is then offered to the bus recovery hook, if one is set, which may
+//! ask for another; see [`set_bus_recovery`](Device::set_bus_recovery).
+//!
+//! Multi-step helpers bracket their accesses with
+//! [`begin_operation`](Device::begin_operation) and
+//! [`end_operation`](Device::end_operation), which count the accesses and
+//! label a failure with the helper's name and the step it failed at.
+
+use embedded_hal::i2c::Operation;
+use regiface::{ByteArray, ReadableRegister};
+
+use super::{Device, DeviceAddress};
+use crate::registers::DatasheetLimits;
+use crate::types::{Access, BusRecovery, Direction, Error, ErrorContext};
+
+/// Register address bytes sent at the start of every access
+pub(super) fn address_bytes(address: u16) -> [u8; 2] {
+    address.to_be_bytes()
+}
+
+/// Returns whether an I2C error means the addressed device did not respond
+pub(super) fn is_nack<E: embedded_hal::i2c::Error>(error: &E) -> bool {
+    matches!(error.kind(), embedded_hal::i2c::ErrorKind::NoAcknowledge(_))
+}
+
+/// Operations of a write transaction: the register address followed by `data`
+pub(super) fn write_operations<'a>(address: &'a [u8; 2], data: &'a [u8]) -> [Operation<'a>; 2] {
+    [Operation::Write(address), Operation::Write(data)]
+}
+
+/// Encodes a register value for writing
+pub(super) fn encode<R: DatasheetLimits>(register: R) -> Result<R::Array, Error> {
+    register
+        .to_bytes()
+        .map_err(|_| Error::SerializationError(ErrorContext::register(R::id(), Direction::Write)))
+}
+
+/// Decodes a register value from the bytes read
+pub(super) fn decode<R: ReadableRegister<IdType = u16>>(bytes: R::Array) -> Result<R, Error> {
+    R::from_bytes(bytes)
+        .map_err(|_| Error::DeserializationError(ErrorContext::register(R::id(), Direction::Read)))
+}
+
+/// Retries of one transaction at most, however often the recovery hook asks
+const MAX_BUS_RETRIES: u8 = 3;
+
+/// Context of a failed bus transaction
+fn bus_error(access: Access, direction: Direction) -> Error {
+    Error::BusError(ErrorContext {
+        access,
+        direction,
+        operation: None,
+    })
+}
+
+/// Multi-step helper in progress and the register accesses it has issued
+#[derive(Debug, Clone, Copy)]
+pub(super) struct OperationProgress {
+    name: &'static str,
+    accesses: u16,
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A> {
+    /// Starts counting the register accesses of a multi-step helper.
+    ///
+    /// Operations do not nest; starting one discards any unfinished one,
+    /// e.g. left behind by a dropped future.
+    pub(crate) fn begin_operation(&mut self, name: &'static str) {
+        self.operation = Some(OperationProgress { name, accesses: 0 });
+    }
+
+    /// Ends the multi-step helper, labelling a failure with the step it happened at
+    pub(crate) fn end_operation<T>(&mut self, result: Result<T, Error>) -> Result<T, Error> {
+        match self.operation.take() {
+            Some(progress) => result.map_err(|e| e.in_operation(progress.name, progress.accesses)),
+            None => result,
+        }
+    }
+
+    /// Counts an access towards the running operation, if any
+    fn count_access(&mut self) {
+        if let Some(progress) = &mut self.operation {
+            progress.accesses = progress.accesses.saturating_add(1);
+        }
+    }
+
+    /// Returns whether to issue a transaction again after `result`.
+    ///
+    /// Calls the recovery hook once for every failed attempt, the last one
+    /// included, but retries at most [`MAX_BUS_RETRIES`] times.
+    pub(super) fn retry_after<E: embedded_hal::i2c::Error>(
+        &mut self,
+        result: &Result<(), E>,
+        retries: &mut u8,
+    ) -> bool {
+        let (Err(error), Some(hook)) = (result, self.bus_recovery) else {
+            return false;
+        };
+        if (hook.0)(error.kind()) != BusRecovery::Retry || *retries >= MAX_BUS_RETRIES {
+            return false;
+        }
+        *retries += 1;
+        true
+    }
+
+    /// Counts a read access towards the running operation and maps the
+    /// outcome of its last attempt
+    pub(super) fn finish_read<E>(
+        &mut self,
+        access: Access,
+        result: Result<(), E>,
+    ) -> Result<(), Error> {
+        self.count_access();
+        result.map_err(|_| bus_error(access, Direction::Read))
+    }
+
+    /// Counts a write access towards the running operation and maps the
+    /// outcome of its last attempt
+    pub(super) fn finish_write<E>(
+        &mut self,
+        access: Access,
+        result: Result<(), E>,
+    ) -> Result<(), Error> {
+        self.count_access();
+        result.map_err(|_| bus_error(access, Direction::Write))
+    }
+
+    /// Records a probing read and decodes it, mapping a NACK to `None`
+    pub(super) fn finish_probe<R, E>(
+        &mut self,
+        bytes: R::Array,
+        result: Result<(), E>,
+    ) -> Result<Option<R>, Error>
+    where
+        R: ReadableRegister<IdType = u16>,
+        E: embedded_hal::i2c::Error,
+    {
+        self.record_read(bytes.as_ref().len(), result.is_ok());
+        self.count_access();
+        match result {
+            Ok(()) => decode(bytes).map(Some),
+            Err(e) if is_nack(&e) => Ok(None),
+            Err(_) => Err(bus_error(Access::Register(R::id()), Direction::Read)),
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! Maximum detectable distance (DMAX)
+//!
+//! A measurement that finds no target does not tell whether there is nothing
+//! in front of the sensor or whether a target is too far away or too dark to
+//! be seen under the current ambient light. [`DmaxModel`] estimates the
+//! largest distance a target of a given reflectance would still have been
+//! detected at, in the spirit of the DMAX estimate of ST's API (datasheet
+//! section 2.7.6).
+//!
+//! # Model
+//!
+//! The return signal rate of a target falls with the square of its distance
+//! and grows with its reflectance:
+//!
+//! `rate(d) = reference_rate * (reflectance / 17%) * (100mm / d)²`
+//!
+//! A measurement converges if that rate accumulates `convergence_counts`
+//! within the convergence time limit and stands out of the ambient rate:
+//!
+//! `required = convergence_counts / max_convergence + ambient_factor * ambient_rate`
+//!
+//! Solving `rate(d) = required` for `d` gives the estimate, capped at the
+//! largest reportable range of 255mm.
+//!
+//! # Accuracy
+//!
+//! The estimate is a rough approximation. The [`DmaxModel::TYPICAL`]
+//! parameters describe a typical module without cover glass; cover glass,
+//! crosstalk, target size and tilt, and part-to-part variation all move the
+//! real limit, easily by tens of percent. Like ST's estimate it overestimates
+//! the range for targets darker than the reflectance asked about. Measure a
+//! known target at known distances to fit the parameters of a product.
+
+use core::time::Duration;
+
+use measurements::Length;
+
+/// Reflectance of a target, in percent
+///
+/// The datasheet characterizes ranging with black (3%), grey (17%) and white
+/// (88%) targets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TargetReflectance {
+    /// Reflectance in percent
+    pub percent: u8,
+}
+
+impl TargetReflectance {
+    /// Black target, 3% reflectance
+    pub const BLACK: Self = Self { percent: 3 };
+
+    /// Grey target, 17% reflectance, the target ST's DMAX is estimated for
+    pub const GREY: Self = Self { percent: 17 };
+
+    /// White target, 88% reflectance
+    pub const WHITE: Self = Self { percent: 88 };
+}
+
+/// Parameters of the maximum detectable distance estimate
+///
+/// See the [module documentation](self) for the model and its accuracy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DmaxModel {
+    /// Return signal rate of a 17% target at 100mm, in Mcps (9.7 fixed point)
+    pub reference_rate: u16,
+    /// Return signal counts a measurement needs to converge
+    pub convergence_counts: u32,
+    /// Return signal rate needed per unit of ambient rate, in percent
+    pub ambient_factor: u16,
+}
+
+impl DmaxModel {
+    /// Largest range the sensor reports at the native 1x scaling
+    const MAX_RANGE_MM: u64 = 255;
+
+    /// Rough parameters of a typical module without cover glass
+    ///
+    /// A 17% target at 100mm returns 1 Mcps, a measurement converges after
+    /// 7350 counts (0.15 Mcps for the default 49ms limit), and the signal
+    /// has to reach 35% of the ambient rate.
+    pub const TYPICAL: Self = Self {
+        reference_rate: 128,
+        convergence_counts: 7_350,
+        ambient_factor: 35,
+    };
+
+    /// Estimates the largest distance a target would be detected at.
+    ///
+    /// Returns zero if `max_convergence` is zero, and at most 255mm.
+    ///
+    /// # Arguments
+    /// * `reflectance` - Reflectance of the target to look for
+    /// * `ambient_rate` - Return ambient rate in Mcps (9.7 fixed point), see
+    ///   [`RangeResultBlock::ambient_rate`](crate::registers::RangeResultBlock::ambient_rate)
+    /// * `max_convergence` - Convergence time limit of the measurement
+    ///
+    /// # Example
+    /// ```
+    /// use core::time::Duration;
+    /// use vl6180x::dmax::{DmaxModel, TargetReflectance};
+    ///
+    /// let model = DmaxModel::TYPICAL;
+    /// let limit = Duration::from_millis(49);
+    /// let dark = model.max_distance(TargetReflectance::GREY, 0, limit);
+    /// let bright = model.max_distance(TargetReflectance::GREY, 3 * 128, limit);
+    /// assert!(bright < dark);
+    /// ```
+    pub fn max_distance(
+        &self,
+        reflectance: TargetReflectance,
+        ambient_rate: u16,
+        max_convergence: Duration,
+    ) -> Length {
+        let micros = max_convergence.as_micros();
+        if micros == 0 {
+            return Length::from_millimeters(0.0);
+        }
+
+        // Required return rate, in Mcps (9.7 fixed point) scaled by 100
+        let convergence = u128::from(self.convergence_counts) * 128 * 100 / micros;
+        let ambient = u128::from(ambient_rate) * u128::from(self.ambient_factor);
+        let required = (convergence + ambient).max(1);
+
+        // rate(d) = required, solved for d² in mm²
+        let available = u128::from(self.reference_rate) * u128::from(reflectance.percent) * 100;
+        let squared = available * 100 * 100 / (17 * required);
+
+        let limit = Self::MAX_RANGE_MM * Self::MAX_RANGE_MM;
+        let squared = u64::try_from(squared).unwrap_or(limit).min(limit);
+        Length::from_millimeters(squared.isqrt() as f64)
+    }
+}
+
+impl Default for DmaxModel {
+    fn default() -> Self {
+        Self::TYPICAL
+    }
+}
@@ -0,0 +1,250 @@
+//! Register dumps
+//!
+//! A [`RegisterDump`] holds the contents of every register in the
+//! [register layout](crate::registers::layout) at one point in time. When a
+//! unit misbehaves, what matters is usually how its registers differ from
+//! those of a working one, or how they changed across a configuration step:
+//! [`RegisterDump::diff`] lists exactly those registers.
+
+use core::fmt;
+
+use crate::device::{Device, DeviceAddress};
+use crate::registers::{self, RegisterLayout};
+use crate::types::Error;
+
+/// Number of register types in the layout
+const REGISTERS: usize = 43;
+
+/// Widest dumped register in bytes
+const MAX_WIDTH: usize = 16;
+
+/// First address of the result registers
+const RESULTS_START: u16 = 0x04D;
+
+/// Last address of the result registers
+const RESULTS_END: u16 = 0x0FF;
+
+/// Layout of the register types in the dump with their index in the layout
+///
+/// Views spanning several register types are left out; the registers they
+/// are made of are dumped instead.
+fn dumped() -> impl Iterator<Item = (usize, RegisterLayout)> {
+    let layout: [RegisterLayout; REGISTERS] = registers::layout();
+    layout.into_iter().enumerate().filter(move |(_, register)| {
+        !layout
+            .iter()
+            .any(|other| other.width < register.width && register.overlaps(other))
+    })
+}
+
+/// Returns whether the register changes with every measurement.
+fn is_volatile(register: &RegisterLayout) -> bool {
+    (RESULTS_START..=RESULTS_END).contains(&register.address)
+}
+
+/// Contents of every register at one point in time
+///
+/// Read one from a device with [`Device::dump_registers`], or build one with
+/// [`new`](RegisterDump::new) and [`set`](RegisterDump::set), e.g. from a dump
+/// logged earlier.
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RegisterDump {
+    values: [[u8; MAX_WIDTH]; REGISTERS],
+}
+
+impl RegisterDump {
+    /// Creates a dump with every register zero.
+    pub fn new() -> Self {
+        Self {
+            values: [[0; MAX_WIDTH]; REGISTERS],
+        }
+    }
+
+    /// Returns the bytes of the register starting at `address`, or `None` if
+    /// no register in the dump starts there.
+    pub fn get(&self, address: u16) -> Option<&[u8]> {
+        let (index, register) = dumped().find(|(_, register)| register.address == address)?;
+        Some(&self.values[index][..register.width])
+    }
+
+    /// Sets the bytes of the register starting at `address`.
+    ///
+    /// Returns `false` and leaves the dump unchanged if no register in the
+    /// dump starts at `address` or `bytes` does not match its width.
+    pub fn set(&mut self, address: u16, bytes: &[u8]) -> bool {
+        match dumped().find(|(_, register)| register.address == address) {
+            Some((index, register)) if bytes.len() == register.width => {
+                self.values[index][..register.width].copy_from_slice(bytes);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the registers in the dump with their bytes, by address.
+    pub fn registers(&self) -> impl Iterator<Item = (RegisterLayout, &[u8])> {
+        dumped().map(|(index, register)| (register, &self.values[index][..register.width]))
+    }
+
+    /// Compares this dump to `other`.
+    ///
+    /// The result registers from 0x04D on change with every measurement and
+    /// are skipped; see [`including_volatile`](DumpDiff::including_volatile)
+    /// to compare them as well.
+    ///
+    /// # Example
+    /// ```
+    /// use vl6180x::dump::RegisterDump;
+    ///
+    /// let working = RegisterDump::new();
+    /// let mut broken = RegisterDump::new();
+    /// broken.set(0x03F, &[0x40]);
+    ///
+    /// let diff = working.diff(&broken);
+    /// let difference = diff.iter().next().unwrap();
+    /// assert_eq!(difference.name, "AlsAnalogueGain");
+    /// assert_eq!((difference.left, difference.right), (&[0x00][..], &[0x40][..]));
+    /// assert_eq!(diff.len(), 1);
+    /// ```
+    pub fn diff<'a>(&'a self, other: &'a Self) -> DumpDiff<'a> {
+        DumpDiff {
+            left: self,
+            right: other,
+            volatile: false,
+        }
+    }
+}
+
+impl Default for RegisterDump {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for RegisterDump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                self.registers()
+                    .map(|(register, bytes)| (register.name, bytes)),
+            )
+            .finish()
+    }
+}
+
+/// A register that differs between two dumps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DumpDifference<'a> {
+    /// Address of the first byte
+    pub address: u16,
+    /// Name of the register type
+    pub name: &'static str,
+    /// Bytes in the dump [`diff`](RegisterDump::diff) was called on
+    pub left: &'a [u8],
+    /// Bytes in the dump passed to [`diff`](RegisterDump::diff)
+    pub right: &'a [u8],
+}
+
+/// Registers that differ between two dumps, returned by [`RegisterDump::diff`]
+///
+/// The `Debug` and `defmt::Format` output lists the differences, so the diff
+/// can be logged directly.
+#[derive(Clone, Copy)]
+pub struct DumpDiff<'a> {
+    left: &'a RegisterDump,
+    right: &'a RegisterDump,
+    volatile: bool,
+}
+
+impl<'a> DumpDiff<'a> {
+    /// Compares the result registers as well.
+    pub fn including_volatile(self) -> Self {
+        Self {
+            volatile: true,
+            ..self
+        }
+    }
+
+    /// Returns the registers that differ, by address.
+    pub fn iter(&self) -> impl Iterator<Item = DumpDifference<'a>> {
+        let (left, right, volatile) = (self.left, self.right, self.volatile);
+        left.registers()
+            .zip(right.registers())
+            .filter(move |((register, l), (_, r))| l != r && (volatile || !is_volatile(register)))
+            .map(|((register, left), (_, right))| DumpDifference {
+                address: register.address,
+                name: register.name,
+                left,
+                right,
+            })
+    }
+
+    /// Returns the number of registers that differ.
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Returns whether the dumps match.
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+}
+
+impl fmt::Debug for DumpDiff<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for DumpDiff<'_> {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "[");
+        for (i, difference) in self.iter().enumerate() {
+            if i > 0 {
+                defmt::write!(f, ", ");
+            }
+            defmt::write!(f, "{}", difference);
+        }
+        defmt::write!(f, "]");
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Reads every register in the layout into a dump.
+    ///
+    /// Costs one I2C transaction per register, bypassing the configuration
+    /// cache, so the dump shows what the device holds.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn dump_registers(&mut self) -> Result<RegisterDump, Error> {
+        let mut dump = RegisterDump::new();
+        for (index, register) in dumped() {
+            self.read_raw_into(register.address, &mut dump.values[index][..register.width])?;
+        }
+        Ok(dump)
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously reads every register in the layout into a dump.
+    ///
+    /// This is the async version of [`dump_registers`](Device::dump_registers).
+    pub async fn dump_registers_async(&mut self) -> Result<RegisterDump, Error> {
+        let mut dump = RegisterDump::new();
+        for (index, register) in dumped() {
+            self.read_raw_into_async(register.address, &mut dump.values[index][..register.width])
+                .await?;
+        }
+        Ok(dump)
+    }
+}
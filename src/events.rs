@@ -0,0 +1,195 @@
+//! Interrupt event queue
+//!
+//! In interrupt-driven designs the interrupt handler should do as little as
+//! possible: read and clear the device's interrupt flags and hand the events
+//! to the main loop. [`EventQueue`] is a fixed-size ring buffer for that hand
+//! off, filled by [`Device::handle_interrupt`](crate::Device::handle_interrupt)
+//! or [`ResultReader::handle_interrupt`](crate::device::ResultReader::handle_interrupt)
+//! and drained by the main loop.
+//!
+//! The queue itself is not synchronized. Share it between the interrupt
+//! handler and the main loop with whatever primitive the platform provides,
+//! e.g. a `critical_section::Mutex<RefCell<EventQueue<N>>>`.
+
+use crate::registers::ResultInterruptStatusGpio;
+
+/// An interrupt reported by the device
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum InterruptEvent {
+    /// A range interrupt fired
+    Range,
+    /// An ALS interrupt fired
+    Als,
+    /// A laser safety or PLL error interrupt fired
+    Error,
+}
+
+impl InterruptEvent {
+    /// Returns the events flagged in an interrupt status, in the order
+    /// range, ALS, error
+    pub(crate) fn from_status(
+        status: ResultInterruptStatusGpio,
+    ) -> impl Iterator<Item = InterruptEvent> {
+        [
+            (status.range_interrupt, Self::Range),
+            (status.als_interrupt, Self::Als),
+            (status.error_interrupt, Self::Error),
+        ]
+        .into_iter()
+        .filter_map(|(pending, event)| pending.then_some(event))
+    }
+}
+
+/// The interrupt sources pending on the device
+///
+/// Returned by [`Device::pending_interrupts`](crate::Device::pending_interrupts).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InterruptSources {
+    status: ResultInterruptStatusGpio,
+}
+
+impl InterruptSources {
+    /// Returns whether `event` is pending.
+    pub fn contains(&self, event: InterruptEvent) -> bool {
+        match event {
+            InterruptEvent::Range => self.status.range_interrupt,
+            InterruptEvent::Als => self.status.als_interrupt,
+            InterruptEvent::Error => self.status.error_interrupt,
+        }
+    }
+
+    /// Returns whether no interrupt is pending.
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    /// Returns the pending events in the order range, ALS, error.
+    pub fn iter(&self) -> impl Iterator<Item = InterruptEvent> {
+        InterruptEvent::from_status(self.status)
+    }
+}
+
+impl From<ResultInterruptStatusGpio> for InterruptSources {
+    fn from(status: ResultInterruptStatusGpio) -> Self {
+        Self { status }
+    }
+}
+
+/// Fixed-capacity queue of [`InterruptEvent`]s
+///
+/// When the queue is full new events are dropped and counted, so the
+/// consumer can tell that it fell behind.
+///
+/// # Example
+/// ```
+/// use vl6180x::events::{EventQueue, InterruptEvent};
+///
+/// let mut queue = EventQueue::<2>::new();
+///
+/// // Interrupt handler side
+/// queue.push(InterruptEvent::Range);
+/// queue.push(InterruptEvent::Als);
+/// queue.push(InterruptEvent::Range);
+///
+/// // Main loop side
+/// let mut events = queue.drain();
+/// assert_eq!(events.next(), Some(InterruptEvent::Range));
+/// assert_eq!(events.next(), Some(InterruptEvent::Als));
+/// assert_eq!(events.next(), None);
+/// assert_eq!(queue.overflows(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct EventQueue<const N: usize> {
+    events: [InterruptEvent; N],
+    head: usize,
+    len: usize,
+    overflows: u32,
+}
+
+impl<const N: usize> EventQueue<N> {
+    /// Creates an empty queue.
+    pub const fn new() -> Self {
+        Self {
+            events: [InterruptEvent::Range; N],
+            head: 0,
+            len: 0,
+            overflows: 0,
+        }
+    }
+
+    /// Appends an event.
+    ///
+    /// Returns `false` and counts an overflow if the queue is full.
+    pub fn push(&mut self, event: InterruptEvent) -> bool {
+        if self.len == N {
+            self.overflows = self.overflows.saturating_add(1);
+            return false;
+        }
+
+        self.events[(self.head + self.len) % N] = event;
+        self.len += 1;
+        true
+    }
+
+    /// Removes and returns the oldest event.
+    pub fn pop(&mut self) -> Option<InterruptEvent> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let event = self.events[self.head];
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        Some(event)
+    }
+
+    /// Returns an iterator removing events oldest first.
+    pub fn drain(&mut self) -> Drain<'_, N> {
+        Drain { queue: self }
+    }
+
+    /// Number of queued events.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether no events are queued.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of events dropped because the queue was full.
+    ///
+    /// Saturates rather than wraps.
+    pub const fn overflows(&self) -> u32 {
+        self.overflows
+    }
+
+    /// Clears the overflow counter.
+    pub fn reset_overflows(&mut self) {
+        self.overflows = 0;
+    }
+}
+
+impl<const N: usize> Default for EventQueue<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Draining iterator returned by [`EventQueue::drain`]
+///
+/// Events not consumed before the iterator is dropped stay in the queue.
+pub struct Drain<'a, const N: usize> {
+    queue: &'a mut EventQueue<N>,
+}
+
+impl<const N: usize> Iterator for Drain<'_, N> {
+    type Item = InterruptEvent;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.queue.pop()
+    }
+}
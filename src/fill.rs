@@ -0,0 +1,233 @@
+//! Fill level of a container
+//!
+//! With the sensor mounted above a container, the measured distance shrinks
+//! as the container fills. [`FillLevel`] maps that distance onto a level
+//! between two calibration distances, one taken with the container empty and
+//! one with it full.
+
+use measurements::Length;
+
+use crate::device::Device;
+use crate::types::Error;
+
+/// Where a distance lies relative to the calibrated band
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FillBand {
+    /// Between the empty and full calibration distances
+    Within,
+    /// Past the empty calibration distance, reported as 0%
+    BelowEmpty,
+    /// Past the full calibration distance, reported as 100%
+    AboveFull,
+}
+
+/// A fill level computed by [`FillLevel::level`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Fill {
+    /// Level in per-mille of the calibrated band, clamped to 0..=1000
+    pub permille: u16,
+    /// Where the distance lay relative to the calibrated band
+    pub band: FillBand,
+}
+
+impl Fill {
+    /// Returns the level in percent, clamped to 0..=100.
+    pub fn percent(&self) -> f32 {
+        f32::from(self.permille) / 10.0
+    }
+
+    /// Returns whether the distance lay within the calibrated band.
+    pub fn is_within_band(&self) -> bool {
+        self.band == FillBand::Within
+    }
+}
+
+/// Maps measured distances onto a fill level between two calibration points
+///
+/// The level is linear in the distance: 0% at the empty distance and 100% at
+/// the full distance. Either distance may be the larger one, so a sensor
+/// looking down onto a liquid surface (empty farther than full) and one
+/// looking up at a float or lid (empty nearer than full) work alike.
+///
+/// Distances are taken as given. For a steadier level, pass the result of
+/// averaging or filtering several measurements instead of a single one.
+///
+/// # Example
+/// ```
+/// use measurements::Length;
+/// use vl6180x::fill::{FillBand, FillLevel};
+///
+/// let tank = FillLevel::new(Length::from_millimeters(90.0), Length::from_millimeters(10.0))
+///     .unwrap();
+///
+/// let fill = tank.level(Length::from_millimeters(30.0));
+/// assert_eq!(fill.permille, 750);
+/// assert_eq!(fill.band, FillBand::Within);
+///
+/// // Beyond the full mark the level is clamped and flagged
+/// let fill = tank.level(Length::from_millimeters(5.0));
+/// assert_eq!(fill.permille, 1000);
+/// assert_eq!(fill.band, FillBand::AboveFull);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FillLevel {
+    empty: Length,
+    full: Length,
+}
+
+impl FillLevel {
+    /// Creates a fill level from its calibration distances.
+    ///
+    /// Returns `None` if both distances are equal, as no level can be derived
+    /// from them.
+    ///
+    /// # Arguments
+    /// * `empty` - Distance measured with the container empty
+    /// * `full` - Distance measured with the container full
+    pub fn new(empty: Length, full: Length) -> Option<Self> {
+        (empty.as_millimeters() != full.as_millimeters()).then_some(Self { empty, full })
+    }
+
+    /// Returns the distance measured with the container empty.
+    pub fn empty(&self) -> Length {
+        self.empty
+    }
+
+    /// Returns the distance measured with the container full.
+    pub fn full(&self) -> Length {
+        self.full
+    }
+
+    /// Converts a measured distance into a fill level.
+    ///
+    /// Distances outside the calibrated band are clamped to 0% or 100% and
+    /// flagged in [`Fill::band`].
+    pub fn level(&self, distance: Length) -> Fill {
+        let empty = self.empty.as_millimeters();
+        let span = empty - self.full.as_millimeters();
+        let fraction = (empty - distance.as_millimeters()) / span;
+        let band = if fraction < 0.0 {
+            FillBand::BelowEmpty
+        } else if fraction > 1.0 {
+            FillBand::AboveFull
+        } else {
+            FillBand::Within
+        };
+        Fill {
+            permille: (fraction.clamp(0.0, 1.0) * 1000.0 + 0.5) as u16,
+            band,
+        }
+    }
+
+    /// Measures the empty calibration distance.
+    ///
+    /// Averages `samples` single-shot range measurements, taking at least
+    /// one. Call with the container empty.
+    ///
+    /// # Arguments
+    /// * `device` - Sensor mounted at its final position
+    /// * `delay` - Delay provider
+    /// * `samples` - Number of measurements to average
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::Timeout` - No sample was reported within the polling budget
+    /// * `Error::RangeError` - A measurement completed with an error status
+    pub fn calibrate_empty<I2C, D>(
+        device: &mut Device<I2C>,
+        delay: &mut D,
+        samples: u8,
+    ) -> Result<Length, Error>
+    where
+        I2C: embedded_hal::i2c::I2c,
+        D: embedded_hal::delay::DelayNs,
+    {
+        average_range(device, delay, samples)
+    }
+
+    /// Measures the full calibration distance.
+    ///
+    /// Like [`calibrate_empty`](FillLevel::calibrate_empty), but called with
+    /// the container full.
+    pub fn calibrate_full<I2C, D>(
+        device: &mut Device<I2C>,
+        delay: &mut D,
+        samples: u8,
+    ) -> Result<Length, Error>
+    where
+        I2C: embedded_hal::i2c::I2c,
+        D: embedded_hal::delay::DelayNs,
+    {
+        average_range(device, delay, samples)
+    }
+
+    /// Asynchronously measures the empty calibration distance.
+    ///
+    /// This is the async version of [`calibrate_empty`](FillLevel::calibrate_empty).
+    pub async fn calibrate_empty_async<I2C, D>(
+        device: &mut Device<I2C>,
+        delay: &mut D,
+        samples: u8,
+    ) -> Result<Length, Error>
+    where
+        I2C: embedded_hal_async::i2c::I2c,
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        average_range_async(device, delay, samples).await
+    }
+
+    /// Asynchronously measures the full calibration distance.
+    ///
+    /// This is the async version of [`calibrate_full`](FillLevel::calibrate_full).
+    pub async fn calibrate_full_async<I2C, D>(
+        device: &mut Device<I2C>,
+        delay: &mut D,
+        samples: u8,
+    ) -> Result<Length, Error>
+    where
+        I2C: embedded_hal_async::i2c::I2c,
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        average_range_async(device, delay, samples).await
+    }
+}
+
+/// Averages at least one single-shot range measurement
+fn average_range<I2C, D>(
+    device: &mut Device<I2C>,
+    delay: &mut D,
+    samples: u8,
+) -> Result<Length, Error>
+where
+    I2C: embedded_hal::i2c::I2c,
+    D: embedded_hal::delay::DelayNs,
+{
+    let samples = samples.max(1);
+    let mut sum = 0.0;
+    for _ in 0..samples {
+        sum += device.measure_range_single(delay)?.as_millimeters();
+    }
+    Ok(Length::from_millimeters(sum / f64::from(samples)))
+}
+
+async fn average_range_async<I2C, D>(
+    device: &mut Device<I2C>,
+    delay: &mut D,
+    samples: u8,
+) -> Result<Length, Error>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+    D: embedded_hal_async::delay::DelayNs,
+{
+    let samples = samples.max(1);
+    let mut sum = 0.0;
+    for _ in 0..samples {
+        sum += device
+            .measure_range_single_async(delay)
+            .await?
+            .as_millimeters();
+    }
+    Ok(Length::from_millimeters(sum / f64::from(samples)))
+}
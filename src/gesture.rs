@@ -0,0 +1,215 @@
+//! Approach and retreat gestures
+//!
+//! [`GestureDetector`] recognizes a hand or object moving towards the sensor,
+//! hovering and moving away again from the range readings alone. It keeps no
+//! history beyond a few fields, so it runs on any stream of readings without
+//! allocation, whether they come from single-shot or continuous ranging.
+
+use measurements::Length;
+
+use crate::types::RangeReading;
+
+/// A gesture reported by [`GestureDetector::feed`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Gesture {
+    /// A target moved from beyond `far` to within `near` quickly enough
+    Approach,
+    /// An approached target stayed near for the hold time
+    Hold,
+    /// An approached target moved beyond `far` or out of range
+    Retreat,
+}
+
+/// Distance and timing parameters of a [`GestureDetector`]
+///
+/// The band between `near` and `far` is a hysteresis: a target counts as
+/// arriving once it crosses `near` and as leaving once it crosses `far`, so
+/// noise around either threshold does not produce events.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GestureConfig {
+    /// Distance below which a target is near
+    pub near: Length,
+    /// Distance at or above which a target is away, at least `near`
+    pub far: Length,
+    /// Longest time in ms from the last away reading to the first near one
+    /// for the movement to count as an approach rather than a drift
+    pub approach_ms: u32,
+    /// Time in ms an approached target must stay near to report a hold
+    pub hold_ms: u32,
+    /// Consecutive no-target readings tolerated before a near target
+    /// counts as gone
+    pub dropouts: u8,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for GestureConfig {
+    fn format(&self, f: defmt::Formatter) {
+        use crate::types::Millimeters;
+
+        defmt::write!(
+            f,
+            "GestureConfig {{ near: {}, far: {}, approach_ms: {}, hold_ms: {}, dropouts: {} }}",
+            Millimeters(self.near),
+            Millimeters(self.far),
+            self.approach_ms,
+            self.hold_ms,
+            self.dropouts
+        );
+    }
+}
+
+impl Default for GestureConfig {
+    /// Hand-sized gestures: near below 50mm, away from 100mm, a 500ms
+    /// approach, a 1s hold and two tolerated dropouts
+    fn default() -> Self {
+        Self {
+            near: Length::from_millimeters(50.0),
+            far: Length::from_millimeters(100.0),
+            approach_ms: 500,
+            hold_ms: 1000,
+            dropouts: 2,
+        }
+    }
+}
+
+/// Where the detector is within a gesture
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// A target is in range without having approached, e.g. at startup or
+    /// after drifting in slowly; waiting for it to go away
+    Ignoring,
+    /// No target near, last seen away at this tick
+    Clear { last_away: u32 },
+    /// Approached target near since this tick
+    Near {
+        since: u32,
+        held: bool,
+        dropouts: u8,
+    },
+}
+
+/// State machine turning range readings into [`Gesture`]s
+///
+/// Feed every reading together with a millisecond tick from the caller's
+/// clock. The tick may wrap around. Failed readings are skipped and
+/// no-target readings count as away, except that up to
+/// [`dropouts`](GestureConfig::dropouts) of them in a row are ignored while
+/// a target is near.
+///
+/// # Example
+/// ```
+/// use measurements::Length;
+/// use vl6180x::gesture::{Gesture, GestureConfig, GestureDetector};
+/// use vl6180x::RangeReading;
+///
+/// let mut detector = GestureDetector::new(GestureConfig::default());
+/// let at = |mm| RangeReading::Valid(Length::from_millimeters(mm));
+///
+/// // A hand sweeping past the sensor
+/// assert_eq!(detector.feed(0, RangeReading::NoTarget), None);
+/// assert_eq!(detector.feed(50, at(80.0)), None);
+/// assert_eq!(detector.feed(100, at(30.0)), Some(Gesture::Approach));
+/// assert_eq!(detector.feed(150, at(60.0)), None);
+/// assert_eq!(detector.feed(200, RangeReading::NoTarget), None);
+/// assert_eq!(detector.feed(250, at(120.0)), Some(Gesture::Retreat));
+/// ```
+#[derive(Debug, Clone)]
+pub struct GestureDetector {
+    config: GestureConfig,
+    state: State,
+}
+
+impl GestureDetector {
+    /// Creates a detector that waits for the first away reading.
+    pub fn new(config: GestureConfig) -> Self {
+        Self {
+            config,
+            state: State::Ignoring,
+        }
+    }
+
+    /// Returns the parameters of the detector.
+    pub fn config(&self) -> GestureConfig {
+        self.config
+    }
+
+    /// Returns whether an approached target is currently near.
+    pub fn is_near(&self) -> bool {
+        matches!(self.state, State::Near { .. })
+    }
+
+    /// Forgets any gesture in progress and waits for the next away reading.
+    pub fn reset(&mut self) {
+        self.state = State::Ignoring;
+    }
+
+    /// Processes the next reading, returning the gesture it completes.
+    ///
+    /// # Arguments
+    /// * `tick_ms` - Time of the reading in milliseconds
+    /// * `reading` - The range reading
+    pub fn feed(&mut self, tick_ms: u32, reading: RangeReading) -> Option<Gesture> {
+        let distance = match reading {
+            RangeReading::Valid(distance) => Some(distance.as_millimeters()),
+            RangeReading::NoTarget => None,
+            RangeReading::Failed(_) => return None,
+        };
+        let near = self.config.near.as_millimeters();
+        let far = self.config.far.as_millimeters();
+        let away = distance.is_none_or(|mm| mm >= far);
+
+        let (state, gesture) = match self.state {
+            State::Near {
+                since,
+                held,
+                dropouts,
+            } => match distance {
+                None if dropouts < self.config.dropouts => (
+                    State::Near {
+                        since,
+                        held,
+                        dropouts: dropouts + 1,
+                    },
+                    None,
+                ),
+                _ if away => (State::Clear { last_away: tick_ms }, Some(Gesture::Retreat)),
+                _ if !held && tick_ms.wrapping_sub(since) >= self.config.hold_ms => (
+                    State::Near {
+                        since,
+                        held: true,
+                        dropouts: 0,
+                    },
+                    Some(Gesture::Hold),
+                ),
+                _ => (
+                    State::Near {
+                        since,
+                        held,
+                        dropouts: 0,
+                    },
+                    None,
+                ),
+            },
+            _ if away => (State::Clear { last_away: tick_ms }, None),
+            State::Clear { last_away } => match distance {
+                Some(mm) if mm < near => {
+                    if tick_ms.wrapping_sub(last_away) <= self.config.approach_ms {
+                        let state = State::Near {
+                            since: tick_ms,
+                            held: false,
+                            dropouts: 0,
+                        };
+                        (state, Some(Gesture::Approach))
+                    } else {
+                        (State::Ignoring, None)
+                    }
+                }
+                _ => (State::Clear { last_away }, None),
+            },
+            State::Ignoring => (State::Ignoring, None),
+        };
+        self.state = state;
+        gesture
+    }
+}
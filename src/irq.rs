@@ -0,0 +1,140 @@
+//! Interrupt flag for bare-metal targets
+//!
+//! An interrupt handler for the GPIO1 line should do no more than note that
+//! the sensor wants attention; the I2C traffic belongs in the main loop.
+//! [`IrqFlag`] carries that note from one to the other, and
+//! [`Device::service_if_flagged`] services the sensor only when it was set.
+//!
+//! The flag is built on `portable-atomic`, so it also works on cores without
+//! atomic read-modify-write instructions such as thumbv6m. There, enable the
+//! `critical-section` or `unsafe-assume-single-core` feature of
+//! `portable-atomic` in the application.
+//!
+//! # Example
+//! ```
+//! use embedded_hal::i2c::I2c;
+//! use vl6180x::events::{EventQueue, InterruptEvent};
+//! use vl6180x::irq::IrqFlag;
+//! use vl6180x::Device;
+//!
+//! static SENSOR_IRQ: IrqFlag = IrqFlag::new();
+//!
+//! // Registered as the EXTI handler of the pin wired to GPIO1
+//! fn exti_handler() {
+//!     // Clear the pending bit of the EXTI line here
+//!     SENSOR_IRQ.notify();
+//! }
+//!
+//! fn main_loop<I2C: I2c>(sensor: &mut Device<I2C>) {
+//!     let mut events = EventQueue::<4>::new();
+//!     loop {
+//!         if let Err(_error) = sensor.service_if_flagged(&SENSOR_IRQ, &mut events) {
+//!             // The flag stays set and the next iteration retries
+//!         }
+//!         for event in events.drain() {
+//!             match event {
+//!                 InterruptEvent::Range => { /* read the range result */ }
+//!                 InterruptEvent::Als => { /* read the ALS result */ }
+//!                 InterruptEvent::Error => { /* reinitialize */ }
+//!             }
+//!         }
+//! #       break;
+//!         // Sleep until the next interrupt, e.g. with `wfi`
+//!     }
+//! }
+//! # exti_handler();
+//! # assert!(SENSOR_IRQ.is_set());
+//! ```
+
+use portable_atomic::{AtomicBool, Ordering};
+
+use crate::device::{Device, DeviceAddress};
+use crate::events::EventQueue;
+use crate::registers::ResultInterruptStatusGpio;
+use crate::types::Error;
+
+/// Flag set by an interrupt handler and taken by the main loop
+///
+/// Usable from a `static`, since all methods take `&self`. Notifications
+/// arriving before the main loop takes the flag merge into one.
+#[derive(Debug, Default)]
+pub struct IrqFlag {
+    pending: AtomicBool,
+}
+
+impl IrqFlag {
+    /// Creates a cleared flag.
+    pub const fn new() -> Self {
+        Self {
+            pending: AtomicBool::new(false),
+        }
+    }
+
+    /// Sets the flag; call from the interrupt handler.
+    pub fn notify(&self) {
+        self.pending.store(true, Ordering::Release);
+    }
+
+    /// Clears the flag, returning whether it was set.
+    pub fn take(&self) -> bool {
+        self.pending.swap(false, Ordering::Acquire)
+    }
+
+    /// Returns whether the flag is set, without clearing it.
+    pub fn is_set(&self) -> bool {
+        self.pending.load(Ordering::Acquire)
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Services the pending interrupts if `flag` was set.
+    ///
+    /// Takes the flag and, if it was set, runs
+    /// [`handle_interrupt`](Device::handle_interrupt), returning its status.
+    /// Without the flag it returns `Ok(None)` without touching the bus. If
+    /// servicing fails the flag is set again, so the next call retries.
+    ///
+    /// # Arguments
+    /// * `flag` - Flag the GPIO1 interrupt handler sets
+    /// * `queue` - Queue receiving one event per pending interrupt source
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn service_if_flagged<const N: usize>(
+        &mut self,
+        flag: &IrqFlag,
+        queue: &mut EventQueue<N>,
+    ) -> Result<Option<ResultInterruptStatusGpio>, Error> {
+        if !flag.take() {
+            return Ok(None);
+        }
+        self.handle_interrupt(queue)
+            .inspect_err(|_| flag.notify())
+            .map(Some)
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously services the pending interrupts if `flag` was set.
+    ///
+    /// This is the async version of [`service_if_flagged`](Device::service_if_flagged).
+    pub async fn service_if_flagged_async<const N: usize>(
+        &mut self,
+        flag: &IrqFlag,
+        queue: &mut EventQueue<N>,
+    ) -> Result<Option<ResultInterruptStatusGpio>, Error> {
+        if !flag.take() {
+            return Ok(None);
+        }
+        self.handle_interrupt_async(queue)
+            .await
+            .inspect_err(|_| flag.notify())
+            .map(Some)
+    }
+}
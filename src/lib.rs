@@ -15,7 +15,8 @@
 //!
 //! # Example
 //! ```no_run
-//! use embedded_hal::i2c::I2c;
+//! use embedded_hal::{delay::DelayNs, i2c::I2c};
+//! use measurements::Length;
 //! use vl6180x::{Device, registers::ModelId};
 //!
 //! fn configure_sensor<I2C: I2c>(i2c: I2C) -> Result<Device<I2C>, vl6180x::Error> {
@@ -26,13 +27,50 @@
 //!     
 //!     Ok(device)
 //! }
+//!
+//! fn distance<I2C: I2c, D: DelayNs>(
+//!     device: &mut Device<I2C>,
+//!     delay: &mut D,
+//! ) -> Result<Length, vl6180x::Error> {
+//!     // Start, wait for, read and clear one range measurement
+//!     device.measure_range_single(delay)
+//! }
 //! ```
 
-pub use regiface::errors::Error;
-
+pub mod beam;
+pub mod buffer;
+pub mod calibration;
+pub mod clock;
+pub mod config;
+pub mod daynight;
 pub mod device;
+pub mod dmax;
+pub mod dump;
+pub mod events;
+pub mod fill;
+pub mod gesture;
+#[cfg(feature = "irq-flag")]
+pub mod irq;
+pub mod obstruction;
+pub mod poller;
+#[cfg(feature = "pololu-compat")]
+pub mod pololu_compat;
 pub mod registers;
+pub mod scheduler;
+pub mod sensor;
+pub mod session;
+#[cfg(feature = "st-compat")]
+pub mod st_compat;
+pub mod text;
 pub mod types;
+#[cfg(feature = "uom")]
+pub mod units;
+pub mod velocity;
+pub mod watchdog;
+pub mod window;
+pub mod wizard;
 
-pub use device::Device;
+pub use config::FullConfig;
+pub use device::{Address, Device, StaticDevice};
+pub use sensor::{AsyncLightSensor, AsyncRangeSensor, LightSensor, RangeSensor};
 pub use types::*;
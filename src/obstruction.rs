@@ -0,0 +1,191 @@
+//! Sensor window obstruction
+//!
+//! A finger or a sticker over the sensor window has a recognizable
+//! signature: the ranging sees something at the window, either as a very
+//! short or underflowing distance or as a return signal far stronger than
+//! any real target would give, and the ALS sees hardly any light.
+//! [`ObstructionConfig::classify`] combines both into a [`WindowState`] for
+//! self-diagnostics and user prompts such as "clean the sensor";
+//! [`Device::detect_obstruction`] takes the measurements.
+
+use measurements::Length;
+
+use crate::device::{Device, DeviceAddress};
+use crate::registers::RangeResultBlock;
+use crate::types::{AlsReading, Error, Luminance, RangeErrorCode, RangeReading};
+
+/// State of the sensor window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WindowState {
+    /// Nothing at the window
+    Clear,
+    /// Something is at the window but light still passes, e.g. a smudge, a
+    /// translucent sticker or a partly covering finger
+    PartiallyObstructed,
+    /// Something at the window blocks the light
+    Covered,
+}
+
+/// Criteria of [`ObstructionConfig::classify`]
+///
+/// The defaults are a starting point; cover glass, housing and the light
+/// the product lives in shift all three, so tune them on the product.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ObstructionConfig {
+    /// Valid distances below this count as something at the window
+    pub contact: Length,
+    /// Light levels below this count as blocked; raise it by day and lower
+    /// it at night, since a dark room looks like a covered window
+    pub dark: Luminance,
+    /// Return signal rate, as a multiple of the reference rate, from which
+    /// the return signal counts as coming from the window, whatever
+    /// distance was reported
+    pub return_ratio: f32,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for ObstructionConfig {
+    fn format(&self, f: defmt::Formatter) {
+        use crate::types::Millimeters;
+
+        defmt::write!(
+            f,
+            "ObstructionConfig {{ contact: {}, dark: {}, return_ratio: {} }}",
+            Millimeters(self.contact),
+            self.dark,
+            self.return_ratio
+        );
+    }
+}
+
+impl Default for ObstructionConfig {
+    /// Contact below 10mm, dark below 5 lux and a return rate of at least
+    /// the reference rate
+    fn default() -> Self {
+        Self {
+            contact: Length::from_millimeters(10.0),
+            dark: Luminance::from_lux(5.0),
+            return_ratio: 1.0,
+        }
+    }
+}
+
+impl ObstructionConfig {
+    /// Classifies the window from one range and one ALS reading of the same
+    /// moment.
+    ///
+    /// Something is at the window if the range reading is valid and below
+    /// [`contact`](Self::contact), underflowed, or, valid or not, came with a
+    /// return rate of at least [`return_ratio`](Self::return_ratio) times the
+    /// reference rate. The light is blocked if the ALS reading is dark or
+    /// below [`dark`](Self::dark). The window is then
+    /// * [`Covered`](WindowState::Covered) with something at the window and
+    ///   the light blocked,
+    /// * [`PartiallyObstructed`](WindowState::PartiallyObstructed) with
+    ///   something at the window and light passing, and
+    /// * [`Clear`](WindowState::Clear) otherwise. A dark room alone does not
+    ///   count as covered.
+    ///
+    /// # Arguments
+    /// * `range` - The range reading
+    /// * `als` - The ALS reading
+    /// * `return_rate` - Return signal rate of the range measurement in Mcps
+    ///   (9.7 fixed point), see [`RangeResultBlock::return_rate`]
+    /// * `reference_rate` - Reference signal rate of the range measurement in
+    ///   Mcps (9.7 fixed point), see [`RangeResultBlock::reference_rate`]
+    ///
+    /// # Example
+    /// ```
+    /// use measurements::Length;
+    /// use vl6180x::obstruction::{ObstructionConfig, WindowState};
+    /// use vl6180x::{AlsReading, Luminance, RangeReading};
+    ///
+    /// let config = ObstructionConfig::default();
+    /// let finger = RangeReading::Valid(Length::from_millimeters(3.0));
+    /// let state = config.classify(finger, AlsReading::Dark, 3000, 1000);
+    /// assert_eq!(state, WindowState::Covered);
+    /// ```
+    pub fn classify(
+        &self,
+        range: RangeReading,
+        als: AlsReading,
+        return_rate: u16,
+        reference_rate: u16,
+    ) -> WindowState {
+        let near = match range {
+            RangeReading::Valid(distance) => distance < self.contact,
+            RangeReading::Failed(code) => matches!(
+                code,
+                RangeErrorCode::RangingUnderflow | RangeErrorCode::RawRangingUnderflow
+            ),
+            RangeReading::NoTarget => false,
+        };
+        let reflective = f32::from(return_rate) >= self.return_ratio * f32::from(reference_rate);
+        let blocked = match als {
+            AlsReading::Valid(light) => light.lux < self.dark.lux,
+            AlsReading::Dark => true,
+            AlsReading::Saturated | AlsReading::Failed(_) => false,
+        };
+
+        match (near || reflective, blocked) {
+            (true, true) => WindowState::Covered,
+            (true, false) => WindowState::PartiallyObstructed,
+            (false, _) => WindowState::Clear,
+        }
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Measures range and light and classifies the sensor window.
+    ///
+    /// Takes the measurements with [`measure_both`](Device::measure_both),
+    /// reads the signal rates of the range measurement from
+    /// [`RangeResultBlock`] and classifies them with
+    /// [`ObstructionConfig::classify`]. Costs one I2C transaction more than
+    /// [`measure_both`](Device::measure_both).
+    ///
+    /// # Arguments
+    /// * `config` - Classification criteria
+    /// * `delay` - Delay provider used while waiting for the samples
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::Timeout` - A sample was not reported within its timeout
+    pub fn detect_obstruction<D>(
+        &mut self,
+        config: &ObstructionConfig,
+        delay: &mut D,
+    ) -> Result<WindowState, Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        let (range, als) = self.measure_both(delay)?;
+        let result: RangeResultBlock = self.read_register()?;
+        Ok(config.classify(range, als, result.return_rate, result.reference_rate))
+    }
+}
+
+impl<I2C, A: DeviceAddress> Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously measures range and light and classifies the sensor window.
+    ///
+    /// This is the async version of [`detect_obstruction`](Device::detect_obstruction).
+    pub async fn detect_obstruction_async<D>(
+        &mut self,
+        config: &ObstructionConfig,
+        delay: &mut D,
+    ) -> Result<WindowState, Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        let (range, als) = self.measure_both_async(delay).await?;
+        let result: RangeResultBlock = self.read_register_async().await?;
+        Ok(config.classify(range, als, result.return_rate, result.reference_rate))
+    }
+}
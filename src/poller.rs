@@ -0,0 +1,288 @@
+//! Non-blocking single-shot measurements
+//!
+//! For firmwares that can neither block nor run async code: a poller is
+//! stepped from a periodic tick and walks through the start, wait, read and
+//! clear sequence of a single-shot measurement. Every
+//! [`step`](RangePoller::step) issues exactly one I2C transaction, so the bus
+//! time each tick costs is known in advance.
+//!
+//! Like the blocking helpers, the pollers wait for the new sample interrupt
+//! and require the interrupt mode of their sensor to be
+//! [`InterruptMode::NewSampleReady`](crate::types::InterruptMode::NewSampleReady).
+
+use core::time::Duration;
+
+use crate::device::Device;
+use crate::registers::{
+    AlsAnalogueGain, AlsIntegrationPeriod, AlsResultBlock, AlsStart, InterruptClear,
+    RangeResultStatus, RangeResultValue, RangeStart, ResultInterruptStatusGpio,
+};
+use crate::types::{AlsGain, AlsReading, Error, RangeReading};
+
+/// Outcome of a single poller step
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Step<T> {
+    /// The measurement is still in progress
+    Pending,
+    /// The measurement finished; the next step starts a new one
+    Ready(T),
+    /// The measurement was abandoned; the next step starts a new one
+    Failed(Error),
+}
+
+/// Position of a [`RangePoller`] in the measurement sequence
+#[derive(Debug, Clone, Copy)]
+enum RangeState {
+    Start,
+    Wait { polls: u32 },
+    ReadStatus,
+    ReadValue(RangeResultStatus),
+    Clear(RangeReading),
+}
+
+/// Single-shot ranging advanced one I2C transaction at a time
+///
+/// A measurement takes at least five steps: the start command, one status
+/// poll per step until the sample is ready, the status and value reads, and
+/// clearing the interrupt.
+///
+/// # Example
+/// ```no_run
+/// use embedded_hal::i2c::I2c;
+/// use vl6180x::poller::{RangePoller, Step};
+/// use vl6180x::{Device, RangeReading};
+///
+/// // Called from a 1kHz tick, giving up after 50ms
+/// fn on_tick<I2C: I2c>(poller: &mut RangePoller, sensor: &mut Device<I2C>) {
+///     match poller.step(sensor) {
+///         Step::Pending => {}
+///         Step::Ready(RangeReading::Valid(_distance)) => { /* use the distance */ }
+///         Step::Ready(_) => { /* no target or measurement fault */ }
+///         Step::Failed(_error) => { /* bus error or timeout */ }
+///     }
+/// }
+///
+/// let mut poller = RangePoller::new(50);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RangePoller {
+    state: RangeState,
+    timeout_polls: u32,
+}
+
+impl RangePoller {
+    /// Creates a poller that starts a measurement on its first step.
+    ///
+    /// # Arguments
+    /// * `timeout_polls` - Status polls without a sample after which the
+    ///   measurement fails with `Error::Timeout`, at least one
+    pub fn new(timeout_polls: u32) -> Self {
+        Self {
+            state: RangeState::Start,
+            timeout_polls: timeout_polls.max(1),
+        }
+    }
+
+    /// Returns whether the next step starts a new measurement.
+    pub fn is_idle(&self) -> bool {
+        matches!(self.state, RangeState::Start)
+    }
+
+    /// Abandons the measurement in progress.
+    ///
+    /// The next step starts a new measurement. If the sensor still completes
+    /// the abandoned measurement, the new one may return its sample.
+    pub fn reset(&mut self) {
+        self.state = RangeState::Start;
+    }
+
+    /// Issues the next I2C transaction of the measurement.
+    ///
+    /// # Errors
+    /// Reported as [`Step::Failed`]:
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::DeserializationError` - The status holds an undefined error code
+    /// * `Error::Timeout` - No sample was reported within the status poll budget
+    pub fn step<I2C>(&mut self, device: &mut Device<I2C>) -> Step<RangeReading>
+    where
+        I2C: embedded_hal::i2c::I2c,
+    {
+        let finished = match self.state {
+            RangeState::Clear(reading) => Some(reading),
+            _ => None,
+        };
+        match self.advance(device) {
+            Ok(next) => {
+                self.state = next;
+                finished.map_or(Step::Pending, Step::Ready)
+            }
+            Err(e) => {
+                self.state = RangeState::Start;
+                Step::Failed(e)
+            }
+        }
+    }
+
+    /// Issues the transaction of the current state, returning the next state
+    fn advance<I2C>(&self, device: &mut Device<I2C>) -> Result<RangeState, Error>
+    where
+        I2C: embedded_hal::i2c::I2c,
+    {
+        Ok(match self.state {
+            RangeState::Start => {
+                device.write_register(RangeStart::SingleShot)?;
+                RangeState::Wait { polls: 0 }
+            }
+            RangeState::Wait { polls } => {
+                let status: ResultInterruptStatusGpio = device.read_register()?;
+                if status.range_interrupt {
+                    RangeState::ReadStatus
+                } else if polls + 1 >= self.timeout_polls {
+                    return Err(Error::Timeout);
+                } else {
+                    RangeState::Wait { polls: polls + 1 }
+                }
+            }
+            RangeState::ReadStatus => RangeState::ReadValue(device.read_register()?),
+            RangeState::ReadValue(status) => {
+                let value: RangeResultValue = device.read_register()?;
+                RangeState::Clear(RangeReading::new(status.error_code, value.distance))
+            }
+            RangeState::Clear(_) => {
+                device.write_register(InterruptClear {
+                    clear_range: true,
+                    clear_als: false,
+                    clear_error: false,
+                })?;
+                RangeState::Start
+            }
+        })
+    }
+}
+
+/// Position of an [`AlsPoller`] in the measurement sequence
+#[derive(Debug, Clone, Copy)]
+enum AlsState {
+    Start,
+    Wait { polls: u32 },
+    ReadResult,
+    ReadGain(AlsResultBlock),
+    ReadIntegration(AlsResultBlock, AlsGain),
+    Clear(AlsReading),
+}
+
+/// Single-shot ALS measurement advanced one I2C transaction at a time
+///
+/// The ALS counterpart of [`RangePoller`]. A measurement takes at least six
+/// steps: the start command, one status poll per step until the sample is
+/// ready, the result, gain and integration period reads, and clearing the
+/// interrupt.
+#[derive(Debug, Clone)]
+pub struct AlsPoller {
+    state: AlsState,
+    timeout_polls: u32,
+}
+
+impl AlsPoller {
+    /// Creates a poller that starts a measurement on its first step.
+    ///
+    /// # Arguments
+    /// * `timeout_polls` - Status polls without a sample after which the
+    ///   measurement fails with `Error::Timeout`, at least one
+    pub fn new(timeout_polls: u32) -> Self {
+        Self {
+            state: AlsState::Start,
+            timeout_polls: timeout_polls.max(1),
+        }
+    }
+
+    /// Returns whether the next step starts a new measurement.
+    pub fn is_idle(&self) -> bool {
+        matches!(self.state, AlsState::Start)
+    }
+
+    /// Abandons the measurement in progress.
+    ///
+    /// See [`RangePoller::reset`].
+    pub fn reset(&mut self) {
+        self.state = AlsState::Start;
+    }
+
+    /// Issues the next I2C transaction of the measurement.
+    ///
+    /// # Errors
+    /// Reported as [`Step::Failed`]:
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::DeserializationError` - A register holds an undefined value
+    /// * `Error::Timeout` - No sample was reported within the status poll budget
+    pub fn step<I2C>(&mut self, device: &mut Device<I2C>) -> Step<AlsReading>
+    where
+        I2C: embedded_hal::i2c::I2c,
+    {
+        let finished = match self.state {
+            AlsState::Clear(reading) => Some(reading),
+            _ => None,
+        };
+        match self.advance(device) {
+            Ok(next) => {
+                self.state = next;
+                finished.map_or(Step::Pending, Step::Ready)
+            }
+            Err(e) => {
+                self.state = AlsState::Start;
+                Step::Failed(e)
+            }
+        }
+    }
+
+    /// Issues the transaction of the current state, returning the next state
+    fn advance<I2C>(&self, device: &mut Device<I2C>) -> Result<AlsState, Error>
+    where
+        I2C: embedded_hal::i2c::I2c,
+    {
+        Ok(match self.state {
+            AlsState::Start => {
+                device.write_register(AlsStart::SingleShot)?;
+                AlsState::Wait { polls: 0 }
+            }
+            AlsState::Wait { polls } => {
+                let status: ResultInterruptStatusGpio = device.read_register()?;
+                if status.als_interrupt {
+                    AlsState::ReadResult
+                } else if polls + 1 >= self.timeout_polls {
+                    return Err(Error::Timeout);
+                } else {
+                    AlsState::Wait { polls: polls + 1 }
+                }
+            }
+            AlsState::ReadResult => AlsState::ReadGain(device.read_register()?),
+            AlsState::ReadGain(block) => {
+                let gain: AlsAnalogueGain = device.read_register()?;
+                AlsState::ReadIntegration(block, gain.gain)
+            }
+            AlsState::ReadIntegration(block, gain) => {
+                let integration: AlsIntegrationPeriod = device.read_register()?;
+                AlsState::Clear(reading(block, gain, integration.period))
+            }
+            AlsState::Clear(_) => {
+                device.write_register(InterruptClear {
+                    clear_range: false,
+                    clear_als: true,
+                    clear_error: false,
+                })?;
+                AlsState::Start
+            }
+        })
+    }
+}
+
+/// Classifies a completed ALS sample
+fn reading(block: AlsResultBlock, gain: AlsGain, integration: Duration) -> AlsReading {
+    AlsReading::new(
+        block.status.error_code,
+        block.value.raw_count,
+        gain,
+        integration,
+    )
+}
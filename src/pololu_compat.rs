@@ -0,0 +1,258 @@
+//! Pololu Arduino library compatibility layer
+//!
+//! [`Vl6180x`] mirrors the shape of the `VL6180X` class of Pololu's Arduino
+//! library, so sketches can be translated line by line: the delay provider is
+//! held next to the device, the timeout is set once with
+//! [`set_timeout`](Vl6180x::set_timeout) and a timed out read is reported
+//! through [`timeout_occurred`](Vl6180x::timeout_occurred).
+//!
+//! | Arduino | This module |
+//! |---------|-------------|
+//! | `init()` | [`init`](Vl6180x::init) |
+//! | `configureDefault()` | [`configure_default`](Vl6180x::configure_default) |
+//! | `setTimeout(ms)` | [`set_timeout`](Vl6180x::set_timeout) |
+//! | `getTimeout()` | [`timeout`](Vl6180x::timeout) |
+//! | `readRangeSingleMillimeters()` | [`read_range_single_millimeters`](Vl6180x::read_range_single_millimeters), or [`read_range_single_millimeters_sentinel`](Vl6180x::read_range_single_millimeters_sentinel) for the sentinel return |
+//! | `readAmbientSingle()` | [`read_ambient_single`](Vl6180x::read_ambient_single), or [`read_ambient_single_sentinel`](Vl6180x::read_ambient_single_sentinel) for the raw count |
+//! | `timeoutOccurred()` | [`timeout_occurred`](Vl6180x::timeout_occurred) |
+//!
+//! This layer does not load ST's private tuning settings in
+//! [`init`](Vl6180x::init), and ranges at the native 1x scaling only.
+//!
+//! # Example
+//! ```no_run
+//! use embedded_hal::{delay::DelayNs, i2c::I2c};
+//! use vl6180x::pololu_compat::Vl6180x;
+//!
+//! fn setup_and_read<I2C: I2c, D: DelayNs>(i2c: I2C, delay: D) -> Result<(), vl6180x::Error> {
+//!     let mut sensor = Vl6180x::new(i2c, delay);
+//!     sensor.init()?;
+//!     sensor.configure_default()?;
+//!     sensor.set_timeout(500);
+//!
+//!     let range = sensor.read_range_single_millimeters_sentinel();
+//!     if sensor.timeout_occurred() {
+//!         // " TIMEOUT"
+//!     } else {
+//!         // `range` millimeters
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use core::time::Duration;
+
+use measurements::Length;
+
+use crate::device::{als_result, poll_limit, range_result, Device};
+use crate::registers::{
+    AlsAnalogueGain, AlsIntegrationPeriod, AlsIntermeasurementPeriod, FreshOutOfReset,
+    InterleavedModeEnable, InterruptConfigGpio, RangeIntermeasurementPeriod,
+    RangeMaxConvergenceTime, RangeVhvRecalibrate, RangeVhvRepeatRate, ReadoutAveraging,
+};
+use crate::types::{AlsGain, Error, InterruptMode, Luminance};
+
+/// Value returned by [`Vl6180x::read_range_single_millimeters_sentinel`] when the read failed
+pub const TIMEOUT_SENTINEL: u16 = 65535;
+
+/// A VL6180X driven with the Pololu Arduino library's API
+///
+/// Owns the [`Device`] and the delay provider used while waiting for a
+/// measurement.
+pub struct Vl6180x<I2C, D> {
+    device: Device<I2C>,
+    delay: D,
+    timeout_ms: u16,
+    did_timeout: bool,
+}
+
+impl<I2C, D> Vl6180x<I2C, D> {
+    /// Creates a sensor at the default I2C address with timeouts disabled.
+    ///
+    /// # Arguments
+    /// * `i2c` - An I2C interface implementing the required embedded-hal traits
+    /// * `delay` - Delay provider used between status polls
+    pub fn new(i2c: I2C, delay: D) -> Self {
+        Self::from_device(Device::new(i2c), delay)
+    }
+
+    /// Wraps an existing device, with timeouts disabled.
+    pub fn from_device(device: Device<I2C>, delay: D) -> Self {
+        Self {
+            device,
+            delay,
+            timeout_ms: 0,
+            did_timeout: false,
+        }
+    }
+
+    /// Returns the wrapped device and delay provider.
+    pub fn release(self) -> (Device<I2C>, D) {
+        (self.device, self.delay)
+    }
+
+    /// Returns the wrapped device, e.g. for configuration not covered here.
+    pub fn device(&mut self) -> &mut Device<I2C> {
+        &mut self.device
+    }
+
+    /// Sets the measurement timeout in milliseconds, `0` to wait indefinitely.
+    pub fn set_timeout(&mut self, timeout_ms: u16) {
+        self.timeout_ms = timeout_ms;
+    }
+
+    /// Returns the measurement timeout in milliseconds.
+    pub fn timeout(&self) -> u16 {
+        self.timeout_ms
+    }
+
+    /// Returns whether a measurement timed out since the last call.
+    ///
+    /// Reading the flag clears it.
+    pub fn timeout_occurred(&mut self) -> bool {
+        core::mem::take(&mut self.did_timeout)
+    }
+
+    /// Status poll budget derived from the timeout, `None` when disabled
+    fn poll_limit(&self) -> Option<u32> {
+        match self.timeout_ms {
+            0 => None,
+            ms => Some(poll_limit(Duration::from_millis(ms as u64))),
+        }
+    }
+
+    /// Records a timeout of `result` in the timeout flag
+    fn track<T>(&mut self, result: Result<T, Error>) -> Result<T, Error> {
+        if matches!(result, Err(Error::Timeout)) {
+            self.did_timeout = true;
+        }
+        result
+    }
+}
+
+impl<I2C, D> Vl6180x<I2C, D>
+where
+    I2C: embedded_hal::i2c::I2c,
+    D: embedded_hal::delay::DelayNs,
+{
+    /// Clears the fresh-out-of-reset flag after power-up.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn init(&mut self) -> Result<(), Error> {
+        self.device.begin_operation("init");
+        let result = init(&mut self.device);
+        self.device.end_operation(result)
+    }
+
+    /// Applies the Pololu library's default configuration.
+    ///
+    /// Readout averaging of 48 samples, ALS gain 1 with 100ms integration,
+    /// VHV recalibration every 255 measurements and once now, 100ms range
+    /// and 500ms ALS intermeasurement periods, new-sample interrupts for both,
+    /// a 49ms maximum convergence time and interleaved mode disabled.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    pub fn configure_default(&mut self) -> Result<(), Error> {
+        self.device.begin_operation("configure_default");
+        let result = configure_default(&mut self.device);
+        self.device.end_operation(result)
+    }
+
+    /// Performs a single-shot range measurement.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::Timeout` - No sample within the timeout; also sets
+    ///   [`timeout_occurred`](Vl6180x::timeout_occurred)
+    /// * `Error::RangeError` - The measurement completed with an error code
+    pub fn read_range_single_millimeters(&mut self) -> Result<Length, Error> {
+        let limit = self.poll_limit();
+        let result = self.device.range_single_within(&mut self.delay, limit);
+        self.track(result).and_then(range_result)
+    }
+
+    /// Performs a single-shot range measurement, returning millimeters.
+    ///
+    /// Like the Arduino library, the range status is not checked, and any
+    /// failure returns [`TIMEOUT_SENTINEL`]; check
+    /// [`timeout_occurred`](Vl6180x::timeout_occurred) to tell timeouts apart.
+    pub fn read_range_single_millimeters_sentinel(&mut self) -> u16 {
+        let limit = self.poll_limit();
+        let result = self.device.range_single_within(&mut self.delay, limit);
+        match self.track(result) {
+            Ok((_, value)) => value.raw_mm() as u16,
+            Err(_) => TIMEOUT_SENTINEL,
+        }
+    }
+
+    /// Performs a single-shot ALS measurement.
+    ///
+    /// # Errors
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::Timeout` - No sample within the timeout; also sets
+    ///   [`timeout_occurred`](Vl6180x::timeout_occurred)
+    /// * `Error::AlsError` - The measurement completed with an error code
+    pub fn read_ambient_single(&mut self) -> Result<Luminance, Error> {
+        let limit = self.poll_limit();
+        let result = self.device.als_single_within(&mut self.delay, limit);
+        self.track(result).and_then(als_result)
+    }
+
+    /// Performs a single-shot ALS measurement, returning the raw count.
+    ///
+    /// Like the Arduino library, the ALS status is not checked, and any
+    /// failure returns `0`; check [`timeout_occurred`](Vl6180x::timeout_occurred)
+    /// to tell timeouts apart.
+    pub fn read_ambient_single_sentinel(&mut self) -> u16 {
+        let limit = self.poll_limit();
+        let result = self.device.als_single_within(&mut self.delay, limit);
+        match self.track(result) {
+            Ok((_, value, _, _)) => value.raw_count,
+            Err(_) => 0,
+        }
+    }
+}
+
+/// Clears the fresh-out-of-reset flag if it is set
+fn init<I2C>(device: &mut Device<I2C>) -> Result<(), Error>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    let reset: FreshOutOfReset = device.read_register()?;
+    if reset.fresh {
+        device.write_register(FreshOutOfReset { fresh: false })?;
+    }
+    Ok(())
+}
+
+/// Writes the Pololu library's default configuration, one register per step
+fn configure_default<I2C>(device: &mut Device<I2C>) -> Result<(), Error>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    device.write_register(ReadoutAveraging::RECOMMENDED)?;
+    device.write_register(AlsAnalogueGain {
+        gain: AlsGain::Gain1,
+    })?;
+    device.write_register(RangeVhvRepeatRate { rate: 255 })?;
+    device.write_register(AlsIntegrationPeriod {
+        period: Duration::from_millis(100),
+    })?;
+    device.write_register(RangeVhvRecalibrate { recalibrate: 1 })?;
+    device.write_register(RangeIntermeasurementPeriod {
+        period: Duration::from_millis(100),
+    })?;
+    device.write_register(AlsIntermeasurementPeriod {
+        period: Duration::from_millis(500),
+    })?;
+    device.write_register(InterruptConfigGpio {
+        range_interrupt: InterruptMode::NewSampleReady,
+        als_interrupt: InterruptMode::NewSampleReady,
+    })?;
+    device.write_register(RangeMaxConvergenceTime {
+        time: Duration::from_millis(49),
+    })?;
+    device.write_register(InterleavedModeEnable { enabled: false })
+}
@@ -6,13 +6,36 @@
 use core::{convert::Infallible, time::Duration};
 use regiface::{register, FromByteArray, ReadableRegister, ToByteArray, WritableRegister};
 
-use crate::types::{AlsGain, Luminance, RegisterError};
+use super::duration::{DurationField, INTERMEASUREMENT};
+use super::{BusyPolicy, DatasheetLimits};
+use crate::types::{AlsGain, FieldLimit, LimitViolation, Luminance, RegisterError};
+
+// Field limits from the register descriptions of datasheet section 6.2
+static THRESH_HIGH: FieldLimit = codes("sysals__thresh_high");
+static THRESH_LOW: FieldLimit = codes("sysals__thresh_low");
+static INTERMEASUREMENT_PERIOD: FieldLimit =
+    FieldLimit::millis("sysals__intermeasurement_period", 10, 2550, 10);
+static INTEGRATION_PERIOD: FieldLimit = FieldLimit::millis("sysals__integration_period", 1, 512, 1);
+
+/// SYSALS__INTEGRATION_PERIOD: 1ms steps, code 0 = 1ms
+const INTEGRATION: DurationField = DurationField::millis(1, 1, 1, 512);
+
+/// Limit of a 16-bit ALS threshold field, in result counts
+const fn codes(field: &'static str) -> FieldLimit {
+    FieldLimit {
+        field,
+        min: 0,
+        max: u16::MAX as i32,
+        step: 1,
+        unit: " codes",
+    }
+}
 
 /// ALS Start Register (0x038)
 ///
 /// Writing to this register starts an ALS measurement.
 #[register(0x0038u16)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, ReadableRegister, WritableRegister)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ReadableRegister, WritableRegister)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum AlsStart {
     /// Single-shot ALS mode (0x01)
@@ -47,6 +70,10 @@ impl ToByteArray for AlsStart {
     }
 }
 
+impl DatasheetLimits for AlsStart {
+    const BUSY_POLICY: BusyPolicy = BusyPolicy::Anytime;
+}
+
 /// ALS Thresholds Register (0x03A-0x03D)
 ///
 /// Combined high and low thresholds for ALS interrupt generation.
@@ -61,6 +88,43 @@ pub struct AlsThresholds {
     pub low: Luminance,
 }
 
+wire_eq!(AlsThresholds, |r| (r.high.lux as u16, r.low.lux as u16));
+
+impl AlsThresholds {
+    /// Largest threshold the SYSALS__THRESH registers can hold
+    pub const MAX: Luminance = Luminance::from_lux(u16::MAX as f32);
+
+    /// Thresholds for an interrupt when the light level is below `limit`.
+    ///
+    /// Pair with [`InterruptMode::LevelLow`](crate::types::InterruptMode::LevelLow).
+    /// The unused high threshold is set to [`MAX`](Self::MAX).
+    pub fn below(limit: Luminance) -> Self {
+        Self {
+            high: Self::MAX,
+            low: limit,
+        }
+    }
+
+    /// Thresholds for an interrupt when the light level is above `limit`.
+    ///
+    /// Pair with [`InterruptMode::LevelHigh`](crate::types::InterruptMode::LevelHigh).
+    /// The unused low threshold is set to zero.
+    pub fn above(limit: Luminance) -> Self {
+        Self {
+            high: limit,
+            low: Luminance::from_lux(0.0),
+        }
+    }
+
+    /// Thresholds for an interrupt when the light level is outside `low..=high`.
+    ///
+    /// Pair with [`InterruptMode::OutOfWindow`](crate::types::InterruptMode::OutOfWindow).
+    /// Returns `None` if `low` is above `high`.
+    pub fn window(low: Luminance, high: Luminance) -> Option<Self> {
+        (low <= high).then_some(Self { high, low })
+    }
+}
+
 impl FromByteArray for AlsThresholds {
     type Error = Infallible;
     type Array = [u8; 4];
@@ -95,25 +159,68 @@ impl ToByteArray for AlsThresholds {
     }
 }
 
+impl DatasheetLimits for AlsThresholds {
+    const BUSY_POLICY: BusyPolicy = BusyPolicy::AlsIdle;
+
+    fn check_limits(&self) -> Result<(), LimitViolation> {
+        THRESH_HIGH.check(self.high.lux as i32)?;
+        THRESH_LOW.check(self.low.lux as i32)
+    }
+}
+
 /// ALS Intermeasurement Period Register (0x03E)
 ///
 /// Time delay between measurements in continuous mode.
 #[register(0x003Eu16)]
-#[derive(Debug, Clone, Copy, ReadableRegister, WritableRegister)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, ReadableRegister, WritableRegister,
+)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct AlsIntermeasurementPeriod {
     /// Period between measurements
     pub period: Duration,
 }
 
+impl AlsIntermeasurementPeriod {
+    /// Period of `ms` milliseconds, or `None` outside the datasheet limits
+    ///
+    /// Accepts 10ms to 2550ms in steps of 10ms.
+    pub const fn checked_ms(ms: u16) -> Option<Self> {
+        if INTERMEASUREMENT_PERIOD.accepts(ms as i32 * 1_000) {
+            Some(Self {
+                period: Duration::from_millis(ms as u64),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Period of `MS` milliseconds, checked at compile time
+    ///
+    /// A value [`checked_ms`](Self::checked_ms) rejects fails the build.
+    ///
+    /// ```
+    /// use vl6180x::registers::AlsIntermeasurementPeriod;
+    ///
+    /// const VALUE: AlsIntermeasurementPeriod = AlsIntermeasurementPeriod::from_ms::<500>();
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use vl6180x::registers::AlsIntermeasurementPeriod;
+    ///
+    /// const VALUE: AlsIntermeasurementPeriod = AlsIntermeasurementPeriod::from_ms::<5>();
+    /// ```
+    pub const fn from_ms<const MS: u16>() -> Self {
+        const { Self::checked_ms(MS).expect("outside the datasheet limits") }
+    }
+}
+
 impl FromByteArray for AlsIntermeasurementPeriod {
     type Error = Infallible;
     type Array = [u8; 1];
 
     fn from_bytes(bytes: Self::Array) -> Result<Self, Self::Error> {
-        // Period is in units of 10ms: value 0 = 10ms, value 1 = 20ms, etc.
-        let milliseconds = (bytes[0] as u64 + 1) * 10;
-        let period = Duration::from_millis(milliseconds);
+        let period = INTERMEASUREMENT.decode(bytes[0].into());
         Ok(Self { period })
     }
 }
@@ -123,19 +230,16 @@ impl ToByteArray for AlsIntermeasurementPeriod {
     type Array = [u8; 1];
 
     fn to_bytes(self) -> Result<Self::Array, Self::Error> {
-        // Convert to units of 10ms
         // Valid range: 10ms to 2560ms (value 0-255 in register)
-        let total_ms = self.period.as_millis() as u64;
+        Ok([INTERMEASUREMENT.encode(self.period)? as u8])
+    }
+}
 
-        if total_ms < 10 {
-            return Err(RegisterError::DurationTooShort);
-        }
-        if total_ms > 2560 {
-            return Err(RegisterError::DurationTooLong);
-        }
+impl DatasheetLimits for AlsIntermeasurementPeriod {
+    const BUSY_POLICY: BusyPolicy = BusyPolicy::AlsIdle;
 
-        let value = (((total_ms + 5) / 10) - 1) as u8;
-        Ok([value])
+    fn check_limits(&self) -> Result<(), LimitViolation> {
+        INTERMEASUREMENT_PERIOD.check_duration(self.period)
     }
 }
 
@@ -143,13 +247,20 @@ impl ToByteArray for AlsIntermeasurementPeriod {
 ///
 /// Configures the ALS analog gain setting.
 #[register(0x003Fu16)]
-#[derive(Debug, Clone, Copy, ReadableRegister, WritableRegister)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ReadableRegister, WritableRegister)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct AlsAnalogueGain {
     /// Analog gain setting
     pub gain: AlsGain,
 }
 
+impl AlsAnalogueGain {
+    /// Register value selecting `gain`
+    pub const fn new(gain: AlsGain) -> Self {
+        Self { gain }
+    }
+}
+
 impl FromByteArray for AlsAnalogueGain {
     type Error = RegisterError;
     type Array = [u8; 1];
@@ -165,47 +276,117 @@ impl ToByteArray for AlsAnalogueGain {
     type Array = [u8; 1];
 
     fn to_bytes(self) -> Result<Self::Array, Self::Error> {
-        Ok([self.gain as u8])
+        // The upper nibble must be written as 0x4, e.g. 0x46 for gain 1.0
+        Ok([0x40 | self.gain as u8])
     }
 }
 
-/// ALS Integration Period Register (0x040)
+impl DatasheetLimits for AlsAnalogueGain {
+    const BUSY_POLICY: BusyPolicy = BusyPolicy::AlsIdle;
+}
+
+/// ALS Integration Period Register (0x040-0x041)
 ///
-/// Integration time for the ALS measurement (in ms, 1-100ms typical range).
+/// Integration time for the ALS measurement (9-bit value, 1 code = 1ms, 0 = 1ms).
+/// The datasheet recommends 100ms.
 #[register(0x0040u16)]
-#[derive(Debug, Clone, Copy, ReadableRegister, WritableRegister)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, ReadableRegister, WritableRegister,
+)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct AlsIntegrationPeriod {
     /// Integration period
     pub period: Duration,
 }
 
+impl AlsIntegrationPeriod {
+    /// Integration period of `ms` milliseconds, or `None` outside the datasheet limits
+    ///
+    /// Accepts 1ms to 512ms.
+    pub const fn checked_ms(ms: u16) -> Option<Self> {
+        if INTEGRATION_PERIOD.accepts(ms as i32 * 1_000) {
+            Some(Self {
+                period: Duration::from_millis(ms as u64),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Integration period of `MS` milliseconds, checked at compile time
+    ///
+    /// A value [`checked_ms`](Self::checked_ms) rejects fails the build.
+    ///
+    /// ```
+    /// use vl6180x::registers::AlsIntegrationPeriod;
+    ///
+    /// const VALUE: AlsIntegrationPeriod = AlsIntegrationPeriod::from_ms::<100>();
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use vl6180x::registers::AlsIntegrationPeriod;
+    ///
+    /// const VALUE: AlsIntegrationPeriod = AlsIntegrationPeriod::from_ms::<513>();
+    /// ```
+    pub const fn from_ms<const MS: u16>() -> Self {
+        const { Self::checked_ms(MS).expect("outside the datasheet limits") }
+    }
+
+    /// Shortest continuous ALS intermeasurement period for this integration
+    /// period
+    ///
+    /// The datasheet's continuous mode limits require 1.1 times the
+    /// integration period, covering processing overhead, to fit into 0.9
+    /// times the intermeasurement period, covering oscillator tolerance.
+    ///
+    /// ```
+    /// use core::time::Duration;
+    /// use vl6180x::registers::AlsIntegrationPeriod;
+    ///
+    /// let integration = AlsIntegrationPeriod::from_ms::<90>();
+    /// assert_eq!(integration.min_intermeasurement_period(), Duration::from_millis(110));
+    /// ```
+    pub const fn min_intermeasurement_period(&self) -> Duration {
+        self.min_intermeasurement_period_with(Duration::ZERO)
+    }
+
+    /// Shortest intermeasurement period fitting an ALS measurement with this
+    /// integration period followed by `busy` of other work, e.g. a range
+    /// measurement
+    pub(crate) const fn min_intermeasurement_period_with(&self, busy: Duration) -> Duration {
+        let nanos = self.period.as_nanos() * 11 / 10 + busy.as_nanos();
+        let nanos = (nanos * 10).div_ceil(9);
+        Duration::new(
+            (nanos / 1_000_000_000) as u64,
+            (nanos % 1_000_000_000) as u32,
+        )
+    }
+}
+
 impl FromByteArray for AlsIntegrationPeriod {
     type Error = Infallible;
-    type Array = [u8; 1];
+    type Array = [u8; 2];
 
     fn from_bytes(bytes: Self::Array) -> Result<Self, Self::Error> {
-        // Integration period is in milliseconds
-        let period = Duration::from_millis(bytes[0] as u64);
+        let period = INTEGRATION.decode(u16::from_be_bytes(bytes) & 0x01FF);
         Ok(Self { period })
     }
 }
 
 impl ToByteArray for AlsIntegrationPeriod {
     type Error = RegisterError;
-    type Array = [u8; 1];
+    type Array = [u8; 2];
 
     fn to_bytes(self) -> Result<Self::Array, Self::Error> {
-        // Valid range: 1ms to 255ms
-        let ms = self.period.as_millis() as u64;
+        // Valid range: 1ms to 512ms (value 0-511 in register)
+        Ok(INTEGRATION.encode(self.period)?.to_be_bytes())
+    }
+}
 
-        if ms < 1 {
-            return Err(RegisterError::DurationTooShort);
-        }
-        if ms > 255 {
-            return Err(RegisterError::DurationTooLong);
-        }
+impl DatasheetLimits for AlsIntegrationPeriod {
+    const BUSY_POLICY: BusyPolicy = BusyPolicy::AlsIdle;
 
-        Ok([ms as u8])
+    fn check_limits(&self) -> Result<(), LimitViolation> {
+        INTEGRATION_PERIOD.check_duration(self.period)
     }
 }
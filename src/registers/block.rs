@@ -0,0 +1,321 @@
+//! Register Blocks
+//!
+//! Contiguous register ranges that are useful to read in a single
+//! transaction, either for speed or so that all values belong to the same
+//! sample. The device auto-increments the register address during a
+//! multi-byte read, so each block is read with one I2C transaction.
+
+use core::time::Duration;
+use measurements::Length;
+use regiface::{register, FromByteArray, ReadableRegister};
+
+use super::{
+    AlsResultValue, ModelId, ModelRevision, ModuleRevision, ModuleTimestamp, RangeResultStatus,
+    ResultAlsStatus, ResultInterruptStatusGpio,
+};
+use crate::dmax::{DmaxModel, TargetReflectance};
+use crate::types::RegisterError;
+
+/// Identification Block (0x000-0x009)
+///
+/// Model and module identification read in one transaction.
+#[register(0x0000u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ReadableRegister)]
+pub struct IdentificationBlock {
+    /// Model ID (0x000)
+    pub model_id: ModelId,
+    /// Model revision (0x001-0x002)
+    pub model_revision: ModelRevision,
+    /// Module revision (0x003-0x004)
+    pub module_revision: ModuleRevision,
+    /// Manufacturing timestamp (0x006-0x009)
+    pub timestamp: ModuleTimestamp,
+}
+
+impl FromByteArray for IdentificationBlock {
+    type Error = RegisterError;
+    type Array = [u8; 10];
+
+    fn from_bytes(bytes: Self::Array) -> Result<Self, Self::Error> {
+        let Ok(model_id) = ModelId::from_bytes([bytes[0]]);
+        let Ok(model_revision) = ModelRevision::from_bytes([bytes[1], bytes[2]]);
+        let Ok(module_revision) = ModuleRevision::from_bytes([bytes[3], bytes[4]]);
+        // 0x005 is reserved
+        let timestamp = ModuleTimestamp::from_bytes([bytes[6], bytes[7], bytes[8], bytes[9]])?;
+
+        Ok(Self {
+            model_id,
+            model_revision,
+            module_revision,
+            timestamp,
+        })
+    }
+}
+
+/// Range Status Block (0x04D-0x062)
+///
+/// The range status, interrupt status and final range value of the same
+/// sample, read in one transaction. The ALS result and history buffer bytes
+/// in between are skipped.
+#[register(0x004Du16)]
+#[derive(Debug, Clone, Copy, ReadableRegister)]
+pub struct RangeStatusBlock {
+    /// Range status (0x04D)
+    pub status: RangeResultStatus,
+    /// Interrupt status (0x04F)
+    pub interrupt: ResultInterruptStatusGpio,
+    /// Final range value (0x062)
+    pub distance: Length,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for RangeStatusBlock {
+    fn format(&self, f: defmt::Formatter) {
+        use crate::types::Millimeters;
+
+        defmt::write!(
+            f,
+            "RangeStatusBlock {{ status: {}, interrupt: {}, distance: {} }}",
+            self.status,
+            self.interrupt,
+            Millimeters(self.distance)
+        );
+    }
+}
+
+wire_eq!(RangeStatusBlock, |r| (r.status, r.interrupt, r.raw_mm()));
+
+impl RangeStatusBlock {
+    /// The final range value as the register byte, in millimeters.
+    ///
+    /// Exactly the byte read from the device for a decoded block.
+    pub fn raw_mm(&self) -> u8 {
+        self.distance.as_millimeters() as u8
+    }
+}
+
+impl FromByteArray for RangeStatusBlock {
+    type Error = RegisterError;
+    type Array = [u8; 22];
+
+    fn from_bytes(bytes: Self::Array) -> Result<Self, Self::Error> {
+        // Offsets are relative to 0x04D
+        let status = RangeResultStatus::from_bytes([bytes[0x00]])?;
+        let Ok(interrupt) = ResultInterruptStatusGpio::from_bytes([bytes[0x02]]);
+
+        Ok(Self {
+            status,
+            interrupt,
+            distance: Length::from_millimeters(bytes[0x15] as f64),
+        })
+    }
+}
+
+/// ALS Result Block (0x04E-0x051)
+///
+/// The ALS status, interrupt status and ALS count of the same sample, read
+/// in one transaction so a continuous measurement cannot complete between
+/// the status and the value.
+#[register(0x004Eu16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ReadableRegister)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AlsResultBlock {
+    /// ALS status (0x04E)
+    pub status: ResultAlsStatus,
+    /// Interrupt status (0x04F)
+    pub interrupt: ResultInterruptStatusGpio,
+    /// ALS count (0x050-0x051)
+    pub value: AlsResultValue,
+}
+
+impl FromByteArray for AlsResultBlock {
+    type Error = RegisterError;
+    type Array = [u8; 4];
+
+    fn from_bytes(bytes: Self::Array) -> Result<Self, Self::Error> {
+        let status = ResultAlsStatus::from_bytes([bytes[0]])?;
+        let Ok(interrupt) = ResultInterruptStatusGpio::from_bytes([bytes[1]]);
+        let Ok(value) = AlsResultValue::from_bytes([bytes[2], bytes[3]]);
+
+        Ok(Self {
+            status,
+            interrupt,
+            value,
+        })
+    }
+}
+
+/// History Buffer (0x052-0x061)
+///
+/// Eight 16-bit history entries, most recent first. Only filled while the
+/// history buffer is enabled through [`HistoryCtrl`](super::HistoryCtrl).
+#[register(0x0052u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ReadableRegister)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct HistoryBuffer {
+    /// Raw history entries
+    ///
+    /// In ALS mode each entry is an ALS count. In range mode each entry holds
+    /// two range values, see [`HistoryBuffer::range_mm`].
+    pub entries: [u16; 8],
+}
+
+impl HistoryBuffer {
+    /// Returns the buffered range values in millimeters, most recent first
+    ///
+    /// Only meaningful when the history buffer is capturing range results.
+    pub fn range_mm(&self) -> [u8; 16] {
+        let mut values = [0u8; 16];
+        for (pair, entry) in values.chunks_exact_mut(2).zip(self.entries) {
+            pair.copy_from_slice(&entry.to_be_bytes());
+        }
+        values
+    }
+}
+
+impl FromByteArray for HistoryBuffer {
+    type Error = core::convert::Infallible;
+    type Array = [u8; 16];
+
+    fn from_bytes(bytes: Self::Array) -> Result<Self, Self::Error> {
+        let mut entries = [0u16; 8];
+        for (entry, raw) in entries.iter_mut().zip(bytes.chunks_exact(2)) {
+            *entry = u16::from_be_bytes([raw[0], raw[1]]);
+        }
+        Ok(Self { entries })
+    }
+}
+
+/// Range Result Block (0x062-0x083)
+///
+/// The final and raw range values together with the signal statistics of
+/// the same sample.
+#[register(0x0062u16)]
+#[derive(Debug, Clone, Copy, ReadableRegister)]
+pub struct RangeResultBlock {
+    /// Final range value (0x062)
+    pub distance: Length,
+    /// Range value with offset but without crosstalk compensation (0x064)
+    pub raw_distance: Length,
+    /// Return signal rate in Mcps (9.7 fixed point) (0x066)
+    pub return_rate: u16,
+    /// Reference signal rate in Mcps (9.7 fixed point) (0x068)
+    pub reference_rate: u16,
+    /// Return array signal count (0x06C)
+    pub return_signal_count: u32,
+    /// Reference array signal count (0x070)
+    pub reference_signal_count: u32,
+    /// Return array ambient count (0x074)
+    pub return_ambient_count: u32,
+    /// Reference array ambient count (0x078)
+    pub reference_ambient_count: u32,
+    /// Return array convergence time (0x07C)
+    pub return_convergence_time: Duration,
+    /// Reference array convergence time (0x080)
+    pub reference_convergence_time: Duration,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for RangeResultBlock {
+    fn format(&self, f: defmt::Formatter) {
+        use crate::types::Millimeters;
+
+        defmt::write!(
+            f,
+            "RangeResultBlock {{ distance: {}, raw_distance: {}, return_rate: {}, reference_rate: {}, return_signal_count: {}, reference_signal_count: {}, return_ambient_count: {}, reference_ambient_count: {}, return_convergence_time: {}, reference_convergence_time: {} }}",
+            Millimeters(self.distance), Millimeters(self.raw_distance), self.return_rate, self.reference_rate, self.return_signal_count, self.reference_signal_count, self.return_ambient_count, self.reference_ambient_count, self.return_convergence_time, self.reference_convergence_time
+        );
+    }
+}
+
+wire_eq!(RangeResultBlock, |r| (
+    r.raw_mm(),
+    r.raw_distance_mm(),
+    r.return_rate,
+    r.reference_rate,
+    r.return_signal_count,
+    r.reference_signal_count,
+    r.return_ambient_count,
+    r.reference_ambient_count,
+    r.return_convergence_time,
+    r.reference_convergence_time
+));
+
+impl RangeResultBlock {
+    /// The final range value as the register byte, in millimeters.
+    ///
+    /// Exactly the byte read from the device for a decoded block.
+    pub fn raw_mm(&self) -> u8 {
+        self.distance.as_millimeters() as u8
+    }
+
+    /// The raw range value as the register byte, in millimeters.
+    ///
+    /// Exactly the byte read from the device for a decoded block.
+    pub fn raw_distance_mm(&self) -> u8 {
+        self.raw_distance.as_millimeters() as u8
+    }
+
+    /// Return ambient rate in Mcps (9.7 fixed point)
+    ///
+    /// The return ambient count over the return convergence time, zero if no
+    /// convergence time was reported.
+    pub fn ambient_rate(&self) -> u16 {
+        let millis = self.return_convergence_time.as_millis();
+        if millis == 0 {
+            return 0;
+        }
+        let rate = u128::from(self.return_ambient_count) * 128 / (millis * 1_000);
+        rate.min(u128::from(u16::MAX)) as u16
+    }
+
+    /// Estimates how far a target would have been detected under the
+    /// ambient light of this measurement.
+    ///
+    /// Uses [`DmaxModel::TYPICAL`](crate::dmax::DmaxModel::TYPICAL) with the
+    /// return convergence time as the time the measurement had. That is the
+    /// whole convergence time limit for a measurement that found no target,
+    /// the case the estimate is meant for. See the [`dmax`](crate::dmax)
+    /// module for its accuracy.
+    ///
+    /// # Arguments
+    /// * `reflectance` - Reflectance of the target to look for
+    pub fn estimated_max_distance(&self, reflectance: TargetReflectance) -> Length {
+        DmaxModel::TYPICAL.max_distance(
+            reflectance,
+            self.ambient_rate(),
+            self.return_convergence_time,
+        )
+    }
+}
+
+impl FromByteArray for RangeResultBlock {
+    type Error = core::convert::Infallible;
+    type Array = [u8; 34];
+
+    fn from_bytes(bytes: Self::Array) -> Result<Self, Self::Error> {
+        // Offsets are relative to 0x062
+        let u16_at = |offset: usize| u16::from_be_bytes([bytes[offset], bytes[offset + 1]]);
+        let u32_at = |offset: usize| {
+            u32::from_be_bytes([
+                bytes[offset],
+                bytes[offset + 1],
+                bytes[offset + 2],
+                bytes[offset + 3],
+            ])
+        };
+
+        Ok(Self {
+            distance: Length::from_millimeters(bytes[0x00] as f64),
+            raw_distance: Length::from_millimeters(bytes[0x02] as f64),
+            return_rate: u16_at(0x04),
+            reference_rate: u16_at(0x06),
+            return_signal_count: u32_at(0x0A),
+            reference_signal_count: u32_at(0x0E),
+            return_ambient_count: u32_at(0x12),
+            reference_ambient_count: u32_at(0x16),
+            return_convergence_time: Duration::from_millis(u32_at(0x1A) as u64),
+            reference_convergence_time: Duration::from_millis(u32_at(0x1E) as u64),
+        })
+    }
+}
@@ -0,0 +1,193 @@
+//! Power-on values of the configuration registers
+//!
+//! [`POWER_ON_DEFAULTS`] holds the reset values the datasheet documents for
+//! the writable configuration registers. The `Default` implementations of the
+//! register types decode their value from it, and
+//! [`Device::factory_reset`](crate::Device::factory_reset) writes it, so both
+//! agree by construction.
+
+use regiface::{ByteArray, ReadableRegister};
+
+use super::*;
+
+/// Documented power-on value of a configuration register
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PowerOnDefault {
+    /// Name of the register type, or of the datasheet register for the
+    /// registers without a type
+    pub name: &'static str,
+    /// Address of the first byte
+    pub address: u16,
+    /// Bytes the register holds after power-on
+    pub bytes: &'static [u8],
+}
+
+/// Power-on values of the writable configuration registers, by address.
+///
+/// The part-to-part range offset is missing: it is loaded from the factory
+/// calibration in NVM at boot and has no fixed value. So are the I2C
+/// address, start commands, interrupt clears and the other registers that
+/// trigger an action rather than hold a setting.
+pub const POWER_ON_DEFAULTS: [PowerOnDefault; 22] = [
+    PowerOnDefault {
+        name: "ModeGpio0",
+        address: 0x010,
+        bytes: &[0x60],
+    },
+    PowerOnDefault {
+        name: "ModeGpio1",
+        address: 0x011,
+        bytes: &[0x20],
+    },
+    PowerOnDefault {
+        name: "HistoryCtrl",
+        address: 0x012,
+        bytes: &[0x00],
+    },
+    PowerOnDefault {
+        name: "InterruptConfigGpio",
+        address: 0x014,
+        bytes: &[0x00],
+    },
+    PowerOnDefault {
+        name: "RangeThresholds",
+        address: 0x019,
+        bytes: &[0xFF, 0x00],
+    },
+    PowerOnDefault {
+        name: "RangeIntermeasurementPeriod",
+        address: 0x01B,
+        bytes: &[0xFF],
+    },
+    PowerOnDefault {
+        name: "RangeMaxConvergenceTime",
+        address: 0x01C,
+        bytes: &[0x31],
+    },
+    PowerOnDefault {
+        name: "RangeCrosstalkCompensationRate",
+        address: 0x01E,
+        bytes: &[0x00, 0x00],
+    },
+    PowerOnDefault {
+        name: "RangeCrosstalkValidHeight",
+        address: 0x021,
+        bytes: &[0x14],
+    },
+    PowerOnDefault {
+        name: "RangeEarlyConvergenceEstimate",
+        address: 0x022,
+        bytes: &[0x00, 0x00],
+    },
+    PowerOnDefault {
+        name: "RangeIgnoreValidHeight",
+        address: 0x025,
+        bytes: &[0x00],
+    },
+    PowerOnDefault {
+        name: "RangeIgnoreThreshold",
+        address: 0x026,
+        bytes: &[0x00, 0x00],
+    },
+    PowerOnDefault {
+        name: "SYSRANGE__MAX_AMBIENT_LEVEL_MULT",
+        address: 0x02C,
+        bytes: &[0xA0],
+    },
+    PowerOnDefault {
+        name: "RangeCheckEnables",
+        address: 0x02D,
+        bytes: &[0x11],
+    },
+    PowerOnDefault {
+        name: "RangeVhvRepeatRate",
+        address: 0x031,
+        bytes: &[0x00],
+    },
+    PowerOnDefault {
+        name: "AlsThresholds",
+        address: 0x03A,
+        bytes: &[0xFF, 0xFF, 0x00, 0x00],
+    },
+    PowerOnDefault {
+        name: "AlsIntermeasurementPeriod",
+        address: 0x03E,
+        bytes: &[0xFF],
+    },
+    PowerOnDefault {
+        name: "AlsAnalogueGain",
+        address: 0x03F,
+        bytes: &[0x06],
+    },
+    PowerOnDefault {
+        name: "AlsIntegrationPeriod",
+        address: 0x040,
+        bytes: &[0x00, 0x00],
+    },
+    PowerOnDefault {
+        name: "ReadoutAveraging",
+        address: 0x10A,
+        bytes: &[0x30],
+    },
+    PowerOnDefault {
+        name: "FIRMWARE__RESULT_SCALER",
+        address: 0x120,
+        bytes: &[0x01],
+    },
+    PowerOnDefault {
+        name: "InterleavedModeEnable",
+        address: 0x2A3,
+        bytes: &[0x00],
+    },
+];
+
+/// Decodes the power-on value of `R` from [`POWER_ON_DEFAULTS`]
+fn power_on<R: ReadableRegister<IdType = u16>>() -> R {
+    let entry = POWER_ON_DEFAULTS
+        .iter()
+        .find(|entry| entry.address == R::id())
+        .expect("register without a power-on value");
+    let mut bytes = R::Array::new();
+    bytes.as_mut().copy_from_slice(entry.bytes);
+    match R::from_bytes(bytes) {
+        Ok(register) => register,
+        Err(_) => unreachable!("power-on value does not decode"),
+    }
+}
+
+macro_rules! power_on_default {
+    ($($ty:ident),* $(,)?) => {
+        $(
+            impl Default for $ty {
+                /// The power-on value, see [`POWER_ON_DEFAULTS`]
+                fn default() -> Self {
+                    power_on()
+                }
+            }
+        )*
+    };
+}
+
+power_on_default![
+    ModeGpio0,
+    ModeGpio1,
+    HistoryCtrl,
+    InterruptConfigGpio,
+    RangeThresholds,
+    RangeIntermeasurementPeriod,
+    RangeMaxConvergenceTime,
+    RangeCrosstalkCompensationRate,
+    RangeCrosstalkValidHeight,
+    RangeEarlyConvergenceEstimate,
+    RangeIgnoreValidHeight,
+    RangeIgnoreThreshold,
+    RangeCheckEnables,
+    RangeVhvRepeatRate,
+    AlsThresholds,
+    AlsIntermeasurementPeriod,
+    AlsAnalogueGain,
+    AlsIntegrationPeriod,
+    ReadoutAveraging,
+    InterleavedModeEnable,
+];
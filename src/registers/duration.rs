@@ -0,0 +1,71 @@
+//! Duration register fields
+//!
+//! Every duration-bearing register stores a whole number of fixed steps,
+//! possibly offset by one so that code 0 stands for the shortest duration.
+//! [`DurationField`] describes such an encoding once, so all of them round
+//! and validate the same way.
+
+use core::time::Duration;
+
+use crate::types::RegisterError;
+
+/// Encoding of a duration as a register code
+///
+/// Code `c` stands for `(c + offset) * step`. Encodable durations range from
+/// `min_steps` to `max_steps` steps.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct DurationField {
+    step: Duration,
+    offset: u16,
+    min_steps: u16,
+    max_steps: u16,
+}
+
+impl DurationField {
+    /// Field counting `step` milliseconds per code, from `min_steps` to
+    /// `max_steps` steps, where code 0 stands for `offset` steps
+    pub(crate) const fn millis(step: u64, offset: u16, min_steps: u16, max_steps: u16) -> Self {
+        Self {
+            step: Duration::from_millis(step),
+            offset,
+            min_steps,
+            max_steps,
+        }
+    }
+
+    /// Shortest encodable duration
+    pub(crate) fn min(&self) -> Duration {
+        self.step * u32::from(self.min_steps)
+    }
+
+    /// Longest encodable duration
+    pub(crate) fn max(&self) -> Duration {
+        self.step * u32::from(self.max_steps)
+    }
+
+    /// Duration stood for by `code`
+    pub(crate) fn decode(&self, code: u16) -> Duration {
+        self.step * (u32::from(code) + u32::from(self.offset))
+    }
+
+    /// Code of `duration`, rounded to the nearest step
+    ///
+    /// # Errors
+    /// * `RegisterError::DurationTooShort` - `duration` is below [`min`](Self::min)
+    /// * `RegisterError::DurationTooLong` - `duration` is above [`max`](Self::max)
+    pub(crate) fn encode(&self, duration: Duration) -> Result<u16, RegisterError> {
+        if duration < self.min() {
+            return Err(RegisterError::DurationTooShort);
+        }
+        if duration > self.max() {
+            return Err(RegisterError::DurationTooLong);
+        }
+
+        let step = self.step.as_micros();
+        let steps = (duration.as_micros() + step / 2) / step;
+        Ok(steps as u16 - self.offset)
+    }
+}
+
+/// SYSRANGE__ and SYSALS__INTERMEASUREMENT_PERIOD: 10ms steps, code 0 = 10ms
+pub(crate) const INTERMEASUREMENT: DurationField = DurationField::millis(10, 1, 1, 256);
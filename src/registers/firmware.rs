@@ -0,0 +1,29 @@
+//! Firmware Registers (0x119 - 0x120)
+//!
+//! These registers report the state of the device firmware.
+
+use core::convert::Infallible;
+use regiface::{register, FromByteArray, ReadableRegister};
+
+/// Firmware Bootup Register (0x119)
+///
+/// Set by the firmware once the initial boot after power-up has completed.
+/// The device should not be configured before this flag is set.
+#[register(0x0119u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ReadableRegister)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FirmwareBootup {
+    /// Boot completed flag
+    pub booted: bool,
+}
+
+impl FromByteArray for FirmwareBootup {
+    type Error = Infallible;
+    type Array = [u8; 1];
+
+    fn from_bytes(bytes: Self::Array) -> Result<Self, Self::Error> {
+        Ok(Self {
+            booted: bytes[0] & 0x01 != 0,
+        })
+    }
+}
@@ -14,7 +14,7 @@ use crate::types::RegisterError;
 /// Expected value: VL6180X (0xB4)
 /// This register contains the device model identification.
 #[register(0x0000u16)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, ReadableRegister)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ReadableRegister)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum ModelId {
     /// VL6180X device (0xB4)
@@ -39,7 +39,7 @@ impl FromByteArray for ModelId {
 ///
 /// Combined major and minor model revision numbers.
 #[register(0x0001u16)]
-#[derive(Debug, Clone, Copy, ReadableRegister)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, ReadableRegister)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct ModelRevision {
     /// Model major revision number
@@ -64,7 +64,7 @@ impl FromByteArray for ModelRevision {
 ///
 /// Combined major and minor module revision numbers.
 #[register(0x0003u16)]
-#[derive(Debug, Clone, Copy, ReadableRegister)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, ReadableRegister)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct ModuleRevision {
     /// Module major revision number
@@ -91,14 +91,17 @@ impl FromByteArray for ModuleRevision {
 /// Reads 4 bytes: date_hi, date_lo, time_hi, time_lo
 ///
 /// Date format:
-/// - date_hi: bits [7:4] = year offset from 2010, bits [3:0] = month (1-12)
-/// - date_lo: bits [7:3] = day of month (1-31), bits [2:0] = reserved
+/// - date_hi: bits `[7:4]` = year offset from 2010, bits `[3:0]` = month (1-12)
+/// - date_lo: bits `[7:3]` = day of month (1-31), bits `[2:0]` = reserved
 ///
 /// Time format:
 /// - time_hi:time_lo forms a 16-bit value
 /// - This value * 2 = seconds since midnight
+///
+/// With the `chrono` feature enabled the timestamp is also available as a
+/// `chrono::NaiveDateTime` through `ModuleTimestamp::as_chrono`.
 #[register(0x0006u16)]
-#[derive(Debug, Clone, Copy, ReadableRegister)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, ReadableRegister)]
 pub struct ModuleTimestamp {
     /// Manufacturing date and time
     pub timestamp: DateTime,
@@ -116,7 +119,7 @@ impl FromByteArray for ModuleTimestamp {
         // Parse time bytes as 16-bit value
         let time_hi = bytes[2];
         let time_lo = bytes[3];
-        let time_value = ((time_hi as u16) << 8) | (time_lo as u16);
+        let time_value = u32::from(u16::from_be_bytes([time_hi, time_lo]));
 
         // Extract date components
         let year = 2010 + ((date_hi >> 4) as i16);
@@ -135,3 +138,30 @@ impl FromByteArray for ModuleTimestamp {
         Ok(Self { timestamp })
     }
 }
+
+#[cfg(feature = "chrono")]
+impl ModuleTimestamp {
+    /// Returns the manufacturing date and time as a `chrono::NaiveDateTime`
+    ///
+    /// Cannot fail: chrono covers every date and time `jiff` can hold.
+    ///
+    /// # Example
+    /// ```
+    /// use regiface::FromByteArray;
+    /// use vl6180x::registers::ModuleTimestamp;
+    ///
+    /// // 2015-10-19, 21600 * 2 seconds after midnight
+    /// let module = ModuleTimestamp::from_bytes([0x5A, 0x98, 0x54, 0x60]).unwrap();
+    ///
+    /// assert_eq!(module.timestamp.to_string(), "2015-10-19T12:00:00");
+    /// assert_eq!(module.as_chrono().to_string(), "2015-10-19 12:00:00");
+    /// ```
+    pub fn as_chrono(&self) -> chrono::NaiveDateTime {
+        let ts = self.timestamp;
+        chrono::NaiveDate::from_ymd_opt(ts.year() as i32, ts.month() as u32, ts.day() as u32)
+            .and_then(|date| {
+                date.and_hms_opt(ts.hour() as u32, ts.minute() as u32, ts.second() as u32)
+            })
+            .expect("jiff date and time outside chrono's range")
+    }
+}
@@ -0,0 +1,51 @@
+//! Address and width of every register type
+
+use regiface::ReadableRegister;
+
+use super::*;
+
+/// Where a register type lives in the device's register map
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RegisterLayout {
+    /// Name of the register type
+    pub name: &'static str,
+    /// Address of the first byte
+    pub address: u16,
+    /// Number of bytes transferred
+    pub width: usize,
+}
+
+impl RegisterLayout {
+    fn of<R: ReadableRegister<IdType = u16>>(name: &'static str) -> Self {
+        Self {
+            name,
+            address: R::id(),
+            width: core::mem::size_of::<R::Array>(),
+        }
+    }
+
+    /// Address one past the last byte
+    pub fn end(&self) -> u16 {
+        self.address + self.width as u16
+    }
+
+    /// Returns whether this register and `other` share at least one byte.
+    pub fn overlaps(&self, other: &RegisterLayout) -> bool {
+        self.address < other.end() && other.address < self.end()
+    }
+}
+
+macro_rules! layout {
+    ($($ty:ident),* $(,)?) => {
+        [$(RegisterLayout::of::<$ty>(stringify!($ty))),*]
+    };
+}
+
+/// Returns the layout of every register type in this module, by address.
+///
+/// The block types are views spanning several registers and overlap the
+/// registers they are read together with.
+pub fn layout() -> [RegisterLayout; 43] {
+    for_each_register!(layout)
+}
@@ -6,15 +6,183 @@
 //! - Range: Ranging sensor configuration
 //! - ALS: Ambient light sensor configuration
 //! - Result: Measurement results
+//! - Firmware: Firmware boot status
+//! - Block: Contiguous multi-register reads
+//!
+//! [`layout`] lists the address and width of every register type, and
+//! [`POWER_ON_DEFAULTS`] the power-on values of the configuration registers.
+//!
+//! Registers holding only integer, enum or [`Duration`](core::time::Duration)
+//! fields derive their comparison traits. Registers holding a floating point
+//! quantity compare, hash and order by the integer value they encode to on the
+//! wire instead, so two values are equal exactly when they would be written to
+//! the device as the same bytes.
+//!
+//! Every writable register implements [`DatasheetLimits`], next to its
+//! definition, with the field ranges the datasheet documents for it and the
+//! measurements that must be idle while it is written.
+
+use regiface::WritableRegister;
+
+use crate::types::LimitViolation;
+
+/// Implements `PartialEq`, `Eq` and `Hash` for a register by comparing the
+/// integer key returned by `$key` instead of its floating point fields
+macro_rules! wire_eq {
+    ($ty:ty, |$reg:ident| $key:expr) => {
+        impl $ty {
+            fn wire_key(&self) -> impl Eq + core::hash::Hash {
+                let $reg = self;
+                $key
+            }
+        }
+
+        impl PartialEq for $ty {
+            fn eq(&self, other: &Self) -> bool {
+                self.wire_key() == other.wire_key()
+            }
+        }
+
+        impl Eq for $ty {}
+
+        impl core::hash::Hash for $ty {
+            fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+                self.wire_key().hash(state);
+            }
+        }
+    };
+}
+
+/// Invokes the macro `$m` with every readable register type of this module,
+/// by address
+///
+/// The per-register tables and impls generated from it, such as the
+/// [`layout`] and the slice parsing, cover a new register once it is added
+/// here.
+macro_rules! for_each_register {
+    ($m:ident) => {
+        $m! {
+            IdentificationBlock,
+            ModelId,
+            ModelRevision,
+            ModuleRevision,
+            ModuleTimestamp,
+            ModeGpio0,
+            ModeGpio1,
+            HistoryCtrl,
+            InterruptConfigGpio,
+            InterruptClear,
+            FreshOutOfReset,
+            GroupedParameterHold,
+            RangeStart,
+            RangeThresholds,
+            RangeIntermeasurementPeriod,
+            RangeMaxConvergenceTime,
+            RangeCrosstalkCompensationRate,
+            RangeCrosstalkValidHeight,
+            RangeEarlyConvergenceEstimate,
+            RangePartToPartOffset,
+            RangeIgnoreValidHeight,
+            RangeIgnoreThreshold,
+            RangeCheckEnables,
+            RangeVhvRecalibrate,
+            RangeVhvRepeatRate,
+            AlsStart,
+            AlsThresholds,
+            AlsIntermeasurementPeriod,
+            AlsAnalogueGain,
+            AlsIntegrationPeriod,
+            RangeResultStatus,
+            RangeStatusBlock,
+            ResultAlsStatus,
+            AlsResultBlock,
+            ResultInterruptStatusGpio,
+            AlsResultValue,
+            HistoryBuffer,
+            RangeResultValue,
+            RangeResultBlock,
+            RangeResultConvergenceTime,
+            ReadoutAveraging,
+            FirmwareBootup,
+            InterleavedModeEnable,
+        }
+    };
+}
+
+/// Measurements that must not be running while a register is written
+///
+/// Checked before register writes when the busy check is enabled, see
+/// [`Device::set_busy_check`](crate::Device::set_busy_check).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BusyPolicy {
+    /// Safe to write while measurements are running, e.g. start commands and
+    /// interrupt clears
+    Anytime,
+    /// Ranging configuration, written only while no range measurement runs
+    RangeIdle,
+    /// ALS configuration, written only while no ALS measurement runs
+    AlsIdle,
+    /// Configuration shared by both sensors, written only while neither runs
+    Idle,
+}
+
+impl BusyPolicy {
+    /// Returns whether a running range measurement blocks the write
+    pub fn needs_range_idle(self) -> bool {
+        matches!(self, Self::RangeIdle | Self::Idle)
+    }
+
+    /// Returns whether a running ALS measurement blocks the write
+    pub fn needs_als_idle(self) -> bool {
+        matches!(self, Self::AlsIdle | Self::Idle)
+    }
+}
+
+/// Datasheet constraints on writing a register
+///
+/// Checked before every register write when strict mode is enabled, see
+/// [`Device::set_strict`](crate::Device::set_strict), or the busy check, see
+/// [`Device::set_busy_check`](crate::Device::set_busy_check). Registers whose
+/// fields accept every value they can encode keep the default
+/// [`check_limits`](Self::check_limits), which accepts everything.
+///
+/// [`Device::write_register`](crate::Device::write_register) requires it, so
+/// register types defined outside this crate implement it to be written,
+/// usually with [`BusyPolicy::Anytime`] and no limits.
+pub trait DatasheetLimits: WritableRegister<IdType = u16> {
+    /// Measurements that must be idle while the register is written
+    const BUSY_POLICY: BusyPolicy;
+
+    /// Checks every field against the range the datasheet allows for it.
+    ///
+    /// # Errors
+    /// The first field found outside its range.
+    fn check_limits(&self) -> Result<(), LimitViolation> {
+        Ok(())
+    }
+}
 
 mod als;
+mod block;
+mod defaults;
+mod duration;
+mod firmware;
 mod identification;
+mod layout;
 mod range;
+mod raw;
 mod result;
+mod slice;
 mod system;
 
 pub use als::*;
+pub use block::*;
+pub use defaults::{PowerOnDefault, POWER_ON_DEFAULTS};
+pub use firmware::*;
 pub use identification::*;
+pub use layout::{layout, RegisterLayout};
 pub use range::*;
+pub use raw::RawRegister;
 pub use result::*;
 pub use system::*;
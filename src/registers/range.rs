@@ -1,4 +1,4 @@
-//! Range Configuration Registers (0x018 - 0x031)
+//! Range Configuration Registers (0x018 - 0x031, 0x10A)
 //!
 //! These registers configure the ranging sensor including measurement timing,
 //! crosstalk compensation, and convergence settings.
@@ -7,13 +7,51 @@ use core::{convert::Infallible, time::Duration};
 use measurements::Length;
 use regiface::{register, FromByteArray, ReadableRegister, ToByteArray, WritableRegister};
 
-use crate::types::RegisterError;
+use super::duration::{DurationField, INTERMEASUREMENT};
+use super::{BusyPolicy, DatasheetLimits};
+use crate::types::{FieldLimit, LimitViolation, RegisterError};
+
+// Field limits from the register descriptions of datasheet section 6.2
+static THRESH_HIGH: FieldLimit = mm("sysrange__thresh_high");
+static THRESH_LOW: FieldLimit = mm("sysrange__thresh_low");
+static INTERMEASUREMENT_PERIOD: FieldLimit =
+    FieldLimit::millis("sysrange__intermeasurement_period", 10, 2550, 10);
+static MAX_CONVERGENCE_TIME: FieldLimit =
+    FieldLimit::millis("sysrange__max_convergence_time", 1, 63, 1);
+static CROSSTALK_VALID_HEIGHT: FieldLimit = mm("sysrange__crosstalk_valid_height");
+static RANGE_IGNORE_VALID_HEIGHT: FieldLimit = mm("sysrange__range_ignore_valid_height");
+static PART_TO_PART_RANGE_OFFSET: FieldLimit = FieldLimit {
+    field: "sysrange__part_to_part_range_offset",
+    min: -128,
+    max: 127,
+    step: 1,
+    unit: "mm",
+};
+
+/// SYSRANGE__MAX_CONVERGENCE_TIME: 1ms steps, code 0 = 0ms
+const CONVERGENCE_TIME: DurationField = DurationField::millis(1, 0, 1, 63);
+
+/// Limit of an 8-bit millimeter field
+const fn mm(field: &'static str) -> FieldLimit {
+    FieldLimit {
+        field,
+        min: 0,
+        max: 255,
+        step: 1,
+        unit: "mm",
+    }
+}
+
+/// A length in whole millimeters, as it is encoded
+fn whole_mm(length: Length) -> i32 {
+    length.as_millimeters() as i32
+}
 
 /// Range Start Register (0x018)
 ///
 /// Writing to this register starts a range measurement.
 #[register(0x0018u16)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, ReadableRegister, WritableRegister)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ReadableRegister, WritableRegister)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum RangeStart {
     /// Single-shot ranging mode (0x01)
@@ -48,13 +86,17 @@ impl ToByteArray for RangeStart {
     }
 }
 
-/// Range Thresholds Register (0x019-0x01C)
+impl DatasheetLimits for RangeStart {
+    const BUSY_POLICY: BusyPolicy = BusyPolicy::Anytime;
+}
+
+/// Range Thresholds Register (0x019-0x01A)
 ///
-/// Combined high and low thresholds for range interrupt generation.
-/// Reads 4 bytes: threshold_high_hi, threshold_high_lo, threshold_low_hi, threshold_low_lo
+/// Combined high and low thresholds for range interrupt generation: one byte
+/// each for SYSRANGE__THRESH_HIGH and SYSRANGE__THRESH_LOW, in millimeters.
+/// Thresholds beyond [`MAX_MM`](Self::MAX_MM) are written as `MAX_MM`.
 #[register(0x0019u16)]
 #[derive(Debug, Clone, Copy, ReadableRegister, WritableRegister)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct RangeThresholds {
     /// High threshold
     pub high: Length,
@@ -62,33 +104,98 @@ pub struct RangeThresholds {
     pub low: Length,
 }
 
+#[cfg(feature = "defmt")]
+impl defmt::Format for RangeThresholds {
+    fn format(&self, f: defmt::Formatter) {
+        use crate::types::Millimeters;
+
+        defmt::write!(
+            f,
+            "RangeThresholds {{ high: {}, low: {} }}",
+            Millimeters(self.high),
+            Millimeters(self.low)
+        );
+    }
+}
+
+wire_eq!(RangeThresholds, |r| (r.raw_high_mm(), r.raw_low_mm()));
+
+impl RangeThresholds {
+    /// Largest threshold the SYSRANGE__THRESH registers can hold, in millimeters
+    pub const MAX_MM: u8 = u8::MAX;
+
+    /// Thresholds for an interrupt when the distance is below `limit`.
+    ///
+    /// Pair with [`InterruptMode::LevelLow`](crate::types::InterruptMode::LevelLow).
+    /// The unused high threshold is set to [`MAX_MM`](Self::MAX_MM).
+    pub fn below(limit: Length) -> Self {
+        Self {
+            high: Length::from_millimeters(Self::MAX_MM as f64),
+            low: limit,
+        }
+    }
+
+    /// Thresholds for an interrupt when the distance is above `limit`.
+    ///
+    /// Pair with [`InterruptMode::LevelHigh`](crate::types::InterruptMode::LevelHigh).
+    /// The unused low threshold is set to zero.
+    pub fn above(limit: Length) -> Self {
+        Self {
+            high: limit,
+            low: Length::from_millimeters(0.0),
+        }
+    }
+
+    /// Thresholds for an interrupt when the distance is outside `low..=high`.
+    ///
+    /// Pair with [`InterruptMode::OutOfWindow`](crate::types::InterruptMode::OutOfWindow).
+    /// Returns `None` if `low` is above `high`.
+    pub fn window(low: Length, high: Length) -> Option<Self> {
+        (low.as_millimeters() <= high.as_millimeters()).then_some(Self { high, low })
+    }
+
+    /// The high threshold as encoded in the register, in millimeters.
+    ///
+    /// Exactly the value read from the device for a decoded register.
+    pub fn raw_high_mm(&self) -> u8 {
+        self.high.as_millimeters() as u8
+    }
+
+    /// The low threshold as encoded in the register, in millimeters.
+    ///
+    /// Exactly the value read from the device for a decoded register.
+    pub fn raw_low_mm(&self) -> u8 {
+        self.low.as_millimeters() as u8
+    }
+}
+
 impl FromByteArray for RangeThresholds {
     type Error = Infallible;
-    type Array = [u8; 4];
+    type Array = [u8; 2];
 
     fn from_bytes(bytes: Self::Array) -> Result<Self, Self::Error> {
-        let high_mm = u16::from_be_bytes([bytes[0], bytes[1]]);
-        let low_mm = u16::from_be_bytes([bytes[2], bytes[3]]);
-
         Ok(Self {
-            high: Length::from_millimeters(high_mm as f64),
-            low: Length::from_millimeters(low_mm as f64),
+            high: Length::from_millimeters(bytes[0] as f64),
+            low: Length::from_millimeters(bytes[1] as f64),
         })
     }
 }
 
 impl ToByteArray for RangeThresholds {
     type Error = Infallible;
-    type Array = [u8; 4];
+    type Array = [u8; 2];
 
     fn to_bytes(self) -> Result<Self::Array, Self::Error> {
-        let high_mm = self.high.as_millimeters() as u16;
-        let low_mm = self.low.as_millimeters() as u16;
+        Ok([self.raw_high_mm(), self.raw_low_mm()])
+    }
+}
+
+impl DatasheetLimits for RangeThresholds {
+    const BUSY_POLICY: BusyPolicy = BusyPolicy::RangeIdle;
 
-        let mut result = [0u8; 4];
-        result[0..2].copy_from_slice(&high_mm.to_be_bytes());
-        result[2..4].copy_from_slice(&low_mm.to_be_bytes());
-        Ok(result)
+    fn check_limits(&self) -> Result<(), LimitViolation> {
+        THRESH_HIGH.check(whole_mm(self.high))?;
+        THRESH_LOW.check(whole_mm(self.low))
     }
 }
 
@@ -96,21 +203,55 @@ impl ToByteArray for RangeThresholds {
 ///
 /// Time delay between measurements in continuous mode.
 #[register(0x001Bu16)]
-#[derive(Debug, Clone, Copy, ReadableRegister, WritableRegister)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, ReadableRegister, WritableRegister,
+)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct RangeIntermeasurementPeriod {
     /// Period between measurements
     pub period: Duration,
 }
 
+impl RangeIntermeasurementPeriod {
+    /// Period of `ms` milliseconds, or `None` outside the datasheet limits
+    ///
+    /// Accepts 10ms to 2550ms in steps of 10ms.
+    pub const fn checked_ms(ms: u16) -> Option<Self> {
+        if INTERMEASUREMENT_PERIOD.accepts(ms as i32 * 1_000) {
+            Some(Self {
+                period: Duration::from_millis(ms as u64),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Period of `MS` milliseconds, checked at compile time
+    ///
+    /// A value [`checked_ms`](Self::checked_ms) rejects fails the build.
+    ///
+    /// ```
+    /// use vl6180x::registers::RangeIntermeasurementPeriod;
+    ///
+    /// const VALUE: RangeIntermeasurementPeriod = RangeIntermeasurementPeriod::from_ms::<100>();
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use vl6180x::registers::RangeIntermeasurementPeriod;
+    ///
+    /// const VALUE: RangeIntermeasurementPeriod = RangeIntermeasurementPeriod::from_ms::<105>();
+    /// ```
+    pub const fn from_ms<const MS: u16>() -> Self {
+        const { Self::checked_ms(MS).expect("outside the datasheet limits") }
+    }
+}
+
 impl FromByteArray for RangeIntermeasurementPeriod {
     type Error = Infallible;
     type Array = [u8; 1];
 
     fn from_bytes(bytes: Self::Array) -> Result<Self, Self::Error> {
-        // Period is in units of 10ms: value 0 = 10ms, value 1 = 20ms, etc.
-        let milliseconds = (bytes[0] as u64 + 1) * 10;
-        let period = Duration::from_millis(milliseconds);
+        let period = INTERMEASUREMENT.decode(bytes[0].into());
         Ok(Self { period })
     }
 }
@@ -120,19 +261,16 @@ impl ToByteArray for RangeIntermeasurementPeriod {
     type Array = [u8; 1];
 
     fn to_bytes(self) -> Result<Self::Array, Self::Error> {
-        // Convert to units of 10ms
         // Valid range: 10ms to 2560ms (value 0-255 in register)
-        let total_ms = self.period.as_millis() as u64;
+        Ok([INTERMEASUREMENT.encode(self.period)? as u8])
+    }
+}
 
-        if total_ms < 10 {
-            return Err(RegisterError::DurationTooShort);
-        }
-        if total_ms > 2560 {
-            return Err(RegisterError::DurationTooLong);
-        }
+impl DatasheetLimits for RangeIntermeasurementPeriod {
+    const BUSY_POLICY: BusyPolicy = BusyPolicy::RangeIdle;
 
-        let value = (((total_ms + 5) / 10) - 1) as u8;
-        Ok([value])
+    fn check_limits(&self) -> Result<(), LimitViolation> {
+        INTERMEASUREMENT_PERIOD.check_duration(self.period)
     }
 }
 
@@ -140,19 +278,75 @@ impl ToByteArray for RangeIntermeasurementPeriod {
 ///
 /// Maximum time to run measurement in ranging modes (up to 63ms).
 #[register(0x001Cu16)]
-#[derive(Debug, Clone, Copy, ReadableRegister, WritableRegister)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, ReadableRegister, WritableRegister,
+)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct RangeMaxConvergenceTime {
     /// Maximum convergence time
     pub time: Duration,
 }
 
+impl RangeMaxConvergenceTime {
+    /// Fixed pre-calibration phase run before every range measurement
+    const PRE_CALIBRATION: Duration = Duration::from_micros(3_200);
+
+    /// Convergence limit of `ms` milliseconds, or `None` outside the
+    /// datasheet limits
+    ///
+    /// Accepts 1ms to 63ms.
+    pub const fn checked_ms(ms: u16) -> Option<Self> {
+        if MAX_CONVERGENCE_TIME.accepts(ms as i32 * 1_000) {
+            Some(Self {
+                time: Duration::from_millis(ms as u64),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Convergence limit of `MS` milliseconds, checked at compile time
+    ///
+    /// A value [`checked_ms`](Self::checked_ms) rejects fails the build.
+    ///
+    /// ```
+    /// use vl6180x::registers::RangeMaxConvergenceTime;
+    ///
+    /// const LIMIT: RangeMaxConvergenceTime = RangeMaxConvergenceTime::from_ms::<49>();
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use vl6180x::registers::RangeMaxConvergenceTime;
+    ///
+    /// const LIMIT: RangeMaxConvergenceTime = RangeMaxConvergenceTime::from_ms::<64>();
+    /// ```
+    pub const fn from_ms<const MS: u16>() -> Self {
+        const { Self::checked_ms(MS).expect("outside the datasheet limits") }
+    }
+
+    /// Upper bound on the time one range measurement takes with this limit
+    ///
+    /// Sums the fixed pre-calibration phase, the maximum convergence time and
+    /// the readout averaging period of the recommended 48 samples.
+    pub const fn measurement_time(&self) -> Duration {
+        self.measurement_time_with(ReadoutAveraging::RECOMMENDED)
+    }
+
+    /// Upper bound on the time one range measurement takes with this limit
+    /// and `averaging`
+    pub const fn measurement_time_with(&self, averaging: ReadoutAveraging) -> Duration {
+        Self::PRE_CALIBRATION
+            .saturating_add(self.time)
+            .saturating_add(averaging.readout_time())
+    }
+}
+
 impl FromByteArray for RangeMaxConvergenceTime {
     type Error = Infallible;
     type Array = [u8; 1];
 
     fn from_bytes(bytes: Self::Array) -> Result<Self, Self::Error> {
-        let time = Duration::from_millis(bytes[0] as u64);
+        let time = CONVERGENCE_TIME.decode((bytes[0] & 0x3F).into());
         Ok(Self { time })
     }
 }
@@ -163,16 +357,15 @@ impl ToByteArray for RangeMaxConvergenceTime {
 
     fn to_bytes(self) -> Result<Self::Array, Self::Error> {
         // Valid range: 1ms to 63ms
-        let ms = self.time.as_millis() as u64;
+        Ok([CONVERGENCE_TIME.encode(self.time)? as u8])
+    }
+}
 
-        if ms < 1 {
-            return Err(RegisterError::DurationTooShort);
-        }
-        if ms > 63 {
-            return Err(RegisterError::DurationTooLong);
-        }
+impl DatasheetLimits for RangeMaxConvergenceTime {
+    const BUSY_POLICY: BusyPolicy = BusyPolicy::RangeIdle;
 
-        Ok([ms as u8])
+    fn check_limits(&self) -> Result<(), LimitViolation> {
+        MAX_CONVERGENCE_TIME.check_duration(self.time)
     }
 }
 
@@ -180,7 +373,9 @@ impl ToByteArray for RangeMaxConvergenceTime {
 ///
 /// Crosstalk compensation value (9.7 fixed point format).
 #[register(0x001Eu16)]
-#[derive(Debug, Clone, Copy, ReadableRegister, WritableRegister)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, ReadableRegister, WritableRegister,
+)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct RangeCrosstalkCompensationRate {
     /// Crosstalk compensation rate (9.7 fixed point)
@@ -206,17 +401,44 @@ impl ToByteArray for RangeCrosstalkCompensationRate {
     }
 }
 
+impl DatasheetLimits for RangeCrosstalkCompensationRate {
+    const BUSY_POLICY: BusyPolicy = BusyPolicy::RangeIdle;
+}
+
 /// Range Crosstalk Valid Height Register (0x021)
 ///
 /// Minimum range value to use for crosstalk compensation.
 #[register(0x0021u16)]
 #[derive(Debug, Clone, Copy, ReadableRegister, WritableRegister)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct RangeCrosstalkValidHeight {
     /// Minimum valid height
     pub height: Length,
 }
 
+#[cfg(feature = "defmt")]
+impl defmt::Format for RangeCrosstalkValidHeight {
+    fn format(&self, f: defmt::Formatter) {
+        use crate::types::Millimeters;
+
+        defmt::write!(
+            f,
+            "RangeCrosstalkValidHeight {{ height: {} }}",
+            Millimeters(self.height)
+        );
+    }
+}
+
+wire_eq!(RangeCrosstalkValidHeight, |r| r.raw_mm());
+
+impl RangeCrosstalkValidHeight {
+    /// The height as the register byte, in millimeters.
+    ///
+    /// Exactly the byte read from the device for a decoded register.
+    pub fn raw_mm(&self) -> u8 {
+        self.height.as_millimeters() as u8
+    }
+}
+
 impl FromByteArray for RangeCrosstalkValidHeight {
     type Error = Infallible;
     type Array = [u8; 1];
@@ -233,8 +455,15 @@ impl ToByteArray for RangeCrosstalkValidHeight {
     type Array = [u8; 1];
 
     fn to_bytes(self) -> Result<Self::Array, Self::Error> {
-        let mm = self.height.as_millimeters() as u8;
-        Ok([mm])
+        Ok([self.raw_mm()])
+    }
+}
+
+impl DatasheetLimits for RangeCrosstalkValidHeight {
+    const BUSY_POLICY: BusyPolicy = BusyPolicy::RangeIdle;
+
+    fn check_limits(&self) -> Result<(), LimitViolation> {
+        CROSSTALK_VALID_HEIGHT.check(whole_mm(self.height))
     }
 }
 
@@ -242,7 +471,9 @@ impl ToByteArray for RangeCrosstalkValidHeight {
 ///
 /// Early convergence estimate threshold (9.7 fixed point format).
 #[register(0x0022u16)]
-#[derive(Debug, Clone, Copy, ReadableRegister, WritableRegister)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, ReadableRegister, WritableRegister,
+)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct RangeEarlyConvergenceEstimate {
     /// Early convergence estimate (9.7 fixed point)
@@ -268,16 +499,186 @@ impl ToByteArray for RangeEarlyConvergenceEstimate {
     }
 }
 
+impl DatasheetLimits for RangeEarlyConvergenceEstimate {
+    const BUSY_POLICY: BusyPolicy = BusyPolicy::RangeIdle;
+}
+
+/// Range Part-to-Part Offset Register (0x024)
+///
+/// Offset added to every range value, in two's complement millimeters.
+/// Loaded from the module's NVM at boot with the factory calibration.
+#[register(0x0024u16)]
+#[derive(Debug, Clone, Copy, ReadableRegister, WritableRegister)]
+pub struct RangePartToPartOffset {
+    /// Range offset
+    pub offset: Length,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for RangePartToPartOffset {
+    fn format(&self, f: defmt::Formatter) {
+        use crate::types::Millimeters;
+
+        defmt::write!(
+            f,
+            "RangePartToPartOffset {{ offset: {} }}",
+            Millimeters(self.offset)
+        );
+    }
+}
+
+wire_eq!(RangePartToPartOffset, |r| r.raw_mm());
+
+impl RangePartToPartOffset {
+    /// The offset as the register byte, in millimeters.
+    ///
+    /// Exactly the byte read from the device for a decoded register.
+    pub fn raw_mm(&self) -> i8 {
+        self.offset.as_millimeters() as i8
+    }
+}
+
+impl FromByteArray for RangePartToPartOffset {
+    type Error = Infallible;
+    type Array = [u8; 1];
+
+    fn from_bytes(bytes: Self::Array) -> Result<Self, Self::Error> {
+        Ok(Self {
+            offset: Length::from_millimeters(bytes[0] as i8 as f64),
+        })
+    }
+}
+
+impl ToByteArray for RangePartToPartOffset {
+    type Error = Infallible;
+    type Array = [u8; 1];
+
+    fn to_bytes(self) -> Result<Self::Array, Self::Error> {
+        Ok([self.raw_mm() as u8])
+    }
+}
+
+impl DatasheetLimits for RangePartToPartOffset {
+    const BUSY_POLICY: BusyPolicy = BusyPolicy::RangeIdle;
+
+    fn check_limits(&self) -> Result<(), LimitViolation> {
+        PART_TO_PART_RANGE_OFFSET.check(whole_mm(self.offset))
+    }
+}
+
+/// Range Ignore Valid Height Register (0x025)
+///
+/// Range below which the range ignore threshold is applied, to ignore the
+/// cover glass. The datasheet recommends 255mm when range ignore is used.
+#[register(0x0025u16)]
+#[derive(Debug, Clone, Copy, ReadableRegister, WritableRegister)]
+pub struct RangeIgnoreValidHeight {
+    /// Height below which returns are ignored
+    pub height: Length,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for RangeIgnoreValidHeight {
+    fn format(&self, f: defmt::Formatter) {
+        use crate::types::Millimeters;
+
+        defmt::write!(
+            f,
+            "RangeIgnoreValidHeight {{ height: {} }}",
+            Millimeters(self.height)
+        );
+    }
+}
+
+wire_eq!(RangeIgnoreValidHeight, |r| r.raw_mm());
+
+impl RangeIgnoreValidHeight {
+    /// The height as the register byte, in millimeters.
+    ///
+    /// Exactly the byte read from the device for a decoded register.
+    pub fn raw_mm(&self) -> u8 {
+        self.height.as_millimeters() as u8
+    }
+}
+
+impl FromByteArray for RangeIgnoreValidHeight {
+    type Error = Infallible;
+    type Array = [u8; 1];
+
+    fn from_bytes(bytes: Self::Array) -> Result<Self, Self::Error> {
+        Ok(Self {
+            height: Length::from_millimeters(bytes[0] as f64),
+        })
+    }
+}
+
+impl ToByteArray for RangeIgnoreValidHeight {
+    type Error = Infallible;
+    type Array = [u8; 1];
+
+    fn to_bytes(self) -> Result<Self::Array, Self::Error> {
+        Ok([self.raw_mm()])
+    }
+}
+
+impl DatasheetLimits for RangeIgnoreValidHeight {
+    const BUSY_POLICY: BusyPolicy = BusyPolicy::RangeIdle;
+
+    fn check_limits(&self) -> Result<(), LimitViolation> {
+        RANGE_IGNORE_VALID_HEIGHT.check(whole_mm(self.height))
+    }
+}
+
+/// Range Ignore Threshold Register (0x026-0x027)
+///
+/// Minimum return signal rate below which a range is ignored (9.7 fixed point
+/// format, Mcps). Must be initialized when range ignore is enabled in
+/// [`RangeCheckEnables`].
+#[register(0x0026u16)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, ReadableRegister, WritableRegister,
+)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RangeIgnoreThreshold {
+    /// Return signal rate threshold (9.7 fixed point)
+    pub rate: u16,
+}
+
+impl FromByteArray for RangeIgnoreThreshold {
+    type Error = Infallible;
+    type Array = [u8; 2];
+
+    fn from_bytes(bytes: Self::Array) -> Result<Self, Self::Error> {
+        Ok(Self {
+            rate: u16::from_be_bytes(bytes),
+        })
+    }
+}
+
+impl ToByteArray for RangeIgnoreThreshold {
+    type Error = Infallible;
+    type Array = [u8; 2];
+
+    fn to_bytes(self) -> Result<Self::Array, Self::Error> {
+        Ok(self.rate.to_be_bytes())
+    }
+}
+
+impl DatasheetLimits for RangeIgnoreThreshold {
+    const BUSY_POLICY: BusyPolicy = BusyPolicy::RangeIdle;
+}
+
 /// Range Check Enables Register (0x02D)
 ///
-/// Enable/disable various range check features.
+/// Enable/disable various range check features: early convergence estimate
+/// (bit 0), range ignore (bit 1) and signal to noise (bit 4).
 #[register(0x002Du16)]
-#[derive(Debug, Clone, Copy, ReadableRegister, WritableRegister)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ReadableRegister, WritableRegister)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct RangeCheckEnables {
     /// Enable range check for signal to noise ratio
     pub enable_snr_check: bool,
-    /// Enable range check for range value
+    /// Enable range ignore check for range value
     pub enable_range_check: bool,
     /// Enable early convergence estimate check
     pub enable_early_convergence_check: bool,
@@ -289,9 +690,9 @@ impl FromByteArray for RangeCheckEnables {
 
     fn from_bytes(bytes: Self::Array) -> Result<Self, Self::Error> {
         Ok(Self {
-            enable_snr_check: bytes[0] & 0x01 != 0,
+            enable_snr_check: bytes[0] & 0x10 != 0,
             enable_range_check: bytes[0] & 0x02 != 0,
-            enable_early_convergence_check: bytes[0] & 0x04 != 0,
+            enable_early_convergence_check: bytes[0] & 0x01 != 0,
         })
     }
 }
@@ -303,23 +704,27 @@ impl ToByteArray for RangeCheckEnables {
     fn to_bytes(self) -> Result<Self::Array, Self::Error> {
         let mut value = 0u8;
         if self.enable_snr_check {
-            value |= 0x01;
+            value |= 0x10;
         }
         if self.enable_range_check {
             value |= 0x02;
         }
         if self.enable_early_convergence_check {
-            value |= 0x04;
+            value |= 0x01;
         }
         Ok([value])
     }
 }
 
+impl DatasheetLimits for RangeCheckEnables {
+    const BUSY_POLICY: BusyPolicy = BusyPolicy::RangeIdle;
+}
+
 /// Range VHV Recalibrate Register (0x02E)
 ///
 /// Controls VHV (Vertical Horizontal Vertical) recalibration.
 #[register(0x002Eu16)]
-#[derive(Debug, Clone, Copy, ReadableRegister, WritableRegister)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ReadableRegister, WritableRegister)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct RangeVhvRecalibrate {
     /// VHV recalibrate value
@@ -346,17 +751,30 @@ impl ToByteArray for RangeVhvRecalibrate {
     }
 }
 
+impl DatasheetLimits for RangeVhvRecalibrate {
+    const BUSY_POLICY: BusyPolicy = BusyPolicy::RangeIdle;
+}
+
 /// Range VHV Repeat Rate Register (0x031)
 ///
 /// Rate at which VHV recalibration is performed.
 #[register(0x0031u16)]
-#[derive(Debug, Clone, Copy, ReadableRegister, WritableRegister)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, ReadableRegister, WritableRegister,
+)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct RangeVhvRepeatRate {
     /// VHV repeat rate value
     pub rate: u8,
 }
 
+impl RangeVhvRepeatRate {
+    /// Repeat rate register value; every value is valid
+    pub const fn new(rate: u8) -> Self {
+        Self { rate }
+    }
+}
+
 impl FromByteArray for RangeVhvRepeatRate {
     type Error = Infallible;
     type Array = [u8; 1];
@@ -374,3 +792,134 @@ impl ToByteArray for RangeVhvRepeatRate {
         Ok([self.rate])
     }
 }
+
+impl DatasheetLimits for RangeVhvRepeatRate {
+    const BUSY_POLICY: BusyPolicy = BusyPolicy::RangeIdle;
+}
+
+/// Readout Averaging Sample Period Register (0x10A)
+///
+/// Number of readout averaging samples taken at the end of each range
+/// measurement. Each sample adds around 64.5µs to the measurement and reduces
+/// noise; the time is taken from the maximum convergence time.
+#[register(0x010Au16)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, ReadableRegister, WritableRegister,
+)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ReadoutAveraging {
+    /// Averaging sample period (0-255)
+    pub samples: u8,
+}
+
+impl ReadoutAveraging {
+    /// The datasheet's recommended setting of 48 samples (around 4.3ms)
+    pub const RECOMMENDED: Self = Self { samples: 48 };
+
+    /// Fixed part of the readout averaging period
+    const FIXED: Duration = Duration::from_micros(1_300);
+
+    /// Readout averaging period added by every sample
+    const PER_SAMPLE: Duration = Duration::from_nanos(64_500);
+
+    /// Averaging with `samples` samples; every count is valid
+    pub const fn new(samples: u8) -> Self {
+        Self { samples }
+    }
+
+    /// The most samples whose readout fits into `budget`
+    ///
+    /// The readout takes at least 1.3ms whatever the setting, so shorter
+    /// budgets yield no samples.
+    pub const fn for_time_budget(budget: Duration) -> Self {
+        let Some(averaging) = budget.checked_sub(Self::FIXED) else {
+            return Self::new(0);
+        };
+        let samples = averaging.as_nanos() / Self::PER_SAMPLE.as_nanos();
+        Self::new(if samples > u8::MAX as u128 {
+            u8::MAX
+        } else {
+            samples as u8
+        })
+    }
+
+    /// The fewest samples keeping the range noise within `target`
+    ///
+    /// Targets below the noise of 255 samples, about 44%, yield 255 samples.
+    pub const fn for_noise_target(target: NoiseTarget) -> Self {
+        let squared = target.percent as u64 * target.percent as u64;
+        if squared == 0 {
+            return Self::new(u8::MAX);
+        }
+        let samples = NoiseTarget::RECOMMENDED_VARIANCE.div_ceil(squared);
+        Self::new(if samples > u8::MAX as u64 {
+            u8::MAX
+        } else {
+            samples as u8
+        })
+    }
+
+    /// Readout averaging period this setting adds to every range measurement
+    ///
+    /// The datasheet gives it as 1.3ms + samples × 64.5µs.
+    pub const fn readout_time(&self) -> Duration {
+        Self::FIXED.saturating_add(Duration::from_nanos(
+            self.samples as u64 * Self::PER_SAMPLE.as_nanos() as u64,
+        ))
+    }
+
+    /// Range noise with this setting, rounded up
+    ///
+    /// No samples are as noisy as a single one.
+    pub const fn noise(&self) -> NoiseTarget {
+        let samples = if self.samples == 0 { 1 } else { self.samples } as u64;
+        let mut percent = (NoiseTarget::RECOMMENDED_VARIANCE / samples).isqrt();
+        while percent * percent * samples < NoiseTarget::RECOMMENDED_VARIANCE {
+            percent += 1;
+        }
+        NoiseTarget {
+            percent: percent as u16,
+        }
+    }
+}
+
+/// Range noise relative to the recommended readout averaging
+///
+/// Averaging `n` samples divides the standard deviation of uncorrelated
+/// readout noise by √n. The target is that standard deviation in percent of
+/// the one with [`ReadoutAveraging::RECOMMENDED`]: at 200% a quarter of the
+/// samples suffice, at 50% four times as many are needed. Noise sources the
+/// averaging does not reach, such as ambient light, come on top.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NoiseTarget {
+    /// Standard deviation in percent of the recommended setting's
+    pub percent: u16,
+}
+
+impl NoiseTarget {
+    /// Samples of the recommended setting times 100% squared
+    const RECOMMENDED_VARIANCE: u64 = 48 * 100 * 100;
+}
+
+impl FromByteArray for ReadoutAveraging {
+    type Error = Infallible;
+    type Array = [u8; 1];
+
+    fn from_bytes(bytes: Self::Array) -> Result<Self, Self::Error> {
+        Ok(Self { samples: bytes[0] })
+    }
+}
+
+impl ToByteArray for ReadoutAveraging {
+    type Error = Infallible;
+    type Array = [u8; 1];
+
+    fn to_bytes(self) -> Result<Self::Array, Self::Error> {
+        Ok([self.samples])
+    }
+}
+
+impl DatasheetLimits for ReadoutAveraging {
+    const BUSY_POLICY: BusyPolicy = BusyPolicy::RangeIdle;
+}
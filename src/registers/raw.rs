@@ -0,0 +1,106 @@
+//! Catch-all register type
+//!
+//! [`RawRegister`] gives any address the typed register path without a
+//! dedicated register struct.
+
+use core::convert::Infallible;
+
+use regiface::{FromByteArray, ReadableRegister, Register, ToByteArray, WritableRegister};
+
+use super::{BusyPolicy, DatasheetLimits};
+
+/// `N` raw bytes starting at register address `ADDR`
+///
+/// Reads and writes the bytes unchanged through the typed transaction path,
+/// for undocumented registers or registers this crate has no type for:
+/// [`read_register`](crate::Device::read_register),
+/// [`write_register`](crate::Device::write_register), their async versions
+/// and every other helper generic over a register type accept it.
+///
+/// The address is part of the type, so it is fixed at compile time. For an
+/// address only known at runtime use
+/// [`read_raw_into`](crate::Device::read_raw_into),
+/// [`read_block`](crate::Device::read_block) and
+/// [`write_block`](crate::Device::write_block) instead. Unlike those, typed
+/// accesses keep the configuration cache up to date, are verified in
+/// paranoid mode and report errors as register accesses.
+///
+/// Nothing is known about the bytes, so writes pass strict mode and the busy
+/// check unconditionally.
+///
+/// # Example
+/// ```no_run
+/// use embedded_hal::i2c::I2c;
+/// use vl6180x::registers::RawRegister;
+/// use vl6180x::{Device, Error};
+///
+/// fn vcsel_pulse_width<I2C: I2c>(sensor: &mut Device<I2C>) -> Result<u8, Error> {
+///     let RawRegister([width]) = sensor.read_register::<RawRegister<0x0097, 1>>()?;
+///     Ok(width)
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RawRegister<const ADDR: u16, const N: usize>(pub [u8; N]);
+
+impl<const ADDR: u16, const N: usize> RawRegister<ADDR, N> {
+    /// Register address of the first byte
+    pub const ADDRESS: u16 = ADDR;
+
+    /// Returns the bytes
+    pub const fn bytes(&self) -> [u8; N] {
+        self.0
+    }
+}
+
+impl<const ADDR: u16, const N: usize> Default for RawRegister<ADDR, N> {
+    fn default() -> Self {
+        Self([0; N])
+    }
+}
+
+impl<const ADDR: u16, const N: usize> From<[u8; N]> for RawRegister<ADDR, N> {
+    fn from(bytes: [u8; N]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl<const ADDR: u16, const N: usize> From<RawRegister<ADDR, N>> for [u8; N] {
+    fn from(register: RawRegister<ADDR, N>) -> Self {
+        register.0
+    }
+}
+
+impl<const ADDR: u16, const N: usize> Register for RawRegister<ADDR, N> {
+    type IdType = u16;
+
+    fn id() -> u16 {
+        ADDR
+    }
+}
+
+impl<const ADDR: u16, const N: usize> FromByteArray for RawRegister<ADDR, N> {
+    type Error = Infallible;
+    type Array = [u8; N];
+
+    fn from_bytes(bytes: Self::Array) -> Result<Self, Self::Error> {
+        Ok(Self(bytes))
+    }
+}
+
+impl<const ADDR: u16, const N: usize> ToByteArray for RawRegister<ADDR, N> {
+    type Error = Infallible;
+    type Array = [u8; N];
+
+    fn to_bytes(self) -> Result<Self::Array, Self::Error> {
+        Ok(self.0)
+    }
+}
+
+impl<const ADDR: u16, const N: usize> ReadableRegister for RawRegister<ADDR, N> {}
+
+impl<const ADDR: u16, const N: usize> WritableRegister for RawRegister<ADDR, N> {}
+
+impl<const ADDR: u16, const N: usize> DatasheetLimits for RawRegister<ADDR, N> {
+    const BUSY_POLICY: BusyPolicy = BusyPolicy::Anytime;
+}
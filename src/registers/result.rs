@@ -1,4 +1,4 @@
-//! Result Registers (0x04D - 0x066)
+//! Result Registers (0x04D - 0x080)
 //!
 //! These registers contain measurement results from both the ranging
 //! and ambient light sensors.
@@ -9,17 +9,53 @@ use regiface::{register, FromByteArray, ReadableRegister};
 
 use crate::types::{AlsErrorCode, RangeErrorCode, RegisterError};
 
+/// Marker for the read-only measurement result registers
+///
+/// Used to restrict [`ResultReader`](crate::device::ResultReader) to result
+/// access.
+pub trait ResultRegister: ReadableRegister<IdType = u16> {}
+
+impl ResultRegister for RangeResultValue {}
+impl ResultRegister for RangeResultStatus {}
+impl ResultRegister for ResultInterruptStatusGpio {}
+impl ResultRegister for AlsResultValue {}
+impl ResultRegister for ResultAlsStatus {}
+impl ResultRegister for RangeResultConvergenceTime {}
+
 /// Range Result Value Register (0x062)
 ///
 /// Range measurement result.
 #[register(0x0062u16)]
 #[derive(Debug, Clone, Copy, ReadableRegister)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct RangeResultValue {
     /// Measured distance
     pub distance: Length,
 }
 
+#[cfg(feature = "defmt")]
+impl defmt::Format for RangeResultValue {
+    fn format(&self, f: defmt::Formatter) {
+        use crate::types::Millimeters;
+
+        defmt::write!(
+            f,
+            "RangeResultValue {{ distance: {} }}",
+            Millimeters(self.distance)
+        );
+    }
+}
+
+wire_eq!(RangeResultValue, |r| r.raw_mm());
+
+impl RangeResultValue {
+    /// The distance as the register byte, in millimeters.
+    ///
+    /// Exactly the byte read from the device for a decoded value.
+    pub fn raw_mm(&self) -> u8 {
+        self.distance.as_millimeters() as u8
+    }
+}
+
 impl FromByteArray for RangeResultValue {
     type Error = core::convert::Infallible;
     type Array = [u8; 1];
@@ -35,7 +71,7 @@ impl FromByteArray for RangeResultValue {
 ///
 /// Contains range error code and device ready status.
 #[register(0x004Du16)]
-#[derive(Debug, Clone, Copy, ReadableRegister)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ReadableRegister)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct RangeResultStatus {
     /// Range error code
@@ -62,8 +98,10 @@ impl FromByteArray for RangeResultStatus {
 /// Result Interrupt Status GPIO Register (0x04F)
 ///
 /// Interrupt status bits for range, ALS, and error interrupts.
+/// Bits `[2:0]` hold the range event code, bits `[5:3]` the ALS event code
+/// and bits `[7:6]` the error code; a non-zero code means the interrupt is pending.
 #[register(0x004Fu16)]
-#[derive(Debug, Clone, Copy, ReadableRegister)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ReadableRegister)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct ResultInterruptStatusGpio {
     /// Range interrupt status
@@ -80,9 +118,9 @@ impl FromByteArray for ResultInterruptStatusGpio {
 
     fn from_bytes(bytes: Self::Array) -> Result<Self, Self::Error> {
         Ok(Self {
-            range_interrupt: bytes[0] & 0x04 != 0,
-            als_interrupt: bytes[0] & 0x08 != 0,
-            error_interrupt: bytes[0] & 0x10 != 0,
+            range_interrupt: bytes[0] & 0x07 != 0,
+            als_interrupt: bytes[0] & 0x38 != 0,
+            error_interrupt: bytes[0] & 0xC0 != 0,
         })
     }
 }
@@ -91,7 +129,7 @@ impl FromByteArray for ResultInterruptStatusGpio {
 ///
 /// ALS measurement result (16-bit raw count value).
 #[register(0x0050u16)]
-#[derive(Debug, Clone, Copy, ReadableRegister)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, ReadableRegister)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct AlsResultValue {
     /// Measured ambient light level (raw counts)
@@ -112,7 +150,7 @@ impl FromByteArray for AlsResultValue {
 ///
 /// ALS status and error information.
 #[register(0x004Eu16)]
-#[derive(Debug, Clone, Copy, ReadableRegister)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ReadableRegister)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct ResultAlsStatus {
     /// ALS error code
@@ -136,11 +174,11 @@ impl FromByteArray for ResultAlsStatus {
     }
 }
 
-/// Range Result Convergence Time Register (0x063-0x066)
+/// Range Result Return Convergence Time Register (0x07C-0x07F)
 ///
 /// Convergence time for the range measurement
-#[register(0x0063u16)]
-#[derive(Debug, Clone, Copy, ReadableRegister)]
+#[register(0x007Cu16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, ReadableRegister)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct RangeResultConvergenceTime {
     /// Convergence time
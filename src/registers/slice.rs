@@ -0,0 +1,97 @@
+//! Parsing registers from byte slices
+//!
+//! Register bytes that arrive inside a larger buffer, e.g. from a DMA
+//! transfer, parse with `TryFrom<&[u8]>` or `parse_prefix` without copying
+//! them into the register's fixed-size array first.
+
+use regiface::{ByteArray, FromByteArray};
+
+use super::*;
+use crate::types::RegisterError;
+
+/// Decodes `R` from the first bytes of `bytes`, returning the rest
+fn parse_prefix<R>(bytes: &[u8]) -> Result<(R, &[u8]), RegisterError>
+where
+    R: FromByteArray,
+    RegisterError: From<R::Error>,
+{
+    let mut array = R::Array::new();
+    let width = array.as_ref().len();
+    if bytes.len() < width {
+        return Err(RegisterError::WrongLength {
+            expected: width,
+            actual: bytes.len(),
+        });
+    }
+    let (head, rest) = bytes.split_at(width);
+    array.as_mut().copy_from_slice(head);
+    Ok((R::from_bytes(array)?, rest))
+}
+
+/// Decodes `R` from exactly its width in bytes
+fn parse_exact<R>(bytes: &[u8]) -> Result<R, RegisterError>
+where
+    R: FromByteArray,
+    RegisterError: From<R::Error>,
+{
+    let width = R::Array::new().as_ref().len();
+    if bytes.len() != width {
+        return Err(RegisterError::WrongLength {
+            expected: width,
+            actual: bytes.len(),
+        });
+    }
+    parse_prefix(bytes).map(|(register, _)| register)
+}
+
+macro_rules! slice_parsing {
+    ($($ty:ident),* $(,)?) => {
+        $(
+            impl TryFrom<&[u8]> for $ty {
+                type Error = RegisterError;
+
+                /// Decodes the register from a slice of exactly its width.
+                ///
+                /// Fails with `RegisterError::WrongLength` for any other
+                /// length, and otherwise like the register's `from_bytes`.
+                fn try_from(bytes: &[u8]) -> Result<Self, RegisterError> {
+                    parse_exact(bytes)
+                }
+            }
+
+            impl $ty {
+                /// Decodes the register from the start of `bytes`, returning
+                /// the bytes after it.
+                ///
+                /// # Errors
+                /// * `RegisterError::WrongLength` - `bytes` is shorter than
+                ///   the register
+                /// * Any error decoding the register's bytes
+                pub fn parse_prefix(bytes: &[u8]) -> Result<(Self, &[u8]), RegisterError> {
+                    parse_prefix(bytes)
+                }
+            }
+        )*
+    };
+}
+
+for_each_register!(slice_parsing);
+
+impl<const ADDR: u16, const N: usize> TryFrom<&[u8]> for RawRegister<ADDR, N> {
+    type Error = RegisterError;
+
+    /// Copies a slice of exactly `N` bytes.
+    fn try_from(bytes: &[u8]) -> Result<Self, RegisterError> {
+        parse_exact(bytes)
+    }
+}
+
+impl<const ADDR: u16, const N: usize> RawRegister<ADDR, N> {
+    /// Copies the first `N` bytes of `bytes`, returning the bytes after them.
+    ///
+    /// # Errors
+    /// * `RegisterError::WrongLength` - `bytes` is shorter than `N`
+    pub fn parse_prefix(bytes: &[u8]) -> Result<(Self, &[u8]), RegisterError> {
+        parse_prefix(bytes)
+    }
+}
@@ -1,18 +1,19 @@
-//! System Registers (0x010 - 0x017)
+//! System Registers (0x010 - 0x017, 0x2A3)
 //!
 //! These registers contain system configuration including GPIO, interrupts,
-//! fresh out of reset flag, and history buffer settings.
+//! fresh out of reset flag, history buffer and interleaved mode settings.
 
 use core::convert::Infallible;
 use regiface::{register, FromByteArray, ReadableRegister, ToByteArray, WritableRegister};
 
+use super::{BusyPolicy, DatasheetLimits};
 use crate::types::{GpioFunction, GpioPolarity, InterruptMode};
 
 /// GPIO0 Mode Register (0x010)
 ///
-/// Configures the function and polarity of GPIO0 pin.
+/// Configures the function (bits `[4:1]`) and polarity (bit 5) of GPIO0 pin.
 #[register(0x0010u16)]
-#[derive(Debug, Clone, Copy, ReadableRegister, WritableRegister)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ReadableRegister, WritableRegister)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct ModeGpio0 {
     /// GPIO0 function select
@@ -32,7 +33,7 @@ impl FromByteArray for ModeGpio0 {
             GpioFunction::Off
         };
 
-        let polarity = if bytes[0] & 0x01 != 0 {
+        let polarity = if bytes[0] & 0x20 != 0 {
             GpioPolarity::ActiveHigh
         } else {
             GpioPolarity::ActiveLow
@@ -54,18 +55,22 @@ impl ToByteArray for ModeGpio0 {
 
         let polarity_bit = match self.polarity {
             GpioPolarity::ActiveLow => 0x00,
-            GpioPolarity::ActiveHigh => 0x01,
+            GpioPolarity::ActiveHigh => 0x20,
         };
 
         Ok([function_bit | polarity_bit])
     }
 }
 
+impl DatasheetLimits for ModeGpio0 {
+    const BUSY_POLICY: BusyPolicy = BusyPolicy::Anytime;
+}
+
 /// GPIO1 Mode Register (0x011)
 ///
-/// Configures the function and polarity of GPIO1 pin.
+/// Configures the function (bits `[4:1]`) and polarity (bit 5) of GPIO1 pin.
 #[register(0x0011u16)]
-#[derive(Debug, Clone, Copy, ReadableRegister, WritableRegister)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ReadableRegister, WritableRegister)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct ModeGpio1 {
     /// GPIO1 function select
@@ -85,7 +90,7 @@ impl FromByteArray for ModeGpio1 {
             GpioFunction::Off
         };
 
-        let polarity = if bytes[0] & 0x01 != 0 {
+        let polarity = if bytes[0] & 0x20 != 0 {
             GpioPolarity::ActiveHigh
         } else {
             GpioPolarity::ActiveLow
@@ -107,18 +112,22 @@ impl ToByteArray for ModeGpio1 {
 
         let polarity_bit = match self.polarity {
             GpioPolarity::ActiveLow => 0x00,
-            GpioPolarity::ActiveHigh => 0x01,
+            GpioPolarity::ActiveHigh => 0x20,
         };
 
         Ok([function_bit | polarity_bit])
     }
 }
 
+impl DatasheetLimits for ModeGpio1 {
+    const BUSY_POLICY: BusyPolicy = BusyPolicy::Anytime;
+}
+
 /// History Control Register (0x012)
 ///
 /// Controls the history buffer for averaging measurements.
 #[register(0x0012u16)]
-#[derive(Debug, Clone, Copy, ReadableRegister, WritableRegister)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ReadableRegister, WritableRegister)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct HistoryCtrl {
     /// Enable history buffer
@@ -134,7 +143,7 @@ impl FromByteArray for HistoryCtrl {
     fn from_bytes(bytes: Self::Array) -> Result<Self, Self::Error> {
         Ok(Self {
             enable: bytes[0] & 0x01 != 0,
-            clear: bytes[0] & 0x02 != 0,
+            clear: bytes[0] & 0x04 != 0,
         })
     }
 }
@@ -149,17 +158,22 @@ impl ToByteArray for HistoryCtrl {
             value |= 0x01;
         }
         if self.clear {
-            value |= 0x02;
+            value |= 0x04;
         }
         Ok([value])
     }
 }
 
+impl DatasheetLimits for HistoryCtrl {
+    const BUSY_POLICY: BusyPolicy = BusyPolicy::Idle;
+}
+
 /// Interrupt Configuration GPIO Register (0x014)
 ///
 /// Configures interrupt modes for range and ALS measurements.
+/// Bits `[2:0]` select the range mode and bits `[5:3]` the ALS mode.
 #[register(0x0014u16)]
-#[derive(Debug, Clone, Copy, ReadableRegister, WritableRegister)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ReadableRegister, WritableRegister)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct InterruptConfigGpio {
     /// Range interrupt mode
@@ -173,8 +187,8 @@ impl FromByteArray for InterruptConfigGpio {
     type Array = [u8; 1];
 
     fn from_bytes(bytes: Self::Array) -> Result<Self, Self::Error> {
-        let range_mode = (bytes[0] >> 3) & 0x07;
-        let als_mode = bytes[0] & 0x07;
+        let range_mode = bytes[0] & 0x07;
+        let als_mode = (bytes[0] >> 3) & 0x07;
 
         let range_interrupt =
             InterruptMode::try_from(range_mode).unwrap_or(InterruptMode::Disabled);
@@ -192,17 +206,21 @@ impl ToByteArray for InterruptConfigGpio {
     type Array = [u8; 1];
 
     fn to_bytes(self) -> Result<Self::Array, Self::Error> {
-        let range_bits = (self.range_interrupt as u8) << 3;
-        let als_bits = self.als_interrupt as u8;
+        let range_bits = self.range_interrupt as u8;
+        let als_bits = (self.als_interrupt as u8) << 3;
         Ok([range_bits | als_bits])
     }
 }
 
+impl DatasheetLimits for InterruptConfigGpio {
+    const BUSY_POLICY: BusyPolicy = BusyPolicy::Idle;
+}
+
 /// Interrupt Clear Register (0x015)
 ///
 /// Writing to this register clears interrupt status flags.
 #[register(0x0015u16)]
-#[derive(Debug, Clone, Copy, ReadableRegister, WritableRegister)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ReadableRegister, WritableRegister)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct InterruptClear {
     /// Clear range interrupt
@@ -245,12 +263,16 @@ impl ToByteArray for InterruptClear {
     }
 }
 
+impl DatasheetLimits for InterruptClear {
+    const BUSY_POLICY: BusyPolicy = BusyPolicy::Anytime;
+}
+
 /// Fresh Out of Reset Register (0x016)
 ///
 /// This register indicates if the device has been reset.
 /// Value is 1 after power-on or reset, and should be cleared by software.
 #[register(0x0016u16)]
-#[derive(Debug, Clone, Copy, ReadableRegister, WritableRegister)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ReadableRegister, WritableRegister)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct FreshOutOfReset {
     /// Fresh out of reset flag (1 = fresh reset, 0 = cleared)
@@ -277,11 +299,15 @@ impl ToByteArray for FreshOutOfReset {
     }
 }
 
+impl DatasheetLimits for FreshOutOfReset {
+    const BUSY_POLICY: BusyPolicy = BusyPolicy::Anytime;
+}
+
 /// Grouped Parameter Hold Register (0x017)
 ///
 /// Controls whether parameter updates are grouped or immediate.
 #[register(0x0017u16)]
-#[derive(Debug, Clone, Copy, ReadableRegister, WritableRegister)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ReadableRegister, WritableRegister)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct GroupedParameterHold {
     /// Hold parameter updates (true = hold, false = immediate)
@@ -307,3 +333,44 @@ impl ToByteArray for GroupedParameterHold {
         Ok([if self.hold { 0x01 } else { 0x00 }])
     }
 }
+
+impl DatasheetLimits for GroupedParameterHold {
+    const BUSY_POLICY: BusyPolicy = BusyPolicy::Anytime;
+}
+
+/// Interleaved Mode Enable Register (0x2A3)
+///
+/// When enabled, a range measurement is performed immediately after each ALS
+/// measurement; the pair is started and paced through the ALS start and
+/// intermeasurement period registers.
+#[register(0x02A3u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ReadableRegister, WritableRegister)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InterleavedModeEnable {
+    /// Interleaved ALS + range mode enabled
+    pub enabled: bool,
+}
+
+impl FromByteArray for InterleavedModeEnable {
+    type Error = Infallible;
+    type Array = [u8; 1];
+
+    fn from_bytes(bytes: Self::Array) -> Result<Self, Self::Error> {
+        Ok(Self {
+            enabled: bytes[0] & 0x01 != 0,
+        })
+    }
+}
+
+impl ToByteArray for InterleavedModeEnable {
+    type Error = Infallible;
+    type Array = [u8; 1];
+
+    fn to_bytes(self) -> Result<Self::Array, Self::Error> {
+        Ok([if self.enabled { 0x01 } else { 0x00 }])
+    }
+}
+
+impl DatasheetLimits for InterleavedModeEnable {
+    const BUSY_POLICY: BusyPolicy = BusyPolicy::Idle;
+}
@@ -0,0 +1,149 @@
+//! Alternating measurements across several sensors
+//!
+//! Sensors with overlapping fields of view see each other's emitter when they
+//! range at the same time, which shows up as wrong distances or spurious
+//! failures. [`AlternatingScheduler`] avoids this by ranging one sensor at a
+//! time, round-robin, with a guard interval between measurements.
+
+use core::time::Duration;
+
+use crate::device::Device;
+use crate::types::{Error, RangeReading};
+
+/// Round-robin single-shot ranging over `N` sensors
+///
+/// Each call to [`measure_next`](AlternatingScheduler::measure_next) ranges
+/// the next sensor in turn, waits the guard interval and returns the result
+/// tagged with the sensor's index. A sensor that fails does not hold up the
+/// others: its error is returned under its index and the next call moves on
+/// to the following sensor.
+///
+/// # Example
+/// ```no_run
+/// use core::time::Duration;
+/// use embedded_hal::{delay::DelayNs, i2c::I2c};
+/// use vl6180x::{scheduler::AlternatingScheduler, Device, RangeReading};
+///
+/// fn run<I2C: I2c, D: DelayNs>(left: Device<I2C>, right: Device<I2C>, delay: &mut D) {
+///     let mut scheduler = AlternatingScheduler::new([left, right], Duration::from_millis(2));
+///     loop {
+///         match scheduler.measure_next(delay) {
+///             (_index, Ok(RangeReading::Valid(_distance))) => { /* use the distance */ }
+///             (_index, Ok(_)) => { /* no target or measurement fault */ }
+///             (_index, Err(_error)) => { /* this sensor is skipped until its next turn */ }
+///         }
+///     }
+/// }
+/// ```
+pub struct AlternatingScheduler<I2C, const N: usize> {
+    devices: [Device<I2C>; N],
+    guard: Duration,
+    next: usize,
+}
+
+impl<I2C, const N: usize> AlternatingScheduler<I2C, N> {
+    /// Creates a scheduler starting with the first device.
+    ///
+    /// # Arguments
+    /// * `devices` - Sensors to alternate between, in measurement order
+    /// * `guard` - Time to wait after each measurement before the next sensor starts
+    ///
+    /// # Panics
+    /// Panics if `N` is zero.
+    pub fn new(devices: [Device<I2C>; N], guard: Duration) -> Self {
+        assert!(N > 0, "AlternatingScheduler needs at least one device");
+        Self {
+            devices,
+            guard,
+            next: 0,
+        }
+    }
+
+    /// Returns the index of the sensor measured by the next call.
+    pub fn next_index(&self) -> usize {
+        self.next
+    }
+
+    /// Returns the guard interval between measurements.
+    pub fn guard(&self) -> Duration {
+        self.guard
+    }
+
+    /// Sets the guard interval between measurements.
+    pub fn set_guard(&mut self, guard: Duration) {
+        self.guard = guard;
+    }
+
+    /// Returns the sensors.
+    pub fn devices(&self) -> &[Device<I2C>; N] {
+        &self.devices
+    }
+
+    /// Returns the sensors for configuration between measurements.
+    pub fn devices_mut(&mut self) -> &mut [Device<I2C>; N] {
+        &mut self.devices
+    }
+
+    /// Consumes the scheduler and returns the sensors.
+    pub fn into_devices(self) -> [Device<I2C>; N] {
+        self.devices
+    }
+
+    /// Guard interval in microseconds, saturating at `u32::MAX`
+    fn guard_us(&self) -> u32 {
+        u32::try_from(self.guard.as_micros()).unwrap_or(u32::MAX)
+    }
+
+    /// Returns the index to measure now and moves on to the next sensor
+    fn advance(&mut self) -> usize {
+        let index = self.next;
+        self.next = (index + 1) % N;
+        index
+    }
+}
+
+impl<I2C, const N: usize> AlternatingScheduler<I2C, N>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Ranges the next sensor in turn.
+    ///
+    /// Takes a single-shot measurement with
+    /// [`read_range`](Device::read_range), then waits the guard interval
+    /// whether or not the measurement succeeded.
+    ///
+    /// Returns the index of the sensor measured together with its result.
+    ///
+    /// # Arguments
+    /// * `delay` - Delay provider
+    pub fn measure_next<D>(&mut self, delay: &mut D) -> (usize, Result<RangeReading, Error>)
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        let index = self.advance();
+        let result = self.devices[index].read_range(delay);
+        delay.delay_us(self.guard_us());
+        (index, result)
+    }
+}
+
+impl<I2C, const N: usize> AlternatingScheduler<I2C, N>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    /// Asynchronously ranges the next sensor in turn.
+    ///
+    /// This is the async version of [`measure_next`](AlternatingScheduler::measure_next).
+    pub async fn measure_next_async<D>(
+        &mut self,
+        delay: &mut D,
+    ) -> (usize, Result<RangeReading, Error>)
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        let index = self.advance();
+        let result = self.devices[index].read_range_async(delay).await;
+        delay.delay_us(self.guard_us()).await;
+        (index, result)
+    }
+}
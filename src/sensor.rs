@@ -0,0 +1,200 @@
+//! Sensor abstraction traits
+//!
+//! These traits let application code be written against "something that
+//! measures distance" or "something that measures light" instead of the
+//! concrete [`Device`], so a fake such as [`FakeRangeSensor`] can be swapped
+//! in for tests. They are local to this crate and are not an attempt at an
+//! ecosystem-wide sensor standard.
+
+use measurements::Length;
+
+use crate::device::{Device, DeviceAddress};
+use crate::types::{Error, Luminance};
+
+/// A sensor that can take a single distance measurement
+pub trait RangeSensor {
+    /// Error returned when a measurement fails
+    type Error;
+
+    /// Takes a single distance measurement
+    ///
+    /// # Arguments
+    /// * `delay` - Delay provider used while waiting for the result
+    fn measure<D>(&mut self, delay: &mut D) -> Result<Length, Self::Error>
+    where
+        D: embedded_hal::delay::DelayNs;
+}
+
+/// A sensor that can take a single ambient light measurement
+pub trait LightSensor {
+    /// Error returned when a measurement fails
+    type Error;
+
+    /// Takes a single ambient light measurement
+    ///
+    /// # Arguments
+    /// * `delay` - Delay provider used while waiting for the result
+    fn measure_lux<D>(&mut self, delay: &mut D) -> Result<Luminance, Self::Error>
+    where
+        D: embedded_hal::delay::DelayNs;
+}
+
+/// Async counterpart of [`RangeSensor`]
+#[allow(async_fn_in_trait)]
+pub trait AsyncRangeSensor {
+    /// Error returned when a measurement fails
+    type Error;
+
+    /// Asynchronously takes a single distance measurement
+    async fn measure_async<D>(&mut self, delay: &mut D) -> Result<Length, Self::Error>
+    where
+        D: embedded_hal_async::delay::DelayNs;
+}
+
+/// Async counterpart of [`LightSensor`]
+#[allow(async_fn_in_trait)]
+pub trait AsyncLightSensor {
+    /// Error returned when a measurement fails
+    type Error;
+
+    /// Asynchronously takes a single ambient light measurement
+    async fn measure_lux_async<D>(&mut self, delay: &mut D) -> Result<Luminance, Self::Error>
+    where
+        D: embedded_hal_async::delay::DelayNs;
+}
+
+impl<I2C, A: DeviceAddress> RangeSensor for Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    type Error = Error;
+
+    fn measure<D>(&mut self, delay: &mut D) -> Result<Length, Self::Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        self.measure_range_single(delay)
+    }
+}
+
+impl<I2C, A: DeviceAddress> LightSensor for Device<I2C, A>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    type Error = Error;
+
+    fn measure_lux<D>(&mut self, delay: &mut D) -> Result<Luminance, Self::Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        self.measure_als_single(delay)
+    }
+}
+
+impl<I2C, A: DeviceAddress> AsyncRangeSensor for Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    type Error = Error;
+
+    async fn measure_async<D>(&mut self, delay: &mut D) -> Result<Length, Self::Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        self.measure_range_single_async(delay).await
+    }
+}
+
+impl<I2C, A: DeviceAddress> AsyncLightSensor for Device<I2C, A>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    type Error = Error;
+
+    async fn measure_lux_async<D>(&mut self, delay: &mut D) -> Result<Luminance, Self::Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        self.measure_als_single_async(delay).await
+    }
+}
+
+/// Scriptable [`RangeSensor`] for tests
+///
+/// Replays a fixed script of results, one per measurement. Once the script is
+/// exhausted the final entry is repeated.
+///
+/// # Example
+/// ```
+/// use measurements::Length;
+/// use vl6180x::{sensor::FakeRangeSensor, Error, RangeSensor};
+///
+/// # struct NoDelay;
+/// # impl embedded_hal::delay::DelayNs for NoDelay {
+/// #     fn delay_ns(&mut self, _: u32) {}
+/// # }
+/// let script = [Ok(Length::from_millimeters(42.0)), Err(Error::Timeout)];
+/// let mut sensor = FakeRangeSensor::new(&script);
+///
+/// assert_eq!(sensor.measure(&mut NoDelay), Ok(Length::from_millimeters(42.0)));
+/// assert_eq!(sensor.measure(&mut NoDelay), Err(Error::Timeout));
+/// assert_eq!(sensor.measure(&mut NoDelay), Err(Error::Timeout));
+/// ```
+#[derive(Debug, Clone)]
+pub struct FakeRangeSensor<'a, E = Error> {
+    script: &'a [Result<Length, E>],
+    position: usize,
+}
+
+impl<'a, E> FakeRangeSensor<'a, E> {
+    /// Creates a fake sensor replaying the given script.
+    ///
+    /// # Panics
+    /// Panics if the script is empty.
+    pub fn new(script: &'a [Result<Length, E>]) -> Self {
+        assert!(
+            !script.is_empty(),
+            "FakeRangeSensor script must not be empty"
+        );
+        Self {
+            script,
+            position: 0,
+        }
+    }
+
+    /// Returns the number of measurements taken so far.
+    pub fn measurements(&self) -> usize {
+        self.position
+    }
+
+    fn next(&mut self) -> Result<Length, E>
+    where
+        E: Copy,
+    {
+        let index = self.position.min(self.script.len() - 1);
+        self.position += 1;
+        self.script[index]
+    }
+}
+
+impl<E: Copy> RangeSensor for FakeRangeSensor<'_, E> {
+    type Error = E;
+
+    fn measure<D>(&mut self, _delay: &mut D) -> Result<Length, Self::Error>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        self.next()
+    }
+}
+
+impl<E: Copy> AsyncRangeSensor for FakeRangeSensor<'_, E> {
+    type Error = E;
+
+    async fn measure_async<D>(&mut self, _delay: &mut D) -> Result<Length, Self::Error>
+    where
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        self.next()
+    }
+}
@@ -0,0 +1,322 @@
+//! Measurement statistics
+//!
+//! Characterizing a sensor means running thousands of measurements and
+//! looking at their spread and at how often each error code came up.
+//! [`MeasurementSession`] and [`AlsSession`] accumulate those figures on the
+//! device itself, in fixed memory and a single pass: the mean and variance
+//! follow Welford's algorithm, which stays accurate over long sessions where
+//! summing the squares would lose precision.
+
+use core::fmt;
+
+use measurements::Length;
+
+use crate::types::{AlsReading, Luminance, RangeErrorCode};
+
+/// Square root by Newton's method, for `no_std`
+fn sqrt(x: f64) -> f64 {
+    if x <= 0.0 || x.is_nan() {
+        return 0.0;
+    }
+    // Halving the exponent lands within a few percent of the root
+    let mut root = f64::from_bits((x.to_bits() >> 1) + (1023 << 51));
+    for _ in 0..6 {
+        root = 0.5 * (root + x / root);
+    }
+    root
+}
+
+/// Spread of a series of values
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Statistics {
+    /// Number of values
+    pub count: u32,
+    /// Smallest value
+    pub min: f64,
+    /// Largest value
+    pub max: f64,
+    /// Arithmetic mean
+    pub mean: f64,
+    /// Sample standard deviation (with `count - 1`), zero for a single value
+    pub std_dev: f64,
+}
+
+impl fmt::Display for Statistics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "n={} min={} max={} mean={:.3} sd={:.3}",
+            self.count, self.min, self.max, self.mean, self.std_dev
+        )
+    }
+}
+
+/// Running count, extremes, mean and variance by Welford's algorithm
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Welford {
+    count: u32,
+    min: f64,
+    max: f64,
+    mean: f64,
+    /// Sum of squared deviations from the mean
+    m2: f64,
+}
+
+impl Welford {
+    const fn new() -> Self {
+        Self {
+            count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            mean: 0.0,
+            m2: 0.0,
+        }
+    }
+
+    fn add(&mut self, value: f64) {
+        self.count = self.count.saturating_add(1);
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        let delta = value - self.mean;
+        self.mean += delta / f64::from(self.count);
+        self.m2 += delta * (value - self.mean);
+    }
+
+    fn statistics(&self) -> Option<Statistics> {
+        (self.count > 0).then(|| Statistics {
+            count: self.count,
+            min: self.min,
+            max: self.max,
+            mean: self.mean,
+            std_dev: match self.count {
+                1 => 0.0,
+                n => sqrt(self.m2 / f64::from(n - 1)),
+            },
+        })
+    }
+}
+
+/// Number of range measurements per [`RangeErrorCode`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RangeErrorCounts {
+    counts: [u32; 16],
+}
+
+impl RangeErrorCounts {
+    /// Returns the number of measurements that ended with `code`.
+    pub fn get(&self, code: RangeErrorCode) -> u32 {
+        self.counts[code as usize]
+    }
+
+    /// Returns the codes that came up with their counts, by code.
+    pub fn iter(&self) -> impl Iterator<Item = (RangeErrorCode, u32)> + '_ {
+        self.counts.iter().enumerate().filter_map(|(code, &count)| {
+            let code = RangeErrorCode::try_from(code as u8).ok()?;
+            (count > 0).then_some((code, count))
+        })
+    }
+}
+
+impl fmt::Display for RangeErrorCounts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (code, count)) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{code:?}: {count}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Figures of a [`MeasurementSession`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RangeSummary {
+    /// Number of measurements, valid or not
+    pub samples: u32,
+    /// Distances in millimeters of the valid measurements, `None` without any
+    pub distance_mm: Option<Statistics>,
+    /// Number of measurements per status code, including the valid ones
+    pub errors: RangeErrorCounts,
+}
+
+impl fmt::Display for RangeSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} samples", self.samples)?;
+        if let Some(distance) = &self.distance_mm {
+            write!(f, ", distance mm: {distance}")?;
+        }
+        write!(f, ", codes: {}", self.errors)
+    }
+}
+
+/// Statistics of a series of range measurements
+///
+/// Record every measurement with its status code, e.g. from
+/// [`Device::read_range_quick`](crate::Device::read_range_quick). Distances
+/// enter the statistics only for measurements without an error; the status
+/// codes of all of them are counted.
+///
+/// # Example
+/// ```
+/// use measurements::Length;
+/// use vl6180x::session::MeasurementSession;
+/// use vl6180x::RangeErrorCode;
+///
+/// let mut session = MeasurementSession::new();
+/// for mm in [99.0, 100.0, 101.0, 100.0] {
+///     session.record(RangeErrorCode::NoError, Length::from_millimeters(mm));
+/// }
+/// session.record(RangeErrorCode::MaxConvergence, Length::from_millimeters(255.0));
+///
+/// let summary = session.summary();
+/// let distance = summary.distance_mm.unwrap();
+/// assert_eq!((distance.min, distance.max, distance.mean), (99.0, 101.0, 100.0));
+/// assert_eq!(summary.errors.get(RangeErrorCode::MaxConvergence), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MeasurementSession {
+    samples: u32,
+    distance: Welford,
+    errors: RangeErrorCounts,
+}
+
+impl MeasurementSession {
+    /// Creates an empty session.
+    pub const fn new() -> Self {
+        Self {
+            samples: 0,
+            distance: Welford::new(),
+            errors: RangeErrorCounts { counts: [0; 16] },
+        }
+    }
+
+    /// Adds a measurement.
+    ///
+    /// # Arguments
+    /// * `code` - Status code of the measurement
+    /// * `distance` - The measured distance, ignored unless `code` is
+    ///   [`RangeErrorCode::NoError`]
+    pub fn record(&mut self, code: RangeErrorCode, distance: Length) {
+        self.samples = self.samples.saturating_add(1);
+        let count = &mut self.errors.counts[code as usize];
+        *count = count.saturating_add(1);
+        if code == RangeErrorCode::NoError {
+            self.distance.add(distance.as_millimeters());
+        }
+    }
+
+    /// Returns the figures so far.
+    pub fn summary(&self) -> RangeSummary {
+        RangeSummary {
+            samples: self.samples,
+            distance_mm: self.distance.statistics(),
+            errors: self.errors,
+        }
+    }
+
+    /// Starts over.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl Default for MeasurementSession {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Figures of an [`AlsSession`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AlsSummary {
+    /// Number of readings, valid or not
+    pub samples: u32,
+    /// Light levels in lux of the valid readings, `None` without any
+    pub lux: Option<Statistics>,
+    /// Readings above the measurable range
+    pub saturated: u32,
+    /// Readings below the measurable range
+    pub dark: u32,
+    /// Failed readings
+    pub failed: u32,
+}
+
+impl fmt::Display for AlsSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} samples", self.samples)?;
+        if let Some(lux) = &self.lux {
+            write!(f, ", lux: {lux}")?;
+        }
+        write!(
+            f,
+            ", saturated: {}, dark: {}, failed: {}",
+            self.saturated, self.dark, self.failed
+        )
+    }
+}
+
+/// Statistics of a series of ALS readings
+///
+/// The ALS counterpart of [`MeasurementSession`]. Light levels enter the
+/// statistics only for valid readings; the others are counted by kind.
+#[derive(Debug, Clone)]
+pub struct AlsSession {
+    summary: AlsSummary,
+    lux: Welford,
+}
+
+impl AlsSession {
+    /// Creates an empty session.
+    pub const fn new() -> Self {
+        Self {
+            summary: AlsSummary {
+                samples: 0,
+                lux: None,
+                saturated: 0,
+                dark: 0,
+                failed: 0,
+            },
+            lux: Welford::new(),
+        }
+    }
+
+    /// Adds a reading.
+    pub fn record(&mut self, reading: AlsReading) {
+        let summary = &mut self.summary;
+        summary.samples = summary.samples.saturating_add(1);
+        let count = match reading {
+            AlsReading::Valid(Luminance { lux }) => {
+                self.lux.add(f64::from(lux));
+                return;
+            }
+            AlsReading::Saturated => &mut summary.saturated,
+            AlsReading::Dark => &mut summary.dark,
+            AlsReading::Failed(_) => &mut summary.failed,
+        };
+        *count = count.saturating_add(1);
+    }
+
+    /// Returns the figures so far.
+    pub fn summary(&self) -> AlsSummary {
+        AlsSummary {
+            lux: self.lux.statistics(),
+            ..self.summary
+        }
+    }
+
+    /// Starts over.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl Default for AlsSession {
+    fn default() -> Self {
+        Self::new()
+    }
+}
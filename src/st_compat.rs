@@ -0,0 +1,214 @@
+//! ST API compatibility layer
+//!
+//! Free functions named after the calls of ST's VL6180x C API, to make porting
+//! firmware written against it a matter of renaming. Each function is a thin
+//! wrapper over the [`Device`] API; the C device handle becomes the
+//! `&mut Device` first argument and status codes become [`Result`]s.
+//!
+//! | C API | This module | Native API |
+//! |-------|-------------|------------|
+//! | `VL6180x_Prepare` | [`prepare`] | [`Device::write_register`], [`Device::set_range_interrupt`], [`Device::set_als_interrupt`] |
+//! | `VL6180x_RangePollMeasurement` | [`range_poll_measurement`] | [`Device::read_range`] |
+//! | `VL6180x_AlsPollMeasurement` | [`als_poll_measurement`] | [`Device::read_als`] |
+//! | `VL6180x_AlsGetLux` | [`als_get_lux`] | [`Luminance::from_counts`] over the ALS result and configuration registers |
+//! | `VL6180x_UpscaleSetScaling` | [`upscale_set_scaling`] | Only a factor of 1 is supported |
+//! | `VL6180x_RangeData_t` | [`RangeData`] | [`RangeResultStatus`], [`RangeResultBlock`] |
+//!
+//! Unlike its C counterpart, [`prepare`] does not load ST's private tuning
+//! settings; apply those with [`Device::write_block`] if your port relied on
+//! them.
+//!
+//! # Example
+//! ```
+//! use vl6180x::{st_compat, Device, RangeErrorCode};
+//! # use embedded_hal::delay::DelayNs;
+//! # use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, Operation};
+//! # struct Bus { regs: [u8; 0x100] }
+//! # impl ErrorType for Bus { type Error = ErrorKind; }
+//! # impl I2c for Bus {
+//! #     fn transaction(&mut self, _: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+//! #         match ops {
+//! #             [Operation::Write(reg), Operation::Read(buf)] => {
+//! #                 let start = reg[1] as usize;
+//! #                 buf.copy_from_slice(&self.regs[start..start + buf.len()]);
+//! #             }
+//! #             [Operation::Write(reg), Operation::Write(data)] => {
+//! #                 let start = reg[1] as usize;
+//! #                 self.regs[start..start + data.len()].copy_from_slice(data);
+//! #             }
+//! #             _ => {}
+//! #         }
+//! #         Ok(())
+//! #     }
+//! # }
+//! # struct NoDelay;
+//! # impl DelayNs for NoDelay { fn delay_ns(&mut self, _: u32) {} }
+//! # let mut regs = [0; 0x100];
+//! # regs[0x16] = 0x01;
+//! # regs[0x4D] = 0x01;
+//! # regs[0x4F] = 0x04;
+//! # regs[0x62] = 87;
+//! # regs[0x66..0x68].copy_from_slice(&[0x01, 0x40]);
+//! # let mut bus = Bus { regs };
+//! # let mut delay = NoDelay;
+//! let mut dev = Device::new(&mut bus);
+//!
+//! st_compat::prepare(&mut dev)?;
+//! let data = st_compat::range_poll_measurement(&mut dev, &mut delay)?;
+//! if data.error_status == RangeErrorCode::NoError {
+//!     assert_eq!(data.range_mm, 87);
+//!     assert_eq!(data.signal_rate_mcps, 0x0140);
+//! }
+//! # Ok::<(), vl6180x::Error>(())
+//! ```
+
+use crate::device::Device;
+use crate::registers::{
+    AlsAnalogueGain, AlsIntegrationPeriod, AlsResultValue, FreshOutOfReset, InterruptClear,
+    RangeResultBlock, RangeResultStatus,
+};
+use crate::types::{
+    AlsErrorCode, AlsInterrupt, AlsReading, Error, ErrorContext, Luminance, RangeErrorCode,
+    RangeInterrupt,
+};
+
+/// Interrupt clear value acknowledging every interrupt source
+const CLEAR_ALL: InterruptClear = InterruptClear {
+    clear_range: true,
+    clear_als: true,
+    clear_error: true,
+};
+
+/// Result of a range measurement, the counterpart of `VL6180x_RangeData_t`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RangeData {
+    /// Final range value in millimeters, only meaningful when
+    /// `error_status` is [`RangeErrorCode::NoError`]
+    pub range_mm: u8,
+    /// Return signal rate in Mcps (9.7 fixed point)
+    pub signal_rate_mcps: u16,
+    /// Status code of the measurement
+    pub error_status: RangeErrorCode,
+}
+
+/// Prepares a freshly booted device for measurements.
+///
+/// Clears the fresh-out-of-reset flag if it is set, configures both the range
+/// and ALS interrupts for new samples, as the polling functions of this module
+/// require, and clears all pending interrupts.
+///
+/// Counterpart of `VL6180x_Prepare`.
+///
+/// # Errors
+/// * `Error::BusError` - I2C communication failed
+pub fn prepare<I2C>(dev: &mut Device<I2C>) -> Result<(), Error>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    let reset: FreshOutOfReset = dev.read_register()?;
+    if reset.fresh {
+        dev.write_register(FreshOutOfReset { fresh: false })?;
+    }
+
+    dev.set_range_interrupt(RangeInterrupt::NewSampleReady)?;
+    dev.set_als_interrupt(AlsInterrupt::NewSampleReady)?;
+    dev.write_register(CLEAR_ALL)
+}
+
+/// Performs a single-shot range measurement and returns its full result.
+///
+/// A measurement that completes with an error code is not an error of this
+/// function; check [`RangeData::error_status`] instead, as with the C API.
+///
+/// Counterpart of `VL6180x_RangePollMeasurement`.
+///
+/// # Errors
+/// * `Error::BusError` - I2C communication failed
+/// * `Error::Timeout` - No sample was reported within the polling budget
+pub fn range_poll_measurement<I2C, D>(
+    dev: &mut Device<I2C>,
+    delay: &mut D,
+) -> Result<RangeData, Error>
+where
+    I2C: embedded_hal::i2c::I2c,
+    D: embedded_hal::delay::DelayNs,
+{
+    dev.read_range(delay)?;
+
+    let status: RangeResultStatus = dev.read_register()?;
+    let block: RangeResultBlock = dev.read_register()?;
+    Ok(RangeData {
+        range_mm: block.raw_mm(),
+        signal_rate_mcps: block.return_rate,
+        error_status: status.error_code,
+    })
+}
+
+/// Performs a single-shot ALS measurement and returns the light level.
+///
+/// Counterpart of `VL6180x_AlsPollMeasurement`.
+///
+/// # Errors
+/// * `Error::BusError` - I2C communication failed
+/// * `Error::Timeout` - No sample was reported within the polling budget
+/// * `Error::AlsError` - The measurement overflowed or underflowed
+pub fn als_poll_measurement<I2C, D>(
+    dev: &mut Device<I2C>,
+    delay: &mut D,
+) -> Result<Luminance, Error>
+where
+    I2C: embedded_hal::i2c::I2c,
+    D: embedded_hal::delay::DelayNs,
+{
+    match dev.read_als(delay)? {
+        AlsReading::Valid(lux) => Ok(lux),
+        AlsReading::Saturated => Err(Error::AlsError(AlsErrorCode::Overflow)),
+        AlsReading::Dark => Err(Error::AlsError(AlsErrorCode::Underflow)),
+        AlsReading::Failed(code) => Err(Error::AlsError(code)),
+    }
+}
+
+/// Converts the last ALS result to lux.
+///
+/// Reads the result count of the most recent ALS measurement and scales it
+/// with the currently configured gain and integration period. No measurement
+/// is started.
+///
+/// Counterpart of `VL6180x_AlsGetLux`.
+///
+/// # Errors
+/// * `Error::BusError` - I2C communication failed
+pub fn als_get_lux<I2C>(dev: &mut Device<I2C>) -> Result<Luminance, Error>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    let value: AlsResultValue = dev.read_register()?;
+    let gain: AlsAnalogueGain = dev.read_register()?;
+    let integration: AlsIntegrationPeriod = dev.read_register()?;
+    Ok(Luminance::from_counts(
+        value.raw_count,
+        gain.gain,
+        integration.period,
+    ))
+}
+
+/// Sets the range upscaling factor.
+///
+/// This crate always ranges at the VL6180X's native 1x scaling, so only a
+/// factor of 1 is accepted; it leaves the device untouched.
+///
+/// Counterpart of `VL6180x_UpscaleSetScaling`.
+///
+/// # Errors
+/// * `Error::SerializationError` - `scaling` is not 1
+pub fn upscale_set_scaling<I2C>(_dev: &mut Device<I2C>, scaling: u8) -> Result<(), Error>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    if scaling == 1 {
+        Ok(())
+    } else {
+        Err(Error::SerializationError(ErrorContext::argument()))
+    }
+}
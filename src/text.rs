@@ -0,0 +1,190 @@
+//! Fixed-buffer text
+//!
+//! Renders distances, light levels and readings into a caller-provided byte
+//! buffer with integer arithmetic, for displays and serial consoles on
+//! targets that cannot afford `core::fmt`'s float formatting. The helpers
+//! depend on neither `ufmt` nor `defmt`.
+//!
+//! # Example
+//! ```
+//! use measurements::Length;
+//! use vl6180x::text::{format_lux, format_mm};
+//! use vl6180x::Luminance;
+//!
+//! let mut buf = [0; 16];
+//! assert_eq!(format_mm(&Length::from_millimeters(123.0), &mut buf), Ok("123 mm"));
+//! assert_eq!(format_lux(&Luminance::from_lux(45.64), &mut buf, 1), Ok("45.6 lx"));
+//! ```
+
+use core::fmt;
+
+use measurements::Length;
+
+use crate::types::{AlsReading, Luminance, RangeReading};
+
+/// Most decimals [`format_lux`] renders: light levels are compared to the
+/// millilux
+pub const MAX_LUX_DECIMALS: u8 = 3;
+
+/// The rendered text does not fit into the buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BufferTooSmall;
+
+impl fmt::Display for BufferTooSmall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Buffer too small for the rendered text")
+    }
+}
+
+/// Appends ASCII text to the front of a buffer
+struct Cursor<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, len: 0 }
+    }
+
+    fn push(&mut self, text: &str) -> Result<(), BufferTooSmall> {
+        let end = self.len + text.len();
+        let dest = self.buf.get_mut(self.len..end).ok_or(BufferTooSmall)?;
+        dest.copy_from_slice(text.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+
+    /// Appends `value` in decimal, zero-padded to at least `width` digits
+    fn push_digits(&mut self, mut value: u64, width: usize) -> Result<(), BufferTooSmall> {
+        let mut digits = [b'0'; 20];
+        let mut start = digits.len();
+        while value > 0 || digits.len() - start < width.max(1) {
+            start -= 1;
+            digits[start] = b'0' + (value % 10) as u8;
+            value /= 10;
+        }
+        // Only ASCII digits were written
+        self.push(core::str::from_utf8(&digits[start..]).unwrap_or_default())
+    }
+
+    /// Appends a fixed-point number of `value` units of 10^-`decimals`
+    fn push_fixed(&mut self, value: i64, decimals: u8) -> Result<(), BufferTooSmall> {
+        if value < 0 {
+            self.push("-")?;
+        }
+        let scale = 10u64.pow(decimals.into());
+        let value = value.unsigned_abs();
+        self.push_digits(value / scale, 1)?;
+        if decimals > 0 {
+            self.push(".")?;
+            self.push_digits(value % scale, decimals.into())?;
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> &'a str {
+        // Only ASCII was written
+        core::str::from_utf8(&self.buf[..self.len]).unwrap_or_default()
+    }
+}
+
+/// Rounds half away from zero
+fn round(value: f64) -> i64 {
+    if value < 0.0 {
+        (value - 0.5) as i64
+    } else {
+        (value + 0.5) as i64
+    }
+}
+
+/// Renders a distance in whole millimeters, e.g. `"123 mm"`.
+///
+/// The distance is rounded half up to the nearest millimeter.
+///
+/// # Errors
+/// * `BufferTooSmall` - The text does not fit into `buf`
+pub fn format_mm<'a>(length: &Length, buf: &'a mut [u8]) -> Result<&'a str, BufferTooSmall> {
+    let mut cursor = Cursor::new(buf);
+    cursor.push_fixed(round(length.as_millimeters()), 0)?;
+    cursor.push(" mm")?;
+    Ok(cursor.finish())
+}
+
+/// Renders a light level in lux with `decimals` decimals, e.g. `"45.6 lx"`.
+///
+/// The light level is taken to the nearest millilux, as light levels are
+/// compared, and rounded half up from there on the last decimal. At most
+/// [`MAX_LUX_DECIMALS`] are rendered; more are treated as that many.
+///
+/// # Errors
+/// * `BufferTooSmall` - The text does not fit into `buf`
+pub fn format_lux<'a>(
+    light: &Luminance,
+    buf: &'a mut [u8],
+    decimals: u8,
+) -> Result<&'a str, BufferTooSmall> {
+    let decimals = decimals.min(MAX_LUX_DECIMALS);
+    let millilux = round(f64::from(light.lux) * 1000.0);
+    let divisor = 10i64.pow((MAX_LUX_DECIMALS - decimals).into());
+    let half = if millilux < 0 {
+        -divisor / 2
+    } else {
+        divisor / 2
+    };
+
+    let mut cursor = Cursor::new(buf);
+    cursor.push_fixed((millilux + half) / divisor, decimals)?;
+    cursor.push(" lx")?;
+    Ok(cursor.finish())
+}
+
+/// Renders a range reading: the distance as in [`format_mm`], `"no target"`,
+/// or the status code of a failed measurement, e.g. `"err 7"`.
+///
+/// # Errors
+/// * `BufferTooSmall` - The text does not fit into `buf`
+pub fn format_range<'a>(
+    reading: &RangeReading,
+    buf: &'a mut [u8],
+) -> Result<&'a str, BufferTooSmall> {
+    match reading {
+        RangeReading::Valid(distance) => format_mm(distance, buf),
+        RangeReading::NoTarget => format_status("no target", None, buf),
+        RangeReading::Failed(code) => format_status("err ", Some(*code as u8), buf),
+    }
+}
+
+/// Renders an ALS reading: the light level as in [`format_lux`],
+/// `"saturated"`, `"dark"`, or the status code of a failed measurement, e.g.
+/// `"err 2"`.
+///
+/// # Errors
+/// * `BufferTooSmall` - The text does not fit into `buf`
+pub fn format_als<'a>(
+    reading: &AlsReading,
+    buf: &'a mut [u8],
+    decimals: u8,
+) -> Result<&'a str, BufferTooSmall> {
+    match reading {
+        AlsReading::Valid(light) => format_lux(light, buf, decimals),
+        AlsReading::Saturated => format_status("saturated", None, buf),
+        AlsReading::Dark => format_status("dark", None, buf),
+        AlsReading::Failed(code) => format_status("err ", Some(*code as u8), buf),
+    }
+}
+
+/// Renders `text` followed by an optional status code
+fn format_status<'a>(
+    text: &str,
+    code: Option<u8>,
+    buf: &'a mut [u8],
+) -> Result<&'a str, BufferTooSmall> {
+    let mut cursor = Cursor::new(buf);
+    cursor.push(text)?;
+    if let Some(code) = code {
+        cursor.push_digits(code.into(), 1)?;
+    }
+    Ok(cursor.finish())
+}
@@ -1,12 +1,24 @@
 //! Common types and enumerations for the VL6180X driver
 
-use core::fmt;
+use core::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    iter::Sum,
+    ops::{Add, Div, Mul, Sub},
+    time::Duration,
+};
+
+use measurements::Length;
+
+use crate::device::Address;
+use crate::registers::{AlsThresholds, RangeMaxConvergenceTime, RangeResultBlock, RangeThresholds};
 
 /// Unified error type for register operations
 ///
 /// This error type covers all failure modes that can occur during
 /// register serialization and deserialization operations.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum RegisterError {
     /// Invalid enum value encountered during deserialization
@@ -18,6 +30,13 @@ pub enum RegisterError {
     DurationTooLong,
     /// Invalid date/time value in timestamp
     InvalidTimestamp,
+    /// A byte slice parsed as a register does not have the register's width
+    WrongLength {
+        /// Width of the register in bytes
+        expected: usize,
+        /// Length of the slice
+        actual: usize,
+    },
 }
 
 impl fmt::Display for RegisterError {
@@ -27,10 +46,389 @@ impl fmt::Display for RegisterError {
             Self::DurationTooShort => write!(f, "Duration is too short"),
             Self::DurationTooLong => write!(f, "Duration is too long"),
             Self::InvalidTimestamp => write!(f, "Invalid timestamp"),
+            Self::WrongLength { expected, actual } => {
+                write!(f, "Expected {} bytes, got {}", expected, actual)
+            }
+        }
+    }
+}
+
+/// Range the datasheet documents for a register field
+///
+/// Allowed values are the multiples of `step` in `min..=max`, in `unit`.
+/// Durations are in microseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FieldLimit {
+    /// Datasheet name of the field
+    pub field: &'static str,
+    /// Smallest allowed value
+    pub min: i32,
+    /// Largest allowed value
+    pub max: i32,
+    /// Allowed values are multiples of this step
+    pub step: i32,
+    /// Unit of the values
+    pub unit: &'static str,
+}
+
+impl FieldLimit {
+    /// Limit of a duration field given in milliseconds, checked in microseconds
+    pub(crate) const fn millis(field: &'static str, min: i32, max: i32, step: i32) -> Self {
+        Self {
+            field,
+            min: min * 1_000,
+            max: max * 1_000,
+            step: step * 1_000,
+            unit: "us",
+        }
+    }
+
+    /// Checks `value` against this limit.
+    ///
+    /// # Errors
+    /// A [`LimitViolation`] if `value` is outside the range or off-step.
+    pub fn check(&'static self, value: i32) -> Result<(), LimitViolation> {
+        if self.accepts(value) {
+            Ok(())
+        } else {
+            Err(LimitViolation { limit: self, value })
+        }
+    }
+
+    /// Returns whether `value` is inside the range and on-step.
+    ///
+    /// Usable in const context, unlike [`check`](Self::check).
+    pub const fn accepts(&self, value: i32) -> bool {
+        self.min <= value && value <= self.max && value % self.step == 0
+    }
+
+    /// Checks a duration against this limit, see [`check`](Self::check)
+    pub(crate) fn check_duration(&'static self, value: Duration) -> Result<(), LimitViolation> {
+        self.check(value.as_micros().min(i32::MAX as u128) as i32)
+    }
+}
+
+/// A register field value outside its [`FieldLimit`]
+///
+/// Returned in [`Error::OutOfSpec`] by register writes in strict mode, see
+/// [`Device::set_strict`](crate::Device::set_strict).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LimitViolation {
+    /// The limit that was violated
+    pub limit: &'static FieldLimit,
+    /// The rejected value, in the limit's unit
+    pub value: i32,
+}
+
+impl fmt::Display for LimitViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let FieldLimit {
+            field,
+            min,
+            max,
+            step,
+            unit,
+        } = self.limit;
+        let value = self.value;
+        write!(f, "{field} = {value}{unit} is outside {min}..={max}{unit}")?;
+        if *step > 1 {
+            write!(f, " in steps of {step}{unit}")?;
+        }
+        Ok(())
+    }
+}
+
+/// How a failed operation accessed the device
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Access {
+    /// A typed register at this 16-bit address
+    Register(u16),
+    /// Raw bytes starting at this 16-bit address, e.g. through
+    /// [`Device::read_block`](crate::Device::read_block)
+    Raw(u16),
+    /// An argument was rejected before any register was accessed
+    Argument,
+}
+
+/// Direction of a failed register access
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Direction {
+    /// Reading from the device
+    Read,
+    /// Writing to the device
+    Write,
+}
+
+/// Position of a failed access within a multi-step helper
+///
+/// Helpers issuing a fixed sequence of accesses, such as
+/// [`Device::arm_wake_on_approach`](crate::Device::arm_wake_on_approach),
+/// count their register accesses so a failure can be traced to one line of
+/// the sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct OperationStep {
+    /// Name of the helper
+    pub name: &'static str,
+    /// 1-based index of the failed register access within the helper
+    pub step: u16,
+}
+
+/// Where a transport or codec failure happened
+///
+/// Carried by [`Error::BusError`], [`Error::SerializationError`] and
+/// [`Error::DeserializationError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ErrorContext {
+    /// What was being accessed
+    pub access: Access,
+    /// Whether the access was a read or a write
+    pub direction: Direction,
+    /// The multi-step helper the access belonged to, if any
+    pub operation: Option<OperationStep>,
+}
+
+impl ErrorContext {
+    /// Context of a typed register access outside any multi-step helper
+    pub const fn register(address: u16, direction: Direction) -> Self {
+        Self {
+            access: Access::Register(address),
+            direction,
+            operation: None,
+        }
+    }
+
+    /// Context of an argument rejected before writing it
+    pub const fn argument() -> Self {
+        Self {
+            access: Access::Argument,
+            direction: Direction::Write,
+            operation: None,
+        }
+    }
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let direction = match self.direction {
+            Direction::Read => "reading",
+            Direction::Write => "writing",
+        };
+        match self.access {
+            Access::Register(address) => write!(f, "{direction} register 0x{address:04X}")?,
+            Access::Raw(address) => write!(f, "{direction} raw bytes at 0x{address:04X}")?,
+            Access::Argument => write!(f, "checking an argument")?,
+        }
+        if let Some(OperationStep { name, step }) = self.operation {
+            write!(f, " ({name} step {step})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Disagreeing reads of a result register
+///
+/// Returned in [`Error::InconsistentRead`] by paranoid reads, see
+/// [`Device::set_paranoid`](crate::Device::set_paranoid), when a tie-break
+/// read matched neither of the first two reads. Registers of up to four
+/// bytes are carried whole; for longer blocks the four bytes starting at the
+/// first byte the reads disagreed on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InconsistentRead {
+    /// Address of the register read
+    pub address: u16,
+    /// Byte offset of the compared bytes within the register
+    pub offset: u8,
+    /// Compared bytes of the first read, big-endian
+    pub first: u32,
+    /// Compared bytes of the second read, big-endian
+    pub second: u32,
+}
+
+impl InconsistentRead {
+    /// Compares two disagreeing reads of the register at `address`
+    pub(crate) fn between(address: u16, first: &[u8], second: &[u8]) -> Self {
+        let offset = if first.len() <= 4 {
+            0
+        } else {
+            first
+                .iter()
+                .zip(second)
+                .position(|(a, b)| a != b)
+                .unwrap_or(0)
+        };
+        let word = |bytes: &[u8]| {
+            bytes[offset..]
+                .iter()
+                .take(4)
+                .fold(0, |word, &byte| word << 8 | u32::from(byte))
+        };
+        Self {
+            address,
+            offset: offset as u8,
+            first: word(first),
+            second: word(second),
+        }
+    }
+}
+
+impl fmt::Display for InconsistentRead {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "register 0x{:04X}+{} read as 0x{:X} then 0x{:X}",
+            self.address, self.offset, self.first, self.second
+        )
+    }
+}
+
+/// Intermeasurement period too short for the measurements it must hold
+///
+/// Returned in [`Error::PeriodTooShort`] with the offending period and the
+/// shortest one that would have been accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PeriodTooShort {
+    /// Configured or requested intermeasurement period
+    pub period: Duration,
+    /// Shortest intermeasurement period that fits the measurements
+    pub required: Duration,
+}
+
+impl fmt::Display for PeriodTooShort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} period, {:?} required", self.period, self.required)
+    }
+}
+
+/// Error type for device operations
+///
+/// Covers the transport and codec failures reported by the register layer as
+/// well as the failure modes of the high-level measurement helpers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// I2C communication failed
+    BusError(ErrorContext),
+    /// Failed to serialize a register value
+    SerializationError(ErrorContext),
+    /// Failed to parse a register value
+    DeserializationError(ErrorContext),
+    /// The device did not report a result within the polling budget
+    Timeout,
+    /// Continuous mode stopped producing samples, see
+    /// [`StallWatchdog`](crate::watchdog::StallWatchdog)
+    Stalled,
+    /// Driving or reading a GPIO pin failed
+    PinError,
+    /// The range measurement completed with an error status
+    RangeError(RangeErrorCode),
+    /// The ALS measurement completed with an error status
+    AlsError(AlsErrorCode),
+    /// A register write in strict mode was outside the datasheet range
+    OutOfSpec(LimitViolation),
+    /// A configuration write was refused because a measurement is running,
+    /// see [`Device::set_busy_check`](crate::Device::set_busy_check), or a
+    /// measurement could not be fitted around a running one
+    DeviceBusy,
+    /// The intermeasurement period is shorter than the measurements it must
+    /// hold
+    PeriodTooShort(PeriodTooShort),
+    /// The interrupt pin was asserted but no interrupt source was pending
+    SpuriousInterrupt,
+    /// Repeated reads of a result register disagreed, see
+    /// [`Device::set_paranoid`](crate::Device::set_paranoid)
+    InconsistentRead(InconsistentRead),
+    /// The device at the address reported a model ID other than the
+    /// VL6180X's 0xB4
+    UnexpectedModel(u8),
+    /// Nothing answered at an address that looks like the default address
+    /// in 8-bit notation, see [`Address::looks_8bit`]
+    EightBitAddress(Address),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BusError(context) => write!(f, "I2C bus error {}", context),
+            Self::SerializationError(context) => {
+                write!(f, "Failed to serialize register value {}", context)
+            }
+            Self::DeserializationError(context) => {
+                write!(f, "Failed to deserialize register value {}", context)
+            }
+            Self::Timeout => write!(f, "Timed out waiting for the device"),
+            Self::Stalled => write!(f, "Device stopped producing samples"),
+            Self::PinError => write!(f, "GPIO pin error"),
+            Self::RangeError(code) => write!(f, "Range measurement error: {:?}", code),
+            Self::AlsError(code) => write!(f, "ALS measurement error: {:?}", code),
+            Self::OutOfSpec(violation) => write!(f, "Out of datasheet range: {}", violation),
+            Self::DeviceBusy => write!(f, "Device is busy with a measurement"),
+            Self::PeriodTooShort(short) => {
+                write!(f, "Intermeasurement period is too short: {}", short)
+            }
+            Self::SpuriousInterrupt => write!(f, "Interrupt pin asserted with nothing pending"),
+            Self::InconsistentRead(read) => write!(f, "Inconsistent result reads: {}", read),
+            Self::UnexpectedModel(id) => write!(f, "Unexpected model ID 0x{:02X}", id),
+            Self::EightBitAddress(address) => write!(
+                f,
+                "No device at 0x{:02X}, the 8-bit notation of 0x{:02X}; use Address::from_8bit",
+                address.get(),
+                address.get() >> 1
+            ),
+        }
+    }
+}
+
+impl Error {
+    /// Returns where a transport or codec failure happened.
+    ///
+    /// `None` for the other variants.
+    pub fn context(&self) -> Option<ErrorContext> {
+        match *self {
+            Self::BusError(context)
+            | Self::SerializationError(context)
+            | Self::DeserializationError(context) => Some(context),
+            _ => None,
+        }
+    }
+
+    /// Attributes a failure to step `accesses` of a multi-step helper
+    ///
+    /// `accesses` counts the accesses issued so far. Serialization fails
+    /// before its access is issued, so it is attributed to the next one.
+    pub(crate) fn in_operation(self, name: &'static str, accesses: u16) -> Self {
+        let attach = |mut context: ErrorContext, step: u16| {
+            context
+                .operation
+                .get_or_insert(OperationStep { name, step });
+            context
+        };
+        match self {
+            Self::BusError(context) => Self::BusError(attach(context, accesses)),
+            Self::SerializationError(context) => {
+                Self::SerializationError(attach(context, accesses.saturating_add(1)))
+            }
+            Self::DeserializationError(context) => {
+                Self::DeserializationError(attach(context, accesses))
+            }
+            other => other,
         }
     }
 }
 
+impl From<core::convert::Infallible> for RegisterError {
+    fn from(never: core::convert::Infallible) -> Self {
+        match never {}
+    }
+}
+
 impl From<jiff::Error> for RegisterError {
     fn from(_: jiff::Error) -> Self {
         Self::InvalidTimestamp
@@ -38,22 +436,245 @@ impl From<jiff::Error> for RegisterError {
 }
 
 /// Luminance measurement in lux
-#[derive(Debug, Clone, Copy, PartialEq)]
+///
+/// Equality, hashing and ordering compare the light level rounded to the
+/// nearest millilux rather than the raw `f32`, so two readings are equal
+/// when they round to the same millilux. `NaN` compares equal to zero.
+///
+/// Light levels can be added, subtracted (saturating at zero), scaled by an
+/// `f32` and summed. The operators follow `f32` and can overflow to
+/// infinity; [`checked_add`](Self::checked_add),
+/// [`checked_mul`](Self::checked_mul) and [`checked_div`](Self::checked_div)
+/// return `None` instead. For example, to average a series of readings:
+///
+/// ```
+/// use vl6180x::Luminance;
+///
+/// let readings = [Luminance::from_lux(10.0), Luminance::from_lux(20.0)];
+/// let average = readings.iter().sum::<Luminance>() / readings.len() as f32;
+/// assert_eq!(average, Luminance::from_millilux(15_000));
+/// ```
+#[derive(Debug, Clone, Copy)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Luminance {
     pub lux: f32,
 }
 
+impl Luminance {
+    /// Factory calibrated ALS lux resolution in lux/count at gain 1 and 100ms integration
+    pub const LUX_RESOLUTION: f32 = 0.32;
+
+    /// Creates a light level in lux.
+    pub const fn from_lux(lux: f32) -> Self {
+        Self { lux }
+    }
+
+    /// Creates a light level in millilux.
+    pub const fn from_millilux(millilux: u32) -> Self {
+        Self {
+            lux: millilux as f32 / 1000.0,
+        }
+    }
+
+    /// Converts a raw ALS count into a light level
+    ///
+    /// Applies the datasheet conversion using the factory calibrated lux
+    /// resolution, the [actual analog gain](AlsGain::actual_gain) and the
+    /// integration period the count was measured with.
+    pub fn from_counts(counts: u16, gain: AlsGain, integration: Duration) -> Self {
+        Self::from_counts_with_gain(counts, gain.actual_gain(), integration)
+    }
+
+    /// Converts a raw ALS count into a light level using a gain factor of
+    /// its own
+    ///
+    /// Like [`from_counts`](Self::from_counts), for a gain calibrated on the
+    /// individual unit instead of the characterized
+    /// [`actual_gain`](AlsGain::actual_gain).
+    pub fn from_counts_with_gain(counts: u16, gain: f32, integration: Duration) -> Self {
+        let integration_ms = integration.as_secs_f32() * 1000.0;
+        Self {
+            lux: Self::LUX_RESOLUTION * (counts as f32 / gain) * (100.0 / integration_ms),
+        }
+    }
+
+    /// Converts a light level into the raw ALS count measuring it
+    ///
+    /// The inverse of [`from_counts`](Self::from_counts), rounded to the
+    /// nearest count and saturating at the 16-bit result range.
+    pub fn to_counts(self, gain: AlsGain, integration: Duration) -> u16 {
+        self.to_counts_with_gain(gain.actual_gain(), integration)
+    }
+
+    /// Converts a light level into the raw ALS count measuring it with a
+    /// gain factor of its own
+    ///
+    /// The inverse of [`from_counts_with_gain`](Self::from_counts_with_gain).
+    pub fn to_counts_with_gain(self, gain: f32, integration: Duration) -> u16 {
+        let integration_ms = integration.as_secs_f32() * 1000.0;
+        let counts = self.lux / Self::LUX_RESOLUTION * gain * (integration_ms / 100.0);
+        (counts + 0.5) as u16
+    }
+
+    /// Adds two light levels, returning `None` if the sum is not finite
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        Self::checked(self.lux + rhs.lux)
+    }
+
+    /// Scales a light level, returning `None` if the result is negative or
+    /// not finite
+    pub fn checked_mul(self, rhs: f32) -> Option<Self> {
+        Self::checked(self.lux * rhs)
+    }
+
+    /// Divides a light level, returning `None` if the result is negative or
+    /// not finite, e.g. when dividing by zero
+    pub fn checked_div(self, rhs: f32) -> Option<Self> {
+        Self::checked(self.lux / rhs)
+    }
+
+    /// Light level of `lux` if it is finite and not negative
+    fn checked(lux: f32) -> Option<Self> {
+        (lux.is_finite() && lux >= 0.0).then_some(Self::from_lux(lux))
+    }
+
+    /// Light level rounded to the nearest millilux, used for comparisons
+    fn millilux(self) -> i64 {
+        let millilux = self.lux as f64 * 1000.0;
+        if millilux < 0.0 {
+            (millilux - 0.5) as i64
+        } else {
+            (millilux + 0.5) as i64
+        }
+    }
+}
+
+impl PartialEq for Luminance {
+    fn eq(&self, other: &Self) -> bool {
+        self.millilux() == other.millilux()
+    }
+}
+
+impl Eq for Luminance {}
+
+impl Hash for Luminance {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.millilux().hash(state);
+    }
+}
+
+impl PartialOrd for Luminance {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Luminance {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.millilux().cmp(&other.millilux())
+    }
+}
+
+impl Add for Luminance {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::from_lux(self.lux + rhs.lux)
+    }
+}
+
+/// Saturates at zero: a light level is never negative.
+impl Sub for Luminance {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::from_lux((self.lux - rhs.lux).max(0.0))
+    }
+}
+
+impl Mul<f32> for Luminance {
+    type Output = Self;
+
+    fn mul(self, rhs: f32) -> Self {
+        Self::from_lux(self.lux * rhs)
+    }
+}
+
+impl Div<f32> for Luminance {
+    type Output = Self;
+
+    fn div(self, rhs: f32) -> Self {
+        Self::from_lux(self.lux / rhs)
+    }
+}
+
+impl Sum for Luminance {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::from_lux(0.0), Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Luminance> for Luminance {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
 impl fmt::Display for Luminance {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{} lux", self.lux)
     }
 }
 
+/// Photon count rate in mega counts per second
+///
+/// Stored in the 9.7 fixed point format of the device's rate registers, so
+/// a rate read from a register converts back to exactly the same bytes.
+///
+/// ```
+/// use vl6180x::Mcps;
+///
+/// let rate = Mcps::from_mcps(0.75);
+/// assert_eq!(rate.fixed, 96);
+/// assert_eq!(rate.as_mcps(), 0.75);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Mcps {
+    /// Rate in 9.7 fixed point: units of 1/128 Mcps
+    pub fixed: u16,
+}
+
+impl Mcps {
+    /// Creates a rate from its 9.7 fixed point representation.
+    pub const fn from_fixed(fixed: u16) -> Self {
+        Self { fixed }
+    }
+
+    /// Creates a rate in Mcps, rounded to the nearest 1/128 Mcps and
+    /// saturating at 0 and at just below 512 Mcps.
+    pub fn from_mcps(mcps: f32) -> Self {
+        Self {
+            fixed: (mcps * 128.0 + 0.5) as u16,
+        }
+    }
+
+    /// Returns the rate in Mcps.
+    pub fn as_mcps(&self) -> f32 {
+        f32::from(self.fixed) / 128.0
+    }
+}
+
+impl fmt::Display for Mcps {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} Mcps", self.as_mcps())
+    }
+}
+
 /// ALS error codes
 ///
 /// These error codes are returned in the RESULT__ALS_STATUS register.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum AlsErrorCode {
@@ -78,11 +699,94 @@ impl TryFrom<u8> for AlsErrorCode {
     }
 }
 
+/// Classified outcome of an ALS measurement
+///
+/// Separates the ends of the measurement range from valid light levels, so
+/// callers such as a gain control loop can react to them directly.
+///
+/// # Example
+/// ```
+/// use core::time::Duration;
+/// use vl6180x::{AlsErrorCode, AlsGain, AlsReading};
+///
+/// let integration = Duration::from_millis(100);
+///
+/// let reading = AlsReading::new(AlsErrorCode::NoError, 0xFFFF, AlsGain::Gain20, integration);
+/// assert_eq!(reading, AlsReading::Saturated);
+/// assert_eq!(reading.ok(), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AlsReading {
+    /// A valid light level
+    Valid(Luminance),
+    /// The light level is above the measurable range at the current gain
+    Saturated,
+    /// The light level is below the measurable range at the current gain
+    Dark,
+    /// The measurement failed with this status code
+    ///
+    /// Not produced for any of the currently defined [`AlsErrorCode`]s, which
+    /// all have a more specific meaning.
+    Failed(AlsErrorCode),
+}
+
+impl AlsReading {
+    /// Raw count reported when the ALS counter is at full scale
+    pub const RAW_SATURATED: u16 = 0xFFFF;
+
+    /// Classifies a raw ALS count by its status code.
+    ///
+    /// An overflow status or a full scale count is [`Saturated`](Self::Saturated)
+    /// and an underflow status is [`Dark`](Self::Dark). Valid counts are
+    /// converted as by [`Luminance::from_counts`].
+    pub fn new(code: AlsErrorCode, counts: u16, gain: AlsGain, integration: Duration) -> Self {
+        match code {
+            AlsErrorCode::Overflow => Self::Saturated,
+            AlsErrorCode::Underflow => Self::Dark,
+            AlsErrorCode::NoError if counts == Self::RAW_SATURATED => Self::Saturated,
+            AlsErrorCode::NoError => Self::Valid(Luminance::from_counts(counts, gain, integration)),
+        }
+    }
+
+    /// Returns the light level of a valid reading.
+    pub fn ok(self) -> Option<Luminance> {
+        match self {
+            Self::Valid(luminance) => Some(luminance),
+            Self::Saturated | Self::Dark | Self::Failed(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for AlsReading {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Valid(luminance) => write!(f, "{}", luminance),
+            Self::Saturated => write!(f, "saturated"),
+            Self::Dark => write!(f, "dark"),
+            Self::Failed(code) => write!(f, "failed: {:?}", code),
+        }
+    }
+}
+
+/// The latest sample of continuous ALS measurements
+///
+/// Returned by [`Device::read_latest_als`](crate::Device::read_latest_als).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LatestAls {
+    /// The classified sample
+    pub reading: AlsReading,
+    /// Whether the sample was reported since the previous read, rather than
+    /// read again
+    pub new: bool,
+}
+
 /// Range error codes from Table 12 of the datasheet
 ///
 /// These error codes are returned in the RESULT__RANGE_STATUS register
 /// to indicate various error conditions during range measurements.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum RangeErrorCode {
@@ -145,6 +849,131 @@ impl RangeErrorCode {
     pub const fn is_valid(&self) -> bool {
         matches!(self, Self::NoError)
     }
+
+    /// Classifies what the status code says about the measurement.
+    ///
+    /// Codes reporting that no return signal strong enough to range on was
+    /// received (early convergence estimate, maximum convergence time, no
+    /// target ignore) and the overflow codes, which the device reports for
+    /// targets beyond its range, mean nothing was in range. A low
+    /// signal-to-noise ratio or an underflow make a single measurement
+    /// unusable, while the VCSEL and PLL codes are hardware faults.
+    pub const fn classification(&self) -> RangeErrorClass {
+        match self {
+            Self::NoError => RangeErrorClass::Valid,
+            Self::EarlyConvergenceEstimate
+            | Self::MaxConvergence
+            | Self::NoTargetIgnore
+            | Self::RawRangingOverflow
+            | Self::RangingOverflow => RangeErrorClass::NoTarget,
+            Self::SignalToNoiseRatio | Self::RawRangingUnderflow | Self::RangingUnderflow => {
+                RangeErrorClass::SignalQuality
+            }
+            Self::VcselContinuityTest
+            | Self::VcselWatchdogTest
+            | Self::VcselWatchdog
+            | Self::Pll1Lock
+            | Self::Pll2Lock => RangeErrorClass::HardwareFault,
+        }
+    }
+}
+
+/// What a [`RangeErrorCode`] says about a measurement
+///
+/// Returned by [`RangeErrorCode::classification`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RangeErrorClass {
+    /// The measurement is valid
+    Valid,
+    /// Nothing was in range
+    NoTarget,
+    /// The signal was too poor for this measurement
+    SignalQuality,
+    /// The VCSEL or a PLL failed
+    HardwareFault,
+}
+
+impl RangeErrorClass {
+    /// Returns whether another measurement may succeed without intervention.
+    pub const fn is_transient(&self) -> bool {
+        matches!(self, Self::NoTarget | Self::SignalQuality)
+    }
+}
+
+/// Formats a [`Length`] in millimetres, for the `defmt::Format` impls of
+/// types holding one
+#[cfg(feature = "defmt")]
+pub(crate) struct Millimeters(pub(crate) Length);
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Millimeters {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{=f64}mm", self.0.as_millimeters());
+    }
+}
+
+/// Classified outcome of a range measurement
+///
+/// Separates "nothing in range", which is a normal outcome for a proximity
+/// sensor, from measurements that failed.
+///
+/// # Example
+/// ```
+/// use measurements::Length;
+/// use vl6180x::{RangeErrorCode, RangeReading};
+///
+/// let readings = [
+///     RangeReading::new(RangeErrorCode::NoError, Length::from_millimeters(40.0)),
+///     RangeReading::new(RangeErrorCode::MaxConvergence, Length::from_millimeters(255.0)),
+///     RangeReading::new(RangeErrorCode::NoError, Length::from_millimeters(60.0)),
+/// ];
+///
+/// // Skip samples without a target when averaging
+/// let (sum, count) = readings
+///     .iter()
+///     .filter_map(|reading| reading.ok())
+///     .fold((0.0, 0), |(sum, count), distance| (sum + distance.as_millimeters(), count + 1));
+/// assert_eq!(sum / count as f64, 50.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RangeReading {
+    /// A target was measured at this distance
+    Valid(Length),
+    /// Nothing was in range
+    NoTarget,
+    /// The measurement failed with this status code
+    Failed(RangeErrorCode),
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for RangeReading {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            Self::Valid(distance) => defmt::write!(f, "Valid({})", Millimeters(*distance)),
+            Self::NoTarget => defmt::write!(f, "NoTarget"),
+            Self::Failed(code) => defmt::write!(f, "Failed({})", code),
+        }
+    }
+}
+
+impl RangeReading {
+    /// Classifies a measured distance by its status code.
+    pub fn new(code: RangeErrorCode, distance: Length) -> Self {
+        match code.classification() {
+            RangeErrorClass::Valid => Self::Valid(distance),
+            RangeErrorClass::NoTarget => Self::NoTarget,
+            RangeErrorClass::SignalQuality | RangeErrorClass::HardwareFault => Self::Failed(code),
+        }
+    }
+
+    /// Returns the distance of a valid reading.
+    pub fn ok(self) -> Option<Length> {
+        match self {
+            Self::Valid(distance) => Some(distance),
+            Self::NoTarget | Self::Failed(_) => None,
+        }
+    }
 }
 
 /// ALS (Ambient Light Sensor) analog gain settings
@@ -152,11 +981,11 @@ impl RangeErrorCode {
 /// The VL6180X supports 8 different analog gain settings for the ALS.
 /// Higher gain settings provide better sensitivity in low-light conditions
 /// but have a reduced maximum measurable light level.
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum AlsGain {
-    /// Gain = 20 (highest gain, lowest max lux)
+    /// Gain = 20
     Gain20 = 0,
     /// Gain = 10
     Gain10 = 1,
@@ -168,10 +997,10 @@ pub enum AlsGain {
     Gain1_67 = 4,
     /// Gain = 1.25
     Gain1_25 = 5,
-    /// Gain = 1.0 (default)
+    /// Gain = 1.0 (lowest gain, highest max lux, default)
     #[default]
     Gain1 = 6,
-    /// Gain = 40 (lowest gain, highest max lux)
+    /// Gain = 40 (highest gain, lowest max lux)
     Gain40 = 7,
 }
 
@@ -195,6 +1024,9 @@ impl TryFrom<u8> for AlsGain {
 
 impl AlsGain {
     /// Get the numeric gain value
+    ///
+    /// This is the nominal design gain; light levels are converted with the
+    /// [`actual_gain`](AlsGain::actual_gain).
     pub const fn gain(&self) -> f32 {
         match self {
             Self::Gain20 => 20.0,
@@ -207,10 +1039,192 @@ impl AlsGain {
             Self::Gain40 => 40.0,
         }
     }
+
+    /// Get the characterized analog gain
+    ///
+    /// The actual gains of the settings below 20 differ from their nominal
+    /// [`gain`](AlsGain::gain) by up to 4% (datasheet table 17). Lux
+    /// conversions, [`full_scale`](AlsGain::full_scale) and
+    /// [`for_max_lux`](AlsGain::for_max_lux) use these values. To use gains
+    /// calibrated on an individual unit instead, convert counts with
+    /// [`Luminance::from_counts_with_gain`].
+    pub const fn actual_gain(&self) -> f32 {
+        match self {
+            Self::Gain20 => 20.0,
+            Self::Gain10 => 10.32,
+            Self::Gain5 => 5.21,
+            Self::Gain2_5 => 2.60,
+            Self::Gain1_67 => 1.72,
+            Self::Gain1_25 => 1.28,
+            Self::Gain1 => 1.01,
+            Self::Gain40 => 40.0,
+        }
+    }
+
+    /// Every gain setting, from the highest gain to the lowest
+    const BY_SENSITIVITY: [Self; 8] = [
+        Self::Gain40,
+        Self::Gain20,
+        Self::Gain10,
+        Self::Gain5,
+        Self::Gain2_5,
+        Self::Gain1_67,
+        Self::Gain1_25,
+        Self::Gain1,
+    ];
+
+    /// Factor by which full scale must exceed the expected maximum in
+    /// [`for_max_lux`](AlsGain::for_max_lux)
+    pub const HEADROOM: f32 = 1.25;
+
+    /// Light level at which the ALS count saturates with this gain
+    ///
+    /// Full scale is inversely proportional to the integration period.
+    pub fn full_scale(&self, integration: Duration) -> Luminance {
+        Luminance::from_counts(u16::MAX, *self, integration)
+    }
+
+    /// Picks the highest gain that can measure up to `max`.
+    ///
+    /// Chooses the most sensitive gain whose full scale at `integration` is at
+    /// least [`HEADROOM`](AlsGain::HEADROOM) times `max`. When even
+    /// [`AlsGain::Gain1`] falls short, it is returned as
+    /// [`GainFit::Saturates`]: bright light will saturate the ALS, so shorten
+    /// the integration period if possible.
+    ///
+    /// # Example
+    /// ```
+    /// use core::time::Duration;
+    /// use vl6180x::{AlsGain, GainFit, Luminance};
+    ///
+    /// let indoors = AlsGain::for_max_lux(Luminance::from_lux(2000.0), Duration::from_millis(100));
+    /// assert_eq!(indoors, GainFit::Fits(AlsGain::Gain5));
+    ///
+    /// let sunlight = AlsGain::for_max_lux(Luminance::from_lux(50_000.0), Duration::from_millis(100));
+    /// assert_eq!(sunlight, GainFit::Saturates(AlsGain::Gain1));
+    /// ```
+    pub fn for_max_lux(max: Luminance, integration: Duration) -> GainFit {
+        let required = max.lux * Self::HEADROOM;
+        Self::BY_SENSITIVITY
+            .into_iter()
+            .find(|gain| gain.full_scale(integration).lux >= required)
+            .map_or(GainFit::Saturates(Self::Gain1), GainFit::Fits)
+    }
+}
+
+/// Gain selected by [`AlsGain::for_max_lux`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum GainFit {
+    /// The gain covers the expected maximum with headroom
+    Fits(AlsGain),
+    /// No gain covers the expected maximum; contains the lowest gain
+    Saturates(AlsGain),
+}
+
+impl GainFit {
+    /// Returns the selected gain.
+    pub fn gain(self) -> AlsGain {
+        match self {
+            Self::Fits(gain) | Self::Saturates(gain) => gain,
+        }
+    }
+}
+
+/// How a new intermeasurement period was applied, see
+/// [`Device::update_range_period`](crate::Device::update_range_period)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PeriodUpdate {
+    /// No continuous measurement was running; the period was written in
+    /// place and applies from the next start
+    InPlace,
+    /// Continuous measurements were stopped, reconfigured and restarted
+    Restarted,
+}
+
+/// Decision of a bus recovery hook, see
+/// [`Device::set_bus_recovery`](crate::Device::set_bus_recovery)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BusRecovery {
+    /// The bus was recovered; issue the failed transaction again
+    Retry,
+    /// Report the failure as `Error::BusError`
+    GiveUp,
+}
+
+/// Bus recovery hook, called with the kind of the failed transaction's error
+///
+/// A plain function, so closures must not capture anything; keep state the
+/// recovery needs, such as the pins of a bus clear, in a `static`.
+pub type BusRecoveryHook = fn(embedded_hal::i2c::ErrorKind) -> BusRecovery;
+
+/// Result of a duty-cycled measurement, see
+/// [`Device::duty_cycle_measurement`](crate::Device::duty_cycle_measurement)
+///
+/// Both times are counted in the poll intervals the driver waited, so they
+/// leave out the time spent on the bus.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DutyCycle<T> {
+    /// The measurement taken while the sensor was powered
+    pub reading: T,
+    /// Time from releasing XSHUT until the firmware reported its boot
+    pub boot_time: Duration,
+    /// Time from starting the measurement until its sample was reported
+    pub measurement_time: Duration,
+}
+
+/// Timeouts used by the wait and poll helpers of a [`Device`](crate::Device)
+///
+/// Set with [`Device::set_timeouts`](crate::Device::set_timeouts). Helpers
+/// taking their own timeout argument use it instead of these defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Timeouts {
+    /// Maximum time to wait for a range sample or for ranging to stop
+    pub range: Duration,
+    /// Maximum time to wait for an ALS sample or for ALS measurements to stop
+    pub als: Duration,
+    /// Maximum time to wait for the firmware to boot
+    pub boot: Duration,
+}
+
+impl Timeouts {
+    /// Longest range measurement: the 63ms maximum convergence time plus
+    /// pre-calibration and readout averaging
+    const WORST_RANGE: Duration = RangeMaxConvergenceTime {
+        time: Duration::from_millis(63),
+    }
+    .measurement_time();
+
+    /// Longest ALS measurement: the 512ms maximum integration period
+    const WORST_ALS: Duration = Duration::from_millis(512);
+
+    /// MCU boot time t4 from datasheet table 4
+    const WORST_BOOT: Duration = Duration::from_millis(1);
+
+    /// Adds half of `worst` as margin for bus latency and polling
+    const fn with_margin(worst: Duration) -> Duration {
+        worst.saturating_add(Duration::from_nanos(worst.as_nanos() as u64 / 2))
+    }
+}
+
+impl Default for Timeouts {
+    /// Worst-case measurement and boot times with a 50% margin: about 106ms
+    /// for ranging, 768ms for the ALS and 1.5ms for booting.
+    fn default() -> Self {
+        Self {
+            range: Self::with_margin(Self::WORST_RANGE),
+            als: Self::with_margin(Self::WORST_ALS),
+            boot: Self::with_margin(Self::WORST_BOOT),
+        }
+    }
 }
 
 /// GPIO polarity configuration
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum GpioPolarity {
     /// Active low (default)
@@ -232,8 +1246,21 @@ impl TryFrom<u8> for GpioPolarity {
     }
 }
 
+/// What the interrupt pin wait does when GPIO1 asserts with nothing pending
+///
+/// See [`Device::set_spurious_interrupt_policy`](crate::Device::set_spurious_interrupt_policy).
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SpuriousInterruptPolicy {
+    /// Fail with `Error::SpuriousInterrupt` (default)
+    #[default]
+    Report,
+    /// Wait for the pin to deassert and assert again, until the timeout
+    KeepWaiting,
+}
+
 /// GPIO function selection
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum GpioFunction {
     /// GPIO is in high-impedance off state (default)
@@ -256,7 +1283,7 @@ impl TryFrom<u8> for GpioFunction {
 }
 
 /// Interrupt mode configuration for both ranging and ALS
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum InterruptMode {
@@ -287,3 +1314,224 @@ impl TryFrom<u8> for InterruptMode {
         }
     }
 }
+
+/// Range interrupt condition together with the thresholds it compares against
+///
+/// The threshold registers power up as zero, so a level or window interrupt
+/// without programmed thresholds fires on every sample. Each threshold-based
+/// condition therefore carries its thresholds.
+///
+/// ```compile_fail
+/// use vl6180x::RangeInterrupt;
+///
+/// // A level interrupt cannot be selected without its threshold
+/// let _ = RangeInterrupt::LevelHigh {};
+/// ```
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub enum RangeInterrupt {
+    /// Interrupts disabled
+    #[default]
+    Disabled,
+    /// Interrupt when the distance is below `low`
+    LevelLow {
+        /// Low threshold
+        low: Length,
+    },
+    /// Interrupt when the distance is above `high`
+    LevelHigh {
+        /// High threshold
+        high: Length,
+    },
+    /// Interrupt when the distance is below `low` or above `high`
+    OutOfWindow {
+        /// Low threshold
+        low: Length,
+        /// High threshold
+        high: Length,
+    },
+    /// Interrupt on every new sample
+    NewSampleReady,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for RangeInterrupt {
+    fn format(&self, f: defmt::Formatter) {
+        match *self {
+            Self::Disabled => defmt::write!(f, "Disabled"),
+            Self::LevelLow { low } => defmt::write!(f, "LevelLow {{ low: {} }}", Millimeters(low)),
+            Self::LevelHigh { high } => {
+                defmt::write!(f, "LevelHigh {{ high: {} }}", Millimeters(high))
+            }
+            Self::OutOfWindow { low, high } => defmt::write!(
+                f,
+                "OutOfWindow {{ low: {}, high: {} }}",
+                Millimeters(low),
+                Millimeters(high)
+            ),
+            Self::NewSampleReady => defmt::write!(f, "NewSampleReady"),
+        }
+    }
+}
+
+impl RangeInterrupt {
+    /// The interrupt mode selected by this condition.
+    pub fn mode(&self) -> InterruptMode {
+        match self {
+            Self::Disabled => InterruptMode::Disabled,
+            Self::LevelLow { .. } => InterruptMode::LevelLow,
+            Self::LevelHigh { .. } => InterruptMode::LevelHigh,
+            Self::OutOfWindow { .. } => InterruptMode::OutOfWindow,
+            Self::NewSampleReady => InterruptMode::NewSampleReady,
+        }
+    }
+
+    /// The thresholds to program, or `None` if the condition does not use any.
+    ///
+    /// Level conditions use [`RangeThresholds::below`] and
+    /// [`RangeThresholds::above`].
+    pub fn thresholds(&self) -> Option<RangeThresholds> {
+        match *self {
+            Self::Disabled | Self::NewSampleReady => None,
+            Self::LevelLow { low } => Some(RangeThresholds::below(low)),
+            Self::LevelHigh { high } => Some(RangeThresholds::above(high)),
+            Self::OutOfWindow { low, high } => Some(RangeThresholds { high, low }),
+        }
+    }
+}
+
+/// Continuous ranging configuration restored after a wake, see
+/// [`Device::disarm_and_resume`](crate::Device::disarm_and_resume)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RangeSchedule {
+    /// Intermeasurement period of continuous ranging
+    pub period: Duration,
+    /// Range interrupt condition
+    pub interrupt: RangeInterrupt,
+}
+
+/// Bounds and thresholds for adapting the range convergence time limit, see
+/// [`Device::enable_adaptive_timing`](crate::Device::enable_adaptive_timing)
+///
+/// After every measurement the limit moves up one `step` if the measurement
+/// failed to converge, the ambient light is bright or most of the limit was
+/// used, and down one `step` if the ambient light is dark and little of the
+/// limit was used. It always stays within `min_convergence` and
+/// `max_convergence`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AdaptiveTiming {
+    /// Shortest convergence time limit, at least 1ms
+    pub min_convergence: Duration,
+    /// Longest convergence time limit, at most 63ms
+    pub max_convergence: Duration,
+    /// Change of the limit per measurement
+    pub step: Duration,
+    /// Ambient counts per millisecond of convergence at or above which the
+    /// limit is raised
+    pub bright_ambient: u32,
+    /// Ambient counts per millisecond of convergence at or below which the
+    /// limit may be lowered
+    pub dark_ambient: u32,
+    /// Percentage of the limit used at or above which the limit is raised
+    pub high_utilization: u8,
+    /// Percentage of the limit used at or below which the limit may be lowered
+    pub low_utilization: u8,
+}
+
+impl AdaptiveTiming {
+    /// Returns the convergence time limit to use after a measurement.
+    ///
+    /// # Arguments
+    /// * `current` - Convergence time limit the measurement ran with
+    /// * `error_code` - Status of the measurement
+    /// * `result` - Diagnostics of the measurement
+    ///
+    /// # Panics
+    /// If `min_convergence` is greater than `max_convergence`.
+    pub fn next_convergence(
+        &self,
+        current: Duration,
+        error_code: RangeErrorCode,
+        result: &RangeResultBlock,
+    ) -> Duration {
+        let used_ms = result.return_convergence_time.as_millis().max(1);
+        let ambient = u128::from(result.return_ambient_count) / used_ms;
+        let utilization =
+            result.return_convergence_time.as_micros() * 100 / current.as_micros().max(1);
+
+        let failed = matches!(
+            error_code,
+            RangeErrorCode::EarlyConvergenceEstimate | RangeErrorCode::MaxConvergence
+        );
+        let next = if failed
+            || ambient >= u128::from(self.bright_ambient)
+            || utilization >= u128::from(self.high_utilization)
+        {
+            current.saturating_add(self.step)
+        } else if ambient <= u128::from(self.dark_ambient)
+            && utilization <= u128::from(self.low_utilization)
+        {
+            current.saturating_sub(self.step)
+        } else {
+            current
+        };
+        next.clamp(self.min_convergence, self.max_convergence)
+    }
+}
+
+/// ALS interrupt condition together with the thresholds it compares against
+///
+/// The ALS counterpart of [`RangeInterrupt`].
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AlsInterrupt {
+    /// Interrupts disabled
+    #[default]
+    Disabled,
+    /// Interrupt when the light level is below `low`
+    LevelLow {
+        /// Low threshold
+        low: Luminance,
+    },
+    /// Interrupt when the light level is above `high`
+    LevelHigh {
+        /// High threshold
+        high: Luminance,
+    },
+    /// Interrupt when the light level is below `low` or above `high`
+    OutOfWindow {
+        /// Low threshold
+        low: Luminance,
+        /// High threshold
+        high: Luminance,
+    },
+    /// Interrupt on every new sample
+    NewSampleReady,
+}
+
+impl AlsInterrupt {
+    /// The interrupt mode selected by this condition.
+    pub fn mode(&self) -> InterruptMode {
+        match self {
+            Self::Disabled => InterruptMode::Disabled,
+            Self::LevelLow { .. } => InterruptMode::LevelLow,
+            Self::LevelHigh { .. } => InterruptMode::LevelHigh,
+            Self::OutOfWindow { .. } => InterruptMode::OutOfWindow,
+            Self::NewSampleReady => InterruptMode::NewSampleReady,
+        }
+    }
+
+    /// The thresholds to program, or `None` if the condition does not use any.
+    ///
+    /// Level conditions use [`AlsThresholds::below`] and
+    /// [`AlsThresholds::above`].
+    pub fn thresholds(&self) -> Option<AlsThresholds> {
+        match *self {
+            Self::Disabled | Self::NewSampleReady => None,
+            Self::LevelLow { low } => Some(AlsThresholds::below(low)),
+            Self::LevelHigh { high } => Some(AlsThresholds::above(high)),
+            Self::OutOfWindow { low, high } => Some(AlsThresholds { high, low }),
+        }
+    }
+}
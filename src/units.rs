@@ -0,0 +1,93 @@
+//! `uom` quantities
+//!
+//! Converts the distances and light levels this crate measures into
+//! [`uom`] quantities, for code that does its dimensional analysis with
+//! `uom` instead of `measurements`. Only available with the `uom` feature.
+//!
+//! `uom` has no illuminance quantity. Illuminance in lux has the same
+//! dimension as luminance in candela per square meter, the steradian being
+//! dimensionless, so light levels are carried as [`Illuminance`], an alias of
+//! `uom`'s luminance whose value in candela per square meter is the value in
+//! lux.
+//!
+//! # Example
+//! ```
+//! use measurements::Length;
+//! use uom::si::length::millimeter;
+//! use vl6180x::units::{lux, AsUom, Illuminance};
+//! use vl6180x::{Luminance, RangeErrorCode, RangeReading};
+//!
+//! let reading = RangeReading::new(RangeErrorCode::NoError, Length::from_millimeters(42.0));
+//! assert_eq!(reading.ok_uom().unwrap().get::<millimeter>(), 42.0);
+//!
+//! let light: Illuminance = Luminance::from_lux(300.0).as_uom();
+//! assert_eq!(light.get::<lux>(), 300.0);
+//! ```
+
+use uom::si::f32;
+use uom::si::length::millimeter;
+
+use crate::types::{AlsReading, Luminance, RangeReading};
+
+/// Illuminance, stored as `uom`'s dimensionally identical luminance
+pub type Illuminance = f32::Luminance;
+
+/// Unit of [`Illuminance`] numerically equal to lux
+#[allow(non_camel_case_types)]
+pub type lux = uom::si::luminance::candela_per_square_meter;
+
+/// Conversion of a measured value into its `uom` quantity
+pub trait AsUom {
+    /// The `uom` quantity
+    type Quantity;
+
+    /// Returns the value as its `uom` quantity.
+    fn as_uom(&self) -> Self::Quantity;
+}
+
+impl AsUom for measurements::Length {
+    type Quantity = f32::Length;
+
+    fn as_uom(&self) -> f32::Length {
+        f32::Length::new::<millimeter>(self.as_millimeters() as _)
+    }
+}
+
+impl AsUom for Luminance {
+    type Quantity = Illuminance;
+
+    fn as_uom(&self) -> Illuminance {
+        Illuminance::new::<lux>(self.lux)
+    }
+}
+
+impl From<Luminance> for Illuminance {
+    fn from(light: Luminance) -> Self {
+        light.as_uom()
+    }
+}
+
+impl From<Illuminance> for Luminance {
+    fn from(light: Illuminance) -> Self {
+        Self::from_lux(light.get::<lux>())
+    }
+}
+
+/// Converts a `uom` length into a `measurements` length, e.g. for a threshold
+pub fn length(length: f32::Length) -> measurements::Length {
+    measurements::Length::from_millimeters(length.get::<millimeter>().into())
+}
+
+impl RangeReading {
+    /// Returns the distance of a valid reading as a `uom` length.
+    pub fn ok_uom(self) -> Option<f32::Length> {
+        self.ok().map(|distance| distance.as_uom())
+    }
+}
+
+impl AlsReading {
+    /// Returns the light level of a valid reading as a `uom` quantity.
+    pub fn ok_uom(self) -> Option<Illuminance> {
+        self.ok().map(|light| light.as_uom())
+    }
+}
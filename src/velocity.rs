@@ -0,0 +1,205 @@
+//! Approach velocity
+//!
+//! [`VelocityEstimator`] turns timestamped range readings into a filtered
+//! rate of change of the distance, e.g. to wake a display before a hand
+//! actually reaches it. It runs an alpha-beta filter, so it keeps no history
+//! and smooths out the 1mm quantization of the readings, which at short
+//! sample intervals would otherwise dominate a plain difference quotient.
+
+use crate::types::RangeReading;
+
+/// Filter parameters of a [`VelocityEstimator`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct VelocityConfig {
+    /// Weight, from 0.0 to below 1.0, the filter keeps on its previous
+    /// estimate at every reading; higher values smooth more but follow
+    /// changes of speed later
+    pub smoothing: f64,
+    /// Consecutive no-target readings tolerated before the target counts
+    /// as lost
+    pub dropouts: u8,
+    /// Longest time in ms between two valid readings of the same target;
+    /// after a longer gap tracking starts over
+    pub max_gap_ms: u32,
+}
+
+impl Default for VelocityConfig {
+    /// Hand-sized movements sampled every 10 to 50ms: a smoothing of 0.8,
+    /// two tolerated dropouts and a 250ms maximum gap
+    fn default() -> Self {
+        Self {
+            smoothing: 0.8,
+            dropouts: 2,
+            max_gap_ms: 250,
+        }
+    }
+}
+
+/// Current output of a [`VelocityEstimator`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct VelocityEstimate {
+    /// Filtered distance in millimeters
+    pub distance_mm: f64,
+    /// Rate of change of the distance in mm/s, negative while the target
+    /// approaches
+    pub mm_per_s: f64,
+    /// Share of the estimate backed by readings rather than by the initial
+    /// guess of a standing target, from 0.0 just after acquiring a target
+    /// towards 1.0
+    pub confidence: f64,
+}
+
+impl VelocityEstimate {
+    /// Returns the closing speed in mm/s, zero while the target is not
+    /// approaching.
+    pub fn closing_speed(&self) -> f64 {
+        (-self.mm_per_s).max(0.0)
+    }
+}
+
+/// Tracked target
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Track {
+    estimate: VelocityEstimate,
+    /// Tick of the last valid reading
+    tick_ms: u32,
+    /// Weight left on the initial guess, `smoothing` to the power of the
+    /// updates so far
+    prior: f64,
+    dropouts: u8,
+}
+
+/// Alpha-beta filter estimating the velocity of a target from its readings
+///
+/// Feed every reading together with a millisecond tick from the caller's
+/// clock. The tick may wrap around. The gains follow from the single
+/// smoothing constant θ as α = 1 − θ² and β = (1 − θ)², the critically
+/// damped choice of a fading-memory filter.
+///
+/// The first valid reading acquires a target at zero velocity. Failed
+/// readings are skipped, a few no-target readings in a row are bridged, and
+/// more of them or a long gap between valid readings lose the target.
+/// Readings with the same tick as the previous one are ignored.
+///
+/// # Example
+/// ```
+/// use measurements::Length;
+/// use vl6180x::velocity::{VelocityConfig, VelocityEstimator};
+/// use vl6180x::RangeReading;
+///
+/// let mut estimator = VelocityEstimator::new(VelocityConfig::default());
+/// let at = |mm| RangeReading::Valid(Length::from_millimeters(mm));
+///
+/// // A hand approaching at 200mm/s, sampled every 20ms
+/// for i in 0..30 {
+///     estimator.feed(i * 20, at(150.0 - 4.0 * i as f64));
+/// }
+/// let estimate = estimator.estimate().unwrap();
+/// assert!((estimate.closing_speed() - 200.0).abs() < 5.0);
+/// assert!(estimate.confidence > 0.99);
+/// ```
+#[derive(Debug, Clone)]
+pub struct VelocityEstimator {
+    config: VelocityConfig,
+    track: Option<Track>,
+}
+
+impl VelocityEstimator {
+    /// Creates an estimator without a target.
+    ///
+    /// A smoothing outside of 0.0 to 0.99 is clamped to that range.
+    pub fn new(config: VelocityConfig) -> Self {
+        Self {
+            config: VelocityConfig {
+                smoothing: config.smoothing.clamp(0.0, 0.99),
+                ..config
+            },
+            track: None,
+        }
+    }
+
+    /// Returns the parameters of the estimator.
+    pub fn config(&self) -> VelocityConfig {
+        self.config
+    }
+
+    /// Returns the current estimate, or `None` without a target.
+    pub fn estimate(&self) -> Option<VelocityEstimate> {
+        self.track.map(|track| track.estimate)
+    }
+
+    /// Forgets the target.
+    pub fn reset(&mut self) {
+        self.track = None;
+    }
+
+    /// Processes the next reading, returning the updated estimate.
+    ///
+    /// # Arguments
+    /// * `tick_ms` - Time of the reading in milliseconds
+    /// * `reading` - The range reading
+    pub fn feed(&mut self, tick_ms: u32, reading: RangeReading) -> Option<VelocityEstimate> {
+        match reading {
+            RangeReading::Valid(distance) => self.update(tick_ms, distance.as_millimeters()),
+            RangeReading::NoTarget => {
+                if let Some(track) = &mut self.track {
+                    if track.dropouts < self.config.dropouts {
+                        track.dropouts += 1;
+                    } else {
+                        self.track = None;
+                    }
+                }
+            }
+            RangeReading::Failed(_) => {}
+        }
+        self.estimate()
+    }
+
+    fn update(&mut self, tick_ms: u32, mm: f64) {
+        let theta = self.config.smoothing;
+        let track = match self.track {
+            Some(track) if tick_ms.wrapping_sub(track.tick_ms) <= self.config.max_gap_ms => track,
+            _ => {
+                self.track = Some(Track {
+                    estimate: VelocityEstimate {
+                        distance_mm: mm,
+                        mm_per_s: 0.0,
+                        confidence: 0.0,
+                    },
+                    tick_ms,
+                    prior: 1.0,
+                    dropouts: 0,
+                });
+                return;
+            }
+        };
+        let elapsed_ms = tick_ms.wrapping_sub(track.tick_ms);
+        if elapsed_ms == 0 {
+            return;
+        }
+
+        let dt = elapsed_ms as f64 / 1000.0;
+        let VelocityEstimate {
+            distance_mm,
+            mm_per_s,
+            ..
+        } = track.estimate;
+        let predicted = distance_mm + mm_per_s * dt;
+        let residual = mm - predicted;
+        let alpha = 1.0 - theta * theta;
+        let beta = (1.0 - theta) * (1.0 - theta);
+        let prior = track.prior * theta;
+        self.track = Some(Track {
+            estimate: VelocityEstimate {
+                distance_mm: predicted + alpha * residual,
+                mm_per_s: mm_per_s + beta * residual / dt,
+                confidence: 1.0 - prior,
+            },
+            tick_ms,
+            prior,
+            dropouts: 0,
+        });
+    }
+}
@@ -0,0 +1,164 @@
+//! Continuous mode stall detection
+//!
+//! Occasionally, e.g. after a supply glitch or an ESD event, the sensor stops
+//! producing samples while still acknowledging I2C. [`StallWatchdog`] detects
+//! this from the time since the last sample, and
+//! [`recover`](StallWatchdog::recover) brings continuous ranging back as a
+//! [`RecoveryPolicy`] prescribes. When XSHUT is wired up,
+//! [`Device::power_cycle`] is the last resort; the device must then be
+//! configured again by the application.
+
+use core::time::Duration;
+
+use crate::device::{Device, DeviceAddress};
+use crate::registers::{RangeIntermeasurementPeriod, RangeMaxConvergenceTime};
+use crate::types::Error;
+
+/// How [`StallWatchdog::recover`] brings a stalled sensor back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveryPolicy {
+    /// Stop and restart continuous ranging, keeping the configuration, see
+    /// [`Device::restart_continuous_range`]
+    Restart,
+    /// Restore the power-on configuration, apply the tuning writes and start
+    /// continuous ranging again, see [`Device::factory_reset`]. Any other
+    /// configuration is lost.
+    FactoryReset(&'static [(u16, u8)]),
+}
+
+/// Detects a continuous measurement that stopped producing samples
+///
+/// Reports a stall once no sample has been seen for `k` times the expected
+/// sample interval.
+///
+/// # Example
+/// ```
+/// use core::time::Duration;
+/// use vl6180x::{watchdog::StallWatchdog, Error};
+///
+/// let mut watchdog = StallWatchdog::new(Duration::from_millis(50), 3);
+///
+/// assert_eq!(watchdog.elapse(Duration::from_millis(100)), Ok(()));
+/// watchdog.sample();
+/// assert_eq!(watchdog.elapse(Duration::from_millis(150)), Ok(()));
+/// assert_eq!(watchdog.elapse(Duration::from_millis(10)), Err(Error::Stalled));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StallWatchdog {
+    limit: Duration,
+    since_sample: Duration,
+}
+
+impl StallWatchdog {
+    /// Creates a watchdog for samples expected every `interval`.
+    ///
+    /// # Arguments
+    /// * `interval` - Expected time between samples
+    /// * `k` - Number of missed intervals tolerated before reporting a stall
+    pub fn new(interval: Duration, k: u32) -> Self {
+        Self {
+            limit: interval.saturating_mul(k),
+            since_sample: Duration::ZERO,
+        }
+    }
+
+    /// Creates a watchdog for continuous ranging.
+    ///
+    /// The expected interval is the intermeasurement period, or the estimated
+    /// measurement time when that is longer, so slow convergence settings do
+    /// not trigger false stalls.
+    pub fn for_continuous_range(
+        period: RangeIntermeasurementPeriod,
+        max_convergence: RangeMaxConvergenceTime,
+        k: u32,
+    ) -> Self {
+        Self::new(period.period.max(max_convergence.measurement_time()), k)
+    }
+
+    /// Time without a sample after which a stall is reported.
+    pub fn limit(&self) -> Duration {
+        self.limit
+    }
+
+    /// Records that a new sample was seen.
+    pub fn sample(&mut self) {
+        self.since_sample = Duration::ZERO;
+    }
+
+    /// Advances the watchdog by `elapsed` without a new sample.
+    ///
+    /// # Errors
+    /// * `Error::Stalled` - No sample has been seen within the limit
+    pub fn elapse(&mut self, elapsed: Duration) -> Result<(), Error> {
+        self.since_sample = self.since_sample.saturating_add(elapsed);
+        if self.since_sample > self.limit {
+            Err(Error::Stalled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Brings continuous ranging back after a stall.
+    ///
+    /// Applies `policy` to `device` and, once ranging runs again, restarts
+    /// the watchdog as if a sample had been seen.
+    ///
+    /// # Arguments
+    /// * `device` - Stalled device
+    /// * `delay` - Delay provider used while waiting for ranging to stop
+    /// * `policy` - Recovery to apply
+    ///
+    /// # Errors
+    /// * `Error::DeviceBusy` - Interleaved mode is running and `policy` is
+    ///   [`Restart`](RecoveryPolicy::Restart)
+    /// * `Error::Timeout` - A measurement did not stop
+    /// * `Error::BusError` - I2C communication failed
+    ///
+    /// On error the watchdog keeps reporting the stall.
+    pub fn recover<I2C, A, D>(
+        &mut self,
+        device: &mut Device<I2C, A>,
+        delay: &mut D,
+        policy: RecoveryPolicy,
+    ) -> Result<(), Error>
+    where
+        I2C: embedded_hal::i2c::I2c,
+        A: DeviceAddress,
+        D: embedded_hal::delay::DelayNs,
+    {
+        match policy {
+            RecoveryPolicy::Restart => device.restart_continuous_range(delay)?,
+            RecoveryPolicy::FactoryReset(tuning) => {
+                device.factory_reset(delay, tuning)?;
+                device.start_continuous_range()?;
+            }
+        }
+        self.sample();
+        Ok(())
+    }
+
+    /// Asynchronously brings continuous ranging back after a stall.
+    ///
+    /// This is the async version of [`recover`](StallWatchdog::recover).
+    pub async fn recover_async<I2C, A, D>(
+        &mut self,
+        device: &mut Device<I2C, A>,
+        delay: &mut D,
+        policy: RecoveryPolicy,
+    ) -> Result<(), Error>
+    where
+        I2C: embedded_hal_async::i2c::I2c,
+        A: DeviceAddress,
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        match policy {
+            RecoveryPolicy::Restart => device.restart_continuous_range_async(delay).await?,
+            RecoveryPolicy::FactoryReset(tuning) => {
+                device.factory_reset_async(delay, tuning).await?;
+                device.start_continuous_range_async().await?;
+            }
+        }
+        self.sample();
+        Ok(())
+    }
+}
@@ -0,0 +1,239 @@
+//! Windowed closest and farthest object
+//!
+//! Obstacle avoidance usually asks for the closest object seen recently
+//! rather than in the latest sample, so a single dropout does not hide it.
+//! [`MinMaxTracker`] keeps the minimum and maximum distance over the last
+//! `N` readings, and optionally over the last few milliseconds, in constant
+//! amortized time per reading and without allocation.
+
+use measurements::Length;
+
+use crate::types::RangeReading;
+
+/// The minimum or maximum distance in the window, with its age
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Extreme {
+    /// The distance
+    pub distance: Length,
+    /// Tick of the reading, in milliseconds
+    pub tick_ms: u32,
+    /// Number of readings fed after it, 0 for the latest reading
+    pub samples_ago: u32,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Extreme {
+    fn format(&self, f: defmt::Formatter) {
+        use crate::types::Millimeters;
+
+        defmt::write!(
+            f,
+            "Extreme {{ distance: {}, tick_ms: {}, samples_ago: {} }}",
+            Millimeters(self.distance),
+            self.tick_ms,
+            self.samples_ago
+        );
+    }
+}
+
+impl Extreme {
+    /// Returns the age of the reading in milliseconds at `now_ms`.
+    pub fn age_ms(&self, now_ms: u32) -> u32 {
+        now_ms.wrapping_sub(self.tick_ms)
+    }
+}
+
+/// A valid reading in a window
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    seq: u32,
+    tick_ms: u32,
+    mm: f64,
+}
+
+/// Fixed-capacity deque of entries, monotonic in distance
+#[derive(Debug, Clone)]
+struct Monotonic<const N: usize> {
+    entries: [Entry; N],
+    head: usize,
+    len: usize,
+}
+
+impl<const N: usize> Monotonic<N> {
+    const fn new() -> Self {
+        Self {
+            entries: [Entry {
+                seq: 0,
+                tick_ms: 0,
+                mm: 0.0,
+            }; N],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    fn front(&self) -> Option<&Entry> {
+        (self.len > 0).then(|| &self.entries[self.head])
+    }
+
+    fn pop_front(&mut self) {
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+    }
+
+    /// Appends `entry` after dropping every entry at the back that it
+    /// dominates
+    fn push(&mut self, entry: Entry, dominates: fn(f64, f64) -> bool) {
+        while self.len > 0 {
+            let back = (self.head + self.len - 1) % N;
+            if !dominates(entry.mm, self.entries[back].mm) {
+                break;
+            }
+            self.len -= 1;
+        }
+        self.entries[(self.head + self.len) % N] = entry;
+        self.len += 1;
+    }
+
+    /// Drops entries from the front while `stale` holds for them
+    fn expire(&mut self, stale: impl Fn(&Entry) -> bool) {
+        while self.front().is_some_and(&stale) {
+            self.pop_front();
+        }
+    }
+}
+
+/// Minimum and maximum distance over a sliding window of readings
+///
+/// The window covers the last `N` readings fed, valid or not. No-target and
+/// failed readings take up a place in the window but never become the
+/// minimum or maximum, so a dropout neither hides nor replaces the closest
+/// object. With [`with_max_age`](MinMaxTracker::with_max_age), readings also
+/// leave the window once they are older than the given number of
+/// milliseconds.
+///
+/// Each reading is added in constant amortized time using two monotonic
+/// deques over fixed-size buffers.
+///
+/// # Panics
+/// [`new`](MinMaxTracker::new) panics if `N` is zero.
+///
+/// # Example
+/// ```
+/// use measurements::Length;
+/// use vl6180x::window::MinMaxTracker;
+/// use vl6180x::RangeReading;
+///
+/// let mut tracker = MinMaxTracker::<3>::new();
+/// let at = |mm| RangeReading::Valid(Length::from_millimeters(mm));
+///
+/// tracker.feed(0, at(80.0));
+/// tracker.feed(10, at(40.0));
+/// tracker.feed(20, RangeReading::NoTarget);
+///
+/// let closest = tracker.min().unwrap();
+/// assert_eq!(closest.distance, Length::from_millimeters(40.0));
+/// assert_eq!(closest.samples_ago, 1);
+///
+/// // Three readings later the 40mm reading has left the window
+/// tracker.feed(30, at(90.0));
+/// tracker.feed(40, at(70.0));
+/// assert_eq!(tracker.min().unwrap().distance, Length::from_millimeters(70.0));
+/// assert_eq!(tracker.max().unwrap().distance, Length::from_millimeters(90.0));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MinMaxTracker<const N: usize> {
+    max_age_ms: Option<u32>,
+    seq: u32,
+    min: Monotonic<N>,
+    max: Monotonic<N>,
+}
+
+impl<const N: usize> MinMaxTracker<N> {
+    /// Creates an empty tracker over the last `N` readings.
+    pub fn new() -> Self {
+        assert!(N > 0, "window of zero readings");
+        Self {
+            max_age_ms: None,
+            seq: 0,
+            min: Monotonic::new(),
+            max: Monotonic::new(),
+        }
+    }
+
+    /// Creates an empty tracker over the last `N` readings that are at most
+    /// `max_age_ms` old.
+    pub fn with_max_age(max_age_ms: u32) -> Self {
+        Self {
+            max_age_ms: Some(max_age_ms),
+            ..Self::new()
+        }
+    }
+
+    /// Adds a reading taken at `tick_ms`, expiring readings that left the window.
+    ///
+    /// The tick may wrap around.
+    ///
+    /// # Arguments
+    /// * `tick_ms` - Time of the reading in milliseconds
+    /// * `reading` - The range reading
+    pub fn feed(&mut self, tick_ms: u32, reading: RangeReading) {
+        self.seq = self.seq.wrapping_add(1);
+        self.expire(tick_ms);
+
+        if let RangeReading::Valid(distance) = reading {
+            let entry = Entry {
+                seq: self.seq,
+                tick_ms,
+                mm: distance.as_millimeters(),
+            };
+            self.min.push(entry, |new, old| new <= old);
+            self.max.push(entry, |new, old| new >= old);
+        }
+    }
+
+    /// Drops readings older than the maximum age at `now_ms`.
+    ///
+    /// [`feed`](MinMaxTracker::feed) does this on its own; call it before
+    /// reading the extremes when no reading arrived for a while.
+    pub fn expire(&mut self, now_ms: u32) {
+        let seq = self.seq;
+        let max_age_ms = self.max_age_ms;
+        let stale = |entry: &Entry| {
+            seq.wrapping_sub(entry.seq) as usize >= N
+                || max_age_ms.is_some_and(|max| now_ms.wrapping_sub(entry.tick_ms) > max)
+        };
+        self.min.expire(stale);
+        self.max.expire(stale);
+    }
+
+    /// Returns the closest distance in the window.
+    pub fn min(&self) -> Option<Extreme> {
+        self.extreme(&self.min)
+    }
+
+    /// Returns the farthest distance in the window.
+    pub fn max(&self) -> Option<Extreme> {
+        self.extreme(&self.max)
+    }
+
+    /// Empties the window.
+    pub fn reset(&mut self) {
+        self.min = Monotonic::new();
+        self.max = Monotonic::new();
+    }
+
+    fn extreme(&self, deque: &Monotonic<N>) -> Option<Extreme> {
+        deque.front().map(|entry| Extreme {
+            distance: Length::from_millimeters(entry.mm),
+            tick_ms: entry.tick_ms,
+            samples_ago: self.seq.wrapping_sub(entry.seq),
+        })
+    }
+}
+
+impl<const N: usize> Default for MinMaxTracker<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
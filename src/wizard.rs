@@ -0,0 +1,412 @@
+//! Guided offset and crosstalk calibration
+//!
+//! [`CalibrationWizard`] sequences the calibration procedure of ST's
+//! application note AN4545 as a state machine for production line firmware:
+//! the operator places a white target for the part-to-part offset, then a
+//! dark target behind the cover glass for the crosstalk compensation, and the
+//! firmware shows the prompt of the [`current_step`](CalibrationWizard::current_step)
+//! and calls [`advance`](CalibrationWizard::advance) whenever the operator
+//! confirms. Nothing runs between calls, so the wizard can wait for the
+//! operator as long as needed.
+//!
+//! The result is a [`CalibrationData`] ready to be stored with
+//! [`CalibrationData::to_blob`].
+
+use core::fmt;
+
+use measurements::Length;
+
+use crate::calibration::CalibrationData;
+use crate::device::Device;
+use crate::registers::{
+    DatasheetLimits, RangeCrosstalkCompensationRate, RangePartToPartOffset, RangeResultBlock,
+};
+use crate::types::Error;
+
+/// Targets and sample counts of a [`CalibrationWizard`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WizardConfig {
+    /// Distance of the white target for the offset calibration
+    pub offset_distance: Length,
+    /// Distance of the dark target behind the cover glass for the crosstalk
+    /// calibration
+    pub crosstalk_distance: Length,
+    /// Single-shot measurements averaged per calibration, at least one
+    pub samples: u8,
+    /// Times a failed measurement step may be repeated before the wizard
+    /// gives up
+    pub retries: u8,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for WizardConfig {
+    fn format(&self, f: defmt::Formatter) {
+        use crate::types::Millimeters;
+
+        defmt::write!(
+            f,
+            "WizardConfig {{ offset_distance: {}, crosstalk_distance: {}, samples: {}, retries: {} }}",
+            Millimeters(self.offset_distance), Millimeters(self.crosstalk_distance), self.samples, self.retries
+        );
+    }
+}
+
+impl Default for WizardConfig {
+    /// The AN4545 procedure: a white target at 50mm, a 3% reflectance target
+    /// at 100mm, ten measurements each and two retries
+    fn default() -> Self {
+        Self {
+            offset_distance: Length::from_millimeters(50.0),
+            crosstalk_distance: Length::from_millimeters(100.0),
+            samples: 10,
+            retries: 2,
+        }
+    }
+}
+
+/// Step of a [`CalibrationWizard`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WizardStep {
+    /// Waiting for the white target at the offset distance
+    PlaceOffsetTarget,
+    /// Ready to measure the range offset
+    MeasureOffset,
+    /// Waiting for the dark target at the crosstalk distance
+    PlaceCrosstalkTarget,
+    /// Ready to measure the crosstalk
+    MeasureCrosstalk,
+    /// Calibration complete, see [`CalibrationWizard::result`]
+    Done,
+    /// A measurement step failed more often than the retries allow
+    Failed,
+}
+
+impl WizardStep {
+    /// Returns an operator prompt for the step.
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::PlaceOffsetTarget => "Place the white target at the offset distance",
+            Self::MeasureOffset => "Measuring the range offset",
+            Self::PlaceCrosstalkTarget => {
+                "Fit the cover glass and place the dark target at the crosstalk distance"
+            }
+            Self::MeasureCrosstalk => "Measuring the crosstalk",
+            Self::Done => "Calibration complete",
+            Self::Failed => "Calibration failed",
+        }
+    }
+
+    /// Returns whether the wizard has finished, successfully or not.
+    pub fn is_finished(self) -> bool {
+        matches!(self, Self::Done | Self::Failed)
+    }
+}
+
+impl fmt::Display for WizardStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+/// State machine guiding the offset and crosstalk calibration
+///
+/// [`advance`](CalibrationWizard::advance) moves from a placement step to
+/// its measurement step without touching the device, and performs the
+/// measurement of a measurement step:
+///
+/// 1. The offset measurement clears the offset and crosstalk compensation,
+///    averages the configured number of ranges and writes the offset that
+///    corrects them to the offset distance.
+/// 2. The crosstalk measurement averages the ranges and return signal rates
+///    with the new offset applied and writes the compensation rate
+///    `rate × (1 − range / distance)`.
+///
+/// A failed measurement returns its error and goes back to the placement
+/// step, so the operator can check the target before trying again; once the
+/// retries are used up the wizard stops at [`WizardStep::Failed`] and
+/// leaves the compensation registers as the failed measurement left them.
+///
+/// # Example
+/// ```no_run
+/// use embedded_hal::{delay::DelayNs, i2c::I2c};
+/// use vl6180x::calibration::CalibrationData;
+/// use vl6180x::wizard::{CalibrationWizard, WizardConfig};
+/// use vl6180x::Device;
+///
+/// fn calibrate<I2C: I2c, D: DelayNs>(
+///     sensor: &mut Device<I2C>,
+///     delay: &mut D,
+///     show: impl Fn(&str),
+///     wait_for_next: impl Fn(),
+/// ) -> Option<CalibrationData> {
+///     let mut wizard = CalibrationWizard::new(WizardConfig::default());
+///     while !wizard.current_step().is_finished() {
+///         show(wizard.current_step().description());
+///         wait_for_next();
+///         if let Err(_error) = wizard.advance(sensor, delay) {
+///             show("Measurement failed, check the target");
+///         }
+///     }
+///     wizard.result()
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CalibrationWizard {
+    config: WizardConfig,
+    step: WizardStep,
+    retries_left: u8,
+    offset: Length,
+    crosstalk_rate: u16,
+}
+
+impl CalibrationWizard {
+    /// Creates a wizard waiting for the offset target.
+    pub fn new(config: WizardConfig) -> Self {
+        Self {
+            config,
+            step: WizardStep::PlaceOffsetTarget,
+            retries_left: config.retries,
+            offset: Length::from_millimeters(0.0),
+            crosstalk_rate: 0,
+        }
+    }
+
+    /// Returns the parameters of the wizard.
+    pub fn config(&self) -> WizardConfig {
+        self.config
+    }
+
+    /// Returns the step the next [`advance`](CalibrationWizard::advance) performs.
+    pub fn current_step(&self) -> WizardStep {
+        self.step
+    }
+
+    /// Returns how often the current measurement step may still fail.
+    pub fn retries_left(&self) -> u8 {
+        self.retries_left
+    }
+
+    /// Returns the calibration once the wizard is done.
+    pub fn result(&self) -> Option<CalibrationData> {
+        (self.step == WizardStep::Done).then_some(CalibrationData {
+            offset: self.offset,
+            crosstalk_rate: self.crosstalk_rate,
+            scaling: 1,
+        })
+    }
+
+    /// Starts over from the offset target.
+    pub fn restart(&mut self) {
+        *self = Self::new(self.config);
+    }
+
+    /// Performs the current step, returning the next one.
+    ///
+    /// Does nothing once the wizard has finished.
+    ///
+    /// # Errors
+    /// Of a measurement step, after which the wizard is back at its placement
+    /// step or, without retries left, at [`WizardStep::Failed`]:
+    /// * `Error::BusError` - I2C communication failed
+    /// * `Error::Timeout` - No sample was reported within the range timeout
+    /// * `Error::RangeError` - A measurement completed with an error code
+    /// * `Error::OutOfSpec` - The offset needed is outside -128mm to 127mm
+    pub fn advance<I2C, D>(
+        &mut self,
+        device: &mut Device<I2C>,
+        delay: &mut D,
+    ) -> Result<WizardStep, Error>
+    where
+        I2C: embedded_hal::i2c::I2c,
+        D: embedded_hal::delay::DelayNs,
+    {
+        let measured = match self.step {
+            WizardStep::MeasureOffset => self.measure_offset(device, delay),
+            WizardStep::MeasureCrosstalk => self.measure_crosstalk(device, delay),
+            _ => Ok(()),
+        };
+        self.settle(measured)
+    }
+
+    fn measure_offset<I2C, D>(
+        &mut self,
+        device: &mut Device<I2C>,
+        delay: &mut D,
+    ) -> Result<(), Error>
+    where
+        I2C: embedded_hal::i2c::I2c,
+        D: embedded_hal::delay::DelayNs,
+    {
+        device.write_register(RangePartToPartOffset {
+            offset: Length::from_millimeters(0.0),
+        })?;
+        device.write_register(RangeCrosstalkCompensationRate { rate: 0 })?;
+        let mut sum = 0.0;
+        for _ in 0..self.samples() {
+            sum += device.measure_range_single(delay)?.as_millimeters();
+        }
+        let offset = self.offset_for(sum)?;
+        device.write_register(offset)?;
+        self.offset = offset.offset;
+        Ok(())
+    }
+
+    fn measure_crosstalk<I2C, D>(
+        &mut self,
+        device: &mut Device<I2C>,
+        delay: &mut D,
+    ) -> Result<(), Error>
+    where
+        I2C: embedded_hal::i2c::I2c,
+        D: embedded_hal::delay::DelayNs,
+    {
+        let (mut range_sum, mut rate_sum) = (0.0, 0.0);
+        for _ in 0..self.samples() {
+            range_sum += device.measure_range_single(delay)?.as_millimeters();
+            let result: RangeResultBlock = device.read_register()?;
+            rate_sum += f64::from(result.return_rate);
+        }
+        let crosstalk = self.crosstalk_for(range_sum, rate_sum);
+        device.write_register(crosstalk)?;
+        self.crosstalk_rate = crosstalk.rate;
+        Ok(())
+    }
+}
+
+impl CalibrationWizard {
+    /// Asynchronously performs the current step, returning the next one.
+    ///
+    /// This is the async version of [`advance`](CalibrationWizard::advance).
+    pub async fn advance_async<I2C, D>(
+        &mut self,
+        device: &mut Device<I2C>,
+        delay: &mut D,
+    ) -> Result<WizardStep, Error>
+    where
+        I2C: embedded_hal_async::i2c::I2c,
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        let measured = match self.step {
+            WizardStep::MeasureOffset => self.measure_offset_async(device, delay).await,
+            WizardStep::MeasureCrosstalk => self.measure_crosstalk_async(device, delay).await,
+            _ => Ok(()),
+        };
+        self.settle(measured)
+    }
+
+    async fn measure_offset_async<I2C, D>(
+        &mut self,
+        device: &mut Device<I2C>,
+        delay: &mut D,
+    ) -> Result<(), Error>
+    where
+        I2C: embedded_hal_async::i2c::I2c,
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        device
+            .write_register_async(RangePartToPartOffset {
+                offset: Length::from_millimeters(0.0),
+            })
+            .await?;
+        device
+            .write_register_async(RangeCrosstalkCompensationRate { rate: 0 })
+            .await?;
+        let mut sum = 0.0;
+        for _ in 0..self.samples() {
+            sum += device
+                .measure_range_single_async(delay)
+                .await?
+                .as_millimeters();
+        }
+        let offset = self.offset_for(sum)?;
+        device.write_register_async(offset).await?;
+        self.offset = offset.offset;
+        Ok(())
+    }
+
+    async fn measure_crosstalk_async<I2C, D>(
+        &mut self,
+        device: &mut Device<I2C>,
+        delay: &mut D,
+    ) -> Result<(), Error>
+    where
+        I2C: embedded_hal_async::i2c::I2c,
+        D: embedded_hal_async::delay::DelayNs,
+    {
+        let (mut range_sum, mut rate_sum) = (0.0, 0.0);
+        for _ in 0..self.samples() {
+            range_sum += device
+                .measure_range_single_async(delay)
+                .await?
+                .as_millimeters();
+            let result: RangeResultBlock = device.read_register_async().await?;
+            rate_sum += f64::from(result.return_rate);
+        }
+        let crosstalk = self.crosstalk_for(range_sum, rate_sum);
+        device.write_register_async(crosstalk).await?;
+        self.crosstalk_rate = crosstalk.rate;
+        Ok(())
+    }
+}
+
+impl CalibrationWizard {
+    fn samples(&self) -> u8 {
+        self.config.samples.max(1)
+    }
+
+    /// Offset correcting the ranges summing to `sum` to the offset distance
+    fn offset_for(&self, sum: f64) -> Result<RangePartToPartOffset, Error> {
+        let average = sum / f64::from(self.samples());
+        let offset = self.config.offset_distance.as_millimeters() - average;
+        let offset = RangePartToPartOffset {
+            offset: Length::from_millimeters(if offset < 0.0 {
+                (offset - 0.5) as i32
+            } else {
+                (offset + 0.5) as i32
+            } as f64),
+        };
+        offset.check_limits().map_err(Error::OutOfSpec)?;
+        Ok(offset)
+    }
+
+    /// Compensation for the ranges and return rates summing to `range_sum`
+    /// and `rate_sum`, saturating at the register range
+    fn crosstalk_for(&self, range_sum: f64, rate_sum: f64) -> RangeCrosstalkCompensationRate {
+        let samples = f64::from(self.samples());
+        let (range, rate) = (range_sum / samples, rate_sum / samples);
+        let rate = rate * (1.0 - range / self.config.crosstalk_distance.as_millimeters());
+        RangeCrosstalkCompensationRate {
+            rate: (rate + 0.5).clamp(0.0, f64::from(u16::MAX)) as u16,
+        }
+    }
+
+    /// Moves on after the current step, or back after a failed measurement
+    fn settle(&mut self, measured: Result<(), Error>) -> Result<WizardStep, Error> {
+        if let Err(e) = measured {
+            self.step = match self.retries_left.checked_sub(1) {
+                Some(left) => {
+                    self.retries_left = left;
+                    match self.step {
+                        WizardStep::MeasureOffset => WizardStep::PlaceOffsetTarget,
+                        _ => WizardStep::PlaceCrosstalkTarget,
+                    }
+                }
+                None => WizardStep::Failed,
+            };
+            return Err(e);
+        }
+
+        self.step = match self.step {
+            WizardStep::PlaceOffsetTarget => WizardStep::MeasureOffset,
+            WizardStep::MeasureOffset => {
+                self.retries_left = self.config.retries;
+                WizardStep::PlaceCrosstalkTarget
+            }
+            WizardStep::PlaceCrosstalkTarget => WizardStep::MeasureCrosstalk,
+            WizardStep::MeasureCrosstalk => WizardStep::Done,
+            finished => finished,
+        };
+        Ok(self.step)
+    }
+}
@@ -0,0 +1,206 @@
+//! Adapting the convergence time limit to the ambient light
+
+mod support;
+
+use core::time::Duration;
+
+use embedded_hal::i2c::ErrorKind;
+use support::{block_on, store, Behavior, Log, NoDelay, RegisterMap, Registers};
+use vl6180x::{AdaptiveTiming, Device, RangeReading};
+
+/// Sensor completing single-shot measurements instantly
+///
+/// Every measurement reports the configured status code, ambient count and
+/// convergence time.
+#[derive(Default)]
+struct Instant {
+    error_code: u8,
+    ambient_count: u32,
+    convergence_ms: u32,
+}
+
+impl Behavior for Instant {
+    fn write(&mut self, regs: &mut Registers, register: u16, data: &[u8]) -> Result<(), ErrorKind> {
+        store(regs, register, data);
+        match (register, data[0]) {
+            (0x018, 0x01) => {
+                regs[0x04D] = self.error_code << 4 | 0x01;
+                regs[0x04F] = 0x04;
+                regs[0x062] = 50;
+                store(regs, 0x074, &self.ambient_count.to_be_bytes());
+                store(regs, 0x07C, &self.convergence_ms.to_be_bytes());
+            }
+            (0x015, _) => regs[0x04F] = 0,
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+type Bus = RegisterMap<Instant>;
+
+/// Idle sensor with a 20ms convergence limit and a 100ms period
+fn bus() -> Bus {
+    RegisterMap::with(Instant::default())
+        .set(0x01B, &[9])
+        .set(0x01C, &[20])
+        .set(0x04D, &[0x01])
+}
+
+/// Ambient light as ambient counts per millisecond
+fn ambient(bus: &mut Bus, per_ms: u32, convergence_ms: u32) {
+    bus.behavior.ambient_count = per_ms * convergence_ms;
+    bus.behavior.convergence_ms = convergence_ms;
+}
+
+/// Convergence limits written, in ms
+fn limits(bus: &Bus) -> Vec<u8> {
+    bus.writes()
+        .into_iter()
+        .filter(|(reg, _)| *reg == 0x01C)
+        .map(|(_, value)| value[0])
+        .collect()
+}
+
+fn ms(value: u64) -> Duration {
+    Duration::from_millis(value)
+}
+
+fn policy() -> AdaptiveTiming {
+    AdaptiveTiming {
+        min_convergence: ms(10),
+        max_convergence: ms(40),
+        step: ms(10),
+        bright_ambient: 1000,
+        dark_ambient: 100,
+        high_utilization: 80,
+        low_utilization: 30,
+    }
+}
+
+fn measure(bus: &mut Bus, times: usize) {
+    let mut dev = Device::new(&mut *bus);
+    dev.enable_adaptive_timing(policy());
+    for _ in 0..times {
+        assert_eq!(
+            dev.read_range(&mut NoDelay).unwrap(),
+            RangeReading::Valid(measurements::Length::from_millimeters(50.0))
+        );
+    }
+    let _ = dev.release();
+}
+
+#[test]
+fn bright_then_dark() {
+    let mut bus = bus();
+
+    // Bright: the limit climbs to the maximum and stays there
+    ambient(&mut bus, 2000, 10);
+    measure(&mut bus, 4);
+    assert_eq!(limits(&bus), [30, 40]);
+
+    // Dark with quick convergence: the limit falls to the minimum
+    bus.log.clear();
+    ambient(&mut bus, 10, 5);
+    measure(&mut bus, 5);
+    assert_eq!(limits(&bus), [30, 20, 10]);
+    assert_eq!(bus.regs[0x1C], 10);
+}
+
+#[test]
+fn changes_are_written_under_grouped_hold() {
+    let mut bus = bus();
+    ambient(&mut bus, 2000, 10);
+    measure(&mut bus, 1);
+
+    assert_eq!(
+        bus.byte_writes(),
+        [
+            (0x018, 0x01),
+            (0x015, 0x01),
+            (0x017, 0x01),
+            (0x01C, 30),
+            (0x017, 0x00)
+        ]
+    );
+}
+
+#[test]
+fn moderate_conditions_keep_the_limit() {
+    let mut bus = bus();
+    // 500 counts/ms and half the limit used
+    ambient(&mut bus, 500, 10);
+    measure(&mut bus, 3);
+    assert!(limits(&bus).is_empty());
+}
+
+#[test]
+fn convergence_failures_raise_the_limit_in_the_dark() {
+    let mut bus = bus();
+    ambient(&mut bus, 10, 2);
+    bus.behavior.error_code = 7;
+    let mut dev = Device::new(&mut bus);
+    dev.enable_adaptive_timing(policy());
+    dev.read_range(&mut NoDelay).unwrap();
+    let _ = dev.release();
+
+    assert_eq!(limits(&bus), [30]);
+}
+
+#[test]
+fn limit_stays_within_the_continuous_period() {
+    let mut bus = bus();
+    // 30ms period: a 30ms limit no longer fits a measurement, 20ms does
+    bus.regs[0x1B] = 2;
+    bus.regs[0x1C] = 10;
+    ambient(&mut bus, 2000, 10);
+    measure(&mut bus, 3);
+
+    assert_eq!(limits(&bus), [20]);
+    assert_eq!(bus.regs[0x1C], 20);
+}
+
+#[test]
+fn disabled_adds_no_traffic() {
+    let mut bus = bus();
+    ambient(&mut bus, 2000, 10);
+    let mut dev = Device::new(&mut bus);
+    dev.enable_adaptive_timing(policy());
+    assert_eq!(dev.adaptive_timing(), Some(policy()));
+    dev.disable_adaptive_timing();
+    dev.read_range(&mut NoDelay).unwrap();
+    let _ = dev.release();
+
+    assert_eq!(bus.byte_writes(), [(0x018, 0x01), (0x015, 0x01)]);
+    // No diagnostics block or convergence limit read
+    assert!(!bus.reads().contains(&(0x062, 34)));
+    assert!(!bus.reads().contains(&(0x01C, 1)));
+}
+
+#[test]
+#[should_panic]
+fn inverted_bounds_are_refused() {
+    let mut dev = Device::new(bus());
+    dev.enable_adaptive_timing(AdaptiveTiming {
+        min_convergence: ms(50),
+        ..policy()
+    });
+}
+
+#[test]
+fn async_matches_blocking() {
+    let mut sync_bus = bus();
+    ambient(&mut sync_bus, 2000, 10);
+    measure(&mut sync_bus, 3);
+
+    let mut async_bus = bus();
+    ambient(&mut async_bus, 2000, 10);
+    let mut dev = Device::new(&mut async_bus);
+    dev.enable_adaptive_timing(policy());
+    for _ in 0..3 {
+        block_on(dev.read_range_async(&mut NoDelay)).unwrap();
+    }
+    let _ = dev.release();
+
+    assert_eq!(sync_bus.log, async_bus.log);
+}
@@ -0,0 +1,94 @@
+//! 7-bit and 8-bit address notations
+
+mod support;
+
+use support::SimulatedVl6180x;
+use vl6180x::{Address, Device, Error};
+
+#[test]
+fn seven_bit_addresses_are_taken_as_is() {
+    assert_eq!(Address::from_7bit(0x29), Some(Address::DEFAULT));
+    assert_eq!(Address::from_7bit(0x7F).map(Address::get), Some(0x7F));
+    assert_eq!(Address::from_7bit(0x80), None);
+    assert_eq!(Address::from(0x30).get(), 0x30);
+    assert_eq!(u8::from(Address::from(0x30)), 0x30);
+    assert_eq!(Address::default(), Address::DEFAULT);
+}
+
+#[test]
+fn eight_bit_addresses_drop_the_rw_bit() {
+    assert_eq!(Address::from_8bit(0x52), Address::DEFAULT);
+    assert_eq!(Address::from_8bit(0x53), Address::DEFAULT);
+    assert_eq!(Address::from_8bit(0x60).get(), 0x30);
+    assert_eq!(Address::DEFAULT.write_8bit(), 0x52);
+    assert_eq!(Address::DEFAULT.read_8bit(), 0x53);
+}
+
+#[test]
+fn display_shows_both_notations() {
+    assert_eq!(Address::DEFAULT.to_string(), "0x29 (8-bit 0x52/0x53)");
+    assert_eq!(Address::from(0x30).to_string(), "0x30 (8-bit 0x60/0x61)");
+}
+
+#[test]
+fn only_the_default_in_8bit_notation_looks_8bit() {
+    assert!(Address::from(0x52).looks_8bit());
+    assert!(Address::from(0x53).looks_8bit());
+    assert!(!Address::DEFAULT.looks_8bit());
+    assert!(!Address::from(0x54).looks_8bit());
+}
+
+#[test]
+fn constructors_take_either_form() {
+    let mut sim = SimulatedVl6180x::new();
+    sim.set_i2c_address(0x30);
+    let dev = Device::new_with_address(&mut sim, 0x30);
+    assert_eq!(dev.address(), 0x30);
+    let _ = dev.release();
+
+    let dev = Device::new_with_address(&mut sim, Address::from_8bit(0x61));
+    assert_eq!(dev.address(), 0x30);
+    let _ = dev.release();
+
+    let dev = Device::on_bus_with_address(&mut sim, Address::from_8bit(0x60));
+    assert_eq!(dev.address(), 0x30);
+}
+
+#[test]
+fn probing_the_8bit_notation_names_the_mistake() {
+    let mut sim = SimulatedVl6180x::new();
+    for address in [0x52, 0x53] {
+        let Err((_, error)) = Device::try_new_with_address(&mut sim, address) else {
+            panic!("nothing answers at 0x{address:02X}");
+        };
+        assert_eq!(error, Error::EightBitAddress(Address::from(address)));
+    }
+    assert_eq!(
+        Error::EightBitAddress(Address::from(0x52)).to_string(),
+        "No device at 0x52, the 8-bit notation of 0x29; use Address::from_8bit"
+    );
+
+    let Ok(dev) = Device::try_new_with_address(&mut sim, Address::from_8bit(0x52)) else {
+        panic!("the sensor answers at 0x29");
+    };
+    let _ = dev.release();
+}
+
+#[test]
+fn a_sensor_moved_to_0x52_is_found() {
+    let mut sim = SimulatedVl6180x::new();
+    sim.set_i2c_address(0x52);
+    let Ok(dev) = Device::try_new_with_address(&mut sim, 0x52) else {
+        panic!("the sensor answers at 0x52");
+    };
+    let _ = dev.release();
+}
+
+#[test]
+fn other_absent_addresses_report_the_bus_error() {
+    let mut sim = SimulatedVl6180x::new();
+    let Err((_, error)) = Device::try_new_with_address(&mut sim, 0x54) else {
+        panic!("nothing answers at 0x54");
+    };
+    assert!(matches!(error, Error::BusError(_)));
+}
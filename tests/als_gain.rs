@@ -0,0 +1,136 @@
+//! ALS gain selection against the datasheet's dynamic range table
+
+use core::time::Duration;
+
+use vl6180x::{AlsGain, GainFit, Luminance};
+
+const INTEGRATION: Duration = Duration::from_millis(100);
+
+/// Maximum light level per gain at 100ms integration, without cover glass
+/// (datasheet Table 16, computed with the nominal gains)
+const DYNAMIC_RANGE: [(AlsGain, f32); 8] = [
+    (AlsGain::Gain1, 20800.0),
+    (AlsGain::Gain1_25, 16640.0),
+    (AlsGain::Gain1_67, 12530.0),
+    (AlsGain::Gain2_5, 8320.0),
+    (AlsGain::Gain5, 4160.0),
+    (AlsGain::Gain10, 2080.0),
+    (AlsGain::Gain20, 1040.0),
+    (AlsGain::Gain40, 520.0),
+];
+
+fn lux(value: f32) -> Luminance {
+    Luminance::from_lux(value)
+}
+
+#[test]
+fn full_scale_matches_datasheet() {
+    for (gain, max) in DYNAMIC_RANGE {
+        let full_scale = gain.full_scale(INTEGRATION).lux;
+        let max = max * gain.gain() / gain.actual_gain();
+        // The table is computed from 65000 counts rather than 65535
+        assert!(
+            (full_scale - max).abs() / max < 0.01,
+            "{gain:?}: {full_scale} vs {max}"
+        );
+    }
+}
+
+#[test]
+fn lux_uses_actual_gain() {
+    for (gain, _) in DYNAMIC_RANGE {
+        let actual = Luminance::from_counts(1000, gain, INTEGRATION);
+        let nominal = Luminance::from_counts_with_gain(1000, gain.gain(), INTEGRATION);
+        assert_eq!(
+            actual,
+            Luminance::from_counts_with_gain(1000, gain.actual_gain(), INTEGRATION)
+        );
+        let correction = actual.lux / nominal.lux;
+        assert!(
+            (correction - gain.gain() / gain.actual_gain()).abs() < 1e-5,
+            "{gain:?}"
+        );
+    }
+
+    // 1000 counts at gain 10 read 31 lux, not the nominal 32
+    let actual = Luminance::from_counts(1000, AlsGain::Gain10, INTEGRATION);
+    assert_eq!(actual, Luminance::from_millilux(31_008));
+    let nominal = Luminance::from_counts_with_gain(1000, 10.0, INTEGRATION);
+    assert_eq!(nominal, Luminance::from_millilux(32_000));
+}
+
+#[test]
+fn gains_20_and_40_are_uncorrected() {
+    for gain in [AlsGain::Gain20, AlsGain::Gain40] {
+        assert_eq!(gain.actual_gain(), gain.gain());
+    }
+}
+
+#[test]
+fn calibrated_gain_round_trips() {
+    let calibrated = 10.4;
+    let light = Luminance::from_counts_with_gain(1234, calibrated, INTEGRATION);
+    assert_eq!(light.to_counts_with_gain(calibrated, INTEGRATION), 1234);
+    assert_ne!(light.to_counts(AlsGain::Gain10, INTEGRATION), 1234);
+}
+
+#[test]
+fn full_scale_shrinks_with_integration() {
+    let short = AlsGain::Gain1.full_scale(Duration::from_millis(50));
+    let long = AlsGain::Gain1.full_scale(Duration::from_millis(200));
+    assert_eq!(short, AlsGain::Gain1.full_scale(INTEGRATION) * 2.0);
+    assert_eq!(long, AlsGain::Gain1.full_scale(INTEGRATION) / 2.0);
+}
+
+#[test]
+fn picks_highest_gain_with_headroom() {
+    for (gain, _) in DYNAMIC_RANGE {
+        let expected = gain.full_scale(INTEGRATION).lux / AlsGain::HEADROOM * 0.99;
+        assert_eq!(
+            AlsGain::for_max_lux(lux(expected), INTEGRATION),
+            GainFit::Fits(gain),
+            "{gain:?}"
+        );
+    }
+}
+
+#[test]
+fn headroom_pushes_to_lower_gain() {
+    // Within Gain10's full scale, but not with headroom
+    assert_eq!(
+        AlsGain::for_max_lux(lux(2000.0), INTEGRATION),
+        GainFit::Fits(AlsGain::Gain5)
+    );
+}
+
+#[test]
+fn dark_environments_get_highest_gain() {
+    assert_eq!(
+        AlsGain::for_max_lux(lux(0.0), INTEGRATION),
+        GainFit::Fits(AlsGain::Gain40)
+    );
+}
+
+#[test]
+fn beyond_lowest_gain_saturates() {
+    let fit = AlsGain::for_max_lux(lux(100_000.0), INTEGRATION);
+    assert_eq!(fit, GainFit::Saturates(AlsGain::Gain1));
+    assert_eq!(fit.gain(), AlsGain::Gain1);
+}
+
+#[test]
+fn long_integration_lowers_gain() {
+    let max = lux(1000.0);
+    assert_eq!(
+        AlsGain::for_max_lux(max, INTEGRATION),
+        GainFit::Fits(AlsGain::Gain10)
+    );
+    assert_eq!(
+        AlsGain::for_max_lux(max, Duration::from_millis(512)),
+        GainFit::Fits(AlsGain::Gain2_5)
+    );
+    assert_eq!(
+        AlsGain::for_max_lux(lux(5000.0), Duration::from_millis(512)),
+        GainFit::Saturates(AlsGain::Gain1)
+    );
+}
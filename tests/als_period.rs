@@ -0,0 +1,145 @@
+//! Continuous ALS periods validated against the integration period
+
+mod support;
+
+use core::time::Duration;
+
+use support::{block_on, Log, NoDelay, SimulatedVl6180x};
+use vl6180x::registers::{AlsIntegrationPeriod, AlsIntermeasurementPeriod};
+use vl6180x::{Device, Error, PeriodTooShort, PeriodUpdate};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+/// 91ms integration needs 100.1ms, which fits into 90% of 111.2ms
+fn too_short() -> Error {
+    Error::PeriodTooShort(PeriodTooShort {
+        period: ms(110),
+        required: Duration::from_nanos(111_222_223),
+    })
+}
+
+/// Sensor configured with `integration` and a 110ms period
+fn configured(integration: u64) -> SimulatedVl6180x {
+    let mut sim = SimulatedVl6180x::new();
+    let mut dev = Device::new(&mut sim);
+    dev.write_register(AlsIntegrationPeriod {
+        period: ms(integration),
+    })
+    .unwrap();
+    dev.write_register(AlsIntermeasurementPeriod { period: ms(110) })
+        .unwrap();
+    let _ = dev.release();
+    sim
+}
+
+fn als_running(sim: &SimulatedVl6180x) -> bool {
+    sim.register(0x04E) & 0x01 == 0
+}
+
+#[test]
+fn datasheet_margins_set_the_minimum_period() {
+    let integration = AlsIntegrationPeriod::from_ms::<100>();
+    assert_eq!(
+        integration.min_intermeasurement_period(),
+        Duration::from_nanos(122_222_223)
+    );
+    let integration = AlsIntegrationPeriod::from_ms::<90>();
+    assert_eq!(integration.min_intermeasurement_period(), ms(110));
+}
+
+#[test]
+fn exactly_fitting_period_starts() {
+    let mut sim = configured(90);
+    let mut dev = Device::new(&mut sim);
+    drop(dev.continuous_als_scope().unwrap());
+    let _ = dev.release();
+
+    let mut sim = configured(90);
+    let als = Device::new(&mut sim)
+        .into_idle()
+        .start_continuous_als()
+        .ok()
+        .unwrap();
+    let _ = als.into_inner().release();
+    assert!(als_running(&sim));
+}
+
+#[test]
+fn period_just_too_short_is_refused() {
+    let mut sim = configured(91);
+    let mut dev = Device::new(&mut sim);
+    assert_eq!(dev.continuous_als_scope().err(), Some(too_short()));
+    let _ = dev.release();
+    assert!(!als_running(&sim));
+
+    let error = Device::new(&mut sim)
+        .into_idle()
+        .start_continuous_als()
+        .err()
+        .unwrap();
+    assert_eq!(error.error, too_short());
+    assert!(!als_running(&sim));
+}
+
+#[test]
+fn period_updates_use_the_same_margin() {
+    let mut sim = configured(90);
+    let mut dev = Device::new(&mut sim);
+    assert_eq!(
+        dev.update_als_period(&mut NoDelay, ms(110)),
+        Ok(PeriodUpdate::InPlace)
+    );
+    let _ = dev.release();
+
+    let mut sim = configured(91);
+    let mut dev = Device::new(&mut sim);
+    assert_eq!(
+        dev.update_als_period(&mut NoDelay, ms(110)),
+        Err(too_short())
+    );
+    let _ = dev.release();
+}
+
+#[test]
+fn async_start_is_validated_the_same_way() {
+    let mut sim = configured(90);
+    let started = block_on(
+        Device::new(&mut sim)
+            .into_idle()
+            .start_continuous_als_async(),
+    );
+    let _ = started.ok().unwrap().into_inner().release();
+    assert!(als_running(&sim));
+
+    let mut sim = configured(91);
+    let started = block_on(
+        Device::new(&mut sim)
+            .into_idle()
+            .start_continuous_als_async(),
+    );
+    assert_eq!(started.err().unwrap().error, too_short());
+    assert!(!als_running(&sim));
+}
+
+#[test]
+fn error_names_both_periods() {
+    assert_eq!(
+        too_short().to_string(),
+        "Intermeasurement period is too short: 110ms period, 111.222223ms required"
+    );
+}
+
+#[test]
+fn integration_period_is_the_nine_bit_code_plus_one() {
+    let mut sim = SimulatedVl6180x::new();
+    let mut dev = Device::new(&mut sim);
+    dev.write_register(AlsIntegrationPeriod { period: ms(300) })
+        .unwrap();
+    let _ = dev.release();
+
+    // 299 = 0x12B spans SYSALS__INTEGRATION_PERIOD at 0x040 and 0x041
+    assert_eq!(sim.writes(), [(0x040, vec![0x01, 0x2B])]);
+    assert_eq!(sim.registers(0x040, 2), [0x01, 0x2B]);
+}
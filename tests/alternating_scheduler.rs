@@ -0,0 +1,224 @@
+//! Round-robin ranging over several sensors
+
+mod support;
+
+use core::cell::RefCell;
+use core::time::Duration;
+
+use embedded_hal::i2c::ErrorKind;
+use measurements::Length;
+use support::{block_on, Behavior, RegisterMap, Registers};
+use vl6180x::scheduler::AlternatingScheduler;
+use vl6180x::{Device, Direction, Error, ErrorContext, RangeReading};
+
+/// What happened on the shared timeline of all sensors
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Event {
+    /// A sensor was told to start ranging
+    Start(u8),
+    /// The delay provider waited this many microseconds
+    Wait(u32),
+}
+
+/// Error of a sensor whose bus fails on the first access, starting ranging
+const BUS_FAULT: Error = Error::BusError(ErrorContext::register(0x018, Direction::Write));
+
+/// How a scripted sensor behaves
+#[derive(Clone, Copy)]
+enum Script {
+    /// Every measurement succeeds at this distance in mm
+    Distance(u8),
+    /// Every transaction fails
+    BusFault,
+    /// Ranging starts but never completes
+    Stuck,
+}
+
+/// Scripted sensor logging onto a timeline shared with the other sensors
+struct Scripted<'a> {
+    id: u8,
+    script: Script,
+    log: &'a RefCell<Vec<Event>>,
+}
+
+impl Behavior for Scripted<'_> {
+    fn transaction(&mut self, _: &mut Registers, _: u8) -> Result<(), ErrorKind> {
+        match self.script {
+            Script::BusFault => Err(ErrorKind::Other),
+            _ => Ok(()),
+        }
+    }
+
+    fn read(&mut self, _: &mut Registers, register: u16, buf: &mut [u8]) -> Result<(), ErrorKind> {
+        buf[0] = match (register, self.script) {
+            (0x04F, Script::Stuck) => 0x00,
+            (0x04F, _) => 0x04,
+            (0x062, Script::Distance(mm)) => mm,
+            _ => 0x00,
+        };
+        Ok(())
+    }
+
+    fn write(&mut self, _: &mut Registers, register: u16, data: &[u8]) -> Result<(), ErrorKind> {
+        if register == 0x018 {
+            assert_eq!(data[0], 0x01, "only single-shot ranging is started");
+            self.log.borrow_mut().push(Event::Start(self.id));
+        }
+        Ok(())
+    }
+}
+
+type Bus<'a> = RegisterMap<Scripted<'a>>;
+
+/// Delay provider that logs guard-sized waits onto the shared timeline
+struct Delay<'a> {
+    log: &'a RefCell<Vec<Event>>,
+}
+
+impl Delay<'_> {
+    fn wait(&mut self, ns: u32) {
+        // Ignore the 1ms polling interval, only the guard matters here
+        if ns != 1_000_000 {
+            self.log.borrow_mut().push(Event::Wait(ns / 1000));
+        }
+    }
+}
+
+impl embedded_hal::delay::DelayNs for Delay<'_> {
+    fn delay_ns(&mut self, ns: u32) {
+        self.wait(ns);
+    }
+}
+
+impl embedded_hal_async::delay::DelayNs for Delay<'_> {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.wait(ns);
+    }
+}
+
+const GUARD: Duration = Duration::from_millis(3);
+
+fn scheduler<'a, const N: usize>(
+    scripts: [Script; N],
+    log: &'a RefCell<Vec<Event>>,
+) -> AlternatingScheduler<Bus<'a>, N> {
+    let mut id = 0;
+    let devices = scripts.map(|script| {
+        id += 1;
+        let mut dev = Device::new(RegisterMap::with(Scripted {
+            id: id - 1,
+            script,
+            log,
+        }));
+        dev.set_timeouts(vl6180x::Timeouts {
+            range: Duration::from_millis(3),
+            ..Default::default()
+        });
+        dev
+    });
+    AlternatingScheduler::new(devices, GUARD)
+}
+
+fn valid(mm: f64) -> Result<RangeReading, Error> {
+    Ok(RangeReading::Valid(Length::from_millimeters(mm)))
+}
+
+#[test]
+fn alternates_strictly_with_guard() {
+    let log = RefCell::new(Vec::new());
+    let mut scheduler = scheduler([Script::Distance(10), Script::Distance(20)], &log);
+    let mut delay = Delay { log: &log };
+
+    let results: Vec<_> = (0..4).map(|_| scheduler.measure_next(&mut delay)).collect();
+
+    assert_eq!(
+        results,
+        [
+            (0, valid(10.0)),
+            (1, valid(20.0)),
+            (0, valid(10.0)),
+            (1, valid(20.0))
+        ]
+    );
+    assert_eq!(
+        *log.borrow(),
+        [
+            Event::Start(0),
+            Event::Wait(3000),
+            Event::Start(1),
+            Event::Wait(3000),
+            Event::Start(0),
+            Event::Wait(3000),
+            Event::Start(1),
+            Event::Wait(3000),
+        ]
+    );
+}
+
+#[test]
+fn failing_sensor_is_skipped() {
+    let log = RefCell::new(Vec::new());
+    let mut scheduler = scheduler(
+        [Script::Distance(10), Script::BusFault, Script::Stuck],
+        &log,
+    );
+    let mut delay = Delay { log: &log };
+
+    let results: Vec<_> = (0..6).map(|_| scheduler.measure_next(&mut delay)).collect();
+
+    assert_eq!(
+        results,
+        [
+            (0, valid(10.0)),
+            (1, Err(BUS_FAULT)),
+            (2, Err(Error::Timeout)),
+            (0, valid(10.0)),
+            (1, Err(BUS_FAULT)),
+            (2, Err(Error::Timeout)),
+        ]
+    );
+    // The guard is kept after failed measurements too
+    let waits = log
+        .borrow()
+        .iter()
+        .filter(|event| **event == Event::Wait(3000))
+        .count();
+    assert_eq!(waits, 6);
+}
+
+#[test]
+fn async_drive_matches_blocking() {
+    let scripts = [Script::Distance(10), Script::BusFault, Script::Distance(30)];
+
+    let sync_log = RefCell::new(Vec::new());
+    let mut sync_scheduler = scheduler(scripts, &sync_log);
+    let mut sync_delay = Delay { log: &sync_log };
+    let sync_results: Vec<_> = (0..6)
+        .map(|_| sync_scheduler.measure_next(&mut sync_delay))
+        .collect();
+
+    let async_log = RefCell::new(Vec::new());
+    let mut async_scheduler = scheduler(scripts, &async_log);
+    let mut async_delay = Delay { log: &async_log };
+    let async_results: Vec<_> = (0..6)
+        .map(|_| block_on(async_scheduler.measure_next_async(&mut async_delay)))
+        .collect();
+
+    assert_eq!(sync_results, async_results);
+    assert_eq!(*sync_log.borrow(), *async_log.borrow());
+}
+
+#[test]
+fn devices_are_handed_back_in_order() {
+    let log = RefCell::new(Vec::new());
+    let mut scheduler = scheduler([Script::Distance(10), Script::Distance(20)], &log);
+    let mut delay = Delay { log: &log };
+    assert_eq!(scheduler.next_index(), 0);
+    let (index, _) = scheduler.measure_next(&mut delay);
+    assert_eq!(index, 0);
+    assert_eq!(scheduler.next_index(), 1);
+
+    let [first, second] = scheduler.into_devices();
+    assert_eq!(first.release().behavior.id, 0);
+    assert_eq!(second.release().behavior.id, 1);
+}
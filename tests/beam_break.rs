@@ -0,0 +1,169 @@
+//! Debounced beam-break counting
+
+mod support;
+
+use measurements::Length;
+use support::{Log, RegisterMap};
+use vl6180x::beam::{BeamBreakConfig, BeamBreakCounter, BlockedState};
+use vl6180x::{Device, InterruptMode, RangeErrorCode, RangeReading};
+
+fn counter() -> BeamBreakCounter {
+    BeamBreakCounter::new(BeamBreakConfig {
+        trigger: Length::from_millimeters(80.0),
+        min_blocked_ms: 30,
+        min_clear_ms: 50,
+    })
+}
+
+/// Feeds readings taken every 10ms, `None` meaning no target, and returns
+/// the debounced state changes with their tick
+fn run(counter: &mut BeamBreakCounter, samples: &[Option<f64>]) -> Vec<(u32, BlockedState)> {
+    samples
+        .iter()
+        .zip((0..).step_by(10))
+        .filter_map(|(&mm, tick)| {
+            let reading = match mm {
+                Some(mm) => RangeReading::Valid(Length::from_millimeters(mm)),
+                None => RangeReading::NoTarget,
+            };
+            counter.feed(tick, reading).map(|state| (tick, state))
+        })
+        .collect()
+}
+
+#[test]
+fn separate_crossings_count_once_each() {
+    let mut counter = counter();
+    let mut samples = vec![None; 3];
+    for _ in 0..3 {
+        samples.extend([Some(40.0); 5]);
+        samples.extend([None; 6]);
+    }
+
+    let changes = run(&mut counter, &samples);
+
+    assert_eq!(
+        changes,
+        [
+            (60, BlockedState::Blocked),
+            (130, BlockedState::Clear),
+            (170, BlockedState::Blocked),
+            (240, BlockedState::Clear),
+            (280, BlockedState::Blocked),
+            (350, BlockedState::Clear),
+        ]
+    );
+    assert_eq!(counter.count(), 3);
+}
+
+#[test]
+fn chatter_at_the_threshold_counts_once() {
+    let mut counter = counter();
+    let mut samples = vec![Some(120.0)];
+    // Readings flicker across 80mm while an edge enters the beam
+    samples.extend([79.0, 81.0, 79.0, 80.0, 79.0].map(Some));
+    samples.extend([Some(78.0); 4]);
+    // And while it leaves
+    samples.extend([81.0, 79.0, 80.0, 79.0, 82.0, 79.0].map(Some));
+    samples.extend([Some(120.0); 6]);
+
+    let changes = run(&mut counter, &samples);
+
+    assert_eq!(
+        changes,
+        [(80, BlockedState::Blocked), (210, BlockedState::Clear)]
+    );
+    assert_eq!(counter.count(), 1);
+}
+
+#[test]
+fn short_gap_does_not_split_a_crossing() {
+    let mut counter = counter();
+    let mut samples = vec![Some(40.0); 5];
+    // Two people close together: the beam clears for only 30ms
+    samples.extend([None; 3]);
+    samples.extend([Some(40.0); 5]);
+    samples.extend([None; 6]);
+
+    run(&mut counter, &samples);
+
+    assert_eq!(counter.count(), 1);
+    assert_eq!(counter.state(), BlockedState::Clear);
+}
+
+#[test]
+fn short_blip_is_ignored() {
+    let mut counter = counter();
+    let samples = [None, Some(40.0), Some(40.0), None, None, Some(50.0), None];
+
+    assert_eq!(run(&mut counter, &samples), []);
+    assert_eq!(counter.count(), 0);
+}
+
+#[test]
+fn failed_readings_only_advance_time() {
+    let mut counter = counter();
+    let failed = RangeReading::Failed(RangeErrorCode::SignalToNoiseRatio);
+
+    let blocked = RangeReading::Valid(Length::from_millimeters(40.0));
+    assert_eq!(counter.feed(0, blocked), None);
+    assert_eq!(counter.feed(20, failed), None);
+    assert_eq!(counter.feed(30, failed), Some(BlockedState::Blocked));
+    assert_eq!(counter.count(), 1);
+}
+
+#[test]
+fn interrupt_path_with_update() {
+    let mut counter = counter();
+
+    assert_eq!(counter.feed_state(1000, BlockedState::Blocked), None);
+    assert_eq!(counter.update(1029), None);
+    assert_eq!(counter.update(1030), Some(BlockedState::Blocked));
+    assert_eq!(counter.feed_state(1100, BlockedState::Clear), None);
+    assert_eq!(counter.update(1150), Some(BlockedState::Clear));
+    assert_eq!(counter.update(2000), None);
+    assert_eq!(counter.count(), 1);
+}
+
+#[test]
+fn tick_wraps_around() {
+    let mut counter = counter();
+    let start = u32::MAX - 10;
+
+    counter.feed_state(start, BlockedState::Blocked);
+    assert_eq!(
+        counter.update(start.wrapping_add(30)),
+        Some(BlockedState::Blocked)
+    );
+}
+
+#[test]
+fn device_window_follows_the_beam() {
+    let trigger = Length::from_millimeters(80.0);
+    // ALS interrupt on new samples, left untouched
+    let mut bus = RegisterMap::new().set(0x014, &[0x20]);
+    let mut dev = Device::new(&mut bus);
+
+    dev.configure_beam_break_interrupt(trigger).unwrap();
+    assert_eq!(
+        dev.range_interrupt_mode().unwrap(),
+        InterruptMode::OutOfWindow
+    );
+    dev.set_beam_break_window(trigger, BlockedState::Blocked)
+        .unwrap();
+    dev.set_beam_break_window(trigger, BlockedState::Clear)
+        .unwrap();
+    let _ = dev.release();
+
+    assert_eq!(
+        bus.writes(),
+        [
+            // Clear: trigger below 80mm
+            (0x019, vec![255, 80]),
+            (0x014, vec![0x23]),
+            // Blocked: trigger above 79mm
+            (0x019, vec![79, 0]),
+            (0x019, vec![255, 80]),
+        ]
+    );
+}
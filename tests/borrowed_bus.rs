@@ -0,0 +1,65 @@
+//! Drivers borrowing one bus in turn
+
+mod support;
+
+use embedded_hal::i2c::I2c;
+use support::{block_on, Multidrop};
+use vl6180x::registers::{ModelId, RangeIntermeasurementPeriod};
+use vl6180x::{Device, Error};
+
+/// Two sensors at 0x29 and 0x30 behind one bus
+fn bus() -> Multidrop {
+    Multidrop::new(&[0x29, 0x30])
+}
+
+/// Generic code written against an owned bus accepts a borrowed one
+fn model<I2C: I2c>(device: &mut Device<I2C>) -> Result<ModelId, Error> {
+    device.read_register()
+}
+
+#[test]
+fn two_drivers_take_turns() {
+    let mut bus = bus();
+
+    let period = RangeIntermeasurementPeriod::from_ms::<100>();
+    Device::on_bus(&mut bus).write_register(period).unwrap();
+    let mut right = Device::on_bus_with_address(&mut bus, 0x30);
+    assert_eq!(model(&mut right), Ok(ModelId::VL6180X));
+    let released: &mut Multidrop = right.release();
+    released.addresses.push(0x68);
+    assert_eq!(model(&mut Device::on_bus(&mut bus)), Ok(ModelId::VL6180X));
+
+    assert_eq!(bus.addresses, [0x29, 0x30, 0x68, 0x29]);
+    assert_eq!(bus.sensors[0].register(0x01B), 9);
+    assert_eq!(bus.sensors[1].register(0x01B), 0xFF);
+}
+
+#[test]
+fn settings_survive_between_borrows() {
+    let mut bus = bus();
+
+    let mut right = Device::on_bus_with_address(&mut bus, 0x30);
+    right.set_strict(true);
+    let (_, state) = right.into_parts();
+
+    bus.addresses.clear();
+    let mut right = Device::from_parts(&mut bus, state);
+    assert!(right.is_strict());
+    assert_eq!(model(&mut right), Ok(ModelId::VL6180X));
+    let _ = right.release();
+
+    assert_eq!(bus.addresses, [0x30]);
+}
+
+#[test]
+fn async_drivers_take_turns() {
+    let mut bus = bus();
+
+    for address in [0x29, 0x30] {
+        let mut device = Device::on_bus_with_address(&mut bus, address);
+        let id: ModelId = block_on(device.read_register_async()).unwrap();
+        assert_eq!(id, ModelId::VL6180X);
+    }
+
+    assert_eq!(bus.addresses, [0x29, 0x30]);
+}
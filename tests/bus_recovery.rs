@@ -0,0 +1,240 @@
+//! Bus recovery hook called on failed transactions
+
+mod support;
+
+use core::cell::Cell;
+use core::time::Duration;
+
+use embedded_hal::i2c::ErrorKind;
+use support::{block_on, SimulatedVl6180x};
+use vl6180x::registers::{RangeMaxConvergenceTime, RangeResultStatus};
+use vl6180x::{Access, BusRecovery, Device, Direction, Error, ErrorContext};
+
+/// Sensor failing its next `failures` transactions with `kind`
+fn failing(failures: u32, kind: ErrorKind) -> SimulatedVl6180x {
+    let mut sim = SimulatedVl6180x::new();
+    if failures > 0 {
+        sim.fail_transactions(failures, kind);
+    }
+    sim
+}
+
+thread_local! {
+    static CALLS: Cell<u32> = const { Cell::new(0) };
+    static LAST_KIND: Cell<Option<ErrorKind>> = const { Cell::new(None) };
+}
+
+/// Resets the hook records of this test's thread
+fn reset() {
+    CALLS.set(0);
+    LAST_KIND.set(None);
+}
+
+fn record(kind: ErrorKind) {
+    CALLS.set(CALLS.get() + 1);
+    LAST_KIND.set(Some(kind));
+}
+
+fn retry(kind: ErrorKind) -> BusRecovery {
+    record(kind);
+    BusRecovery::Retry
+}
+
+fn give_up(kind: ErrorKind) -> BusRecovery {
+    record(kind);
+    BusRecovery::GiveUp
+}
+
+fn bus_error(access: Access, direction: Direction) -> Error {
+    Error::BusError(ErrorContext {
+        access,
+        direction,
+        operation: None,
+    })
+}
+
+#[test]
+fn without_a_hook_failures_are_returned() {
+    let mut bus = failing(1, ErrorKind::Bus);
+    let mut dev = Device::new(&mut bus);
+    assert!(dev.bus_recovery().is_none());
+    assert_eq!(
+        dev.read_register::<RangeMaxConvergenceTime>(),
+        Err(bus_error(Access::Register(0x01C), Direction::Read))
+    );
+    let _ = dev.release();
+    assert_eq!(bus.transactions(), 1);
+}
+
+#[test]
+fn recovered_read_is_retried() {
+    reset();
+    let mut bus = failing(1, ErrorKind::Bus);
+    bus.set_registers(0x01C, &[30]);
+    let mut dev = Device::new(&mut bus);
+    dev.set_bus_recovery(retry);
+
+    let limit: RangeMaxConvergenceTime = dev.read_register().unwrap();
+    assert_eq!(limit.time, Duration::from_millis(30));
+    let _ = dev.release();
+    assert_eq!(CALLS.get(), 1);
+    assert_eq!(LAST_KIND.get(), Some(ErrorKind::Bus));
+    assert_eq!(bus.transactions(), 2);
+}
+
+#[test]
+fn recovered_write_is_retried() {
+    reset();
+    let mut bus = failing(1, ErrorKind::ArbitrationLoss);
+    let mut dev = Device::new(&mut bus);
+    dev.set_bus_recovery(retry);
+
+    dev.write_block(0x01C, &[40]).unwrap();
+    let _ = dev.release();
+    assert_eq!(CALLS.get(), 1);
+    assert_eq!(LAST_KIND.get(), Some(ErrorKind::ArbitrationLoss));
+    assert_eq!(bus.transactions(), 2);
+    assert_eq!(bus.register(0x01C), 40);
+}
+
+#[test]
+fn giving_up_returns_the_failure() {
+    reset();
+    let mut bus = failing(1, ErrorKind::Bus);
+    let mut dev = Device::new(&mut bus);
+    dev.set_bus_recovery(give_up);
+
+    assert_eq!(
+        dev.write_block(0x01C, &[40]),
+        Err(bus_error(Access::Raw(0x01C), Direction::Write))
+    );
+    let _ = dev.release();
+    assert_eq!(CALLS.get(), 1);
+    assert_eq!(bus.transactions(), 1);
+    assert_eq!(bus.register(0x01C), SimulatedVl6180x::new().register(0x01C));
+}
+
+#[test]
+fn each_failed_attempt_calls_the_hook_once() {
+    reset();
+    let mut bus = failing(2, ErrorKind::Bus);
+    let mut dev = Device::new(&mut bus);
+    dev.set_bus_recovery(retry);
+
+    assert!(dev.read_register::<RangeMaxConvergenceTime>().is_ok());
+    assert!(dev.read_register::<RangeMaxConvergenceTime>().is_ok());
+    let _ = dev.release();
+    assert_eq!(CALLS.get(), 2);
+    assert_eq!(bus.transactions(), 4);
+}
+
+#[test]
+fn retries_are_bounded() {
+    reset();
+    let mut bus = failing(u32::MAX, ErrorKind::Bus);
+    let mut dev = Device::new(&mut bus);
+    dev.set_bus_recovery(retry);
+
+    assert_eq!(
+        dev.read_register::<RangeMaxConvergenceTime>(),
+        Err(bus_error(Access::Register(0x01C), Direction::Read))
+    );
+    let _ = dev.release();
+    assert_eq!(CALLS.get(), 4);
+    assert_eq!(bus.transactions(), 4);
+}
+
+#[test]
+fn final_failure_calls_the_hook_too() {
+    reset();
+    let mut bus = failing(4, ErrorKind::ArbitrationLoss);
+    let mut dev = Device::new(&mut bus);
+    dev.set_bus_recovery(retry);
+
+    assert_eq!(
+        block_on(dev.write_block_async(0x01C, &[40])),
+        Err(bus_error(Access::Raw(0x01C), Direction::Write))
+    );
+    assert_eq!(CALLS.get(), 4);
+    assert_eq!(LAST_KIND.get(), Some(ErrorKind::ArbitrationLoss));
+
+    // The hook recovered the bus after the fourth failure
+    dev.write_block(0x01C, &[40]).unwrap();
+    let _ = dev.release();
+    assert_eq!(CALLS.get(), 4);
+    assert_eq!(bus.transactions(), 5);
+    assert_eq!(bus.register(0x01C), 40);
+}
+
+#[test]
+fn deserialization_errors_skip_the_hook() {
+    reset();
+    let mut bus = failing(0, ErrorKind::Bus);
+    bus.set_registers(0x04D, &[0x91]);
+    let mut dev = Device::new(&mut bus);
+    dev.set_bus_recovery(retry);
+
+    assert_eq!(
+        dev.read_register::<RangeResultStatus>(),
+        Err(Error::DeserializationError(ErrorContext::register(
+            0x04D,
+            Direction::Read
+        )))
+    );
+    let _ = dev.release();
+    assert_eq!(CALLS.get(), 0);
+    assert_eq!(bus.transactions(), 1);
+}
+
+#[test]
+fn hook_can_be_cleared_and_survives_parts() {
+    reset();
+    let mut bus = failing(1, ErrorKind::Bus);
+    let mut dev = Device::new(&mut bus);
+    dev.set_bus_recovery(retry);
+    let (i2c, state) = dev.into_parts();
+    let mut dev = Device::from_parts(i2c, state);
+    assert!(dev.bus_recovery().is_some());
+    assert!(dev.read_register::<RangeMaxConvergenceTime>().is_ok());
+
+    dev.clear_bus_recovery();
+    assert!(dev.bus_recovery().is_none());
+    let _ = dev.release();
+    assert_eq!(CALLS.get(), 1);
+}
+
+#[test]
+fn async_transactions_are_retried() {
+    reset();
+    let mut bus = failing(2, ErrorKind::Bus);
+    bus.set_registers(0x01C, &[30]);
+    let mut dev = Device::new(&mut bus);
+    dev.set_bus_recovery(retry);
+
+    let limit: RangeMaxConvergenceTime = block_on(dev.read_register_async()).unwrap();
+    assert_eq!(limit.time, Duration::from_millis(30));
+    block_on(dev.write_block_async(0x01C, &[40])).unwrap();
+    let _ = dev.release();
+    assert_eq!(CALLS.get(), 2);
+    assert_eq!(bus.transactions(), 4);
+    assert_eq!(bus.register(0x01C), 40);
+}
+
+#[cfg(feature = "bus-stats")]
+#[test]
+fn stats_count_every_attempt() {
+    reset();
+    let mut bus = failing(2, ErrorKind::Bus);
+    let mut dev = Device::new(&mut bus);
+    dev.set_bus_recovery(retry);
+
+    dev.read_register::<RangeMaxConvergenceTime>().unwrap();
+    block_on(dev.write_block_async(0x01C, &[40])).unwrap();
+    // Two failed reads before the one that got through, then one write
+    let stats = dev.stats();
+    assert_eq!(stats.reads, 3);
+    assert_eq!(stats.writes, 1);
+    assert_eq!(stats.errors, 2);
+    assert_eq!(stats.bytes_written, 3 * 2 + 3);
+    assert_eq!(stats.bytes_read, 1);
+}
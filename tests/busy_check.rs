@@ -0,0 +1,182 @@
+//! The busy check refuses configuration writes while a measurement runs
+
+mod support;
+
+use core::time::Duration;
+
+use embedded_hal::i2c::ErrorKind;
+use support::{load, Behavior, Log, NoDelay, RegisterMap, Registers};
+use vl6180x::registers::{
+    AlsIntegrationPeriod, BusyPolicy, DatasheetLimits, InterruptClear, InterruptConfigGpio,
+    RangeMaxConvergenceTime, RangeResultValue, RangeStart,
+};
+use vl6180x::{Device, Error, RangeInterrupt};
+
+/// Sensor whose device ready bits are set after `busy_polls` status reads
+#[derive(Default)]
+struct Busy {
+    busy_polls: u32,
+}
+
+impl Behavior for Busy {
+    fn read(
+        &mut self,
+        regs: &mut Registers,
+        register: u16,
+        buf: &mut [u8],
+    ) -> Result<(), ErrorKind> {
+        if register == 0x04D && self.busy_polls > 0 {
+            self.busy_polls -= 1;
+            if self.busy_polls == 0 {
+                regs[0x04D] |= 0x01;
+                regs[0x04E] |= 0x01;
+            }
+        }
+        load(regs, register, buf);
+        Ok(())
+    }
+}
+
+type Bus = RegisterMap<Busy>;
+
+/// A bus with the given sensors running a measurement
+fn bus(range_busy: bool, als_busy: bool) -> Bus {
+    RegisterMap::with(Busy::default())
+        .set(0x04D, &[u8::from(!range_busy)])
+        .set(0x04E, &[u8::from(!als_busy)])
+}
+
+/// Number of reads and writes so far
+fn counts(bus: &Bus) -> (usize, usize) {
+    (bus.reads().len(), bus.writes().len())
+}
+
+fn convergence() -> RangeMaxConvergenceTime {
+    RangeMaxConvergenceTime {
+        time: Duration::from_millis(30),
+    }
+}
+
+#[test]
+fn busy_check_is_off_by_default() {
+    let mut bus = bus(true, true);
+    let mut dev = Device::new(&mut bus);
+    assert!(!dev.busy_check_enabled());
+
+    dev.write_register(convergence()).unwrap();
+    let _ = dev.release();
+    assert_eq!(counts(&bus), (0, 1));
+}
+
+#[test]
+fn idle_device_is_written_after_one_status_read() {
+    let mut bus = bus(false, false);
+    let mut dev = Device::new(&mut bus);
+    dev.set_busy_check(true);
+
+    dev.write_register(convergence()).unwrap();
+    let _ = dev.release();
+    assert_eq!(counts(&bus), (1, 1));
+    assert_eq!(bus.regs[0x1C], 30);
+}
+
+#[test]
+fn running_range_blocks_range_configuration() {
+    let mut bus = bus(true, false);
+    let mut dev = Device::new(&mut bus);
+    dev.set_busy_check(true);
+
+    assert_eq!(dev.write_register(convergence()), Err(Error::DeviceBusy));
+    let _ = dev.release();
+    assert!(bus.writes().is_empty());
+}
+
+#[test]
+fn running_range_leaves_als_configuration_writable() {
+    let mut bus = bus(true, false);
+    let mut dev = Device::new(&mut bus);
+    dev.set_busy_check(true);
+
+    dev.write_register(AlsIntegrationPeriod {
+        period: Duration::from_millis(100),
+    })
+    .unwrap();
+}
+
+#[test]
+fn shared_configuration_needs_both_idle() {
+    let mut bus = bus(false, true);
+    let mut dev = Device::new(&mut bus);
+    dev.set_busy_check(true);
+
+    assert_eq!(
+        dev.modify_register(|_: &mut InterruptConfigGpio| {}),
+        Err(Error::DeviceBusy)
+    );
+    assert_eq!(
+        dev.set_range_interrupt(RangeInterrupt::NewSampleReady),
+        Err(Error::DeviceBusy)
+    );
+}
+
+#[test]
+fn commands_and_reads_are_never_checked() {
+    let mut bus = bus(true, true);
+    let mut dev = Device::new(&mut bus);
+    dev.set_busy_check(true);
+
+    dev.write_register(RangeStart::Continuous).unwrap();
+    dev.write_register(InterruptClear {
+        clear_range: true,
+        clear_als: true,
+        clear_error: true,
+    })
+    .unwrap();
+    let _: RangeResultValue = dev.read_register().unwrap();
+    let _ = dev.release();
+    assert_eq!(counts(&bus), (1, 2));
+}
+
+#[test]
+fn classification() {
+    assert_eq!(RangeStart::BUSY_POLICY, BusyPolicy::Anytime);
+    assert_eq!(InterruptClear::BUSY_POLICY, BusyPolicy::Anytime);
+    assert_eq!(RangeMaxConvergenceTime::BUSY_POLICY, BusyPolicy::RangeIdle);
+    assert_eq!(AlsIntegrationPeriod::BUSY_POLICY, BusyPolicy::AlsIdle);
+    assert_eq!(InterruptConfigGpio::BUSY_POLICY, BusyPolicy::Idle);
+}
+
+#[test]
+fn wait_until_ready_returns_once_idle() {
+    let mut bus = bus(true, true);
+    bus.behavior.busy_polls = 3;
+    let mut dev = Device::new(&mut bus);
+
+    let waited = dev.wait_until_ready(&mut NoDelay, Duration::from_millis(10));
+    assert_eq!(waited, Ok(Duration::from_millis(2)));
+
+    dev.set_busy_check(true);
+    dev.write_register(convergence()).unwrap();
+}
+
+#[test]
+fn wait_until_ready_is_immediate_when_idle() {
+    let mut bus = bus(false, false);
+    let mut dev = Device::new(&mut bus);
+    assert_eq!(
+        dev.wait_until_ready(&mut NoDelay, Duration::ZERO),
+        Ok(Duration::ZERO)
+    );
+}
+
+#[test]
+fn wait_until_ready_times_out() {
+    let mut bus = bus(false, true);
+    let mut dev = Device::new(&mut bus);
+    assert_eq!(
+        dev.wait_until_ready(&mut NoDelay, Duration::from_millis(5)),
+        Err(Error::Timeout)
+    );
+    let _ = dev.release();
+    assert_eq!(bus.reads().len(), 6);
+}
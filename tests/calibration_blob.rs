@@ -0,0 +1,119 @@
+//! Calibration blob format and applying calibration to the device
+
+mod support;
+
+use measurements::Length;
+use support::{block_on, RegisterMap};
+use vl6180x::calibration::CalibrationData;
+use vl6180x::config::ConfigFormatError;
+use vl6180x::{Device, Error, ErrorContext};
+
+/// Version 1 encoding of [`calibration`], frozen when the format was
+/// introduced. Must stay readable by every later version.
+const V1_BLOB: [u8; 7] = [0x01, 0xFD, 0x02, 0x19, 0x01, 0xDA, 0x3C];
+
+fn calibration() -> CalibrationData {
+    CalibrationData {
+        offset: Length::from_millimeters(-3.0),
+        crosstalk_rate: 537,
+        scaling: 1,
+    }
+}
+
+#[test]
+fn round_trip() {
+    let mut buf = [0u8; 16];
+    let len = calibration().to_blob(&mut buf).unwrap();
+    assert_eq!(len, CalibrationData::ENCODED_LEN);
+    assert_eq!(CalibrationData::from_blob(&buf[..len]), Ok(calibration()));
+}
+
+#[test]
+fn frozen_v1_blob() {
+    let mut buf = [0u8; CalibrationData::ENCODED_LEN];
+    calibration().to_blob(&mut buf).unwrap();
+    assert_eq!(buf, V1_BLOB);
+    assert_eq!(CalibrationData::from_blob(&V1_BLOB), Ok(calibration()));
+}
+
+#[test]
+fn corrupted_bytes_are_rejected() {
+    for i in 1..CalibrationData::ENCODED_LEN {
+        let mut blob = V1_BLOB;
+        blob[i] ^= 0x10;
+        assert_eq!(
+            CalibrationData::from_blob(&blob),
+            Err(ConfigFormatError::ChecksumMismatch),
+            "flipped a bit in byte {i}"
+        );
+    }
+}
+
+#[test]
+fn malformed_blobs() {
+    assert_eq!(
+        CalibrationData::from_blob(&V1_BLOB[..6]),
+        Err(ConfigFormatError::BufferTooSmall)
+    );
+    assert_eq!(
+        CalibrationData::from_blob(&[]),
+        Err(ConfigFormatError::BufferTooSmall)
+    );
+    // Erased EEPROM
+    assert_eq!(
+        CalibrationData::from_blob(&[0xFF; 7]),
+        Err(ConfigFormatError::UnsupportedVersion(0xFF))
+    );
+    assert_eq!(
+        calibration().to_blob(&mut [0u8; 6]),
+        Err(ConfigFormatError::BufferTooSmall)
+    );
+    let out_of_range = CalibrationData {
+        offset: Length::from_millimeters(128.0),
+        ..calibration()
+    };
+    assert_eq!(
+        out_of_range.to_blob(&mut [0u8; 7]),
+        Err(ConfigFormatError::InvalidField)
+    );
+}
+
+#[test]
+fn apply_and_read_back() {
+    let mut bus = RegisterMap::new();
+    let mut dev = Device::new(&mut bus);
+    dev.apply_calibration(&calibration()).unwrap();
+    assert_eq!(dev.read_calibration(), Ok(calibration()));
+    let _ = dev.release();
+
+    assert_eq!(bus.regs[0x024], 0xFD);
+    assert_eq!(bus.regs[0x01E..0x020], [0x02, 0x19]);
+}
+
+#[test]
+fn unsupported_scaling_is_refused() {
+    let mut bus = RegisterMap::new();
+    let mut dev = Device::new(&mut bus);
+    let upscaled = CalibrationData {
+        scaling: 2,
+        ..calibration()
+    };
+    assert_eq!(
+        dev.apply_calibration(&upscaled),
+        Err(Error::SerializationError(ErrorContext::argument()))
+    );
+    let _ = dev.release();
+    assert!(bus.regs.iter().all(|&byte| byte == 0));
+}
+
+#[test]
+fn async_matches_blocking() {
+    let mut bus = RegisterMap::new();
+    let mut dev = Device::new(&mut bus);
+    block_on(dev.apply_calibration_async(&calibration())).unwrap();
+    assert_eq!(block_on(dev.read_calibration_async()), Ok(calibration()));
+    let _ = dev.release();
+
+    assert_eq!(bus.regs[0x024], 0xFD);
+    assert_eq!(bus.regs[0x01E..0x020], [0x02, 0x19]);
+}
@@ -0,0 +1,111 @@
+//! Calibration relevant registers read and written as one group
+
+mod support;
+
+use measurements::Length;
+use support::{block_on, Log, RegisterMap};
+use vl6180x::calibration::{CalibrationData, CalibrationRegisters};
+use vl6180x::registers::{
+    RangeCrosstalkCompensationRate, RangeCrosstalkValidHeight, RangeIgnoreThreshold,
+    RangeIgnoreValidHeight, RangePartToPartOffset,
+};
+use vl6180x::Device;
+
+/// Register map after boot, with the SNR and early convergence checks on
+fn bus() -> RegisterMap {
+    RegisterMap::new().set(0x021, &[0x14]).set(0x02D, &[0x11])
+}
+
+fn registers() -> CalibrationRegisters {
+    CalibrationRegisters {
+        offset: RangePartToPartOffset {
+            offset: Length::from_millimeters(-7.0),
+        },
+        crosstalk_rate: RangeCrosstalkCompensationRate { rate: 0x0123 },
+        crosstalk_valid_height: RangeCrosstalkValidHeight {
+            height: Length::from_millimeters(30.0),
+        },
+        range_ignore: true,
+        range_ignore_valid_height: RangeIgnoreValidHeight {
+            height: Length::from_millimeters(255.0),
+        },
+        range_ignore_threshold: RangeIgnoreThreshold { rate: 0x00C0 },
+    }
+}
+
+#[test]
+fn written_set_reads_back() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.write_calibration_registers(&registers()).unwrap();
+    assert_eq!(dev.read_calibration_registers(), Ok(registers()));
+    let _ = dev.release();
+
+    assert_eq!(bus.regs[0x024], 0xF9);
+    assert_eq!(bus.regs[0x01E..0x020], [0x01, 0x23]);
+    assert_eq!(bus.regs[0x021], 30);
+    assert_eq!(bus.regs[0x025], 0xFF);
+    assert_eq!(bus.regs[0x026..0x028], [0x00, 0xC0]);
+}
+
+#[test]
+fn writes_are_grouped_and_keep_the_other_checks() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.write_calibration_registers(&registers()).unwrap();
+    let _ = dev.release();
+
+    let writes = bus.writes();
+    assert_eq!(
+        bus.registers_written(),
+        [0x017, 0x024, 0x01E, 0x021, 0x025, 0x026, 0x02D, 0x017]
+    );
+    assert_eq!(writes[0].1, [0x01]);
+    assert_eq!(writes[7].1, [0x00]);
+    assert_eq!(bus.regs[0x02D], 0x13);
+}
+
+#[test]
+fn disabling_range_ignore_clears_only_its_bit() {
+    let mut bus = bus();
+    bus.regs[0x02D] = 0x13;
+    let mut dev = Device::new(&mut bus);
+    dev.write_calibration_registers(&CalibrationRegisters {
+        range_ignore: false,
+        ..registers()
+    })
+    .unwrap();
+    let _ = dev.release();
+
+    assert_eq!(bus.regs[0x02D], 0x11);
+}
+
+#[test]
+fn persistable_part_converts_to_calibration_data() {
+    assert_eq!(
+        CalibrationData::from(&registers()),
+        CalibrationData {
+            offset: Length::from_millimeters(-7.0),
+            crosstalk_rate: 0x0123,
+            scaling: 1,
+        }
+    );
+}
+
+#[test]
+fn async_matches_blocking() {
+    let mut sync_bus = bus();
+    let mut dev = Device::new(&mut sync_bus);
+    dev.write_calibration_registers(&registers()).unwrap();
+    let sync_read = dev.read_calibration_registers();
+    let _ = dev.release();
+
+    let mut async_bus = bus();
+    let mut dev = Device::new(&mut async_bus);
+    block_on(dev.write_calibration_registers_async(&registers())).unwrap();
+    let async_read = block_on(dev.read_calibration_registers_async());
+    let _ = dev.release();
+
+    assert_eq!(async_read, sync_read);
+    assert_eq!(async_bus.writes(), sync_bus.writes());
+}
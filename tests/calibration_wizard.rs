@@ -0,0 +1,210 @@
+//! Guided calibration on a simulated sensor
+
+mod support;
+
+use core::cell::Cell;
+
+use embedded_hal::i2c::ErrorKind;
+use measurements::Length;
+use support::{block_on, clear_interrupts, store, Behavior, NoDelay, RegisterMap, Registers};
+use vl6180x::calibration::CalibrationData;
+use vl6180x::wizard::{CalibrationWizard, WizardConfig, WizardStep};
+use vl6180x::{Device, Error, RangeErrorCode};
+
+/// What the sensor is looking at
+#[derive(Clone, Copy)]
+enum Scene {
+    /// Nothing in range
+    Empty,
+    /// A target at this distance in mm, returning this signal rate (9.7 Mcps)
+    Target(u8, u16),
+}
+
+/// Sensor with a part-to-part error of +7mm that applies its offset register
+/// to every range, looking at a scene the test changes between steps
+struct Wizarded<'a> {
+    scene: &'a Cell<Scene>,
+}
+
+impl Wizarded<'_> {
+    const PART_ERROR_MM: i16 = 7;
+
+    /// Completes a range measurement of the current scene
+    fn measure(&self, regs: &mut Registers) {
+        let (status, distance, rate) = match self.scene.get() {
+            Scene::Empty => (0xB1, 255, 0),
+            Scene::Target(mm, rate) => {
+                let offset = i16::from(regs[0x024] as i8);
+                let mm = (i16::from(mm) + Self::PART_ERROR_MM + offset).clamp(0, 255);
+                (0x01, mm as u8, rate)
+            }
+        };
+        regs[0x04D] = status;
+        regs[0x062] = distance;
+        store(regs, 0x066, &rate.to_be_bytes());
+        regs[0x04F] |= 0x04;
+    }
+}
+
+impl Behavior for Wizarded<'_> {
+    fn write(&mut self, regs: &mut Registers, register: u16, data: &[u8]) -> Result<(), ErrorKind> {
+        store(regs, register, data);
+        match (register, data[0]) {
+            (0x018, 0x01) => self.measure(regs),
+            (0x015, clear) => clear_interrupts(regs, clear),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+type Sensor<'a> = Device<RegisterMap<Wizarded<'a>>>;
+
+/// Sensor looking at `scene`, with a factory calibration the wizard
+/// overwrites
+fn sensor(scene: &Cell<Scene>) -> Sensor<'_> {
+    Device::new(
+        RegisterMap::with(Wizarded { scene })
+            .set(0x024, &[3])
+            .set(0x01E, &[0x00, 0x20]),
+    )
+}
+
+/// Reads `len` bytes starting at `register` back from the sensor
+fn registers(dev: &mut Sensor<'_>, register: u16, len: usize) -> Vec<u8> {
+    let mut bytes = vec![0; len];
+    dev.read_raw_into(register, &mut bytes).unwrap();
+    bytes
+}
+
+/// The calibration of the simulated sensor: -7mm offset, and a dark target
+/// at 100mm measured at 80mm with a return rate of 64 gives 64 × 0.2
+fn expected() -> CalibrationData {
+    CalibrationData {
+        offset: Length::from_millimeters(-7.0),
+        crosstalk_rate: 13,
+        scaling: 1,
+    }
+}
+
+#[test]
+fn walks_through_both_calibrations() {
+    let scene = Cell::new(Scene::Empty);
+    let mut dev = sensor(&scene);
+    let mut wizard = CalibrationWizard::new(WizardConfig::default());
+    assert_eq!(wizard.current_step(), WizardStep::PlaceOffsetTarget);
+
+    scene.set(Scene::Target(50, 200));
+    assert_eq!(
+        wizard.advance(&mut dev, &mut NoDelay),
+        Ok(WizardStep::MeasureOffset)
+    );
+    assert_eq!(
+        wizard.advance(&mut dev, &mut NoDelay),
+        Ok(WizardStep::PlaceCrosstalkTarget)
+    );
+    assert_eq!(registers(&mut dev, 0x024, 1), [(-7i8) as u8]);
+
+    // The wizard waits as long as the operator needs
+    scene.set(Scene::Target(80, 64));
+    assert_eq!(
+        wizard.advance(&mut dev, &mut NoDelay),
+        Ok(WizardStep::MeasureCrosstalk)
+    );
+    assert_eq!(wizard.result(), None);
+    assert_eq!(wizard.advance(&mut dev, &mut NoDelay), Ok(WizardStep::Done));
+
+    assert_eq!(wizard.result(), Some(expected()));
+    assert_eq!(registers(&mut dev, 0x01E, 2), [0x00, 13]);
+    assert_eq!(dev.read_calibration(), Ok(expected()));
+
+    // Finished wizards stay put
+    assert_eq!(wizard.advance(&mut dev, &mut NoDelay), Ok(WizardStep::Done));
+}
+
+#[test]
+fn failed_step_goes_back_to_its_placement_and_retries() {
+    let scene = Cell::new(Scene::Empty);
+    let mut dev = sensor(&scene);
+    let mut wizard = CalibrationWizard::new(WizardConfig::default());
+
+    // The operator forgot the target
+    wizard.advance(&mut dev, &mut NoDelay).unwrap();
+    assert_eq!(
+        wizard.advance(&mut dev, &mut NoDelay),
+        Err(Error::RangeError(RangeErrorCode::SignalToNoiseRatio))
+    );
+    assert_eq!(wizard.current_step(), WizardStep::PlaceOffsetTarget);
+    assert_eq!(wizard.retries_left(), 1);
+
+    scene.set(Scene::Target(50, 200));
+    wizard.advance(&mut dev, &mut NoDelay).unwrap();
+    assert_eq!(
+        wizard.advance(&mut dev, &mut NoDelay),
+        Ok(WizardStep::PlaceCrosstalkTarget)
+    );
+    // Each measurement step has its own retries
+    assert_eq!(wizard.retries_left(), 2);
+
+    scene.set(Scene::Target(80, 64));
+    wizard.advance(&mut dev, &mut NoDelay).unwrap();
+    wizard.advance(&mut dev, &mut NoDelay).unwrap();
+    assert_eq!(wizard.result(), Some(expected()));
+}
+
+#[test]
+fn gives_up_once_the_retries_are_used() {
+    let scene = Cell::new(Scene::Empty);
+    let mut dev = sensor(&scene);
+    let mut wizard = CalibrationWizard::new(WizardConfig {
+        retries: 1,
+        ..WizardConfig::default()
+    });
+
+    for _ in 0..2 {
+        wizard.advance(&mut dev, &mut NoDelay).unwrap();
+        assert!(wizard.advance(&mut dev, &mut NoDelay).is_err());
+    }
+    assert_eq!(wizard.current_step(), WizardStep::Failed);
+    assert!(wizard.current_step().is_finished());
+    assert_eq!(wizard.result(), None);
+
+    wizard.restart();
+    assert_eq!(wizard.current_step(), WizardStep::PlaceOffsetTarget);
+    assert_eq!(wizard.retries_left(), 1);
+}
+
+#[test]
+fn offset_beyond_the_register_range_fails_the_step() {
+    let scene = Cell::new(Scene::Empty);
+    let mut dev = sensor(&scene);
+    let mut wizard = CalibrationWizard::new(WizardConfig {
+        offset_distance: Length::from_millimeters(200.0),
+        ..WizardConfig::default()
+    });
+
+    scene.set(Scene::Target(20, 200));
+    wizard.advance(&mut dev, &mut NoDelay).unwrap();
+    assert!(matches!(
+        wizard.advance(&mut dev, &mut NoDelay),
+        Err(Error::OutOfSpec(_))
+    ));
+    assert_eq!(wizard.current_step(), WizardStep::PlaceOffsetTarget);
+}
+
+#[test]
+fn async_matches_blocking() {
+    let scene = Cell::new(Scene::Empty);
+    let mut dev = sensor(&scene);
+    let mut wizard = CalibrationWizard::new(WizardConfig::default());
+    scene.set(Scene::Target(50, 200));
+    for _ in 0..2 {
+        block_on(wizard.advance_async(&mut dev, &mut NoDelay)).unwrap();
+    }
+    scene.set(Scene::Target(80, 64));
+    for _ in 0..2 {
+        block_on(wizard.advance_async(&mut dev, &mut NoDelay)).unwrap();
+    }
+
+    assert_eq!(wizard.result(), Some(expected()));
+}
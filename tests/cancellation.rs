@@ -0,0 +1,158 @@
+//! Single-shot measurements recover from dropped futures and failed waits
+
+mod support;
+
+use core::future::Future;
+use core::pin::{pin, Pin};
+use core::task::{Context, Poll, Waker};
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::I2c;
+use measurements::Length;
+use support::{block_on, NoDelay, SimulatedVl6180x};
+use vl6180x::{Device, Error};
+
+/// Delay that returns to the executor once before completing
+struct YieldingDelay;
+
+/// Future pending on its first poll
+struct Yield(bool);
+
+impl Future for Yield {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            Poll::Pending
+        }
+    }
+}
+
+impl embedded_hal_async::delay::DelayNs for YieldingDelay {
+    async fn delay_ns(&mut self, _: u32) {
+        Yield(false).await;
+    }
+}
+
+impl DelayNs for YieldingDelay {
+    fn delay_ns(&mut self, _: u32) {}
+}
+
+/// Starts an async measurement on a stalled sensor and drops it mid-wait,
+/// then lets the abandoned measurement complete at 50mm and moves the
+/// target to 80mm
+fn abandon_measurement(mut dev: Device<&mut SimulatedVl6180x>) -> Device<&mut SimulatedVl6180x> {
+    let (sim, state) = dev.into_parts();
+    sim.stall();
+    dev = Device::from_parts(sim, state);
+    let mut delay = YieldingDelay;
+    {
+        let mut future = pin!(dev.measure_range_single_async(&mut delay));
+        let mut cx = Context::from_waker(Waker::noop());
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+    }
+
+    let (sim, state) = dev.into_parts();
+    sim.resume();
+    complete_at_50mm_then_move(sim);
+    Device::from_parts(sim, state)
+}
+
+/// Lets the running measurement report its sample, then moves the target
+/// from 50mm to 80mm
+fn complete_at_50mm_then_move(sim: &mut SimulatedVl6180x) {
+    let mut status = [0];
+    I2c::write_read(sim, 0x29, &[0x00, 0x4F], &mut status).unwrap();
+    assert_eq!(status[0] & 0x07, 0x04);
+    sim.set_distance(80);
+}
+
+#[test]
+fn dropped_future_leaves_no_stale_sample() {
+    let mut sim = SimulatedVl6180x::new();
+    let mut dev = abandon_measurement(Device::new(&mut sim));
+
+    let distance = block_on(dev.measure_range_single_async(&mut YieldingDelay));
+    assert_eq!(distance, Ok(Length::from_millimeters(80.0)));
+    let _ = dev.release();
+    assert_eq!(sim.range_starts(), 2);
+    assert_eq!(sim.register(0x04F) & 0x07, 0);
+}
+
+#[test]
+fn blocking_call_recovers_too() {
+    let mut sim = SimulatedVl6180x::new();
+    let mut dev = abandon_measurement(Device::new(&mut sim));
+
+    let distance = dev.measure_range_single(&mut YieldingDelay);
+    assert_eq!(distance, Ok(Length::from_millimeters(80.0)));
+}
+
+#[test]
+fn recovers_after_a_failed_wait() {
+    let mut sim = SimulatedVl6180x::new();
+    // The first interrupt status poll fails after the measurement started
+    sim.nack_transaction(1);
+    let mut dev = Device::new(&mut sim);
+
+    let result = block_on(dev.measure_range_single_async(&mut YieldingDelay));
+    assert!(matches!(result, Err(Error::BusError(_))));
+    let (sim, state) = dev.into_parts();
+    complete_at_50mm_then_move(sim);
+    let mut dev = Device::from_parts(sim, state);
+
+    let distance = block_on(dev.measure_range_single_async(&mut YieldingDelay));
+    assert_eq!(distance, Ok(Length::from_millimeters(80.0)));
+}
+
+#[test]
+fn blocking_call_recovers_after_a_failed_wait() {
+    let mut sim = SimulatedVl6180x::new();
+    // The first interrupt status poll fails after the measurement started
+    sim.nack_transaction(1);
+    let mut dev = Device::new(&mut sim);
+
+    let result = dev.measure_range_single(&mut NoDelay);
+    assert!(matches!(result, Err(Error::BusError(_))));
+    let (sim, state) = dev.into_parts();
+    complete_at_50mm_then_move(sim);
+    let mut dev = Device::from_parts(sim, state);
+
+    let distance = dev.measure_range_single(&mut NoDelay);
+    assert_eq!(distance, Ok(Length::from_millimeters(80.0)));
+    let sim = dev.release();
+    assert_eq!(sim.range_starts(), 2);
+}
+
+#[test]
+fn blocking_call_recovers_after_a_timeout() {
+    let mut sim = SimulatedVl6180x::new();
+    sim.stall();
+    let mut dev = Device::new(&mut sim);
+
+    assert_eq!(dev.measure_range_single(&mut NoDelay), Err(Error::Timeout));
+    let (sim, state) = dev.into_parts();
+    sim.resume();
+    complete_at_50mm_then_move(sim);
+    let mut dev = Device::from_parts(sim, state);
+
+    let distance = dev.measure_range_single(&mut NoDelay);
+    assert_eq!(distance, Ok(Length::from_millimeters(80.0)));
+    let sim = dev.release();
+    assert_eq!(sim.range_starts(), 2);
+    assert_eq!(sim.register(0x04F) & 0x07, 0);
+}
+
+#[test]
+fn completed_measurements_cost_no_recovery() {
+    let mut sim = SimulatedVl6180x::new();
+    let mut dev = Device::new(&mut sim);
+
+    block_on(dev.measure_range_single_async(&mut YieldingDelay)).unwrap();
+    block_on(dev.measure_range_single_async(&mut YieldingDelay)).unwrap();
+    let _ = dev.release();
+    assert_eq!(sim.transactions(), 10);
+}
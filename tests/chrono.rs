@@ -0,0 +1,24 @@
+//! Module timestamps as `chrono` dates
+#![cfg(feature = "chrono")]
+
+use regiface::FromByteArray;
+use vl6180x::registers::ModuleTimestamp;
+
+fn as_chrono(bytes: [u8; 4]) -> String {
+    let module = ModuleTimestamp::from_bytes(bytes).unwrap();
+    module.as_chrono().to_string()
+}
+
+#[test]
+fn timestamps_convert_to_the_same_date_and_time() {
+    assert_eq!(as_chrono([0x5A, 0x98, 0x54, 0x60]), "2015-10-19 12:00:00");
+    assert_eq!(as_chrono([0x11, 0x08, 0x00, 0x00]), "2011-01-01 00:00:00");
+    // Last two-second step of the day
+    assert_eq!(as_chrono([0xFC, 0xF8, 0xA8, 0xBF]), "2025-12-31 23:59:58");
+}
+
+#[test]
+fn blank_timestamp_never_reaches_chrono() {
+    // An unprogrammed module reads all zeroes: month and day 0
+    assert!(ModuleTimestamp::from_bytes([0x00; 4]).is_err());
+}
@@ -0,0 +1,153 @@
+//! Configuration registers served from the cache
+
+mod support;
+
+use embedded_hal::i2c::ErrorKind;
+use support::{store, Behavior, Log, NoDelay, RegisterMap, Registers};
+use vl6180x::registers::{AlsAnalogueGain, FreshOutOfReset, InterruptConfigGpio};
+use vl6180x::{AlsGain, Device, InterruptMode, Luminance, RangeInterrupt};
+
+/// Keeps the ALS sample and its interrupt across start and clear
+struct HeldSample;
+
+impl Behavior for HeldSample {
+    fn write(&mut self, regs: &mut Registers, register: u16, data: &[u8]) -> Result<(), ErrorKind> {
+        if !matches!(register, 0x015 | 0x038) {
+            store(regs, register, data);
+        }
+        Ok(())
+    }
+}
+
+type Bus = RegisterMap<HeldSample>;
+
+/// Sensor whose ALS sample is always ready, reporting 100 counts at gain 1
+/// and 100ms
+fn bus() -> Bus {
+    RegisterMap::with(HeldSample)
+        .set(0x03F, &[0x46])
+        .set(0x041, &[0x63])
+        .set(0x04E, &[0x01])
+        .set(0x04F, &[0x20])
+        .set(0x051, &[100])
+}
+
+#[test]
+fn als_measurement_reads_gain_and_integration_once() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    for _ in 0..3 {
+        assert_eq!(
+            dev.measure_als_single(&mut NoDelay),
+            Ok(Luminance::from_lux(32.0 / 1.01))
+        );
+    }
+    let _ = dev.release();
+
+    // Status and result each time, gain and integration period only once
+    assert_eq!(
+        bus.registers_read(),
+        [0x04F, 0x04E, 0x03F, 0x040, 0x04F, 0x04E, 0x04F, 0x04E]
+    );
+    // Start, status, result, clear, plus gain and integration the first time
+    assert_eq!(bus.transactions(), 6 + 4 + 4);
+}
+
+#[test]
+fn typed_writes_update_the_cache() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.write_register(AlsAnalogueGain::new(AlsGain::Gain10))
+        .unwrap();
+    assert_eq!(
+        dev.measure_als_single(&mut NoDelay),
+        Ok(Luminance::from_lux(32.0 / 10.32))
+    );
+    let _ = dev.release();
+
+    assert_eq!(bus.registers_read(), [0x04F, 0x04E, 0x040]);
+}
+
+#[test]
+fn raw_writes_invalidate_the_cache() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.measure_als_single(&mut NoDelay).unwrap();
+    dev.write_block(0x03F, &[0x41]).unwrap();
+    assert_eq!(
+        dev.measure_als_single(&mut NoDelay),
+        Ok(Luminance::from_lux(32.0 / 10.32))
+    );
+    let _ = dev.release();
+
+    assert_eq!(bus.registers_read()[4..], [0x04F, 0x04E, 0x03F, 0x040]);
+}
+
+#[test]
+fn fresh_out_of_reset_invalidates_the_cache() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.measure_als_single(&mut NoDelay).unwrap();
+
+    // Not fresh: the cache is kept
+    let fresh: FreshOutOfReset = dev.read_register().unwrap();
+    assert!(!fresh.fresh);
+    dev.measure_als_single(&mut NoDelay).unwrap();
+    let (bus, state) = dev.into_parts();
+    assert_eq!(bus.registers_read()[4..], [0x016, 0x04F, 0x04E]);
+
+    // The device was reset behind the driver's back
+    bus.regs[0x016] = 0x01;
+    bus.regs[0x03F] = 0x41;
+    bus.log.clear();
+    let mut dev = Device::from_parts(bus, state);
+    let fresh: FreshOutOfReset = dev.read_register().unwrap();
+    assert!(fresh.fresh);
+    assert_eq!(
+        dev.measure_als_single(&mut NoDelay),
+        Ok(Luminance::from_lux(32.0 / 10.32))
+    );
+    let bus = dev.release();
+    assert_eq!(bus.registers_read(), [0x016, 0x04F, 0x04E, 0x03F, 0x040]);
+}
+
+#[test]
+fn explicit_invalidation_rereads() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.measure_als_single(&mut NoDelay).unwrap();
+    dev.invalidate_cache();
+    dev.measure_als_single(&mut NoDelay).unwrap();
+    let _ = dev.release();
+
+    assert_eq!(bus.registers_read()[4..], [0x04F, 0x04E, 0x03F, 0x040]);
+}
+
+#[test]
+fn interrupt_helpers_modify_the_cached_configuration() {
+    let mut bus = bus();
+    bus.regs[0x014] = 0x20;
+    let mut dev = Device::new(&mut bus);
+    dev.set_range_interrupt(RangeInterrupt::NewSampleReady)
+        .unwrap();
+    dev.set_range_interrupt(RangeInterrupt::Disabled).unwrap();
+    let config: InterruptConfigGpio = dev.read_register().unwrap();
+    let _ = dev.release();
+
+    assert_eq!(bus.registers_read(), [0x014]);
+    assert_eq!(config.range_interrupt, InterruptMode::Disabled);
+    assert_eq!(config.als_interrupt, InterruptMode::NewSampleReady);
+    assert_eq!(bus.regs[0x014], 0x20);
+}
+
+#[test]
+fn register_reads_into_a_buffer_bypass_the_cache() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    let _: AlsAnalogueGain = dev.read_register().unwrap();
+    let mut buf = [0];
+    dev.read_register_into::<AlsAnalogueGain>(&mut buf).unwrap();
+    let _ = dev.release();
+
+    assert_eq!(bus.registers_read(), [0x03F, 0x03F]);
+}
@@ -0,0 +1,137 @@
+//! Stability tests for the [`FullConfig`] binary format
+
+use core::time::Duration;
+
+use measurements::Length;
+use vl6180x::config::ConfigFormatError;
+use vl6180x::registers::*;
+use vl6180x::types::*;
+use vl6180x::FullConfig;
+
+/// Version 1 encoding of [`config`], frozen when the format was introduced.
+/// Must never change.
+const V1_BLOB: [u8; FullConfig::ENCODED_LEN] = [
+    0x01, 0x00, 0xC8, 0x00, 0x32, 0x09, 0x1E, 0x00, 0x00, 0x14, 0x00, 0x8E, 0x11, 0xFF, 0x01, 0x90,
+    0x00, 0x64, 0x31, 0x46, 0x00, 0x63, 0x24, 0x00, 0x10, 0xA1, 0x40,
+];
+
+fn config() -> FullConfig {
+    FullConfig {
+        range_thresholds: RangeThresholds {
+            high: Length::from_millimeters(200.0),
+            low: Length::from_millimeters(50.0),
+        },
+        range_intermeasurement_period: RangeIntermeasurementPeriod {
+            period: Duration::from_millis(100),
+        },
+        range_max_convergence_time: RangeMaxConvergenceTime {
+            time: Duration::from_millis(30),
+        },
+        range_crosstalk_compensation_rate: RangeCrosstalkCompensationRate { rate: 0 },
+        range_crosstalk_valid_height: RangeCrosstalkValidHeight {
+            height: Length::from_millimeters(20.0),
+        },
+        range_early_convergence_estimate: RangeEarlyConvergenceEstimate { estimate: 0x008E },
+        range_check_enables: RangeCheckEnables {
+            enable_snr_check: true,
+            enable_range_check: false,
+            enable_early_convergence_check: true,
+        },
+        range_vhv_repeat_rate: RangeVhvRepeatRate { rate: 0xFF },
+        als_thresholds: AlsThresholds {
+            high: Luminance { lux: 400.0 },
+            low: Luminance { lux: 100.0 },
+        },
+        als_intermeasurement_period: AlsIntermeasurementPeriod {
+            period: Duration::from_millis(500),
+        },
+        als_analogue_gain: AlsAnalogueGain {
+            gain: AlsGain::Gain1,
+        },
+        als_integration_period: AlsIntegrationPeriod {
+            period: Duration::from_millis(100),
+        },
+        interrupt_config: InterruptConfigGpio {
+            range_interrupt: InterruptMode::NewSampleReady,
+            als_interrupt: InterruptMode::NewSampleReady,
+        },
+        gpio0: ModeGpio0 {
+            function: GpioFunction::Off,
+            polarity: GpioPolarity::ActiveLow,
+        },
+        gpio1: ModeGpio1 {
+            function: GpioFunction::InterruptOutput,
+            polarity: GpioPolarity::ActiveLow,
+        },
+    }
+}
+
+#[test]
+fn round_trip() {
+    let mut buf = [0u8; 32];
+    let len = config().to_bytes(&mut buf).unwrap();
+    assert_eq!(len, FullConfig::ENCODED_LEN);
+    assert_eq!(FullConfig::from_bytes(&buf[..len]), Ok(config()));
+}
+
+#[test]
+fn frozen_v1_blob() {
+    let mut buf = [0u8; FullConfig::ENCODED_LEN];
+    config().to_bytes(&mut buf).unwrap();
+    assert_eq!(buf, V1_BLOB);
+    assert_eq!(FullConfig::from_bytes(&V1_BLOB), Ok(config()));
+}
+
+#[test]
+fn buffer_too_small() {
+    let mut buf = [0u8; FullConfig::ENCODED_LEN - 1];
+    assert_eq!(
+        config().to_bytes(&mut buf),
+        Err(ConfigFormatError::BufferTooSmall)
+    );
+    assert_eq!(
+        FullConfig::from_bytes(&V1_BLOB[..FullConfig::ENCODED_LEN - 1]),
+        Err(ConfigFormatError::BufferTooSmall)
+    );
+}
+
+#[test]
+fn unsupported_version() {
+    let mut blob = V1_BLOB;
+    blob[0] = 2;
+    assert_eq!(
+        FullConfig::from_bytes(&blob),
+        Err(ConfigFormatError::UnsupportedVersion(2))
+    );
+}
+
+#[test]
+fn half_written_page() {
+    // Erased flash reads back as 0xFF
+    let mut blob = V1_BLOB;
+    blob[FullConfig::ENCODED_LEN / 2..].fill(0xFF);
+    assert_eq!(
+        FullConfig::from_bytes(&blob),
+        Err(ConfigFormatError::ChecksumMismatch)
+    );
+}
+
+#[test]
+fn corrupted_field() {
+    let mut blob = V1_BLOB;
+    blob[5] ^= 0x01;
+    assert_eq!(
+        FullConfig::from_bytes(&blob),
+        Err(ConfigFormatError::ChecksumMismatch)
+    );
+}
+
+#[test]
+fn unencodable_field() {
+    let mut config = config();
+    config.range_max_convergence_time.time = Duration::from_millis(64);
+    assert_eq!(
+        config.to_bytes(&mut [0u8; FullConfig::ENCODED_LEN]),
+        Err(ConfigFormatError::InvalidField)
+    );
+}
@@ -0,0 +1,62 @@
+//! Configuration values built and validated in const context
+
+use core::time::Duration;
+
+use vl6180x::registers::{
+    AlsAnalogueGain, AlsIntegrationPeriod, AlsIntermeasurementPeriod, RangeIntermeasurementPeriod,
+    RangeMaxConvergenceTime, RangeVhvRepeatRate, ReadoutAveraging,
+};
+use vl6180x::AlsGain;
+
+static CONVERGENCE: RangeMaxConvergenceTime = RangeMaxConvergenceTime::from_ms::<49>();
+static RANGE_PERIOD: RangeIntermeasurementPeriod = RangeIntermeasurementPeriod::from_ms::<100>();
+static ALS_PERIOD: AlsIntermeasurementPeriod = AlsIntermeasurementPeriod::from_ms::<2550>();
+static INTEGRATION: AlsIntegrationPeriod = AlsIntegrationPeriod::from_ms::<512>();
+static AVERAGING: ReadoutAveraging = ReadoutAveraging::new(32);
+static VHV_REPEAT: RangeVhvRepeatRate = RangeVhvRepeatRate::new(255);
+static GAIN: AlsAnalogueGain = AlsAnalogueGain::new(AlsGain::Gain10);
+
+// Rejections, evaluated at compile time
+const _: () = assert!(RangeMaxConvergenceTime::checked_ms(0).is_none());
+const _: () = assert!(RangeMaxConvergenceTime::checked_ms(64).is_none());
+const _: () = assert!(RangeIntermeasurementPeriod::checked_ms(5).is_none());
+const _: () = assert!(RangeIntermeasurementPeriod::checked_ms(105).is_none());
+const _: () = assert!(RangeIntermeasurementPeriod::checked_ms(2560).is_none());
+const _: () = assert!(AlsIntermeasurementPeriod::checked_ms(0).is_none());
+const _: () = assert!(AlsIntegrationPeriod::checked_ms(513).is_none());
+
+#[test]
+fn accepted_values_hold_what_was_asked_for() {
+    assert_eq!(CONVERGENCE.time, Duration::from_millis(49));
+    assert_eq!(RANGE_PERIOD.period, Duration::from_millis(100));
+    assert_eq!(ALS_PERIOD.period, Duration::from_millis(2550));
+    assert_eq!(INTEGRATION.period, Duration::from_millis(512));
+    assert_eq!(AVERAGING.samples, 32);
+    assert_eq!(VHV_REPEAT.rate, 255);
+    assert_eq!(GAIN.gain, AlsGain::Gain10);
+}
+
+#[test]
+fn checked_constructors_follow_the_datasheet_limits() {
+    assert_eq!(
+        RangeMaxConvergenceTime::checked_ms(1),
+        Some(RangeMaxConvergenceTime {
+            time: Duration::from_millis(1)
+        })
+    );
+    assert!(RangeMaxConvergenceTime::checked_ms(63).is_some());
+    assert!(RangeIntermeasurementPeriod::checked_ms(10).is_some());
+    assert!(RangeIntermeasurementPeriod::checked_ms(2550).is_some());
+    assert!(AlsIntegrationPeriod::checked_ms(1).is_some());
+    assert!(AlsIntegrationPeriod::checked_ms(0).is_none());
+}
+
+#[test]
+fn accepted_values_pass_strict_checks() {
+    use vl6180x::registers::DatasheetLimits;
+
+    assert!(CONVERGENCE.check_limits().is_ok());
+    assert!(RANGE_PERIOD.check_limits().is_ok());
+    assert!(ALS_PERIOD.check_limits().is_ok());
+    assert!(INTEGRATION.check_limits().is_ok());
+}
@@ -0,0 +1,146 @@
+//! Tracked continuous ALS measurements and reading their latest sample
+
+mod support;
+
+use core::time::Duration;
+
+use support::{block_on, NoDelay, SimulatedVl6180x};
+use vl6180x::registers::AlsIntegrationPeriod;
+use vl6180x::{AlsReading, Device, Error};
+
+/// Device with a 100ms integration period
+fn device(sim: &mut SimulatedVl6180x) -> Device<&mut SimulatedVl6180x> {
+    let mut dev = Device::new(sim);
+    dev.write_register(AlsIntegrationPeriod {
+        period: Duration::from_millis(100),
+    })
+    .unwrap();
+    dev
+}
+
+fn als_running(sim: &SimulatedVl6180x) -> bool {
+    sim.register(0x04E) & 0x01 == 0
+}
+
+#[test]
+fn start_programs_the_period() {
+    let mut sim = SimulatedVl6180x::new();
+    let mut dev = device(&mut sim);
+
+    dev.start_continuous_als(Duration::from_millis(500))
+        .unwrap();
+    assert!(dev.is_continuous_als_running());
+    let _ = dev.release();
+    assert_eq!(sim.register(0x03E), 49);
+    assert!(als_running(&sim));
+}
+
+#[test]
+fn period_shorter_than_integration_is_refused() {
+    let mut sim = SimulatedVl6180x::new();
+    let mut dev = device(&mut sim);
+
+    assert!(matches!(
+        dev.start_continuous_als(Duration::from_millis(100)),
+        Err(Error::PeriodTooShort(_))
+    ));
+    assert!(!dev.is_continuous_als_running());
+    let _ = dev.release();
+    assert!(!als_running(&sim));
+}
+
+#[test]
+fn second_start_is_refused() {
+    let mut sim = SimulatedVl6180x::new();
+    let mut dev = device(&mut sim);
+
+    dev.start_continuous_als(Duration::from_millis(500))
+        .unwrap();
+    assert_eq!(
+        dev.start_continuous_als(Duration::from_millis(200)),
+        Err(Error::DeviceBusy)
+    );
+    let _ = dev.release();
+    assert_eq!(sim.register(0x03E), 49);
+    assert!(als_running(&sim));
+}
+
+#[test]
+fn stop_waits_until_ready() {
+    let mut sim = SimulatedVl6180x::new();
+    let mut dev = device(&mut sim);
+
+    dev.start_continuous_als(Duration::from_millis(500))
+        .unwrap();
+    dev.stop_continuous_als(&mut NoDelay).unwrap();
+    assert!(!dev.is_continuous_als_running());
+    let _ = dev.release();
+    assert!(!als_running(&sim));
+}
+
+#[test]
+fn stop_without_start_does_nothing() {
+    let mut sim = SimulatedVl6180x::new();
+    let mut dev = Device::new(&mut sim);
+
+    dev.stop_continuous_als(&mut NoDelay).unwrap();
+    let _ = dev.release();
+    assert_eq!(sim.transactions(), 0);
+}
+
+#[test]
+fn latest_sample_is_new_once() {
+    let mut sim = SimulatedVl6180x::new();
+    let mut dev = device(&mut sim);
+
+    dev.start_continuous_als(Duration::from_millis(500))
+        .unwrap();
+    // The status read lets the sensor report a sample
+    assert!(dev.peek_interrupt_status().unwrap().als_interrupt);
+
+    let first = dev.read_latest_als().unwrap();
+    assert!(first.new);
+    assert!(matches!(first.reading, AlsReading::Valid(_)));
+    let again = dev.read_latest_als().unwrap();
+    assert!(!again.new);
+    assert_eq!(again.reading, first.reading);
+}
+
+#[test]
+fn single_shot_is_refused_while_measuring() {
+    let mut sim = SimulatedVl6180x::new();
+    let mut dev = device(&mut sim);
+
+    dev.start_continuous_als(Duration::from_millis(500))
+        .unwrap();
+    assert_eq!(dev.measure_als_single(&mut NoDelay), Err(Error::DeviceBusy));
+    assert_eq!(
+        block_on(dev.read_als_async(&mut NoDelay)),
+        Err(Error::DeviceBusy)
+    );
+    #[cfg(feature = "nb")]
+    assert_eq!(dev.try_read_als(), Err(nb::Error::Other(Error::DeviceBusy)));
+
+    dev.stop_continuous_als(&mut NoDelay).unwrap();
+    assert!(dev.measure_als_single(&mut NoDelay).is_ok());
+}
+
+#[test]
+fn async_round_trip() {
+    let mut sim = SimulatedVl6180x::new();
+    let mut dev = device(&mut sim);
+
+    let latest = block_on(async {
+        dev.start_continuous_als_async(Duration::from_millis(500))
+            .await?;
+        dev.peek_interrupt_status_async().await?;
+        let latest = dev.read_latest_als_async().await?;
+        dev.stop_continuous_als_async(&mut NoDelay).await?;
+        Ok::<_, Error>(latest)
+    })
+    .unwrap();
+    assert!(latest.new);
+    assert!(!dev.is_continuous_als_running());
+    let _ = dev.release();
+    assert!(!als_running(&sim));
+}
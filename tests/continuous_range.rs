@@ -0,0 +1,306 @@
+//! Tracked continuous ranging start and stop sequences
+
+mod support;
+
+use core::time::Duration;
+
+use measurements::Length;
+use support::{block_on, Log, NoDelay, SimulatedVl6180x};
+use vl6180x::registers::ResultInterruptStatusGpio;
+use vl6180x::{Device, Error, RangeReading};
+
+/// Sensor 100mm from its target
+///
+/// The ranging core reports ready `busy_polls` status reads after
+/// continuous ranging stops.
+fn sensor(busy_polls: u32) -> SimulatedVl6180x {
+    let mut sim = SimulatedVl6180x::new();
+    sim.set_distance(100);
+    sim.set_stop_polls(busy_polls.saturating_add(1));
+    sim
+}
+
+/// SYSRANGE__START written with the continuous start bit
+fn start() -> (Vec<u8>, usize) {
+    (vec![0x00, 0x18, 0x03], 0)
+}
+
+/// RESULT__RANGE_STATUS read
+fn status_read() -> (Vec<u8>, usize) {
+    (vec![0x00, 0x4D], 1)
+}
+
+/// Whether the ranging core of the sensor runs
+fn core_running(sim: &SimulatedVl6180x) -> bool {
+    sim.register(0x04D) & 0x01 == 0
+}
+
+#[test]
+fn start_writes_the_continuous_start_bit() {
+    let mut bus = sensor(0);
+    let mut dev = Device::new(&mut bus);
+
+    dev.start_continuous_range().unwrap();
+    assert!(dev.is_continuous_range_running());
+    let _ = dev.release();
+    assert_eq!(bus.bytes(), [start()]);
+    assert!(core_running(&bus));
+}
+
+#[test]
+fn second_start_does_not_toggle_ranging_off() {
+    let mut bus = sensor(0);
+    let mut dev = Device::new(&mut bus);
+
+    dev.start_continuous_range().unwrap();
+    dev.start_continuous_range().unwrap();
+    assert!(dev.is_continuous_range_running());
+    let _ = dev.release();
+    assert_eq!(bus.bytes(), [start()]);
+    assert!(core_running(&bus));
+    assert_eq!(bus.range_starts(), 1);
+}
+
+#[test]
+fn stop_writes_the_stop_bit_and_waits_until_ready() {
+    let mut bus = sensor(2);
+    let mut dev = Device::new(&mut bus);
+
+    dev.start_continuous_range().unwrap();
+    dev.stop_continuous_range(&mut NoDelay).unwrap();
+    assert!(!dev.is_continuous_range_running());
+    let _ = dev.release();
+    assert_eq!(
+        bus.bytes(),
+        [
+            start(),
+            start(),
+            status_read(),
+            status_read(),
+            status_read()
+        ]
+    );
+    assert!(!core_running(&bus));
+}
+
+#[test]
+fn stop_without_start_writes_nothing() {
+    let mut bus = sensor(0);
+    let mut dev = Device::new(&mut bus);
+
+    dev.stop_continuous_range(&mut NoDelay).unwrap();
+    let _ = dev.release();
+    assert!(bus.log().is_empty());
+}
+
+#[test]
+fn stop_reports_a_core_that_never_becomes_ready() {
+    let mut bus = sensor(u32::MAX);
+    let mut dev = Device::new(&mut bus);
+
+    dev.start_continuous_range().unwrap();
+    assert_eq!(dev.stop_continuous_range(&mut NoDelay), Err(Error::Timeout));
+    // The stop bit was written, so a retry must not write it again
+    assert!(!dev.is_continuous_range_running());
+    assert_eq!(dev.stop_continuous_range(&mut NoDelay), Ok(()));
+    let _ = dev.release();
+    assert_eq!(bus.registers_written(), [0x018, 0x018]);
+}
+
+#[test]
+fn single_shot_is_refused_while_ranging() {
+    let mut bus = sensor(0);
+    let mut dev = Device::new(&mut bus);
+
+    dev.start_continuous_range().unwrap();
+    assert_eq!(
+        dev.measure_range_single(&mut NoDelay),
+        Err(Error::DeviceBusy)
+    );
+    assert_eq!(dev.read_range(&mut NoDelay), Err(Error::DeviceBusy));
+    assert_eq!(
+        block_on(dev.measure_range_single_async(&mut NoDelay)),
+        Err(Error::DeviceBusy)
+    );
+    #[cfg(feature = "nb")]
+    assert_eq!(
+        dev.try_read_range(),
+        Err(nb::Error::Other(Error::DeviceBusy))
+    );
+    let _ = dev.release();
+    assert_eq!(bus.bytes(), [start()]);
+}
+
+#[test]
+fn single_shot_works_again_after_stop() {
+    let mut bus = sensor(0);
+    let mut dev = Device::new(&mut bus);
+
+    dev.start_continuous_range().unwrap();
+    dev.stop_continuous_range(&mut NoDelay).unwrap();
+    let distance = dev.measure_range_single(&mut NoDelay).unwrap();
+    assert_eq!(distance.as_millimeters(), 100.0);
+    let _ = dev.release();
+    assert_eq!(bus.range_starts(), 2);
+    assert!(!core_running(&bus));
+}
+
+#[test]
+fn ranging_started_by_other_helpers_is_tracked() {
+    let mut bus = sensor(0);
+    let mut dev = Device::new(&mut bus);
+
+    dev.arm_wake_on_approach(
+        &mut NoDelay,
+        Length::from_millimeters(60.0),
+        Duration::from_millis(500),
+    )
+    .unwrap();
+    assert!(dev.is_continuous_range_running());
+    assert_eq!(
+        dev.measure_range_single(&mut NoDelay),
+        Err(Error::DeviceBusy)
+    );
+    dev.restart_continuous_range(&mut NoDelay).unwrap();
+    assert!(dev.is_continuous_range_running());
+
+    dev.stop_continuous_range(&mut NoDelay).unwrap();
+    assert!(!dev.is_continuous_range_running());
+    let _ = dev.release();
+    assert!(!core_running(&bus));
+}
+
+#[test]
+fn stopping_a_scope_takes_over_running_ranging() {
+    let mut bus = sensor(0);
+    let mut dev = Device::new(&mut bus);
+
+    dev.start_continuous_range().unwrap();
+    drop(dev.continuous_ranging_scope().unwrap());
+    assert!(!dev.is_continuous_range_running());
+    assert_eq!(dev.last_drop_error(), None);
+    let _ = dev.release();
+    // Started once, stopped by the scope without waiting
+    assert_eq!(bus.bytes(), [start(), start()]);
+}
+
+#[test]
+fn async_sequences_match_blocking() {
+    let mut blocking = sensor(1);
+    let mut dev = Device::new(&mut blocking);
+    dev.start_continuous_range().unwrap();
+    dev.start_continuous_range().unwrap();
+    dev.stop_continuous_range(&mut NoDelay).unwrap();
+    let _ = dev.release();
+
+    let mut nonblocking = sensor(1);
+    let mut dev = Device::new(&mut nonblocking);
+    block_on(async {
+        dev.start_continuous_range_async().await?;
+        dev.start_continuous_range_async().await?;
+        dev.stop_continuous_range_async(&mut NoDelay).await
+    })
+    .unwrap();
+    let _ = dev.release();
+
+    assert_eq!(blocking.log(), nonblocking.log());
+    assert_eq!(
+        blocking.bytes(),
+        [start(), start(), status_read(), status_read()]
+    );
+}
+
+#[test]
+fn typed_handle_shares_the_tracked_state() {
+    let mut bus = sensor(0);
+    let ranging = Device::new(&mut bus).into_continuous_range().unwrap();
+
+    let mut dev = ranging.into_inner();
+    assert!(dev.is_continuous_range_running());
+    assert_eq!(
+        dev.measure_range_single(&mut NoDelay),
+        Err(Error::DeviceBusy)
+    );
+    dev.stop_continuous_range(&mut NoDelay).unwrap();
+
+    let idle = dev.into_idle();
+    let started = idle.start_continuous_ranging().unwrap();
+    let dev = started.stop(&mut NoDelay).unwrap().into_inner();
+    assert!(!dev.is_continuous_range_running());
+    let _ = dev.release();
+    assert_eq!(bus.range_starts(), 2);
+    assert!(!core_running(&bus));
+}
+
+#[test]
+fn typed_start_is_refused_during_interleaved_mode() {
+    let mut bus = sensor(0);
+    let mut dev = Device::new(&mut bus);
+    dev.start_interleaved(Duration::from_millis(2000)).unwrap();
+
+    let Err(refused) = dev.into_continuous_range() else {
+        panic!("started ranging during interleaved mode");
+    };
+    assert_eq!(refused.error, Error::DeviceBusy);
+    assert!(refused.device.is_interleaved_running());
+    let _ = refused.device.release();
+    assert_eq!(bus.range_starts(), 0);
+}
+
+#[test]
+fn typed_handle_round_trip() {
+    let mut bus = sensor(0);
+    let dev = Device::new(&mut bus);
+
+    let mut ranging = dev.into_continuous_range().unwrap();
+    let status: ResultInterruptStatusGpio = ranging.read_register().unwrap();
+    assert!(status.range_interrupt);
+    assert_eq!(
+        ranging.read_latest(),
+        Ok(RangeReading::Valid(Length::from_millimeters(100.0)))
+    );
+    ranging.clear_interrupt().unwrap();
+    let dev = ranging.stop(&mut NoDelay).unwrap().into_inner();
+    let _ = dev.release();
+
+    let status_read_gpio = (vec![0x00, 0x4F], 1);
+    let block_read = (vec![0x00, 0x4D], 0x62 - 0x4D + 1);
+    let clear = (vec![0x00, 0x15, 0x01], 0);
+    assert_eq!(
+        bus.bytes(),
+        [
+            start(),
+            status_read_gpio,
+            block_read,
+            clear,
+            start(),
+            status_read()
+        ]
+    );
+    assert_eq!(bus.register(0x04F) & 0x07, 0);
+    assert!(!core_running(&bus));
+}
+
+#[test]
+fn async_typed_handle_matches_blocking() {
+    let mut blocking = sensor(0);
+    let mut ranging = Device::new(&mut blocking).into_continuous_range().unwrap();
+    let reading = ranging.read_latest().unwrap();
+    ranging.clear_interrupt().unwrap();
+    let _ = ranging.stop(&mut NoDelay).unwrap();
+
+    let mut nonblocking = sensor(0);
+    let async_reading = block_on(async {
+        let mut ranging = Device::new(&mut nonblocking)
+            .into_continuous_range_async()
+            .await?;
+        let reading = ranging.read_latest_async().await?;
+        ranging.clear_interrupt_async().await?;
+        let _ = ranging.stop_async(&mut NoDelay).await?;
+        Ok::<_, Error>(reading)
+    })
+    .unwrap();
+
+    assert_eq!(reading, async_reading);
+    assert_eq!(blocking.log(), nonblocking.log());
+}
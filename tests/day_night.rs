@@ -0,0 +1,146 @@
+//! Day and night switching with hysteresis and dwell time
+
+mod support;
+
+use support::{Log, RegisterMap};
+use vl6180x::daynight::{DayNight, DayNightConfig, DayNightSwitch};
+use vl6180x::{AlsErrorCode, AlsReading, Device, InterruptMode, Luminance};
+
+/// Switch around 50 lux, day above 60 and night below 40, with a 300ms dwell
+fn switch(initial: DayNight) -> DayNightSwitch {
+    DayNightSwitch::new(
+        DayNightConfig {
+            threshold: Luminance::from_lux(50.0),
+            hysteresis: Luminance::from_lux(10.0),
+            dwell_ms: 300,
+        },
+        initial,
+    )
+}
+
+/// Feeds readings taken every 100ms and returns the changes with their tick
+fn run(switch: &mut DayNightSwitch, lux: &[f32]) -> Vec<(u32, DayNight)> {
+    lux.iter()
+        .zip((0..).step_by(100))
+        .filter_map(|(&lux, tick)| {
+            let reading = AlsReading::Valid(Luminance::from_lux(lux));
+            switch.feed(tick, reading).map(|state| (tick, state))
+        })
+        .collect()
+}
+
+#[test]
+fn oscillation_within_the_band_never_switches() {
+    let mut switch = switch(DayNight::Day);
+    let lux: Vec<f32> = (0..50).map(|i| 50.0 + (i % 7) as f32 * 3.0 - 9.0).collect();
+
+    assert_eq!(run(&mut switch, &lux), []);
+    assert_eq!(switch.state(), DayNight::Day);
+}
+
+#[test]
+fn oscillation_across_the_band_faster_than_the_dwell_never_switches() {
+    let mut switch = switch(DayNight::Day);
+    let lux = [70.0, 30.0, 30.0, 70.0, 30.0, 35.0, 80.0, 20.0, 20.0, 65.0];
+
+    assert_eq!(run(&mut switch, &lux), []);
+    assert_eq!(switch.state(), DayNight::Day);
+}
+
+#[test]
+fn dusk_and_dawn_switch_once_each() {
+    let mut switch = switch(DayNight::Day);
+    // Fading through the band with noise, then rising again
+    let lux = [
+        80.0, 65.0, 55.0, 45.0, 38.0, 42.0, 36.0, 39.0, 30.0, 25.0, 20.0, 45.0, 55.0, 62.0, 58.0,
+        61.0, 64.0, 70.0,
+    ];
+
+    assert_eq!(
+        run(&mut switch, &lux),
+        [(700, DayNight::Night), (1600, DayNight::Day)]
+    );
+}
+
+#[test]
+fn return_to_the_original_side_cancels_the_dwell() {
+    let mut switch = switch(DayNight::Night);
+    let lux = [70.0, 70.0, 20.0, 70.0, 70.0, 70.0, 70.0];
+
+    assert_eq!(run(&mut switch, &lux), [(600, DayNight::Day)]);
+}
+
+#[test]
+fn saturated_and_dark_readings_pick_a_side() {
+    let mut switch = switch(DayNight::Night);
+    assert_eq!(switch.feed(0, AlsReading::Saturated), None);
+    assert_eq!(switch.side(), DayNight::Day);
+    assert_eq!(switch.feed(300, AlsReading::Saturated), Some(DayNight::Day));
+    assert_eq!(switch.feed(400, AlsReading::Dark), None);
+    assert_eq!(switch.side(), DayNight::Night);
+}
+
+#[test]
+fn failed_readings_only_advance_time() {
+    let mut switch = switch(DayNight::Day);
+    let failed = AlsReading::Failed(AlsErrorCode::NoError);
+
+    assert_eq!(
+        switch.feed(0, AlsReading::Valid(Luminance::from_lux(10.0))),
+        None
+    );
+    assert_eq!(switch.feed(200, failed), None);
+    assert_eq!(switch.feed(300, failed), Some(DayNight::Night));
+}
+
+#[test]
+fn interrupt_path_with_update() {
+    let mut switch = switch(DayNight::Day);
+
+    assert_eq!(switch.feed_side(1000, DayNight::Night), None);
+    assert_eq!(switch.update(1299), None);
+    assert_eq!(switch.update(1300), Some(DayNight::Night));
+    assert_eq!(switch.update(5000), None);
+    assert_eq!(switch.feed_side(5000, DayNight::Day), None);
+    assert_eq!(switch.feed_side(5100, DayNight::Night), None);
+    assert_eq!(switch.update(9000), None);
+    assert_eq!(switch.state(), DayNight::Night);
+}
+
+#[test]
+fn tick_wraps_around() {
+    let mut switch = switch(DayNight::Night);
+    let start = u32::MAX - 100;
+
+    switch.feed_side(start, DayNight::Day);
+    assert_eq!(switch.update(start.wrapping_add(300)), Some(DayNight::Day));
+}
+
+#[test]
+fn device_interrupt_follows_the_side() {
+    let config = switch(DayNight::Day).config();
+    // Range interrupt on new samples, left untouched; gain 1 and 100ms
+    let mut bus = RegisterMap::new()
+        .set(0x014, &[0x04])
+        .set(0x03F, &[0x46])
+        .set(0x041, &[0x63]);
+    let mut dev = Device::new(&mut bus);
+
+    dev.configure_day_night(&config, DayNight::Day).unwrap();
+    assert_eq!(dev.als_interrupt_mode().unwrap(), InterruptMode::LevelLow);
+    dev.configure_day_night(&config, DayNight::Night).unwrap();
+    assert_eq!(dev.als_interrupt_mode().unwrap(), InterruptMode::LevelHigh);
+    let _ = dev.release();
+
+    assert_eq!(
+        bus.writes(),
+        [
+            // Day: trigger below 40 lux, 126 counts
+            (0x03A, vec![0xFF, 0xFF, 0x00, 126]),
+            (0x014, vec![0x0C]),
+            // Night: trigger above 60 lux, 189 counts
+            (0x03A, vec![0x00, 189, 0x00, 0x00]),
+            (0x014, vec![0x14]),
+        ]
+    );
+}
@@ -0,0 +1,83 @@
+//! Taking a device apart and reassembling it keeps its address and settings
+
+mod support;
+
+use core::time::Duration;
+
+use embedded_hal::i2c::I2c;
+use support::{NoDelay, RegisterMap};
+use vl6180x::registers::{RangeIntermeasurementPeriod, RangeMaxConvergenceTime};
+use vl6180x::{Device, Error, Timeouts};
+
+/// Both sensors idle, no range sample pending
+fn bus() -> RegisterMap {
+    RegisterMap::new().set(0x04D, &[0x01, 0x01])
+}
+
+fn timeouts() -> Timeouts {
+    Timeouts {
+        range: Duration::from_millis(5),
+        ..Timeouts::default()
+    }
+}
+
+/// A device at 0x30 in strict mode with the busy check and short timeouts
+fn configured(bus: &mut RegisterMap) -> Device<&mut RegisterMap> {
+    let mut dev = Device::new_with_address(bus, 0x30);
+    dev.set_strict(true);
+    dev.set_busy_check(true);
+    dev.set_timeouts(timeouts());
+    dev
+}
+
+/// Writes a valid and an out-of-spec register and times out a measurement
+fn exercise(dev: &mut Device<&mut RegisterMap>) -> [Result<(), Error>; 3] {
+    [
+        dev.write_register(RangeMaxConvergenceTime {
+            time: Duration::from_millis(30),
+        }),
+        dev.write_register(RangeIntermeasurementPeriod {
+            period: Duration::from_millis(2560),
+        }),
+        dev.measure_range_single(&mut NoDelay).map(|_| ()),
+    ]
+}
+
+#[test]
+fn state_keeps_address_and_timeouts() {
+    let mut bus = bus();
+    let (_, state) = configured(&mut bus).into_parts();
+
+    assert_eq!(state.address(), 0x30);
+    assert_eq!(state.timeouts(), timeouts());
+}
+
+#[test]
+fn round_trip_preserves_behavior() {
+    let mut reference_bus = bus();
+    let reference = exercise(&mut configured(&mut reference_bus));
+
+    let mut bus = bus();
+    let (i2c, state) = configured(&mut bus).into_parts();
+    i2c.write(0x68, &[0x00, 0x6B, 0x00]).unwrap();
+    let mut dev = Device::from_parts(i2c, state);
+    assert!(dev.is_strict());
+    assert!(dev.busy_check_enabled());
+    let result = exercise(&mut dev);
+    let _ = dev.release();
+
+    assert_eq!(result, reference);
+    assert!(matches!(result[1], Err(Error::OutOfSpec(_))));
+    assert_eq!(result[2], Err(Error::Timeout));
+    // Only the transaction to the other peripheral differs
+    bus.log.retain(|t| t.address != 0x68);
+    assert_eq!(bus.log, reference_bus.log);
+    assert!(bus.log.iter().all(|t| t.address == 0x30));
+}
+
+#[test]
+fn release_still_returns_the_bus() {
+    let mut bus = bus();
+    let i2c = configured(&mut bus).release();
+    i2c.write(0x30, &[0x00, 0x16, 0x00]).unwrap();
+}
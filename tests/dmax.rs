@@ -0,0 +1,106 @@
+//! Maximum detectable distance estimates over synthetic conditions
+
+use core::time::Duration;
+
+use measurements::Length;
+use regiface::FromByteArray;
+use vl6180x::dmax::{DmaxModel, TargetReflectance};
+use vl6180x::registers::RangeResultBlock;
+
+const BLACK: TargetReflectance = TargetReflectance::BLACK;
+const GREY: TargetReflectance = TargetReflectance::GREY;
+const WHITE: TargetReflectance = TargetReflectance::WHITE;
+
+/// Reflectance, ambient rate (Mcps, 9.7), convergence limit (ms), estimate (mm)
+const TABLE: &[(TargetReflectance, u16, u64, f64)] = &[
+    (BLACK, 0, 10, 48.0),
+    (BLACK, 0, 49, 108.0),
+    (BLACK, 128, 49, 59.0),
+    (BLACK, 1280, 49, 21.0),
+    (GREY, 0, 10, 116.0),
+    (GREY, 64, 49, 175.0),
+    (GREY, 128, 49, 141.0),
+    (GREY, 384, 63, 92.0),
+    (GREY, 1280, 49, 52.0),
+    (WHITE, 64, 10, 238.0),
+    (WHITE, 384, 49, 207.0),
+    (WHITE, 1280, 63, 119.0),
+];
+
+#[test]
+fn typical_model_over_a_table_of_conditions() {
+    for &(reflectance, ambient, millis, expected) in TABLE {
+        assert_eq!(
+            DmaxModel::TYPICAL.max_distance(reflectance, ambient, Duration::from_millis(millis)),
+            Length::from_millimeters(expected),
+            "{}% at ambient {ambient} within {millis}ms",
+            reflectance.percent
+        );
+    }
+}
+
+#[test]
+fn brighter_ambient_shortens_the_estimate() {
+    let limit = Duration::from_millis(30);
+    let mut previous = f64::MAX;
+    for ambient in [0, 32, 128, 512, 2048, u16::MAX] {
+        let estimate = DmaxModel::TYPICAL
+            .max_distance(GREY, ambient, limit)
+            .as_millimeters();
+        assert!(estimate <= previous, "ambient {ambient}");
+        previous = estimate;
+    }
+}
+
+#[test]
+fn darker_targets_and_shorter_limits_shorten_the_estimate() {
+    let model = DmaxModel::TYPICAL;
+    let limit = Duration::from_millis(20);
+    assert!(model.max_distance(BLACK, 256, limit) < model.max_distance(GREY, 256, limit));
+    assert!(model.max_distance(GREY, 256, limit) < model.max_distance(WHITE, 256, limit));
+    assert!(
+        model.max_distance(GREY, 256, Duration::from_millis(5))
+            < model.max_distance(GREY, 256, limit)
+    );
+}
+
+#[test]
+fn estimate_is_capped_and_zero_without_time() {
+    let model = DmaxModel::TYPICAL;
+    assert_eq!(
+        model.max_distance(WHITE, 0, Duration::from_millis(63)),
+        Length::from_millimeters(255.0)
+    );
+    assert_eq!(
+        model.max_distance(WHITE, 0, Duration::ZERO),
+        Length::from_millimeters(0.0)
+    );
+}
+
+#[test]
+fn no_target_measurement_estimates_from_its_ambient_rate() {
+    // Ran for the whole 49ms limit and counted 49000 ambient counts: 1 Mcps
+    let mut bytes = [0; 34];
+    bytes[0x12..0x16].copy_from_slice(&49_000u32.to_be_bytes());
+    bytes[0x1A..0x1E].copy_from_slice(&49u32.to_be_bytes());
+    let Ok(block) = RangeResultBlock::from_bytes(bytes);
+
+    assert_eq!(block.ambient_rate(), 128);
+    assert_eq!(
+        block.estimated_max_distance(GREY),
+        Length::from_millimeters(141.0)
+    );
+}
+
+#[test]
+fn block_without_convergence_time_has_no_ambient_rate() {
+    let mut bytes = [0; 34];
+    bytes[0x12..0x16].copy_from_slice(&1_000u32.to_be_bytes());
+    let Ok(block) = RangeResultBlock::from_bytes(bytes);
+
+    assert_eq!(block.ambient_rate(), 0);
+    assert_eq!(
+        block.estimated_max_distance(WHITE),
+        Length::from_millimeters(0.0)
+    );
+}
@@ -0,0 +1,94 @@
+//! Shared conversion rules of the duration-bearing registers
+//!
+//! Every writable register holding a duration encodes it the same way:
+//! durations between the shortest and longest encodable value are rounded to
+//! the nearest step, and anything outside is rejected rather than clamped.
+
+use core::time::Duration;
+
+use regiface::{ByteArray, FromByteArray, ToByteArray};
+use vl6180x::registers::*;
+use vl6180x::types::*;
+
+/// Big-endian code held by a register's bytes
+fn code_of(bytes: impl ByteArray) -> u16 {
+    bytes
+        .as_ref()
+        .iter()
+        .fold(0, |code, &byte| code << 8 | u16::from(byte))
+}
+
+/// Register bytes holding `code`, big-endian
+fn bytes_of<A: ByteArray>(code: u16) -> A {
+    let mut bytes = A::new();
+    let len = bytes.as_ref().len();
+    bytes
+        .as_mut()
+        .copy_from_slice(&code.to_be_bytes()[2 - len..]);
+    bytes
+}
+
+/// Declares the conversion tests of a duration register: its field, step,
+/// encodable range and the code of the shortest duration.
+macro_rules! duration_registers {
+    ($($name:ident: $reg:ident { $field:ident }, step $step:literal, $min:literal..=$max:literal, first code $first:literal;)*) => {
+        $(
+            mod $name {
+                use super::*;
+
+                const STEP: Duration = Duration::from_millis($step);
+                const MIN: Duration = Duration::from_millis($min);
+                const MAX: Duration = Duration::from_millis($max);
+                const LAST: u16 = $first + ($max - $min) / $step;
+
+                fn encode(duration: Duration) -> Result<u16, RegisterError> {
+                    $reg { $field: duration }.to_bytes().map(code_of)
+                }
+
+                fn decode(code: u16) -> Duration {
+                    $reg::from_bytes(bytes_of(code)).unwrap().$field
+                }
+
+                #[test]
+                fn bounds_are_encodable() {
+                    assert_eq!(encode(MIN), Ok($first));
+                    assert_eq!(encode(MAX), Ok(LAST));
+                }
+
+                #[test]
+                fn out_of_range_is_rejected() {
+                    let micro = Duration::from_micros(1);
+                    assert_eq!(encode(MIN - micro), Err(RegisterError::DurationTooShort));
+                    assert_eq!(encode(MAX + micro), Err(RegisterError::DurationTooLong));
+                    assert_eq!(encode(Duration::ZERO), Err(RegisterError::DurationTooShort));
+                }
+
+                #[test]
+                fn rounds_to_the_nearest_step() {
+                    let half = STEP / 2;
+                    let micro = Duration::from_micros(1);
+                    assert_eq!(encode(MIN + half - micro), Ok($first));
+                    assert_eq!(encode(MIN + half), Ok($first + 1));
+                    assert_eq!(encode(MAX - half), Ok(LAST));
+                    assert_eq!(encode(MAX - half - micro), Ok(LAST - 1));
+                }
+
+                #[test]
+                fn every_code_round_trips() {
+                    for code in $first..=LAST {
+                        let duration = decode(code);
+                        assert_eq!(duration, MIN + STEP * u32::from(code - $first));
+                        assert_eq!(encode(duration), Ok(code));
+                    }
+                }
+            }
+        )*
+    };
+}
+
+duration_registers! {
+    range_intermeasurement: RangeIntermeasurementPeriod { period }, step 10, 10..=2560, first code 0;
+    range_max_convergence: RangeMaxConvergenceTime { time }, step 1, 1..=63, first code 1;
+    als_intermeasurement: AlsIntermeasurementPeriod { period }, step 10, 10..=2560, first code 0;
+    als_integration: AlsIntegrationPeriod { period }, step 1, 1..=512, first code 0;
+}
@@ -0,0 +1,343 @@
+//! Powering the sensor up for a single measurement through XSHUT
+
+mod support;
+
+use core::cell::{Cell, RefCell};
+use core::time::Duration;
+
+use embedded_hal::digital::{ErrorKind as PinErrorKind, ErrorType as PinErrorType, OutputPin};
+use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+use measurements::Length;
+use support::{block_on, load, store, Behavior, NoDelay, RegisterMap, Registers};
+use vl6180x::calibration::CalibrationData;
+use vl6180x::{AlsReading, Device, Error, RangeReading};
+
+/// Status reads a measurement takes to report its sample
+const MEASUREMENT_POLLS: u32 = 3;
+
+/// Pin edges and bus writes in the order they happened
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Event {
+    High,
+    Low,
+    Write(u16, u8),
+}
+
+/// Sensor powered through XSHUT
+struct Xshut<'a> {
+    log: &'a RefCell<Vec<Event>>,
+    powered: &'a Cell<bool>,
+    /// Boot flag reads left until the firmware has booted, `None` never
+    boot_polls: Option<u32>,
+    /// Status reads left until the running measurement reports its sample
+    sample_polls: Option<(u32, u8)>,
+    /// Register whose write fails on the bus
+    failing_write: Option<u16>,
+}
+
+impl Behavior for Xshut<'_> {
+    fn transaction(&mut self, _: &mut Registers, _: u8) -> Result<(), ErrorKind> {
+        if !self.powered.get() {
+            return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address));
+        }
+        Ok(())
+    }
+
+    fn read(
+        &mut self,
+        regs: &mut Registers,
+        register: u16,
+        buf: &mut [u8],
+    ) -> Result<(), ErrorKind> {
+        match register {
+            0x119 => {
+                let booted = match &mut self.boot_polls {
+                    Some(0) => true,
+                    Some(polls) => {
+                        *polls -= 1;
+                        false
+                    }
+                    None => false,
+                };
+                regs[0x119] = u8::from(booted);
+            }
+            0x04F => {
+                if let Some((polls, bit)) = &mut self.sample_polls {
+                    if *polls == 0 {
+                        regs[0x04F] |= *bit;
+                        self.sample_polls = None;
+                    } else {
+                        *polls -= 1;
+                    }
+                }
+            }
+            _ => {}
+        }
+        load(regs, register, buf);
+        Ok(())
+    }
+
+    fn write(&mut self, regs: &mut Registers, register: u16, data: &[u8]) -> Result<(), ErrorKind> {
+        self.log.borrow_mut().push(Event::Write(register, data[0]));
+        if self.failing_write == Some(register) {
+            return Err(ErrorKind::Bus);
+        }
+        match (register, data[0]) {
+            (0x018, 0x01) => self.sample_polls = Some((MEASUREMENT_POLLS, 0x04)),
+            (0x038, 0x01) => self.sample_polls = Some((MEASUREMENT_POLLS, 0x20)),
+            (0x015, _) => regs[0x04F] = 0,
+            _ => store(regs, register, data),
+        }
+        Ok(())
+    }
+}
+
+type Bus<'a> = RegisterMap<Xshut<'a>>;
+
+/// Power-on register map of a sensor that boots after two flag reads
+fn bus<'a>(log: &'a RefCell<Vec<Event>>, powered: &'a Cell<bool>) -> Bus<'a> {
+    RegisterMap::with(Xshut {
+        log,
+        powered,
+        boot_polls: Some(2),
+        sample_polls: None,
+        failing_write: None,
+    })
+    .set(0x016, &[0x01])
+    .set(0x03F, &[0x06])
+    .set(0x04D, &[0x01, 0x01])
+    .set(0x051, &[0x20])
+    .set(0x062, &[90])
+    .set(0x10A, &[0x30])
+}
+
+/// XSHUT output powering the simulated sensor
+struct Pin<'a> {
+    log: &'a RefCell<Vec<Event>>,
+    powered: &'a Cell<bool>,
+    /// Whether driving the pin low fails
+    stuck_high: bool,
+}
+
+impl<'a> Pin<'a> {
+    fn new(log: &'a RefCell<Vec<Event>>, powered: &'a Cell<bool>) -> Self {
+        Self {
+            log,
+            powered,
+            stuck_high: false,
+        }
+    }
+}
+
+impl PinErrorType for Pin<'_> {
+    type Error = PinErrorKind;
+}
+
+impl OutputPin for Pin<'_> {
+    fn set_low(&mut self) -> Result<(), PinErrorKind> {
+        if self.stuck_high {
+            return Err(PinErrorKind::Other);
+        }
+        self.log.borrow_mut().push(Event::Low);
+        self.powered.set(false);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), PinErrorKind> {
+        self.log.borrow_mut().push(Event::High);
+        self.powered.set(true);
+        Ok(())
+    }
+}
+
+fn calibration() -> CalibrationData {
+    CalibrationData {
+        offset: Length::from_millimeters(-3.0),
+        crosstalk_rate: 0x0040,
+        scaling: 1,
+    }
+}
+
+/// Runs `cycle` on a booting sensor, returning its result and the event log
+fn with_sensor<T>(
+    setup: impl FnOnce(&mut Bus<'_>),
+    cycle: impl FnOnce(&mut Device<&mut Bus<'_>>, &mut Pin<'_>) -> T,
+) -> (T, Vec<Event>, bool) {
+    let log = RefCell::new(Vec::new());
+    let powered = Cell::new(false);
+    let mut bus = bus(&log, &powered);
+    let mut pin = Pin::new(&log, &powered);
+    setup(&mut bus);
+    let mut dev = Device::new(&mut bus);
+    let result = cycle(&mut dev, &mut pin);
+    let _ = dev.release();
+    (result, log.into_inner(), powered.get())
+}
+
+fn position(log: &[Event], event: Event) -> usize {
+    log.iter()
+        .position(|logged| *logged == event)
+        .unwrap_or_else(|| panic!("{event:?} missing from {log:?}"))
+}
+
+#[test]
+fn range_cycle_runs_in_order() {
+    let (result, log, powered) = with_sensor(
+        |_| {},
+        |dev, pin| dev.duty_cycle_measurement(pin, &mut NoDelay, &calibration()),
+    );
+
+    let cycle = result.unwrap();
+    assert_eq!(
+        cycle.reading,
+        RangeReading::Valid(Length::from_millimeters(90.0))
+    );
+    assert!(!powered);
+
+    let released = log.iter().rposition(|event| *event == Event::High).unwrap();
+    let fresh = position(&log, Event::Write(0x016, 0x00));
+    let interrupts = position(&log, Event::Write(0x014, 0x24));
+    let offset = position(&log, Event::Write(0x024, 0xFD));
+    let crosstalk = position(&log, Event::Write(0x01E, 0x00));
+    let start = position(&log, Event::Write(0x018, 0x01));
+    assert_eq!(log[0], Event::Low);
+    assert!(released < fresh);
+    assert!(fresh < interrupts);
+    assert!(interrupts < offset && interrupts < crosstalk);
+    assert!(offset < start && crosstalk < start);
+    assert_eq!(log.last(), Some(&Event::Low));
+}
+
+#[test]
+fn reports_the_timing_breakdown() {
+    let (result, _, _) = with_sensor(
+        |_| {},
+        |dev, pin| dev.duty_cycle_measurement(pin, &mut NoDelay, &calibration()),
+    );
+
+    let cycle = result.unwrap();
+    assert_eq!(cycle.boot_time, Duration::from_millis(2));
+    assert_eq!(cycle.measurement_time, Duration::from_millis(3));
+}
+
+#[test]
+fn als_cycle_measures_without_calibration() {
+    let (result, log, powered) =
+        with_sensor(|_| {}, |dev, pin| dev.duty_cycle_als(pin, &mut NoDelay));
+
+    let cycle = result.unwrap();
+    assert!(matches!(cycle.reading, AlsReading::Valid(_)));
+    assert_eq!(cycle.measurement_time, Duration::from_millis(3));
+    assert!(!powered);
+    assert!(position(&log, Event::Write(0x014, 0x24)) < position(&log, Event::Write(0x038, 0x01)));
+    assert!(!log
+        .iter()
+        .any(|event| matches!(event, Event::Write(0x024 | 0x01E, _))));
+    assert_eq!(log.last(), Some(&Event::Low));
+}
+
+#[test]
+fn boot_timeout_leaves_the_pin_low() {
+    let (result, log, powered) = with_sensor(
+        |bus| bus.behavior.boot_polls = None,
+        |dev, pin| dev.duty_cycle_measurement(pin, &mut NoDelay, &calibration()),
+    );
+
+    assert_eq!(result, Err(Error::Timeout));
+    assert!(!powered);
+    assert_eq!(log.last(), Some(&Event::Low));
+    assert!(!log
+        .iter()
+        .any(|event| matches!(event, Event::Write(0x018, _))));
+}
+
+#[test]
+fn bus_error_leaves_the_pin_low() {
+    let (result, log, powered) = with_sensor(
+        |bus| bus.behavior.failing_write = Some(0x018),
+        |dev, pin| dev.duty_cycle_measurement(pin, &mut NoDelay, &calibration()),
+    );
+
+    assert!(matches!(result, Err(Error::BusError(_))));
+    assert!(!powered);
+    assert_eq!(log.last(), Some(&Event::Low));
+}
+
+#[test]
+fn invalid_calibration_leaves_the_pin_low() {
+    let invalid = CalibrationData {
+        scaling: 2,
+        ..calibration()
+    };
+    let (result, log, powered) = with_sensor(
+        |_| {},
+        |dev, pin| dev.duty_cycle_measurement(pin, &mut NoDelay, &invalid),
+    );
+
+    assert!(matches!(result, Err(Error::SerializationError(_))));
+    assert!(!powered);
+    assert!(!log
+        .iter()
+        .any(|event| matches!(event, Event::Write(0x018, _))));
+}
+
+#[test]
+fn failed_shutdown_is_reported() {
+    let (result, log, powered) = with_sensor(
+        |_| {},
+        |dev, pin| {
+            dev.duty_cycle_measurement(&mut StuckAfterBoot(pin), &mut NoDelay, &calibration())
+        },
+    );
+
+    assert_eq!(result, Err(Error::PinError));
+    assert!(powered);
+    assert!(log.contains(&Event::Write(0x018, 0x01)));
+    assert_ne!(log.last(), Some(&Event::Low));
+}
+
+/// Pin that sticks high once it was released, failing the shutdown
+struct StuckAfterBoot<'a, 'b>(&'a mut Pin<'b>);
+
+impl PinErrorType for StuckAfterBoot<'_, '_> {
+    type Error = PinErrorKind;
+}
+
+impl OutputPin for StuckAfterBoot<'_, '_> {
+    fn set_low(&mut self) -> Result<(), PinErrorKind> {
+        self.0.set_low()
+    }
+
+    fn set_high(&mut self) -> Result<(), PinErrorKind> {
+        self.0.stuck_high = true;
+        self.0.set_high()
+    }
+}
+
+#[test]
+fn async_range_cycle_leaves_the_pin_low() {
+    let (result, log, powered) = with_sensor(
+        |_| {},
+        |dev, pin| block_on(dev.duty_cycle_measurement_async(pin, &mut NoDelay, &calibration())),
+    );
+
+    let cycle = result.unwrap();
+    assert_eq!(
+        cycle.reading,
+        RangeReading::Valid(Length::from_millimeters(90.0))
+    );
+    assert_eq!(cycle.boot_time, Duration::from_millis(2));
+    assert!(!powered);
+    assert_eq!(log.last(), Some(&Event::Low));
+}
+
+#[test]
+fn async_bus_error_leaves_the_pin_low() {
+    let (result, _, powered) = with_sensor(
+        |bus| bus.behavior.failing_write = Some(0x038),
+        |dev, pin| block_on(dev.duty_cycle_als_async(pin, &mut NoDelay)),
+    );
+
+    assert!(matches!(result, Err(Error::BusError(_))));
+    assert!(!powered);
+}
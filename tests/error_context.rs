@@ -0,0 +1,205 @@
+//! Transport and codec errors name the access and helper step that failed
+
+mod support;
+
+use core::time::Duration;
+
+use embedded_hal::i2c::ErrorKind;
+use measurements::Length;
+use support::{block_on, NoDelay, RegisterMap};
+use vl6180x::registers::{RangeResultStatus, RangeStart};
+use vl6180x::{
+    Access, Device, Direction, Error, ErrorContext, OperationStep, RangeInterrupt, RangeSchedule,
+};
+
+/// Idle sensor with a 49ms convergence limit
+fn bus() -> RegisterMap {
+    RegisterMap::new().set(0x01C, &[0x31]).set(0x04D, &[0x01])
+}
+
+/// Bus failing its `n`th transaction, counting from 1, and every later one
+fn failing_at(n: u32) -> RegisterMap {
+    let mut bus = bus();
+    bus.fail_after(n - 1, ErrorKind::Other);
+    bus
+}
+
+fn in_step(access: Access, direction: Direction, name: &'static str, step: u16) -> ErrorContext {
+    ErrorContext {
+        access,
+        direction,
+        operation: Some(OperationStep { name, step }),
+    }
+}
+
+#[test]
+fn error_stays_small_and_copy() {
+    fn assert_copy<T: Copy>() {}
+    assert_copy::<Error>();
+    // 20 bytes on 32-bit targets
+    assert!(core::mem::size_of::<Error>() <= 5 * core::mem::size_of::<usize>());
+}
+
+#[test]
+fn typed_accesses_name_their_register() {
+    let mut bus = failing_at(1);
+    let mut dev = Device::new(&mut bus);
+
+    assert_eq!(
+        dev.read_register::<RangeResultStatus>(),
+        Err(Error::BusError(ErrorContext::register(
+            0x04D,
+            Direction::Read
+        )))
+    );
+    assert_eq!(
+        dev.write_register(RangeStart::SingleShot),
+        Err(Error::BusError(ErrorContext::register(
+            0x018,
+            Direction::Write
+        )))
+    );
+}
+
+#[test]
+fn raw_accesses_are_marked_raw() {
+    let mut bus = failing_at(1);
+    let mut dev = Device::new(&mut bus);
+
+    let read = dev.read_block::<4>(0x062).unwrap_err();
+    assert_eq!(
+        read.context().map(|c| (c.access, c.direction)),
+        Some((Access::Raw(0x062), Direction::Read))
+    );
+    let write = dev.write_block(0x019, &[0, 0]).unwrap_err();
+    assert_eq!(
+        write.context().map(|c| (c.access, c.direction)),
+        Some((Access::Raw(0x019), Direction::Write))
+    );
+}
+
+#[test]
+fn undecodable_values_name_their_register() {
+    let mut bus = bus().set(0x04D, &[0x91]);
+
+    assert_eq!(
+        Device::new(&mut bus).read_range_quick(),
+        Err(Error::DeserializationError(ErrorContext::register(
+            0x04D,
+            Direction::Read
+        )))
+    );
+}
+
+#[test]
+fn failing_step_of_a_helper_is_reported() {
+    // Read the convergence limit, read and write back GPIO1, then fail
+    // writing the thresholds
+    let mut bus = failing_at(4);
+    let mut dev = Device::new(&mut bus);
+
+    let error = dev
+        .arm_wake_on_approach(
+            &mut NoDelay,
+            Length::from_millimeters(60.0),
+            Duration::from_millis(500),
+        )
+        .unwrap_err();
+    assert_eq!(
+        error,
+        Error::BusError(in_step(
+            Access::Register(0x019),
+            Direction::Write,
+            "arm_wake_on_approach",
+            4
+        ))
+    );
+    assert_eq!(
+        error.to_string(),
+        "I2C bus error writing register 0x0019 (arm_wake_on_approach step 4)"
+    );
+}
+
+#[test]
+fn label_ends_with_the_helper() {
+    let mut bus = failing_at(7);
+    let mut dev = Device::new(&mut bus);
+
+    dev.arm_wake_on_approach(
+        &mut NoDelay,
+        Length::from_millimeters(60.0),
+        Duration::from_millis(500),
+    )
+    .unwrap_err();
+    assert_eq!(
+        dev.read_register::<RangeResultStatus>(),
+        Err(Error::BusError(ErrorContext::register(
+            0x04D,
+            Direction::Read
+        )))
+    );
+}
+
+#[test]
+fn async_helpers_report_the_same_step() {
+    let schedule = RangeSchedule {
+        period: Duration::from_millis(100),
+        interrupt: RangeInterrupt::NewSampleReady,
+    };
+    for n in 1..=5 {
+        let mut bus = failing_at(n);
+        let mut dev = Device::new(&mut bus);
+        let sync_result = dev.disarm_and_resume(&mut NoDelay, schedule);
+
+        let mut bus = failing_at(n);
+        let mut dev = Device::new(&mut bus);
+        let async_result = block_on(dev.disarm_and_resume_async(&mut NoDelay, schedule));
+
+        assert_eq!(
+            sync_result.unwrap_err().context().and_then(|c| c.operation),
+            Some(OperationStep {
+                name: "disarm_and_resume",
+                step: n as u16
+            })
+        );
+        assert_eq!(async_result, sync_result);
+    }
+}
+
+#[cfg(feature = "pololu-compat")]
+mod pololu {
+    use super::*;
+    use vl6180x::pololu_compat::Vl6180x;
+
+    #[test]
+    fn initialize_reports_the_failing_register_and_step() {
+        let mut bus = failing_at(3);
+        let mut sensor = Vl6180x::new(&mut bus, NoDelay);
+
+        assert_eq!(
+            sensor.configure_default(),
+            Err(Error::BusError(in_step(
+                Access::Register(0x031),
+                Direction::Write,
+                "configure_default",
+                3
+            )))
+        );
+    }
+
+    #[test]
+    fn init_failure_on_the_first_read() {
+        let mut bus = failing_at(1);
+        let mut sensor = Vl6180x::new(&mut bus, NoDelay);
+
+        assert_eq!(
+            sensor.init(),
+            Err(Error::BusError(in_step(
+                Access::Register(0x016),
+                Direction::Read,
+                "init",
+                1
+            )))
+        );
+    }
+}
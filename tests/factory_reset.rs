@@ -0,0 +1,282 @@
+//! Restoring the power-on configuration without a power cycle
+
+mod support;
+
+use core::time::Duration;
+
+use measurements::Length;
+use support::{block_on, Log, NoDelay, SimulatedVl6180x};
+use vl6180x::registers::{
+    AlsAnalogueGain, AlsIntegrationPeriod, AlsIntermeasurementPeriod, AlsThresholds,
+    InterleavedModeEnable, InterruptConfigGpio, ModeGpio0, ModeGpio1, RangeCheckEnables,
+    RangeIntermeasurementPeriod, RangeMaxConvergenceTime, RangeThresholds, ReadoutAveraging,
+    POWER_ON_DEFAULTS,
+};
+use vl6180x::{Device, FullConfig};
+
+/// Sensor with a scrambled configuration and interrupts pending, running
+/// both continuous modes when `running`
+fn scrambled(running: bool) -> SimulatedVl6180x {
+    let mut bus = SimulatedVl6180x::new();
+    let mut state: u32 = 0x1234_5678;
+    let noise: Vec<u8> = (0..0x300)
+        .map(|_| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (state >> 16) as u8
+        })
+        .collect();
+    // Keep the sensor answering its address
+    bus.set_registers(0x000, &noise[..0x212]);
+    bus.set_registers(0x213, &noise[0x213..]);
+    bus.set_registers(0x04D, &[0x01, 0x01]);
+    if running {
+        bus.start_continuous();
+    }
+    bus.set_registers(0x04F, &[0x24]);
+    bus
+}
+
+/// XSHUT pin that always switches
+struct Xshut;
+
+impl embedded_hal::digital::ErrorType for Xshut {
+    type Error = core::convert::Infallible;
+}
+
+impl embedded_hal::digital::OutputPin for Xshut {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+fn assert_defaults(bus: &SimulatedVl6180x) {
+    for default in &POWER_ON_DEFAULTS {
+        assert_eq!(
+            bus.registers(default.address, default.bytes.len()),
+            default.bytes,
+            "{}",
+            default.name
+        );
+    }
+}
+
+#[test]
+fn register_map_equals_defaults() {
+    let mut bus = scrambled(true);
+    let offset = bus.register(0x024);
+    let address = bus.register(0x212);
+    Device::new(&mut bus)
+        .factory_reset(&mut NoDelay, &[])
+        .unwrap();
+
+    assert_defaults(&bus);
+    assert_eq!(bus.register(0x024), offset);
+    assert_eq!(bus.register(0x212), address);
+    assert_eq!(bus.register(0x04F), 0);
+}
+
+#[test]
+fn running_measurements_are_stopped_first() {
+    let mut bus = scrambled(false);
+    // 100ms integration, so a 500ms ALS period is accepted
+    bus.set_registers(0x040, &[0x00, 0x63]);
+    let mut dev = Device::new(&mut bus);
+    dev.start_continuous_range().unwrap();
+    dev.start_continuous_als(Duration::from_millis(500))
+        .unwrap();
+    dev.factory_reset(&mut NoDelay, &[]).unwrap();
+    let _ = dev.release();
+
+    let writes = bus.writes();
+    let last_start = writes
+        .iter()
+        .position(|(address, _)| *address == 0x038)
+        .unwrap();
+    let first_default = writes
+        .iter()
+        .position(|(address, _)| *address == POWER_ON_DEFAULTS[0].address)
+        .unwrap();
+    let stops: Vec<_> = writes[last_start + 1..first_default]
+        .iter()
+        .map(|(address, data)| (*address, data[0]))
+        .collect();
+    assert_eq!(stops, [(0x018, 0x03), (0x038, 0x03)]);
+    assert_eq!(bus.register(0x04D) & 0x01, 0x01);
+    assert_eq!(bus.register(0x04E) & 0x01, 0x01);
+}
+
+#[test]
+fn nothing_is_tracked_as_running_afterwards() {
+    let mut bus = SimulatedVl6180x::new();
+    let mut dev = Device::new(&mut bus);
+    dev.start_continuous_range().unwrap();
+    dev.start_continuous_als(Duration::from_millis(500))
+        .unwrap();
+    // New sample interrupts for both sensors on top of the defaults
+    dev.factory_reset(&mut NoDelay, &[(0x014, 0x24)]).unwrap();
+
+    assert!(!dev.is_continuous_range_running());
+    assert!(!dev.is_continuous_als_running());
+    assert_eq!(
+        dev.measure_range_single(&mut NoDelay),
+        Ok(Length::from_millimeters(50.0))
+    );
+    assert!(dev.measure_als_single(&mut NoDelay).is_ok());
+}
+
+#[test]
+fn power_cycle_forgets_running_measurements() {
+    let mut bus = SimulatedVl6180x::new();
+    let mut dev = Device::new(&mut bus);
+    dev.start_continuous_range().unwrap();
+    dev.start_continuous_als(Duration::from_millis(500))
+        .unwrap();
+    dev.power_cycle(&mut Xshut, &mut NoDelay, None).unwrap();
+
+    assert!(!dev.is_continuous_range_running());
+    assert!(!dev.is_continuous_als_running());
+    assert_eq!(dev.stop_continuous_range(&mut NoDelay), Ok(()));
+    let _ = dev.release();
+    assert_eq!(
+        bus.writes()
+            .iter()
+            .filter(|(address, _)| *address == 0x018)
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn idle_sensor_is_not_started() {
+    let mut bus = scrambled(false);
+    Device::new(&mut bus)
+        .factory_reset(&mut NoDelay, &[])
+        .unwrap();
+
+    assert!(!bus
+        .writes()
+        .iter()
+        .any(|(address, _)| *address == 0x018 || *address == 0x038));
+    assert_defaults(&bus);
+}
+
+#[test]
+fn registers_read_back_as_their_defaults() {
+    let mut bus = scrambled(true);
+    let mut dev = Device::new(&mut bus);
+    // Fill the configuration cache with the scrambled values
+    let _: AlsIntegrationPeriod = dev.read_register().unwrap();
+    let _: AlsAnalogueGain = dev.read_register().unwrap();
+    dev.factory_reset(&mut NoDelay, &[]).unwrap();
+
+    assert_eq!(dev.read_register::<ModeGpio0>(), Ok(ModeGpio0::default()));
+    assert_eq!(dev.read_register::<ModeGpio1>(), Ok(ModeGpio1::default()));
+    assert_eq!(
+        dev.read_register::<InterruptConfigGpio>(),
+        Ok(InterruptConfigGpio::default())
+    );
+    assert_eq!(
+        dev.read_register::<RangeThresholds>(),
+        Ok(RangeThresholds::default())
+    );
+    assert_eq!(
+        dev.read_register::<RangeIntermeasurementPeriod>(),
+        Ok(RangeIntermeasurementPeriod::default())
+    );
+    assert_eq!(
+        dev.read_register::<RangeMaxConvergenceTime>(),
+        Ok(RangeMaxConvergenceTime::default())
+    );
+    assert_eq!(
+        dev.read_register::<RangeCheckEnables>(),
+        Ok(RangeCheckEnables::default())
+    );
+    assert_eq!(
+        dev.read_register::<AlsThresholds>(),
+        Ok(AlsThresholds::default())
+    );
+    assert_eq!(
+        dev.read_register::<AlsIntermeasurementPeriod>(),
+        Ok(AlsIntermeasurementPeriod::default())
+    );
+    assert_eq!(
+        dev.read_register::<AlsAnalogueGain>(),
+        Ok(AlsAnalogueGain::default())
+    );
+    assert_eq!(
+        dev.read_register::<AlsIntegrationPeriod>(),
+        Ok(AlsIntegrationPeriod::default())
+    );
+    assert_eq!(
+        dev.read_register::<ReadoutAveraging>(),
+        Ok(ReadoutAveraging::default())
+    );
+    assert_eq!(
+        dev.read_register::<InterleavedModeEnable>(),
+        Ok(InterleavedModeEnable::default())
+    );
+}
+
+#[test]
+fn defaults_decode_the_documented_values() {
+    assert_eq!(
+        RangeMaxConvergenceTime::default().time,
+        Duration::from_millis(49)
+    );
+    assert_eq!(
+        RangeIntermeasurementPeriod::default().period,
+        Duration::from_millis(2560)
+    );
+    assert_eq!(
+        AlsIntegrationPeriod::default().period,
+        Duration::from_millis(1)
+    );
+    assert_eq!(ReadoutAveraging::default(), ReadoutAveraging::RECOMMENDED);
+    assert!(!InterleavedModeEnable::default().enabled);
+
+    let config = FullConfig::default();
+    assert_eq!(config.als_analogue_gain, AlsAnalogueGain::default());
+    assert_eq!(config.range_thresholds, RangeThresholds::default());
+}
+
+#[test]
+fn defaults_are_ordered_and_disjoint() {
+    for pair in POWER_ON_DEFAULTS.windows(2) {
+        let end = pair[0].address + pair[0].bytes.len() as u16;
+        assert!(end <= pair[1].address, "{} overlaps", pair[0].name);
+    }
+}
+
+#[test]
+fn tuning_is_applied_after_the_defaults() {
+    let mut bus = scrambled(true);
+    let tuning = [(0x207, 0x01), (0x208, 0x01), (0x031, 0xFF)];
+    Device::new(&mut bus)
+        .factory_reset(&mut NoDelay, &tuning)
+        .unwrap();
+
+    let writes = bus.writes();
+    let tail: Vec<_> = writes[writes.len() - 3..]
+        .iter()
+        .map(|(address, data)| (*address, data[0]))
+        .collect();
+    assert_eq!(tail, tuning);
+    assert_eq!(bus.register(0x207), 0x01);
+    assert_eq!(bus.register(0x031), 0xFF);
+}
+
+#[test]
+fn async_register_map_equals_defaults() {
+    let mut bus = scrambled(true);
+    let offset = bus.register(0x024);
+    block_on(Device::new(&mut bus).factory_reset_async(&mut NoDelay, &[(0x207, 0x01)])).unwrap();
+
+    assert_defaults(&bus);
+    assert_eq!(bus.register(0x024), offset);
+    assert_eq!(bus.register(0x207), 0x01);
+    assert_eq!(bus.register(0x04F), 0);
+}
@@ -0,0 +1,252 @@
+//! Error handling exercised end to end against injected faults
+
+mod support;
+
+use core::time::Duration;
+
+use embedded_hal::i2c::ErrorKind;
+use measurements::Length;
+use support::{block_on, NoDelay, SimulatedVl6180x};
+use vl6180x::device::HealthVerdict;
+use vl6180x::registers::{InterruptClear, RangeStart, ResultInterruptStatusGpio};
+use vl6180x::watchdog::{RecoveryPolicy, StallWatchdog};
+use vl6180x::{BusRecovery, Device, Error, RangeErrorCode};
+
+fn retry_nacks(kind: ErrorKind) -> BusRecovery {
+    match kind {
+        ErrorKind::NoAcknowledge(_) => BusRecovery::Retry,
+        _ => BusRecovery::GiveUp,
+    }
+}
+
+/// Takes the pending continuous range sample, if any
+fn next_sample(dev: &mut Device<&mut SimulatedVl6180x>) -> Option<Length> {
+    let status: ResultInterruptStatusGpio = dev.read_register().unwrap();
+    if !status.range_interrupt {
+        return None;
+    }
+    let (_, distance) = dev.read_range_quick().unwrap();
+    dev.write_register(InterruptClear {
+        clear_range: true,
+        clear_als: false,
+        clear_error: false,
+    })
+    .unwrap();
+    Some(distance)
+}
+
+#[test]
+fn nack_fails_the_measurement() {
+    let mut sim = SimulatedVl6180x::new();
+    sim.nack_transaction(1);
+    let mut dev = Device::new(&mut sim);
+
+    assert!(matches!(
+        dev.measure_range_single(&mut NoDelay),
+        Err(Error::BusError(_))
+    ));
+    assert_eq!(
+        dev.measure_range_single(&mut NoDelay),
+        Ok(Length::from_millimeters(50.0))
+    );
+}
+
+#[test]
+fn nack_is_retried_by_the_recovery_hook() {
+    let mut sim = SimulatedVl6180x::new();
+    sim.nack_transaction(1);
+    sim.nack_transaction(2);
+    let mut dev = Device::new(&mut sim);
+    dev.set_bus_recovery(retry_nacks);
+
+    assert_eq!(
+        dev.measure_range_single(&mut NoDelay),
+        Ok(Length::from_millimeters(50.0))
+    );
+}
+
+#[test]
+fn transient_errors_are_retried() {
+    let mut sim = SimulatedVl6180x::new();
+    sim.fail_measurements(RangeErrorCode::SignalToNoiseRatio, 2);
+    let mut dev = Device::new(&mut sim);
+
+    assert_eq!(
+        dev.measure_distance_with_retries(5, &mut NoDelay),
+        Ok((Length::from_millimeters(50.0), 3))
+    );
+    let _ = dev.release();
+    assert_eq!(sim.range_starts(), 3);
+}
+
+#[test]
+fn exhausted_retries_return_the_last_error() {
+    let mut sim = SimulatedVl6180x::new();
+    sim.fail_measurements(RangeErrorCode::MaxConvergence, 5);
+    let mut dev = Device::new(&mut sim);
+
+    assert_eq!(
+        dev.measure_distance_with_retries(3, &mut NoDelay),
+        Err(Error::RangeError(RangeErrorCode::MaxConvergence))
+    );
+    let _ = dev.release();
+    assert_eq!(sim.range_starts(), 3);
+}
+
+#[test]
+fn hardware_faults_are_not_retried() {
+    let mut sim = SimulatedVl6180x::new();
+    sim.fail_measurements(RangeErrorCode::VcselContinuityTest, 1);
+    let mut dev = Device::new(&mut sim);
+
+    assert_eq!(
+        dev.measure_distance_with_retries(5, &mut NoDelay),
+        Err(Error::RangeError(RangeErrorCode::VcselContinuityTest))
+    );
+    let _ = dev.release();
+    assert_eq!(sim.range_starts(), 1);
+}
+
+#[test]
+fn stall_times_out_single_shots() {
+    let mut sim = SimulatedVl6180x::new();
+    sim.stall();
+    let mut dev = Device::new(&mut sim);
+
+    assert_eq!(dev.measure_range_single(&mut NoDelay), Err(Error::Timeout));
+}
+
+/// Tick of the stall tests
+const TICK: Duration = Duration::from_millis(10);
+
+/// Stalled sensor ranging continuously with no sample pending
+fn stalled() -> SimulatedVl6180x {
+    let mut sim = SimulatedVl6180x::new();
+    sim.set_registers(0x04F, &[0x00]);
+    sim.stall();
+    sim
+}
+
+/// Ticks the watchdog until it reports the stall
+fn detect_stall(dev: &mut Device<&mut SimulatedVl6180x>, watchdog: &mut StallWatchdog) {
+    let mut ticks = 0;
+    let stalled = loop {
+        if next_sample(dev).is_some() {
+            watchdog.sample();
+        } else if let Err(error) = watchdog.elapse(TICK) {
+            break error;
+        }
+        ticks += 1;
+        assert!(ticks < 10, "stall went unnoticed");
+    };
+    assert_eq!(stalled, Error::Stalled);
+}
+
+#[test]
+fn stalled_continuous_ranging_is_detected_and_restarted() {
+    let mut sim = stalled();
+    let mut dev = Device::new(&mut sim);
+    let mut watchdog = StallWatchdog::new(TICK, 3);
+    dev.start_continuous_range().unwrap();
+    detect_stall(&mut dev, &mut watchdog);
+
+    watchdog
+        .recover(&mut dev, &mut NoDelay, RecoveryPolicy::Restart)
+        .unwrap();
+    assert_eq!(watchdog.elapse(TICK), Ok(()));
+    assert!(dev.is_continuous_range_running());
+    assert_eq!(next_sample(&mut dev), Some(Length::from_millimeters(50.0)));
+    assert_eq!(next_sample(&mut dev), Some(Length::from_millimeters(50.0)));
+}
+
+#[test]
+fn stalled_continuous_ranging_is_recovered_by_a_factory_reset() {
+    // Range interrupt on every new sample, which the power-on defaults lack
+    static TUNING: [(u16, u8); 1] = [(0x014, 0x04)];
+    let mut sim = stalled();
+    let mut dev = Device::new(&mut sim);
+    let mut watchdog = StallWatchdog::new(TICK, 3);
+    dev.start_continuous_range().unwrap();
+    detect_stall(&mut dev, &mut watchdog);
+
+    block_on(watchdog.recover_async(
+        &mut dev,
+        &mut NoDelay,
+        RecoveryPolicy::FactoryReset(&TUNING),
+    ))
+    .unwrap();
+    assert_eq!(watchdog.elapse(TICK), Ok(()));
+    assert!(dev.is_continuous_range_running());
+    assert_eq!(next_sample(&mut dev), Some(Length::from_millimeters(50.0)));
+}
+
+#[test]
+fn corrupted_read_goes_unnoticed_without_paranoid_reads() {
+    let mut sim = SimulatedVl6180x::new();
+    sim.corrupt_next_read(0x062, 0x10);
+    let mut dev = Device::new(&mut sim);
+
+    assert_eq!(
+        dev.measure_range_single(&mut NoDelay),
+        Ok(Length::from_millimeters(34.0))
+    );
+}
+
+#[test]
+fn corrupted_read_is_caught_by_paranoid_reads() {
+    let mut sim = SimulatedVl6180x::new();
+    sim.corrupt_next_read(0x062, 0x10);
+    let mut dev = Device::new(&mut sim);
+    dev.set_paranoid(true);
+
+    assert_eq!(
+        dev.measure_range_single(&mut NoDelay),
+        Ok(Length::from_millimeters(50.0))
+    );
+}
+
+#[test]
+fn spontaneous_reset_is_detected() {
+    let mut sim = SimulatedVl6180x::new();
+    let mut dev = Device::new(&mut sim);
+    assert_eq!(
+        dev.health_check().map(|report| report.verdict),
+        Ok(HealthVerdict::Healthy)
+    );
+
+    let _ = dev.release();
+    sim.spontaneous_reset();
+    let mut dev = Device::new(&mut sim);
+    let report = dev.health_check().unwrap();
+    assert_eq!(report.verdict, HealthVerdict::NeedsReinit);
+    assert_eq!(report.fresh_out_of_reset, Some(true));
+
+    // The reset also disabled the interrupts the measurement polls for
+    assert_eq!(dev.measure_range_single(&mut NoDelay), Err(Error::Timeout));
+}
+
+#[test]
+fn reset_stops_continuous_ranging() {
+    let mut sim = SimulatedVl6180x::new();
+    let mut dev = Device::new(&mut sim);
+    dev.write_register(RangeStart::Continuous).unwrap();
+    assert!(next_sample(&mut dev).is_some());
+
+    let _ = dev.release();
+    sim.spontaneous_reset();
+    assert_eq!(sim.register(0x04D) & 0x01, 0x01);
+    assert_eq!(sim.register(0x016), 0x01);
+}
+
+#[test]
+fn async_nack_is_retried_by_the_recovery_hook() {
+    let mut sim = SimulatedVl6180x::new();
+    sim.nack_transaction(0);
+    let mut dev = Device::new(&mut sim);
+    dev.set_bus_recovery(retry_nacks);
+
+    assert_eq!(
+        block_on(dev.measure_range_single_async(&mut NoDelay)),
+        Ok(Length::from_millimeters(50.0))
+    );
+}
@@ -0,0 +1,140 @@
+//! Per-field setters and getters pinned to the bytes on the bus
+
+mod support;
+
+use core::time::Duration;
+
+use measurements::Length;
+use support::{block_on, Log, RegisterMap};
+use vl6180x::{AlsGain, Device, Error, Mcps};
+
+/// ALS gain 1, everything else zero
+fn bus() -> RegisterMap {
+    RegisterMap::new().set(0x03F, &[0x46])
+}
+
+/// Writes wrapped in a grouped parameter hold
+fn held(address: u16, bytes: &[u8]) -> Vec<(u16, Vec<u8>)> {
+    vec![
+        (0x017, vec![0x01]),
+        (address, bytes.to_vec()),
+        (0x017, vec![0x00]),
+    ]
+}
+
+#[test]
+fn als_gain_is_written_held() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.set_als_gain(AlsGain::Gain20).unwrap();
+    assert_eq!(dev.als_gain(), Ok(AlsGain::Gain20));
+    let _ = dev.release();
+
+    assert_eq!(bus.writes(), held(0x03F, &[0x40]));
+}
+
+#[test]
+fn als_integration_is_written_held() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.set_als_integration(Duration::from_millis(100)).unwrap();
+    assert_eq!(dev.als_integration(), Ok(Duration::from_millis(100)));
+    let _ = dev.release();
+
+    assert_eq!(bus.writes(), held(0x040, &[0x00, 0x63]));
+}
+
+#[test]
+fn max_convergence_time_is_written_held() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.set_max_convergence_time(Duration::from_millis(30))
+        .unwrap();
+    assert_eq!(dev.max_convergence_time(), Ok(Duration::from_millis(30)));
+    let _ = dev.release();
+
+    assert_eq!(bus.writes(), held(0x01C, &[30]));
+}
+
+#[test]
+fn range_offset_is_written_in_twos_complement() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.set_range_offset(Length::from_millimeters(-5.0))
+        .unwrap();
+    assert_eq!(dev.range_offset(), Ok(Length::from_millimeters(-5.0)));
+    let _ = dev.release();
+
+    assert_eq!(bus.writes(), [(0x024, vec![0xFB])]);
+}
+
+#[test]
+fn crosstalk_rate_is_written_in_fixed_point() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.set_crosstalk_rate(Mcps::from_mcps(2.5)).unwrap();
+    assert_eq!(dev.crosstalk_rate(), Ok(Mcps::from_fixed(320)));
+    let _ = dev.release();
+
+    assert_eq!(bus.writes(), [(0x01E, vec![0x01, 0x40])]);
+}
+
+#[test]
+fn out_of_range_values_are_rejected_before_writing() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    assert!(matches!(
+        dev.set_als_integration(Duration::from_millis(600)),
+        Err(Error::SerializationError(_))
+    ));
+    assert!(matches!(
+        dev.set_max_convergence_time(Duration::from_millis(64)),
+        Err(Error::SerializationError(_))
+    ));
+    dev.set_strict(true);
+    assert!(matches!(
+        dev.set_range_offset(Length::from_millimeters(130.0)),
+        Err(Error::OutOfSpec(_))
+    ));
+    let _ = dev.release();
+
+    // The parameter holds are released again
+    assert!(bus.writes().iter().all(|(address, _)| *address == 0x017));
+    assert_eq!(bus.regs[0x017], 0x00);
+}
+
+#[test]
+fn async_setters_write_the_same_bytes() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    block_on(dev.set_als_gain_async(AlsGain::Gain20)).unwrap();
+    block_on(dev.set_als_integration_async(Duration::from_millis(100))).unwrap();
+    block_on(dev.set_max_convergence_time_async(Duration::from_millis(30))).unwrap();
+    block_on(dev.set_range_offset_async(Length::from_millimeters(-5.0))).unwrap();
+    block_on(dev.set_crosstalk_rate_async(Mcps::from_mcps(2.5))).unwrap();
+    assert_eq!(block_on(dev.als_gain_async()), Ok(AlsGain::Gain20));
+    assert_eq!(
+        block_on(dev.als_integration_async()),
+        Ok(Duration::from_millis(100))
+    );
+    assert_eq!(
+        block_on(dev.max_convergence_time_async()),
+        Ok(Duration::from_millis(30))
+    );
+    assert_eq!(
+        block_on(dev.range_offset_async()),
+        Ok(Length::from_millimeters(-5.0))
+    );
+    assert_eq!(
+        block_on(dev.crosstalk_rate_async()),
+        Ok(Mcps::from_fixed(320))
+    );
+    let _ = dev.release();
+
+    let mut expected = held(0x03F, &[0x40]);
+    expected.extend(held(0x040, &[0x00, 0x63]));
+    expected.extend(held(0x01C, &[30]));
+    expected.push((0x024, vec![0xFB]));
+    expected.push((0x01E, vec![0x01, 0x40]));
+    assert_eq!(bus.writes(), expected);
+}
@@ -0,0 +1,118 @@
+//! Mapping distances onto a fill level
+
+mod support;
+
+use embedded_hal::i2c::ErrorKind;
+use measurements::Length;
+use support::{Behavior, NoDelay, RegisterMap, Registers};
+use vl6180x::fill::{Fill, FillBand, FillLevel};
+use vl6180x::{Device, Error, RangeErrorCode};
+
+fn mm(value: f64) -> Length {
+    Length::from_millimeters(value)
+}
+
+fn fill(permille: u16, band: FillBand) -> Fill {
+    Fill { permille, band }
+}
+
+#[test]
+fn looking_down() {
+    let tank = FillLevel::new(mm(80.0), mm(20.0)).unwrap();
+
+    assert_eq!(tank.level(mm(80.0)), fill(0, FillBand::Within));
+    assert_eq!(tank.level(mm(65.0)), fill(250, FillBand::Within));
+    assert_eq!(tank.level(mm(50.0)), fill(500, FillBand::Within));
+    assert_eq!(tank.level(mm(20.0)), fill(1000, FillBand::Within));
+    assert_eq!(tank.level(mm(49.97)), fill(501, FillBand::Within));
+}
+
+#[test]
+fn inverted_mounting() {
+    let tank = FillLevel::new(mm(15.0), mm(95.0)).unwrap();
+
+    assert_eq!(tank.level(mm(15.0)), fill(0, FillBand::Within));
+    assert_eq!(tank.level(mm(35.0)), fill(250, FillBand::Within));
+    assert_eq!(tank.level(mm(95.0)), fill(1000, FillBand::Within));
+    assert_eq!(tank.level(mm(10.0)), fill(0, FillBand::BelowEmpty));
+    assert_eq!(tank.level(mm(120.0)), fill(1000, FillBand::AboveFull));
+}
+
+#[test]
+fn out_of_band_is_clamped_and_flagged() {
+    let tank = FillLevel::new(mm(80.0), mm(20.0)).unwrap();
+
+    let empty = tank.level(mm(81.0));
+    assert_eq!(empty, fill(0, FillBand::BelowEmpty));
+    assert!(!empty.is_within_band());
+    assert_eq!(empty.percent(), 0.0);
+
+    let full = tank.level(mm(0.0));
+    assert_eq!(full, fill(1000, FillBand::AboveFull));
+    assert!(!full.is_within_band());
+    assert_eq!(full.percent(), 100.0);
+
+    assert_eq!(tank.level(mm(255.0)).band, FillBand::BelowEmpty);
+}
+
+#[test]
+fn equal_calibration_points_are_rejected() {
+    assert_eq!(FillLevel::new(mm(40.0), mm(40.0)), None);
+}
+
+/// Sensor reporting a fixed sequence of range samples
+struct Samples {
+    samples: &'static [(u8, u8)],
+    position: usize,
+}
+
+impl Behavior for Samples {
+    fn read(&mut self, _: &mut Registers, register: u16, buf: &mut [u8]) -> Result<(), ErrorKind> {
+        let (status, distance) = self.samples[self.position];
+        buf[0] = match register {
+            0x04F => 0x04,
+            0x04D => status,
+            _ => {
+                self.position += 1;
+                distance
+            }
+        };
+        Ok(())
+    }
+}
+
+fn bus(samples: &'static [(u8, u8)]) -> RegisterMap<Samples> {
+    RegisterMap::with(Samples {
+        samples,
+        position: 0,
+    })
+}
+
+#[test]
+fn calibration_averages_samples() {
+    let mut dev = Device::new(bus(&[
+        (0x00, 78),
+        (0x00, 81),
+        (0x00, 81),
+        (0x00, 20),
+        (0x00, 22),
+    ]));
+
+    let empty = FillLevel::calibrate_empty(&mut dev, &mut NoDelay, 3).unwrap();
+    let full = FillLevel::calibrate_full(&mut dev, &mut NoDelay, 2).unwrap();
+    assert_eq!(empty.as_millimeters(), 80.0);
+    assert_eq!(full.as_millimeters(), 21.0);
+
+    let tank = FillLevel::new(empty, full).unwrap();
+    assert_eq!(tank.level(mm(50.5)).permille, 500);
+}
+
+#[test]
+fn calibration_fails_on_a_failed_measurement() {
+    let mut dev = Device::new(bus(&[(0x00, 80), (0xB0, 0)]));
+
+    assert_eq!(
+        FillLevel::calibrate_empty(&mut dev, &mut NoDelay, 4),
+        Err(Error::RangeError(RangeErrorCode::SignalToNoiseRatio))
+    );
+}
@@ -0,0 +1,180 @@
+//! Gesture detection over synthetic range streams
+
+use measurements::Length;
+use vl6180x::gesture::{Gesture, GestureConfig, GestureDetector};
+use vl6180x::{RangeErrorCode, RangeReading};
+
+/// One reading of a synthetic stream: millimeters, or `None` for no target
+type Sample = (u32, Option<f64>);
+
+/// Feeds `samples` to a detector with the default configuration and returns
+/// the gestures with the tick that produced them
+fn run(samples: &[Sample]) -> Vec<(u32, Gesture)> {
+    run_with(GestureDetector::new(GestureConfig::default()), samples)
+}
+
+fn run_with(mut detector: GestureDetector, samples: &[Sample]) -> Vec<(u32, Gesture)> {
+    samples
+        .iter()
+        .filter_map(|&(tick, mm)| {
+            let reading = match mm {
+                Some(mm) => RangeReading::Valid(Length::from_millimeters(mm)),
+                None => RangeReading::NoTarget,
+            };
+            detector.feed(tick, reading).map(|gesture| (tick, gesture))
+        })
+        .collect()
+}
+
+#[test]
+fn clean_swipe() {
+    let samples = [
+        (0, None),
+        (50, Some(140.0)),
+        (100, Some(90.0)),
+        (150, Some(55.0)),
+        (200, Some(30.0)),
+        (250, Some(25.0)),
+        (300, Some(45.0)),
+        (350, Some(80.0)),
+        (400, Some(130.0)),
+        (450, None),
+    ];
+
+    assert_eq!(
+        run(&samples),
+        [(200, Gesture::Approach), (400, Gesture::Retreat)]
+    );
+}
+
+#[test]
+fn hover() {
+    let mut samples = vec![(0, None), (100, Some(70.0)), (200, Some(40.0))];
+    samples.extend((3..=15).map(|i| (i * 100, Some(35.0 + (i % 3) as f64))));
+    samples.push((1600, None));
+    samples.push((1700, None));
+    samples.push((1800, None));
+
+    assert_eq!(
+        run(&samples),
+        [
+            (200, Gesture::Approach),
+            (1200, Gesture::Hold),
+            (1800, Gesture::Retreat),
+        ]
+    );
+}
+
+#[test]
+fn noisy_partial_gesture() {
+    // A hand enters the band, jitters across the near threshold only after
+    // lingering, drops out and leaves again: no complete gesture
+    let samples = [
+        (0, None),
+        (100, Some(95.0)),
+        (200, Some(70.0)),
+        (300, None),
+        (400, Some(65.0)),
+        (500, Some(99.0)),
+        (1100, Some(48.0)),
+        (1150, Some(52.0)),
+        (1200, Some(47.0)),
+        (1300, Some(85.0)),
+        (1400, Some(150.0)),
+    ];
+
+    assert_eq!(run(&samples), []);
+}
+
+#[test]
+fn jitter_in_the_band_is_not_a_retreat() {
+    let samples = [
+        (0, None),
+        (100, Some(40.0)),
+        (200, Some(60.0)),
+        (250, Some(48.0)),
+        (300, Some(99.0)),
+        (350, Some(45.0)),
+    ];
+
+    assert_eq!(run(&samples), [(100, Gesture::Approach)]);
+}
+
+#[test]
+fn occasional_dropouts_are_tolerated() {
+    let samples = [
+        (0, None),
+        (100, Some(40.0)),
+        (200, None),
+        (300, None),
+        (400, Some(40.0)),
+        (500, None),
+        (600, None),
+        (700, None),
+    ];
+
+    assert_eq!(
+        run(&samples),
+        [(100, Gesture::Approach), (700, Gesture::Retreat)]
+    );
+}
+
+#[test]
+fn failed_readings_are_skipped() {
+    let mut detector = GestureDetector::new(GestureConfig::default());
+    let failed = RangeReading::Failed(RangeErrorCode::SignalToNoiseRatio);
+
+    assert_eq!(detector.feed(0, RangeReading::NoTarget), None);
+    assert_eq!(detector.feed(100, failed), None);
+    assert_eq!(
+        detector.feed(200, RangeReading::Valid(Length::from_millimeters(30.0))),
+        Some(Gesture::Approach)
+    );
+    for tick in [300, 400, 500] {
+        assert_eq!(detector.feed(tick, failed), None);
+    }
+    assert!(detector.is_near());
+}
+
+#[test]
+fn target_present_at_startup_is_ignored_until_it_leaves() {
+    let samples = [
+        (0, Some(30.0)),
+        (100, Some(20.0)),
+        (200, Some(110.0)),
+        (300, Some(30.0)),
+    ];
+
+    assert_eq!(run(&samples), [(300, Gesture::Approach)]);
+}
+
+#[test]
+fn tick_wraps_around() {
+    let start = u32::MAX - 150;
+    let samples = [
+        (start, None),
+        (start.wrapping_add(100), Some(30.0)),
+        (start.wrapping_add(1100), Some(30.0)),
+    ];
+
+    assert_eq!(
+        run(&samples),
+        [
+            (start.wrapping_add(100), Gesture::Approach),
+            (start.wrapping_add(1100), Gesture::Hold),
+        ]
+    );
+}
+
+#[test]
+fn reset_forgets_the_gesture_in_progress() {
+    let mut detector = GestureDetector::new(GestureConfig::default());
+    let near = RangeReading::Valid(Length::from_millimeters(30.0));
+    detector.feed(0, RangeReading::NoTarget);
+    detector.feed(100, near);
+    assert!(detector.is_near());
+
+    detector.reset();
+    assert!(!detector.is_near());
+    assert_eq!(detector.feed(200, RangeReading::NoTarget), None);
+}
@@ -0,0 +1,255 @@
+//! Single-shot ranging squeezed between continuous ALS measurements
+
+mod support;
+
+use core::cell::Cell;
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::ErrorKind;
+use measurements::Length;
+use support::{block_on, clear_interrupts, store, Behavior, RegisterMap, Registers};
+use vl6180x::{Device, Error, RangeReading};
+
+/// Time each bus transaction takes, in microseconds
+const TRANSACTION_US: u64 = 100;
+
+/// Time a range measurement takes, in microseconds
+const RANGE_US: u64 = 12_000;
+
+/// Continuous ALS on a simulated clock, 100ms period and 50ms integration.
+///
+/// ALS measurement `k` integrates from `k * period` to `k * period +
+/// integration` and then reports its sample. A range measurement started
+/// during an integration, or running when one starts, aborts it.
+struct Timeline<'a> {
+    now: &'a Cell<u64>,
+    /// Integrations processed so far
+    cycles: u64,
+    completed: u32,
+    aborted: u32,
+    /// End of the running range measurement
+    range_end: Option<u64>,
+}
+
+impl Timeline<'_> {
+    fn period_us(regs: &Registers) -> u64 {
+        (u64::from(regs[0x03E]) + 1) * 10_000
+    }
+
+    fn integration_us(regs: &Registers) -> u64 {
+        (u64::from(regs[0x041]) + 1) * 1_000
+    }
+
+    /// Whether an ALS integration runs at `t`
+    fn integrating(regs: &Registers, t: u64) -> bool {
+        t % Self::period_us(regs) < Self::integration_us(regs)
+    }
+
+    /// Processes the measurements that ended by now
+    fn advance(&mut self, regs: &mut Registers) {
+        let now = self.now.get();
+        let (period, integration) = (Self::period_us(regs), Self::integration_us(regs));
+        loop {
+            let end = self.cycles * period + integration;
+            if end > now {
+                break;
+            }
+            let start = self.cycles * period;
+            let overlapped = self
+                .range_end
+                .is_some_and(|range_end| range_end > start && range_end - RANGE_US < end);
+            if overlapped {
+                self.aborted += 1;
+            } else {
+                self.completed += 1;
+                regs[0x04F] |= 0x20;
+            }
+            self.cycles += 1;
+        }
+        if self.range_end.is_some_and(|end| end <= now) {
+            self.range_end = None;
+            regs[0x04F] |= 0x04;
+        }
+    }
+}
+
+impl Behavior for Timeline<'_> {
+    fn transaction(&mut self, regs: &mut Registers, _: u8) -> Result<(), ErrorKind> {
+        self.now.set(self.now.get() + TRANSACTION_US);
+        self.advance(regs);
+        Ok(())
+    }
+
+    fn write(&mut self, regs: &mut Registers, register: u16, data: &[u8]) -> Result<(), ErrorKind> {
+        match (register, data[0]) {
+            (0x018, 0x01) => {
+                let now = self.now.get();
+                if Self::integrating(regs, now) {
+                    self.aborted += 1;
+                }
+                self.range_end = Some(now + RANGE_US);
+            }
+            (0x015, clear) => clear_interrupts(regs, clear),
+            _ => store(regs, register, data),
+        }
+        Ok(())
+    }
+}
+
+type Bus<'a> = RegisterMap<Timeline<'a>>;
+
+/// Both interrupts on new samples, 25ms convergence limit
+fn bus(now: &Cell<u64>) -> Bus<'_> {
+    RegisterMap::with(Timeline {
+        now,
+        cycles: 0,
+        completed: 0,
+        aborted: 0,
+        range_end: None,
+    })
+    .set(0x014, &[0x24])
+    .set(0x01C, &[25])
+    .set(0x03E, &[9])
+    .set(0x041, &[49])
+    .set(0x04D, &[0x01])
+    .set(0x062, &[80])
+    .set(0x10A, &[48])
+}
+
+/// Delay advancing the simulated clock
+struct Clock<'a>(&'a Cell<u64>);
+
+impl DelayNs for Clock<'_> {
+    fn delay_ns(&mut self, ns: u32) {
+        self.0.set(self.0.get() + u64::from(ns).div_ceil(1_000));
+    }
+}
+
+impl embedded_hal_async::delay::DelayNs for Clock<'_> {
+    async fn delay_ns(&mut self, ns: u32) {
+        DelayNs::delay_ns(self, ns);
+    }
+}
+
+/// Reads and clears pending ALS samples until `until`, as an application
+/// polling the ALS would
+fn consume_als(dev: &mut Device<&mut Bus<'_>>, clock: &mut Clock<'_>, until: u64) -> u32 {
+    let mut samples = 0;
+    while clock.0.get() < until {
+        let mut status = [0];
+        dev.read_raw_into(0x04F, &mut status).unwrap();
+        if status[0] & 0x38 != 0 {
+            dev.write_block(0x015, &[0x02]).unwrap();
+            samples += 1;
+        }
+        clock.delay_ms(1);
+    }
+    samples
+}
+
+#[test]
+fn ranges_between_als_measurements() {
+    let now = Cell::new(20_000);
+    let mut clock = Clock(&now);
+    let mut bus = bus(&now);
+    let mut dev = Device::new(&mut bus);
+
+    let reading = dev.range_single_during_continuous_als(&mut clock);
+    assert_eq!(
+        reading,
+        Ok(RangeReading::Valid(Length::from_millimeters(80.0)))
+    );
+    // Ranged after the first ALS sample and before the second measurement
+    assert!(now.get() > 50_000 && now.get() < 100_000);
+
+    let samples = consume_als(&mut dev, &mut clock, 460_000);
+    let _ = dev.release();
+    assert_eq!(bus.behavior.aborted, 0);
+    assert_eq!(bus.behavior.completed, 5);
+    assert_eq!(samples, 5);
+}
+
+#[test]
+fn leaves_the_als_sample_pending() {
+    let now = Cell::new(0);
+    let mut clock = Clock(&now);
+    let mut bus = bus(&now);
+    let mut dev = Device::new(&mut bus);
+
+    dev.range_single_during_continuous_als(&mut clock).unwrap();
+    let _ = dev.release();
+    assert_eq!(bus.regs[0x04F] & 0x38, 0x20);
+    assert_eq!(bus.regs[0x04F] & 0x07, 0);
+}
+
+#[test]
+fn repeated_ranging_loses_no_als_sample() {
+    let now = Cell::new(0);
+    let mut clock = Clock(&now);
+    let mut bus = bus(&now);
+    let mut dev = Device::new(&mut bus);
+
+    let mut samples = 0;
+    for _ in 0..4 {
+        dev.range_single_during_continuous_als(&mut clock).unwrap();
+        let until = now.get() + 30_000;
+        samples += consume_als(&mut dev, &mut clock, until);
+    }
+    let _ = dev.release();
+    assert_eq!(bus.behavior.aborted, 0);
+    assert_eq!(samples, bus.behavior.completed);
+    assert_eq!(samples, 4);
+}
+
+#[test]
+fn pending_als_sample_is_refused() {
+    let now = Cell::new(0);
+    let mut clock = Clock(&now);
+    let mut bus = bus(&now);
+    bus.regs[0x04F] = 0x20;
+    let mut dev = Device::new(&mut bus);
+
+    assert_eq!(
+        dev.range_single_during_continuous_als(&mut clock),
+        Err(Error::DeviceBusy)
+    );
+    let _ = dev.release();
+    assert_eq!(bus.behavior.range_end, None);
+}
+
+#[test]
+fn too_short_gap_is_refused() {
+    let now = Cell::new(0);
+    let mut clock = Clock(&now);
+    let mut bus = bus(&now);
+    // 60ms period leaves 10ms after the 50ms integration
+    bus.regs[0x03E] = 5;
+    let mut dev = Device::new(&mut bus);
+
+    assert!(matches!(
+        dev.range_single_during_continuous_als(&mut clock),
+        Err(Error::PeriodTooShort(_))
+    ));
+    let _ = dev.release();
+    assert_eq!(bus.behavior.range_end, None);
+    assert_eq!(bus.behavior.aborted, 0);
+}
+
+#[test]
+fn async_ranges_between_als_measurements() {
+    let now = Cell::new(120_000);
+    let mut clock = Clock(&now);
+    let mut bus = bus(&now);
+    bus.behavior.cycles = 1;
+    let mut dev = Device::new(&mut bus);
+
+    let reading = block_on(dev.range_single_during_continuous_als_async(&mut clock));
+    assert_eq!(
+        reading,
+        Ok(RangeReading::Valid(Length::from_millimeters(80.0)))
+    );
+    assert!(now.get() > 150_000 && now.get() < 200_000);
+    let _ = dev.release();
+    assert_eq!(bus.behavior.aborted, 0);
+    assert_eq!(bus.behavior.completed, 1);
+}
@@ -0,0 +1,159 @@
+//! Interleaved range and ALS measurements
+
+mod support;
+
+use core::time::Duration;
+
+use measurements::Length;
+use support::{block_on, Log, NoDelay, SimulatedVl6180x};
+use vl6180x::registers::ResultInterruptStatusGpio;
+use vl6180x::{AlsReading, Device, Error, RangeReading};
+
+/// Sensor with 50ms ALS integration, 30ms convergence limit and 48 sample
+/// averaging, measuring 80mm and 1000 counts
+fn sensor() -> SimulatedVl6180x {
+    let mut sim = SimulatedVl6180x::new();
+    sim.set_registers(0x01C, &[30]);
+    sim.set_registers(0x041, &[49]);
+    sim.set_registers(0x10A, &[48]);
+    sim.set_distance(80);
+    sim.set_als_counts(1000);
+    sim
+}
+
+const PERIOD: Duration = Duration::from_millis(120);
+
+#[test]
+fn start_programs_period_then_enables_interleaving_then_starts_als() {
+    let mut bus = sensor();
+    let mut dev = Device::new(&mut bus);
+
+    dev.start_interleaved(PERIOD).unwrap();
+    assert!(dev.is_interleaved_running());
+    let _ = dev.release();
+    assert_eq!(
+        bus.writes(),
+        [(0x03E, vec![11]), (0x2A3, vec![0x01]), (0x038, vec![0x03])]
+    );
+    // INTERLEAVED_MODE__ENABLE lies above 0x0FF: 0x0A3 stays untouched
+    assert_eq!(bus.register(0x2A3), 0x01);
+    assert_eq!(bus.register(0x0A3), sensor().register(0x0A3));
+}
+
+#[test]
+fn period_must_hold_both_measurements() {
+    let mut bus = sensor();
+    let mut dev = Device::new(&mut bus);
+
+    // Long enough for the 50ms integration alone, not with ranging after it
+    assert!(matches!(
+        dev.start_interleaved(Duration::from_millis(90)),
+        Err(Error::PeriodTooShort(_))
+    ));
+    assert!(!dev.is_interleaved_running());
+    let _ = dev.release();
+    assert!(bus.writes().is_empty());
+}
+
+#[test]
+fn read_returns_both_samples_and_clears_both_interrupts() {
+    let mut bus = sensor();
+    let mut dev = Device::new(&mut bus);
+
+    dev.start_interleaved(PERIOD).unwrap();
+    let status: ResultInterruptStatusGpio = dev.read_register().unwrap();
+    assert!(status.range_interrupt && status.als_interrupt);
+    let (range, als) = dev.read_interleaved().unwrap();
+    assert_eq!(range, RangeReading::Valid(Length::from_millimeters(80.0)));
+    assert!(matches!(als, AlsReading::Valid(_)));
+    let _ = dev.release();
+    assert_eq!(bus.writes().last(), Some(&(0x015, vec![0x03])));
+    assert_eq!(bus.register(0x04F), 0x00);
+}
+
+#[test]
+fn stop_waits_for_both_cores_and_disables_interleaving() {
+    let mut bus = sensor();
+    let mut dev = Device::new(&mut bus);
+
+    dev.start_interleaved(PERIOD).unwrap();
+    dev.stop_interleaved(&mut NoDelay).unwrap();
+    assert!(!dev.is_interleaved_running());
+    let _ = dev.release();
+
+    // After the three reads and three writes of the start
+    assert_eq!(
+        bus.bytes()[6..],
+        [
+            (vec![0x00, 0x38, 0x03], 0),
+            (vec![0x00, 0x4E], 1),
+            (vec![0x00, 0x4D], 1),
+            (vec![0x02, 0xA3, 0x00], 0),
+        ]
+    );
+    assert_eq!(bus.register(0x2A3), 0x00);
+    assert_eq!(bus.register(0x04E) & 0x01, 0x01);
+}
+
+#[test]
+fn stop_without_start_does_nothing() {
+    let mut bus = sensor();
+    let mut dev = Device::new(&mut bus);
+
+    dev.stop_interleaved(&mut NoDelay).unwrap();
+    let _ = dev.release();
+    assert!(bus.log().is_empty());
+}
+
+#[test]
+fn other_measurements_are_refused_while_interleaved() {
+    let mut bus = sensor();
+    let mut dev = Device::new(&mut bus);
+
+    dev.start_interleaved(PERIOD).unwrap();
+    assert_eq!(
+        dev.measure_range_single(&mut NoDelay),
+        Err(Error::DeviceBusy)
+    );
+    assert_eq!(dev.measure_als_single(&mut NoDelay), Err(Error::DeviceBusy));
+    assert_eq!(dev.start_continuous_range(), Err(Error::DeviceBusy));
+    assert_eq!(
+        dev.start_continuous_als(Duration::from_millis(500)),
+        Err(Error::DeviceBusy)
+    );
+    assert_eq!(dev.start_interleaved(PERIOD), Err(Error::DeviceBusy));
+}
+
+#[test]
+fn interleaving_is_refused_while_ranging() {
+    let mut bus = sensor();
+    let mut dev = Device::new(&mut bus);
+
+    dev.start_continuous_range().unwrap();
+    assert_eq!(dev.start_interleaved(PERIOD), Err(Error::DeviceBusy));
+    assert!(!dev.is_interleaved_running());
+}
+
+#[test]
+fn async_sequences_match_blocking() {
+    let mut blocking = sensor();
+    let mut dev = Device::new(&mut blocking);
+    dev.start_interleaved(PERIOD).unwrap();
+    let readings = dev.read_interleaved().unwrap();
+    dev.stop_interleaved(&mut NoDelay).unwrap();
+    let _ = dev.release();
+
+    let mut nonblocking = sensor();
+    let mut dev = Device::new(&mut nonblocking);
+    let async_readings = block_on(async {
+        dev.start_interleaved_async(PERIOD).await?;
+        let readings = dev.read_interleaved_async().await?;
+        dev.stop_interleaved_async(&mut NoDelay).await?;
+        Ok::<_, Error>(readings)
+    })
+    .unwrap();
+    let _ = dev.release();
+
+    assert_eq!(readings, async_readings);
+    assert_eq!(blocking.log(), nonblocking.log());
+}
@@ -0,0 +1,174 @@
+//! Interrupt conditions select the right mode and carry the thresholds it needs
+
+use measurements::Length;
+use regiface::ToByteArray;
+use vl6180x::registers::{AlsThresholds, RangeThresholds};
+use vl6180x::types::*;
+
+fn mm(value: f64) -> Length {
+    Length::from_millimeters(value)
+}
+
+fn lux(value: f32) -> Luminance {
+    Luminance { lux: value }
+}
+
+#[test]
+fn range_disabled_needs_no_thresholds() {
+    let condition = RangeInterrupt::Disabled;
+    assert_eq!(condition.mode(), InterruptMode::Disabled);
+    assert_eq!(condition.thresholds(), None);
+}
+
+#[test]
+fn range_new_sample_ready_needs_no_thresholds() {
+    let condition = RangeInterrupt::NewSampleReady;
+    assert_eq!(condition.mode(), InterruptMode::NewSampleReady);
+    assert_eq!(condition.thresholds(), None);
+}
+
+#[test]
+fn range_level_low_programs_low_threshold() {
+    let condition = RangeInterrupt::LevelLow { low: mm(50.0) };
+    assert_eq!(condition.mode(), InterruptMode::LevelLow);
+    assert_eq!(
+        condition.thresholds(),
+        Some(RangeThresholds::below(mm(50.0)))
+    );
+}
+
+#[test]
+fn range_level_high_programs_high_threshold() {
+    let condition = RangeInterrupt::LevelHigh { high: mm(150.0) };
+    assert_eq!(condition.mode(), InterruptMode::LevelHigh);
+    assert_eq!(
+        condition.thresholds(),
+        Some(RangeThresholds::above(mm(150.0)))
+    );
+}
+
+#[test]
+fn range_out_of_window_programs_both_thresholds() {
+    let condition = RangeInterrupt::OutOfWindow {
+        low: mm(50.0),
+        high: mm(150.0),
+    };
+    assert_eq!(condition.mode(), InterruptMode::OutOfWindow);
+    assert_eq!(
+        condition.thresholds(),
+        RangeThresholds::window(mm(50.0), mm(150.0))
+    );
+}
+
+#[test]
+fn als_disabled_needs_no_thresholds() {
+    let condition = AlsInterrupt::Disabled;
+    assert_eq!(condition.mode(), InterruptMode::Disabled);
+    assert_eq!(condition.thresholds(), None);
+}
+
+#[test]
+fn als_new_sample_ready_needs_no_thresholds() {
+    let condition = AlsInterrupt::NewSampleReady;
+    assert_eq!(condition.mode(), InterruptMode::NewSampleReady);
+    assert_eq!(condition.thresholds(), None);
+}
+
+#[test]
+fn als_level_low_programs_low_threshold() {
+    let condition = AlsInterrupt::LevelLow { low: lux(10.0) };
+    assert_eq!(condition.mode(), InterruptMode::LevelLow);
+    assert_eq!(
+        condition.thresholds(),
+        Some(AlsThresholds::below(lux(10.0)))
+    );
+}
+
+#[test]
+fn als_level_high_programs_high_threshold() {
+    let condition = AlsInterrupt::LevelHigh { high: lux(500.0) };
+    assert_eq!(condition.mode(), InterruptMode::LevelHigh);
+    assert_eq!(
+        condition.thresholds(),
+        Some(AlsThresholds::above(lux(500.0)))
+    );
+}
+
+#[test]
+fn als_out_of_window_programs_both_thresholds() {
+    let condition = AlsInterrupt::OutOfWindow {
+        low: lux(10.0),
+        high: lux(500.0),
+    };
+    assert_eq!(condition.mode(), InterruptMode::OutOfWindow);
+    assert_eq!(
+        condition.thresholds(),
+        AlsThresholds::window(lux(10.0), lux(500.0))
+    );
+}
+
+#[test]
+fn range_below_fills_high_with_max() {
+    let thresholds = RangeThresholds::below(mm(50.0));
+    assert_eq!(thresholds.raw_low_mm(), 50);
+    assert_eq!(thresholds.raw_high_mm(), RangeThresholds::MAX_MM);
+}
+
+#[test]
+fn range_above_fills_low_with_zero() {
+    let thresholds = RangeThresholds::above(mm(150.0));
+    assert_eq!(thresholds.raw_high_mm(), 150);
+    assert_eq!(thresholds.raw_low_mm(), 0);
+}
+
+#[test]
+fn range_window_keeps_both() {
+    let thresholds = RangeThresholds::window(mm(50.0), mm(150.0)).unwrap();
+    assert_eq!(thresholds.raw_high_mm(), 150);
+    assert_eq!(thresholds.raw_low_mm(), 50);
+}
+
+#[test]
+fn range_window_rejects_inverted_bounds() {
+    assert_eq!(RangeThresholds::window(mm(150.0), mm(50.0)), None);
+    assert!(RangeThresholds::window(mm(80.0), mm(80.0)).is_some());
+}
+
+#[test]
+fn range_below_encoding() {
+    let bytes = RangeThresholds::below(mm(50.0)).to_bytes().unwrap();
+    assert_eq!(bytes, [0xFF, 0x32]);
+}
+
+#[test]
+fn range_above_encoding() {
+    let bytes = RangeThresholds::above(mm(150.0)).to_bytes().unwrap();
+    assert_eq!(bytes, [0x96, 0x00]);
+}
+
+#[test]
+fn als_below_encoding() {
+    let bytes = AlsThresholds::below(lux(10.0)).to_bytes().unwrap();
+    assert_eq!(bytes, [0xFF, 0xFF, 0x00, 0x0A]);
+}
+
+#[test]
+fn als_above_encoding() {
+    let bytes = AlsThresholds::above(lux(500.0)).to_bytes().unwrap();
+    assert_eq!(bytes, [0x01, 0xF4, 0x00, 0x00]);
+}
+
+#[test]
+fn als_window_encoding() {
+    let bytes = AlsThresholds::window(lux(10.0), lux(500.0))
+        .unwrap()
+        .to_bytes()
+        .unwrap();
+    assert_eq!(bytes, [0x01, 0xF4, 0x00, 0x0A]);
+}
+
+#[test]
+fn als_window_rejects_inverted_bounds() {
+    assert_eq!(AlsThresholds::window(lux(500.0), lux(10.0)), None);
+    assert!(AlsThresholds::window(lux(10.0), lux(10.0)).is_some());
+}
@@ -0,0 +1,78 @@
+//! Inspecting pending interrupts without consuming them
+
+mod support;
+
+use support::{Clearing, Log, RegisterMap};
+use vl6180x::events::{EventQueue, InterruptEvent};
+use vl6180x::Device;
+
+/// Sensor with `status` pending in the interrupt status register
+fn bus(status: u8) -> RegisterMap<Clearing> {
+    RegisterMap::with(Clearing).set(0x04F, &[status])
+}
+
+#[test]
+fn peek_reads_once_and_never_clears() {
+    // Range new sample ready and ALS level low pending
+    let mut bus = bus(0x0C);
+    let mut dev = Device::new(&mut bus);
+
+    let first = dev.peek_interrupt_status().unwrap();
+    let second = dev.peek_interrupt_status().unwrap();
+    let _ = dev.release();
+
+    assert_eq!(first, second);
+    assert!(first.range_interrupt && first.als_interrupt && !first.error_interrupt);
+    assert_eq!(bus.registers_read(), [0x04F, 0x04F]);
+    assert!(bus.writes().is_empty());
+    assert_eq!(bus.regs[0x04F], 0x0C);
+}
+
+#[test]
+fn pending_interrupts_is_typed_peek() {
+    let mut bus = bus(0x44);
+    let mut dev = Device::new(&mut bus);
+
+    let sources = dev.pending_interrupts().unwrap();
+    let _ = dev.release();
+
+    assert!(sources.contains(InterruptEvent::Range));
+    assert!(!sources.contains(InterruptEvent::Als));
+    assert!(sources.contains(InterruptEvent::Error));
+    assert_eq!(
+        sources.iter().collect::<Vec<_>>(),
+        [InterruptEvent::Range, InterruptEvent::Error]
+    );
+    assert_eq!(bus.registers_read(), [0x04F]);
+    assert!(bus.writes().is_empty());
+}
+
+#[test]
+fn nothing_pending() {
+    let mut bus = bus(0x00);
+    let sources = Device::new(&mut bus).pending_interrupts().unwrap();
+
+    assert!(sources.is_empty());
+    assert_eq!(sources.iter().next(), None);
+}
+
+#[test]
+fn handle_consumes_what_peek_left() {
+    let mut bus = bus(0x0C);
+    let mut dev = Device::new(&mut bus);
+    let mut queue = EventQueue::<4>::new();
+
+    let peeked = dev.peek_interrupt_status().unwrap();
+    let handled = dev.handle_interrupt(&mut queue).unwrap();
+    let after = dev.pending_interrupts().unwrap();
+    let _ = dev.release();
+
+    assert_eq!(peeked, handled);
+    assert!(after.is_empty());
+    assert_eq!(
+        queue.drain().collect::<Vec<_>>(),
+        [InterruptEvent::Range, InterruptEvent::Als]
+    );
+    assert_eq!(bus.registers_read(), [0x04F, 0x04F, 0x04F]);
+    assert_eq!(bus.writes(), [(0x015, vec![0x03])]);
+}
@@ -0,0 +1,296 @@
+//! Waiting on the GPIO1 interrupt output
+
+mod support;
+
+use std::collections::VecDeque;
+
+use core::future::pending;
+use core::time::Duration;
+
+use embedded_hal::digital::{ErrorKind as PinErrorKind, ErrorType as PinErrorType, InputPin};
+use embedded_hal::i2c::ErrorKind;
+use support::{block_on, load, Behavior, Log, NoDelay, RegisterMap, Registers};
+use vl6180x::{Device, Error, SpuriousInterruptPolicy};
+
+/// Interrupt statuses returned by the next status reads, before the
+/// register file
+struct Statuses(VecDeque<u8>);
+
+impl Behavior for Statuses {
+    fn read(
+        &mut self,
+        regs: &mut Registers,
+        register: u16,
+        buf: &mut [u8],
+    ) -> Result<(), ErrorKind> {
+        load(regs, register, buf);
+        if register == 0x04F {
+            if let Some(status) = self.0.pop_front() {
+                buf[0] = status;
+            }
+        }
+        Ok(())
+    }
+}
+
+type Bus = RegisterMap<Statuses>;
+
+/// GPIO1 configured as interrupt output with the given polarity and
+/// interrupt status
+fn bus(active_high: bool, status: u8) -> Bus {
+    let gpio1 = 0x10 | if active_high { 0x20 } else { 0x00 };
+    RegisterMap::with(Statuses(VecDeque::new()))
+        .set(0x011, &[gpio1])
+        .set(0x04F, &[status])
+}
+
+/// Active-low GPIO1 reporting `statuses` in turn, then nothing pending
+fn scripted(statuses: &[u8]) -> Bus {
+    let mut bus = bus(false, 0x00);
+    bus.behavior.0.extend(statuses);
+    bus
+}
+
+/// Pin reporting a scripted sequence of levels, repeating the last one
+struct Pin<'a> {
+    levels: &'a [bool],
+    reads: u32,
+}
+
+impl<'a> Pin<'a> {
+    fn new(levels: &'a [bool]) -> Self {
+        Self { levels, reads: 0 }
+    }
+}
+
+impl PinErrorType for Pin<'_> {
+    type Error = PinErrorKind;
+}
+
+impl InputPin for Pin<'_> {
+    fn is_high(&mut self) -> Result<bool, PinErrorKind> {
+        let index = (self.reads as usize).min(self.levels.len() - 1);
+        self.reads += 1;
+        Ok(self.levels[index])
+    }
+
+    fn is_low(&mut self) -> Result<bool, PinErrorKind> {
+        self.is_high().map(|high| !high)
+    }
+}
+
+impl embedded_hal_async::digital::Wait for Pin<'_> {
+    async fn wait_for_high(&mut self) -> Result<(), PinErrorKind> {
+        self.wait_for(true).await
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), PinErrorKind> {
+        self.wait_for(false).await
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), PinErrorKind> {
+        self.wait_for(true).await
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), PinErrorKind> {
+        self.wait_for(false).await
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), PinErrorKind> {
+        let level = self.is_high()?;
+        self.wait_for(!level).await
+    }
+}
+
+impl Pin<'_> {
+    /// Consumes levels until one matches, never completing once the
+    /// script has ended on another level
+    async fn wait_for(&mut self, high: bool) -> Result<(), PinErrorKind> {
+        loop {
+            if self.is_high()? == high {
+                return Ok(());
+            }
+            if self.reads as usize >= self.levels.len() {
+                pending::<()>().await;
+            }
+        }
+    }
+}
+
+const TIMEOUT: Duration = Duration::from_millis(10);
+
+#[test]
+fn asserted_pin_returns_immediately() {
+    let mut bus = bus(false, 0x04);
+    let mut pin = Pin::new(&[false]);
+    let status = Device::new(&mut bus)
+        .wait_for_interrupt_pin(&mut pin, &mut NoDelay, TIMEOUT)
+        .unwrap();
+
+    assert!(status.range_interrupt);
+    assert!(!status.als_interrupt);
+    assert_eq!(pin.reads, 2);
+    assert_eq!(bus.reads().len(), 2);
+}
+
+#[test]
+fn delayed_assertion_polls_only_the_pin() {
+    let mut bus = bus(false, 0x20);
+    let mut pin = Pin::new(&[true, true, true, false]);
+    let status = Device::new(&mut bus)
+        .wait_for_interrupt_pin(&mut pin, &mut NoDelay, TIMEOUT)
+        .unwrap();
+
+    assert!(status.als_interrupt);
+    assert_eq!(pin.reads, 5);
+    assert_eq!(bus.reads().len(), 2);
+}
+
+#[test]
+fn active_high_polarity_is_honored() {
+    let mut bus = bus(true, 0x04);
+    let mut pin = Pin::new(&[false, false, true]);
+    let status = Device::new(&mut bus)
+        .wait_for_interrupt_pin(&mut pin, &mut NoDelay, TIMEOUT)
+        .unwrap();
+
+    assert!(status.range_interrupt);
+    assert_eq!(pin.reads, 4);
+}
+
+#[test]
+fn inactive_pin_times_out_without_reading_the_status() {
+    let mut bus = bus(false, 0x04);
+    let mut pin = Pin::new(&[true]);
+    let result = Device::new(&mut bus).wait_for_interrupt_pin(&mut pin, &mut NoDelay, TIMEOUT);
+
+    assert_eq!(result, Err(Error::Timeout));
+    assert_eq!(pin.reads, 11);
+    assert_eq!(bus.reads().len(), 1);
+}
+
+#[test]
+fn asserted_pin_without_pending_source_is_spurious() {
+    let mut bus = bus(false, 0x00);
+    let mut pin = Pin::new(&[false]);
+    let result = Device::new(&mut bus).wait_for_interrupt_pin(&mut pin, &mut NoDelay, TIMEOUT);
+
+    assert_eq!(result, Err(Error::SpuriousInterrupt));
+}
+
+#[test]
+fn spurious_interrupts_are_reported_by_default() {
+    let mut bus = bus(false, 0x00);
+    let dev = Device::new(&mut bus);
+    assert_eq!(
+        dev.spurious_interrupt_policy(),
+        SpuriousInterruptPolicy::Report
+    );
+}
+
+#[test]
+fn glitch_is_waited_out_without_reading_the_status() {
+    let mut bus = bus(false, 0x04);
+    let mut pin = Pin::new(&[true, false, true, true, false, false]);
+    let status = Device::new(&mut bus)
+        .wait_for_interrupt_pin(&mut pin, &mut NoDelay, TIMEOUT)
+        .unwrap();
+
+    assert!(status.range_interrupt);
+    assert_eq!(pin.reads, 6);
+    assert_eq!(bus.reads().len(), 2);
+}
+
+#[test]
+fn spurious_then_real_interrupt_returns_the_real_one() {
+    let mut bus = scripted(&[0x00, 0x04]);
+    let mut pin = Pin::new(&[false, false, true, false, false]);
+    let mut dev = Device::new(&mut bus);
+    dev.set_spurious_interrupt_policy(SpuriousInterruptPolicy::KeepWaiting);
+    let status = dev
+        .wait_for_interrupt_pin(&mut pin, &mut NoDelay, TIMEOUT)
+        .unwrap();
+    let _ = dev.release();
+
+    assert!(status.range_interrupt);
+    assert_eq!(bus.reads().len(), 3);
+}
+
+#[test]
+fn spurious_interrupt_is_reported_before_the_real_one() {
+    let mut bus = scripted(&[0x00, 0x04]);
+    let mut pin = Pin::new(&[false]);
+    let result = Device::new(&mut bus).wait_for_interrupt_pin(&mut pin, &mut NoDelay, TIMEOUT);
+
+    assert_eq!(result, Err(Error::SpuriousInterrupt));
+}
+
+#[test]
+fn pin_stuck_active_times_out_after_one_status_read() {
+    let mut bus = bus(false, 0x00);
+    let mut pin = Pin::new(&[false]);
+    let mut dev = Device::new(&mut bus);
+    dev.set_spurious_interrupt_policy(SpuriousInterruptPolicy::KeepWaiting);
+    let result = dev.wait_for_interrupt_pin(&mut pin, &mut NoDelay, TIMEOUT);
+    let _ = dev.release();
+
+    assert_eq!(result, Err(Error::Timeout));
+    assert_eq!(bus.reads().len(), 2);
+}
+
+#[test]
+fn async_wait_returns_the_pending_status() {
+    let mut bus = bus(true, 0x20);
+    let mut pin = Pin::new(&[false, false, true]);
+    let status = block_on(Device::new(&mut bus).wait_for_interrupt_pin_async(
+        &mut pin,
+        &mut NoDelay,
+        TIMEOUT,
+    ))
+    .unwrap();
+
+    assert!(status.als_interrupt);
+    assert_eq!(bus.reads().len(), 2);
+}
+
+#[test]
+fn async_spurious_then_real_interrupt_returns_the_real_one() {
+    let mut bus = scripted(&[0x00, 0x04]);
+    let mut pin = Pin::new(&[false, false, true, false, true, false, false]);
+    let mut dev = Device::new(&mut bus);
+    dev.set_spurious_interrupt_policy(SpuriousInterruptPolicy::KeepWaiting);
+    let status =
+        block_on(dev.wait_for_interrupt_pin_async(&mut pin, &mut NoDelay, TIMEOUT)).unwrap();
+    let _ = dev.release();
+
+    // The glitch on the fourth level is not confirmed by the fifth
+    assert!(status.range_interrupt);
+    assert_eq!(bus.reads().len(), 3);
+}
+
+#[test]
+fn async_spurious_interrupt_is_reported_by_default() {
+    let mut bus = scripted(&[0x00, 0x04]);
+    let mut pin = Pin::new(&[false]);
+    let result = block_on(Device::new(&mut bus).wait_for_interrupt_pin_async(
+        &mut pin,
+        &mut NoDelay,
+        TIMEOUT,
+    ));
+
+    assert_eq!(result, Err(Error::SpuriousInterrupt));
+}
+
+#[test]
+fn async_inactive_pin_times_out() {
+    let mut bus = bus(false, 0x04);
+    let mut pin = Pin::new(&[true]);
+    let result = block_on(Device::new(&mut bus).wait_for_interrupt_pin_async(
+        &mut pin,
+        &mut NoDelay,
+        TIMEOUT,
+    ));
+
+    assert_eq!(result, Err(Error::Timeout));
+    assert_eq!(bus.reads().len(), 1);
+}
@@ -0,0 +1,118 @@
+//! Interrupt flag handing GPIO1 interrupts to the main loop
+#![cfg(feature = "irq-flag")]
+
+mod support;
+
+use embedded_hal::i2c::ErrorKind;
+use support::{block_on, Clearing, RegisterMap};
+use vl6180x::events::{EventQueue, InterruptEvent};
+use vl6180x::irq::IrqFlag;
+use vl6180x::{Device, Error};
+
+/// Sensor with `status` pending in the interrupt status register
+fn bus(status: u8) -> RegisterMap<Clearing> {
+    RegisterMap::with(Clearing).set(0x04F, &[status])
+}
+
+#[test]
+fn take_clears_the_flag() {
+    let flag = IrqFlag::new();
+    assert!(!flag.is_set());
+    assert!(!flag.take());
+
+    flag.notify();
+    assert!(flag.is_set());
+    assert!(flag.take());
+    assert!(!flag.is_set());
+    assert!(!flag.take());
+}
+
+#[test]
+fn notifications_before_a_take_merge() {
+    static FLAG: IrqFlag = IrqFlag::new();
+    FLAG.notify();
+    FLAG.notify();
+    assert!(FLAG.take());
+    assert!(!FLAG.take());
+}
+
+#[test]
+fn no_flag_means_no_bus_traffic() {
+    let mut bus = bus(0x04);
+    let flag = IrqFlag::new();
+    let mut events = EventQueue::<4>::new();
+    let mut dev = Device::new(&mut bus);
+    for _ in 0..3 {
+        assert_eq!(dev.service_if_flagged(&flag, &mut events), Ok(None));
+    }
+    let _ = dev.release();
+
+    assert_eq!(bus.transactions(), 0);
+    assert_eq!(bus.regs[0x04F], 0x04);
+    assert_eq!(events.pop(), None);
+}
+
+#[test]
+fn flagged_interrupt_is_serviced_once() {
+    let mut bus = bus(0x24);
+    let flag = IrqFlag::new();
+    let mut events = EventQueue::<4>::new();
+    let mut dev = Device::new(&mut bus);
+
+    flag.notify();
+    let status = dev.service_if_flagged(&flag, &mut events).unwrap();
+    assert!(status.is_some_and(|status| status.range_interrupt));
+    assert!(!flag.is_set());
+    assert_eq!(dev.service_if_flagged(&flag, &mut events), Ok(None));
+    let _ = dev.release();
+
+    // Status read and clear
+    assert_eq!(bus.transactions(), 2);
+    assert_eq!(bus.regs[0x04F], 0x00);
+    let drained: Vec<_> = events.drain().collect();
+    assert_eq!(drained, [InterruptEvent::Range, InterruptEvent::Als]);
+}
+
+#[test]
+fn failed_service_keeps_the_flag_for_a_retry() {
+    let mut bus = bus(0x04);
+    bus.fail_next(1, ErrorKind::Other);
+    let flag = IrqFlag::new();
+    let mut events = EventQueue::<4>::new();
+    flag.notify();
+
+    let mut dev = Device::new(&mut bus);
+    assert!(matches!(
+        dev.service_if_flagged(&flag, &mut events),
+        Err(Error::BusError(_))
+    ));
+    assert!(flag.is_set());
+
+    assert!(dev
+        .service_if_flagged(&flag, &mut events)
+        .unwrap()
+        .is_some());
+    assert!(!flag.is_set());
+    assert_eq!(events.pop(), Some(InterruptEvent::Range));
+}
+
+#[test]
+fn async_service_matches_blocking() {
+    let mut bus = bus(0x04);
+    let flag = IrqFlag::new();
+    let mut events = EventQueue::<4>::new();
+    let mut dev = Device::new(&mut bus);
+
+    assert_eq!(
+        block_on(dev.service_if_flagged_async(&flag, &mut events)),
+        Ok(None)
+    );
+    flag.notify();
+    assert!(block_on(dev.service_if_flagged_async(&flag, &mut events))
+        .unwrap()
+        .is_some());
+    let _ = dev.release();
+
+    assert_eq!(bus.transactions(), 2);
+    assert_eq!(events.pop(), Some(InterruptEvent::Range));
+}
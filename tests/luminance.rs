@@ -0,0 +1,107 @@
+//! Arithmetic and ordering of [`Luminance`]
+
+use vl6180x::Luminance;
+
+fn lux(value: f32) -> Luminance {
+    Luminance::from_lux(value)
+}
+
+#[test]
+fn constructors_agree() {
+    assert_eq!(Luminance::from_millilux(1_500), lux(1.5));
+    assert_eq!(Luminance::from_millilux(0), lux(0.0));
+    assert_eq!(Luminance::from_lux(2.0).lux, 2.0);
+}
+
+#[test]
+fn zero_is_additive_identity() {
+    assert_eq!(lux(12.5) + lux(0.0), lux(12.5));
+    assert_eq!(lux(12.5) - lux(0.0), lux(12.5));
+}
+
+#[test]
+fn add_is_commutative() {
+    assert_eq!(lux(1.25) + lux(3.5), lux(3.5) + lux(1.25));
+}
+
+#[test]
+fn sub_undoes_add() {
+    assert_eq!(lux(10.0) + lux(2.5) - lux(2.5), lux(10.0));
+}
+
+#[test]
+fn sub_saturates_at_zero() {
+    assert_eq!(lux(1.0) - lux(5.0), lux(0.0));
+    assert_eq!((lux(1.0) - lux(5.0)).lux, 0.0);
+}
+
+#[test]
+fn mul_and_div_are_inverse() {
+    assert_eq!(lux(7.5) * 4.0 / 4.0, lux(7.5));
+    assert_eq!(lux(7.5) * 1.0, lux(7.5));
+    assert_eq!(lux(7.5) / 1.0, lux(7.5));
+}
+
+#[test]
+fn sum_of_empty_is_zero() {
+    assert_eq!(
+        core::iter::empty::<Luminance>().sum::<Luminance>(),
+        lux(0.0)
+    );
+}
+
+#[test]
+fn sum_matches_repeated_add() {
+    let readings = [lux(1.0), lux(2.25), lux(3.5)];
+    assert_eq!(readings.iter().sum::<Luminance>(), lux(6.75));
+    assert_eq!(readings.into_iter().sum::<Luminance>(), lux(6.75));
+}
+
+#[test]
+fn checked_ops_match_the_operators() {
+    assert_eq!(lux(1.25).checked_add(lux(3.5)), Some(lux(1.25) + lux(3.5)));
+    assert_eq!(lux(7.5).checked_mul(4.0), Some(lux(30.0)));
+    assert_eq!(lux(7.5).checked_div(4.0), Some(lux(1.875)));
+}
+
+#[test]
+fn checked_add_refuses_overflow() {
+    assert_eq!(lux(f32::MAX).checked_add(lux(f32::MAX)), None);
+    assert_eq!(lux(f32::MAX).checked_add(lux(0.0)), Some(lux(f32::MAX)));
+}
+
+#[test]
+fn checked_mul_refuses_overflow_and_negative_results() {
+    assert_eq!(lux(f32::MAX).checked_mul(2.0), None);
+    assert_eq!(lux(1.0).checked_mul(-1.0), None);
+    assert_eq!(lux(1.0).checked_mul(f32::NAN), None);
+}
+
+#[test]
+fn checked_div_refuses_division_by_zero() {
+    assert_eq!(lux(1.0).checked_div(0.0), None);
+    assert_eq!(lux(1.0).checked_div(-2.0), None);
+    assert_eq!(lux(0.0).checked_div(0.0), None);
+}
+
+#[test]
+fn ordering_follows_lux() {
+    assert!(lux(1.0) < lux(2.0));
+    assert!(lux(2.0) > lux(1.0));
+    assert_eq!(lux(1.0).max(lux(2.0)), lux(2.0));
+}
+
+#[test]
+fn readings_rounding_to_the_same_millilux_are_equal() {
+    assert_eq!(lux(1.0), lux(1.0004));
+    assert_eq!(lux(1.0).cmp(&lux(1.0004)), core::cmp::Ordering::Equal);
+    // 0.2 millilux apart, but rounding to 1000 and 1001 millilux
+    assert_ne!(lux(1.0004), lux(1.0006));
+}
+
+#[test]
+fn ordering_across_rounding_boundary() {
+    // 1.0004 lux rounds down to 1000 millilux, 1.0006 lux up to 1001
+    assert!(lux(1.0004) < lux(1.0006));
+    assert_eq!(lux(1.0006), Luminance::from_millilux(1_001));
+}
@@ -0,0 +1,141 @@
+//! Distance and light level from one call
+
+mod support;
+
+use core::time::Duration;
+
+use embedded_hal::i2c::ErrorKind;
+use measurements::Length;
+use support::{block_on, clear_interrupts, store, Behavior, Log, NoDelay, RegisterMap, Registers};
+use vl6180x::{AlsGain, AlsReading, Device, Error, Luminance, RangeReading, Timeouts};
+
+/// Sensor completing single-shot measurements on their start write and
+/// raising the new sample interrupts selected in 0x014
+struct SingleShot {
+    als_completes: bool,
+}
+
+impl Behavior for SingleShot {
+    fn write(&mut self, regs: &mut Registers, register: u16, data: &[u8]) -> Result<(), ErrorKind> {
+        store(regs, register, data);
+        let config = regs[0x014];
+        match (register, data[0]) {
+            (0x018, 0x01) if config & 0x07 == 0x04 => regs[0x04F] |= 0x04,
+            (0x038, 0x01) if config & 0x38 == 0x20 && self.als_completes => regs[0x04F] |= 0x20,
+            (0x015, clear) => clear_interrupts(regs, clear),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+type Bus = RegisterMap<SingleShot>;
+
+/// Idle sensor with gain 1, 100ms integration, 64 counts and 120mm
+fn bus(interrupt_config: u8) -> Bus {
+    RegisterMap::with(SingleShot {
+        als_completes: true,
+    })
+    .set(0x014, &[interrupt_config])
+    .set(0x03F, &[0x46])
+    .set(0x041, &[0x63])
+    .set(0x04D, &[0x01, 0x01])
+    .set(0x051, &[64])
+    .set(0x062, &[120])
+}
+
+/// Both interrupts on new sample ready
+const NEW_SAMPLES: u8 = 0x24;
+/// Range out of window, ALS disabled
+const RANGE_WINDOW: u8 = 0x03;
+
+fn expected() -> (RangeReading, AlsReading) {
+    (
+        RangeReading::Valid(Length::from_millimeters(120.0)),
+        AlsReading::Valid(Luminance::from_counts(
+            64,
+            AlsGain::Gain1,
+            Duration::from_millis(100),
+        )),
+    )
+}
+
+#[test]
+fn als_then_range_under_the_existing_configuration() {
+    let mut bus = bus(NEW_SAMPLES);
+    let mut dev = Device::new(&mut bus);
+    assert_eq!(dev.measure_both(&mut NoDelay), Ok(expected()));
+    let _ = dev.release();
+
+    assert_eq!(
+        bus.byte_writes(),
+        [(0x038, 0x01), (0x015, 0x02), (0x018, 0x01), (0x015, 0x01)]
+    );
+}
+
+#[test]
+fn altered_interrupt_configuration_is_restored() {
+    let mut bus = bus(RANGE_WINDOW);
+    let mut dev = Device::new(&mut bus);
+    assert_eq!(dev.measure_both(&mut NoDelay), Ok(expected()));
+    let _ = dev.release();
+
+    assert_eq!(
+        bus.byte_writes(),
+        [
+            (0x014, NEW_SAMPLES),
+            (0x038, 0x01),
+            (0x015, 0x02),
+            (0x018, 0x01),
+            (0x015, 0x01),
+            (0x014, RANGE_WINDOW),
+        ]
+    );
+}
+
+#[test]
+fn pending_error_interrupt_is_left_alone() {
+    let mut bus = bus(NEW_SAMPLES);
+    bus.regs[0x04F] = 0x40;
+    let mut dev = Device::new(&mut bus);
+    dev.measure_both(&mut NoDelay).unwrap();
+    let _ = dev.release();
+
+    assert_eq!(bus.regs[0x04F], 0x40);
+}
+
+#[test]
+fn configuration_is_restored_after_a_timeout() {
+    let mut bus = bus(RANGE_WINDOW);
+    bus.behavior.als_completes = false;
+    let mut dev = Device::new(&mut bus);
+    dev.set_timeouts(Timeouts {
+        als: Duration::from_millis(1),
+        ..Timeouts::default()
+    });
+    assert_eq!(dev.measure_both(&mut NoDelay), Err(Error::Timeout));
+    let _ = dev.release();
+
+    assert_eq!(
+        bus.byte_writes(),
+        [(0x014, NEW_SAMPLES), (0x038, 0x01), (0x014, RANGE_WINDOW)]
+    );
+}
+
+#[test]
+fn async_matches_blocking() {
+    for config in [NEW_SAMPLES, RANGE_WINDOW] {
+        let mut sync_bus = bus(config);
+        let mut dev = Device::new(&mut sync_bus);
+        let sync_result = dev.measure_both(&mut NoDelay);
+        let _ = dev.release();
+
+        let mut async_bus = bus(config);
+        let mut dev = Device::new(&mut async_bus);
+        let async_result = block_on(dev.measure_both_async(&mut NoDelay));
+        let _ = dev.release();
+
+        assert_eq!(async_result, sync_result);
+        assert_eq!(async_bus.byte_writes(), sync_bus.byte_writes());
+    }
+}
@@ -0,0 +1,161 @@
+//! Buffering measurements between an interrupt handler and the main loop
+
+mod support;
+
+use embedded_hal::i2c::I2c;
+use measurements::Length;
+use support::{Clearing, RegisterMap};
+use vl6180x::buffer::{MeasurementBuffer, OverflowPolicy};
+use vl6180x::events::{EventQueue, InterruptEvent};
+use vl6180x::{Device, RangeReading};
+
+fn at(mm: f64) -> RangeReading {
+    RangeReading::Valid(Length::from_millimeters(mm))
+}
+
+fn filled<const N: usize>(
+    policy: OverflowPolicy,
+    mms: &[f64],
+) -> MeasurementBuffer<RangeReading, N> {
+    let mut buffer = MeasurementBuffer::new(policy);
+    for &mm in mms {
+        buffer.push(at(mm));
+    }
+    buffer
+}
+
+#[test]
+fn drop_oldest_keeps_the_latest() {
+    let mut buffer = MeasurementBuffer::<_, 3>::new(OverflowPolicy::DropOldest);
+    assert_eq!(buffer.push(at(1.0)), None);
+    assert_eq!(buffer.push(at(2.0)), None);
+    assert_eq!(buffer.push(at(3.0)), None);
+    assert!(buffer.is_full());
+    assert_eq!(buffer.push(at(4.0)), Some(at(1.0)));
+    assert_eq!(buffer.push(at(5.0)), Some(at(2.0)));
+
+    assert_eq!(
+        buffer.drain().collect::<Vec<_>>(),
+        [at(3.0), at(4.0), at(5.0)]
+    );
+    assert_eq!(buffer.overflows(), 2);
+}
+
+#[test]
+fn reject_newest_keeps_the_earliest() {
+    let mut buffer = filled::<3>(OverflowPolicy::RejectNewest, &[1.0, 2.0, 3.0]);
+    assert_eq!(buffer.push(at(4.0)), Some(at(4.0)));
+
+    assert_eq!(
+        buffer.drain().collect::<Vec<_>>(),
+        [at(1.0), at(2.0), at(3.0)]
+    );
+    assert_eq!(buffer.overflows(), 1);
+}
+
+#[test]
+fn iteration_follows_the_ring_around() {
+    let mut buffer = filled::<3>(OverflowPolicy::DropOldest, &[1.0, 2.0, 3.0]);
+    assert_eq!(buffer.pop(), Some(at(1.0)));
+    assert_eq!(buffer.pop(), Some(at(2.0)));
+    buffer.push(at(4.0));
+    buffer.push(at(5.0));
+
+    assert_eq!(
+        buffer.iter().copied().collect::<Vec<_>>(),
+        [at(3.0), at(4.0), at(5.0)]
+    );
+    assert_eq!(buffer.peek(), Some(&at(3.0)));
+    assert_eq!(buffer.len(), 3);
+}
+
+#[test]
+fn undrained_measurements_stay_buffered() {
+    let mut buffer = filled::<4>(OverflowPolicy::DropOldest, &[1.0, 2.0, 3.0]);
+    assert_eq!(buffer.drain().next(), Some(at(1.0)));
+
+    assert_eq!(buffer.len(), 2);
+    assert_eq!(buffer.pop(), Some(at(2.0)));
+}
+
+#[test]
+fn clear_keeps_the_overflow_count() {
+    let mut buffer = filled::<2>(OverflowPolicy::RejectNewest, &[1.0, 2.0, 3.0]);
+    buffer.clear();
+    assert!(buffer.is_empty());
+    assert_eq!(buffer.pop(), None);
+    assert_eq!(buffer.overflows(), 1);
+
+    buffer.reset_overflows();
+    assert_eq!(buffer.overflows(), 0);
+}
+
+#[test]
+#[should_panic]
+fn zero_capacity_is_refused() {
+    let _ = MeasurementBuffer::<RangeReading, 0>::new(OverflowPolicy::DropOldest);
+}
+
+/// Completes a sample and raises the new sample ready interrupt
+fn sample(bus: &mut RegisterMap<Clearing>, mm: u8) {
+    bus.regs[0x062] = mm;
+    bus.regs[0x04F] = 0x04;
+}
+
+/// Interrupt handler: acknowledge the interrupt and buffer the new sample
+fn on_gpio1<I2C: I2c, const N: usize>(
+    sensor: &mut Device<I2C>,
+    events: &mut EventQueue<4>,
+    buffer: &mut MeasurementBuffer<RangeReading, N>,
+) {
+    sensor.handle_interrupt(events).unwrap();
+    for event in events.drain() {
+        if event == InterruptEvent::Range {
+            let (code, distance) = sensor.read_range_quick().unwrap();
+            buffer.push(RangeReading::new(code, distance));
+        }
+    }
+}
+
+/// Runs `samples` interrupts, then drains the buffer like a main loop would
+fn isr_then_main_loop<const N: usize>(
+    policy: OverflowPolicy,
+    samples: &[u8],
+) -> (Vec<RangeReading>, u32) {
+    // Idle sensor whose range interrupt clears on SYSTEM__INTERRUPT_CLEAR
+    let mut bus = RegisterMap::with(Clearing).set(0x04D, &[0x01]);
+    let mut events = EventQueue::new();
+    let mut buffer = MeasurementBuffer::<_, N>::new(policy);
+
+    for &mm in samples {
+        sample(&mut bus, mm);
+        let mut sensor = Device::new(&mut bus);
+        on_gpio1(&mut sensor, &mut events, &mut buffer);
+        let _ = sensor.release();
+        assert_eq!(bus.regs[0x04F] & 0x07, 0);
+    }
+
+    let drained = buffer.drain().collect();
+    (drained, buffer.overflows())
+}
+
+#[test]
+fn isr_pushes_main_loop_drains() {
+    let (readings, overflows) = isr_then_main_loop::<8>(OverflowPolicy::DropOldest, &[40, 42, 45]);
+
+    assert_eq!(readings, [at(40.0), at(42.0), at(45.0)]);
+    assert_eq!(overflows, 0);
+}
+
+#[test]
+fn slow_main_loop_sees_the_configured_overflow() {
+    let samples = [10, 20, 30, 40, 50, 60];
+
+    let (latest, overflows) = isr_then_main_loop::<4>(OverflowPolicy::DropOldest, &samples);
+    assert_eq!(latest, [at(30.0), at(40.0), at(50.0), at(60.0)]);
+    assert_eq!(overflows, 2);
+
+    let (earliest, overflows) = isr_then_main_loop::<4>(OverflowPolicy::RejectNewest, &samples);
+    assert_eq!(earliest, [at(10.0), at(20.0), at(30.0), at(40.0)]);
+    assert_eq!(overflows, 2);
+}
@@ -0,0 +1,141 @@
+//! Measurement statistics against known sequences
+
+use measurements::Length;
+use vl6180x::session::{AlsSession, MeasurementSession, Statistics};
+use vl6180x::{AlsErrorCode, AlsReading, Luminance, RangeErrorCode};
+
+fn mm(mm: f64) -> Length {
+    Length::from_millimeters(mm)
+}
+
+fn close(actual: f64, expected: f64) -> bool {
+    (actual - expected).abs() < 1e-9
+}
+
+fn session_of(values: &[f64]) -> Statistics {
+    let mut session = MeasurementSession::new();
+    for &value in values {
+        session.record(RangeErrorCode::NoError, mm(value));
+    }
+    session.summary().distance_mm.unwrap()
+}
+
+#[test]
+fn textbook_sequence() {
+    // Population standard deviation 2, sample standard deviation √(32/7)
+    let stats = session_of(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]);
+    assert_eq!(stats.count, 8);
+    assert_eq!((stats.min, stats.max), (2.0, 9.0));
+    assert!(close(stats.mean, 5.0));
+    assert!(close(stats.std_dev, (32.0f64 / 7.0).sqrt()));
+}
+
+#[test]
+fn constant_sequence_has_no_spread() {
+    let stats = session_of(&[100.0; 1000]);
+    assert_eq!(stats.mean, 100.0);
+    assert_eq!(stats.std_dev, 0.0);
+}
+
+#[test]
+fn single_value() {
+    let stats = session_of(&[42.0]);
+    assert_eq!(stats.count, 1);
+    assert_eq!((stats.min, stats.max, stats.mean), (42.0, 42.0, 42.0));
+    assert_eq!(stats.std_dev, 0.0);
+}
+
+#[test]
+fn long_session_stays_accurate() {
+    // A ramp 100..=200 repeated: mean 150, population variance 850
+    let values: Vec<f64> = (0..100_000).map(|i| 100.0 + (i % 101) as f64).collect();
+    let stats = session_of(&values);
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance =
+        values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64;
+    assert!(close(stats.mean, mean));
+    assert!((stats.std_dev - variance.sqrt()).abs() < 1e-9);
+}
+
+#[test]
+fn error_codes_are_counted_and_kept_out_of_the_statistics() {
+    let mut session = MeasurementSession::new();
+    session.record(RangeErrorCode::NoError, mm(50.0));
+    session.record(RangeErrorCode::MaxConvergence, mm(255.0));
+    session.record(RangeErrorCode::MaxConvergence, mm(255.0));
+    session.record(RangeErrorCode::SignalToNoiseRatio, mm(3.0));
+    session.record(RangeErrorCode::NoError, mm(52.0));
+
+    let summary = session.summary();
+    assert_eq!(summary.samples, 5);
+    let distance = summary.distance_mm.unwrap();
+    assert_eq!((distance.count, distance.mean), (2, 51.0));
+    assert_eq!(summary.errors.get(RangeErrorCode::NoError), 2);
+    assert_eq!(summary.errors.get(RangeErrorCode::MaxConvergence), 2);
+    assert_eq!(summary.errors.get(RangeErrorCode::RangingOverflow), 0);
+    let counts: Vec<_> = summary.errors.iter().collect();
+    assert_eq!(
+        counts,
+        [
+            (RangeErrorCode::NoError, 2),
+            (RangeErrorCode::MaxConvergence, 2),
+            (RangeErrorCode::SignalToNoiseRatio, 1),
+        ]
+    );
+}
+
+#[test]
+fn session_without_valid_measurements_has_no_statistics() {
+    let mut session = MeasurementSession::new();
+    assert_eq!(session.summary().samples, 0);
+    assert_eq!(session.summary().distance_mm, None);
+
+    session.record(RangeErrorCode::RangingOverflow, mm(255.0));
+    assert_eq!(session.summary().distance_mm, None);
+
+    session.reset();
+    assert_eq!(session.summary().samples, 0);
+    assert_eq!(session.summary().errors.iter().count(), 0);
+}
+
+#[test]
+fn summary_displays_every_figure() {
+    let mut session = MeasurementSession::new();
+    session.record(RangeErrorCode::NoError, mm(10.0));
+    session.record(RangeErrorCode::NoError, mm(20.0));
+    session.record(RangeErrorCode::MaxConvergence, mm(255.0));
+    assert_eq!(
+        session.summary().to_string(),
+        "3 samples, distance mm: n=2 min=10 max=20 mean=15.000 sd=7.071, \
+         codes: NoError: 2, MaxConvergence: 1"
+    );
+}
+
+#[test]
+fn als_session_counts_readings_by_kind() {
+    let mut session = AlsSession::new();
+    for lux in [10.0, 20.0, 30.0] {
+        session.record(AlsReading::Valid(Luminance::from_lux(lux)));
+    }
+    session.record(AlsReading::Saturated);
+    session.record(AlsReading::Dark);
+    session.record(AlsReading::Dark);
+    session.record(AlsReading::Failed(AlsErrorCode::Overflow));
+
+    let summary = session.summary();
+    assert_eq!(summary.samples, 7);
+    let lux = summary.lux.unwrap();
+    assert_eq!((lux.count, lux.min, lux.max), (3, 10.0, 30.0));
+    assert!(close(lux.mean, 20.0));
+    assert!(close(lux.std_dev, 10.0));
+    assert_eq!((summary.saturated, summary.dark, summary.failed), (1, 2, 1));
+    assert_eq!(
+        summary.to_string(),
+        "7 samples, lux: n=3 min=10 max=30 mean=20.000 sd=10.000, \
+         saturated: 1, dark: 2, failed: 1"
+    );
+
+    session.reset();
+    assert_eq!(session.summary().samples, 0);
+    assert_eq!(session.summary().lux, None);
+}
@@ -0,0 +1,150 @@
+//! Windowed minimum and maximum over adversarial sequences
+
+use measurements::Length;
+use vl6180x::window::MinMaxTracker;
+use vl6180x::{RangeErrorCode, RangeReading};
+
+fn at(mm: u32) -> RangeReading {
+    RangeReading::Valid(Length::from_millimeters(mm as f64))
+}
+
+fn mm(length: Length) -> u32 {
+    length.as_millimeters() as u32
+}
+
+fn extremes<const N: usize>(tracker: &MinMaxTracker<N>) -> Option<(u32, u32)> {
+    Some((mm(tracker.min()?.distance), mm(tracker.max()?.distance)))
+}
+
+/// Minimum and maximum of the valid readings among the last `n`
+fn naive(history: &[Option<u32>], n: usize) -> Option<(u32, u32)> {
+    let window = &history[history.len().saturating_sub(n)..];
+    let valid = window.iter().flatten();
+    Some((*valid.clone().min()?, *valid.max()?))
+}
+
+#[test]
+fn empty_tracker() {
+    let mut tracker = MinMaxTracker::<4>::new();
+    assert_eq!(tracker.min(), None);
+    tracker.feed(0, RangeReading::NoTarget);
+    assert_eq!(tracker.max(), None);
+}
+
+#[test]
+fn rising_ramp() {
+    let mut tracker = MinMaxTracker::<4>::new();
+    for (i, distance) in (10..30).enumerate() {
+        tracker.feed(i as u32, at(distance));
+        let low = distance.saturating_sub(3).max(10);
+        assert_eq!(extremes(&tracker), Some((low, distance)));
+        assert_eq!(tracker.max().unwrap().samples_ago, 0);
+        assert_eq!(tracker.min().unwrap().samples_ago, distance - low);
+    }
+}
+
+#[test]
+fn falling_ramp() {
+    let mut tracker = MinMaxTracker::<4>::new();
+    for (i, distance) in (10..30).rev().enumerate() {
+        tracker.feed(i as u32, at(distance));
+        let high = (distance + 3).min(29);
+        assert_eq!(extremes(&tracker), Some((distance, high)));
+        assert_eq!(tracker.min().unwrap().samples_ago, 0);
+    }
+}
+
+#[test]
+fn spike_holds_for_the_window_then_expires() {
+    let mut tracker = MinMaxTracker::<3>::new();
+    tracker.feed(0, at(100));
+    tracker.feed(1, at(5));
+    for tick in 2..4 {
+        tracker.feed(tick, at(100));
+        assert_eq!(extremes(&tracker), Some((5, 100)));
+    }
+    tracker.feed(4, at(100));
+    assert_eq!(extremes(&tracker), Some((100, 100)));
+}
+
+#[test]
+fn dropouts_keep_the_closest_object() {
+    let mut tracker = MinMaxTracker::<4>::new();
+    tracker.feed(0, at(40));
+    tracker.feed(1, RangeReading::NoTarget);
+    tracker.feed(2, RangeReading::Failed(RangeErrorCode::SignalToNoiseRatio));
+    tracker.feed(3, RangeReading::NoTarget);
+
+    let closest = tracker.min().unwrap();
+    assert_eq!(mm(closest.distance), 40);
+    assert_eq!(closest.samples_ago, 3);
+    assert_eq!(closest.tick_ms, 0);
+
+    // Dropouts still take up places in the window
+    tracker.feed(4, RangeReading::NoTarget);
+    assert_eq!(tracker.min(), None);
+}
+
+#[test]
+fn readings_expire_by_age() {
+    let mut tracker = MinMaxTracker::<8>::with_max_age(100);
+    tracker.feed(0, at(20));
+    tracker.feed(60, at(50));
+
+    let closest = tracker.min().unwrap();
+    assert_eq!(closest.age_ms(100), 100);
+    assert_eq!(extremes(&tracker), Some((20, 50)));
+
+    // Expired without a new reading
+    tracker.expire(101);
+    assert_eq!(extremes(&tracker), Some((50, 50)));
+    tracker.feed(161, RangeReading::NoTarget);
+    assert_eq!(extremes(&tracker), None);
+}
+
+#[test]
+fn tick_wraps_around() {
+    let mut tracker = MinMaxTracker::<8>::with_max_age(100);
+    tracker.feed(u32::MAX - 10, at(20));
+    tracker.feed(30, at(50));
+    assert_eq!(extremes(&tracker), Some((20, 50)));
+    assert_eq!(tracker.min().unwrap().age_ms(30), 41);
+}
+
+#[test]
+fn equal_distances_report_the_latest() {
+    let mut tracker = MinMaxTracker::<4>::new();
+    tracker.feed(0, at(30));
+    tracker.feed(1, at(30));
+    assert_eq!(tracker.min().unwrap().samples_ago, 0);
+    assert_eq!(tracker.max().unwrap().samples_ago, 0);
+}
+
+#[test]
+fn reset_empties_the_window() {
+    let mut tracker = MinMaxTracker::<4>::new();
+    tracker.feed(0, at(30));
+    tracker.reset();
+    assert_eq!(extremes(&tracker), None);
+    tracker.feed(1, at(60));
+    assert_eq!(extremes(&tracker), Some((60, 60)));
+}
+
+#[test]
+fn matches_naive_window_on_pseudo_random_sequence() {
+    const N: usize = 5;
+    let mut tracker = MinMaxTracker::<N>::new();
+    let mut history = Vec::new();
+    let mut state = 0x1234_5678u32;
+
+    for tick in 0..2000 {
+        // Linear congruential generator
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        let value = (state >> 24) % 64;
+        let sample = (!value.is_multiple_of(7)).then_some(value);
+
+        tracker.feed(tick, sample.map_or(RangeReading::NoTarget, at));
+        history.push(sample);
+        assert_eq!(extremes(&tracker), naive(&history, N), "at tick {tick}");
+    }
+}
@@ -0,0 +1,214 @@
+//! Non-blocking readers returning WouldBlock until the sample is ready
+#![cfg(feature = "nb")]
+
+mod support;
+
+use core::time::Duration;
+use embedded_hal::i2c::ErrorKind;
+use measurements::Length;
+
+use support::{block_on, Delayed, NoDelay, RegisterMap};
+use vl6180x::{AlsErrorCode, Device, Error, Luminance, RangeErrorCode};
+
+/// Sensor measuring 75mm and 100 ALS counts at gain 1 and 100ms
+fn bus(polls_to_ready: u32) -> RegisterMap<Delayed> {
+    RegisterMap::with(Delayed::new(Some(polls_to_ready)))
+        .set(0x03F, &[0x46])
+        .set(0x041, &[0x63])
+        .set(0x04D, &[0x01, 0x01])
+        .set(0x051, &[100])
+        .set(0x062, &[75])
+}
+
+#[test]
+fn range_would_block_until_the_sample_is_ready() {
+    let mut bus = bus(2);
+    let mut dev = Device::new(&mut bus);
+
+    // Start, then two polls without a sample
+    for _ in 0..3 {
+        assert_eq!(dev.try_read_range(), Err(nb::Error::WouldBlock));
+    }
+    assert_eq!(dev.try_read_range(), Ok(Length::from_millimeters(75.0)));
+    let _ = dev.release();
+
+    // Start, three polls, status, value, clear
+    assert_eq!(bus.behavior.starts, 1);
+    assert_eq!(bus.transactions(), 7);
+    assert_eq!(bus.regs[0x04F], 0x00);
+}
+
+#[test]
+fn next_call_after_a_sample_starts_a_new_measurement() {
+    let mut bus = bus(0);
+    let mut dev = Device::new(&mut bus);
+    for _ in 0..2 {
+        assert_eq!(dev.try_read_range(), Err(nb::Error::WouldBlock));
+        assert_eq!(dev.try_read_range(), Ok(Length::from_millimeters(75.0)));
+    }
+    let _ = dev.release();
+
+    assert_eq!(bus.behavior.starts, 2);
+}
+
+#[test]
+fn block_macro_waits_for_the_sample() {
+    let mut bus = bus(5);
+    let mut dev = Device::new(&mut bus);
+    assert_eq!(
+        nb::block!(dev.try_read_range()),
+        Ok(Length::from_millimeters(75.0))
+    );
+    assert_eq!(
+        nb::block!(dev.try_read_als()),
+        Ok(Luminance::from_lux(32.0 / 1.01))
+    );
+}
+
+#[test]
+fn range_error_code_ends_the_measurement() {
+    let mut bus = bus(0);
+    bus.regs[0x04D] = 0xB1;
+    let mut dev = Device::new(&mut bus);
+    assert_eq!(dev.try_read_range(), Err(nb::Error::WouldBlock));
+    assert_eq!(
+        dev.try_read_range(),
+        Err(nb::Error::Other(Error::RangeError(
+            RangeErrorCode::SignalToNoiseRatio
+        )))
+    );
+    // The next call starts over
+    assert_eq!(dev.try_read_range(), Err(nb::Error::WouldBlock));
+    let _ = dev.release();
+
+    assert_eq!(bus.behavior.starts, 2);
+    assert_eq!(bus.regs[0x04F], 0x00);
+}
+
+#[test]
+fn bus_error_while_polling_ends_the_measurement() {
+    let mut bus = bus(3);
+    let mut dev = Device::new(&mut bus);
+    assert_eq!(dev.try_read_range(), Err(nb::Error::WouldBlock));
+    let (bus, state) = dev.into_parts();
+
+    bus.fail_next(1, ErrorKind::Other);
+    let mut dev = Device::from_parts(bus, state);
+    assert!(matches!(
+        dev.try_read_range(),
+        Err(nb::Error::Other(Error::BusError(_)))
+    ));
+    // The next call starts over
+    assert_eq!(dev.try_read_range(), Err(nb::Error::WouldBlock));
+    let bus = dev.release();
+
+    assert_eq!(bus.behavior.starts, 2);
+}
+
+#[test]
+fn als_would_block_until_the_sample_is_ready() {
+    let mut bus = bus(1);
+    bus.regs[0x04E] = 0x11;
+    let mut dev = Device::new(&mut bus);
+    assert_eq!(dev.try_read_als(), Err(nb::Error::WouldBlock));
+    assert_eq!(dev.try_read_als(), Err(nb::Error::WouldBlock));
+    assert_eq!(
+        dev.try_read_als(),
+        Err(nb::Error::Other(Error::AlsError(AlsErrorCode::Overflow)))
+    );
+    let _ = dev.release();
+
+    // Start, two polls, result, clear, gain, integration period
+    assert_eq!(bus.transactions(), 7);
+    assert_eq!(bus.regs[0x04F], 0x00);
+}
+
+#[test]
+fn range_and_als_are_tracked_separately() {
+    let mut bus = bus(1);
+    let mut dev = Device::new(&mut bus);
+    assert_eq!(dev.try_read_range(), Err(nb::Error::WouldBlock));
+    assert_eq!(dev.try_read_als(), Err(nb::Error::WouldBlock));
+    assert_eq!(dev.try_read_range(), Err(nb::Error::WouldBlock));
+    assert_eq!(dev.try_read_als(), Ok(Luminance::from_lux(32.0 / 1.01)));
+    assert_eq!(dev.try_read_range(), Ok(Length::from_millimeters(75.0)));
+    let _ = dev.release();
+
+    assert_eq!(bus.behavior.starts, 2);
+}
+
+#[test]
+fn measurement_in_flight_survives_taking_the_device_apart() {
+    let mut bus = bus(0);
+    let mut dev = Device::new(&mut bus);
+    assert_eq!(dev.try_read_range(), Err(nb::Error::WouldBlock));
+    let (i2c, state) = dev.into_parts();
+    let mut dev = Device::from_parts(i2c, state);
+    assert_eq!(dev.try_read_range(), Ok(Length::from_millimeters(75.0)));
+    let _ = dev.release();
+
+    assert_eq!(bus.behavior.starts, 1);
+}
+
+#[test]
+fn range_single_nb_drives_the_same_measurement() {
+    let mut bus = bus(1);
+    let mut dev = Device::new(&mut bus);
+    assert_eq!(dev.range_single_nb(), Err(nb::Error::WouldBlock));
+    assert_eq!(dev.try_read_range(), Err(nb::Error::WouldBlock));
+    assert_eq!(dev.range_single_nb(), Ok(Length::from_millimeters(75.0)));
+    let _ = dev.release();
+
+    assert_eq!(bus.behavior.starts, 1);
+    assert_eq!(bus.regs[0x04F], 0x00);
+}
+
+#[test]
+fn single_shot_range_is_refused_while_a_sample_is_pending() {
+    let mut bus = bus(0);
+    let mut dev = Device::new(&mut bus);
+    assert_eq!(dev.range_single_nb(), Err(nb::Error::WouldBlock));
+
+    assert_eq!(
+        dev.measure_range_single(&mut NoDelay),
+        Err(Error::DeviceBusy)
+    );
+    assert_eq!(
+        dev.measure_range_single_within(&mut NoDelay, Duration::from_millis(10)),
+        Err(Error::DeviceBusy)
+    );
+    assert_eq!(
+        block_on(dev.measure_range_single_async(&mut NoDelay)),
+        Err(Error::DeviceBusy)
+    );
+    assert_eq!(
+        block_on(dev.measure_range_single_within_async(&mut NoDelay, Duration::from_millis(10))),
+        Err(Error::DeviceBusy)
+    );
+    // Collecting the sample frees the sensor again
+    assert_eq!(dev.range_single_nb(), Ok(Length::from_millimeters(75.0)));
+    assert_eq!(
+        dev.measure_range_single(&mut NoDelay),
+        Ok(Length::from_millimeters(75.0))
+    );
+    let _ = dev.release();
+
+    assert_eq!(bus.behavior.starts, 2);
+}
+
+#[test]
+fn single_shot_als_is_refused_while_a_sample_is_pending() {
+    let mut bus = bus(0);
+    let mut dev = Device::new(&mut bus);
+    assert_eq!(dev.try_read_als(), Err(nb::Error::WouldBlock));
+
+    assert_eq!(dev.measure_als_single(&mut NoDelay), Err(Error::DeviceBusy));
+    assert_eq!(
+        block_on(dev.measure_als_single_async(&mut NoDelay)),
+        Err(Error::DeviceBusy)
+    );
+    assert_eq!(dev.try_read_als(), Ok(Luminance::from_lux(32.0 / 1.01)));
+    let _ = dev.release();
+
+    assert_eq!(bus.behavior.starts, 1);
+}
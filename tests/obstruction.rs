@@ -0,0 +1,152 @@
+//! Sensor window obstruction from combined range and ALS readings
+
+mod support;
+
+use measurements::Length;
+use support::{block_on, NoDelay, SimulatedVl6180x};
+use vl6180x::obstruction::{ObstructionConfig, WindowState};
+use vl6180x::{AlsReading, Device, Luminance, RangeErrorCode, RangeReading};
+
+fn at(mm: f64) -> RangeReading {
+    RangeReading::Valid(Length::from_millimeters(mm))
+}
+
+fn lux(lux: f32) -> AlsReading {
+    AlsReading::Valid(Luminance::from_lux(lux))
+}
+
+/// Return rate well below the reference rate, as from a real target
+const WEAK: (u16, u16) = (200, 1000);
+/// Return rate above the reference rate, as from the window itself
+const STRONG: (u16, u16) = (1500, 1000);
+
+fn classify(range: RangeReading, als: AlsReading, (ret, reference): (u16, u16)) -> WindowState {
+    ObstructionConfig::default().classify(range, als, ret, reference)
+}
+
+#[test]
+fn target_in_front_of_a_lit_window_is_clear() {
+    assert_eq!(classify(at(80.0), lux(200.0), WEAK), WindowState::Clear);
+    assert_eq!(
+        classify(RangeReading::NoTarget, lux(200.0), WEAK),
+        WindowState::Clear
+    );
+    assert_eq!(
+        classify(at(80.0), AlsReading::Saturated, WEAK),
+        WindowState::Clear
+    );
+}
+
+#[test]
+fn dark_room_alone_is_clear() {
+    assert_eq!(
+        classify(RangeReading::NoTarget, AlsReading::Dark, WEAK),
+        WindowState::Clear
+    );
+    assert_eq!(classify(at(60.0), lux(1.0), WEAK), WindowState::Clear);
+}
+
+#[test]
+fn finger_on_the_window_is_covered() {
+    assert_eq!(
+        classify(at(2.0), AlsReading::Dark, STRONG),
+        WindowState::Covered
+    );
+    assert_eq!(classify(at(0.0), lux(0.5), WEAK), WindowState::Covered);
+    let underflow = RangeReading::Failed(RangeErrorCode::RangingUnderflow);
+    assert_eq!(
+        classify(underflow, AlsReading::Dark, WEAK),
+        WindowState::Covered
+    );
+}
+
+#[test]
+fn crosstalk_dominated_no_target_counts_as_at_the_window() {
+    assert_eq!(
+        classify(RangeReading::NoTarget, AlsReading::Dark, STRONG),
+        WindowState::Covered
+    );
+    assert_eq!(
+        classify(RangeReading::NoTarget, lux(150.0), STRONG),
+        WindowState::PartiallyObstructed
+    );
+}
+
+#[test]
+fn something_at_a_lit_window_is_partially_obstructed() {
+    assert_eq!(
+        classify(at(5.0), lux(40.0), WEAK),
+        WindowState::PartiallyObstructed
+    );
+    let underflow = RangeReading::Failed(RangeErrorCode::RawRangingUnderflow);
+    assert_eq!(
+        classify(underflow, AlsReading::Saturated, WEAK),
+        WindowState::PartiallyObstructed
+    );
+}
+
+#[test]
+fn other_range_failures_do_not_count_as_at_the_window() {
+    let failed = RangeReading::Failed(RangeErrorCode::SignalToNoiseRatio);
+    assert_eq!(classify(failed, AlsReading::Dark, WEAK), WindowState::Clear);
+}
+
+#[test]
+fn criteria_are_tunable() {
+    let config = ObstructionConfig {
+        contact: Length::from_millimeters(20.0),
+        dark: Luminance::from_lux(50.0),
+        return_ratio: 2.0,
+    };
+    assert_eq!(
+        config.classify(at(15.0), lux(30.0), 200, 1000),
+        WindowState::Covered
+    );
+    assert_eq!(
+        config.classify(RangeReading::NoTarget, lux(30.0), 1500, 1000),
+        WindowState::Clear
+    );
+    assert_eq!(
+        config.classify(RangeReading::NoTarget, lux(30.0), 2000, 1000),
+        WindowState::Covered
+    );
+}
+
+/// Sensor at gain 1 and 100ms integration reporting the given counts,
+/// distance and signal rates
+fn sensor(counts: u16, mm: u8, (ret, reference): (u16, u16)) -> SimulatedVl6180x {
+    let mut sensor = SimulatedVl6180x::new();
+    sensor.set_als_counts(counts);
+    sensor.set_distance(mm);
+    sensor.set_reference_rate(reference);
+    sensor.set_registers(0x03F, &[0x46]);
+    sensor.set_registers(0x041, &[0x63]);
+    sensor.set_registers(0x064, &[mm]);
+    sensor.set_registers(0x066, &ret.to_be_bytes());
+    sensor
+}
+
+#[test]
+fn device_classifies_its_own_measurements() {
+    let config = ObstructionConfig::default();
+    let scenes = [
+        (sensor(500, 80, WEAK), WindowState::Clear),
+        (sensor(3, 2, STRONG), WindowState::Covered),
+        (sensor(500, 4, WEAK), WindowState::PartiallyObstructed),
+    ];
+    for (mut bus, expected) in scenes {
+        let mut dev = Device::new(&mut bus);
+        assert_eq!(dev.detect_obstruction(&config, &mut NoDelay), Ok(expected));
+    }
+}
+
+#[test]
+fn async_matches_blocking() {
+    let config = ObstructionConfig::default();
+    let mut bus = sensor(3, 2, STRONG);
+    let mut dev = Device::new(&mut bus);
+    assert_eq!(
+        block_on(dev.detect_obstruction_async(&config, &mut NoDelay)),
+        Ok(WindowState::Covered)
+    );
+}
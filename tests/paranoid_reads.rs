@@ -0,0 +1,135 @@
+//! Paranoid result reads catching corrupted bytes
+
+mod support;
+
+use measurements::Length;
+use support::{block_on, Log, SimulatedVl6180x};
+use vl6180x::registers::{AlsResultValue, FreshOutOfReset, RangeResultValue, RangeStatusBlock};
+use vl6180x::{Device, Error, InconsistentRead};
+
+/// Fresh sensor with a range of 42mm and an ALS count of 0x0180, the reads
+/// starting at `address` corrupted in turn by XORing `glitches` into them
+fn sensor(address: u16, glitches: &[&[u8]]) -> SimulatedVl6180x {
+    let mut sensor = SimulatedVl6180x::powered_on();
+    sensor.set_registers(0x050, &[0x01, 0x80]);
+    sensor.set_registers(0x062, &[42]);
+    for glitch in glitches {
+        sensor.corrupt_next_read_bytes(address, glitch);
+    }
+    sensor
+}
+
+fn read_range(bus: &mut SimulatedVl6180x) -> Result<Length, Error> {
+    let mut dev = Device::new(bus);
+    dev.set_paranoid(true);
+    let value = dev.read_register::<RangeResultValue>();
+    let _ = dev.release();
+    value.map(|value| value.distance)
+}
+
+#[test]
+fn off_by_default_reads_once() {
+    let mut bus = sensor(0x062, &[&[0x40]]);
+    let mut dev = Device::new(&mut bus);
+    assert!(!dev.is_paranoid());
+    let value: RangeResultValue = dev.read_register().unwrap();
+    let _ = dev.release();
+
+    assert_eq!(value.distance, Length::from_millimeters(106.0));
+    assert_eq!(bus.registers_read(), [0x062]);
+}
+
+#[test]
+fn matching_reads_cost_one_extra_read() {
+    let mut bus = sensor(0x062, &[]);
+    assert_eq!(read_range(&mut bus), Ok(Length::from_millimeters(42.0)));
+    assert_eq!(bus.registers_read(), [0x062, 0x062]);
+}
+
+#[test]
+fn tie_break_outvotes_a_corrupted_first_read() {
+    let mut bus = sensor(0x062, &[&[0x40]]);
+    assert_eq!(read_range(&mut bus), Ok(Length::from_millimeters(42.0)));
+    assert_eq!(bus.registers_read(), [0x062, 0x062, 0x062]);
+}
+
+#[test]
+fn tie_break_outvotes_a_corrupted_second_read() {
+    let mut bus = sensor(0x062, &[&[0x00], &[0x08]]);
+    assert_eq!(read_range(&mut bus), Ok(Length::from_millimeters(42.0)));
+    assert_eq!(bus.registers_read(), [0x062, 0x062, 0x062]);
+}
+
+#[test]
+fn three_different_reads_are_reported_with_both_values() {
+    let mut bus = sensor(0x050, &[&[0x40], &[0x00, 0x01], &[0x02]]);
+    let mut dev = Device::new(&mut bus);
+    dev.set_paranoid(true);
+    let value = dev.read_register::<AlsResultValue>();
+    let _ = dev.release();
+
+    assert_eq!(
+        value.unwrap_err(),
+        Error::InconsistentRead(InconsistentRead {
+            address: 0x050,
+            offset: 0,
+            first: 0x4180,
+            second: 0x0181,
+        })
+    );
+}
+
+#[test]
+fn long_blocks_report_the_first_differing_bytes() {
+    let mut glitch = [0; 22];
+    glitch[0x15] = 0x01;
+    let mut bus = sensor(0x04D, &[&[0x00], &glitch, &[0x02]]);
+    let mut dev = Device::new(&mut bus);
+    dev.set_paranoid(true);
+    let block = dev.read_register::<RangeStatusBlock>();
+    let _ = dev.release();
+
+    assert_eq!(
+        block.unwrap_err(),
+        Error::InconsistentRead(InconsistentRead {
+            address: 0x04D,
+            offset: 0x15,
+            first: 42,
+            second: 43,
+        })
+    );
+}
+
+#[test]
+fn configuration_reads_are_not_repeated() {
+    let mut bus = sensor(0x016, &[&[0x01]]);
+    let mut dev = Device::new(&mut bus);
+    dev.set_paranoid(true);
+    let fresh: FreshOutOfReset = dev.read_register().unwrap();
+    let _ = dev.release();
+
+    assert!(!fresh.fresh);
+    assert_eq!(bus.registers_read(), [0x016]);
+}
+
+#[test]
+fn async_reads_are_verified_alike() {
+    let mut bus = sensor(0x062, &[&[0x40], &[0x00], &[0x00]]);
+    let mut dev = Device::new(&mut bus);
+    dev.set_paranoid(true);
+    let value = block_on(dev.read_register_async::<RangeResultValue>());
+    let _ = dev.release();
+
+    assert_eq!(value.unwrap().distance, Length::from_millimeters(42.0));
+    assert_eq!(bus.registers_read(), [0x062, 0x062, 0x062]);
+}
+
+#[test]
+fn setting_survives_taking_the_device_apart() {
+    let mut bus = sensor(0x062, &[]);
+    let mut dev = Device::new(&mut bus);
+    dev.set_paranoid(true);
+    let (i2c, state) = dev.into_parts();
+    let dev = Device::from_parts(i2c, state);
+    assert!(dev.is_paranoid());
+}
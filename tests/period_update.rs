@@ -0,0 +1,212 @@
+//! Intermeasurement period updates in place and by restarting
+
+mod support;
+
+use core::time::Duration;
+
+use embedded_hal::i2c::ErrorKind;
+use support::{store, Behavior, Log, NoDelay, RegisterMap, Registers};
+use vl6180x::{Device, Direction, Error, ErrorContext, PeriodUpdate};
+
+/// Start registers toggling continuous measurements, the device ready bits
+/// following the running state
+struct Toggling;
+
+impl Behavior for Toggling {
+    fn write(&mut self, regs: &mut Registers, register: u16, data: &[u8]) -> Result<(), ErrorKind> {
+        store(regs, register, data);
+        match (register, data[0]) {
+            (0x018, 0x03) => regs[0x04D] ^= 0x01,
+            (0x038, 0x03) => regs[0x04E] ^= 0x01,
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+type Bus = RegisterMap<Toggling>;
+
+/// Idle sensor with a 30ms convergence limit and 100ms ALS integration
+fn sensor() -> Bus {
+    RegisterMap::with(Toggling)
+        .set(0x01C, &[30])
+        .set(0x040, &[0x00, 0x63])
+        .set(0x04D, &[0x01, 0x01])
+}
+
+/// Device starting the given continuous measurements, ALS every second
+fn running(bus: &mut Bus, range: bool, als: bool) -> Device<&mut Bus> {
+    let mut dev = Device::new(bus);
+    if range {
+        dev.start_continuous_range().unwrap();
+    }
+    if als {
+        dev.start_continuous_als(ms(1000)).unwrap();
+    }
+    dev
+}
+
+fn range_running(bus: &Bus) -> bool {
+    bus.regs[0x04D] & 0x01 == 0
+}
+
+fn als_running(bus: &Bus) -> bool {
+    bus.regs[0x04E] & 0x01 == 0
+}
+
+fn ms(value: u64) -> Duration {
+    Duration::from_millis(value)
+}
+
+#[test]
+fn idle_range_period_is_written_in_place_under_hold() {
+    let mut bus = sensor();
+    let update = Device::new(&mut bus).update_range_period(&mut NoDelay, ms(100));
+
+    assert_eq!(update, Ok(PeriodUpdate::InPlace));
+    assert_eq!(
+        bus.byte_writes(),
+        [(0x017, 0x01), (0x01B, 0x09), (0x017, 0x00)]
+    );
+    assert!(!range_running(&bus));
+}
+
+#[test]
+fn running_range_is_stopped_reconfigured_and_restarted() {
+    let mut bus = sensor();
+    let update = running(&mut bus, true, false).update_range_period(&mut NoDelay, ms(200));
+
+    assert_eq!(update, Ok(PeriodUpdate::Restarted));
+    assert_eq!(
+        bus.byte_writes(),
+        [(0x018, 0x03), (0x018, 0x03), (0x01B, 0x13), (0x018, 0x03)]
+    );
+    assert!(range_running(&bus));
+}
+
+#[cfg(feature = "nb")]
+#[test]
+fn single_shot_is_not_mistaken_for_continuous_ranging() {
+    // Both cores busy with single-shot measurements
+    let mut bus = sensor().set(0x04D, &[0x00, 0x00]);
+    let mut dev = Device::new(&mut bus);
+    assert_eq!(dev.try_read_range(), Err(nb::Error::WouldBlock));
+    assert_eq!(dev.try_read_als(), Err(nb::Error::WouldBlock));
+
+    assert_eq!(
+        dev.update_range_period(&mut NoDelay, ms(100)),
+        Ok(PeriodUpdate::InPlace)
+    );
+    assert_eq!(
+        dev.update_als_period(&mut NoDelay, ms(500)),
+        Ok(PeriodUpdate::InPlace)
+    );
+    assert!(!dev.is_continuous_range_running());
+    assert!(!dev.is_continuous_als_running());
+    let _ = dev.release();
+    assert_eq!(
+        bus.byte_writes(),
+        [
+            (0x018, 0x01),
+            (0x038, 0x01),
+            (0x017, 0x01),
+            (0x01B, 0x09),
+            (0x017, 0x00),
+            (0x017, 0x01),
+            (0x03E, 0x31),
+            (0x017, 0x00)
+        ]
+    );
+}
+
+#[test]
+fn interleaved_mode_is_refused() {
+    let mut bus = sensor();
+    let mut dev = Device::new(&mut bus);
+    dev.start_interleaved(ms(2000)).unwrap();
+
+    assert_eq!(
+        dev.update_range_period(&mut NoDelay, ms(100)),
+        Err(Error::DeviceBusy)
+    );
+    assert_eq!(
+        dev.update_als_period(&mut NoDelay, ms(500)),
+        Err(Error::DeviceBusy)
+    );
+    assert!(dev.is_interleaved_running());
+}
+
+#[test]
+fn running_als_does_not_restart_ranging() {
+    let mut bus = sensor();
+    let update = running(&mut bus, false, true).update_range_period(&mut NoDelay, ms(100));
+
+    assert_eq!(update, Ok(PeriodUpdate::InPlace));
+    assert!(als_running(&bus));
+}
+
+#[test]
+fn range_period_shorter_than_a_measurement_is_rejected() {
+    // 30ms convergence limit plus pre-calibration and readout averaging
+    let mut bus = sensor();
+    let update = running(&mut bus, true, false).update_range_period(&mut NoDelay, ms(30));
+
+    assert!(matches!(update, Err(Error::PeriodTooShort(_))));
+    assert_eq!(bus.byte_writes(), [(0x018, 0x03)]);
+    assert!(range_running(&bus));
+}
+
+#[test]
+fn unencodable_range_period_is_rejected_before_stopping() {
+    let mut bus = sensor();
+    let update = running(&mut bus, true, false).update_range_period(&mut NoDelay, ms(3000));
+
+    assert_eq!(
+        update,
+        Err(Error::SerializationError(ErrorContext::register(
+            0x01B,
+            Direction::Write
+        )))
+    );
+    assert_eq!(bus.byte_writes(), [(0x018, 0x03)]);
+}
+
+#[test]
+fn idle_als_period_is_written_in_place_under_hold() {
+    let mut bus = sensor();
+    let update = Device::new(&mut bus).update_als_period(&mut NoDelay, ms(500));
+
+    assert_eq!(update, Ok(PeriodUpdate::InPlace));
+    assert_eq!(
+        bus.byte_writes(),
+        [(0x017, 0x01), (0x03E, 0x31), (0x017, 0x00)]
+    );
+}
+
+#[test]
+fn running_als_is_stopped_reconfigured_and_restarted() {
+    let mut bus = sensor();
+    let update = running(&mut bus, false, true).update_als_period(&mut NoDelay, ms(500));
+
+    assert_eq!(update, Ok(PeriodUpdate::Restarted));
+    assert_eq!(
+        bus.byte_writes(),
+        [
+            (0x03E, 0x63),
+            (0x038, 0x03),
+            (0x038, 0x03),
+            (0x03E, 0x31),
+            (0x038, 0x03)
+        ]
+    );
+    assert!(als_running(&bus));
+}
+
+#[test]
+fn als_period_shorter_than_integration_is_rejected() {
+    let mut bus = sensor();
+    let update = Device::new(&mut bus).update_als_period(&mut NoDelay, ms(50));
+
+    assert!(matches!(update, Err(Error::PeriodTooShort(_))));
+    assert!(bus.writes().is_empty());
+}
@@ -0,0 +1,162 @@
+//! Single-shot measurements stepped one transaction at a time
+
+use core::cell::RefCell;
+use core::time::Duration;
+
+mod support;
+
+use embedded_hal::i2c::ErrorKind;
+use embedded_hal_bus::i2c::RefCellDevice;
+use measurements::Length;
+use support::{Delayed, RegisterMap};
+use vl6180x::poller::{AlsPoller, RangePoller, Step};
+use vl6180x::{AlsErrorCode, AlsGain, AlsReading, Device, Error, RangeErrorCode, RangeReading};
+
+type Sensor = RegisterMap<Delayed>;
+
+/// Sensor measuring 75mm and 200 ALS counts at gain 1 and 100ms, its samples
+/// ready `polls_to_ready` status polls after their start
+fn sensor(polls_to_ready: Option<u32>) -> RefCell<Sensor> {
+    RefCell::new(
+        RegisterMap::with(Delayed::new(polls_to_ready))
+            .set(0x03F, &[0x46])
+            .set(0x041, &[0x63])
+            .set(0x04D, &[0x01, 0x01])
+            .set(0x050, &200u16.to_be_bytes())
+            .set(0x062, &[75]),
+    )
+}
+
+/// Steps until the poller leaves `Pending`, checking every step costs
+/// exactly one transaction, and returns the outcome and the steps taken
+fn run<T: PartialEq>(
+    sensor: &RefCell<Sensor>,
+    mut step: impl FnMut() -> Step<T>,
+) -> (Step<T>, u32) {
+    for steps in 1..100 {
+        let before = sensor.borrow().transactions();
+        let outcome = step();
+        assert_eq!(sensor.borrow().transactions() - before, 1, "step {steps}");
+        if outcome != Step::Pending {
+            return (outcome, steps);
+        }
+    }
+    panic!("poller never finished");
+}
+
+#[test]
+fn range_measurement_walks_the_sequence() {
+    let sensor = sensor(Some(2));
+    let mut dev = Device::new(RefCellDevice::new(&sensor));
+    let mut poller = RangePoller::new(10);
+    assert!(poller.is_idle());
+
+    // Start, three status polls, status, value, clear
+    let (step, steps) = run(&sensor, || poller.step(&mut dev));
+    assert_eq!(
+        step,
+        Step::Ready(RangeReading::Valid(Length::from_millimeters(75.0)))
+    );
+    assert_eq!(steps, 7);
+    assert!(poller.is_idle());
+    assert_eq!(sensor.borrow().regs[0x04F], 0x00);
+
+    // The next step starts another measurement
+    let (step, _) = run(&sensor, || poller.step(&mut dev));
+    assert!(matches!(step, Step::Ready(RangeReading::Valid(_))));
+}
+
+#[test]
+fn range_error_codes_are_classified() {
+    let sensor = sensor(Some(0));
+    sensor.borrow_mut().regs[0x04D] = 0xB1;
+    let mut dev = Device::new(RefCellDevice::new(&sensor));
+    let mut poller = RangePoller::new(5);
+
+    let (step, _) = run(&sensor, || poller.step(&mut dev));
+    assert_eq!(
+        step,
+        Step::Ready(RangeReading::Failed(RangeErrorCode::SignalToNoiseRatio))
+    );
+}
+
+#[test]
+fn range_times_out_after_its_poll_budget() {
+    let sensor = sensor(None);
+    let mut dev = Device::new(RefCellDevice::new(&sensor));
+    let mut poller = RangePoller::new(4);
+
+    // Start and four status polls
+    let (step, steps) = run(&sensor, || poller.step(&mut dev));
+    assert_eq!(step, Step::Failed(Error::Timeout));
+    assert_eq!(steps, 5);
+    assert!(poller.is_idle());
+}
+
+#[test]
+fn bus_error_abandons_the_measurement() {
+    let sensor = sensor(Some(0));
+    let mut dev = Device::new(RefCellDevice::new(&sensor));
+    let mut poller = RangePoller::new(5);
+
+    assert_eq!(poller.step(&mut dev), Step::Pending);
+    sensor.borrow_mut().fail_next(1, ErrorKind::Other);
+    assert!(matches!(
+        poller.step(&mut dev),
+        Step::Failed(Error::BusError(_))
+    ));
+    assert!(poller.is_idle());
+
+    // Start, one status poll, status, value, clear
+    let (step, steps) = run(&sensor, || poller.step(&mut dev));
+    assert!(matches!(step, Step::Ready(RangeReading::Valid(_))));
+    assert_eq!(steps, 5);
+}
+
+#[test]
+fn reset_starts_over() {
+    let sensor = sensor(Some(3));
+    let mut dev = Device::new(RefCellDevice::new(&sensor));
+    let mut poller = RangePoller::new(10);
+    poller.step(&mut dev);
+    poller.step(&mut dev);
+    assert!(!poller.is_idle());
+
+    poller.reset();
+    assert!(poller.is_idle());
+    assert_eq!(poller.step(&mut dev), Step::Pending);
+    assert_eq!(sensor.borrow().transactions(), 3);
+    assert_eq!(sensor.borrow().behavior.range_polls, Some(3));
+}
+
+#[test]
+fn als_measurement_walks_the_sequence() {
+    let sensor = sensor(Some(1));
+    let mut dev = Device::new(RefCellDevice::new(&sensor));
+    let mut poller = AlsPoller::new(10);
+
+    // Start, two status polls, result, gain, integration period, clear
+    let (step, steps) = run(&sensor, || poller.step(&mut dev));
+    assert_eq!(
+        step,
+        Step::Ready(AlsReading::new(
+            AlsErrorCode::NoError,
+            200,
+            AlsGain::Gain1,
+            Duration::from_millis(100)
+        ))
+    );
+    assert_eq!(steps, 7);
+    assert_eq!(sensor.borrow().regs[0x04F], 0x00);
+}
+
+#[test]
+fn als_times_out_after_its_poll_budget() {
+    let sensor = sensor(None);
+    let mut dev = Device::new(RefCellDevice::new(&sensor));
+    let mut poller = AlsPoller::new(1);
+
+    let (step, steps) = run(&sensor, || poller.step(&mut dev));
+    assert_eq!(step, Step::Failed(Error::Timeout));
+    assert_eq!(steps, 2);
+}
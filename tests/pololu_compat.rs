@@ -0,0 +1,186 @@
+//! The Pololu compatibility layer keeps the Arduino library's timeout bookkeeping
+#![cfg(feature = "pololu-compat")]
+
+mod support;
+
+use embedded_hal::delay::DelayNs;
+use measurements::Length;
+use support::{Log, RegisterMap};
+use vl6180x::pololu_compat::{Vl6180x, TIMEOUT_SENTINEL};
+use vl6180x::{Error, Luminance};
+
+/// Delay that only accounts for the time it was asked to wait
+#[derive(Default)]
+struct Clock {
+    elapsed_ns: u64,
+}
+
+impl DelayNs for Clock {
+    fn delay_ns(&mut self, ns: u32) {
+        self.elapsed_ns += ns as u64;
+    }
+}
+
+fn sensor(bus: &mut RegisterMap) -> Vl6180x<&mut RegisterMap, Clock> {
+    Vl6180x::new(bus, Clock::default())
+}
+
+fn bus() -> RegisterMap {
+    RegisterMap::new()
+}
+
+/// A bus with a completed range sample of 87mm pending
+fn ranging_bus() -> RegisterMap {
+    let mut bus = bus();
+    bus.regs[0x04D] = 0x01;
+    bus.regs[0x04F] = 0x04;
+    bus.regs[0x062] = 87;
+    bus
+}
+
+#[test]
+fn init_clears_fresh_out_of_reset() {
+    let mut bus = bus();
+    bus.regs[0x016] = 0x01;
+    sensor(&mut bus).init().unwrap();
+    assert_eq!(bus.regs[0x016], 0x00);
+}
+
+#[test]
+fn configure_default_matches_arduino_library() {
+    let mut bus = bus();
+    bus.regs[0x2A3] = 0x01;
+    sensor(&mut bus).configure_default().unwrap();
+
+    assert_eq!(bus.regs[0x10A], 0x30);
+    assert_eq!(bus.regs[0x03F], 0x46);
+    assert_eq!(bus.regs[0x031], 0xFF);
+    assert_eq!(bus.regs[0x040..0x042], [0x00, 0x63]);
+    assert_eq!(bus.regs[0x02E], 0x01);
+    assert_eq!(bus.regs[0x01B], 0x09);
+    assert_eq!(bus.regs[0x03E], 0x31);
+    assert_eq!(bus.regs[0x014], 0x24);
+    assert_eq!(bus.regs[0x01C], 0x31);
+    assert_eq!(bus.regs[0x2A3], 0x00);
+}
+
+#[test]
+fn timeout_defaults_to_disabled() {
+    let mut bus = bus();
+    let mut sensor = sensor(&mut bus);
+    assert_eq!(sensor.timeout(), 0);
+    sensor.set_timeout(500);
+    assert_eq!(sensor.timeout(), 500);
+}
+
+#[test]
+fn range_read_succeeds_without_timeout() {
+    let mut bus = ranging_bus();
+    let mut sensor = sensor(&mut bus);
+    sensor.set_timeout(100);
+
+    assert_eq!(
+        sensor.read_range_single_millimeters(),
+        Ok(Length::from_millimeters(87.0))
+    );
+    assert_eq!(sensor.read_range_single_millimeters_sentinel(), 87);
+    assert!(!sensor.timeout_occurred());
+}
+
+#[test]
+fn range_timeout_sets_flag_until_read() {
+    let mut bus = bus();
+    let mut sensor = sensor(&mut bus);
+    sensor.set_timeout(20);
+
+    assert_eq!(sensor.read_range_single_millimeters(), Err(Error::Timeout));
+    assert!(sensor.timeout_occurred());
+    assert!(!sensor.timeout_occurred());
+}
+
+#[test]
+fn range_sentinel_on_timeout() {
+    let mut bus = bus();
+    let mut sensor = sensor(&mut bus);
+    sensor.set_timeout(20);
+
+    assert_eq!(
+        sensor.read_range_single_millimeters_sentinel(),
+        TIMEOUT_SENTINEL
+    );
+    assert!(sensor.timeout_occurred());
+}
+
+#[test]
+fn timeout_bounds_the_wait() {
+    let mut bus = bus();
+    let mut sensor = sensor(&mut bus);
+    sensor.set_timeout(20);
+    sensor.read_range_single_millimeters_sentinel();
+
+    let (_, clock) = sensor.release();
+    assert!(clock.elapsed_ns <= 20_000_000, "{}", clock.elapsed_ns);
+    assert_eq!(bus.polls(0x04F), 20);
+}
+
+#[test]
+fn longer_timeout_polls_longer() {
+    let mut bus = bus();
+    let mut sensor = sensor(&mut bus);
+    sensor.set_timeout(250);
+    sensor.read_range_single_millimeters_sentinel();
+    let _ = sensor.release();
+
+    // Longer than the fixed budget of the native helpers
+    assert_eq!(bus.polls(0x04F), 250);
+}
+
+#[test]
+fn success_does_not_clear_pending_timeout() {
+    let mut bus = bus();
+    let mut sensor = sensor(&mut bus);
+    sensor.set_timeout(5);
+    sensor.read_range_single_millimeters_sentinel();
+
+    // The flag is held across reads until queried, as in the Arduino library
+    sensor.device().write_block(0x4F, &[0x04]).unwrap();
+    sensor.device().write_block(0x62, &[87]).unwrap();
+    assert_eq!(sensor.read_range_single_millimeters_sentinel(), 87);
+    assert!(sensor.timeout_occurred());
+}
+
+#[test]
+fn range_sentinel_ignores_range_status() {
+    let mut bus = ranging_bus();
+    bus.regs[0x04D] = 0x61;
+    bus.regs[0x062] = 255;
+    let mut sensor = sensor(&mut bus);
+
+    assert_eq!(sensor.read_range_single_millimeters_sentinel(), 255);
+    assert!(matches!(
+        sensor.read_range_single_millimeters(),
+        Err(Error::RangeError(_))
+    ));
+    assert!(!sensor.timeout_occurred());
+}
+
+#[test]
+fn ambient_read_and_timeout() {
+    let mut bus = bus();
+    bus.regs[0x03F] = 0x46;
+    bus.regs[0x040..0x042].copy_from_slice(&[0x00, 0x63]);
+    bus.regs[0x050..0x052].copy_from_slice(&1000u16.to_be_bytes());
+    let mut sensor = sensor(&mut bus);
+    sensor.set_timeout(10);
+
+    assert_eq!(sensor.read_ambient_single_sentinel(), 0);
+    assert!(sensor.timeout_occurred());
+
+    sensor.device().write_block(0x4F, &[0x20]).unwrap();
+    assert_eq!(
+        sensor.read_ambient_single(),
+        Ok(Luminance { lux: 320.0 / 1.01 })
+    );
+    assert_eq!(sensor.read_ambient_single_sentinel(), 1000);
+    assert!(!sensor.timeout_occurred());
+}
@@ -0,0 +1,262 @@
+//! Switching measurement profiles writes only what changes
+
+mod support;
+
+use core::time::Duration;
+
+use embedded_hal::i2c::ErrorKind;
+use support::{block_on, load, store, Behavior, Log, NoDelay, RegisterMap, Registers};
+use vl6180x::device::Profile;
+use vl6180x::registers::RangeIntermeasurementPeriod;
+use vl6180x::{Device, Error, ErrorContext};
+
+/// Ranging and ALS cores starting and stopping with their start registers
+#[derive(Default)]
+struct Cores {
+    ranging: bool,
+    als: bool,
+}
+
+impl Behavior for Cores {
+    fn read(
+        &mut self,
+        regs: &mut Registers,
+        register: u16,
+        buf: &mut [u8],
+    ) -> Result<(), ErrorKind> {
+        regs[0x04D] = u8::from(!self.ranging);
+        regs[0x04E] = u8::from(!self.als);
+        load(regs, register, buf);
+        Ok(())
+    }
+
+    fn write(&mut self, regs: &mut Registers, register: u16, data: &[u8]) -> Result<(), ErrorKind> {
+        store(regs, register, data);
+        match (register, data[0]) {
+            (0x018, 0x03) => self.ranging = !self.ranging,
+            (0x038, 0x03) => self.als = !self.als,
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+fn bus() -> RegisterMap<Cores> {
+    RegisterMap::with(Cores::default())
+}
+
+const HOLD: u16 = 0x017;
+
+/// Fast tracking at a slower rate, otherwise identical
+const RELAXED_TRACKING: Profile = Profile {
+    name: "relaxed_tracking",
+    range_period: RangeIntermeasurementPeriod {
+        period: Duration::from_millis(50),
+    },
+    ..Profile::FAST_TRACKING
+};
+
+static PROFILES: [Profile; 4] = [
+    Profile::LOW_POWER,
+    Profile::FAST_TRACKING,
+    RELAXED_TRACKING,
+    Profile {
+        name: "renamed",
+        ..Profile::FAST_TRACKING
+    },
+];
+
+const LOW_POWER: usize = 0;
+const FAST_TRACKING: usize = 1;
+const RELAXED: usize = 2;
+const RENAMED: usize = 3;
+
+#[test]
+fn first_switch_writes_everything_under_hold() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.load_profiles(&PROFILES);
+    dev.switch_profile(&mut NoDelay, LOW_POWER).unwrap();
+    assert_eq!(dev.active_profile(), Some(LOW_POWER));
+    let _ = dev.release();
+
+    // Adjacent registers share a transaction
+    assert_eq!(
+        bus.registers_written(),
+        [HOLD, 0x01B, 0x10A, 0x03E, 0x014, HOLD]
+    );
+    assert_eq!(bus.writes()[0].1, [0x01]);
+    assert_eq!(bus.writes()[1].1, [99, 30]);
+    assert_eq!(bus.writes()[3].1, [199, 0x46, 0x00, 0x31]);
+    assert_eq!(bus.writes()[5].1, [0x00]);
+    // 1s range period, 30ms convergence, 16 samples, 2s ALS period
+    assert_eq!(bus.regs[0x01B], 99);
+    assert_eq!(bus.regs[0x01C], 30);
+    assert_eq!(bus.regs[0x10A], 16);
+    assert_eq!(bus.regs[0x03E], 199);
+}
+
+#[test]
+fn similar_profiles_differ_by_one_write() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.load_profiles(&PROFILES);
+    dev.switch_profile(&mut NoDelay, FAST_TRACKING).unwrap();
+    dev.switch_profile(&mut NoDelay, RELAXED).unwrap();
+    assert_eq!(dev.active_profile(), Some(RELAXED));
+    let _ = dev.release();
+
+    assert_eq!(bus.registers_written()[6..], [HOLD, 0x01B, HOLD]);
+    assert_eq!(bus.regs[0x01B], 4);
+}
+
+#[test]
+fn running_measurements_are_restarted() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.load_profiles(&PROFILES);
+    dev.switch_profile(&mut NoDelay, FAST_TRACKING).unwrap();
+    dev.start_continuous_range().unwrap();
+    dev.start_continuous_als(Duration::from_millis(1000))
+        .unwrap();
+    let (_, state) = dev.into_parts();
+
+    bus.log.clear();
+    let mut dev = Device::from_parts(&mut bus, state);
+    dev.switch_profile(&mut NoDelay, RELAXED).unwrap();
+    let _ = dev.release();
+
+    assert_eq!(
+        bus.registers_written(),
+        [0x018, 0x038, HOLD, 0x01B, HOLD, 0x018, 0x038]
+    );
+    assert!(bus.behavior.ranging && bus.behavior.als);
+}
+
+#[test]
+fn identical_settings_touch_nothing() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.load_profiles(&PROFILES);
+    dev.switch_profile(&mut NoDelay, FAST_TRACKING).unwrap();
+    dev.start_continuous_range().unwrap();
+    let (_, state) = dev.into_parts();
+
+    bus.log.clear();
+    let mut dev = Device::from_parts(&mut bus, state);
+    dev.switch_profile(&mut NoDelay, RENAMED).unwrap();
+    assert_eq!(dev.active_profile(), Some(RENAMED));
+    let _ = dev.release();
+
+    assert!(bus.writes().is_empty());
+    assert!(bus.behavior.ranging);
+}
+
+#[test]
+fn built_in_profiles_share_gain_and_interrupts() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.load_profiles(&PROFILES);
+    dev.switch_profile(&mut NoDelay, LOW_POWER).unwrap();
+    let (_, state) = dev.into_parts();
+
+    bus.log.clear();
+    let mut dev = Device::from_parts(&mut bus, state);
+    dev.switch_profile(&mut NoDelay, FAST_TRACKING).unwrap();
+    let _ = dev.release();
+
+    assert_eq!(
+        bus.registers_written(),
+        [HOLD, 0x01B, 0x10A, 0x03E, 0x040, HOLD]
+    );
+}
+
+#[test]
+fn period_shorter_than_a_measurement_is_refused() {
+    static TOO_FAST: [Profile; 1] = [Profile {
+        name: "too_fast",
+        range_period: RangeIntermeasurementPeriod {
+            period: Duration::from_millis(10),
+        },
+        ..Profile::FAST_TRACKING
+    }];
+
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.load_profiles(&TOO_FAST);
+    assert!(matches!(
+        dev.switch_profile(&mut NoDelay, 0),
+        Err(Error::PeriodTooShort(_))
+    ));
+    assert_eq!(dev.active_profile(), None);
+    let _ = dev.release();
+    assert!(bus.writes().is_empty());
+}
+
+#[test]
+fn unknown_index_is_refused() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.load_profiles(&PROFILES);
+    dev.switch_profile(&mut NoDelay, FAST_TRACKING).unwrap();
+
+    let refused = Err(Error::SerializationError(ErrorContext::argument()));
+    assert_eq!(dev.switch_profile(&mut NoDelay, PROFILES.len()), refused);
+    assert_eq!(
+        block_on(dev.switch_profile_async(&mut NoDelay, PROFILES.len())),
+        refused
+    );
+    assert_eq!(dev.active_profile(), Some(FAST_TRACKING));
+    let _ = dev.release();
+    assert_eq!(bus.transactions(), 6);
+}
+
+#[test]
+fn runtime_profile_is_compared_with_the_active_one() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.load_profiles(&PROFILES);
+    dev.switch_profile(&mut NoDelay, FAST_TRACKING).unwrap();
+
+    let period = Duration::from_millis(50);
+    let relaxed = Profile {
+        name: "relaxed_at_runtime",
+        range_period: RangeIntermeasurementPeriod { period },
+        ..Profile::FAST_TRACKING
+    };
+    dev.apply_profile(&mut NoDelay, &relaxed).unwrap();
+    assert_eq!(dev.active_profile(), None);
+    block_on(dev.apply_profile_async(&mut NoDelay, &relaxed)).unwrap();
+    dev.switch_profile(&mut NoDelay, RELAXED).unwrap();
+    assert_eq!(dev.active_profile(), Some(RELAXED));
+    let _ = dev.release();
+
+    assert_eq!(bus.registers_written()[6..], [HOLD, 0x01B, HOLD]);
+    assert_eq!(bus.regs[0x01B], 4);
+}
+
+#[test]
+fn async_matches_blocking() {
+    let sequence = [LOW_POWER, FAST_TRACKING, RELAXED, RENAMED, LOW_POWER];
+
+    let mut sync_bus = bus();
+    sync_bus.behavior.ranging = true;
+    let mut dev = Device::new(&mut sync_bus);
+    dev.load_profiles(&PROFILES);
+    for index in sequence {
+        dev.switch_profile(&mut NoDelay, index).unwrap();
+    }
+    let _ = dev.release();
+
+    let mut async_bus = bus();
+    async_bus.behavior.ranging = true;
+    let mut dev = Device::new(&mut async_bus);
+    dev.load_profiles(&PROFILES);
+    for index in sequence {
+        block_on(dev.switch_profile_async(&mut NoDelay, index)).unwrap();
+    }
+    let _ = dev.release();
+
+    assert_eq!(sync_bus.writes(), async_bus.writes());
+    assert_eq!(sync_bus.regs, async_bus.regs);
+}
@@ -0,0 +1,71 @@
+//! Range thresholds touch only SYSRANGE__THRESH_HIGH and SYSRANGE__THRESH_LOW
+//!
+//! Regression tests for the threshold register spanning four bytes, which
+//! overwrote the intermeasurement period (0x01B) and max convergence time
+//! (0x01C) whenever thresholds were set.
+
+mod support;
+
+use measurements::Length;
+use support::{Log, RegisterMap};
+use vl6180x::registers::RangeThresholds;
+use vl6180x::{AlsInterrupt, Device, GpioPolarity, RangeInterrupt};
+
+/// Power-on timing configuration at 0x01B/0x01C
+fn bus() -> RegisterMap {
+    RegisterMap::new()
+        .set(0x019, &[0xFF])
+        .set(0x01B, &[0xFF, 0x31])
+}
+
+fn mm(value: f64) -> Length {
+    Length::from_millimeters(value)
+}
+
+#[test]
+fn writing_thresholds_touches_two_bytes_at_0x019() {
+    let mut bus = bus();
+    Device::new(&mut bus)
+        .write_register(RangeThresholds::window(mm(50.0), mm(150.0)).unwrap())
+        .unwrap();
+
+    assert_eq!(bus.bytes(), [(vec![0x00, 0x19, 150, 50], 0)]);
+    assert_eq!(bus.regs[0x019..0x01D], [150, 50, 0xFF, 0x31]);
+}
+
+#[test]
+fn reading_thresholds_reads_two_bytes_at_0x019() {
+    let mut bus = bus();
+    bus.regs[0x01A] = 40;
+    let thresholds: RangeThresholds = Device::new(&mut bus).read_register().unwrap();
+
+    assert_eq!(bus.bytes(), [(vec![0x00, 0x19], 2)]);
+    assert_eq!(
+        (thresholds.raw_high_mm(), thresholds.raw_low_mm()),
+        (255, 40)
+    );
+}
+
+#[test]
+fn interrupt_configuration_preserves_timing_registers() {
+    let mut bus = bus();
+    Device::new(&mut bus)
+        .configure_gpio1_interrupt(
+            GpioPolarity::ActiveLow,
+            RangeInterrupt::LevelLow { low: mm(80.0) },
+            AlsInterrupt::Disabled,
+        )
+        .unwrap();
+
+    assert_eq!(bus.regs[0x019..0x01D], [0xFF, 80, 0xFF, 0x31]);
+}
+
+#[test]
+fn thresholds_beyond_255mm_saturate() {
+    let mut bus = bus();
+    Device::new(&mut bus)
+        .write_register(RangeThresholds::above(mm(300.0)))
+        .unwrap();
+
+    assert_eq!(bus.bytes(), [(vec![0x00, 0x19, 0xFF, 0x00], 0)]);
+}
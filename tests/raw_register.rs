@@ -0,0 +1,151 @@
+//! Typed access to arbitrary registers through RawRegister
+
+mod support;
+
+use regiface::Register;
+use support::{block_on, Log, RegisterMap};
+use vl6180x::registers::{AlsAnalogueGain, InterruptConfigGpio, RawRegister};
+use vl6180x::{AlsGain, Device, InterruptMode};
+
+fn bus() -> RegisterMap {
+    RegisterMap::new()
+        .set(0x097, &[0x0F])
+        .set(0x0B8, &[0x12, 0x34])
+        .set(0x1A0, &[0xDE, 0xAD, 0xBE, 0xEF])
+}
+
+#[test]
+fn reads_one_byte() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    assert_eq!(
+        dev.read_register::<RawRegister<0x0097, 1>>(),
+        Ok(RawRegister([0x0F]))
+    );
+    let _ = dev.release();
+    assert_eq!(bus.reads(), [(0x097, 1)]);
+}
+
+#[test]
+fn reads_two_bytes() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    assert_eq!(
+        dev.read_register::<RawRegister<0x00B8, 2>>(),
+        Ok(RawRegister([0x12, 0x34]))
+    );
+    let _ = dev.release();
+    assert_eq!(bus.reads(), [(0x0B8, 2)]);
+}
+
+#[test]
+fn reads_four_bytes() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    let raw: RawRegister<0x01A0, 4> = dev.read_register().unwrap();
+    assert_eq!(raw.bytes(), [0xDE, 0xAD, 0xBE, 0xEF]);
+    let _ = dev.release();
+    assert_eq!(bus.reads(), [(0x1A0, 4)]);
+}
+
+#[test]
+fn writes_one_two_and_four_bytes() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.write_register(RawRegister::<0x0097, 1>([0x1B]))
+        .unwrap();
+    dev.write_register(RawRegister::<0x00B8, 2>([0xAB, 0xCD]))
+        .unwrap();
+    dev.write_register(RawRegister::<0x01A0, 4>::from([1, 2, 3, 4]))
+        .unwrap();
+    let _ = dev.release();
+
+    assert_eq!(
+        bus.writes(),
+        [
+            (0x097, vec![0x1B]),
+            (0x0B8, vec![0xAB, 0xCD]),
+            (0x1A0, vec![1, 2, 3, 4]),
+        ]
+    );
+}
+
+#[test]
+fn round_trips_through_the_device() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.write_register(RawRegister::<0x0120, 2>([0x55, 0xAA]))
+        .unwrap();
+    assert_eq!(
+        dev.read_register::<RawRegister<0x0120, 2>>(),
+        Ok(RawRegister([0x55, 0xAA]))
+    );
+}
+
+#[test]
+fn address_is_the_type_parameter() {
+    assert_eq!(RawRegister::<0x0097, 1>::id(), 0x0097);
+    assert_eq!(RawRegister::<0x0097, 1>::ADDRESS, 0x0097);
+    assert_eq!(<[u8; 2]>::from(RawRegister::<0x00B8, 2>([7, 8])), [7, 8]);
+    assert_eq!(RawRegister::<0x0000, 4>::default(), RawRegister([0; 4]));
+}
+
+#[test]
+fn matches_the_typed_register_at_the_same_address() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.write_register(InterruptConfigGpio {
+        range_interrupt: InterruptMode::NewSampleReady,
+        als_interrupt: InterruptMode::Disabled,
+    })
+    .unwrap();
+    assert_eq!(
+        dev.read_register::<RawRegister<0x0014, 1>>(),
+        Ok(RawRegister([0x04]))
+    );
+}
+
+#[test]
+fn spanning_write_invalidates_the_cached_register() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.write_register(AlsAnalogueGain {
+        gain: AlsGain::Gain1,
+    })
+    .unwrap();
+    // ALS intermeasurement period and analogue gain in one write
+    dev.write_register(RawRegister::<0x003E, 2>([0x09, 0x40]))
+        .unwrap();
+    assert_eq!(
+        dev.read_register::<AlsAnalogueGain>(),
+        Ok(AlsAnalogueGain {
+            gain: AlsGain::Gain20
+        })
+    );
+}
+
+#[test]
+fn exact_write_updates_the_cached_register() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.write_register(RawRegister::<0x003F, 1>([0x46]))
+        .unwrap();
+    let _: AlsAnalogueGain = dev.read_register().unwrap();
+    let _ = dev.release();
+    assert!(bus.reads().is_empty());
+}
+
+#[test]
+fn async_reads_and_writes() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    block_on(dev.write_register_async(RawRegister::<0x01A0, 4>([9, 8, 7, 6]))).unwrap();
+    assert_eq!(
+        block_on(dev.read_register_async::<RawRegister<0x01A0, 4>>()),
+        Ok(RawRegister([9, 8, 7, 6]))
+    );
+    assert_eq!(
+        block_on(dev.read_register_async::<RawRegister<0x0097, 1>>()),
+        Ok(RawRegister([0x0F]))
+    );
+}
@@ -0,0 +1,81 @@
+//! Reads into caller-provided buffers
+
+mod support;
+
+use regiface::FromByteArray;
+use support::{block_on, Op, RegisterMap};
+use vl6180x::registers::ModuleTimestamp;
+use vl6180x::Device;
+
+/// Manufacturing timestamp 2015-10-19 12:00 and a few bytes after it
+fn bus() -> RegisterMap {
+    RegisterMap::new()
+        .set(0x006, &[0x5A, 0x98, 0x54, 0x60])
+        .set(0x1A0, &[0xDE, 0xAD, 0xBE, 0xEF, 0x01])
+}
+
+/// Asserts the bus saw one write_read of `len` bytes from `register`
+fn assert_one_write_read(bus: &RegisterMap, register: u16, len: usize) {
+    let [transaction] = bus.log.as_slice() else {
+        panic!("expected one transaction, got {:?}", bus.log);
+    };
+    assert_eq!(
+        transaction.ops,
+        [Op::Write(register.to_be_bytes().to_vec()), Op::Read(len)]
+    );
+}
+
+#[test]
+fn register_lands_in_the_buffer_undecoded() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    let mut buf = [0; 4];
+    dev.read_register_into::<ModuleTimestamp>(&mut buf).unwrap();
+    let _ = dev.release();
+
+    assert_eq!(buf, [0x5A, 0x98, 0x54, 0x60]);
+    assert_eq!(
+        ModuleTimestamp::from_bytes(buf)
+            .unwrap()
+            .timestamp
+            .to_string(),
+        "2015-10-19T12:00:00"
+    );
+    assert_one_write_read(&bus, 0x006, 4);
+}
+
+#[test]
+fn raw_bytes_land_in_the_buffer() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    let mut buf = [0; 5];
+    dev.read_raw_into(0x1A0, &mut buf).unwrap();
+    let _ = dev.release();
+
+    assert_eq!(buf, [0xDE, 0xAD, 0xBE, 0xEF, 0x01]);
+    assert_one_write_read(&bus, 0x1A0, 5);
+}
+
+#[test]
+fn async_register_lands_in_the_buffer() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    let mut buf = [0; 4];
+    block_on(dev.read_register_into_async::<ModuleTimestamp>(&mut buf)).unwrap();
+    let _ = dev.release();
+
+    assert_eq!(buf, [0x5A, 0x98, 0x54, 0x60]);
+    assert_one_write_read(&bus, 0x006, 4);
+}
+
+#[test]
+fn async_raw_bytes_land_in_the_buffer() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    let mut buf = [0; 3];
+    block_on(dev.read_raw_into_async(0x1A1, &mut buf)).unwrap();
+    let _ = dev.release();
+
+    assert_eq!(buf, [0xAD, 0xBE, 0xEF]);
+    assert_one_write_read(&bus, 0x1A1, 3);
+}
@@ -0,0 +1,150 @@
+//! Reading several registers into a tuple
+
+mod support;
+
+use core::time::Duration;
+
+use embedded_hal::i2c::ErrorKind;
+use measurements::Length;
+use support::{block_on, load, Behavior, Log, RegisterMap, Registers};
+use vl6180x::registers::{
+    AlsAnalogueGain, AlsIntegrationPeriod, FreshOutOfReset, ModelId, RangeIntermeasurementPeriod,
+    RangeMaxConvergenceTime, RangeResultStatus, RangeResultValue, ResultInterruptStatusGpio,
+};
+use vl6180x::{AlsGain, Device, Error, RangeErrorCode};
+
+/// Register file failing reads of one register
+struct FailAt(Option<u16>);
+
+impl Behavior for FailAt {
+    fn read(
+        &mut self,
+        regs: &mut Registers,
+        register: u16,
+        buf: &mut [u8],
+    ) -> Result<(), ErrorKind> {
+        if self.0 == Some(register) {
+            return Err(ErrorKind::Other);
+        }
+        load(regs, register, buf);
+        Ok(())
+    }
+}
+
+/// A completed 75mm range sample, ALS gain 1 and 100ms integration
+fn bus() -> RegisterMap<FailAt> {
+    RegisterMap::with(FailAt(None))
+        .set(0x000, &[0xB4])
+        .set(0x01B, &[0x09, 30])
+        .set(0x03F, &[0x46])
+        .set(0x041, &[0x63])
+        .set(0x04D, &[0x01])
+        .set(0x04F, &[0x04])
+        .set(0x062, &[75])
+}
+
+#[test]
+fn registers_are_read_in_tuple_order() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    let (status, value, gain, interrupt) = dev
+        .read_many::<(
+            RangeResultStatus,
+            RangeResultValue,
+            AlsAnalogueGain,
+            ResultInterruptStatusGpio,
+        )>()
+        .unwrap();
+    let _ = dev.release();
+
+    assert_eq!(status.error_code, RangeErrorCode::NoError);
+    assert_eq!(value.distance, Length::from_millimeters(75.0));
+    assert_eq!(gain.gain, AlsGain::Gain1);
+    assert!(interrupt.range_interrupt);
+    assert_eq!(bus.registers_read(), [0x04D, 0x062, 0x03F, 0x04F]);
+}
+
+#[test]
+fn single_register_tuple() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    let (id,) = dev.read_many::<(ModelId,)>().unwrap();
+    assert_eq!(id, ModelId::VL6180X);
+}
+
+#[test]
+fn eight_registers_fit_in_one_call() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    let (id, fresh, period, limit, _, integration, _, value) = dev
+        .read_many::<(
+            ModelId,
+            FreshOutOfReset,
+            RangeIntermeasurementPeriod,
+            RangeMaxConvergenceTime,
+            AlsAnalogueGain,
+            AlsIntegrationPeriod,
+            RangeResultStatus,
+            RangeResultValue,
+        )>()
+        .unwrap();
+    let _ = dev.release();
+
+    assert_eq!(id, ModelId::VL6180X);
+    assert!(!fresh.fresh);
+    assert_eq!(period.period, Duration::from_millis(100));
+    assert_eq!(limit.time, Duration::from_millis(30));
+    assert_eq!(integration.period, Duration::from_millis(100));
+    assert_eq!(value.distance, Length::from_millimeters(75.0));
+    assert_eq!(
+        bus.registers_read(),
+        [0x000, 0x016, 0x01B, 0x01C, 0x03F, 0x040, 0x04D, 0x062]
+    );
+}
+
+#[test]
+fn first_error_stops_the_reads() {
+    let mut bus = bus();
+    bus.behavior.0 = Some(0x062);
+    let mut dev = Device::new(&mut bus);
+    let result = dev.read_many::<(RangeResultStatus, RangeResultValue, AlsAnalogueGain)>();
+    let _ = dev.release();
+
+    assert!(matches!(result, Err(Error::BusError(_))));
+    assert_eq!(bus.registers_read(), [0x04D, 0x062]);
+}
+
+#[test]
+fn cached_registers_are_not_read_again() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    let _: AlsAnalogueGain = dev.read_register().unwrap();
+    let (gain, _) = dev
+        .read_many::<(AlsAnalogueGain, RangeResultValue)>()
+        .unwrap();
+    let _ = dev.release();
+
+    assert_eq!(gain.gain, AlsGain::Gain1);
+    assert_eq!(bus.registers_read(), [0x03F, 0x062]);
+}
+
+#[test]
+fn async_reads_match_the_blocking_ones() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    let (status, value) =
+        block_on(dev.read_many_async::<(RangeResultStatus, RangeResultValue)>()).unwrap();
+    let _ = dev.release();
+
+    assert_eq!(status.error_code, RangeErrorCode::NoError);
+    assert_eq!(value.distance, Length::from_millimeters(75.0));
+    assert_eq!(bus.registers_read(), [0x04D, 0x062]);
+
+    bus.behavior.0 = Some(0x04D);
+    bus.log.clear();
+    let mut dev = Device::new(&mut bus);
+    let result = block_on(dev.read_many_async::<(RangeResultStatus, RangeResultValue)>());
+    let _ = dev.release();
+    assert!(matches!(result, Err(Error::BusError(_))));
+    assert_eq!(bus.registers_read(), [0x04D]);
+}
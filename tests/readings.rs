@@ -0,0 +1,158 @@
+//! Classification of measurement status codes into readings
+
+use core::time::Duration;
+
+use measurements::Length;
+use vl6180x::types::*;
+
+const INTEGRATION: Duration = Duration::from_millis(100);
+
+fn distance() -> Length {
+    Length::from_millimeters(42.0)
+}
+
+/// Every status code the device defines
+fn range_codes() -> impl Iterator<Item = RangeErrorCode> {
+    (0..=15).filter_map(|value| RangeErrorCode::try_from(value).ok())
+}
+
+#[test]
+fn range_no_error_is_valid() {
+    assert_eq!(
+        RangeReading::new(RangeErrorCode::NoError, distance()),
+        RangeReading::Valid(distance())
+    );
+}
+
+#[test]
+fn range_missing_target_codes_are_no_target() {
+    for code in [
+        RangeErrorCode::EarlyConvergenceEstimate,
+        RangeErrorCode::MaxConvergence,
+        RangeErrorCode::NoTargetIgnore,
+        RangeErrorCode::RawRangingOverflow,
+        RangeErrorCode::RangingOverflow,
+    ] {
+        assert_eq!(RangeReading::new(code, distance()), RangeReading::NoTarget);
+    }
+}
+
+#[test]
+fn range_fault_codes_are_failed() {
+    for code in [
+        RangeErrorCode::VcselContinuityTest,
+        RangeErrorCode::VcselWatchdogTest,
+        RangeErrorCode::VcselWatchdog,
+        RangeErrorCode::Pll1Lock,
+        RangeErrorCode::Pll2Lock,
+        RangeErrorCode::SignalToNoiseRatio,
+        RangeErrorCode::RawRangingUnderflow,
+        RangeErrorCode::RangingUnderflow,
+    ] {
+        assert_eq!(
+            RangeReading::new(code, distance()),
+            RangeReading::Failed(code)
+        );
+    }
+}
+
+#[test]
+fn range_reading_agrees_with_classification() {
+    for code in range_codes() {
+        let reading = RangeReading::new(code, distance());
+        let expected = match code.classification() {
+            RangeErrorClass::Valid => RangeReading::Valid(distance()),
+            RangeErrorClass::NoTarget => RangeReading::NoTarget,
+            RangeErrorClass::SignalQuality | RangeErrorClass::HardwareFault => {
+                RangeReading::Failed(code)
+            }
+        };
+        assert_eq!(reading, expected, "{code:?}");
+        assert_eq!(
+            code.is_valid(),
+            code.classification() == RangeErrorClass::Valid,
+            "{code:?}"
+        );
+    }
+}
+
+#[test]
+fn range_transient_classes() {
+    assert!(RangeErrorCode::MaxConvergence
+        .classification()
+        .is_transient());
+    assert!(RangeErrorCode::SignalToNoiseRatio
+        .classification()
+        .is_transient());
+    assert!(!RangeErrorCode::Pll1Lock.classification().is_transient());
+    assert!(!RangeErrorCode::NoError.classification().is_transient());
+}
+
+#[test]
+fn range_ok_only_for_valid() {
+    assert_eq!(RangeReading::Valid(distance()).ok(), Some(distance()));
+    assert_eq!(RangeReading::NoTarget.ok(), None);
+    assert_eq!(RangeReading::Failed(RangeErrorCode::Pll1Lock).ok(), None);
+}
+
+fn als(code: AlsErrorCode, counts: u16) -> AlsReading {
+    AlsReading::new(code, counts, AlsGain::Gain1, INTEGRATION)
+}
+
+#[test]
+fn als_no_error_is_valid() {
+    assert_eq!(
+        als(AlsErrorCode::NoError, 1000),
+        AlsReading::Valid(Luminance { lux: 320.0 / 1.01 })
+    );
+    assert_eq!(
+        als(AlsErrorCode::NoError, 0),
+        AlsReading::Valid(Luminance { lux: 0.0 })
+    );
+}
+
+#[test]
+fn als_full_scale_count_is_saturated() {
+    assert_eq!(
+        als(AlsErrorCode::NoError, AlsReading::RAW_SATURATED),
+        AlsReading::Saturated
+    );
+}
+
+#[test]
+fn als_overflow_is_saturated() {
+    assert_eq!(als(AlsErrorCode::Overflow, 1000), AlsReading::Saturated);
+    assert_eq!(
+        als(AlsErrorCode::Overflow, AlsReading::RAW_SATURATED),
+        AlsReading::Saturated
+    );
+}
+
+#[test]
+fn als_underflow_is_dark() {
+    assert_eq!(als(AlsErrorCode::Underflow, 0), AlsReading::Dark);
+    assert_eq!(als(AlsErrorCode::Underflow, 1000), AlsReading::Dark);
+}
+
+#[test]
+fn als_ok_only_for_valid() {
+    let lux = Luminance { lux: 12.5 };
+    assert_eq!(AlsReading::Valid(lux).ok(), Some(lux));
+    assert_eq!(AlsReading::Saturated.ok(), None);
+    assert_eq!(AlsReading::Dark.ok(), None);
+    assert_eq!(AlsReading::Failed(AlsErrorCode::Overflow).ok(), None);
+}
+
+#[test]
+fn als_display() {
+    assert_eq!(
+        AlsReading::Valid(Luminance { lux: 12.5 }).to_string(),
+        "12.5 lux"
+    );
+    assert_eq!(AlsReading::Saturated.to_string(), "saturated");
+    assert_eq!(AlsReading::Dark.to_string(), "dark");
+    assert_eq!(
+        AlsReading::Failed(AlsErrorCode::Overflow).to_string(),
+        "failed: Overflow"
+    );
+}
@@ -0,0 +1,145 @@
+//! Readout averaging timing and noise tradeoff
+
+mod support;
+
+use core::time::Duration;
+
+use support::{block_on, Log, RegisterMap};
+use vl6180x::registers::{NoiseTarget, RangeMaxConvergenceTime, ReadoutAveraging};
+use vl6180x::{Device, Error};
+
+fn us(us: u64) -> Duration {
+    Duration::from_micros(us)
+}
+
+fn samples(samples: u8) -> ReadoutAveraging {
+    ReadoutAveraging::new(samples)
+}
+
+fn noise(percent: u16) -> NoiseTarget {
+    NoiseTarget { percent }
+}
+
+#[test]
+fn readout_time_follows_the_datasheet_formula() {
+    // 1.3ms + samples × 64.5µs
+    assert_eq!(samples(0).readout_time(), us(1_300));
+    assert_eq!(samples(1).readout_time(), Duration::from_nanos(1_364_500));
+    // The recommended 48 samples, "around 4.3ms"
+    assert_eq!(ReadoutAveraging::RECOMMENDED.readout_time(), us(4_396));
+    assert_eq!(
+        samples(255).readout_time(),
+        Duration::from_nanos(17_747_500)
+    );
+}
+
+#[test]
+fn measurement_time_includes_the_readout_averaging() {
+    let limit = RangeMaxConvergenceTime::from_ms::<30>();
+    assert_eq!(limit.measurement_time(), us(3_200 + 30_000 + 4_396));
+    assert_eq!(
+        limit.measurement_time(),
+        limit.measurement_time_with(ReadoutAveraging::RECOMMENDED)
+    );
+    assert_eq!(
+        limit.measurement_time_with(samples(0)),
+        us(3_200 + 30_000 + 1_300)
+    );
+}
+
+#[test]
+fn time_budget_picks_the_most_samples_that_fit() {
+    assert_eq!(ReadoutAveraging::for_time_budget(us(4_396)), samples(48));
+    assert_eq!(ReadoutAveraging::for_time_budget(us(4_395)), samples(47));
+    assert_eq!(ReadoutAveraging::for_time_budget(us(1_300)), samples(0));
+    assert_eq!(ReadoutAveraging::for_time_budget(us(1_000)), samples(0));
+    assert_eq!(ReadoutAveraging::for_time_budget(us(17_747)), samples(254));
+    assert_eq!(
+        ReadoutAveraging::for_time_budget(Duration::from_secs(1)),
+        samples(255)
+    );
+
+    for budget in (1_300..20_000).step_by(7) {
+        let averaging = ReadoutAveraging::for_time_budget(us(budget));
+        assert!(averaging.readout_time() <= us(budget));
+        if averaging.samples < 255 {
+            assert!(samples(averaging.samples + 1).readout_time() > us(budget));
+        }
+    }
+}
+
+#[test]
+fn noise_target_picks_the_fewest_samples_that_meet_it() {
+    assert_eq!(ReadoutAveraging::for_noise_target(noise(100)), samples(48));
+    assert_eq!(ReadoutAveraging::for_noise_target(noise(200)), samples(12));
+    assert_eq!(ReadoutAveraging::for_noise_target(noise(50)), samples(192));
+    assert_eq!(ReadoutAveraging::for_noise_target(noise(150)), samples(22));
+    assert_eq!(ReadoutAveraging::for_noise_target(noise(1000)), samples(1));
+    // Beyond what 255 samples reach
+    assert_eq!(ReadoutAveraging::for_noise_target(noise(40)), samples(255));
+    assert_eq!(ReadoutAveraging::for_noise_target(noise(0)), samples(255));
+}
+
+#[test]
+fn noise_is_reported_relative_to_the_recommended_setting() {
+    assert_eq!(ReadoutAveraging::RECOMMENDED.noise(), noise(100));
+    assert_eq!(samples(12).noise(), noise(200));
+    assert_eq!(samples(192).noise(), noise(50));
+    assert_eq!(samples(255).noise(), noise(44));
+    assert_eq!(samples(0).noise(), samples(1).noise());
+
+    for n in 1..=255 {
+        let target = samples(n).noise();
+        let averaging = ReadoutAveraging::for_noise_target(target);
+        assert!(averaging.samples <= n, "{n} samples");
+        assert!(averaging.noise() <= target, "{n} samples");
+    }
+}
+
+/// 30ms convergence limit, 40ms intermeasurement period
+fn bus() -> RegisterMap {
+    RegisterMap::new().set(0x01B, &[3, 30]).set(0x10A, &[48])
+}
+
+#[test]
+fn averaging_that_fits_the_period_is_written() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    // 3.2ms + 30ms + 1.3ms + 80 × 64.5µs = 39.66ms
+    assert_eq!(dev.set_readout_averaging(samples(80)), Ok(()));
+    let _ = dev.release();
+
+    assert_eq!(bus.byte_writes(), [(0x10A, 80)]);
+}
+
+#[test]
+fn averaging_too_long_for_the_period_is_rejected() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    // 40.30ms
+    assert!(matches!(
+        dev.set_readout_averaging(samples(90)),
+        Err(Error::PeriodTooShort(_))
+    ));
+    let _ = dev.release();
+
+    assert!(bus.writes().is_empty());
+    assert_eq!(bus.regs[0x10A], 48);
+}
+
+#[test]
+fn async_averaging_is_checked_the_same_way() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    assert!(matches!(
+        block_on(dev.set_readout_averaging_async(samples(90))),
+        Err(Error::PeriodTooShort(_))
+    ));
+    assert_eq!(
+        block_on(dev.set_readout_averaging_async(samples(16))),
+        Ok(())
+    );
+    let _ = dev.release();
+
+    assert_eq!(bus.byte_writes(), [(0x10A, 16)]);
+}
@@ -0,0 +1,123 @@
+//! Register dumps and their differences
+
+mod support;
+
+use support::RegisterMap;
+use vl6180x::dump::{DumpDifference, RegisterDump};
+use vl6180x::Device;
+
+/// Dump of a unit with GPIO1 as interrupt output and ALS gain 1
+fn working() -> RegisterDump {
+    let mut dump = RegisterDump::new();
+    assert!(dump.set(0x000, &[0xB4]));
+    assert!(dump.set(0x011, &[0x30]));
+    assert!(dump.set(0x03F, &[0x46]));
+    assert!(dump.set(0x040, &[0x00, 0x63]));
+    assert!(dump.set(0x062, &[75]));
+    dump
+}
+
+#[test]
+fn identical_dumps_have_no_differences() {
+    let (left, right) = (working(), working());
+    let diff = left.diff(&right);
+    assert!(diff.is_empty());
+    assert_eq!(diff.len(), 0);
+    assert!(diff.including_volatile().is_empty());
+}
+
+#[test]
+fn differing_registers_are_reported_by_address() {
+    let left = working();
+    let mut right = working();
+    right.set(0x040, &[0x00, 0x31]);
+    right.set(0x011, &[0x10]);
+
+    let differences: Vec<_> = left.diff(&right).iter().collect();
+    assert_eq!(
+        differences,
+        [
+            DumpDifference {
+                address: 0x011,
+                name: "ModeGpio1",
+                left: &[0x30],
+                right: &[0x10],
+            },
+            DumpDifference {
+                address: 0x040,
+                name: "AlsIntegrationPeriod",
+                left: &[0x00, 0x63],
+                right: &[0x00, 0x31],
+            },
+        ]
+    );
+}
+
+#[test]
+fn result_registers_are_only_compared_on_request() {
+    let left = working();
+    let mut right = working();
+    right.set(0x062, &[80]);
+    right.set(0x052, &[1; 16]);
+
+    assert!(left.diff(&right).is_empty());
+    let names: Vec<_> = left
+        .diff(&right)
+        .including_volatile()
+        .iter()
+        .map(|difference| difference.name)
+        .collect();
+    assert_eq!(names, ["HistoryBuffer", "RangeResultValue"]);
+}
+
+#[test]
+fn views_are_not_part_of_the_dump() {
+    let mut dump = RegisterDump::new();
+    // RangeResultBlock starts at 0x062 but spans several registers
+    assert!(!dump.set(0x062, &[0; 2]));
+    assert!(!dump.set(0x005, &[0]));
+    assert_eq!(dump.get(0x005), None);
+    assert_eq!(dump, RegisterDump::new());
+
+    let names: Vec<_> = dump
+        .registers()
+        .map(|(register, _)| register.name)
+        .collect();
+    assert!(names.contains(&"ModelId"));
+    assert!(!names.contains(&"IdentificationBlock"));
+    assert!(!names.contains(&"RangeResultBlock"));
+}
+
+#[test]
+fn debug_output_lists_the_differences() {
+    let left = working();
+    let mut right = working();
+    right.set(0x03F, &[0x40]);
+
+    assert_eq!(
+        format!("{:?}", left.diff(&right)),
+        "[DumpDifference { address: 63, name: \"AlsAnalogueGain\", left: [70], right: [64] }]"
+    );
+    assert!(format!("{right:?}").contains("\"AlsAnalogueGain\": [64]"));
+}
+
+#[test]
+fn device_dump_reads_every_register() {
+    let mut bus = RegisterMap::new()
+        .set(0x000, &[0xB4])
+        .set(0x011, &[0x30])
+        .set(0x03F, &[0x46])
+        .set(0x041, &[0x63])
+        .set(0x062, &[75])
+        .set(0x2A3, &[0x01]);
+
+    let mut dev = Device::new(&mut bus);
+    let dump = dev.dump_registers().unwrap();
+    let _ = dev.release();
+
+    assert_eq!(bus.transactions(), dump.registers().count());
+    assert_eq!(dump.get(0x2A3), Some(&[0x01][..]));
+    let mut expected = working();
+    expected.set(0x2A3, &[0x01]);
+    assert!(expected.diff(&dump).including_volatile().is_empty());
+}
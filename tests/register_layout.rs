@@ -0,0 +1,233 @@
+//! Register map layout
+//!
+//! Checks [`registers::layout`] against the register map in the datasheet
+//! (DocID026171, table 28) and against the register definitions themselves,
+//! so a new register type is checked as soon as it is declared.
+
+use std::fs;
+use std::path::Path;
+
+use vl6180x::registers::{self, RegisterLayout};
+
+/// Public register map: address, width in bytes and datasheet name
+const DATASHEET_MAP: &[(u16, u16, &str)] = &[
+    (0x000, 1, "IDENTIFICATION__MODEL_ID"),
+    (0x001, 1, "IDENTIFICATION__MODEL_REV_MAJOR"),
+    (0x002, 1, "IDENTIFICATION__MODEL_REV_MINOR"),
+    (0x003, 1, "IDENTIFICATION__MODULE_REV_MAJOR"),
+    (0x004, 1, "IDENTIFICATION__MODULE_REV_MINOR"),
+    (0x006, 1, "IDENTIFICATION__DATE_HI"),
+    (0x007, 1, "IDENTIFICATION__DATE_LO"),
+    (0x008, 2, "IDENTIFICATION__TIME"),
+    (0x010, 1, "SYSTEM__MODE_GPIO0"),
+    (0x011, 1, "SYSTEM__MODE_GPIO1"),
+    (0x012, 1, "SYSTEM__HISTORY_CTRL"),
+    (0x014, 1, "SYSTEM__INTERRUPT_CONFIG_GPIO"),
+    (0x015, 1, "SYSTEM__INTERRUPT_CLEAR"),
+    (0x016, 1, "SYSTEM__FRESH_OUT_OF_RESET"),
+    (0x017, 1, "SYSTEM__GROUPED_PARAMETER_HOLD"),
+    (0x018, 1, "SYSRANGE__START"),
+    (0x019, 1, "SYSRANGE__THRESH_HIGH"),
+    (0x01A, 1, "SYSRANGE__THRESH_LOW"),
+    (0x01B, 1, "SYSRANGE__INTERMEASUREMENT_PERIOD"),
+    (0x01C, 1, "SYSRANGE__MAX_CONVERGENCE_TIME"),
+    (0x01E, 2, "SYSRANGE__CROSSTALK_COMPENSATION_RATE"),
+    (0x021, 1, "SYSRANGE__CROSSTALK_VALID_HEIGHT"),
+    (0x022, 2, "SYSRANGE__EARLY_CONVERGENCE_ESTIMATE"),
+    (0x024, 1, "SYSRANGE__PART_TO_PART_RANGE_OFFSET"),
+    (0x025, 1, "SYSRANGE__RANGE_IGNORE_VALID_HEIGHT"),
+    (0x026, 2, "SYSRANGE__RANGE_IGNORE_THRESHOLD"),
+    (0x02C, 1, "SYSRANGE__MAX_AMBIENT_LEVEL_MULT"),
+    (0x02D, 1, "SYSRANGE__RANGE_CHECK_ENABLES"),
+    (0x02E, 1, "SYSRANGE__VHV_RECALIBRATE"),
+    (0x031, 1, "SYSRANGE__VHV_REPEAT_RATE"),
+    (0x038, 1, "SYSALS__START"),
+    (0x03A, 2, "SYSALS__THRESH_HIGH"),
+    (0x03C, 2, "SYSALS__THRESH_LOW"),
+    (0x03E, 1, "SYSALS__INTERMEASUREMENT_PERIOD"),
+    (0x03F, 1, "SYSALS__ANALOGUE_GAIN"),
+    (0x040, 2, "SYSALS__INTEGRATION_PERIOD"),
+    (0x04D, 1, "RESULT__RANGE_STATUS"),
+    (0x04E, 1, "RESULT__ALS_STATUS"),
+    (0x04F, 1, "RESULT__INTERRUPT_STATUS_GPIO"),
+    (0x050, 2, "RESULT__ALS_VAL"),
+    (0x052, 16, "RESULT__HISTORY_BUFFER_x"),
+    (0x062, 1, "RESULT__RANGE_VAL"),
+    (0x064, 1, "RESULT__RANGE_RAW"),
+    (0x066, 2, "RESULT__RANGE_RETURN_RATE"),
+    (0x068, 2, "RESULT__RANGE_REFERENCE_RATE"),
+    (0x06C, 4, "RESULT__RANGE_RETURN_SIGNAL_COUNT"),
+    (0x070, 4, "RESULT__RANGE_REFERENCE_SIGNAL_COUNT"),
+    (0x074, 4, "RESULT__RANGE_RETURN_AMB_COUNT"),
+    (0x078, 4, "RESULT__RANGE_REFERENCE_AMB_COUNT"),
+    (0x07C, 4, "RESULT__RANGE_RETURN_CONV_TIME"),
+    (0x080, 4, "RESULT__RANGE_REFERENCE_CONV_TIME"),
+    (0x10A, 1, "READOUT__AVERAGING_SAMPLE_PERIOD"),
+    (0x119, 1, "FIRMWARE__BOOTUP"),
+    (0x120, 1, "FIRMWARE__RESULT_SCALER"),
+    (0x212, 1, "I2C_SLAVE__DEVICE_ADDRESS"),
+    (0x2A3, 1, "INTERLEAVED_MODE__ENABLE"),
+];
+
+/// Register types deliberately spanning several registers, read in one
+/// transaction; they overlap the registers they are made of
+const VIEWS: &[&str] = &[
+    "IdentificationBlock",
+    "RangeStatusBlock",
+    "AlsResultBlock",
+    "HistoryBuffer",
+    "RangeResultBlock",
+];
+
+/// Datasheet registers no register type covers yet
+const NOT_YET_MODELED: &[u16] = &[0x02C, 0x120, 0x212];
+
+fn layout() -> Vec<RegisterLayout> {
+    registers::layout().to_vec()
+}
+
+fn is_view(register: &RegisterLayout) -> bool {
+    VIEWS.contains(&register.name)
+}
+
+fn covers(register: &RegisterLayout, address: u16) -> bool {
+    (register.address..register.end()).contains(&address)
+}
+
+#[test]
+fn layout_is_sorted_by_address_with_unique_names() {
+    let layout = layout();
+    for pair in layout.windows(2) {
+        assert!(
+            pair[0].address <= pair[1].address,
+            "{} listed before {}",
+            pair[0].name,
+            pair[1].name
+        );
+    }
+    for (i, register) in layout.iter().enumerate() {
+        assert!(
+            layout[i + 1..].iter().all(|r| r.name != register.name),
+            "{} listed twice",
+            register.name
+        );
+    }
+}
+
+#[test]
+fn registers_do_not_overlap_except_views() {
+    let layout = layout();
+    for (i, a) in layout.iter().enumerate() {
+        for b in &layout[i + 1..] {
+            if a.overlaps(b) {
+                assert!(
+                    is_view(a) || is_view(b),
+                    "{} ({:#05X}..{:#05X}) overlaps {} ({:#05X}..{:#05X})",
+                    a.name,
+                    a.address,
+                    a.end(),
+                    b.name,
+                    b.address,
+                    b.end()
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn registers_are_aligned_to_the_datasheet_map() {
+    for register in layout() {
+        let start = DATASHEET_MAP
+            .iter()
+            .find(|&&(address, _, _)| address == register.address);
+        assert!(
+            start.is_some(),
+            "{} starts at {:#05X}, not a datasheet register",
+            register.name,
+            register.address
+        );
+
+        // Every byte of the register belongs to a datasheet register lying
+        // entirely within it
+        for &(address, width, name) in DATASHEET_MAP {
+            let end = address + width;
+            if address < register.end() && register.address < end {
+                assert!(
+                    register.address <= address && end <= register.end(),
+                    "{} cuts {} in half",
+                    register.name,
+                    name
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn every_datasheet_register_is_modeled_or_listed() {
+    let layout = layout();
+    for &(address, _, name) in DATASHEET_MAP {
+        let modeled = layout.iter().any(|r| covers(r, address));
+        let listed = NOT_YET_MODELED.contains(&address);
+        assert!(
+            modeled || listed,
+            "{name} at {address:#05X} is neither modeled nor listed as not yet modeled"
+        );
+        assert!(
+            !(modeled && listed),
+            "{name} at {address:#05X} is modeled, remove it from NOT_YET_MODELED"
+        );
+    }
+}
+
+#[test]
+fn views_are_listed_in_the_layout() {
+    let layout = layout();
+    for view in VIEWS {
+        assert!(
+            layout.iter().any(|r| r.name == *view),
+            "{view} is not in the layout"
+        );
+    }
+}
+
+/// Every `#[register(..)]` declaration in the sources appears in the layout
+#[test]
+fn layout_lists_every_declared_register() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/registers");
+    let layout = layout();
+    let mut declared = 0;
+
+    for entry in fs::read_dir(dir).unwrap() {
+        let source = fs::read_to_string(entry.unwrap().path()).unwrap();
+        let mut lines = source.lines();
+        while let Some(line) = lines.next() {
+            let Some(attr) = line.trim().strip_prefix("#[register(") else {
+                continue;
+            };
+            let address = attr.trim_end_matches(")]").trim_end_matches("u16");
+            let address = u16::from_str_radix(address.trim_start_matches("0x"), 16).unwrap();
+            let name = lines
+                .by_ref()
+                .find_map(|line| {
+                    let item = line.trim().strip_prefix("pub ")?;
+                    let item = item
+                        .strip_prefix("struct ")
+                        .or_else(|| item.strip_prefix("enum "))?;
+                    item.split(|c: char| !c.is_alphanumeric()).next()
+                })
+                .unwrap();
+
+            declared += 1;
+            assert!(
+                layout
+                    .iter()
+                    .any(|r| r.name == name && r.address == address),
+                "{name} at {address:#05X} is missing from registers::layout()"
+            );
+        }
+    }
+
+    assert_eq!(declared, layout.len());
+}
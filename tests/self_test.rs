@@ -0,0 +1,132 @@
+//! Self-test against a healthy and a faulty simulated sensor
+
+mod support;
+
+use measurements::Length;
+use support::{block_on, NoDelay, SimulatedVl6180x};
+use vl6180x::device::SelfTestReport;
+use vl6180x::{AlsReading, Device, Error, Mcps, RangeErrorCode, RangeReading};
+
+fn self_test(sim: &mut SimulatedVl6180x) -> SelfTestReport {
+    Device::new(sim).self_test(&mut NoDelay).unwrap()
+}
+
+fn assert_healthy(report: &SelfTestReport) {
+    assert!(report.passed());
+    assert_eq!(report.vhv.value, Some(core::time::Duration::ZERO));
+    assert_eq!(
+        report.ranging.value,
+        [Some(RangeReading::Valid(Length::from_millimeters(50.0))); 3]
+    );
+    assert_eq!(report.reference_rate.value, [Some(Mcps::from_mcps(5.0)); 3]);
+    assert!(matches!(report.als.value, Some(AlsReading::Valid(_))));
+}
+
+fn assert_vcsel_fault(report: &SelfTestReport) {
+    assert!(!report.passed());
+    assert!(report.vhv.passed);
+    assert!(!report.ranging.passed);
+    assert_eq!(
+        report.ranging.value,
+        [Some(RangeReading::Failed(RangeErrorCode::VcselContinuityTest)); 3]
+    );
+    assert!(!report.reference_rate.passed);
+    assert_eq!(report.reference_rate.value, [Some(Mcps::from_fixed(0)); 3]);
+    assert!(report.als.passed);
+}
+
+fn vcsel_fault() -> SimulatedVl6180x {
+    let mut sim = SimulatedVl6180x::new();
+    sim.fail_measurements(RangeErrorCode::VcselContinuityTest, 3);
+    sim.set_reference_rate(0);
+    sim
+}
+
+#[test]
+fn healthy_unit_passes() {
+    let mut sim = SimulatedVl6180x::new();
+    assert_healthy(&self_test(&mut sim));
+    assert_eq!(sim.range_starts(), 3);
+    assert_eq!(sim.register(0x02E), 0);
+}
+
+#[test]
+fn vcsel_fault_fails_ranging_and_reference_rate() {
+    assert_vcsel_fault(&self_test(&mut vcsel_fault()));
+}
+
+#[test]
+fn one_pll_fault_fails_ranging() {
+    let mut sim = SimulatedVl6180x::new();
+    sim.fail_measurements(RangeErrorCode::Pll2Lock, 1);
+    let report = self_test(&mut sim);
+    assert!(!report.ranging.passed);
+    assert_eq!(
+        report.ranging.value[0],
+        Some(RangeReading::Failed(RangeErrorCode::Pll2Lock))
+    );
+    assert!(report.reference_rate.passed);
+}
+
+#[test]
+fn weak_signal_is_not_a_fault() {
+    let mut sim = SimulatedVl6180x::new();
+    sim.fail_measurements(RangeErrorCode::SignalToNoiseRatio, 3);
+    let report = self_test(&mut sim);
+    assert!(report.passed());
+    assert_eq!(
+        report.ranging.value,
+        [Some(RangeReading::Failed(RangeErrorCode::SignalToNoiseRatio)); 3]
+    );
+}
+
+#[test]
+fn implausible_reference_rate_fails() {
+    let mut sim = SimulatedVl6180x::new();
+    sim.set_reference_rate(SelfTestReport::REFERENCE_RATE_MAX.fixed + 1);
+    let report = self_test(&mut sim);
+    assert!(report.ranging.passed);
+    assert!(!report.reference_rate.passed);
+}
+
+#[test]
+fn dark_light_path_fails() {
+    let mut sim = SimulatedVl6180x::new();
+    sim.set_als_counts(0);
+    let report = self_test(&mut sim);
+    assert!(!report.passed());
+    assert!(!report.als.passed);
+    assert!(report.ranging.passed);
+}
+
+#[test]
+fn stalled_ranging_is_reported_not_returned() {
+    let mut sim = SimulatedVl6180x::new();
+    sim.stall();
+    let report = self_test(&mut sim);
+    assert!(!report.ranging.passed);
+    assert_eq!(report.ranging.value, [None; 3]);
+    assert!(!report.reference_rate.passed);
+    assert_eq!(report.reference_rate.value, [None; 3]);
+}
+
+#[test]
+fn bus_errors_are_returned() {
+    let mut sim = SimulatedVl6180x::new();
+    sim.nack_transaction(4);
+    assert!(matches!(
+        Device::new(&mut sim).self_test(&mut NoDelay),
+        Err(Error::BusError(_))
+    ));
+}
+
+#[test]
+fn async_self_test_matches_the_blocking_one() {
+    let mut sim = SimulatedVl6180x::new();
+    let report = block_on(Device::new(&mut sim).self_test_async(&mut NoDelay)).unwrap();
+    assert_healthy(&report);
+
+    let mut sim = vcsel_fault();
+    let report = block_on(Device::new(&mut sim).self_test_async(&mut NoDelay)).unwrap();
+    assert_vcsel_fault(&report);
+}
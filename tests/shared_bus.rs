@@ -0,0 +1,124 @@
+//! Driver sharing its bus with another peripheral through embedded-hal-bus
+
+mod support;
+
+use core::cell::RefCell;
+
+use critical_section::Mutex;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::I2c;
+use embedded_hal_bus::i2c::{CriticalSectionDevice, RefCellDevice};
+use measurements::Length;
+use support::{Delayed, Log, RegisterMap, SharedBus, EEPROM};
+use vl6180x::{Device, Luminance};
+
+/// Sensor whose samples take two status polls, measuring 75mm and 100 ALS
+/// counts at gain 1 and 100ms
+fn bus() -> SharedBus {
+    SharedBus::new(
+        RegisterMap::with(Delayed::new(Some(2)))
+            .set(0x03F, &[0x46])
+            .set(0x041, &[0x63])
+            .set(0x04D, &[0x01, 0x01])
+            .set(0x051, &[100])
+            .set(0x062, &[75]),
+    )
+}
+
+/// Another driver using the EEPROM between the sensor's transactions
+///
+/// Touches the registers the sensor is polled and cleared at, so that any
+/// traffic reaching the wrong peripheral shows.
+struct Neighbour<I2C> {
+    eeprom: I2C,
+    turns: u8,
+}
+
+impl<I2C: I2c> Neighbour<I2C> {
+    fn new(eeprom: I2C) -> Self {
+        Self { eeprom, turns: 0 }
+    }
+
+    fn turn(&mut self) {
+        self.turns += 1;
+        self.eeprom
+            .write(EEPROM, &[0x00, 0x15, self.turns])
+            .unwrap();
+        let mut status = [0];
+        self.eeprom
+            .write_read(EEPROM, &[0x00, 0x4F], &mut status)
+            .unwrap();
+    }
+}
+
+/// Waiting between status polls hands the bus to the neighbour
+impl<I2C: I2c> DelayNs for Neighbour<I2C> {
+    fn delay_ns(&mut self, _: u32) {
+        self.turn();
+    }
+}
+
+#[test]
+fn single_shot_range_survives_traffic_between_polls() {
+    let bus = RefCell::new(bus());
+    let mut dev = Device::new(RefCellDevice::new(&bus));
+    let mut neighbour = Neighbour::new(RefCellDevice::new(&bus));
+
+    assert_eq!(
+        dev.measure_range_single(&mut neighbour),
+        Ok(Length::from_millimeters(75.0))
+    );
+
+    let bus = bus.borrow();
+    assert_eq!(neighbour.turns, 2);
+    assert_eq!(
+        bus.addresses,
+        [0x29, 0x29, 0x50, 0x50, 0x29, 0x50, 0x50, 0x29, 0x29, 0x29, 0x29]
+    );
+    assert_eq!(bus.sensor.behavior.starts, 1);
+    assert_eq!(bus.sensor.regs[0x04F], 0x00);
+    assert_eq!(bus.eeprom.writes(), [(0x015, vec![1]), (0x015, vec![2])]);
+}
+
+#[test]
+fn single_shot_als_survives_traffic_between_polls() {
+    let bus = Mutex::new(RefCell::new(bus()));
+    let mut dev = Device::new(CriticalSectionDevice::new(&bus));
+    let mut neighbour = Neighbour::new(CriticalSectionDevice::new(&bus));
+
+    assert_eq!(
+        dev.measure_als_single(&mut neighbour),
+        Ok(Luminance::from_lux(32.0 / 1.01))
+    );
+
+    critical_section::with(|cs| {
+        let bus = bus.borrow_ref(cs);
+        assert_eq!(neighbour.turns, 2);
+        assert_eq!(bus.sensor.behavior.starts, 1);
+        assert_eq!(bus.sensor.regs[0x04F], 0x00);
+        assert_eq!(bus.eeprom.regs[0x015], 2);
+        assert!(bus.eeprom.reads().iter().all(|&read| read == (0x04F, 1)));
+    });
+}
+
+#[cfg(feature = "nb")]
+#[test]
+fn non_blocking_range_survives_traffic_between_steps() {
+    let bus = RefCell::new(bus());
+    let mut dev = Device::new(RefCellDevice::new(&bus));
+    let mut neighbour = Neighbour::new(RefCellDevice::new(&bus));
+
+    let range = loop {
+        match dev.try_read_range() {
+            Err(nb::Error::WouldBlock) => neighbour.turn(),
+            result => break result,
+        }
+    };
+
+    assert_eq!(range, Ok(Length::from_millimeters(75.0)));
+    let bus = bus.borrow();
+    assert_eq!(neighbour.turns, 3);
+    assert_eq!(bus.sensor.behavior.starts, 1);
+    assert_eq!(bus.sensor.regs[0x04F], 0x00);
+    assert_eq!(bus.eeprom.regs[0x015], 3);
+}
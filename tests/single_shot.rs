@@ -0,0 +1,101 @@
+//! Single-shot range and ALS measurement helpers
+
+mod support;
+
+use measurements::Length;
+use support::{block_on, Delayed, Log, NoDelay, RegisterMap};
+use vl6180x::{AlsErrorCode, Device, Error, Luminance, RangeErrorCode};
+
+/// Sensor whose samples take two status polls, measuring 75mm and 100 ALS
+/// counts at gain 1 and 100ms
+fn bus() -> RegisterMap<Delayed> {
+    RegisterMap::with(Delayed::new(Some(2)))
+        .set(0x03F, &[0x46])
+        .set(0x041, &[0x63])
+        .set(0x04D, &[0x01, 0x01])
+        .set(0x051, &[100])
+        .set(0x062, &[75])
+}
+
+#[test]
+fn range_starts_polls_reads_and_clears() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    assert_eq!(
+        dev.measure_range_single(&mut NoDelay),
+        Ok(Length::from_millimeters(75.0))
+    );
+    let _ = dev.release();
+
+    assert_eq!(bus.writes(), [(0x018, vec![0x01]), (0x015, vec![0x01])]);
+    assert_eq!(bus.registers_read(), [0x04F, 0x04F, 0x04F, 0x04D, 0x062]);
+    assert_eq!(bus.regs[0x04F], 0x00);
+}
+
+#[test]
+fn range_error_code_is_returned_and_the_interrupt_cleared() {
+    let mut bus = bus().set(0x04D, &[0xB1]);
+    let mut dev = Device::new(&mut bus);
+    assert_eq!(
+        dev.measure_range_single(&mut NoDelay),
+        Err(Error::RangeError(RangeErrorCode::SignalToNoiseRatio))
+    );
+    let _ = dev.release();
+
+    assert_eq!(bus.writes().last(), Some(&(0x015, vec![0x01])));
+    assert_eq!(bus.regs[0x04F], 0x00);
+}
+
+#[test]
+fn wedged_sensor_times_out_after_a_bounded_number_of_polls() {
+    let mut bus = RegisterMap::with(Delayed::new(None));
+    let mut dev = Device::new(&mut bus);
+    assert_eq!(dev.measure_range_single(&mut NoDelay), Err(Error::Timeout));
+    assert_eq!(dev.measure_als_single(&mut NoDelay), Err(Error::Timeout));
+    let _ = dev.release();
+
+    // One poll per millisecond of the default 105.9ms and 768ms timeouts
+    assert_eq!(bus.polls(0x04F), 105 + 768);
+    assert_eq!(bus.behavior.starts, 2);
+}
+
+#[test]
+fn als_starts_polls_reads_and_clears() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    assert_eq!(
+        dev.measure_als_single(&mut NoDelay),
+        Ok(Luminance::from_lux(32.0 / 1.01))
+    );
+    let _ = dev.release();
+
+    assert_eq!(bus.writes(), [(0x038, vec![0x01]), (0x015, vec![0x02])]);
+    assert_eq!(bus.regs[0x04F], 0x00);
+}
+
+#[test]
+fn als_error_code_is_returned_and_the_interrupt_cleared() {
+    let mut bus = bus().set(0x04E, &[0x11]);
+    let mut dev = Device::new(&mut bus);
+    assert_eq!(
+        dev.measure_als_single(&mut NoDelay),
+        Err(Error::AlsError(AlsErrorCode::Overflow))
+    );
+    let _ = dev.release();
+
+    assert_eq!(bus.writes().last(), Some(&(0x015, vec![0x02])));
+    assert_eq!(bus.regs[0x04F], 0x00);
+}
+
+#[test]
+fn async_range_matches_blocking() {
+    let mut blocking = bus();
+    let distance = Device::new(&mut blocking).measure_range_single(&mut NoDelay);
+
+    let mut nonblocking = bus();
+    let async_distance =
+        block_on(Device::new(&mut nonblocking).measure_range_single_async(&mut NoDelay));
+
+    assert_eq!(distance, async_distance);
+    assert_eq!(blocking.log, nonblocking.log);
+}
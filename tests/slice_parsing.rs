@@ -0,0 +1,133 @@
+//! Parsing registers out of byte slices
+
+use core::time::Duration;
+
+use measurements::Length;
+use vl6180x::registers::{
+    AlsAnalogueGain, AlsIntegrationPeriod, AlsThresholds, ModelId, RangeResultStatus,
+    RangeResultValue, RangeStatusBlock, RawRegister, ResultAlsStatus,
+};
+use vl6180x::{AlsGain, RangeErrorCode, RegisterError};
+
+#[test]
+fn exact_length_slices_parse() {
+    assert_eq!(ModelId::try_from(&[0xB4][..]), Ok(ModelId::VL6180X));
+    assert_eq!(
+        AlsIntegrationPeriod::try_from(&[0x00, 0x63][..]),
+        Ok(AlsIntegrationPeriod {
+            period: Duration::from_millis(100)
+        })
+    );
+    assert_eq!(
+        AlsThresholds::try_from(&[0x01, 0x00, 0x00, 0x10][..])
+            .map(|thresholds| (thresholds.high.lux, thresholds.low.lux)),
+        Ok((256.0, 16.0))
+    );
+    assert_eq!(
+        RawRegister::<0x0097, 2>::try_from(&[1, 2][..]),
+        Ok(RawRegister([1, 2]))
+    );
+}
+
+#[test]
+fn block_parses_from_its_full_width() {
+    let mut bytes = [0; 22];
+    bytes[0] = 0x01;
+    bytes[2] = 0x04;
+    bytes[21] = 42;
+    let block = RangeStatusBlock::try_from(&bytes[..]).unwrap();
+    assert_eq!(block.status.error_code, RangeErrorCode::NoError);
+    assert!(block.interrupt.range_interrupt);
+    assert_eq!(block.distance, Length::from_millimeters(42.0));
+}
+
+#[test]
+fn too_short_slices_are_rejected() {
+    assert_eq!(
+        ModelId::try_from(&[][..]),
+        Err(RegisterError::WrongLength {
+            expected: 1,
+            actual: 0
+        })
+    );
+    assert_eq!(
+        AlsThresholds::try_from(&[0x01, 0x00, 0x00][..]),
+        Err(RegisterError::WrongLength {
+            expected: 4,
+            actual: 3
+        })
+    );
+    assert_eq!(
+        RangeStatusBlock::try_from(&[0; 21][..]),
+        Err(RegisterError::WrongLength {
+            expected: 22,
+            actual: 21
+        })
+    );
+    assert_eq!(
+        RawRegister::<0x0097, 4>::parse_prefix(&[1, 2]),
+        Err(RegisterError::WrongLength {
+            expected: 4,
+            actual: 2
+        })
+    );
+}
+
+#[test]
+fn too_long_slices_are_rejected() {
+    assert_eq!(
+        RangeResultValue::try_from(&[42, 0][..]),
+        Err(RegisterError::WrongLength {
+            expected: 1,
+            actual: 2
+        })
+    );
+    assert_eq!(
+        AlsIntegrationPeriod::try_from(&[0x00, 0x63, 0x00][..]),
+        Err(RegisterError::WrongLength {
+            expected: 2,
+            actual: 3
+        })
+    );
+    assert_eq!(
+        RawRegister::<0x0097, 1>::try_from(&[1, 2][..]),
+        Err(RegisterError::WrongLength {
+            expected: 1,
+            actual: 2
+        })
+    );
+}
+
+#[test]
+fn decode_errors_pass_through() {
+    assert_eq!(
+        RangeResultStatus::try_from(&[0x90][..]),
+        Err(RegisterError::InvalidEnumValue(9))
+    );
+    assert!(matches!(
+        ResultAlsStatus::parse_prefix(&[0xF0, 0x00]),
+        Err(RegisterError::InvalidEnumValue(_))
+    ));
+}
+
+#[test]
+fn prefixes_parse_in_sequence() {
+    let buffer = [0x01, 0x46, 0x00, 0x63, 0xAA];
+    let (status, rest) = RangeResultStatus::parse_prefix(&buffer).unwrap();
+    let (gain, rest) = AlsAnalogueGain::parse_prefix(rest).unwrap();
+    let (integration, rest) = AlsIntegrationPeriod::parse_prefix(rest).unwrap();
+
+    assert!(status.device_ready);
+    assert_eq!(gain.gain, AlsGain::Gain1);
+    assert_eq!(integration.period, Duration::from_millis(100));
+    assert_eq!(rest, [0xAA]);
+}
+
+#[test]
+fn wrong_length_displays_both_lengths() {
+    let error = RegisterError::WrongLength {
+        expected: 2,
+        actual: 5,
+    };
+    assert_eq!(error.to_string(), "Expected 2 bytes, got 5");
+}
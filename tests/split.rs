@@ -0,0 +1,87 @@
+//! Configuration and result halves of a split device sharing one bus
+
+mod support;
+
+use core::cell::RefCell;
+use core::time::Duration;
+
+use embedded_hal::i2c::ErrorKind;
+use embedded_hal_bus::i2c::RefCellDevice;
+use measurements::Length;
+use support::{Clearing, Log, RegisterMap};
+use vl6180x::registers::{InterruptClear, RangeResultValue, RangeStart, ResultInterruptStatusGpio};
+use vl6180x::{BusRecovery, Device, Timeouts};
+
+const CLEAR_RANGE: InterruptClear = InterruptClear {
+    clear_range: true,
+    clear_als: false,
+    clear_error: false,
+};
+
+/// Sensor with a range sample of 75mm pending
+fn bus() -> RefCell<RegisterMap<Clearing>> {
+    RefCell::new(
+        RegisterMap::with(Clearing)
+            .set(0x04F, &[0x04])
+            .set(0x062, &[75]),
+    )
+}
+
+fn retry(_: ErrorKind) -> BusRecovery {
+    BusRecovery::Retry
+}
+
+#[test]
+fn halves_interleave_on_the_shared_bus() {
+    let bus = bus();
+    let dev = Device::new(RefCellDevice::new(&bus));
+    let (mut config, mut reader) = dev.split(RefCellDevice::new(&bus));
+
+    config.write_register(RangeStart::SingleShot).unwrap();
+    let status: ResultInterruptStatusGpio = reader.read_register().unwrap();
+    assert!(status.range_interrupt);
+    let distance: RangeResultValue = reader.read_register().unwrap();
+    reader.clear_interrupts(CLEAR_RANGE).unwrap();
+    config.write_register(RangeStart::SingleShot).unwrap();
+    assert_eq!(distance.distance, Length::from_millimeters(75.0));
+
+    let (dev, _) = config.rejoin(reader);
+    let _ = dev.release();
+    let bus = bus.borrow();
+    assert_eq!(bus.registers_read(), [0x04F, 0x062]);
+    assert_eq!(
+        bus.writes(),
+        [
+            (0x018, vec![0x01]),
+            (0x015, vec![0x01]),
+            (0x018, vec![0x01])
+        ]
+    );
+    assert_eq!(bus.regs[0x04F], 0x00);
+}
+
+#[test]
+fn reader_keeps_the_device_settings() {
+    let timeouts = Timeouts {
+        range: Duration::from_millis(20),
+        ..Timeouts::default()
+    };
+    let bus = bus();
+    let mut dev = Device::new(RefCellDevice::new(&bus));
+    dev.set_timeouts(timeouts);
+    dev.set_paranoid(true);
+    dev.set_bus_recovery(retry);
+
+    let (config, mut reader) = dev.split(RefCellDevice::new(&bus));
+    assert_eq!(reader.timeouts(), timeouts);
+    assert!(reader.is_paranoid());
+
+    bus.borrow_mut().fail_next(1, ErrorKind::Bus);
+    let distance: RangeResultValue = reader.read_register().unwrap();
+    assert_eq!(distance.distance, Length::from_millimeters(75.0));
+    // One failed read retried by the hook, then two verified reads
+    assert_eq!(bus.borrow().polls(0x062), 3);
+
+    let (dev, _) = config.rejoin(reader);
+    assert_eq!(dev.timeouts(), timeouts);
+}
@@ -0,0 +1,97 @@
+//! The ST API facade maps onto the native device API
+#![cfg(feature = "st-compat")]
+
+mod support;
+
+use support::{NoDelay, RegisterMap};
+use vl6180x::st_compat::{self, RangeData};
+use vl6180x::{Device, Error, ErrorContext, Luminance, RangeErrorCode};
+
+fn bus() -> RegisterMap {
+    RegisterMap::new()
+}
+
+#[test]
+fn prepare_clears_reset_and_enables_new_sample_interrupts() {
+    let mut bus = bus();
+    bus.regs[0x016] = 0x01;
+    st_compat::prepare(&mut Device::new(&mut bus)).unwrap();
+
+    assert_eq!(bus.regs[0x016], 0x00);
+    assert_eq!(bus.regs[0x014], 0x24);
+    assert_eq!(bus.regs[0x015], 0x07);
+}
+
+#[test]
+fn range_poll_measurement_reports_value_and_signal_rate() {
+    let mut bus = bus();
+    bus.regs[0x04D] = 0x01;
+    bus.regs[0x04F] = 0x04;
+    bus.regs[0x062] = 87;
+    bus.regs[0x066..0x068].copy_from_slice(&[0x01, 0x40]);
+
+    let data = st_compat::range_poll_measurement(&mut Device::new(&mut bus), &mut NoDelay);
+    assert_eq!(
+        data,
+        Ok(RangeData {
+            range_mm: 87,
+            signal_rate_mcps: 0x0140,
+            error_status: RangeErrorCode::NoError,
+        })
+    );
+}
+
+#[test]
+fn range_poll_measurement_returns_error_status_instead_of_failing() {
+    let mut bus = bus();
+    bus.regs[0x04D] = 0x61;
+    bus.regs[0x04F] = 0x04;
+    bus.regs[0x062] = 255;
+
+    let data = st_compat::range_poll_measurement(&mut Device::new(&mut bus), &mut NoDelay).unwrap();
+    assert_eq!(data.error_status, RangeErrorCode::EarlyConvergenceEstimate);
+    assert_eq!(data.range_mm, 255);
+}
+
+#[test]
+fn range_poll_measurement_times_out_without_sample() {
+    let mut bus = bus();
+    let data = st_compat::range_poll_measurement(&mut Device::new(&mut bus), &mut NoDelay);
+    assert_eq!(data, Err(Error::Timeout));
+}
+
+#[test]
+fn als_get_lux_scales_last_result() {
+    let mut bus = bus();
+    bus.regs[0x03F] = 0x46;
+    bus.regs[0x040..0x042].copy_from_slice(&[0x00, 0x63]);
+    bus.regs[0x050..0x052].copy_from_slice(&1000u16.to_be_bytes());
+
+    let lux = st_compat::als_get_lux(&mut Device::new(&mut bus));
+    assert_eq!(lux, Ok(Luminance { lux: 320.0 / 1.01 }));
+}
+
+#[test]
+fn als_poll_measurement_matches_get_lux() {
+    let mut bus = bus();
+    bus.regs[0x03F] = 0x46;
+    bus.regs[0x040..0x042].copy_from_slice(&[0x00, 0x63]);
+    bus.regs[0x04F] = 0x20;
+    bus.regs[0x050..0x052].copy_from_slice(&1000u16.to_be_bytes());
+
+    let mut dev = Device::new(&mut bus);
+    let polled = st_compat::als_poll_measurement(&mut dev, &mut NoDelay);
+    assert_eq!(polled, Ok(Luminance { lux: 320.0 / 1.01 }));
+    assert_eq!(st_compat::als_get_lux(&mut dev), polled);
+}
+
+#[test]
+fn upscale_only_accepts_unity() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    assert_eq!(st_compat::upscale_set_scaling(&mut dev, 1), Ok(()));
+    assert_eq!(
+        st_compat::upscale_set_scaling(&mut dev, 2),
+        Err(Error::SerializationError(ErrorContext::argument()))
+    );
+}
@@ -0,0 +1,150 @@
+//! Runtime and compile-time addressed devices running the same scenarios
+
+mod support;
+
+use core::cell::RefCell;
+use core::mem::size_of;
+
+use embedded_hal_bus::i2c::RefCellDevice;
+
+use measurements::Length;
+use support::{block_on, NoDelay, SimulatedVl6180x};
+use vl6180x::device::{DeviceAddress, FixedAddress, StaticDevice};
+use vl6180x::registers::{ModelId, RangeStart, ResultInterruptStatusGpio};
+use vl6180x::{Device, Error, Luminance, RangeSensor};
+
+type Sim<'a, A> = Device<&'a mut SimulatedVl6180x, A>;
+
+fn single_shots<A: DeviceAddress>(dev: &mut Sim<'_, A>) {
+    assert_eq!(dev.read_register::<ModelId>(), Ok(ModelId::VL6180X));
+    assert_eq!(
+        dev.measure_range_single(&mut NoDelay),
+        Ok(Length::from_millimeters(50.0))
+    );
+    assert_eq!(
+        dev.measure_als_single(&mut NoDelay),
+        Ok(Luminance::from_lux(8192.0 / 1.01))
+    );
+}
+
+fn async_single_shots<A: DeviceAddress>(dev: &mut Sim<'_, A>) {
+    assert_eq!(
+        block_on(dev.measure_range_single_async(&mut NoDelay)),
+        Ok(Length::from_millimeters(50.0))
+    );
+}
+
+fn scoped_continuous<A: DeviceAddress>(dev: &mut Sim<'_, A>) {
+    let mut guard = dev.continuous_ranging_scope().unwrap();
+    assert_eq!(
+        guard.next_sample(&mut NoDelay),
+        Ok(Length::from_millimeters(50.0))
+    );
+    assert_eq!(guard.stop(&mut NoDelay), Ok(()));
+}
+
+fn through_the_trait(sensor: &mut impl RangeSensor<Error = Error>) {
+    assert_eq!(
+        sensor.measure(&mut NoDelay),
+        Ok(Length::from_millimeters(50.0))
+    );
+}
+
+#[test]
+fn runtime_address_runs_the_scenarios() {
+    let mut sim = SimulatedVl6180x::new();
+    let mut dev = Device::new(&mut sim);
+    assert_eq!(dev.address(), 0x29);
+    single_shots(&mut dev);
+    async_single_shots(&mut dev);
+    scoped_continuous(&mut dev);
+    through_the_trait(&mut dev);
+}
+
+#[test]
+fn static_address_runs_the_scenarios() {
+    let mut sim = SimulatedVl6180x::new();
+    let mut dev: StaticDevice<_> = StaticDevice::new_static(&mut sim);
+    assert_eq!(dev.address(), 0x29);
+    single_shots(&mut dev);
+    async_single_shots(&mut dev);
+    scoped_continuous(&mut dev);
+    through_the_trait(&mut dev);
+}
+
+#[test]
+fn custom_addresses_reach_the_moved_sensor() {
+    let mut sim = SimulatedVl6180x::new();
+    sim.set_i2c_address(0x30);
+
+    let mut dev = Device::new_with_address(&mut sim, 0x30);
+    single_shots(&mut dev);
+    let _ = dev.release();
+
+    let mut dev = StaticDevice::<_, 0x30>::new_static(&mut sim);
+    assert_eq!(dev.address(), 0x30);
+    single_shots(&mut dev);
+}
+
+#[test]
+fn wrong_addresses_are_not_acknowledged() {
+    let mut sim = SimulatedVl6180x::new();
+    sim.set_i2c_address(0x30);
+
+    let mut dev = Device::new(&mut sim);
+    assert!(matches!(
+        dev.read_register::<ModelId>(),
+        Err(Error::BusError(_))
+    ));
+    let _ = dev.release();
+
+    let mut dev = StaticDevice::<_, 0x31>::new_static(&mut sim);
+    assert!(matches!(
+        dev.read_register::<ModelId>(),
+        Err(Error::BusError(_))
+    ));
+    let _ = dev.release();
+    assert_eq!(sim.transactions(), 0);
+}
+
+#[test]
+fn fixed_address_takes_no_space() {
+    assert_eq!(size_of::<FixedAddress<0x30>>(), 0);
+    assert_eq!(FixedAddress::<0x30>.get(), 0x30);
+}
+
+#[test]
+fn static_address_survives_typed_modes_and_parts() {
+    let mut sim = SimulatedVl6180x::new();
+    sim.set_i2c_address(0x30);
+    let Ok(dev) = StaticDevice::<_, 0x30>::try_new_static(&mut sim) else {
+        panic!("the sensor answers at 0x30");
+    };
+
+    let ranging = dev.into_continuous_range().unwrap();
+    let dev = ranging.stop(&mut NoDelay).unwrap().into_inner();
+
+    let (bus, state) = dev.into_parts();
+    assert_eq!(state.address(), 0x30);
+    let dev: StaticDevice<_, 0x30> = Device::from_parts(bus, state);
+    assert_eq!(dev.address(), 0x30);
+    let _ = dev.release();
+
+    let result = StaticDevice::<_, 0x29>::try_new_static(&mut sim);
+    assert!(matches!(result, Err((_, Error::BusError(_)))));
+}
+
+#[test]
+fn split_halves_keep_the_static_address() {
+    let mut sim = SimulatedVl6180x::new();
+    sim.set_i2c_address(0x30);
+    let bus = RefCell::new(&mut sim);
+    let dev = StaticDevice::<_, 0x30>::new_static(RefCellDevice::new(&bus));
+
+    let (mut config, mut reader) = dev.split(RefCellDevice::new(&bus));
+    config.write_register(RangeStart::SingleShot).unwrap();
+    assert!(reader.read_register::<ResultInterruptStatusGpio>().is_ok());
+
+    let (dev, _) = config.rejoin(reader);
+    assert_eq!(dev.address(), 0x30);
+}
@@ -0,0 +1,211 @@
+//! Strict mode rejects register writes outside the datasheet ranges
+
+mod support;
+
+use core::time::Duration;
+
+use measurements::Length;
+use support::{Log, RegisterMap};
+use vl6180x::registers::{
+    AlsIntegrationPeriod, AlsIntermeasurementPeriod, AlsThresholds, DatasheetLimits,
+    RangeCrosstalkValidHeight, RangeIntermeasurementPeriod, RangeMaxConvergenceTime,
+    RangePartToPartOffset, RangeThresholds, RangeVhvRepeatRate,
+};
+use vl6180x::{Device, Direction, Error, ErrorContext, FieldLimit, LimitViolation, Luminance};
+
+fn bus() -> RegisterMap {
+    RegisterMap::new()
+}
+
+fn range_period(ms: u64) -> RangeIntermeasurementPeriod {
+    RangeIntermeasurementPeriod {
+        period: Duration::from_millis(ms),
+    }
+}
+
+#[test]
+fn strict_mode_is_off_by_default() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    assert!(!dev.is_strict());
+    dev.set_strict(true);
+    assert!(dev.is_strict());
+}
+
+#[test]
+fn permissive_mode_writes_encodable_values() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+
+    // Code 255 is beyond the documented 0-254, 15ms is rounded to 20ms
+    dev.write_register(RangeThresholds::above(Length::from_millimeters(300.0)))
+        .unwrap();
+    dev.write_register(range_period(2560)).unwrap();
+    dev.write_register(range_period(15)).unwrap();
+    let _ = dev.release();
+
+    assert_eq!(bus.writes().len(), 3);
+    assert_eq!(bus.regs[0x01B], 0x01);
+}
+
+#[test]
+fn permissive_mode_still_rejects_unencodable_values() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    assert_eq!(
+        dev.write_register(AlsIntegrationPeriod {
+            period: Duration::from_millis(600),
+        }),
+        Err(Error::SerializationError(ErrorContext::register(
+            0x040,
+            Direction::Write
+        )))
+    );
+}
+
+#[test]
+fn strict_mode_writes_in_range_values() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.set_strict(true);
+
+    dev.write_register(RangeThresholds::below(Length::from_millimeters(50.0)))
+        .unwrap();
+    dev.write_register(range_period(10)).unwrap();
+    dev.write_register(range_period(2550)).unwrap();
+    dev.write_register(RangeMaxConvergenceTime {
+        time: Duration::from_millis(63),
+    })
+    .unwrap();
+    dev.write_register(AlsIntegrationPeriod {
+        period: Duration::from_millis(512),
+    })
+    .unwrap();
+    dev.write_register(RangeVhvRepeatRate { rate: 255 })
+        .unwrap();
+    let _ = dev.release();
+
+    assert_eq!(bus.writes().len(), 6);
+    assert_eq!(bus.regs[0x01B], 0xFE);
+}
+
+#[test]
+fn strict_mode_rejects_before_writing() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.set_strict(true);
+
+    assert_eq!(
+        dev.write_register(range_period(2560)),
+        Err(Error::OutOfSpec(LimitViolation {
+            limit: &FieldLimit {
+                field: "sysrange__intermeasurement_period",
+                min: 10_000,
+                max: 2_550_000,
+                step: 10_000,
+                unit: "us",
+            },
+            value: 2_560_000,
+        }))
+    );
+    let _ = dev.release();
+
+    assert_eq!(bus.writes().len(), 0);
+    assert_eq!(bus.regs[0x01B], 0x00);
+}
+
+#[test]
+fn strict_mode_rejects_off_step_durations() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.set_strict(true);
+
+    let Err(Error::OutOfSpec(violation)) = dev.write_register(AlsIntermeasurementPeriod {
+        period: Duration::from_millis(15),
+    }) else {
+        panic!("off-step period accepted");
+    };
+    assert_eq!(violation.limit.field, "sysals__intermeasurement_period");
+    assert_eq!(violation.value, 15_000);
+}
+
+#[test]
+fn strict_mode_names_the_offending_field() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.set_strict(true);
+
+    let Err(Error::OutOfSpec(violation)) =
+        dev.write_register(RangeThresholds::above(Length::from_millimeters(300.0)))
+    else {
+        panic!("threshold above 255mm accepted");
+    };
+    assert_eq!(violation.limit.field, "sysrange__thresh_high");
+    assert_eq!((violation.limit.min, violation.limit.max), (0, 255));
+    assert_eq!(
+        violation.to_string(),
+        "sysrange__thresh_high = 300mm is outside 0..=255mm"
+    );
+}
+
+#[test]
+fn strict_mode_checks_modified_registers() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.set_strict(true);
+
+    let result = dev.modify_register(|reg: &mut RangeMaxConvergenceTime| {
+        reg.time = Duration::from_millis(64);
+    });
+    assert!(matches!(result, Err(Error::OutOfSpec(_))));
+}
+
+#[test]
+fn limits_table() {
+    assert_eq!(range_period(20).check_limits(), Ok(()));
+    assert!(range_period(0).check_limits().is_err());
+    assert!(RangeMaxConvergenceTime {
+        time: Duration::ZERO
+    }
+    .check_limits()
+    .is_err());
+    assert!(AlsIntegrationPeriod {
+        period: Duration::from_micros(1_500)
+    }
+    .check_limits()
+    .is_err());
+    assert!(RangeCrosstalkValidHeight {
+        height: Length::from_millimeters(256.0)
+    }
+    .check_limits()
+    .is_err());
+    assert!(RangePartToPartOffset {
+        offset: Length::from_millimeters(-129.0)
+    }
+    .check_limits()
+    .is_err());
+    assert!(AlsThresholds {
+        high: Luminance::from_lux(70_000.0),
+        low: Luminance::from_lux(0.0),
+    }
+    .check_limits()
+    .is_err());
+    assert_eq!(
+        AlsThresholds {
+            high: Luminance::from_lux(65_535.0),
+            low: Luminance::from_lux(0.0),
+        }
+        .check_limits(),
+        Ok(())
+    );
+}
+
+#[test]
+fn violation_display_includes_step() {
+    let violation = range_period(15).check_limits().unwrap_err();
+    assert_eq!(
+        Error::OutOfSpec(violation).to_string(),
+        "Out of datasheet range: sysrange__intermeasurement_period = 15000us \
+         is outside 10000..=2550000us in steps of 10000us"
+    );
+}
@@ -0,0 +1,172 @@
+//! Fixtures shared by the integration tests
+//!
+//! [`SimulatedVl6180x`] models the sensor end to end, [`RegisterMap`] is a
+//! plain register file for tests pinning the bytes on the bus. Both decode
+//! the full 16-bit register address and log every transaction.
+
+#![allow(dead_code, unused_imports)]
+
+mod register_map;
+mod simulator;
+
+use core::future::Future;
+use core::pin::pin;
+use core::task::{Context, Poll, Waker};
+
+use embedded_hal::i2c::Operation;
+
+pub use register_map::{
+    clear_interrupts, each_byte, load, store, Behavior, Clearing, Delayed, Plain, RegisterMap,
+    Registers, SharedBus, EEPROM,
+};
+pub use simulator::{Multidrop, SimulatedVl6180x};
+
+/// One operation of a logged transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Op {
+    /// Bytes written, register address included
+    Write(Vec<u8>),
+    /// Number of bytes read
+    Read(usize),
+}
+
+/// A logged bus transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transaction {
+    /// 7-bit I2C address the transaction was sent to
+    pub address: u8,
+    pub ops: Vec<Op>,
+}
+
+impl Transaction {
+    fn record(address: u8, ops: &[Operation<'_>]) -> Self {
+        let ops = ops
+            .iter()
+            .map(|op| match op {
+                Operation::Write(data) => Op::Write(data.to_vec()),
+                Operation::Read(buf) => Op::Read(buf.len()),
+            })
+            .collect();
+        Self { address, ops }
+    }
+
+    /// All bytes written, register address included
+    pub fn written(&self) -> Vec<u8> {
+        self.ops
+            .iter()
+            .filter_map(|op| match op {
+                Op::Write(data) => Some(data.as_slice()),
+                Op::Read(_) => None,
+            })
+            .flatten()
+            .copied()
+            .collect()
+    }
+
+    /// 16-bit register address the transaction starts at
+    pub fn register(&self) -> u16 {
+        let written = self.written();
+        u16::from_be_bytes([written[0], written[1]])
+    }
+
+    /// Bytes written after the register address
+    pub fn payload(&self) -> Vec<u8> {
+        self.written().split_off(2)
+    }
+
+    /// Number of bytes read
+    pub fn read(&self) -> usize {
+        self.ops
+            .iter()
+            .map(|op| match op {
+                Op::Write(_) => 0,
+                Op::Read(len) => *len,
+            })
+            .sum()
+    }
+
+    /// Whether the transaction only writes
+    pub fn is_write(&self) -> bool {
+        self.read() == 0
+    }
+}
+
+/// Views of a transaction log
+pub trait Log {
+    fn log(&self) -> &[Transaction];
+
+    /// Register writes as address and payload
+    fn writes(&self) -> Vec<(u16, Vec<u8>)> {
+        self.log()
+            .iter()
+            .filter(|t| t.is_write())
+            .map(|t| (t.register(), t.payload()))
+            .collect()
+    }
+
+    /// Single-byte view of the register writes: address and first payload
+    /// byte
+    fn byte_writes(&self) -> Vec<(u16, u8)> {
+        self.writes()
+            .into_iter()
+            .map(|(register, data)| (register, data[0]))
+            .collect()
+    }
+
+    /// Register reads as address and length
+    fn reads(&self) -> Vec<(u16, usize)> {
+        self.log()
+            .iter()
+            .filter(|t| !t.is_write())
+            .map(|t| (t.register(), t.read()))
+            .collect()
+    }
+
+    /// Registers written, in order
+    fn registers_written(&self) -> Vec<u16> {
+        self.writes()
+            .into_iter()
+            .map(|(register, _)| register)
+            .collect()
+    }
+
+    /// Registers read, in order
+    fn registers_read(&self) -> Vec<u16> {
+        self.reads()
+            .into_iter()
+            .map(|(register, _)| register)
+            .collect()
+    }
+
+    /// Reads of `register`
+    fn polls(&self, register: u16) -> usize {
+        self.reads().iter().filter(|(r, _)| *r == register).count()
+    }
+
+    /// Transactions as the bytes written and the number of bytes read
+    fn bytes(&self) -> Vec<(Vec<u8>, usize)> {
+        self.log().iter().map(|t| (t.written(), t.read())).collect()
+    }
+}
+
+/// Delay that returns immediately
+pub struct NoDelay;
+
+impl embedded_hal::delay::DelayNs for NoDelay {
+    fn delay_ns(&mut self, _: u32) {}
+}
+
+impl embedded_hal_async::delay::DelayNs for NoDelay {
+    async fn delay_ns(&mut self, _: u32) {}
+}
+
+/// Polls a future that never waits to completion
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
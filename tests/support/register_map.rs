@@ -0,0 +1,341 @@
+//! Plain register file with pluggable device behaviour
+
+use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, NoAcknowledgeSource, Operation};
+
+use super::{Log, Transaction};
+
+/// Register address space of the sensor
+pub type Registers = [u8; 0x300];
+
+/// Copies the bytes starting at `register` into `buf`
+pub fn load(regs: &Registers, register: u16, buf: &mut [u8]) {
+    let start = usize::from(register);
+    buf.copy_from_slice(&regs[start..start + buf.len()]);
+}
+
+/// Stores `data` starting at `register`
+pub fn store(regs: &mut Registers, register: u16, data: &[u8]) {
+    let start = usize::from(register);
+    regs[start..start + data.len()].copy_from_slice(data);
+}
+
+/// Registers covered by a write of `data` starting at `register`, with the
+/// byte each receives
+///
+/// The sensor auto-increments the register address, so a block write acts
+/// as a write to every register it covers.
+pub fn each_byte(register: u16, data: &[u8]) -> impl Iterator<Item = (u16, u8)> + '_ {
+    (register..).zip(data.iter().copied())
+}
+
+/// Clears the interrupt status fields selected by a `SYSTEM__INTERRUPT_CLEAR`
+/// value
+pub fn clear_interrupts(regs: &mut Registers, clear: u8) {
+    for (bit, mask) in [(0x01, 0x07), (0x02, 0x38), (0x04, 0xC0)] {
+        if clear & bit != 0 {
+            regs[0x04F] &= !mask;
+        }
+    }
+}
+
+/// What a [`RegisterMap`] does beyond storing and returning bytes
+///
+/// Every method defaults to plain register file behaviour.
+pub trait Behavior {
+    /// Runs before every transaction to `address`; an error fails it
+    fn transaction(&mut self, regs: &mut Registers, address: u8) -> Result<(), ErrorKind> {
+        let _ = (regs, address);
+        Ok(())
+    }
+
+    /// Answers a read of `buf.len()` bytes starting at `register`
+    fn read(
+        &mut self,
+        regs: &mut Registers,
+        register: u16,
+        buf: &mut [u8],
+    ) -> Result<(), ErrorKind> {
+        load(regs, register, buf);
+        Ok(())
+    }
+
+    /// Handles a write of `data` starting at `register`
+    fn write(&mut self, regs: &mut Registers, register: u16, data: &[u8]) -> Result<(), ErrorKind> {
+        store(regs, register, data);
+        Ok(())
+    }
+}
+
+/// Behaviour of a plain register file
+#[derive(Debug, Default)]
+pub struct Plain;
+
+impl Behavior for Plain {}
+
+/// Register file whose interrupt status clears on writes to
+/// `SYSTEM__INTERRUPT_CLEAR`
+#[derive(Debug, Default)]
+pub struct Clearing;
+
+impl Behavior for Clearing {
+    fn write(&mut self, regs: &mut Registers, register: u16, data: &[u8]) -> Result<(), ErrorKind> {
+        store(regs, register, data);
+        for (register, value) in each_byte(register, data) {
+            if register == 0x015 {
+                clear_interrupts(regs, value);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Sensor whose single-shot samples become ready a number of status polls
+/// after their start command
+#[derive(Debug)]
+pub struct Delayed {
+    /// Status polls a started measurement takes, `None` never finishing
+    pub polls_to_ready: Option<u32>,
+    /// Status polls left until the range and the ALS sample are ready
+    pub range_polls: Option<u32>,
+    pub als_polls: Option<u32>,
+    /// Measurements started so far
+    pub starts: u32,
+}
+
+impl Delayed {
+    pub fn new(polls_to_ready: Option<u32>) -> Self {
+        Self {
+            polls_to_ready,
+            range_polls: None,
+            als_polls: None,
+            starts: 0,
+        }
+    }
+
+    /// Counts a status poll of a running measurement, completing it when due
+    fn poll(&mut self, regs: &mut Registers) {
+        for (polls, flag) in [(&mut self.range_polls, 0x04), (&mut self.als_polls, 0x20)] {
+            if let Some(remaining) = polls {
+                if *remaining == 0 {
+                    regs[0x04F] |= flag;
+                    *polls = None;
+                } else {
+                    *remaining -= 1;
+                }
+            }
+        }
+    }
+}
+
+impl Behavior for Delayed {
+    fn read(
+        &mut self,
+        regs: &mut Registers,
+        register: u16,
+        buf: &mut [u8],
+    ) -> Result<(), ErrorKind> {
+        if register == 0x04F {
+            self.poll(regs);
+        }
+        load(regs, register, buf);
+        Ok(())
+    }
+
+    fn write(&mut self, regs: &mut Registers, register: u16, data: &[u8]) -> Result<(), ErrorKind> {
+        for byte in each_byte(register, data) {
+            match byte {
+                (0x018, 0x01) => {
+                    self.starts += 1;
+                    self.range_polls = self.polls_to_ready;
+                }
+                (0x038, 0x01) => {
+                    self.starts += 1;
+                    self.als_polls = self.polls_to_ready;
+                }
+                (0x015, clear) => clear_interrupts(regs, clear),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Register file behind an I2C bus, answering every address
+///
+/// Logs every transaction, failed ones included, and can fail transactions
+/// on demand.
+pub struct RegisterMap<B = Plain> {
+    pub regs: Registers,
+    pub log: Vec<Transaction>,
+    pub behavior: B,
+    failure: Option<Failure>,
+}
+
+/// Transactions a [`RegisterMap`] fails
+struct Failure {
+    kind: ErrorKind,
+    /// Transactions left to succeed before failing
+    after: u32,
+    /// Transactions left to fail
+    count: u32,
+}
+
+impl RegisterMap {
+    /// Register file of zeroes
+    pub fn new() -> Self {
+        Self::with(Plain)
+    }
+}
+
+impl<B: Behavior> RegisterMap<B> {
+    /// Register file of zeroes with `behavior`
+    pub fn with(behavior: B) -> Self {
+        Self {
+            regs: [0; 0x300],
+            log: Vec::new(),
+            behavior,
+            failure: None,
+        }
+    }
+
+    /// Sets the bytes starting at `register`
+    pub fn set(mut self, register: u16, data: &[u8]) -> Self {
+        store(&mut self.regs, register, data);
+        self
+    }
+
+    /// Bytes starting at `register`
+    pub fn registers(&self, register: u16, len: usize) -> &[u8] {
+        let start = usize::from(register);
+        &self.regs[start..start + len]
+    }
+
+    /// Fails the next `count` transactions with `kind`
+    pub fn fail_next(&mut self, count: u32, kind: ErrorKind) {
+        self.failure = Some(Failure {
+            kind,
+            after: 0,
+            count,
+        });
+    }
+
+    /// Fails every transaction with `kind` once the next `after` succeeded
+    pub fn fail_after(&mut self, after: u32, kind: ErrorKind) {
+        self.failure = Some(Failure {
+            kind,
+            after,
+            count: u32::MAX,
+        });
+    }
+
+    /// Fails every transaction from now on with `kind`
+    pub fn fail(&mut self, kind: ErrorKind) {
+        self.fail_next(u32::MAX, kind);
+    }
+
+    /// Number of transactions so far, failed ones included
+    pub fn transactions(&self) -> usize {
+        self.log.len()
+    }
+
+    fn run(&mut self, address: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+        let transaction = Transaction::record(address, ops);
+        let register = transaction.register();
+        let payload = transaction.payload();
+        let reads = !transaction.is_write();
+        self.log.push(transaction);
+
+        if let Some(failure) = &mut self.failure {
+            if failure.after > 0 {
+                failure.after -= 1;
+            } else {
+                let kind = failure.kind;
+                failure.count -= 1;
+                if failure.count == 0 {
+                    self.failure = None;
+                }
+                return Err(kind);
+            }
+        }
+        self.behavior.transaction(&mut self.regs, address)?;
+        if !reads {
+            return self.behavior.write(&mut self.regs, register, &payload);
+        }
+        let mut register = register;
+        for op in ops.iter_mut() {
+            if let Operation::Read(buf) = op {
+                self.behavior.read(&mut self.regs, register, buf)?;
+                register += buf.len() as u16;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<B: Behavior> Log for RegisterMap<B> {
+    fn log(&self) -> &[Transaction] {
+        &self.log
+    }
+}
+
+impl<B> ErrorType for RegisterMap<B> {
+    type Error = ErrorKind;
+}
+
+impl<B: Behavior> I2c for RegisterMap<B> {
+    fn transaction(&mut self, address: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+        self.run(address, ops)
+    }
+}
+
+impl<B: Behavior> embedded_hal_async::i2c::I2c for RegisterMap<B> {
+    async fn transaction(
+        &mut self,
+        address: u8,
+        ops: &mut [Operation<'_>],
+    ) -> Result<(), ErrorKind> {
+        self.run(address, ops)
+    }
+}
+
+/// 7-bit address of the EEPROM on a [`SharedBus`]
+pub const EEPROM: u8 = 0x50;
+
+/// Sensor at 0x29 sharing its bus with an EEPROM with 16-bit word addresses
+///
+/// Logs the address of every transaction, unanswered ones included.
+pub struct SharedBus {
+    pub sensor: RegisterMap<Delayed>,
+    pub eeprom: RegisterMap,
+    pub addresses: Vec<u8>,
+}
+
+impl SharedBus {
+    /// `sensor` next to a blank EEPROM
+    pub fn new(sensor: RegisterMap<Delayed>) -> Self {
+        Self {
+            sensor,
+            eeprom: RegisterMap::new(),
+            addresses: Vec::new(),
+        }
+    }
+
+    fn run(&mut self, address: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+        self.addresses.push(address);
+        match address {
+            0x29 => self.sensor.run(address, ops),
+            EEPROM => self.eeprom.run(address, ops),
+            _ => Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+        }
+    }
+}
+
+impl ErrorType for SharedBus {
+    type Error = ErrorKind;
+}
+
+impl I2c for SharedBus {
+    fn transaction(&mut self, address: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+        self.run(address, ops)
+    }
+}
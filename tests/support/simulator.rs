@@ -0,0 +1,470 @@
+//! Simulated VL6180X
+//!
+//! Models the register map, single-shot and continuous measurements and the
+//! interrupt status closely enough to drive the driver end to end, and can
+//! inject the faults a real sensor and bus produce.
+
+use std::collections::VecDeque;
+
+use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, NoAcknowledgeSource, Operation};
+use vl6180x::registers::POWER_ON_DEFAULTS;
+use vl6180x::RangeErrorCode;
+
+use super::{each_byte, Log, Transaction};
+
+/// Interrupt mode reporting every new sample
+const NEW_SAMPLE_READY: u8 = 4;
+
+/// Simulated sensor behind an I2C bus
+///
+/// Measurements complete on the first interrupt status read after they were
+/// started; continuous measurements produce a sample on every status read
+/// that finds the previous one acknowledged.
+pub struct SimulatedVl6180x {
+    regs: [u8; 0x300],
+    distance_mm: u8,
+    als_counts: u16,
+    /// Reference channel rate in Mcps (9.7 fixed point)
+    reference_rate: u16,
+    range_running: bool,
+    range_continuous: bool,
+    als_running: bool,
+    als_continuous: bool,
+    transactions: u32,
+    range_starts: u32,
+    /// Transaction numbers that are NACKed
+    nacks: Vec<u32>,
+    /// Error returned by the next transactions and how many are left
+    failures: Option<(ErrorKind, u32)>,
+    /// Error code and number of range measurements left to report it
+    range_errors: Option<(RangeErrorCode, u32)>,
+    stalled: bool,
+    /// Register addresses and the bits flipped on their next read, byte by
+    /// byte
+    corruptions: VecDeque<(u16, Vec<u8>)>,
+    /// Device ready reads a core takes to stop after continuous mode ends
+    stop_polls: u32,
+    /// Device ready reads left until the ranging and the ALS core stop
+    stopping: [Option<u32>; 2],
+    log: Vec<Transaction>,
+}
+
+impl SimulatedVl6180x {
+    /// Sensor right after power-up: power-on defaults, interrupts disabled
+    /// and the fresh-out-of-reset flag set
+    pub fn powered_on() -> Self {
+        let mut sim = Self {
+            regs: [0; 0x300],
+            distance_mm: 50,
+            als_counts: 0x0100,
+            reference_rate: 5 * 128,
+            range_running: false,
+            range_continuous: false,
+            als_running: false,
+            als_continuous: false,
+            transactions: 0,
+            range_starts: 0,
+            nacks: Vec::new(),
+            failures: None,
+            range_errors: None,
+            stalled: false,
+            corruptions: VecDeque::new(),
+            stop_polls: 0,
+            stopping: [None; 2],
+            log: Vec::new(),
+        };
+        sim.load_power_on_state();
+        sim
+    }
+
+    /// Initialized sensor: fresh-out-of-reset flag cleared and both
+    /// interrupts reporting new samples
+    pub fn new() -> Self {
+        let mut sim = Self::powered_on();
+        sim.regs[0x016] = 0x00;
+        sim.regs[0x014] = NEW_SAMPLE_READY | NEW_SAMPLE_READY << 3;
+        sim
+    }
+
+    fn load_power_on_state(&mut self) {
+        self.regs = [0; 0x300];
+        for default in &POWER_ON_DEFAULTS {
+            let start = usize::from(default.address);
+            self.regs[start..start + default.bytes.len()].copy_from_slice(default.bytes);
+        }
+        self.regs[0x000] = 0xB4;
+        self.regs[0x016] = 0x01;
+        self.regs[0x04D] = 0x01;
+        self.regs[0x04E] = 0x01;
+        self.regs[0x119] = 0x01;
+        self.regs[0x212] = 0x29;
+        self.range_running = false;
+        self.range_continuous = false;
+        self.als_running = false;
+        self.als_continuous = false;
+        self.stalled = false;
+        self.stopping = [None; 2];
+    }
+
+    /// Sets the distance of the target
+    pub fn set_distance(&mut self, millimeters: u8) {
+        self.distance_mm = millimeters;
+    }
+
+    /// Sets the raw ALS count measurements report
+    pub fn set_als_counts(&mut self, counts: u16) {
+        self.als_counts = counts;
+    }
+
+    /// Sets the reference channel rate reported by the next range
+    /// measurements, in Mcps (9.7 fixed point)
+    pub fn set_reference_rate(&mut self, rate: u16) {
+        self.reference_rate = rate;
+    }
+
+    /// Reads a register byte
+    pub fn register(&self, address: u16) -> u8 {
+        self.regs[usize::from(address)]
+    }
+
+    /// Sets the bytes starting at `address`, as the sensor itself would
+    pub fn set_registers(&mut self, address: u16, bytes: &[u8]) {
+        let start = usize::from(address);
+        self.regs[start..start + bytes.len()].copy_from_slice(bytes);
+    }
+
+    /// Reads the bytes starting at `address`
+    pub fn registers(&self, address: u16, len: usize) -> &[u8] {
+        let start = usize::from(address);
+        &self.regs[start..start + len]
+    }
+
+    /// Starts continuous ranging and ALS, as an earlier user of the sensor
+    /// would have
+    pub fn start_continuous(&mut self) {
+        self.write(0x018, &[0x03]);
+        self.write(0x038, &[0x03]);
+    }
+
+    /// Makes a core take `polls` reads of its device ready flag to stop
+    /// after continuous measurements end, `u32::MAX` never stopping
+    pub fn set_stop_polls(&mut self, polls: u32) {
+        self.stop_polls = polls;
+    }
+
+    /// Sets the model ID the sensor reports, as another part would
+    pub fn set_model_id(&mut self, id: u8) {
+        self.regs[0x000] = id;
+    }
+
+    /// Moves the sensor to another 7-bit I2C address, as a write to
+    /// `I2C_SLAVE__DEVICE_ADDRESS` would
+    pub fn set_i2c_address(&mut self, address: u8) {
+        self.regs[0x212] = address;
+    }
+
+    /// Number of transactions addressed to the sensor so far, NACKed ones
+    /// included
+    pub fn transactions(&self) -> u32 {
+        self.transactions
+    }
+
+    /// Number of range measurements started so far
+    pub fn range_starts(&self) -> u32 {
+        self.range_starts
+    }
+
+    /// NACKs the transaction `n` transactions from now, 0 being the next one
+    pub fn nack_transaction(&mut self, n: u32) {
+        self.nacks.push(self.transactions + n);
+    }
+
+    /// Fails the next `count` transactions with `kind`, as a wedged or noisy
+    /// bus would
+    pub fn fail_transactions(&mut self, count: u32, kind: ErrorKind) {
+        self.failures = Some((kind, count));
+    }
+
+    /// Reports `code` for the next `k` range measurements
+    pub fn fail_measurements(&mut self, code: RangeErrorCode, k: u32) {
+        self.range_errors = Some((code, k));
+    }
+
+    /// Freezes the sample-ready flags: running measurements never complete.
+    ///
+    /// The stall ends when continuous ranging is stopped or the sensor
+    /// resets.
+    pub fn stall(&mut self) {
+        self.stalled = true;
+    }
+
+    /// Ends a stall: running measurements complete again
+    pub fn resume(&mut self) {
+        self.stalled = false;
+    }
+
+    /// Flips `bits` in the first byte of the next read starting at `address`
+    pub fn corrupt_next_read(&mut self, address: u16, bits: u8) {
+        self.corrupt_next_read_bytes(address, &[bits]);
+    }
+
+    /// Flips `bits` in the bytes of the next read starting at `address`,
+    /// byte by byte; reads queued for the same address are corrupted in
+    /// turn
+    pub fn corrupt_next_read_bytes(&mut self, address: u16, bits: &[u8]) {
+        self.corruptions.push_back((address, bits.to_vec()));
+    }
+
+    /// Resets the sensor as a supply glitch would: every register reverts to
+    /// its power-on value, the fresh-out-of-reset flag is set and running
+    /// measurements stop
+    pub fn spontaneous_reset(&mut self) {
+        self.load_power_on_state();
+    }
+
+    fn interrupt_mode(&self, shift: u8) -> u8 {
+        (self.regs[0x014] >> shift) & 0x07
+    }
+
+    /// Completes the running measurements whose sample can be reported
+    fn advance(&mut self) {
+        if self.stalled {
+            return;
+        }
+        if self.range_running && self.regs[0x04F] & 0x07 == 0 {
+            self.complete_range();
+        }
+        if self.als_running && self.regs[0x04F] & 0x38 == 0 {
+            // With INTERLEAVED_MODE__ENABLE set a range measurement follows
+            // every ALS measurement
+            if self.regs[0x2A3] & 0x01 != 0 {
+                self.complete_range();
+            }
+            self.complete_als();
+        }
+    }
+
+    fn complete_range(&mut self) {
+        let code = match &mut self.range_errors {
+            Some((code, left)) if *left > 0 => {
+                *left -= 1;
+                *code as u8
+            }
+            _ => 0,
+        };
+        self.range_running = self.range_continuous;
+        let ready = u8::from(!self.range_running);
+        self.regs[0x04D] = code << 4 | ready;
+        self.regs[0x062] = if code == 0 { self.distance_mm } else { 255 };
+        self.regs[0x068..0x06A].copy_from_slice(&self.reference_rate.to_be_bytes());
+        if self.interrupt_mode(0) == NEW_SAMPLE_READY {
+            self.regs[0x04F] |= NEW_SAMPLE_READY;
+        }
+    }
+
+    fn complete_als(&mut self) {
+        self.als_running = self.als_continuous;
+        self.regs[0x04E] = u8::from(!self.als_running);
+        self.regs[0x050..0x052].copy_from_slice(&self.als_counts.to_be_bytes());
+        if self.interrupt_mode(3) == NEW_SAMPLE_READY {
+            self.regs[0x04F] |= NEW_SAMPLE_READY << 3;
+        }
+    }
+
+    /// Stops core 0 (ranging) or 1 (ALS) once its stop polls have passed
+    fn stop(&mut self, core: usize) {
+        if self.stop_polls == 0 {
+            self.regs[0x04D + core] |= 0x01;
+        } else {
+            self.stopping[core] = Some(self.stop_polls);
+        }
+    }
+
+    /// Counts a device ready read of a stopping core
+    fn poll_ready(&mut self, core: usize) {
+        if let Some(left) = &mut self.stopping[core] {
+            *left = left.saturating_sub(1);
+            if *left == 0 {
+                self.stopping[core] = None;
+                self.regs[0x04D + core] |= 0x01;
+            }
+        }
+    }
+
+    /// Writes `data` starting at `address`, one register at a time as the
+    /// sensor auto-increments the address
+    fn write(&mut self, address: u16, data: &[u8]) {
+        for (address, value) in each_byte(address, data) {
+            self.write_byte(address, value);
+        }
+    }
+
+    fn write_byte(&mut self, address: u16, value: u8) {
+        match address {
+            0x015 => {
+                for (bit, mask) in [(0x01, 0x07), (0x02, 0x38), (0x04, 0xC0)] {
+                    if value & bit != 0 {
+                        self.regs[0x04F] &= !mask;
+                    }
+                }
+            }
+            0x018 if value & 0x01 != 0 => {
+                if self.range_continuous {
+                    self.range_continuous = false;
+                    self.range_running = false;
+                    self.stalled = false;
+                    self.stop(0);
+                } else {
+                    self.range_starts += 1;
+                    self.range_continuous = value & 0x02 != 0;
+                    self.range_running = true;
+                    self.regs[0x04D] &= !0x01;
+                }
+            }
+            // VHV recalibration completes instantly and clears its bit
+            0x02E => {}
+            0x038 if value & 0x01 != 0 => {
+                if self.als_continuous {
+                    self.als_continuous = false;
+                    self.als_running = false;
+                    self.stop(1);
+                } else {
+                    self.als_continuous = value & 0x02 != 0;
+                    self.als_running = true;
+                    self.regs[0x04E] &= !0x01;
+                }
+            }
+            _ => {
+                self.regs[usize::from(address)] = value;
+            }
+        }
+    }
+
+    fn run(&mut self, address: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+        if address != self.regs[0x212] {
+            return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address));
+        }
+        self.log.push(Transaction::record(address, ops));
+        let transaction = self.transactions;
+        self.transactions += 1;
+        if let Some(index) = self.nacks.iter().position(|&n| n == transaction) {
+            self.nacks.swap_remove(index);
+            return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data));
+        }
+        if let Some((kind, left)) = &mut self.failures {
+            let kind = *kind;
+            *left -= 1;
+            if *left == 0 {
+                self.failures = None;
+            }
+            return Err(kind);
+        }
+
+        match ops {
+            [Operation::Write(reg), Operation::Read(buf)] => {
+                let address = u16::from_be_bytes([reg[0], reg[1]]);
+                match address {
+                    0x04D => self.poll_ready(0),
+                    0x04E => self.poll_ready(1),
+                    0x04F => self.advance(),
+                    _ => {}
+                }
+                let start = usize::from(address);
+                let len = buf.len();
+                buf.copy_from_slice(&self.regs[start..start + len]);
+                if let Some(index) = self.corruptions.iter().position(|(a, _)| *a == address) {
+                    let (_, bits) = self.corruptions.remove(index).unwrap();
+                    buf.iter_mut()
+                        .zip(bits)
+                        .for_each(|(byte, flip)| *byte ^= flip);
+                }
+            }
+            [Operation::Write(reg), Operation::Write(data)] => {
+                self.write(u16::from_be_bytes([reg[0], reg[1]]), data);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+impl ErrorType for SimulatedVl6180x {
+    type Error = ErrorKind;
+}
+
+impl I2c for SimulatedVl6180x {
+    fn transaction(&mut self, address: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+        self.run(address, ops)
+    }
+}
+
+impl embedded_hal_async::i2c::I2c for SimulatedVl6180x {
+    async fn transaction(
+        &mut self,
+        address: u8,
+        ops: &mut [Operation<'_>],
+    ) -> Result<(), ErrorKind> {
+        self.run(address, ops)
+    }
+}
+
+impl Log for SimulatedVl6180x {
+    fn log(&self) -> &[Transaction] {
+        &self.log
+    }
+}
+
+/// Several simulated sensors on one bus, each answering its own address
+///
+/// Logs the address of every transaction, unanswered ones included.
+pub struct Multidrop {
+    pub sensors: Vec<SimulatedVl6180x>,
+    pub addresses: Vec<u8>,
+}
+
+impl Multidrop {
+    /// Sensors at the given 7-bit addresses
+    pub fn new(addresses: &[u8]) -> Self {
+        let sensors = addresses
+            .iter()
+            .map(|&address| {
+                let mut sensor = SimulatedVl6180x::new();
+                sensor.set_i2c_address(address);
+                sensor
+            })
+            .collect();
+        Self {
+            sensors,
+            addresses: Vec::new(),
+        }
+    }
+
+    fn run(&mut self, address: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+        self.addresses.push(address);
+        self.sensors
+            .iter_mut()
+            .find(|sensor| sensor.regs[0x212] == address)
+            .ok_or(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address))?
+            .run(address, ops)
+    }
+}
+
+impl ErrorType for Multidrop {
+    type Error = ErrorKind;
+}
+
+impl I2c for Multidrop {
+    fn transaction(&mut self, address: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+        self.run(address, ops)
+    }
+}
+
+impl embedded_hal_async::i2c::I2c for Multidrop {
+    async fn transaction(
+        &mut self,
+        address: u8,
+        ops: &mut [Operation<'_>],
+    ) -> Result<(), ErrorKind> {
+        self.run(address, ops)
+    }
+}
@@ -0,0 +1,163 @@
+//! The blocking and async paths emit byte-identical transactions
+
+mod support;
+
+use core::time::Duration;
+
+use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+use support::{block_on, NoDelay, RegisterMap};
+use vl6180x::registers::{
+    AlsIntegrationPeriod, InterruptConfigGpio, ModelId, RangeMaxConvergenceTime, RangeStatusBlock,
+};
+use vl6180x::{Device, Direction, Error, ErrorContext};
+
+/// Booted sensor with a valid 100mm range sample ready
+fn bus() -> RegisterMap {
+    RegisterMap::new()
+        .set(0x000, &[0xB4])
+        .set(0x01C, &[0x31])
+        .set(0x040, &[0x00, 0x63])
+        .set(0x04F, &[0x04])
+        .set(0x062, &[100])
+}
+
+/// Sensor failing every transaction with `kind`
+fn failing(kind: ErrorKind) -> RegisterMap {
+    let mut bus = bus();
+    bus.fail(kind);
+    bus
+}
+
+/// Runs `blocking` and `nonblocking` on fresh buses and asserts both return
+/// the same value after emitting the same transactions
+fn assert_parity<T, B, A>(bus: fn() -> RegisterMap, blocking: B, nonblocking: A)
+where
+    T: PartialEq + core::fmt::Debug,
+    B: FnOnce(&mut Device<&mut RegisterMap>) -> T,
+    A: AsyncFnOnce(&mut Device<&mut RegisterMap>) -> T,
+{
+    let mut sync_bus = bus();
+    let sync_result = blocking(&mut Device::new_with_address(&mut sync_bus, 0x30));
+
+    let mut async_bus = bus();
+    let async_result = block_on(nonblocking(&mut Device::new_with_address(
+        &mut async_bus,
+        0x30,
+    )));
+
+    assert_eq!(sync_result, async_result);
+    assert!(!sync_bus.log.is_empty());
+    assert_eq!(sync_bus.log, async_bus.log);
+    assert_eq!(sync_bus.regs, async_bus.regs);
+}
+
+#[test]
+fn register_reads() {
+    assert_parity(
+        bus,
+        |dev| dev.read_register::<RangeStatusBlock>(),
+        async |dev| dev.read_register_async::<RangeStatusBlock>().await,
+    );
+    assert_parity(
+        bus,
+        |dev| dev.read_block::<3>(0x004D),
+        async |dev| dev.read_block_async::<3>(0x004D).await,
+    );
+    assert_parity(
+        bus,
+        |dev| {
+            let mut buf = [0; 1];
+            dev.read_register_into::<ModelId>(&mut buf).map(|()| buf)
+        },
+        async |dev| {
+            let mut buf = [0; 1];
+            dev.read_register_into_async::<ModelId>(&mut buf)
+                .await
+                .map(|()| buf)
+        },
+    );
+}
+
+#[test]
+fn register_writes() {
+    let convergence = RangeMaxConvergenceTime {
+        time: Duration::from_millis(30),
+    };
+    assert_parity(
+        bus,
+        |dev| dev.write_register(convergence),
+        async |dev| dev.write_register_async(convergence).await,
+    );
+    assert_parity(
+        bus,
+        |dev| dev.write_block(0x0040, &[0x00, 0x31]),
+        async |dev| dev.write_block_async(0x0040, &[0x00, 0x31]).await,
+    );
+    assert_parity(
+        bus,
+        |dev| {
+            dev.modify_register(|period: &mut AlsIntegrationPeriod| {
+                period.period *= 2;
+            })
+        },
+        async |dev| {
+            dev.modify_register_async(|period: &mut AlsIntegrationPeriod| {
+                period.period *= 2;
+            })
+            .await
+        },
+    );
+}
+
+#[test]
+fn rejected_writes_touch_nothing() {
+    let too_long = AlsIntegrationPeriod {
+        period: Duration::from_millis(600),
+    };
+    let mut bus = bus();
+    let sync_result = Device::new(&mut bus).write_register(too_long);
+    let async_result = block_on(Device::new(&mut bus).write_register_async(too_long));
+
+    assert_eq!(
+        sync_result,
+        Err(Error::SerializationError(ErrorContext::register(
+            0x040,
+            Direction::Write
+        )))
+    );
+    assert_eq!(async_result, sync_result);
+    assert!(bus.log.is_empty());
+}
+
+#[test]
+fn bus_errors_map_alike() {
+    assert_parity(
+        || failing(ErrorKind::ArbitrationLoss),
+        |dev| dev.read_register::<InterruptConfigGpio>(),
+        async |dev| dev.read_register_async::<InterruptConfigGpio>().await,
+    );
+    assert_parity(
+        || failing(ErrorKind::ArbitrationLoss),
+        |dev| dev.write_block(0x0014, &[0x04]),
+        async |dev| dev.write_block_async(0x0014, &[0x04]).await,
+    );
+}
+
+#[test]
+fn nack_while_booting_maps_alike() {
+    let timeout = Duration::from_millis(2);
+    assert_parity(
+        || failing(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+        |dev| dev.wait_for_boot(&mut NoDelay, timeout),
+        async |dev| dev.wait_for_boot_async(&mut NoDelay, timeout).await,
+    );
+}
+
+#[test]
+fn measurements() {
+    assert_parity(
+        bus,
+        |dev| dev.measure_range_single(&mut NoDelay),
+        async |dev| dev.measure_range_single_async(&mut NoDelay).await,
+    );
+}
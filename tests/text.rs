@@ -0,0 +1,93 @@
+//! Rendering readings into fixed buffers
+
+use measurements::Length;
+use vl6180x::text::{format_als, format_lux, format_mm, format_range, BufferTooSmall};
+use vl6180x::{AlsErrorCode, AlsReading, Luminance, RangeErrorCode, RangeReading};
+
+fn mm(value: f64) -> Length {
+    Length::from_millimeters(value)
+}
+
+fn lux(value: f32) -> Luminance {
+    Luminance::from_lux(value)
+}
+
+#[test]
+fn distances_round_to_whole_millimeters() {
+    let mut buf = [0; 16];
+    assert_eq!(format_mm(&mm(123.0), &mut buf), Ok("123 mm"));
+    assert_eq!(format_mm(&mm(0.0), &mut buf), Ok("0 mm"));
+    assert_eq!(format_mm(&mm(41.4), &mut buf), Ok("41 mm"));
+    assert_eq!(format_mm(&mm(41.5), &mut buf), Ok("42 mm"));
+    assert_eq!(format_mm(&mm(255.0), &mut buf), Ok("255 mm"));
+    assert_eq!(format_mm(&mm(-7.5), &mut buf), Ok("-8 mm"));
+}
+
+#[test]
+fn light_levels_round_half_up_on_the_last_decimal() {
+    let mut buf = [0; 16];
+    assert_eq!(format_lux(&lux(45.6), &mut buf, 1), Ok("45.6 lx"));
+    assert_eq!(format_lux(&lux(45.64), &mut buf, 1), Ok("45.6 lx"));
+    assert_eq!(format_lux(&lux(45.65), &mut buf, 1), Ok("45.7 lx"));
+    assert_eq!(format_lux(&lux(45.95), &mut buf, 1), Ok("46.0 lx"));
+    assert_eq!(format_lux(&lux(0.5), &mut buf, 0), Ok("1 lx"));
+    assert_eq!(format_lux(&lux(0.004), &mut buf, 2), Ok("0.00 lx"));
+    assert_eq!(format_lux(&lux(0.005), &mut buf, 2), Ok("0.01 lx"));
+    assert_eq!(format_lux(&lux(1.5), &mut buf, 3), Ok("1.500 lx"));
+    assert_eq!(format_lux(&lux(20_800.0), &mut buf, 0), Ok("20800 lx"));
+}
+
+#[test]
+fn decimals_stop_at_the_millilux() {
+    let mut buf = [0; 16];
+    assert_eq!(format_lux(&lux(1.2345), &mut buf, 6), Ok("1.235 lx"));
+}
+
+#[test]
+fn range_readings_render_compactly() {
+    let mut buf = [0; 16];
+    let valid = RangeReading::Valid(mm(57.0));
+    assert_eq!(format_range(&valid, &mut buf), Ok("57 mm"));
+    assert_eq!(
+        format_range(&RangeReading::NoTarget, &mut buf),
+        Ok("no target")
+    );
+    let failed = RangeReading::Failed(RangeErrorCode::MaxConvergence);
+    assert_eq!(format_range(&failed, &mut buf), Ok("err 7"));
+    let failed = RangeReading::Failed(RangeErrorCode::RangingOverflow);
+    assert_eq!(format_range(&failed, &mut buf), Ok("err 15"));
+}
+
+#[test]
+fn als_readings_render_compactly() {
+    let mut buf = [0; 16];
+    let valid = AlsReading::Valid(lux(312.25));
+    assert_eq!(format_als(&valid, &mut buf, 1), Ok("312.3 lx"));
+    assert_eq!(
+        format_als(&AlsReading::Saturated, &mut buf, 1),
+        Ok("saturated")
+    );
+    assert_eq!(format_als(&AlsReading::Dark, &mut buf, 1), Ok("dark"));
+    let failed = AlsReading::Failed(AlsErrorCode::Underflow);
+    assert_eq!(format_als(&failed, &mut buf, 1), Ok("err 2"));
+}
+
+#[test]
+fn text_must_fit_the_buffer() {
+    let mut buf = [0; 6];
+    assert_eq!(format_mm(&mm(123.0), &mut buf), Ok("123 mm"));
+    assert_eq!(format_mm(&mm(1234.0), &mut buf), Err(BufferTooSmall));
+    assert_eq!(format_lux(&lux(45.6), &mut buf, 1), Err(BufferTooSmall));
+    assert_eq!(
+        format_range(&RangeReading::NoTarget, &mut buf),
+        Err(BufferTooSmall)
+    );
+    assert_eq!(format_mm(&mm(1.0), &mut []), Err(BufferTooSmall));
+}
+
+#[test]
+fn only_the_rendered_prefix_is_written() {
+    let mut buf = [b'#'; 10];
+    assert_eq!(format_mm(&mm(8.0), &mut buf), Ok("8 mm"));
+    assert_eq!(&buf[4..], b"######");
+}
@@ -0,0 +1,100 @@
+//! Per-device timeouts used by the wait and poll helpers
+
+mod support;
+
+use core::time::Duration;
+
+use support::{Log, NoDelay, RegisterMap};
+use vl6180x::{Device, Error, Timeouts};
+
+/// Sensor that never reports a sample, never boots and never stops
+fn bus() -> RegisterMap {
+    RegisterMap::new()
+}
+
+const INTERRUPT_STATUS: u16 = 0x004F;
+const FIRMWARE_BOOTUP: u16 = 0x0119;
+const STATUS: u16 = 0x004D;
+
+fn ms(value: u64) -> Duration {
+    Duration::from_millis(value)
+}
+
+fn timeouts() -> Timeouts {
+    Timeouts {
+        range: ms(10),
+        als: ms(20),
+        boot: ms(5),
+    }
+}
+
+#[test]
+fn defaults_cover_worst_case_measurements() {
+    let defaults = Timeouts::default();
+    assert_eq!(defaults.range, Duration::from_micros(105_894));
+    assert_eq!(defaults.als, ms(768));
+    assert_eq!(defaults.boot, Duration::from_micros(1_500));
+
+    let mut bus = bus();
+    assert_eq!(Device::new(&mut bus).timeouts(), defaults);
+}
+
+#[test]
+fn default_range_timeout_bounds_polling() {
+    let mut bus = bus();
+    let result = Device::new(&mut bus).measure_range_single(&mut NoDelay);
+
+    assert_eq!(result, Err(Error::Timeout));
+    assert_eq!(bus.polls(INTERRUPT_STATUS), 105);
+}
+
+#[test]
+fn stored_timeouts_are_honored() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.set_timeouts(timeouts());
+    assert_eq!(dev.timeouts(), timeouts());
+
+    assert_eq!(dev.measure_range_single(&mut NoDelay), Err(Error::Timeout));
+    assert_eq!(dev.measure_als_single(&mut NoDelay), Err(Error::Timeout));
+    assert_eq!(dev.wait_for_boot(&mut NoDelay, None), Err(Error::Timeout));
+    let _ = dev.release();
+
+    assert_eq!(bus.polls(INTERRUPT_STATUS), 10 + 20);
+    assert_eq!(bus.polls(FIRMWARE_BOOTUP), 6);
+}
+
+#[test]
+fn per_call_timeouts_override_the_stored_ones() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.set_timeouts(timeouts());
+
+    assert_eq!(
+        dev.measure_range_single_within(&mut NoDelay, ms(3)),
+        Err(Error::Timeout)
+    );
+    assert_eq!(
+        dev.measure_als_single_within(&mut NoDelay, ms(4)),
+        Err(Error::Timeout)
+    );
+    assert_eq!(dev.wait_for_boot(&mut NoDelay, ms(2)), Err(Error::Timeout));
+    let _ = dev.release();
+
+    assert_eq!(bus.polls(INTERRUPT_STATUS), 3 + 4);
+    assert_eq!(bus.polls(FIRMWARE_BOOTUP), 3);
+}
+
+#[test]
+fn wait_until_ready_defaults_to_the_longer_measurement_timeout() {
+    let mut bus = bus();
+    let mut dev = Device::new(&mut bus);
+    dev.set_timeouts(timeouts());
+
+    assert_eq!(
+        dev.wait_until_ready(&mut NoDelay, None),
+        Err(Error::Timeout)
+    );
+    let _ = dev.release();
+    assert_eq!(bus.polls(STATUS), 21);
+}
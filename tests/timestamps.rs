@@ -0,0 +1,177 @@
+//! Timestamping samples with a user-supplied clock
+
+mod support;
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use embedded_hal::i2c::ErrorKind;
+use measurements::Length;
+use support::{block_on, load, store, Behavior, NoDelay, RegisterMap, Registers};
+use vl6180x::clock::{Clock, Timestamped};
+use vl6180x::{Device, RangeReading};
+
+/// Fake monotonic clock advanced by the simulated bus
+struct FakeClock(AtomicU64);
+
+impl FakeClock {
+    const fn new() -> Self {
+        Self(AtomicU64::new(0))
+    }
+
+    fn tick(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl Clock for FakeClock {
+    fn now_millis(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Sensor whose samples become ready on the third status poll
+///
+/// Every transaction takes 1ms on the fake clock.
+struct Ticking {
+    clock: &'static FakeClock,
+    polls: u32,
+}
+
+impl Behavior for Ticking {
+    fn transaction(&mut self, _: &mut Registers, _: u8) -> Result<(), ErrorKind> {
+        self.clock.tick();
+        Ok(())
+    }
+
+    fn read(
+        &mut self,
+        regs: &mut Registers,
+        register: u16,
+        buf: &mut [u8],
+    ) -> Result<(), ErrorKind> {
+        if register == 0x04F {
+            self.polls += 1;
+            if self.polls == 3 {
+                regs[0x04F] = 0x24;
+            }
+        }
+        load(regs, register, buf);
+        Ok(())
+    }
+
+    fn write(&mut self, regs: &mut Registers, register: u16, data: &[u8]) -> Result<(), ErrorKind> {
+        store(regs, register, data);
+        match register {
+            0x018 | 0x038 => self.polls = 0,
+            0x015 => regs[0x04F] = 0,
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+fn bus(clock: &'static FakeClock) -> RegisterMap<Ticking> {
+    RegisterMap::with(Ticking { clock, polls: 0 })
+        .set(0x040, &[0x00, 0x63])
+        .set(0x04D, &[0x01])
+        .set(0x062, &[42])
+}
+
+fn valid() -> RangeReading {
+    RangeReading::Valid(Length::from_millimeters(42.0))
+}
+
+#[test]
+fn range_sample_is_stamped_when_ready_is_observed() {
+    static CLOCK: FakeClock = FakeClock::new();
+    let mut dev = Device::new(bus(&CLOCK));
+    dev.set_clock(&CLOCK);
+
+    // Start at 1ms, polls at 2ms, 3ms and 4ms
+    let reading = dev.read_range_timestamped(&mut NoDelay).unwrap();
+    assert_eq!(
+        reading,
+        Timestamped {
+            value: valid(),
+            timestamp: Some(4)
+        }
+    );
+    assert_eq!(dev.last_sample_time(), Some(4));
+    assert!(CLOCK.now_millis() > 4);
+
+    // The next sample is stamped afresh
+    let reading = dev.read_range_timestamped(&mut NoDelay).unwrap();
+    assert_eq!(reading.timestamp, Some(11));
+}
+
+#[test]
+fn als_sample_is_stamped() {
+    static CLOCK: FakeClock = FakeClock::new();
+    let mut dev = Device::new(bus(&CLOCK));
+    dev.set_clock(&CLOCK);
+
+    let reading = dev.read_als_timestamped(&mut NoDelay).unwrap();
+    assert_eq!(reading.timestamp, Some(4));
+}
+
+#[test]
+fn plain_helpers_note_the_sample_time() {
+    static CLOCK: FakeClock = FakeClock::new();
+    let mut dev = Device::new(bus(&CLOCK));
+    dev.set_clock(&CLOCK);
+
+    dev.measure_range_single(&mut NoDelay).unwrap();
+    assert_eq!(dev.last_sample_time(), Some(4));
+}
+
+#[test]
+fn without_a_clock_nothing_is_stamped() {
+    static CLOCK: FakeClock = FakeClock::new();
+    let mut dev = Device::new(bus(&CLOCK));
+
+    let reading = dev.read_range_timestamped(&mut NoDelay).unwrap();
+    assert_eq!(
+        reading,
+        Timestamped {
+            value: valid(),
+            timestamp: None
+        }
+    );
+    assert_eq!(dev.last_sample_time(), None);
+
+    dev.set_clock(&CLOCK);
+    dev.read_range(&mut NoDelay).unwrap();
+    dev.remove_clock();
+    assert_eq!(dev.last_sample_time(), None);
+    assert_eq!(
+        dev.read_als_timestamped(&mut NoDelay).unwrap().timestamp,
+        None
+    );
+}
+
+#[test]
+fn clock_survives_into_parts() {
+    static CLOCK: FakeClock = FakeClock::new();
+    let mut dev = Device::new(bus(&CLOCK));
+    dev.set_clock(&CLOCK);
+    let (bus, state) = dev.into_parts();
+    let mut dev = Device::from_parts(bus, state);
+
+    assert_eq!(
+        dev.read_range_timestamped(&mut NoDelay).unwrap().timestamp,
+        Some(4)
+    );
+}
+
+#[test]
+fn async_matches_blocking() {
+    static CLOCK: FakeClock = FakeClock::new();
+    let mut dev = Device::new(bus(&CLOCK));
+    dev.set_clock(&CLOCK);
+
+    let range = block_on(dev.read_range_timestamped_async(&mut NoDelay)).unwrap();
+    assert_eq!(range.timestamp, Some(4));
+    let before = CLOCK.now_millis();
+    let als = block_on(dev.read_als_timestamped_async(&mut NoDelay)).unwrap();
+    assert_eq!(als.timestamp, Some(before + 4));
+}
@@ -0,0 +1,86 @@
+//! The combined result reads stay within their documented transaction budget
+
+mod support;
+
+use measurements::Length;
+use support::{NoDelay, RegisterMap};
+use vl6180x::{Device, Direction, Error, ErrorContext, Luminance, RangeErrorCode};
+
+fn bus() -> RegisterMap {
+    RegisterMap::new()
+}
+
+#[test]
+fn range_quick_is_one_transaction() {
+    let mut bus = bus();
+    bus.regs[0x04D] = 0x01;
+    bus.regs[0x062] = 87;
+
+    let mut sensor = Device::new(&mut bus);
+    assert_eq!(
+        sensor.read_range_quick(),
+        Ok((RangeErrorCode::NoError, Length::from_millimeters(87.0)))
+    );
+    sensor.release();
+    assert_eq!(bus.transactions(), 1);
+}
+
+#[test]
+fn range_quick_reports_error_code() {
+    let mut bus = bus();
+    bus.regs[0x04D] = 0x71;
+    bus.regs[0x062] = 255;
+
+    let reading = Device::new(&mut bus).read_range_quick();
+    assert_eq!(
+        reading,
+        Ok((
+            RangeErrorCode::MaxConvergence,
+            Length::from_millimeters(255.0)
+        ))
+    );
+}
+
+#[test]
+fn range_quick_rejects_undefined_code() {
+    let mut bus = bus();
+    bus.regs[0x04D] = 0x91;
+    assert_eq!(
+        Device::new(&mut bus).read_range_quick(),
+        Err(Error::DeserializationError(ErrorContext::register(
+            0x04D,
+            Direction::Read
+        )))
+    );
+}
+
+#[cfg(feature = "bus-stats")]
+#[test]
+fn range_quick_bus_stats() {
+    let mut bus = bus();
+    let mut sensor = Device::new(&mut bus);
+    sensor.read_range_quick().unwrap();
+
+    let stats = sensor.stats();
+    assert_eq!(stats.transactions(), 1);
+    assert_eq!(stats.bytes_read, 22);
+}
+
+#[test]
+fn als_single_reads_result_as_one_block() {
+    let mut bus = bus();
+    bus.regs[0x03F] = 0x46;
+    bus.regs[0x040..0x042].copy_from_slice(&[0x00, 0x63]);
+    bus.regs[0x04E] = 0x01;
+    bus.regs[0x04F] = 0x20;
+    bus.regs[0x050..0x052].copy_from_slice(&1000u16.to_be_bytes());
+
+    let mut sensor = Device::new(&mut bus);
+    assert_eq!(
+        sensor.measure_als_single(&mut NoDelay),
+        Ok(Luminance { lux: 320.0 / 1.01 })
+    );
+    sensor.release();
+    // Start, one status poll, result block, clear, gain and integration period
+    assert_eq!(bus.transactions(), 6);
+}
@@ -0,0 +1,76 @@
+//! Probing constructors against present, absent and foreign devices
+
+mod support;
+
+use support::{block_on, SimulatedVl6180x};
+use vl6180x::registers::ModelId;
+use vl6180x::{Device, Error};
+
+#[test]
+fn present_sensor_is_accepted() {
+    let mut sim = SimulatedVl6180x::new();
+    let Ok(mut dev) = Device::try_new(&mut sim) else {
+        panic!("the sensor answers at 0x29");
+    };
+    assert_eq!(dev.address(), 0x29);
+    assert!(dev.read_register::<ModelId>().is_ok());
+    let _ = dev.release();
+    assert_eq!(sim.transactions(), 2);
+}
+
+#[test]
+fn present_sensor_at_custom_address_is_accepted() {
+    let mut sim = SimulatedVl6180x::new();
+    sim.set_i2c_address(0x30);
+    let Ok(dev) = Device::try_new_with_address(&mut sim, 0x30) else {
+        panic!("the sensor answers at 0x30");
+    };
+    assert_eq!(dev.address(), 0x30);
+    let _ = dev.release();
+}
+
+#[test]
+fn absent_sensor_returns_the_bus() {
+    let mut sim = SimulatedVl6180x::new();
+    sim.set_i2c_address(0x30);
+    let Err((bus, error)) = Device::try_new(&mut sim) else {
+        panic!("nothing answers at 0x29");
+    };
+    assert!(matches!(error, Error::BusError(_)));
+
+    let Ok(dev) = Device::try_new_with_address(bus, 0x30) else {
+        panic!("the returned bus still reaches the sensor");
+    };
+    let _ = dev.release();
+    assert_eq!(sim.transactions(), 1);
+}
+
+#[test]
+fn wrong_model_returns_the_bus() {
+    let mut sim = SimulatedVl6180x::new();
+    sim.set_model_id(0xAA);
+    let Err((bus, error)) = Device::try_new(&mut sim) else {
+        panic!("0xAA is not a VL6180X");
+    };
+    assert!(matches!(error, Error::UnexpectedModel(0xAA)));
+
+    let mut dev = Device::new(bus);
+    assert!(dev.read_register::<ModelId>().is_ok());
+    let _ = dev.release();
+}
+
+#[test]
+fn async_constructors_probe_the_same_way() {
+    let mut sim = SimulatedVl6180x::new();
+    let Ok(dev) = block_on(Device::try_new_async(&mut sim)) else {
+        panic!("the sensor answers at 0x29");
+    };
+    let _ = dev.release();
+
+    sim.set_model_id(0xAA);
+    let result = block_on(Device::try_new_async(&mut sim));
+    assert!(matches!(result, Err((_, Error::UnexpectedModel(0xAA)))));
+
+    let result = block_on(Device::try_new_with_address_async(&mut sim, 0x30));
+    assert!(matches!(result, Err((_, Error::BusError(_)))));
+}
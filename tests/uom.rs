@@ -0,0 +1,85 @@
+//! Conversions into `uom` quantities
+#![cfg(feature = "uom")]
+
+mod support;
+
+use core::time::Duration;
+
+use measurements::Length;
+use support::{NoDelay, SimulatedVl6180x};
+use uom::si::f32 as si;
+use uom::si::length::{meter, millimeter};
+use vl6180x::units::{length, lux, AsUom, Illuminance};
+use vl6180x::{AlsErrorCode, AlsGain, AlsReading, Device, Luminance, RangeErrorCode, RangeReading};
+
+#[test]
+fn every_range_value_agrees_within_rounding() {
+    for mm in 0..=u8::MAX {
+        let distance = Length::from_millimeters(mm.into());
+        let converted = distance.as_uom();
+        assert!((converted.get::<millimeter>() - f32::from(mm)).abs() < 1e-4);
+        assert!((f64::from(converted.get::<meter>()) - distance.as_meters()).abs() < 1e-7);
+        assert!((length(converted).as_millimeters() - distance.as_millimeters()).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn fractional_lengths_agree_within_rounding() {
+    let distance = Length::from_millimeters(12.345_678_9);
+    let converted = distance.as_uom();
+    assert!((f64::from(converted.get::<millimeter>()) - distance.as_millimeters()).abs() < 1e-5);
+    assert!((length(converted).as_millimeters() - distance.as_millimeters()).abs() < 1e-5);
+}
+
+#[test]
+fn light_levels_agree_within_rounding() {
+    for gain in [AlsGain::Gain1, AlsGain::Gain10, AlsGain::Gain40] {
+        for counts in [0, 1, 100, 12_345, u16::MAX - 1] {
+            let light = Luminance::from_counts(counts, gain, Duration::from_millis(100));
+            let converted: Illuminance = light.into();
+            assert_eq!(converted.get::<lux>(), light.lux);
+            assert_eq!(Luminance::from(converted), light);
+        }
+    }
+}
+
+#[test]
+fn uom_light_level_converts_back() {
+    let light = si::Luminance::new::<lux>(123.456);
+    assert_eq!(Luminance::from(light), Luminance::from_millilux(123_456));
+}
+
+#[test]
+fn only_valid_readings_convert() {
+    let valid = RangeReading::new(RangeErrorCode::NoError, Length::from_millimeters(42.0));
+    assert_eq!(valid.ok_uom(), Some(si::Length::new::<millimeter>(42.0)));
+    let no_target = RangeReading::new(
+        RangeErrorCode::MaxConvergence,
+        Length::from_millimeters(255.0),
+    );
+    assert_eq!(no_target.ok_uom(), None);
+
+    let light = AlsReading::new(
+        AlsErrorCode::NoError,
+        100,
+        AlsGain::Gain1,
+        Duration::from_millis(100),
+    );
+    assert_eq!(light.ok_uom(), Some(Illuminance::new::<lux>(32.0 / 1.01)));
+    let dark = AlsReading::new(
+        AlsErrorCode::Underflow,
+        0,
+        AlsGain::Gain1,
+        Duration::from_millis(100),
+    );
+    assert_eq!(dark.ok_uom(), None);
+}
+
+#[test]
+fn measured_distance_converts() {
+    let mut sim = SimulatedVl6180x::new();
+    sim.set_distance(73);
+    let mut dev = Device::new(&mut sim);
+    let distance = dev.measure_range_single(&mut NoDelay).unwrap().as_uom();
+    assert!((distance.get::<millimeter>() - 73.0).abs() < 1e-4);
+}
@@ -0,0 +1,177 @@
+//! Velocity estimation over synthetic trajectories
+
+use measurements::Length;
+use vl6180x::velocity::{VelocityConfig, VelocityEstimate, VelocityEstimator};
+use vl6180x::{RangeErrorCode, RangeReading};
+
+/// Reading of a target at `mm`, quantized to the sensor's 1mm resolution
+fn at(mm: f64) -> RangeReading {
+    RangeReading::Valid(Length::from_millimeters(mm.round()))
+}
+
+/// Feeds a reading every `interval_ms` from `position(t)` with `t` in
+/// seconds, returning the last estimate
+fn track(
+    estimator: &mut VelocityEstimator,
+    start_ms: u32,
+    samples: u32,
+    interval_ms: u32,
+    position: impl Fn(f64) -> f64,
+) -> VelocityEstimate {
+    let mut estimate = None;
+    for i in 0..samples {
+        let tick = start_ms + i * interval_ms;
+        estimate = estimator.feed(tick, at(position(tick as f64 / 1000.0)));
+    }
+    estimate.unwrap()
+}
+
+#[test]
+fn constant_velocity_is_followed() {
+    let mut estimator = VelocityEstimator::new(VelocityConfig::default());
+    let estimate = track(&mut estimator, 0, 40, 20, |t| 180.0 - 150.0 * t);
+
+    assert!((estimate.mm_per_s + 150.0).abs() < 5.0, "{estimate:?}");
+    assert!((estimate.closing_speed() - 150.0).abs() < 5.0);
+    assert!(estimate.confidence > 0.99);
+}
+
+#[test]
+fn quantization_is_smoothed_out() {
+    // 30mm/s sampled every 10ms moves a third of a millimeter per reading
+    let mut estimator = VelocityEstimator::new(VelocityConfig::default());
+    let mut worst: f64 = 0.0;
+    for i in 0..100 {
+        let tick = i * 10;
+        let estimate = estimator
+            .feed(tick, at(100.0 - 0.03 * tick as f64))
+            .unwrap();
+        if i >= 50 {
+            worst = worst.max((estimate.mm_per_s + 30.0).abs());
+        }
+    }
+    assert!(worst < 15.0, "{worst}");
+}
+
+#[test]
+fn stop_decays_to_zero() {
+    let mut estimator = VelocityEstimator::new(VelocityConfig::default());
+    track(&mut estimator, 0, 25, 20, |t| 150.0 - 200.0 * t);
+    let estimate = track(&mut estimator, 500, 40, 20, |_| 50.0);
+
+    assert!(estimate.mm_per_s.abs() < 2.0, "{estimate:?}");
+    assert!((estimate.distance_mm - 50.0).abs() < 0.5);
+}
+
+#[test]
+fn reversal_changes_sign() {
+    let mut estimator = VelocityEstimator::new(VelocityConfig::default());
+    let approaching = track(&mut estimator, 0, 25, 20, |t| 150.0 - 200.0 * t);
+    assert!(approaching.mm_per_s < -190.0);
+
+    let leaving = track(&mut estimator, 500, 30, 20, |t| 50.0 + 100.0 * (t - 0.5));
+    assert!((leaving.mm_per_s - 100.0).abs() < 5.0, "{leaving:?}");
+    assert_eq!(leaving.closing_speed(), 0.0);
+}
+
+#[test]
+fn first_reading_acquires_a_standing_target() {
+    let mut estimator = VelocityEstimator::new(VelocityConfig::default());
+    assert_eq!(estimator.feed(0, RangeReading::NoTarget), None);
+    assert_eq!(
+        estimator.feed(10, at(80.0)),
+        Some(VelocityEstimate {
+            distance_mm: 80.0,
+            mm_per_s: 0.0,
+            confidence: 0.0,
+        })
+    );
+
+    let mut confidence = 0.0;
+    for i in 1..10 {
+        let estimate = estimator.feed(10 + i * 20, at(80.0)).unwrap();
+        assert!(estimate.confidence > confidence);
+        confidence = estimate.confidence;
+    }
+}
+
+#[test]
+fn dropouts_are_bridged() {
+    let mut estimator = VelocityEstimator::new(VelocityConfig::default());
+    track(&mut estimator, 0, 25, 20, |t| 150.0 - 200.0 * t);
+    let before = estimator.estimate().unwrap();
+
+    assert_eq!(estimator.feed(500, RangeReading::NoTarget), Some(before));
+    assert_eq!(
+        estimator.feed(
+            520,
+            RangeReading::Failed(RangeErrorCode::EarlyConvergenceEstimate)
+        ),
+        Some(before)
+    );
+    assert_eq!(estimator.feed(540, RangeReading::NoTarget), Some(before));
+
+    // The reading after the gap continues the trajectory
+    let after = estimator.feed(560, at(150.0 - 200.0 * 0.56)).unwrap();
+    assert!((after.mm_per_s + 200.0).abs() < 10.0, "{after:?}");
+    assert!(after.confidence > before.confidence);
+}
+
+#[test]
+fn target_is_lost_after_too_many_dropouts() {
+    let mut estimator = VelocityEstimator::new(VelocityConfig::default());
+    track(&mut estimator, 0, 10, 20, |_| 60.0);
+    for tick in [200, 220] {
+        assert!(estimator.feed(tick, RangeReading::NoTarget).is_some());
+    }
+    assert_eq!(estimator.feed(240, RangeReading::NoTarget), None);
+
+    // A new target starts from scratch
+    let estimate = estimator.feed(260, at(30.0)).unwrap();
+    assert_eq!(estimate.mm_per_s, 0.0);
+    assert_eq!(estimate.confidence, 0.0);
+}
+
+#[test]
+fn long_gap_starts_over() {
+    let mut estimator = VelocityEstimator::new(VelocityConfig::default());
+    track(&mut estimator, 0, 10, 20, |t| 150.0 - 200.0 * t);
+
+    let estimate = estimator.feed(180 + 251, at(20.0)).unwrap();
+    assert_eq!(estimate.mm_per_s, 0.0);
+    assert_eq!(estimate.distance_mm, 20.0);
+}
+
+#[test]
+fn duplicate_ticks_are_ignored() {
+    let mut estimator = VelocityEstimator::new(VelocityConfig::default());
+    estimator.feed(0, at(50.0));
+    let estimate = estimator.feed(0, at(90.0)).unwrap();
+    assert_eq!(estimate.distance_mm, 50.0);
+}
+
+#[test]
+fn tick_may_wrap_around() {
+    let mut wrapping = VelocityEstimator::new(VelocityConfig::default());
+    let mut plain = VelocityEstimator::new(VelocityConfig::default());
+    for i in 0..20u32 {
+        let reading = at(150.0 - 2.0 * i as f64);
+        let wrapped = wrapping.feed((u32::MAX - 100).wrapping_add(i * 20), reading);
+        assert_eq!(wrapped, plain.feed(i * 20, reading));
+    }
+}
+
+#[test]
+fn less_smoothing_reacts_faster() {
+    let run = |smoothing| {
+        let mut estimator = VelocityEstimator::new(VelocityConfig {
+            smoothing,
+            ..VelocityConfig::default()
+        });
+        track(&mut estimator, 0, 6, 20, |t| 150.0 - 200.0 * t).mm_per_s
+    };
+
+    assert!(run(0.5) < run(0.9));
+    // Without smoothing the estimate is the difference quotient
+    assert_eq!(run(0.0), -200.0);
+}
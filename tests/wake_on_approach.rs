@@ -0,0 +1,222 @@
+//! Arming wake on approach and resuming normal ranging
+
+mod support;
+
+use core::time::Duration;
+
+use embedded_hal::i2c::ErrorKind;
+use measurements::Length;
+use support::{block_on, each_byte, load, store, Behavior, Log, NoDelay, RegisterMap, Registers};
+use vl6180x::{
+    Access, Device, Direction, Error, ErrorContext, OperationStep, RangeInterrupt, RangeSchedule,
+};
+
+/// Ranging core starting and stopping with SYSRANGE__START
+struct Ranging {
+    running: bool,
+}
+
+impl Behavior for Ranging {
+    fn read(
+        &mut self,
+        regs: &mut Registers,
+        register: u16,
+        buf: &mut [u8],
+    ) -> Result<(), ErrorKind> {
+        regs[0x04D] = u8::from(!self.running);
+        load(regs, register, buf);
+        Ok(())
+    }
+
+    fn write(&mut self, regs: &mut Registers, register: u16, data: &[u8]) -> Result<(), ErrorKind> {
+        store(regs, register, data);
+        for byte in each_byte(register, data) {
+            match byte {
+                (0x018, 0x03) => self.running = !self.running,
+                (0x015, clear) if clear & 0x01 != 0 => regs[0x04F] &= !0x07,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+type Bus = RegisterMap<Ranging>;
+
+/// Idle sensor with GPIO1 active high and a 49ms convergence limit
+fn idle() -> Bus {
+    RegisterMap::with(Ranging { running: false })
+        .set(0x011, &[0x20])
+        .set(0x01C, &[0x31])
+}
+
+/// Device ranging continuously with a range interrupt pending
+fn running(bus: &mut Bus) -> Device<&mut Bus> {
+    bus.regs[0x04F] = 0x01;
+    let mut dev = Device::new(bus);
+    dev.start_continuous_range().unwrap();
+    dev
+}
+
+const SLOW: Duration = Duration::from_millis(500);
+
+fn threshold() -> Length {
+    Length::from_millimeters(60.0)
+}
+
+fn fast() -> RangeSchedule {
+    RangeSchedule {
+        period: Duration::from_millis(100),
+        interrupt: RangeInterrupt::NewSampleReady,
+    }
+}
+
+fn writes(list: &[(u16, &[u8])]) -> Vec<(u16, Vec<u8>)> {
+    list.iter()
+        .map(|&(reg, data)| (reg, data.to_vec()))
+        .collect()
+}
+
+#[test]
+fn arming_an_idle_sensor() {
+    let mut bus = idle();
+    Device::new(&mut bus)
+        .arm_wake_on_approach(&mut NoDelay, threshold(), SLOW)
+        .unwrap();
+
+    assert_eq!(
+        bus.writes(),
+        writes(&[
+            // GPIO1 interrupt output, active high kept
+            (0x011, &[0x30]),
+            // Thresholds high 255mm and low 60mm, 500ms intermeasurement
+            // period
+            (0x019, &[0xFF, 60, 49]),
+            // Range interrupt level low and clear the range interrupt
+            (0x014, &[0x01, 0x01]),
+            // Start continuous ranging
+            (0x018, &[0x03]),
+        ])
+    );
+    assert!(bus.behavior.running);
+}
+
+#[test]
+fn arming_stops_running_ranging_first() {
+    let mut bus = idle();
+    running(&mut bus)
+        .arm_wake_on_approach(&mut NoDelay, threshold(), SLOW)
+        .unwrap();
+
+    // Started, then stopped before arming
+    assert_eq!(
+        bus.writes()[..2],
+        writes(&[(0x018, &[0x03]), (0x018, &[0x03])])
+    );
+    assert_eq!(bus.writes()[2..].len(), 4);
+    assert!(bus.behavior.running);
+    assert_eq!(bus.regs[0x04F] & 0x07, 0);
+}
+
+#[cfg(feature = "nb")]
+#[test]
+fn arming_during_a_single_shot_is_refused() {
+    let mut bus = idle();
+    let mut dev = Device::new(&mut bus);
+    assert_eq!(dev.try_read_range(), Err(nb::Error::WouldBlock));
+
+    assert_eq!(
+        dev.arm_wake_on_approach(&mut NoDelay, threshold(), SLOW),
+        Err(Error::DeviceBusy)
+    );
+    assert_eq!(
+        dev.disarm_and_resume(&mut NoDelay, fast()),
+        Err(Error::DeviceBusy)
+    );
+    let _ = dev.release();
+    // Only the single-shot start: ranging was neither stopped nor started
+    assert_eq!(bus.writes(), writes(&[(0x018, &[0x01])]));
+    assert!(!bus.behavior.running);
+}
+
+#[test]
+fn resume_after_wake() {
+    let mut bus = idle();
+    let mut dev = Device::new(&mut bus);
+    dev.arm_wake_on_approach(&mut NoDelay, threshold(), SLOW)
+        .unwrap();
+    let (_, state) = dev.into_parts();
+
+    // Something came close: a level low interrupt is pending
+    bus.regs[0x04F] = 0x01;
+    bus.log.clear();
+    Device::from_parts(&mut bus, state)
+        .disarm_and_resume(&mut NoDelay, fast())
+        .unwrap();
+
+    assert_eq!(
+        bus.writes(),
+        writes(&[
+            // Stop the slow ranging
+            (0x018, &[0x03]),
+            // 100ms intermeasurement period
+            (0x01B, &[9]),
+            // Range interrupt on every new sample and clear the range
+            // interrupt
+            (0x014, &[0x04, 0x01]),
+            (0x018, &[0x03]),
+        ])
+    );
+    assert!(bus.behavior.running);
+    assert_eq!(bus.regs[0x04F] & 0x07, 0);
+    assert_eq!(bus.regs[0x011], 0x30);
+}
+
+#[test]
+fn period_shorter_than_a_measurement_is_refused() {
+    let mut bus = idle();
+    let mut dev = Device::new(&mut bus);
+
+    assert!(matches!(
+        dev.arm_wake_on_approach(&mut NoDelay, threshold(), Duration::from_millis(20)),
+        Err(Error::PeriodTooShort(_))
+    ));
+    assert_eq!(
+        dev.disarm_and_resume(
+            &mut NoDelay,
+            RangeSchedule {
+                period: Duration::from_millis(3000),
+                ..fast()
+            }
+        ),
+        Err(Error::SerializationError(ErrorContext {
+            access: Access::Register(0x01B),
+            direction: Direction::Write,
+            operation: Some(OperationStep {
+                name: "disarm_and_resume",
+                step: 2,
+            }),
+        }))
+    );
+    let _ = dev.release();
+    assert!(bus.writes().is_empty());
+}
+
+#[test]
+fn async_matches_blocking() {
+    let mut sync_bus = idle();
+    let mut dev = running(&mut sync_bus);
+    dev.arm_wake_on_approach(&mut NoDelay, threshold(), SLOW)
+        .unwrap();
+    dev.disarm_and_resume(&mut NoDelay, fast()).unwrap();
+    let _ = dev.release();
+
+    let mut async_bus = idle();
+    let mut dev = running(&mut async_bus);
+    block_on(dev.arm_wake_on_approach_async(&mut NoDelay, threshold(), SLOW)).unwrap();
+    block_on(dev.disarm_and_resume_async(&mut NoDelay, fast())).unwrap();
+    let _ = dev.release();
+
+    assert_eq!(sync_bus.writes(), async_bus.writes());
+    assert_eq!(sync_bus.regs, async_bus.regs);
+}
@@ -0,0 +1,232 @@
+//! Callbacks invoked on threshold crossings
+
+mod support;
+
+use measurements::Length;
+use support::{block_on, store, Clearing, Log, RegisterMap};
+use vl6180x::{Device, Luminance};
+
+type Bus = RegisterMap<Clearing>;
+
+/// Sensor with ALS gain 1 and 100ms integration, interrupts disabled
+fn bus() -> Bus {
+    RegisterMap::with(Clearing)
+        .set(0x03F, &[0x46])
+        .set(0x041, &[0x63])
+}
+
+/// Completes a range sample, raising the range interrupt if the condition
+/// programmed in 0x014 holds
+fn range_sample(bus: &mut Bus, distance: u8, error_code: u8) {
+    let regs = &mut bus.regs;
+    regs[0x04D] = error_code << 4 | 0x01;
+    regs[0x062] = distance;
+    let (high, low) = (regs[0x019], regs[0x01A]);
+    let mode = regs[0x014] & 0x07;
+    if holds(mode, distance.into(), high.into(), low.into()) {
+        regs[0x04F] = regs[0x04F] & !0x07 | mode;
+    }
+}
+
+/// Completes an ALS sample, raising the ALS interrupt if the condition
+/// programmed in 0x014 holds
+fn als_sample(bus: &mut Bus, counts: u16) {
+    let regs = &mut bus.regs;
+    regs[0x04E] = 0x01;
+    store(regs, 0x050, &counts.to_be_bytes());
+    let high = u16::from_be_bytes([regs[0x03A], regs[0x03B]]);
+    let low = u16::from_be_bytes([regs[0x03C], regs[0x03D]]);
+    let mode = regs[0x014] >> 3 & 0x07;
+    if holds(mode, counts, high, low) {
+        regs[0x04F] = regs[0x04F] & !0x38 | mode << 3;
+    }
+}
+
+fn holds(mode: u8, value: u16, high: u16, low: u16) -> bool {
+    match mode {
+        1 => value < low,
+        2 => value > high,
+        3 => value < low || value > high,
+        4 => true,
+        _ => false,
+    }
+}
+
+#[test]
+fn arming_programs_thresholds_and_modes() {
+    let mut bus = bus();
+    bus.regs[0x014] = 0x04;
+    let mut dev = Device::new(&mut bus);
+    dev.watch()
+        .on_als_above(Luminance::from_lux(500.0), |_| {})
+        .arm()
+        .unwrap();
+    let _ = dev.release();
+
+    // 500 lux at gain 1 and 100ms integration is 1578 counts
+    assert_eq!(bus.regs[0x03A..0x03E], [0x06, 0x2A, 0x00, 0x00]);
+    // The range interrupt mode was left alone
+    assert_eq!(bus.regs[0x014], 0x14);
+
+    let mut dev = Device::new(&mut bus);
+    dev.watch()
+        .on_range_below(Length::from_millimeters(60.0), |_| {})
+        .arm()
+        .unwrap();
+    let _ = dev.release();
+
+    assert_eq!(bus.regs[0x01A], 60);
+    assert_eq!(bus.regs[0x014], 0x11);
+}
+
+#[test]
+fn range_callback_fires_on_crossing_below() {
+    let mut bus = bus();
+    let mut distances = Vec::new();
+    {
+        let mut dev = Device::new(&mut bus);
+        dev.watch()
+            .on_range_below(Length::from_millimeters(60.0), |_| {})
+            .arm()
+            .unwrap();
+    }
+
+    for distance in [120, 80, 59, 40, 61] {
+        range_sample(&mut bus, distance, 0);
+        let mut dev = Device::new(&mut bus);
+        dev.watch()
+            .on_range_below(Length::from_millimeters(60.0), |d| distances.push(d))
+            .service()
+            .unwrap();
+    }
+
+    assert_eq!(
+        distances,
+        [
+            Length::from_millimeters(59.0),
+            Length::from_millimeters(40.0)
+        ]
+    );
+    assert_eq!(bus.regs[0x04F], 0x00);
+}
+
+#[test]
+fn als_callback_receives_the_light_level() {
+    let mut bus = bus();
+    let mut levels = Vec::new();
+    {
+        let mut dev = Device::new(&mut bus);
+        dev.watch()
+            .on_als_above(Luminance::from_lux(500.0), |_| {})
+            .arm()
+            .unwrap();
+    }
+
+    for counts in [1000, 1578, 2500] {
+        als_sample(&mut bus, counts);
+        let mut dev = Device::new(&mut bus);
+        dev.watch()
+            .on_als_above(Luminance::from_lux(500.0), |light| levels.push(light))
+            .service()
+            .unwrap();
+    }
+
+    assert_eq!(levels, [Luminance::from_lux(800.0 / 1.01)]);
+}
+
+#[test]
+fn each_sensor_gets_its_own_callback() {
+    let mut bus = bus();
+    let mut ranges = 0;
+    let mut lights = 0;
+    {
+        let mut dev = Device::new(&mut bus);
+        dev.watch()
+            .on_range_above(Length::from_millimeters(100.0), |_| {})
+            .on_als_below(Luminance::from_lux(32.0), |_| {})
+            .arm()
+            .unwrap();
+    }
+    assert_eq!(bus.regs[0x014], 0x0A);
+
+    range_sample(&mut bus, 150, 0);
+    als_sample(&mut bus, 50);
+    {
+        let mut dev = Device::new(&mut bus);
+        let status = dev
+            .watch()
+            .on_range_above(Length::from_millimeters(100.0), |_| ranges += 1)
+            .on_als_below(Luminance::from_lux(32.0), |_| lights += 1)
+            .service()
+            .unwrap();
+        assert!(status.range_interrupt && status.als_interrupt);
+    }
+
+    assert_eq!((ranges, lights), (1, 1));
+    assert_eq!(bus.regs[0x04F], 0x00);
+}
+
+#[test]
+fn failed_samples_are_cleared_without_a_callback() {
+    let mut bus = bus();
+    bus.regs[0x014] = 0x01;
+    bus.regs[0x01A] = 60;
+    range_sample(&mut bus, 20, 11);
+
+    let mut calls = 0;
+    let mut dev = Device::new(&mut bus);
+    dev.watch()
+        .on_range_below(Length::from_millimeters(60.0), |_| calls += 1)
+        .service()
+        .unwrap();
+    let _ = dev.release();
+
+    assert_eq!(calls, 0);
+    assert_eq!(bus.regs[0x04F], 0x00);
+}
+
+#[test]
+fn unwatched_interrupts_are_cleared_without_reading_samples() {
+    let mut bus = bus();
+    bus.regs[0x014] = 0x20;
+    als_sample(&mut bus, 100);
+
+    let mut dev = Device::new(&mut bus);
+    dev.watch()
+        .on_range_below(Length::from_millimeters(60.0), |_| {})
+        .service()
+        .unwrap();
+    let _ = dev.release();
+
+    assert_eq!(bus.writes(), [(0x015, vec![0x02])]);
+    assert_eq!(bus.regs[0x04F], 0x00);
+}
+
+#[test]
+fn async_matches_blocking() {
+    let run = |asynchronous: bool| {
+        let mut bus = bus();
+        let mut seen = Vec::new();
+        let mut steps = |bus: &mut Bus, arm: bool| {
+            let mut dev = Device::new(bus);
+            let mut watch = dev
+                .watch()
+                .on_range_below(Length::from_millimeters(60.0), |d| seen.push(d));
+            let result = match (asynchronous, arm) {
+                (false, true) => watch.arm(),
+                (false, false) => watch.service().map(drop),
+                (true, true) => block_on(watch.arm_async()),
+                (true, false) => block_on(watch.service_async()).map(drop),
+            };
+            result.unwrap();
+        };
+        steps(&mut bus, true);
+        for distance in [90, 30] {
+            range_sample(&mut bus, distance, 0);
+            steps(&mut bus, false);
+        }
+        (seen, bus.writes())
+    };
+
+    assert_eq!(run(true), run(false));
+}
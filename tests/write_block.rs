@@ -0,0 +1,45 @@
+//! Block writes to adjacent registers
+
+mod support;
+
+use support::{block_on, Log, Op, RegisterMap};
+use vl6180x::Device;
+
+/// Asserts the bus saw one transaction writing `data` from `register`
+fn assert_one_block(bus: &RegisterMap, register: u16, data: &[u8]) {
+    let [transaction] = bus.log.as_slice() else {
+        panic!("expected one transaction, got {:?}", bus.log);
+    };
+    assert_eq!(
+        transaction.ops,
+        [
+            Op::Write(register.to_be_bytes().to_vec()),
+            Op::Write(data.to_vec())
+        ]
+    );
+    let mut stream = register.to_be_bytes().to_vec();
+    stream.extend_from_slice(data);
+    assert_eq!(bus.bytes(), [(stream, 0)]);
+}
+
+#[test]
+fn block_is_one_transaction_with_the_address_first() {
+    let mut bus = RegisterMap::new();
+    let mut dev = Device::new(&mut bus);
+    dev.write_block(0x019, &[0xFF, 60, 49]).unwrap();
+    let _ = dev.release();
+
+    assert_one_block(&bus, 0x019, &[0xFF, 60, 49]);
+    assert_eq!(bus.regs[0x019..0x01C], [0xFF, 60, 49]);
+}
+
+#[test]
+fn async_block_is_one_transaction_with_the_address_first() {
+    let mut bus = RegisterMap::new();
+    let mut dev = Device::new(&mut bus);
+    block_on(dev.write_block_async(0x03F, &[0x46, 0x00, 0x31])).unwrap();
+    let _ = dev.release();
+
+    assert_one_block(&bus, 0x03F, &[0x46, 0x00, 0x31]);
+    assert_eq!(bus.regs[0x03F..0x042], [0x46, 0x00, 0x31]);
+}